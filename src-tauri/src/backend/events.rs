@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 #[derive(Serialize, Clone)]
@@ -24,8 +24,223 @@ pub(crate) struct TerminalExit {
     pub(crate) terminal_id: String,
 }
 
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OperationProgress {
+    pub(crate) operation_id: String,
+    pub(crate) completed: u32,
+    pub(crate) total: u32,
+    pub(crate) current_item: Option<String>,
+}
+
+/// Terminal event for an operation; `error` is set when it failed.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OperationFinished {
+    pub(crate) operation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ScopeChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A change under a watched scope root; `path` is relative to that root.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScopeChanged {
+    pub(crate) watch_id: String,
+    pub(crate) path: String,
+    pub(crate) change: ScopeChangeKind,
+}
+
+/// Every scope change seen while a batched operation ran, merged per path and
+/// in first-seen order, emitted once instead of one `scope-changed` each.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BatchChanged {
+    pub(crate) batch_id: String,
+    pub(crate) changes: Vec<ScopeChanged>,
+}
+
+/// Workspaces that were repointed or removed outside the usual add/remove flow.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspacesChanged {
+    pub(crate) workspace_ids: Vec<String>,
+}
+
+/// One key whose value differs after a `config.toml` write; `path` is dotted and
+/// `old`/`new` are `None` when the key was added or removed.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct ConfigKeyChange {
+    pub(crate) path: String,
+    pub(crate) old: Option<Value>,
+    pub(crate) new: Option<Value>,
+}
+
+/// The semantic changes a single `config.toml` write applied, tagged with the
+/// command that made it.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigPatch {
+    pub(crate) origin: String,
+    pub(crate) codex_home: String,
+    pub(crate) changes: Vec<ConfigKeyChange>,
+}
+
+/// A `workspace_prewarm` that ran to the end; `warmed` lists the steps that
+/// succeeded and `failed` the ones that did not.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PrewarmDone {
+    pub(crate) workspace_id: String,
+    pub(crate) warmed: Vec<String>,
+    pub(crate) failed: Vec<PrewarmFailure>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PrewarmFailure {
+    pub(crate) step: String,
+    pub(crate) error: String,
+}
+
+/// Whether a workspace root can be used, from `workspace_check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum WorkspaceRootStatus {
+    Ok,
+    Missing,
+    NotDirectory,
+    Unreadable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceCheck {
+    pub(crate) workspace_id: String,
+    pub(crate) path: String,
+    pub(crate) status: WorkspaceRootStatus,
+    pub(crate) error: Option<String>,
+}
+
+/// Every workspace root checked at once; `broken` lists the ids whose status
+/// is not `ok`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceHealth {
+    pub(crate) checked_at_ms: i64,
+    pub(crate) workspaces: Vec<WorkspaceCheck>,
+    pub(crate) broken: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TreeNodeKind {
+    File,
+    Dir,
+    /// Listed but never followed.
+    Symlink,
+}
+
+/// One entry of a tree scan; `path` is relative to the scanned root.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TreeNode {
+    pub(crate) path: String,
+    pub(crate) kind: TreeNodeKind,
+    pub(crate) depth: usize,
+}
+
+/// A batch of entries from a `tree_scan_start` walk.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TreeEntries {
+    pub(crate) scan_id: String,
+    pub(crate) entries: Vec<TreeNode>,
+}
+
+/// The scan stopped at `limit` entries; `tree-done` still follows.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TreeTruncated {
+    pub(crate) scan_id: String,
+    pub(crate) limit: usize,
+}
+
+/// Terminal event of a tree scan.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TreeDone {
+    pub(crate) scan_id: String,
+    pub(crate) total: usize,
+    pub(crate) cancelled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
 pub(crate) trait EventSink: Clone + Send + Sync + 'static {
     fn emit_app_server_event(&self, event: AppServerEvent);
     fn emit_terminal_output(&self, event: TerminalOutput);
     fn emit_terminal_exit(&self, event: TerminalExit);
+    fn emit_operation_progress(&self, event: OperationProgress);
+    fn emit_operation_finished(&self, event: OperationFinished);
+    fn emit_scope_changed(&self, event: ScopeChanged);
+    fn emit_batch_changed(&self, event: BatchChanged);
+    fn emit_workspaces_changed(&self, event: WorkspacesChanged);
+    fn emit_config_patch(&self, event: ConfigPatch);
+    fn emit_prewarm_done(&self, event: PrewarmDone);
+    fn emit_workspace_health(&self, event: WorkspaceHealth);
+    fn emit_tree_entries(&self, event: TreeEntries);
+    fn emit_tree_truncated(&self, event: TreeTruncated);
+    fn emit_tree_done(&self, event: TreeDone);
+}
+
+/// Emits progress for a multi-file operation the caller tagged with an
+/// `operationId`. Without an id every method is a no-op.
+pub(crate) struct OperationReporter<E: EventSink> {
+    sink: E,
+    operation_id: Option<String>,
+}
+
+impl<E: EventSink> OperationReporter<E> {
+    pub(crate) fn new(sink: E, operation_id: Option<String>) -> Self {
+        Self { sink, operation_id }
+    }
+
+    pub(crate) fn sink(&self) -> &E {
+        &self.sink
+    }
+
+    pub(crate) fn operation_id(&self) -> Option<&str> {
+        self.operation_id.as_deref()
+    }
+
+    pub(crate) fn progress(&self, completed: u32, total: u32, current_item: Option<&str>) {
+        if let Some(operation_id) = &self.operation_id {
+            self.sink.emit_operation_progress(OperationProgress {
+                operation_id: operation_id.clone(),
+                completed,
+                total,
+                current_item: current_item.map(str::to_string),
+            });
+        }
+    }
+
+    /// Emits `operation-done` or `operation-failed` and passes `result` through.
+    pub(crate) fn finish<T>(&self, result: Result<T, String>) -> Result<T, String> {
+        if let Some(operation_id) = &self.operation_id {
+            self.sink.emit_operation_finished(OperationFinished {
+                operation_id: operation_id.clone(),
+                error: result.as_ref().err().cloned(),
+            });
+        }
+        result
+    }
 }
@@ -7,14 +7,32 @@ mod codex_args;
 mod codex_config;
 #[path = "../codex/home.rs"]
 mod codex_home;
+#[path = "../files/archive.rs"]
+mod file_archive;
+#[allow(dead_code)]
+#[path = "../files/compare.rs"]
+mod file_compare;
+#[path = "../files/generation.rs"]
+mod file_generation;
 #[path = "../files/io.rs"]
 mod file_io;
 #[path = "../files/ops.rs"]
 mod file_ops;
 #[path = "../files/policy.rs"]
 mod file_policy;
+#[path = "../files/search.rs"]
+mod file_search;
+#[path = "../files/template.rs"]
+mod file_template;
+#[path = "../files/validate.rs"]
+mod file_validate;
+#[path = "../files/watch.rs"]
+mod file_watch;
 #[path = "../git_utils.rs"]
 mod git_utils;
+#[allow(dead_code)]
+#[path = "../remote_backend/capabilities.rs"]
+mod remote_capabilities;
 #[path = "codex_monitor_daemon/rpc.rs"]
 mod rpc;
 #[path = "../rules.rs"]
@@ -30,6 +48,9 @@ mod transport;
 mod types;
 #[path = "../utils.rs"]
 mod utils;
+#[allow(dead_code)]
+#[path = "../workspaces/files.rs"]
+mod workspace_files;
 #[path = "../workspaces/macos.rs"]
 mod workspace_macos;
 #[path = "../workspaces/settings.rs"]
@@ -48,7 +69,22 @@ mod codex {
     }
 }
 
+mod workspaces {
+    pub(crate) mod files {
+        pub(crate) use crate::workspace_files::*;
+    }
+}
+
 mod files {
+    pub(crate) mod archive {
+        pub(crate) use crate::file_archive::*;
+    }
+    pub(crate) mod compare {
+        pub(crate) use crate::file_compare::*;
+    }
+    pub(crate) mod generation {
+        pub(crate) use crate::file_generation::*;
+    }
     pub(crate) mod io {
         pub(crate) use crate::file_io::*;
     }
@@ -58,6 +94,18 @@ mod files {
     pub(crate) mod policy {
         pub(crate) use crate::file_policy::*;
     }
+    pub(crate) mod search {
+        pub(crate) use crate::file_search::*;
+    }
+    pub(crate) mod template {
+        pub(crate) use crate::file_template::*;
+    }
+    pub(crate) mod validate {
+        pub(crate) use crate::file_validate::*;
+    }
+    pub(crate) mod watch {
+        pub(crate) use crate::file_watch::*;
+    }
 }
 
 use serde::{Deserialize, Serialize};
@@ -76,8 +124,13 @@ use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{broadcast, mpsc, Mutex, Semaphore};
 
 use backend::app_server::{spawn_workspace_session, WorkspaceSession};
-use backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use backend::events::{
+    AppServerEvent, BatchChanged, ConfigPatch, EventSink, OperationFinished, OperationProgress,
+    OperationReporter, PrewarmDone, ScopeChanged, TerminalExit, TerminalOutput, TreeDone,
+    TreeEntries, TreeTruncated, WorkspaceCheck, WorkspaceHealth, WorkspacesChanged,
+};
 use shared::codex_core::CodexLoginCancelState;
+use shared::config_patch_core::ConfigWriter;
 use shared::process_core::kill_child_process_tree;
 use shared::prompts_core::{self, CustomPromptEntry};
 use shared::{
@@ -86,10 +139,11 @@ use shared::{
 };
 use storage::{read_settings, read_workspaces};
 use types::{
-    AppSettings, GitCommitDiff, GitFileDiff, GitHubIssuesResponse, GitHubPullRequestComment,
-    GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse, LocalUsageSnapshot,
-    WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus,
+    AppSettings, FeaturePreset, GitCommitDiff, GitFileDiff, GitHubIssuesResponse,
+    GitHubPullRequestComment, GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse,
+    LocalUsageSnapshot, WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus,
 };
+use workspace_files::should_skip_dir;
 use workspace_settings::apply_workspace_settings_update;
 
 const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:4732";
@@ -126,6 +180,17 @@ enum DaemonEvent {
     TerminalOutput(TerminalOutput),
     #[allow(dead_code)]
     TerminalExit(TerminalExit),
+    OperationProgress(OperationProgress),
+    OperationFinished(OperationFinished),
+    ScopeChanged(ScopeChanged),
+    BatchChanged(BatchChanged),
+    WorkspacesChanged(WorkspacesChanged),
+    ConfigPatch(ConfigPatch),
+    PrewarmDone(PrewarmDone),
+    WorkspaceHealth(WorkspaceHealth),
+    TreeEntries(TreeEntries),
+    TreeTruncated(TreeTruncated),
+    TreeDone(TreeDone),
 }
 
 impl EventSink for DaemonEventSink {
@@ -140,6 +205,50 @@ impl EventSink for DaemonEventSink {
     fn emit_terminal_exit(&self, event: TerminalExit) {
         let _ = self.tx.send(DaemonEvent::TerminalExit(event));
     }
+
+    fn emit_operation_progress(&self, event: OperationProgress) {
+        let _ = self.tx.send(DaemonEvent::OperationProgress(event));
+    }
+
+    fn emit_operation_finished(&self, event: OperationFinished) {
+        let _ = self.tx.send(DaemonEvent::OperationFinished(event));
+    }
+
+    fn emit_scope_changed(&self, event: ScopeChanged) {
+        let _ = self.tx.send(DaemonEvent::ScopeChanged(event));
+    }
+
+    fn emit_batch_changed(&self, event: BatchChanged) {
+        let _ = self.tx.send(DaemonEvent::BatchChanged(event));
+    }
+
+    fn emit_workspaces_changed(&self, event: WorkspacesChanged) {
+        let _ = self.tx.send(DaemonEvent::WorkspacesChanged(event));
+    }
+
+    fn emit_config_patch(&self, event: ConfigPatch) {
+        let _ = self.tx.send(DaemonEvent::ConfigPatch(event));
+    }
+
+    fn emit_prewarm_done(&self, event: PrewarmDone) {
+        let _ = self.tx.send(DaemonEvent::PrewarmDone(event));
+    }
+
+    fn emit_workspace_health(&self, event: WorkspaceHealth) {
+        let _ = self.tx.send(DaemonEvent::WorkspaceHealth(event));
+    }
+
+    fn emit_tree_entries(&self, event: TreeEntries) {
+        let _ = self.tx.send(DaemonEvent::TreeEntries(event));
+    }
+
+    fn emit_tree_truncated(&self, event: TreeTruncated) {
+        let _ = self.tx.send(DaemonEvent::TreeTruncated(event));
+    }
+
+    fn emit_tree_done(&self, event: TreeDone) {
+        let _ = self.tx.send(DaemonEvent::TreeDone(event));
+    }
 }
 
 struct DaemonConfig {
@@ -155,8 +264,18 @@ struct DaemonState {
     storage_path: PathBuf,
     settings_path: PathBuf,
     app_settings: Mutex<AppSettings>,
+    config_drift: shared::config_drift_core::ConfigDriftCache,
     event_sink: DaemonEventSink,
     codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
+    scope_watchers: files_core::ScopeWatchers,
+    file_generations: file_generation::Generations,
+    scope_root_overrides: file_policy::ScopeRootOverrides,
+    readiness_cache: files_core::ReadinessCache,
+    prewarm: files_core::PrewarmSlot,
+    workspace_health: workspaces_core::WorkspaceHealthCache,
+    tree_scans: shared::tree_scan_core::TreeScans,
+    /// Request ids of running `file_read_many` batches, for `remote_cancel`.
+    reads_in_flight: Mutex<HashSet<String>>,
     daemon_binary_path: Option<String>,
 }
 
@@ -172,6 +291,10 @@ impl DaemonState {
         let settings_path = config.data_dir.join("settings.json");
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        codex_home::set_canonicalize_codex_home(app_settings.canonicalize_codex_home);
+        codex_home::set_codex_home_override(app_settings.codex_home_override.as_deref());
+        let scope_root_overrides =
+            file_policy::ScopeRootOverrides::new(&app_settings.scope_root_overrides);
         let daemon_binary_path = std::env::current_exe()
             .ok()
             .and_then(|path| path.to_str().map(str::to_string));
@@ -182,8 +305,17 @@ impl DaemonState {
             storage_path,
             settings_path,
             app_settings: Mutex::new(app_settings),
+            config_drift: Mutex::new(None),
             event_sink,
             codex_login_cancels: Mutex::new(HashMap::new()),
+            scope_watchers: Default::default(),
+            file_generations: file_generation::Generations::new(),
+            scope_root_overrides,
+            readiness_cache: Default::default(),
+            prewarm: Default::default(),
+            workspace_health: Mutex::new(None),
+            tree_scans: Default::default(),
+            reads_in_flight: Mutex::new(HashSet::new()),
             daemon_binary_path,
         }
     }
@@ -243,6 +375,132 @@ impl DaemonState {
         workspaces_core::list_workspaces_core(&self.workspaces, &self.sessions).await
     }
 
+    async fn workspace_state_repair(&self) -> Result<storage::WorkspaceStateRepairReport, String> {
+        workspaces_core::workspace_state_repair_core(&self.workspaces, &self.storage_path).await
+    }
+
+    async fn workspace_list_broken(&self) -> Vec<WorkspaceInfo> {
+        workspaces_core::workspace_list_broken_core(&self.workspaces, &self.sessions).await
+    }
+
+    async fn workspace_check(&self, workspace_id: String) -> Result<WorkspaceCheck, String> {
+        workspaces_core::workspace_check_core(&self.workspaces, &workspace_id).await
+    }
+
+    async fn workspace_health(&self) -> WorkspaceHealth {
+        workspaces_core::workspace_health_core(&self.workspaces, &self.workspace_health).await
+    }
+
+    async fn workspace_relink(
+        &self,
+        id: String,
+        new_root: String,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::workspace_relink_core(
+            &self.workspaces,
+            &self.sessions,
+            &self.storage_path,
+            self.event_sink.clone(),
+            id,
+            new_root,
+        )
+        .await
+    }
+
+    async fn workspace_prune_broken(&self) -> Result<Vec<String>, String> {
+        workspaces_core::workspace_prune_broken_core(
+            &self.workspaces,
+            &self.sessions,
+            &self.storage_path,
+            self.event_sink.clone(),
+        )
+        .await
+    }
+
+    async fn profile_export(&self) -> Result<String, String> {
+        workspaces_core::profile_export_core(&self.workspaces, &self.app_settings).await
+    }
+
+    async fn profile_import(
+        &self,
+        request: workspaces_core::ProfileImportRequest,
+    ) -> Result<workspaces_core::ProfileImportReport, String> {
+        workspaces_core::profile_import_core(
+            request,
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            &self.storage_path,
+            &self.settings_path,
+            &self.scope_root_overrides,
+            self.event_sink.clone(),
+        )
+        .await
+    }
+
+    async fn workspace_find_duplicates(&self) -> Vec<workspaces_core::WorkspaceOverlap> {
+        workspaces_core::workspace_find_duplicates_core(&self.workspaces).await
+    }
+
+    async fn workspace_set_codex_home(
+        &self,
+        id: String,
+        path: Option<String>,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::workspace_set_codex_home_core(
+            &self.workspaces,
+            &self.sessions,
+            &self.storage_path,
+            id,
+            path,
+        )
+        .await
+    }
+
+    async fn workspace_effective_model(
+        &self,
+        workspace_id: String,
+    ) -> Result<workspaces_core::EffectiveWorkspaceDefault, String> {
+        workspaces_core::workspace_effective_model_core(&self.workspaces, &workspace_id).await
+    }
+
+    async fn workspace_effective_personality(
+        &self,
+        workspace_id: String,
+    ) -> Result<workspaces_core::EffectiveWorkspaceDefault, String> {
+        workspaces_core::workspace_effective_personality_core(&self.workspaces, &workspace_id).await
+    }
+
+    async fn workspace_effective_features(
+        &self,
+        workspace_id: String,
+    ) -> Result<Vec<workspaces_core::EffectiveFeatureFlag>, String> {
+        workspaces_core::workspace_effective_features_core(&self.workspaces, &workspace_id).await
+    }
+
+    async fn effective_sandbox(
+        &self,
+        workspace_id: String,
+    ) -> Result<workspaces_core::SandboxResolution, String> {
+        workspaces_core::effective_sandbox_core(&self.workspaces, &workspace_id).await
+    }
+
+    async fn workspace_set_default_override(
+        &self,
+        workspace_id: String,
+        key: workspaces_core::WorkspaceDefaultKey,
+        value: Option<String>,
+    ) -> Result<workspaces_core::EffectiveWorkspaceDefault, String> {
+        workspaces_core::workspace_set_default_override_core(
+            &self.workspaces,
+            &self.storage_path,
+            &workspace_id,
+            key,
+            value,
+        )
+        .await
+    }
+
     async fn is_workspace_path_dir(&self, path: String) -> bool {
         workspaces_core::is_workspace_path_dir_core(&path)
     }
@@ -250,11 +508,41 @@ impl DaemonState {
     async fn add_workspace(
         &self,
         path: String,
+        reject_duplicate: bool,
         client_version: String,
-    ) -> Result<WorkspaceInfo, String> {
+    ) -> Result<workspaces_core::AddedWorkspace, String> {
         let client_version = client_version.clone();
         workspaces_core::add_workspace_core(
             path,
+            None,
+            reject_duplicate,
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            &self.storage_path,
+            move |entry, default_bin, codex_args, codex_home| {
+                spawn_with_client(
+                    self.event_sink.clone(),
+                    client_version.clone(),
+                    entry,
+                    default_bin,
+                    codex_args,
+                    codex_home,
+                )
+            },
+        )
+        .await
+    }
+
+    async fn workspace_import_existing(
+        &self,
+        root_path: String,
+        name: Option<String>,
+        client_version: String,
+    ) -> Result<workspaces_core::ImportedWorkspace, String> {
+        workspaces_core::workspace_import_existing_core(
+            root_path,
+            name,
             &self.workspaces,
             &self.sessions,
             &self.app_settings,
@@ -525,159 +813,1452 @@ impl DaemonState {
         .await
     }
 
-    async fn connect_workspace(&self, id: String, client_version: String) -> Result<(), String> {
-        {
-            let sessions = self.sessions.lock().await;
-            if sessions.contains_key(&id) {
-                return Ok(());
-            }
-        }
-
-        let client_version = client_version.clone();
-        workspaces_core::connect_workspace_core(
-            id,
+    async fn connect_workspace(&self, id: String, client_version: String) -> Result<(), String> {
+        {
+            let sessions = self.sessions.lock().await;
+            if sessions.contains_key(&id) {
+                return Ok(());
+            }
+        }
+
+        let client_version = client_version.clone();
+        workspaces_core::connect_workspace_core(
+            id,
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            move |entry, default_bin, codex_args, codex_home| {
+                spawn_with_client(
+                    self.event_sink.clone(),
+                    client_version.clone(),
+                    entry,
+                    default_bin,
+                    codex_args,
+                    codex_home,
+                )
+            },
+        )
+        .await
+    }
+
+    async fn set_workspace_runtime_codex_args(
+        &self,
+        workspace_id: String,
+        codex_args: Option<String>,
+        client_version: String,
+    ) -> Result<workspaces_core::WorkspaceRuntimeCodexArgsResult, String> {
+        workspaces_core::set_workspace_runtime_codex_args_core(
+            workspace_id,
+            codex_args,
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            move |entry, default_bin, next_args, codex_home| {
+                spawn_with_client(
+                    self.event_sink.clone(),
+                    client_version.clone(),
+                    entry,
+                    default_bin,
+                    next_args,
+                    codex_home,
+                )
+            },
+        )
+        .await
+    }
+
+    async fn get_app_settings(&self) -> AppSettings {
+        settings_core::get_app_settings_core(&self.app_settings, &self.scope_root_overrides).await
+    }
+
+    async fn update_app_settings(&self, settings: AppSettings) -> Result<AppSettings, String> {
+        settings_core::update_app_settings_core(
+            settings,
+            &self.app_settings,
+            &self.settings_path,
+            &self.scope_root_overrides,
+            ConfigWriter::new(
+                "update_app_settings",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+        .await
+    }
+
+    async fn codex_home_relocate(
+        &self,
+        new_path: String,
+        copy_all: bool,
+        overwrite: bool,
+        remove_old: bool,
+    ) -> Result<shared::codex_home_core::CodexHomeRelocation, String> {
+        shared::codex_home_core::codex_home_relocate_core(
+            &new_path,
+            copy_all,
+            overwrite,
+            remove_old,
+            &self.app_settings,
+            &self.settings_path,
+            &self.scope_root_overrides,
+            &self.file_generations,
+        )
+        .await
+    }
+
+    async fn feature_preset_save(&self, name: String) -> Result<FeaturePreset, String> {
+        settings_core::feature_preset_save_core(
+            &name,
+            &self.app_settings,
+            &self.settings_path,
+            &self.scope_root_overrides,
+        )
+        .await
+    }
+
+    async fn feature_preset_list(&self) -> Vec<FeaturePreset> {
+        settings_core::feature_preset_list_core(&self.app_settings).await
+    }
+
+    async fn feature_preset_apply(
+        &self,
+        name: String,
+        clear_missing: bool,
+    ) -> Result<FeaturePreset, String> {
+        settings_core::feature_preset_apply_core(
+            &name,
+            clear_missing,
+            &self.app_settings,
+            &self.scope_root_overrides,
+            ConfigWriter::new(
+                "feature_preset_apply",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+        .await
+    }
+
+    async fn feature_preset_apply_all(
+        &self,
+        name: String,
+        workspace_ids: Vec<String>,
+        operation_id: Option<String>,
+    ) -> Result<workspaces_core::PresetApplyAllReport, String> {
+        let reporter = OperationReporter::new(self.event_sink.clone(), operation_id);
+        workspaces_core::feature_preset_apply_all_core(
+            &self.workspaces,
+            &self.storage_path,
+            &self.app_settings,
+            &self.scope_root_overrides,
+            &name,
+            workspace_ids,
+            &reporter,
+            ConfigWriter::new(
+                "feature_preset_apply_all",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+        .await
+    }
+
+    async fn feature_preset_delete(&self, name: String) -> Result<bool, String> {
+        settings_core::feature_preset_delete_core(
+            &name,
+            &self.app_settings,
+            &self.settings_path,
+            &self.scope_root_overrides,
+        )
+        .await
+    }
+
+    async fn set_codex_feature_flag(
+        &self,
+        feature_key: String,
+        enabled: bool,
+    ) -> Result<(), String> {
+        codex_config::write_feature_enabled(
+            &self.scope_root_overrides,
+            feature_key.as_str(),
+            enabled,
+            ConfigWriter::new(
+                "set_codex_feature_flag",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn config_read(&self) -> Result<codex_config::CodexConfigValues, String> {
+        codex_config::read_config_values(&self.scope_root_overrides)
+    }
+
+    async fn config_write(
+        &self,
+        values: codex_config::CodexConfigValues,
+    ) -> Result<Vec<String>, String> {
+        codex_config::write_config_values(
+            &self.scope_root_overrides,
+            &values,
+            ConfigWriter::new("config_write", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn config_undo(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<bool, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::undo_config_write(
+            codex_home,
+            ConfigWriter::new("config_undo", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn config_redo(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<bool, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::redo_config_write(
+            codex_home,
+            ConfigWriter::new("config_redo", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn config_history(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Vec<shared::config_history_core::HistoryEntry>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        Ok(codex_config::read_config_history(codex_home))
+    }
+
+    async fn config_format(
+        &self,
+        sort_keys: bool,
+        dry_run: bool,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<String, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::format_config(
+            sort_keys,
+            dry_run,
+            codex_home,
+            ConfigWriter::new("config_format", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn config_migrate_preview(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Vec<shared::config_migrate_core::MigrationStep>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::preview_config_migration(codex_home)
+    }
+
+    async fn config_codex_home(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Option<PathBuf>, String> {
+        files_core::resolve_config_codex_home_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            codex_home.as_deref(),
+            workspace_id.as_deref(),
+        )
+        .await
+        .map(Some)
+    }
+
+    async fn config_merge(
+        &self,
+        changes: HashMap<String, Option<shared::config_toml_core::ConfigValue>>,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<(), String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::merge_config(
+            &changes,
+            codex_home,
+            ConfigWriter::new("config_merge", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn config_transaction(
+        &self,
+        ops: Vec<shared::config_toml_core::ConfigOp>,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<(), String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_transaction(
+            &ops,
+            codex_home,
+            ConfigWriter::new(
+                "config_transaction",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn config_array_add(
+        &self,
+        key: String,
+        value: String,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<bool, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_array_add(
+            &key,
+            &value,
+            codex_home,
+            ConfigWriter::new("config_array_add", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn config_array_remove(
+        &self,
+        key: String,
+        value: String,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<bool, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_array_remove(
+            &key,
+            &value,
+            codex_home,
+            ConfigWriter::new(
+                "config_array_remove",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn config_export_redacted(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+        extra_keys: Vec<String>,
+    ) -> Result<String, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::export_redacted_config(codex_home, &extra_keys)
+    }
+
+    async fn config_strip_comments(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+        write: bool,
+    ) -> Result<String, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::strip_config_comments(
+            codex_home,
+            write,
+            ConfigWriter::new(
+                "config_strip_comments",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn config_indent_info(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<shared::config_toml_core::IndentInfo, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_indent_info(codex_home)
+    }
+
+    async fn config_reindent(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+        style: shared::config_toml_core::IndentStyle,
+    ) -> Result<String, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::reindent_config(
+            codex_home,
+            style,
+            ConfigWriter::new("config_reindent", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn config_load_tolerant(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Option<shared::config_duplicates_core::ConfigParseError>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_load_tolerant(codex_home)
+    }
+
+    async fn config_fix_duplicate_keys(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<usize, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::fix_config_duplicate_keys(
+            codex_home,
+            ConfigWriter::new(
+                "config_fix_duplicate_keys",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn config_roundtrip_check(
+        &self,
+        proposed: String,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<shared::config_roundtrip_core::RoundtripReport, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_roundtrip_check(codex_home, &proposed)
+    }
+
+    async fn config_drift_check(
+        &self,
+    ) -> Result<Vec<shared::config_drift_core::DriftEntry>, String> {
+        shared::config_drift_core::config_drift_check_core(
+            &self.app_settings,
+            &self.scope_root_overrides,
+            &self.config_drift,
+        )
+        .await
+    }
+
+    async fn config_with_defaults(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Vec<codex_config::ResolvedSetting>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_with_defaults(codex_home)
+    }
+
+    async fn config_profile_diff(
+        &self,
+        profile_a: String,
+        profile_b: String,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Vec<codex_config::ConfigDelta>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_profile_diff(&profile_a, &profile_b, codex_home)
+    }
+
+    async fn config_profile_create(
+        &self,
+        name: String,
+        base: Option<String>,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<(), String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_profile_create(
+            &name,
+            base.as_deref(),
+            codex_home,
+            ConfigWriter::new(
+                "config_profile_create",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn config_profile_delete(
+        &self,
+        name: String,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Option<String>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_profile_delete(
+            &name,
+            codex_home,
+            ConfigWriter::new(
+                "config_profile_delete",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn config_profile_rename(
+        &self,
+        old: String,
+        new: String,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<(), String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_profile_rename(
+            &old,
+            &new,
+            codex_home,
+            ConfigWriter::new(
+                "config_profile_rename",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn tools_config_read(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<codex_config::ToolsConfig, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::tools_config_read(codex_home)
+    }
+
+    async fn tools_config_set(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+        tool: String,
+        enabled: Option<bool>,
+    ) -> Result<codex_config::ToolsConfig, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::tools_config_set(
+            codex_home,
+            &tool,
+            enabled,
+            ConfigWriter::new("tools_config_set", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn mcp_servers_list(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Vec<codex_config::McpServerInfo>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::mcp_servers_list(codex_home)
+    }
+
+    async fn mcp_server_set_enabled(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+        name: String,
+        enabled: bool,
+    ) -> Result<(), String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::mcp_server_set_enabled(
+            codex_home,
+            &name,
+            enabled,
+            ConfigWriter::new(
+                "mcp_server_set_enabled",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn config_schema_check(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<codex_config::SchemaStatus, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_schema_check(codex_home)
+    }
+
+    async fn config_stat(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Option<u64>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::config_modified_ms(codex_home)
+    }
+
+    async fn config_migrate(
+        &self,
+        codex_home: Option<String>,
+        workspace_id: Option<String>,
+    ) -> Result<Vec<shared::config_migrate_core::MigrationStep>, String> {
+        let codex_home = self.config_codex_home(codex_home, workspace_id).await?;
+        codex_config::migrate_config(
+            codex_home,
+            ConfigWriter::new("config_migrate", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn read_effective_config(
+        &self,
+    ) -> Result<shared::config_toml_core::EffectiveConfig, String> {
+        codex_config::read_effective_config(&self.scope_root_overrides)
+    }
+
+    async fn codex_effective_config_path(
+        &self,
+    ) -> Result<codex_config::EffectiveConfigPath, String> {
+        codex_config::codex_effective_config_path(&self.scope_root_overrides)
+    }
+
+    async fn features_catalog(&self) -> Result<Vec<codex_config::FeatureInfo>, String> {
+        codex_config::features_catalog(&self.scope_root_overrides)
+    }
+
+    async fn read_effective_personality(
+        &self,
+    ) -> Result<codex_config::EffectivePersonality, String> {
+        codex_config::read_effective_personality(&self.scope_root_overrides)
+    }
+
+    async fn read_reasoning_effort(
+        &self,
+        profile: Option<String>,
+    ) -> Result<Option<String>, String> {
+        codex_config::read_reasoning_effort(&self.scope_root_overrides, profile)
+    }
+
+    async fn write_reasoning_effort(
+        &self,
+        profile: Option<String>,
+        effort: Option<String>,
+    ) -> Result<(), String> {
+        codex_config::write_reasoning_effort(
+            &self.scope_root_overrides,
+            profile,
+            effort,
+            ConfigWriter::new(
+                "write_reasoning_effort",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn get_agents_settings(&self) -> Result<agents_config_core::AgentsSettingsDto, String> {
+        agents_config_core::get_agents_settings_core()
+    }
+
+    async fn set_agents_core_settings(
+        &self,
+        input: agents_config_core::SetAgentsCoreInput,
+    ) -> Result<agents_config_core::AgentsSettingsDto, String> {
+        agents_config_core::set_agents_core_settings_core(
+            input,
+            ConfigWriter::new(
+                "set_agents_core_settings",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+    }
+
+    async fn create_agent(
+        &self,
+        input: agents_config_core::CreateAgentInput,
+    ) -> Result<agents_config_core::AgentsSettingsDto, String> {
+        agents_config_core::create_agent_core(
+            input,
+            ConfigWriter::new("create_agent", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn update_agent(
+        &self,
+        input: agents_config_core::UpdateAgentInput,
+    ) -> Result<agents_config_core::AgentsSettingsDto, String> {
+        agents_config_core::update_agent_core(
+            input,
+            ConfigWriter::new("update_agent", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn delete_agent(
+        &self,
+        input: agents_config_core::DeleteAgentInput,
+    ) -> Result<agents_config_core::AgentsSettingsDto, String> {
+        agents_config_core::delete_agent_core(
+            input,
+            ConfigWriter::new("delete_agent", &self.event_sink, &self.file_generations),
+        )
+    }
+
+    async fn read_agent_config_toml(&self, agent_name: String) -> Result<String, String> {
+        agents_config_core::read_agent_config_toml_core(agent_name.as_str())
+    }
+
+    async fn write_agent_config_toml(
+        &self,
+        agent_name: String,
+        content: String,
+    ) -> Result<(), String> {
+        agents_config_core::write_agent_config_toml_core(agent_name.as_str(), content.as_str())
+    }
+
+    async fn list_workspace_files(&self, workspace_id: String) -> Result<Vec<String>, String> {
+        workspaces_core::list_workspace_files_core(&self.workspaces, &workspace_id, |root| {
+            list_workspace_files_inner(root, 20000)
+        })
+        .await
+    }
+
+    async fn read_workspace_file(
+        &self,
+        workspace_id: String,
+        path: String,
+    ) -> Result<WorkspaceFileResponse, String> {
+        workspaces_core::read_workspace_file_core(
+            &self.workspaces,
+            &workspace_id,
+            &path,
+            |root, rel_path| read_workspace_file_inner(root, rel_path),
+        )
+        .await
+    }
+
+    async fn file_read(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<file_io::TextFileResponse, String> {
+        files_core::file_read_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn file_read_timed(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<files_core::TimedRead, String> {
+        files_core::file_read_timed_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn file_write(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        content: String,
+        expected_modified_ms: Option<u64>,
+        force: bool,
+        verify: bool,
+    ) -> Result<(), String> {
+        let expected = verify.then(|| (content.clone(), workspace_id.clone()));
+        files_core::file_write_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+            content,
+            expected_modified_ms,
+            force,
+            ConfigWriter::new("file_write", &self.event_sink, &self.file_generations),
+        )
+        .await?;
+        match expected {
+            Some((content, workspace_id)) => {
+                files_core::file_verify_written_core(
+                    &self.workspaces,
+                    &self.scope_root_overrides,
+                    scope,
+                    kind,
+                    workspace_id,
+                    &content,
+                )
+                .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    async fn file_write_preview(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        content: String,
+    ) -> Result<Vec<file_compare::DiffHunk>, String> {
+        files_core::file_write_preview_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+            &content,
+        )
+        .await
+    }
+
+    async fn file_write_report(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        content: String,
+        expected_modified_ms: Option<u64>,
+        force: bool,
+    ) -> Result<files_core::WriteReport, String> {
+        files_core::file_write_report_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+            content,
+            expected_modified_ms,
+            force,
+            ConfigWriter::new(
+                "file_write_report",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+        .await
+    }
+
+    async fn file_write_templated(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        template: String,
+        vars: HashMap<String, String>,
+        strict: bool,
+    ) -> Result<(), String> {
+        files_core::file_write_templated_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+            template,
+            vars,
+            strict,
+            ConfigWriter::new(
+                "file_write_templated",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+        .await
+    }
+
+    async fn file_render_template(
+        &self,
+        template: String,
+        vars: HashMap<String, String>,
+        workspace_id: Option<String>,
+        strict: bool,
+    ) -> file_template::RenderedTemplate {
+        files_core::file_render_template_core(
+            &self.workspaces,
+            template,
+            vars,
+            workspace_id,
+            strict,
+        )
+        .await
+    }
+
+    fn defaults_catalog(&self) -> Vec<file_template::DefaultInfo> {
+        file_template::defaults_catalog()
+    }
+
+    async fn file_reset_to_default(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        backup: bool,
+    ) -> Result<bool, String> {
+        files_core::file_reset_to_default_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.data_dir,
+            scope,
+            kind,
+            workspace_id,
+            backup,
+            ConfigWriter::new(
+                "file_reset_to_default",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+        .await
+    }
+
+    async fn file_paths(
+        &self,
+        requests: Vec<file_policy::ScopedFileRef>,
+    ) -> Vec<files_core::PathResult> {
+        files_core::file_paths_core(&self.workspaces, &self.scope_root_overrides, requests).await
+    }
+
+    async fn file_read_many(
+        &self,
+        requests: Vec<file_policy::ScopedFileRef>,
+        request_id: String,
+    ) -> files_core::FileReadManyResponse {
+        files_core::file_read_many_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.reads_in_flight,
+            requests,
+            &request_id,
+        )
+        .await
+    }
+
+    async fn remote_cancel(&self, request_id: String) -> bool {
+        files_core::remote_cancel_core(&self.reads_in_flight, &request_id).await
+    }
+
+    async fn file_copy(
+        &self,
+        source: file_policy::ScopedFileRef,
+        target: file_policy::ScopedFileRef,
+    ) -> Result<(), String> {
+        files_core::file_copy_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.file_generations,
+            source,
+            target,
+        )
+        .await
+    }
+
+    async fn file_snapshot(
+        &self,
+        file: file_policy::ScopedFileRef,
+        label: Option<String>,
+    ) -> Result<shared::file_snapshot_core::FileSnapshot, String> {
+        shared::file_snapshot_core::file_snapshot_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.data_dir,
+            file,
+            label,
+        )
+        .await
+    }
+
+    async fn file_snapshot_list(
+        &self,
+        file: file_policy::ScopedFileRef,
+    ) -> Result<Vec<shared::file_snapshot_core::FileSnapshot>, String> {
+        shared::file_snapshot_core::file_snapshot_list_core(&self.data_dir, file)
+    }
+
+    async fn file_snapshot_restore(
+        &self,
+        snapshot_id: String,
+    ) -> Result<files_core::WriteReport, String> {
+        shared::file_snapshot_core::file_snapshot_restore_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.data_dir,
+            &snapshot_id,
+            ConfigWriter::new(
+                "file_snapshot_restore",
+                &self.event_sink,
+                &self.file_generations,
+            ),
+        )
+        .await
+    }
+
+    async fn cleanup_orphans(
+        &self,
+        dry_run: bool,
+    ) -> Result<Vec<shared::orphans_core::OrphanEntry>, String> {
+        shared::orphans_core::cleanup_orphans_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.data_dir,
+            dry_run,
+        )
+        .await
+    }
+
+    async fn tree_scan_start(
+        &self,
+        scope: file_policy::FileScope,
+        workspace_id: Option<String>,
+        max_depth: Option<usize>,
+    ) -> Result<String, String> {
+        let (scan_id, root) = shared::tree_scan_core::tree_scan_start_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.tree_scans,
+            scope,
+            workspace_id,
+        )
+        .await?;
+        tokio::spawn(shared::tree_scan_core::tree_scan_run_core(
+            self.tree_scans.clone(),
+            root,
+            scan_id.clone(),
+            max_depth,
+            self.event_sink.clone(),
+        ));
+        Ok(scan_id)
+    }
+
+    async fn tree_scan_cancel(&self, scan_id: String) -> bool {
+        shared::tree_scan_core::tree_scan_cancel_core(&self.tree_scans, &scan_id).await
+    }
+
+    async fn file_swap(
+        &self,
+        scope_a: file_policy::FileScope,
+        kind_a: file_policy::FileKind,
+        scope_b: file_policy::FileScope,
+        kind_b: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<(), String> {
+        files_core::file_swap_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope_a,
+            kind_a,
+            scope_b,
+            kind_b,
+            workspace_id,
+            ConfigWriter::new("file_swap", &self.event_sink, &self.file_generations),
+        )
+        .await
+    }
+
+    async fn file_search(
+        &self,
+        scope: file_policy::FileScope,
+        workspace_id: Option<String>,
+        query: String,
+        regex: bool,
+        max_results: u32,
+    ) -> Result<Vec<file_search::SearchHit>, String> {
+        files_core::file_search_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            workspace_id,
+            query,
+            regex,
+            max_results,
+        )
+        .await
+    }
+
+    async fn file_touch(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<bool, String> {
+        files_core::file_touch_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+            ConfigWriter::new("file_touch", &self.event_sink, &self.file_generations),
+        )
+        .await
+    }
+
+    async fn file_truncate(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        keep_last_bytes: Option<u64>,
+    ) -> Result<u64, String> {
+        files_core::file_truncate_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.file_generations,
+            scope,
+            kind,
+            workspace_id,
+            keep_last_bytes,
+        )
+        .await
+    }
+
+    async fn file_write_symlink(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        target: String,
+    ) -> Result<(), String> {
+        files_core::file_write_symlink_core(
             &self.workspaces,
-            &self.sessions,
-            &self.app_settings,
-            move |entry, default_bin, codex_args, codex_home| {
-                spawn_with_client(
-                    self.event_sink.clone(),
-                    client_version.clone(),
-                    entry,
-                    default_bin,
-                    codex_args,
-                    codex_home,
-                )
-            },
+            &self.scope_root_overrides,
+            &self.file_generations,
+            scope,
+            kind,
+            workspace_id,
+            target,
         )
         .await
     }
 
-    async fn set_workspace_runtime_codex_args(
+    async fn workspace_initialize(
         &self,
         workspace_id: String,
-        codex_args: Option<String>,
-        client_version: String,
-    ) -> Result<workspaces_core::WorkspaceRuntimeCodexArgsResult, String> {
-        workspaces_core::set_workspace_runtime_codex_args_core(
-            workspace_id,
-            codex_args,
+        overwrite: bool,
+    ) -> Result<file_ops::WorkspaceInitReport, String> {
+        files_core::workspace_initialize_core(
             &self.workspaces,
-            &self.sessions,
-            &self.app_settings,
-            move |entry, default_bin, next_args, codex_home| {
-                spawn_with_client(
-                    self.event_sink.clone(),
-                    client_version.clone(),
-                    entry,
-                    default_bin,
-                    next_args,
-                    codex_home,
-                )
-            },
+            &self.scope_root_overrides,
+            &self.file_generations,
+            workspace_id,
+            overwrite,
         )
         .await
     }
 
-    async fn get_app_settings(&self) -> AppSettings {
-        settings_core::get_app_settings_core(&self.app_settings).await
+    async fn workspace_readiness(
+        &self,
+        workspace_id: String,
+    ) -> Result<files_core::Readiness, String> {
+        files_core::workspace_readiness_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.file_generations,
+            &self.readiness_cache,
+            workspace_id,
+        )
+        .await
     }
 
-    async fn update_app_settings(&self, settings: AppSettings) -> Result<AppSettings, String> {
-        settings_core::update_app_settings_core(settings, &self.app_settings, &self.settings_path)
-            .await
+    async fn workspace_prewarm(&self, workspace_id: String) -> bool {
+        let token = files_core::workspace_prewarm_begin_core(&self.prewarm).await;
+        files_core::workspace_prewarm_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.file_generations,
+            &self.readiness_cache,
+            &self.prewarm,
+            &token,
+            workspace_id,
+            &self.event_sink,
+        )
+        .await
     }
 
-    async fn set_codex_feature_flag(
+    async fn agents_effective(
         &self,
-        feature_key: String,
-        enabled: bool,
-    ) -> Result<(), String> {
-        codex_config::write_feature_enabled(feature_key.as_str(), enabled)
+        workspace_id: String,
+    ) -> Result<files_core::AgentsView, String> {
+        files_core::agents_effective_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            workspace_id,
+        )
+        .await
     }
 
-    async fn get_agents_settings(&self) -> Result<agents_config_core::AgentsSettingsDto, String> {
-        agents_config_core::get_agents_settings_core()
+    async fn file_permissions_audit(
+        &self,
+        workspace_id: Option<String>,
+    ) -> Result<Vec<files_core::PermIssue>, String> {
+        files_core::file_permissions_audit_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            workspace_id,
+        )
+        .await
     }
 
-    async fn set_agents_core_settings(
+    async fn file_fix_permissions(
         &self,
-        input: agents_config_core::SetAgentsCoreInput,
-    ) -> Result<agents_config_core::AgentsSettingsDto, String> {
-        agents_config_core::set_agents_core_settings_core(input)
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<bool, String> {
+        files_core::file_fix_permissions_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+        )
+        .await
     }
 
-    async fn create_agent(
+    async fn file_link_info(
         &self,
-        input: agents_config_core::CreateAgentInput,
-    ) -> Result<agents_config_core::AgentsSettingsDto, String> {
-        agents_config_core::create_agent_core(input)
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<file_io::LinkInfo, String> {
+        files_core::file_link_info_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+        )
+        .await
     }
 
-    async fn update_agent(
+    async fn file_writable(
         &self,
-        input: agents_config_core::UpdateAgentInput,
-    ) -> Result<agents_config_core::AgentsSettingsDto, String> {
-        agents_config_core::update_agent_core(input)
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<bool, String> {
+        files_core::file_writable_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+        )
+        .await
     }
 
-    async fn delete_agent(
+    async fn file_read_range(
         &self,
-        input: agents_config_core::DeleteAgentInput,
-    ) -> Result<agents_config_core::AgentsSettingsDto, String> {
-        agents_config_core::delete_agent_core(input)
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        offset: u64,
+        length: u32,
+    ) -> Result<file_io::FileRangeResponse, String> {
+        files_core::file_read_range_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+            offset,
+            length,
+        )
+        .await
     }
 
-    async fn read_agent_config_toml(&self, agent_name: String) -> Result<String, String> {
-        agents_config_core::read_agent_config_toml_core(agent_name.as_str())
+    async fn policy_set_scope_override(
+        &self,
+        scope: file_policy::FileScope,
+        workspace_id: Option<String>,
+        path: String,
+    ) -> Result<String, String> {
+        settings_core::policy_set_scope_override_core(
+            scope,
+            workspace_id.as_deref(),
+            &path,
+            &self.app_settings,
+            &self.settings_path,
+            &self.scope_root_overrides,
+            &self.readiness_cache,
+        )
+        .await
     }
 
-    async fn write_agent_config_toml(
+    async fn policy_clear_scope_override(
         &self,
-        agent_name: String,
-        content: String,
-    ) -> Result<(), String> {
-        agents_config_core::write_agent_config_toml_core(agent_name.as_str(), content.as_str())
+        scope: file_policy::FileScope,
+        workspace_id: Option<String>,
+    ) -> Result<bool, String> {
+        settings_core::policy_clear_scope_override_core(
+            scope,
+            workspace_id.as_deref(),
+            &self.app_settings,
+            &self.settings_path,
+            &self.scope_root_overrides,
+            &self.readiness_cache,
+        )
+        .await
     }
 
-    async fn list_workspace_files(&self, workspace_id: String) -> Result<Vec<String>, String> {
-        workspaces_core::list_workspace_files_core(&self.workspaces, &workspace_id, |root| {
-            list_workspace_files_inner(root, 20000)
-        })
+    async fn file_read_capped(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+        max_bytes: u64,
+    ) -> Result<file_io::CappedRead, String> {
+        files_core::file_read_capped_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+            max_bytes,
+        )
         .await
     }
 
-    async fn read_workspace_file(
+    async fn file_read_lines_normalized(
         &self,
-        workspace_id: String,
-        path: String,
-    ) -> Result<WorkspaceFileResponse, String> {
-        workspaces_core::read_workspace_file_core(
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<file_io::NormalizedLines, String> {
+        files_core::file_read_lines_normalized_core(
             &self.workspaces,
-            &workspace_id,
-            &path,
-            |root, rel_path| read_workspace_file_inner(root, rel_path),
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
         )
         .await
     }
 
-    async fn file_read(
+    async fn policy_explain(
         &self,
         scope: file_policy::FileScope,
         kind: file_policy::FileKind,
         workspace_id: Option<String>,
-    ) -> Result<file_io::TextFileResponse, String> {
-        files_core::file_read_core(&self.workspaces, scope, kind, workspace_id).await
+    ) -> file_policy::PolicyExplanation {
+        files_core::policy_explain_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            kind,
+            workspace_id,
+        )
+        .await
     }
 
-    async fn file_write(
+    async fn scope_watch(
         &self,
         scope: file_policy::FileScope,
+        workspace_id: Option<String>,
+    ) -> Result<String, String> {
+        files_core::scope_watch_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            &self.scope_watchers,
+            self.event_sink.clone(),
+            scope,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn scope_unwatch(&self, watch_id: String) -> bool {
+        files_core::scope_unwatch_core(&self.scope_watchers, &watch_id).await
+    }
+
+    async fn scope_watch_set_coalesce_window(&self, window_ms: u32) -> Result<(), String> {
+        files_core::scope_watch_set_coalesce_window_core(&self.scope_watchers, u64::from(window_ms))
+    }
+
+    async fn policy_rename_kind_file(
+        &self,
+        workspace_id: Option<String>,
         kind: file_policy::FileKind,
+        old_name: String,
+        new_name: String,
+    ) -> Result<Vec<String>, String> {
+        files_core::policy_rename_kind_file_core(
+            &self.workspaces,
+            &self.file_generations,
+            workspace_id,
+            kind,
+            old_name,
+            new_name,
+        )
+        .await
+    }
+
+    async fn scope_write_test(
+        &self,
+        scope: file_policy::FileScope,
         workspace_id: Option<String>,
-        content: String,
     ) -> Result<(), String> {
-        files_core::file_write_core(&self.workspaces, scope, kind, workspace_id, content).await
+        files_core::scope_write_test_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            scope,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn scope_disk_usage(
+        &self,
+        workspace_id: Option<String>,
+        recursive: bool,
+    ) -> Result<files_core::ScopeDiskUsage, String> {
+        files_core::scope_disk_usage_core(
+            &self.workspaces,
+            &self.scope_root_overrides,
+            workspace_id,
+            recursive,
+        )
+        .await
+    }
+
+    async fn disk_free(&self, path_kind: shared::disk_core::PathKind) -> Result<u64, String> {
+        shared::disk_core::disk_free_core(&self.workspaces, &self.data_dir, path_kind).await
+    }
+
+    async fn workspace_export_zip(
+        &self,
+        workspace_id: String,
+        dest_path: String,
+        operation_id: Option<String>,
+    ) -> Result<Vec<file_archive::ArchiveEntry>, String> {
+        let reporter = OperationReporter::new(self.event_sink.clone(), operation_id);
+        files_core::workspace_export_zip_core(&self.workspaces, workspace_id, dest_path, &reporter)
+            .await
+    }
+
+    async fn workspace_import_zip(
+        &self,
+        workspace_id: String,
+        source_path: String,
+        operation_id: Option<String>,
+    ) -> Result<Vec<file_archive::ArchiveEntry>, String> {
+        let reporter = OperationReporter::new(self.event_sink.clone(), operation_id);
+        files_core::workspace_import_zip_core(
+            &self.workspaces,
+            &self.file_generations,
+            &self.scope_watchers.coalescer,
+            workspace_id,
+            source_path,
+            &reporter,
+        )
+        .await
     }
 
     async fn start_thread(&self, workspace_id: String) -> Result<Value, String> {
@@ -1343,13 +2924,6 @@ impl DaemonState {
     }
 }
 
-fn should_skip_dir(name: &str) -> bool {
-    matches!(
-        name,
-        ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
-    )
-}
-
 fn normalize_git_path(path: &str) -> String {
     path.replace('\\', "/")
 }
@@ -1604,8 +3178,17 @@ mod tests {
             storage_path: data_dir.join("workspaces.json"),
             settings_path: data_dir.join("settings.json"),
             app_settings: Mutex::new(AppSettings::default()),
+            config_drift: Mutex::new(None),
             event_sink: DaemonEventSink { tx },
             codex_login_cancels: Mutex::new(HashMap::new()),
+            scope_watchers: Default::default(),
+            file_generations: file_generation::Generations::new(),
+            scope_root_overrides: Default::default(),
+            readiness_cache: Default::default(),
+            prewarm: Default::default(),
+            workspace_health: Mutex::new(None),
+            tree_scans: Default::default(),
+            reads_in_flight: Mutex::new(HashSet::new()),
             daemon_binary_path: Some("/tmp/codex-monitor-daemon".to_string()),
         }
     }
@@ -1759,6 +3342,52 @@ mod tests {
         });
     }
 
+    #[test]
+    fn rpc_capabilities_list_every_routed_method() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-capabilities");
+            let state = test_state(&tmp);
+
+            let result = rpc::handle_rpc_request(
+                &state,
+                "capabilities",
+                json!({}),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("capabilities should succeed");
+
+            assert!(result
+                .get("protocolVersion")
+                .and_then(Value::as_u64)
+                .is_some());
+            let reported: HashSet<&str> = result
+                .get("methods")
+                .and_then(Value::as_array)
+                .expect("methods")
+                .iter()
+                .filter_map(Value::as_str)
+                .collect();
+            let sources = [
+                include_str!("codex_monitor_daemon/rpc/codex.rs"),
+                include_str!("codex_monitor_daemon/rpc/daemon.rs"),
+                include_str!("codex_monitor_daemon/rpc/git.rs"),
+                include_str!("codex_monitor_daemon/rpc/prompts.rs"),
+                include_str!("codex_monitor_daemon/rpc/workspace.rs"),
+            ];
+            let routed: HashSet<&str> = sources
+                .iter()
+                .flat_map(|source| source.lines())
+                .filter_map(|line| line.strip_prefix("        \""))
+                .filter_map(|arm| arm.split_once("=>").map(|(pattern, _)| pattern))
+                .flat_map(|pattern| pattern.split('|'))
+                .map(|name| name.trim().trim_matches('"'))
+                .collect();
+            assert_eq!(reported, routed);
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
     #[test]
     fn rpc_daemon_info_reports_identity() {
         run_async_test(async {
@@ -1923,6 +3552,17 @@ fn main() {
         };
         let state = Arc::new(DaemonState::load(&config, event_sink));
         let config = Arc::new(config);
+        {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                workspaces_core::workspace_health_startup_core(
+                    &state.workspaces,
+                    &state.workspace_health,
+                    &state.event_sink,
+                )
+                .await;
+            });
+        }
 
         let listener = match TcpListener::bind(config.listen).await {
             Ok(listener) => listener,
@@ -1,5 +1,7 @@
 use super::*;
 
+#[path = "rpc/capabilities.rs"]
+mod capabilities;
 #[path = "rpc/codex.rs"]
 mod codex;
 #[path = "rpc/daemon.rs"]
@@ -49,6 +51,54 @@ fn build_event_notification(event: DaemonEvent) -> Option<String> {
             "method": "terminal-exit",
             "params": payload,
         }),
+        DaemonEvent::OperationProgress(payload) => json!({
+            "method": "operation-progress",
+            "params": payload,
+        }),
+        DaemonEvent::OperationFinished(payload) => json!({
+            "method": if payload.error.is_some() {
+                "operation-failed"
+            } else {
+                "operation-done"
+            },
+            "params": payload,
+        }),
+        DaemonEvent::ScopeChanged(payload) => json!({
+            "method": "scope-changed",
+            "params": payload,
+        }),
+        DaemonEvent::BatchChanged(payload) => json!({
+            "method": "batch-changed",
+            "params": payload,
+        }),
+        DaemonEvent::WorkspacesChanged(payload) => json!({
+            "method": "workspaces-changed",
+            "params": payload,
+        }),
+        DaemonEvent::ConfigPatch(payload) => json!({
+            "method": "config-patch",
+            "params": payload,
+        }),
+        DaemonEvent::PrewarmDone(payload) => json!({
+            "method": "prewarm-done",
+            "params": payload,
+        }),
+        DaemonEvent::WorkspaceHealth(payload) => json!({
+            "method": "workspace-health",
+            "params": payload,
+        }),
+        DaemonEvent::TreeEntries(payload) => json!({
+            "method": "tree-entry",
+            "params": payload,
+        }),
+        DaemonEvent::TreeTruncated(payload) => json!({
+            "method": "tree-truncated",
+            "params": payload,
+        }),
+        DaemonEvent::TreeDone(payload) => json!({
+            "method": "tree-done",
+            "params": payload,
+        }),
     };
     serde_json::to_string(&payload).ok()
 }
@@ -176,23 +226,106 @@ pub(super) async fn forward_events(
     }
 }
 
+/// One decoded client request. `request_id` is the client's correlation id,
+/// used only for logging.
+pub(super) struct RpcRequest {
+    pub(super) id: Option<u64>,
+    pub(super) request_id: Option<String>,
+    pub(super) method: String,
+    pub(super) params: Value,
+}
+
+/// Scope watches started over one connection, stopped when it closes.
+#[derive(Default)]
+pub(super) struct ConnectionWatches {
+    closed: bool,
+    watch_ids: HashSet<String>,
+}
+
+impl ConnectionWatches {
+    /// Tracks the watch a `scope_watch` result names, or a `scope_unwatch`
+    /// of one. Returns a watch started after the connection closed, which the
+    /// caller must stop.
+    fn record(
+        &mut self,
+        method: &str,
+        unwatch_id: Option<String>,
+        result: &Value,
+    ) -> Option<String> {
+        match method {
+            "scope_watch" => {
+                let watch_id = result.as_str()?.to_string();
+                if self.closed {
+                    return Some(watch_id);
+                }
+                self.watch_ids.insert(watch_id);
+            }
+            "scope_unwatch" => {
+                self.watch_ids.remove(unwatch_id.as_deref()?);
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Stops every scope watch the closed connection left running.
+pub(super) async fn stop_connection_watches(
+    state: &DaemonState,
+    watches: &std::sync::Mutex<ConnectionWatches>,
+) {
+    let watch_ids = match watches.lock() {
+        Ok(mut watches) => {
+            watches.closed = true;
+            std::mem::take(&mut watches.watch_ids)
+        }
+        Err(_) => return,
+    };
+    for watch_id in watch_ids {
+        state.scope_unwatch(watch_id).await;
+    }
+}
+
 pub(super) fn spawn_rpc_response_task(
     state: Arc<DaemonState>,
     out_tx: mpsc::UnboundedSender<String>,
-    id: Option<u64>,
-    method: String,
-    params: Value,
+    request: RpcRequest,
     client_version: String,
     request_limiter: Arc<Semaphore>,
+    watches: Arc<std::sync::Mutex<ConnectionWatches>>,
 ) {
     tokio::spawn(async move {
         let Ok(_permit) = request_limiter.acquire_owned().await else {
             return;
         };
+        let RpcRequest {
+            id,
+            request_id,
+            method,
+            params,
+        } = request;
+        let unwatch_id = params
+            .get("watchId")
+            .and_then(Value::as_str)
+            .map(str::to_string);
         let result = handle_rpc_request(&state, &method, params, client_version).await;
+        if let Ok(value) = &result {
+            let orphaned = watches
+                .lock()
+                .ok()
+                .and_then(|mut watches| watches.record(&method, unwatch_id, value));
+            if let Some(watch_id) = orphaned {
+                state.scope_unwatch(watch_id).await;
+            }
+        }
         let response = match result {
             Ok(result) => build_result_response(id, result),
-            Err(message) => build_error_response(id, &message),
+            Err(message) => {
+                if let Some(request_id) = request_id.as_deref() {
+                    eprintln!("daemon: `{method}` failed (request id: {request_id}): {message}");
+                }
+                build_error_response(id, &message)
+            }
         };
         if let Some(response) = response {
             let _ = out_tx.send(response);
@@ -0,0 +1,234 @@
+use super::*;
+
+use crate::remote_capabilities::REMOTE_PROTOCOL_VERSION;
+
+/// Every method the dispatcher routes, reported by `capabilities`; kept in
+/// step with the handlers by the test below.
+pub(super) const SUPPORTED_METHODS: &[&str] = &[
+    "account_rate_limits",
+    "account_read",
+    "add_clone",
+    "add_workspace",
+    "add_workspace_from_git_url",
+    "add_worktree",
+    "agents_effective",
+    "apply_worktree_changes",
+    "apps_list",
+    "archive_thread",
+    "capabilities",
+    "cleanup_orphans",
+    "codex_doctor",
+    "codex_effective_config_path",
+    "codex_home_relocate",
+    "codex_login",
+    "codex_login_cancel",
+    "collaboration_mode_list",
+    "compact_thread",
+    "config_array_add",
+    "config_array_remove",
+    "config_drift_check",
+    "config_export_redacted",
+    "config_fix_duplicate_keys",
+    "config_format",
+    "config_history",
+    "config_indent_info",
+    "config_load_tolerant",
+    "config_merge",
+    "config_migrate",
+    "config_migrate_preview",
+    "config_profile_create",
+    "config_profile_delete",
+    "config_profile_diff",
+    "config_profile_rename",
+    "config_read",
+    "config_redo",
+    "config_reindent",
+    "config_roundtrip_check",
+    "config_schema_check",
+    "config_stat",
+    "config_strip_comments",
+    "config_transaction",
+    "config_undo",
+    "config_with_defaults",
+    "config_write",
+    "connect_workspace",
+    "create_agent",
+    "daemon_info",
+    "daemon_shutdown",
+    "defaults_catalog",
+    "delete_agent",
+    "disk_free",
+    "effective_sandbox",
+    "experimental_feature_list",
+    "feature_preset_apply",
+    "feature_preset_apply_all",
+    "feature_preset_delete",
+    "feature_preset_list",
+    "feature_preset_save",
+    "features_catalog",
+    "file_copy",
+    "file_fix_permissions",
+    "file_link_info",
+    "file_paths",
+    "file_permissions_audit",
+    "file_read",
+    "file_read_capped",
+    "file_read_lines_normalized",
+    "file_read_many",
+    "file_read_range",
+    "file_read_timed",
+    "file_render_template",
+    "file_reset_to_default",
+    "file_search",
+    "file_snapshot",
+    "file_snapshot_list",
+    "file_snapshot_restore",
+    "file_swap",
+    "file_touch",
+    "file_truncate",
+    "file_validate_kind",
+    "file_writable",
+    "file_write",
+    "file_write_preview",
+    "file_write_report",
+    "file_write_symlink",
+    "file_write_templated",
+    "fork_thread",
+    "generate_agent_description",
+    "generate_run_metadata",
+    "get_agents_settings",
+    "get_app_settings",
+    "get_codex_config_path",
+    "get_config_model",
+    "get_open_app_icon",
+    "is_macos_debug_build",
+    "is_workspace_path_dir",
+    "list_mcp_server_status",
+    "list_threads",
+    "list_workspace_files",
+    "list_workspaces",
+    "local_usage_snapshot",
+    "mcp_server_set_enabled",
+    "mcp_servers_list",
+    "menu_set_accelerators",
+    "model_list",
+    "open_workspace_in",
+    "ping",
+    "policy_clear_scope_override",
+    "policy_explain",
+    "policy_rename_kind_file",
+    "policy_set_scope_override",
+    "profile_export",
+    "profile_import",
+    "prompts_create",
+    "prompts_delete",
+    "prompts_global_dir",
+    "prompts_list",
+    "prompts_move",
+    "prompts_update",
+    "prompts_workspace_dir",
+    "read_agent_config_toml",
+    "read_effective_config",
+    "read_effective_personality",
+    "read_reasoning_effort",
+    "read_workspace_file",
+    "remember_approval_rule",
+    "remote_cancel",
+    "remove_workspace",
+    "remove_worktree",
+    "rename_worktree",
+    "rename_worktree_upstream",
+    "respond_to_server_request",
+    "resume_thread",
+    "scope_disk_usage",
+    "scope_unwatch",
+    "scope_watch",
+    "scope_watch_set_coalesce_window",
+    "scope_write_test",
+    "send_notification_fallback",
+    "send_user_message",
+    "set_agents_core_settings",
+    "set_codex_feature_flag",
+    "set_thread_name",
+    "set_workspace_runtime_codex_args",
+    "skills_list",
+    "start_review",
+    "start_thread",
+    "thread_live_subscribe",
+    "thread_live_unsubscribe",
+    "tools_config_read",
+    "tools_config_set",
+    "tree_scan_cancel",
+    "tree_scan_start",
+    "turn_interrupt",
+    "turn_steer",
+    "update_agent",
+    "update_app_settings",
+    "update_workspace_settings",
+    "workspace_check",
+    "workspace_effective_features",
+    "workspace_effective_model",
+    "workspace_effective_personality",
+    "workspace_export_zip",
+    "workspace_find_duplicates",
+    "workspace_health",
+    "workspace_import_existing",
+    "workspace_import_zip",
+    "workspace_initialize",
+    "workspace_list_broken",
+    "workspace_prewarm",
+    "workspace_prune_broken",
+    "workspace_readiness",
+    "workspace_relink",
+    "workspace_set_codex_home",
+    "workspace_set_default_override",
+    "workspace_state_repair",
+    "worktree_setup_mark_ran",
+    "worktree_setup_status",
+    "write_agent_config_toml",
+    "write_reasoning_effort",
+];
+
+pub(super) fn capabilities() -> Value {
+    json!({
+        "protocolVersion": REMOTE_PROTOCOL_VERSION,
+        "methods": SUPPORTED_METHODS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::SUPPORTED_METHODS;
+
+    /// The methods every handler matches on, read from the `"method" =>` arms.
+    fn routed_methods() -> BTreeSet<&'static str> {
+        [
+            include_str!("daemon.rs"),
+            include_str!("workspace.rs"),
+            include_str!("codex.rs"),
+            include_str!("git.rs"),
+            include_str!("prompts.rs"),
+        ]
+        .into_iter()
+        .flat_map(|source| source.lines())
+        .filter_map(|line| {
+            let arm = line.strip_prefix("        \"")?;
+            let (method, rest) = arm.split_once('"')?;
+            rest.trim_start().starts_with("=>").then_some(method)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn supported_methods_match_the_dispatcher() {
+        let listed: BTreeSet<&str> = SUPPORTED_METHODS.iter().copied().collect();
+        assert_eq!(
+            listed.len(),
+            SUPPORTED_METHODS.len(),
+            "a method is listed twice"
+        );
+        assert_eq!(listed, routed_methods());
+    }
+}
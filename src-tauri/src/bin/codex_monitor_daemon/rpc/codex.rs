@@ -17,12 +17,28 @@ pub(super) async fn try_handle(
 ) -> Option<Result<Value, String>> {
     match method {
         "get_codex_config_path" => {
-            let path = match settings_core::get_codex_config_path_core() {
+            let path = match settings_core::get_codex_config_path_core(&state.scope_root_overrides)
+            {
                 Ok(value) => value,
                 Err(err) => return Some(Err(err)),
             };
             Some(Ok(Value::String(path)))
         }
+        "codex_home_relocate" => {
+            let new_path = match parse_string(params, "newPath") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let copy_all = parse_optional_bool(params, "copyAll").unwrap_or(false);
+            let overwrite = parse_optional_bool(params, "overwrite").unwrap_or(false);
+            let remove_old = parse_optional_bool(params, "removeOld").unwrap_or(false);
+            Some(
+                state
+                    .codex_home_relocate(new_path, copy_all, overwrite, remove_old)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
         "get_config_model" => {
             let workspace_id = match parse_string(params, "workspaceId") {
                 Ok(value) => value,
@@ -292,6 +308,447 @@ pub(super) async fn try_handle(
                     .map(|_| json!({ "ok": true })),
             )
         }
+        "config_read" => Some(
+            state
+                .config_read()
+                .await
+                .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+        ),
+        "config_write" => {
+            let values_value = parse_optional_value(params, "values").unwrap_or(Value::Null);
+            let values: codex_config::CodexConfigValues = match serde_json::from_value(values_value)
+            {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err.to_string())),
+            };
+            Some(
+                state
+                    .config_write(values)
+                    .await
+                    .map(|canonicalized| json!({ "ok": true, "canonicalized": canonicalized })),
+            )
+        }
+        "config_undo" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_undo(codex_home, workspace_id)
+                    .await
+                    .map(|changed| json!(changed)),
+            )
+        }
+        "config_redo" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_redo(codex_home, workspace_id)
+                    .await
+                    .map(|changed| json!(changed)),
+            )
+        }
+        "config_history" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_history(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_format" => {
+            let sort_keys = parse_optional_bool(params, "sortKeys").unwrap_or(false);
+            let dry_run = parse_optional_bool(params, "dryRun").unwrap_or(false);
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_format(sort_keys, dry_run, codex_home, workspace_id)
+                    .await
+                    .map(Value::String),
+            )
+        }
+        "config_migrate_preview" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_migrate_preview(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_migrate" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_migrate(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_merge" => {
+            let changes = match params
+                .as_object()
+                .and_then(|map| map.get("changes"))
+                .cloned()
+                .ok_or_else(|| "missing `changes`".to_string())
+                .and_then(|value| serde_json::from_value(value).map_err(|err| err.to_string()))
+            {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_merge(changes, codex_home, workspace_id)
+                    .await
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
+        "config_transaction" => {
+            let ops = match params
+                .as_object()
+                .and_then(|map| map.get("ops"))
+                .cloned()
+                .ok_or_else(|| "missing `ops`".to_string())
+                .and_then(|value| serde_json::from_value(value).map_err(|err| err.to_string()))
+            {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_transaction(ops, codex_home, workspace_id)
+                    .await
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
+        "config_array_add" => {
+            let key = match parse_string(params, "key") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let value = match parse_string(params, "value") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_array_add(key, value, codex_home, workspace_id)
+                    .await
+                    .map(Value::Bool),
+            )
+        }
+        "config_array_remove" => {
+            let key = match parse_string(params, "key") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let value = match parse_string(params, "value") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_array_remove(key, value, codex_home, workspace_id)
+                    .await
+                    .map(Value::Bool),
+            )
+        }
+        "config_export_redacted" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            let extra_keys = parse_optional_string_array(params, "extraKeys").unwrap_or_default();
+            Some(
+                state
+                    .config_export_redacted(codex_home, workspace_id, extra_keys)
+                    .await
+                    .map(Value::String),
+            )
+        }
+        "config_strip_comments" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            let write = parse_optional_bool(params, "write").unwrap_or(false);
+            Some(
+                state
+                    .config_strip_comments(codex_home, workspace_id, write)
+                    .await
+                    .map(Value::String),
+            )
+        }
+        "config_indent_info" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_indent_info(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_reindent" => {
+            let style = match params
+                .as_object()
+                .and_then(|map| map.get("style"))
+                .cloned()
+                .ok_or_else(|| "missing `style`".to_string())
+                .and_then(|value| serde_json::from_value(value).map_err(|err| err.to_string()))
+            {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_reindent(codex_home, workspace_id, style)
+                    .await
+                    .map(Value::String),
+            )
+        }
+        "config_load_tolerant" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_load_tolerant(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_fix_duplicate_keys" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_fix_duplicate_keys(codex_home, workspace_id)
+                    .await
+                    .map(Value::from),
+            )
+        }
+        "config_roundtrip_check" => {
+            let proposed = match parse_string(params, "proposed") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_roundtrip_check(proposed, codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_drift_check" => Some(
+            state
+                .config_drift_check()
+                .await
+                .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+        ),
+        "config_with_defaults" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_with_defaults(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_profile_diff" => {
+            let profile_a = match parse_string(params, "profileA") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let profile_b = match parse_string(params, "profileB") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_profile_diff(profile_a, profile_b, codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_profile_create" => {
+            let name = match parse_string(params, "name") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let base = parse_optional_string(params, "base");
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_profile_create(name, base, codex_home, workspace_id)
+                    .await
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
+        "config_profile_delete" => {
+            let name = match parse_string(params, "name") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_profile_delete(name, codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_profile_rename" => {
+            let old = match parse_string(params, "old") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let new = match parse_string(params, "new") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_profile_rename(old, new, codex_home, workspace_id)
+                    .await
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
+        "tools_config_read" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .tools_config_read(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "tools_config_set" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            let tool = match parse_string(params, "tool") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let enabled = parse_optional_bool(params, "enabled");
+            Some(
+                state
+                    .tools_config_set(codex_home, workspace_id, tool, enabled)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "mcp_servers_list" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .mcp_servers_list(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "mcp_server_set_enabled" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            let name = match parse_string(params, "name") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let enabled = match parse_optional_bool(params, "enabled") {
+                Some(value) => value,
+                None => return Some(Err("missing or invalid `enabled`".to_string())),
+            };
+            Some(
+                state
+                    .mcp_server_set_enabled(codex_home, workspace_id, name, enabled)
+                    .await
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
+        "config_schema_check" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_schema_check(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "config_stat" => {
+            let codex_home = parse_optional_string(params, "codexHome");
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(
+                state
+                    .config_stat(codex_home, workspace_id)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "read_effective_config" => Some(
+            state
+                .read_effective_config()
+                .await
+                .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+        ),
+        "codex_effective_config_path" => Some(
+            state
+                .codex_effective_config_path()
+                .await
+                .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+        ),
+        "features_catalog" => Some(
+            state
+                .features_catalog()
+                .await
+                .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+        ),
+        "read_effective_personality" => Some(
+            state
+                .read_effective_personality()
+                .await
+                .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+        ),
+        "read_reasoning_effort" => {
+            let profile = parse_optional_string(params, "profile");
+            Some(
+                state
+                    .read_reasoning_effort(profile)
+                    .await
+                    .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())),
+            )
+        }
+        "write_reasoning_effort" => {
+            let profile = parse_optional_string(params, "profile");
+            let effort = parse_optional_string(params, "effort");
+            Some(
+                state
+                    .write_reasoning_effort(profile, effort)
+                    .await
+                    .map(|_| json!({ "ok": true })),
+            )
+        }
         "get_agents_settings" => Some(
             state
                 .get_agents_settings()
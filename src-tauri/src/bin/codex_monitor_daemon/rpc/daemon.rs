@@ -8,6 +8,7 @@ pub(super) async fn try_handle(
     match method {
         "ping" => Some(Ok(json!({ "ok": true }))),
         "daemon_info" => Some(Ok(state.daemon_info())),
+        "capabilities" => Some(Ok(capabilities::capabilities())),
         "daemon_shutdown" => {
             tokio::spawn(async {
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
@@ -12,6 +12,68 @@ struct FileReadRequest {
     workspace_id: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileReadCappedRequest {
+    scope: file_policy::FileScope,
+    kind: file_policy::FileKind,
+    workspace_id: Option<String>,
+    max_bytes: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiskFreeRequest {
+    path_kind: crate::shared::disk_core::PathKind,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileTruncateRequest {
+    scope: file_policy::FileScope,
+    kind: file_policy::FileKind,
+    workspace_id: Option<String>,
+    #[serde(default)]
+    keep_last_bytes: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileResetToDefaultRequest {
+    scope: file_policy::FileScope,
+    kind: file_policy::FileKind,
+    workspace_id: Option<String>,
+    backup: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileReadRangeRequest {
+    scope: file_policy::FileScope,
+    kind: file_policy::FileKind,
+    workspace_id: Option<String>,
+    offset: u64,
+    length: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TreeScanStartRequest {
+    scope: file_policy::FileScope,
+    workspace_id: Option<String>,
+    #[serde(default)]
+    max_depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileWriteSymlinkRequest {
+    scope: file_policy::FileScope,
+    kind: file_policy::FileKind,
+    workspace_id: Option<String>,
+    target: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct FileWriteRequest {
@@ -19,6 +81,151 @@ struct FileWriteRequest {
     kind: file_policy::FileKind,
     workspace_id: Option<String>,
     content: String,
+    #[serde(default)]
+    expected_modified_ms: Option<u64>,
+    #[serde(default)]
+    force: bool,
+    #[serde(default)]
+    verify: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileWriteTemplatedRequest {
+    scope: file_policy::FileScope,
+    kind: file_policy::FileKind,
+    workspace_id: Option<String>,
+    template: String,
+    #[serde(default)]
+    vars: HashMap<String, String>,
+    #[serde(default)]
+    strict: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileRenderTemplateRequest {
+    template: String,
+    #[serde(default)]
+    vars: HashMap<String, String>,
+    workspace_id: Option<String>,
+    #[serde(default)]
+    strict: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileValidateKindRequest {
+    kind: file_policy::FileKind,
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScopeOverrideRequest {
+    scope: file_policy::FileScope,
+    #[serde(default)]
+    workspace_id: Option<String>,
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScopeRequest {
+    scope: file_policy::FileScope,
+    #[serde(default)]
+    workspace_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScopeWatchRequest {
+    scope: file_policy::FileScope,
+    #[serde(default)]
+    workspace_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileSwapRequest {
+    scope_a: file_policy::FileScope,
+    kind_a: file_policy::FileKind,
+    scope_b: file_policy::FileScope,
+    kind_b: file_policy::FileKind,
+    #[serde(default)]
+    workspace_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FilePathsRequest {
+    requests: Vec<file_policy::ScopedFileRef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceInitializeRequest {
+    workspace_id: String,
+    #[serde(default)]
+    overwrite: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileReadManyRequest {
+    requests: Vec<file_policy::ScopedFileRef>,
+    request_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileCopyRequest {
+    source: file_policy::ScopedFileRef,
+    target: file_policy::ScopedFileRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileSnapshotRequest {
+    #[serde(flatten)]
+    file: file_policy::ScopedFileRef,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileSearchRequest {
+    scope: file_policy::FileScope,
+    workspace_id: Option<String>,
+    query: String,
+    #[serde(default)]
+    regex: bool,
+    max_results: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceExportZipRequest {
+    workspace_id: String,
+    dest_path: String,
+    #[serde(default)]
+    operation_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceImportZipRequest {
+    workspace_id: String,
+    source_path: String,
+    #[serde(default)]
+    operation_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PolicyRenameKindFileRequest {
+    #[serde(default)]
+    workspace_id: Option<String>,
+    kind: file_policy::FileKind,
+    old_name: String,
+    new_name: String,
 }
 
 fn parse_file_read_request(params: &Value) -> Result<FileReadRequest, String> {
@@ -29,6 +236,14 @@ fn parse_file_write_request(params: &Value) -> Result<FileWriteRequest, String>
     serde_json::from_value(params.clone()).map_err(|err| err.to_string())
 }
 
+fn parse_file_copy_request(params: &Value) -> Result<FileCopyRequest, String> {
+    serde_json::from_value(params.clone()).map_err(|err| err.to_string())
+}
+
+fn parse_file_search_request(params: &Value) -> Result<FileSearchRequest, String> {
+    serde_json::from_value(params.clone()).map_err(|err| err.to_string())
+}
+
 fn parse_workspace_request<T: DeserializeOwned>(params: &Value) -> Result<T, String> {
     workspace_rpc::from_params(params)
 }
@@ -69,6 +284,64 @@ pub(super) async fn try_handle(
 ) -> Option<Result<Value, String>> {
     match method {
         "list_workspaces" => Some(serialize_value(state.list_workspaces().await)),
+        "workspace_state_repair" => Some(serialize_result(state.workspace_state_repair()).await),
+        "workspace_list_broken" => Some(serialize_value(state.workspace_list_broken().await)),
+        "workspace_check" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_result(state.workspace_check(workspace_id)).await)
+        }
+        "workspace_health" => Some(serialize_value(state.workspace_health().await)),
+        "workspace_relink" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceRelinkRequest);
+            Some(serialize_result(state.workspace_relink(request.id, request.new_root)).await)
+        }
+        "workspace_prune_broken" => Some(serialize_result(state.workspace_prune_broken()).await),
+        "profile_export" => Some(serialize_result(state.profile_export()).await),
+        "profile_import" => {
+            let request = parse_request_or_err!(params, workspaces_core::ProfileImportRequest);
+            Some(serialize_result(state.profile_import(request)).await)
+        }
+        "workspace_find_duplicates" => {
+            Some(serialize_value(state.workspace_find_duplicates().await))
+        }
+        "workspace_set_codex_home" => {
+            let request =
+                parse_request_or_err!(params, workspace_rpc::SetWorkspaceCodexHomeRequest);
+            Some(serialize_result(state.workspace_set_codex_home(request.id, request.path)).await)
+        }
+        "workspace_effective_model" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
+            Some(serialize_result(state.workspace_effective_model(request.workspace_id)).await)
+        }
+        "workspace_effective_personality" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
+            Some(
+                serialize_result(state.workspace_effective_personality(request.workspace_id)).await,
+            )
+        }
+        "workspace_effective_features" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
+            Some(serialize_result(state.workspace_effective_features(request.workspace_id)).await)
+        }
+        "effective_sandbox" => {
+            let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
+            Some(serialize_result(state.effective_sandbox(request.workspace_id)).await)
+        }
+        "workspace_set_default_override" => {
+            let request =
+                parse_request_or_err!(params, workspace_rpc::SetWorkspaceDefaultOverrideRequest);
+            Some(
+                serialize_result(state.workspace_set_default_override(
+                    request.workspace_id,
+                    request.key,
+                    request.value,
+                ))
+                .await,
+            )
+        }
         "is_workspace_path_dir" => {
             let request = parse_request_or_err!(params, workspace_rpc::IsWorkspacePathDirRequest);
             Some(serialize_value(
@@ -78,8 +351,24 @@ pub(super) async fn try_handle(
         "add_workspace" => {
             let request = parse_request_or_err!(params, workspace_rpc::AddWorkspaceRequest);
             Some(
-                serialize_result(state.add_workspace(request.path, client_version.to_string()))
-                    .await,
+                serialize_result(state.add_workspace(
+                    request.path,
+                    request.reject_duplicate,
+                    client_version.to_string(),
+                ))
+                .await,
+            )
+        }
+        "workspace_import_existing" => {
+            let request =
+                parse_request_or_err!(params, workspace_rpc::WorkspaceImportExistingRequest);
+            Some(
+                serialize_result(state.workspace_import_existing(
+                    request.root_path,
+                    request.name,
+                    client_version.to_string(),
+                ))
+                .await,
             )
         }
         "add_workspace_from_git_url" => {
@@ -214,6 +503,20 @@ pub(super) async fn try_handle(
                 .await,
             )
         }
+        "file_read_timed" => {
+            let request = match parse_file_read_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_read_timed(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                ))
+                .await,
+            )
+        }
         "file_write" => {
             let request = match parse_file_write_request(params) {
                 Ok(value) => value,
@@ -225,6 +528,430 @@ pub(super) async fn try_handle(
                     request.kind,
                     request.workspace_id,
                     request.content,
+                    request.expected_modified_ms,
+                    request.force,
+                    request.verify,
+                ))
+                .await,
+            )
+        }
+        "file_write_preview" => {
+            let request = match parse_file_write_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_write_preview(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                    request.content,
+                ))
+                .await,
+            )
+        }
+        "file_write_report" => {
+            let request = match parse_file_write_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_write_report(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                    request.content,
+                    request.expected_modified_ms,
+                    request.force,
+                ))
+                .await,
+            )
+        }
+        "file_write_templated" => {
+            let request = parse_request_or_err!(params, FileWriteTemplatedRequest);
+            Some(
+                serialize_ok(state.file_write_templated(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                    request.template,
+                    request.vars,
+                    request.strict,
+                ))
+                .await,
+            )
+        }
+        "file_render_template" => {
+            let request = parse_request_or_err!(params, FileRenderTemplateRequest);
+            Some(serialize_value(
+                state
+                    .file_render_template(
+                        request.template,
+                        request.vars,
+                        request.workspace_id,
+                        request.strict,
+                    )
+                    .await,
+            ))
+        }
+        "defaults_catalog" => Some(serialize_value(state.defaults_catalog())),
+        "file_reset_to_default" => {
+            let request = parse_request_or_err!(params, FileResetToDefaultRequest);
+            Some(
+                serialize_result(state.file_reset_to_default(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                    request.backup,
+                ))
+                .await,
+            )
+        }
+        "file_paths" => {
+            let request = parse_request_or_err!(params, FilePathsRequest);
+            Some(serialize_value(state.file_paths(request.requests).await))
+        }
+        "file_read_many" => {
+            let request = parse_request_or_err!(params, FileReadManyRequest);
+            Some(serialize_value(
+                state
+                    .file_read_many(request.requests, request.request_id)
+                    .await,
+            ))
+        }
+        "remote_cancel" => {
+            let request_id = match parse_string(params, "requestId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_value(state.remote_cancel(request_id).await))
+        }
+        "file_copy" => {
+            let request = match parse_file_copy_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_ok(state.file_copy(request.source, request.target)).await)
+        }
+        "file_snapshot" => {
+            let request = parse_request_or_err!(params, FileSnapshotRequest);
+            Some(serialize_result(state.file_snapshot(request.file, request.label)).await)
+        }
+        "file_snapshot_list" => {
+            let file = parse_request_or_err!(params, file_policy::ScopedFileRef);
+            Some(serialize_result(state.file_snapshot_list(file)).await)
+        }
+        "file_snapshot_restore" => {
+            let snapshot_id = match parse_string(params, "snapshotId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_result(state.file_snapshot_restore(snapshot_id)).await)
+        }
+        "cleanup_orphans" => {
+            // Only an explicit `dryRun: false` deletes anything.
+            let dry_run = parse_optional_bool(params, "dryRun").unwrap_or(true);
+            Some(serialize_result(state.cleanup_orphans(dry_run)).await)
+        }
+        "tree_scan_start" => {
+            let request = parse_request_or_err!(params, TreeScanStartRequest);
+            Some(
+                serialize_result(state.tree_scan_start(
+                    request.scope,
+                    request.workspace_id,
+                    request.max_depth,
+                ))
+                .await,
+            )
+        }
+        "tree_scan_cancel" => {
+            let scan_id = match parse_string(params, "scanId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_value(state.tree_scan_cancel(scan_id).await))
+        }
+        "file_swap" => {
+            let request = parse_request_or_err!(params, FileSwapRequest);
+            Some(
+                serialize_ok(state.file_swap(
+                    request.scope_a,
+                    request.kind_a,
+                    request.scope_b,
+                    request.kind_b,
+                    request.workspace_id,
+                ))
+                .await,
+            )
+        }
+        "file_search" => {
+            let request = match parse_file_search_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_search(
+                    request.scope,
+                    request.workspace_id,
+                    request.query,
+                    request.regex,
+                    request.max_results,
+                ))
+                .await,
+            )
+        }
+        "file_validate_kind" => {
+            let request = parse_request_or_err!(params, FileValidateKindRequest);
+            Some(serialize_value(file_validate::validate_kind_content(
+                request.kind,
+                &request.content,
+            )))
+        }
+        "workspace_initialize" => {
+            let request = parse_request_or_err!(params, WorkspaceInitializeRequest);
+            Some(
+                serialize_result(
+                    state.workspace_initialize(request.workspace_id, request.overwrite),
+                )
+                .await,
+            )
+        }
+        "workspace_readiness" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_result(state.workspace_readiness(workspace_id)).await)
+        }
+        "workspace_prewarm" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_value(state.workspace_prewarm(workspace_id).await))
+        }
+        "agents_effective" => {
+            let workspace_id = match parse_string(params, "workspaceId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_result(state.agents_effective(workspace_id)).await)
+        }
+        "file_touch" => {
+            let request = match parse_file_read_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_touch(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                ))
+                .await,
+            )
+        }
+        "file_truncate" => {
+            let request = parse_request_or_err!(params, FileTruncateRequest);
+            Some(
+                serialize_result(state.file_truncate(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                    request.keep_last_bytes,
+                ))
+                .await,
+            )
+        }
+        "file_write_symlink" => {
+            let request = parse_request_or_err!(params, FileWriteSymlinkRequest);
+            Some(
+                serialize_ok(state.file_write_symlink(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                    request.target,
+                ))
+                .await,
+            )
+        }
+        "file_permissions_audit" => {
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            Some(serialize_result(state.file_permissions_audit(workspace_id)).await)
+        }
+        "file_fix_permissions" => {
+            let request = match parse_file_read_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_fix_permissions(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                ))
+                .await,
+            )
+        }
+        "file_link_info" => {
+            let request = match parse_file_read_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_link_info(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                ))
+                .await,
+            )
+        }
+        "file_writable" => {
+            let request = match parse_file_read_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_writable(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                ))
+                .await,
+            )
+        }
+        "file_read_capped" => {
+            let request = parse_request_or_err!(params, FileReadCappedRequest);
+            Some(
+                serialize_result(state.file_read_capped(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                    request.max_bytes,
+                ))
+                .await,
+            )
+        }
+        "file_read_lines_normalized" => {
+            let request = match parse_file_read_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(
+                serialize_result(state.file_read_lines_normalized(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                ))
+                .await,
+            )
+        }
+        "file_read_range" => {
+            let request = parse_request_or_err!(params, FileReadRangeRequest);
+            Some(
+                serialize_result(state.file_read_range(
+                    request.scope,
+                    request.kind,
+                    request.workspace_id,
+                    request.offset,
+                    request.length,
+                ))
+                .await,
+            )
+        }
+        "policy_explain" => {
+            let request = match parse_file_read_request(params) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_value(
+                state
+                    .policy_explain(request.scope, request.kind, request.workspace_id)
+                    .await,
+            ))
+        }
+        "policy_set_scope_override" => {
+            let request = parse_request_or_err!(params, ScopeOverrideRequest);
+            Some(
+                serialize_result(state.policy_set_scope_override(
+                    request.scope,
+                    request.workspace_id,
+                    request.path,
+                ))
+                .await,
+            )
+        }
+        "policy_clear_scope_override" => {
+            let request = parse_request_or_err!(params, ScopeRequest);
+            Some(
+                serialize_result(
+                    state.policy_clear_scope_override(request.scope, request.workspace_id),
+                )
+                .await,
+            )
+        }
+        "scope_watch" => {
+            let request = parse_request_or_err!(params, ScopeWatchRequest);
+            Some(serialize_result(state.scope_watch(request.scope, request.workspace_id)).await)
+        }
+        "scope_unwatch" => {
+            let watch_id = match parse_string(params, "watchId") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_value(state.scope_unwatch(watch_id).await))
+        }
+        "scope_watch_set_coalesce_window" => {
+            let window_ms = match parse_optional_u32(params, "windowMs") {
+                Some(value) => value,
+                None => return Some(Err("missing or invalid `windowMs`".to_string())),
+            };
+            Some(serialize_ok(state.scope_watch_set_coalesce_window(window_ms)).await)
+        }
+        "policy_rename_kind_file" => {
+            let request = parse_request_or_err!(params, PolicyRenameKindFileRequest);
+            Some(
+                serialize_result(state.policy_rename_kind_file(
+                    request.workspace_id,
+                    request.kind,
+                    request.old_name,
+                    request.new_name,
+                ))
+                .await,
+            )
+        }
+        "scope_write_test" => {
+            let request = parse_request_or_err!(params, ScopeWatchRequest);
+            Some(serialize_ok(state.scope_write_test(request.scope, request.workspace_id)).await)
+        }
+        "scope_disk_usage" => {
+            let workspace_id = parse_optional_string(params, "workspaceId");
+            let recursive = parse_optional_bool(params, "recursive").unwrap_or(false);
+            Some(serialize_result(state.scope_disk_usage(workspace_id, recursive)).await)
+        }
+        "disk_free" => {
+            let request = parse_request_or_err!(params, DiskFreeRequest);
+            Some(serialize_result(state.disk_free(request.path_kind)).await)
+        }
+        "workspace_export_zip" => {
+            let request = parse_request_or_err!(params, WorkspaceExportZipRequest);
+            Some(
+                serialize_result(state.workspace_export_zip(
+                    request.workspace_id,
+                    request.dest_path,
+                    request.operation_id,
+                ))
+                .await,
+            )
+        }
+        "workspace_import_zip" => {
+            let request = parse_request_or_err!(params, WorkspaceImportZipRequest);
+            Some(
+                serialize_result(state.workspace_import_zip(
+                    request.workspace_id,
+                    request.source_path,
+                    request.operation_id,
                 ))
                 .await,
             )
@@ -241,6 +968,41 @@ pub(super) async fn try_handle(
             };
             Some(serialize_result(state.update_app_settings(settings)).await)
         }
+        "feature_preset_save" => {
+            let name = match parse_string(params, "name") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_result(state.feature_preset_save(name)).await)
+        }
+        "feature_preset_list" => Some(serialize_value(state.feature_preset_list().await)),
+        "feature_preset_apply" => {
+            let name = match parse_string(params, "name") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let clear_missing = parse_optional_bool(params, "clearMissing").unwrap_or(false);
+            Some(serialize_result(state.feature_preset_apply(name, clear_missing)).await)
+        }
+        "feature_preset_apply_all" => {
+            let request =
+                parse_request_or_err!(params, workspace_rpc::FeaturePresetApplyAllRequest);
+            Some(
+                serialize_result(state.feature_preset_apply_all(
+                    request.name,
+                    request.workspace_ids,
+                    request.operation_id,
+                ))
+                .await,
+            )
+        }
+        "feature_preset_delete" => {
+            let name = match parse_string(params, "name") {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            Some(serialize_result(state.feature_preset_delete(name)).await)
+        }
         "apply_worktree_changes" => {
             let request = parse_request_or_err!(params, workspace_rpc::WorkspaceIdRequest);
             Some(serialize_ok(state.apply_worktree_changes(request.workspace_id)).await)
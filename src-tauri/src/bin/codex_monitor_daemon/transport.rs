@@ -1,6 +1,6 @@
 use super::rpc::{
     build_error_response, build_result_response, forward_events, parse_auth_token,
-    spawn_rpc_response_task,
+    spawn_rpc_response_task, stop_connection_watches, ConnectionWatches, RpcRequest,
 };
 use super::*;
 
@@ -28,6 +28,7 @@ pub(super) async fn handle_client(
     let mut authenticated = config.token.is_none();
     let mut events_task: Option<tokio::task::JoinHandle<()>> = None;
     let request_limiter = Arc::new(Semaphore::new(MAX_IN_FLIGHT_RPC_PER_CONNECTION));
+    let watches = Arc::new(std::sync::Mutex::new(ConnectionWatches::default()));
     let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
 
     if authenticated {
@@ -84,17 +85,26 @@ pub(super) async fn handle_client(
             continue;
         }
 
+        let request_id = message
+            .get("requestId")
+            .and_then(|value| value.as_str())
+            .map(str::to_string);
         spawn_rpc_response_task(
             Arc::clone(&state),
             out_tx.clone(),
-            id,
-            method,
-            params,
+            RpcRequest {
+                id,
+                request_id,
+                method,
+                params,
+            },
             client_version.clone(),
             Arc::clone(&request_limiter),
+            Arc::clone(&watches),
         );
     }
 
+    stop_connection_watches(&state, &watches).await;
     drop(out_tx);
     if let Some(task) = events_task {
         task.abort();
@@ -1,48 +1,493 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
+use serde::{Deserialize, Serialize};
+
+use crate::files::policy::{FileScope, ScopeRootOverrides};
+use crate::shared::config_duplicates_core::{self, ConfigParseError};
+use crate::shared::config_history_core::{self, HistoryEntry};
+use crate::shared::config_lock_core;
+use crate::shared::config_migrate_core::{self, MigrationStep};
+use crate::shared::config_patch_core::{self, ConfigWriter};
+use crate::shared::config_roundtrip_core::{self, RoundtripReport};
 use crate::shared::config_toml_core;
 
-pub(crate) fn read_steer_enabled() -> Result<Option<bool>, String> {
-    read_feature_flag("steer")
+/// Codex-owned values surfaced through app settings. `None` means the value is
+/// not set in `config.toml` (on read) or should be left untouched (on write).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexConfigValues {
+    pub(crate) collaboration_modes_enabled: Option<bool>,
+    pub(crate) steer_enabled: Option<bool>,
+    pub(crate) unified_exec_enabled: Option<bool>,
+    pub(crate) apps_enabled: Option<bool>,
+    pub(crate) personality: Option<String>,
 }
 
-pub(crate) fn read_collaboration_modes_enabled() -> Result<Option<bool>, String> {
-    read_feature_flag("collaboration_modes")
+/// The personality Codex uses when `config.toml` does not set a valid one.
+pub(crate) const DEFAULT_PERSONALITY: &str = "friendly";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PersonalitySource {
+    Configured,
+    Default,
 }
 
-pub(crate) fn read_unified_exec_enabled() -> Result<Option<bool>, String> {
-    read_feature_flag("unified_exec")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EffectivePersonality {
+    pub(crate) value: String,
+    pub(crate) source: PersonalitySource,
 }
 
-pub(crate) fn read_apps_enabled() -> Result<Option<bool>, String> {
-    read_feature_flag("apps")
+/// The `config.toml` the Codex CLI reads next to the one the app edits.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EffectiveConfigPath {
+    pub(crate) path: String,
+    /// `None` when the app cannot resolve a CODEX_HOME of its own.
+    pub(crate) app_path: Option<String>,
+    /// The two files differ, so changes made in the app will not reach Codex.
+    pub(crate) diverges: bool,
 }
 
-pub(crate) fn read_personality() -> Result<Option<String>, String> {
-    let Some(root) = resolve_default_codex_home() else {
-        return Ok(None);
+const TOOLS_TABLE: &str = "tools";
+/// Codex's defaults for the `[tools]` entries when they are not set.
+const DEFAULT_WEB_SEARCH: bool = false;
+const DEFAULT_VIEW_IMAGE: bool = true;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ToolSource {
+    Configured,
+    Default,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ToolSetting {
+    pub(crate) enabled: bool,
+    pub(crate) source: ToolSource,
+}
+
+/// The `[tools]` booleans. An unset or non-boolean entry reports Codex's
+/// default with `source: "default"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ToolsConfig {
+    pub(crate) web_search: ToolSetting,
+    pub(crate) view_image: ToolSetting,
+}
+
+/// Codex's defaults for top-level settings the app knows about. `model` has
+/// none: Codex picks one when it is unset.
+const DEFAULT_REASONING_EFFORT: &str = "medium";
+pub(crate) const DEFAULT_SANDBOX_MODE: &str = "read-only";
+pub(crate) const DEFAULT_APPROVAL_POLICY: &str = "on-request";
+
+/// Where a `config_with_defaults` value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SettingSource {
+    File,
+    Default,
+}
+
+/// One setting keyed by dotted `config.toml` path, with its effective value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResolvedSetting {
+    pub(crate) key: String,
+    pub(crate) value: serde_json::Value,
+    pub(crate) source: SettingSource,
+}
+
+/// A setting whose effective value differs between two profiles. `None`
+/// means the key is unset there and has no known default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigDelta {
+    pub(crate) key: String,
+    pub(crate) value_a: Option<serde_json::Value>,
+    pub(crate) value_b: Option<serde_json::Value>,
+}
+
+struct FeatureDefinition {
+    key: &'static str,
+    label: &'static str,
+    description: &'static str,
+    default_enabled: bool,
+}
+
+/// Top-level marker a config written for a newer schema carries.
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+const SUPPORTED_SCHEMA_VERSION: i64 = 1;
+/// `[features]` keys known to come from Codex releases whose config this app
+/// would rewrite lossily; none so far.
+const NEWER_FEATURE_KEYS: &[&str] = &[];
+
+/// Every `[features]` flag the app knows about. Flags mirrored in
+/// `CodexConfigValues` are mapped in `feature_slot`.
+const FEATURE_CATALOG: &[FeatureDefinition] = &[
+    FeatureDefinition {
+        key: "steer",
+        label: "Steer",
+        description: "Send follow-up messages to a running turn instead of queueing them.",
+        default_enabled: true,
+    },
+    FeatureDefinition {
+        key: "collaboration_modes",
+        label: "Collaboration modes",
+        description: "Switch between plan and code collaboration modes.",
+        default_enabled: true,
+    },
+    FeatureDefinition {
+        key: "unified_exec",
+        label: "Unified exec",
+        description: "Run shell commands through a single long-lived exec session.",
+        default_enabled: true,
+    },
+    FeatureDefinition {
+        key: "apps",
+        label: "Apps",
+        description: "Let Codex use connected apps.",
+        default_enabled: false,
+    },
+    FeatureDefinition {
+        key: "multi_agent",
+        label: "Multi-agent",
+        description: "Let Codex spawn and coordinate sub-agents.",
+        default_enabled: false,
+    },
+];
+
+/// A catalog feature with its current `config.toml` value (`None` when unset).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FeatureInfo {
+    pub(crate) key: String,
+    pub(crate) label: String,
+    pub(crate) description: String,
+    pub(crate) enabled: Option<bool>,
+    pub(crate) default_enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SchemaState {
+    Current,
+    /// Uses keys Codex has renamed; `config_migrate` can update them.
+    Older,
+    /// Declares a newer schema or uses a key known to need one, which a
+    /// rewrite could clobber. Keys that are merely unrecognized do not count.
+    Newer,
+}
+
+/// How `config.toml` compares to the schema this app understands, with the
+/// keys that led to that verdict. `unknown_keys` are informational; only
+/// `newer_keys` make the state `Newer`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SchemaStatus {
+    pub(crate) state: SchemaState,
+    pub(crate) unknown_keys: Vec<String>,
+    pub(crate) newer_keys: Vec<String>,
+    pub(crate) migratable_keys: Vec<String>,
+}
+
+/// One `[mcp_servers.<name>]` entry. `command` is `None` for servers reached
+/// by URL; `enabled` defaults to `true` when unset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpServerInfo {
+    pub(crate) name: String,
+    pub(crate) command: Option<String>,
+    pub(crate) args: Vec<String>,
+    pub(crate) enabled: bool,
+}
+
+pub(crate) fn features_catalog(
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<Vec<FeatureInfo>, String> {
+    let Some(root) = resolve_default_codex_home(scope_overrides) else {
+        return Ok(features_catalog_from_document(&toml_edit::Document::new()));
     };
     let (_, document) = config_toml_core::load_global_config_document(&root)?;
-    Ok(read_personality_from_document(&document))
+    Ok(features_catalog_from_document(&document))
+}
+
+/// Every known setting with its effective value, followed by any other keys
+/// set in `config.toml` as they are.
+pub(crate) fn config_with_defaults(
+    codex_home: Option<PathBuf>,
+) -> Result<Vec<ResolvedSetting>, String> {
+    let root = require_codex_home(codex_home)?;
+    let (_, document) = config_toml_core::load_global_config_document(&root)?;
+    Ok(config_with_defaults_from_document(&document))
+}
+
+/// The settings whose effective values differ between two `[profiles.*]`,
+/// each layered over the top-level settings and Codex's defaults.
+pub(crate) fn config_profile_diff(
+    profile_a: &str,
+    profile_b: &str,
+    codex_home: Option<PathBuf>,
+) -> Result<Vec<ConfigDelta>, String> {
+    let document = match codex_home.or_else(crate::codex::home::resolve_default_codex_home) {
+        Some(root) => config_toml_core::load_global_config_document(&root)?.1,
+        None => toml_edit::Document::new(),
+    };
+    config_profile_diff_from_document(&document, profile_a, profile_b)
+}
+
+pub(crate) fn config_profile_create(
+    name: &str,
+    base: Option<&str>,
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_profile_create_core(&root, name, base, writer)
 }
 
-pub(crate) fn write_steer_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("steer", enabled)
+pub(crate) fn config_profile_delete(
+    name: &str,
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<Option<String>, String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_profile_delete_core(&root, name, writer)
 }
 
-pub(crate) fn write_collaboration_modes_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("collaboration_modes", enabled)
+pub(crate) fn config_profile_rename(
+    old: &str,
+    new: &str,
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_profile_rename_core(&root, old, new, writer)
 }
 
-pub(crate) fn write_unified_exec_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("unified_exec", enabled)
+pub(crate) fn read_config_values(
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<CodexConfigValues, String> {
+    let Some(root) = resolve_default_codex_home(scope_overrides) else {
+        return Ok(CodexConfigValues::default());
+    };
+    let (_, document) = config_toml_core::load_global_config_document(&root)?;
+    Ok(read_config_values_from_document(&document))
 }
 
-pub(crate) fn write_apps_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("apps", enabled)
+/// Returns the misspelled keys (`Personality`) rewritten to their canonical
+/// lowercase name.
+pub(crate) fn write_config_values(
+    scope_overrides: &ScopeRootOverrides,
+    values: &CodexConfigValues,
+    writer: ConfigWriter<'_>,
+) -> Result<Vec<String>, String> {
+    let Some(root) = resolve_default_codex_home(scope_overrides) else {
+        return Ok(Vec::new());
+    };
+    let _lock = config_lock_core::lock_config_for_write(&root)?;
+    let (_, mut document) = config_toml_core::load_global_config_document(&root)?;
+    let canonicalized = apply_config_values_to_document(&mut document, values)?;
+    config_toml_core::persist_global_config_document(&root, &document, writer)?;
+    Ok(canonicalized)
 }
 
-pub(crate) fn write_feature_enabled(feature_key: &str, enabled: bool) -> Result<(), String> {
+pub(crate) fn merge_config(
+    changes: &HashMap<String, Option<config_toml_core::ConfigValue>>,
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_merge_core(&root, changes, writer)
+}
+
+pub(crate) fn config_transaction(
+    ops: &[config_toml_core::ConfigOp],
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_transaction_core(&root, ops, writer)
+}
+
+pub(crate) fn config_array_add(
+    key: &str,
+    value: &str,
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_array_add_core(&root, key, value, writer)
+}
+
+pub(crate) fn config_array_remove(
+    key: &str,
+    value: &str,
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_array_remove_core(&root, key, value, writer)
+}
+
+pub(crate) fn export_redacted_config(
+    codex_home: Option<PathBuf>,
+    extra_keys: &[String],
+) -> Result<String, String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_export_redacted_core(&root, extra_keys)
+}
+
+pub(crate) fn config_indent_info(
+    codex_home: Option<PathBuf>,
+) -> Result<config_toml_core::IndentInfo, String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_indent_info_core(&root)
+}
+
+pub(crate) fn reindent_config(
+    codex_home: Option<PathBuf>,
+    style: config_toml_core::IndentStyle,
+    writer: ConfigWriter<'_>,
+) -> Result<String, String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_reindent_core(&root, style, writer)
+}
+
+pub(crate) fn strip_config_comments(
+    codex_home: Option<PathBuf>,
+    write: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<String, String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::config_strip_comments_core(&root, write, writer)
+}
+
+pub(crate) fn config_load_tolerant(
+    codex_home: Option<PathBuf>,
+) -> Result<Option<ConfigParseError>, String> {
+    let root = require_codex_home(codex_home)?;
+    config_duplicates_core::config_load_tolerant_core(&root)
+}
+
+/// Skips `reject_newer_schema`: a file with duplicate keys cannot be parsed to
+/// check its schema, and the fix only deletes lines.
+pub(crate) fn fix_config_duplicate_keys(
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<usize, String> {
+    let root = require_codex_home(codex_home)?;
+    config_duplicates_core::config_fix_duplicate_keys_core(&root, writer)
+}
+
+pub(crate) fn tools_config_read(codex_home: Option<PathBuf>) -> Result<ToolsConfig, String> {
+    let root = require_codex_home(codex_home)?;
+    let (_, document) = config_toml_core::load_global_config_document(&root)?;
+    Ok(tools_config_from_document(&document))
+}
+
+/// Sets `[tools] <tool>`; `None` removes the key so Codex's default applies.
+/// Returns the resulting tools config.
+pub(crate) fn tools_config_set(
+    codex_home: Option<PathBuf>,
+    tool: &str,
+    enabled: Option<bool>,
+    writer: ConfigWriter<'_>,
+) -> Result<ToolsConfig, String> {
+    let key = normalize_tool_key(tool).ok_or_else(|| format!("unknown tool `{}`", tool.trim()))?;
+    let root = require_codex_home(codex_home)?;
+    let _lock = config_lock_core::lock_config_for_write(&root)?;
+    let (_, mut document) = config_toml_core::load_global_config_document(&root)?;
+    config_toml_core::set_table_flag(&mut document, TOOLS_TABLE, key, enabled)?;
+    config_toml_core::persist_global_config_document(&root, &document, writer)?;
+    Ok(tools_config_from_document(&document))
+}
+
+pub(crate) fn config_roundtrip_check(
+    codex_home: Option<PathBuf>,
+    proposed: &str,
+) -> Result<RoundtripReport, String> {
+    let root = require_codex_home(codex_home)?;
+    config_roundtrip_core::config_roundtrip_check_core(&root, proposed)
+}
+
+pub(crate) fn mcp_servers_list(codex_home: Option<PathBuf>) -> Result<Vec<McpServerInfo>, String> {
+    let root = require_codex_home(codex_home)?;
+    let (_, document) = config_toml_core::load_global_config_document(&root)?;
+    Ok(mcp_servers_from_document(&document))
+}
+
+pub(crate) fn mcp_server_set_enabled(
+    codex_home: Option<PathBuf>,
+    name: &str,
+    enabled: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let root = require_codex_home(codex_home)?;
+    let _lock = config_lock_core::lock_config_for_write(&root)?;
+    let (_, mut document) = config_toml_core::load_global_config_document(&root)?;
+    set_mcp_server_enabled_in_document(&mut document, name, enabled)?;
+    config_toml_core::persist_global_config_document(&root, &document, writer)
+}
+
+pub(crate) fn read_effective_personality(
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<EffectivePersonality, String> {
+    let Some(root) = resolve_default_codex_home(scope_overrides) else {
+        return Ok(effective_personality_from_document(
+            &toml_edit::Document::new(),
+        ));
+    };
+    let (_, document) = config_toml_core::load_global_config_document(&root)?;
+    Ok(effective_personality_from_document(&document))
+}
+
+pub(crate) fn read_reasoning_effort(
+    scope_overrides: &ScopeRootOverrides,
+    profile: Option<String>,
+) -> Result<Option<String>, String> {
+    let Some(root) = resolve_default_codex_home(scope_overrides) else {
+        return Ok(None);
+    };
+    let (_, document) = config_toml_core::load_global_config_document(&root)?;
+    Ok(read_reasoning_effort_from_document(
+        &document,
+        normalize_profile_name(profile.as_deref()),
+    ))
+}
+
+/// Writes `model_reasoning_effort` at the top level or under
+/// `[profiles.<profile>]`; `None` removes the key.
+pub(crate) fn write_reasoning_effort(
+    scope_overrides: &ScopeRootOverrides,
+    profile: Option<String>,
+    effort: Option<String>,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let Some(root) = resolve_default_codex_home(scope_overrides) else {
+        return Ok(());
+    };
+    let _lock = config_lock_core::lock_config_for_write(&root)?;
+    let (_, mut document) = config_toml_core::load_global_config_document(&root)?;
+    apply_reasoning_effort_to_document(
+        &mut document,
+        normalize_profile_name(profile.as_deref()),
+        effort.as_deref(),
+    )?;
+    config_toml_core::persist_global_config_document(&root, &document, writer)
+}
+
+pub(crate) fn write_feature_enabled(
+    scope_overrides: &ScopeRootOverrides,
+    feature_key: &str,
+    enabled: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
     let key = feature_key.trim();
     if key.is_empty() {
         return Err("feature key is empty".to_string());
@@ -50,61 +495,647 @@ pub(crate) fn write_feature_enabled(feature_key: &str, enabled: bool) -> Result<
     if key.eq_ignore_ascii_case("collab") {
         return Err("feature key `collab` is no longer supported; use `multi_agent`".to_string());
     }
-    write_feature_flag(key, enabled)
+    write_feature_flag(scope_overrides, key, enabled, writer)
 }
 
-pub(crate) fn write_personality(personality: &str) -> Result<(), String> {
-    let Some(root) = resolve_default_codex_home() else {
+fn write_feature_flag(
+    scope_overrides: &ScopeRootOverrides,
+    key: &str,
+    enabled: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let Some(root) = resolve_default_codex_home(scope_overrides) else {
         return Ok(());
     };
+    let _lock = config_lock_core::lock_config_for_write(&root)?;
     let (_, mut document) = config_toml_core::load_global_config_document(&root)?;
-    let normalized = normalize_personality_value(personality);
-    config_toml_core::set_top_level_string(&mut document, "personality", normalized);
-    config_toml_core::persist_global_config_document(&root, &document)
+    config_toml_core::set_feature_flag(&mut document, key, enabled)?;
+    config_toml_core::persist_global_config_document(&root, &document, writer)
 }
 
-fn read_feature_flag(key: &str) -> Result<Option<bool>, String> {
-    let Some(root) = resolve_default_codex_home() else {
-        return Ok(None);
-    };
+/// Every boolean `[features]` entry, including flags outside the catalog.
+pub(crate) fn read_feature_flags(
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<BTreeMap<String, bool>, String> {
+    let root = require_default_codex_home(scope_overrides)?;
     let (_, document) = config_toml_core::load_global_config_document(&root)?;
-    Ok(config_toml_core::read_feature_flag(&document, key))
+    Ok(feature_flags_from_document(&document))
 }
 
-fn write_feature_flag(key: &str, enabled: bool) -> Result<(), String> {
-    let Some(root) = resolve_default_codex_home() else {
-        return Ok(());
-    };
+/// Sets `flags` in one write. With `clear_missing`, flags not in `flags` are
+/// removed so they fall back to Codex defaults; otherwise they are left as is.
+pub(crate) fn write_feature_flags(
+    scope_overrides: &ScopeRootOverrides,
+    flags: &BTreeMap<String, bool>,
+    clear_missing: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let root = require_default_codex_home(scope_overrides)?;
+    let _lock = config_lock_core::lock_config_for_write(&root)?;
     let (_, mut document) = config_toml_core::load_global_config_document(&root)?;
-    config_toml_core::set_feature_flag(&mut document, key, enabled)?;
-    config_toml_core::persist_global_config_document(&root, &document)
+    apply_feature_flags_to_document(&mut document, flags, clear_missing)?;
+    config_toml_core::persist_global_config_document(&root, &document, writer)
+}
+
+pub(crate) fn undo_config_write(
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let Some(root) = codex_home.or_else(crate::codex::home::resolve_default_codex_home) else {
+        return Ok(false);
+    };
+    config_history_core::config_undo_core(&root, writer)
 }
 
-pub(crate) fn config_toml_path() -> Option<PathBuf> {
-    resolve_default_codex_home().map(|home| home.join("config.toml"))
+pub(crate) fn redo_config_write(
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let Some(root) = codex_home.or_else(crate::codex::home::resolve_default_codex_home) else {
+        return Ok(false);
+    };
+    config_history_core::config_redo_core(&root, writer)
 }
 
-pub(crate) fn read_config_model(codex_home: Option<PathBuf>) -> Result<Option<String>, String> {
-    let root = codex_home.or_else(resolve_default_codex_home);
-    let Some(root) = root else {
-        return Err("Unable to resolve CODEX_HOME".to_string());
+pub(crate) fn read_config_history(codex_home: Option<PathBuf>) -> Vec<HistoryEntry> {
+    codex_home
+        .or_else(crate::codex::home::resolve_default_codex_home)
+        .map(|root| config_history_core::config_history_core(&root))
+        .unwrap_or_default()
+}
+
+pub(crate) fn format_config(
+    sort_keys: bool,
+    dry_run: bool,
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<String, String> {
+    let root = require_codex_home(codex_home)?;
+    config_toml_core::format_global_config(&root, sort_keys, dry_run, writer)
+}
+
+pub(crate) fn preview_config_migration(
+    codex_home: Option<PathBuf>,
+) -> Result<Vec<MigrationStep>, String> {
+    let Some(root) = codex_home.or_else(crate::codex::home::resolve_default_codex_home) else {
+        return Ok(Vec::new());
+    };
+    config_migrate_core::config_migrate_preview_core(&root)
+}
+
+pub(crate) fn migrate_config(
+    codex_home: Option<PathBuf>,
+    writer: ConfigWriter<'_>,
+) -> Result<Vec<MigrationStep>, String> {
+    let Some(root) = codex_home.or_else(crate::codex::home::resolve_default_codex_home) else {
+        return Ok(Vec::new());
+    };
+    config_migrate_core::config_migrate_core(&root, writer)
+}
+
+pub(crate) fn read_effective_config(
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<config_toml_core::EffectiveConfig, String> {
+    let root = require_default_codex_home(scope_overrides)?;
+    config_toml_core::read_effective_global_config(&root)
+}
+
+pub(crate) fn config_toml_path(scope_overrides: &ScopeRootOverrides) -> Option<PathBuf> {
+    resolve_default_codex_home(scope_overrides).map(|home| home.join("config.toml"))
+}
+
+/// The `config.toml` the Codex CLI will read, and whether it is not the file
+/// the app edits, e.g. for a `CODEX_HOME` written with `~` or a global scope
+/// root override.
+pub(crate) fn codex_effective_config_path(
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<EffectiveConfigPath, String> {
+    let codex_home = crate::codex::home::resolve_codex_cli_home()?;
+    let app_home = resolve_default_codex_home(scope_overrides);
+    let diverges = match app_home.as_deref() {
+        Some(app_home) => !same_dir(&codex_home, app_home),
+        None => true,
     };
+    let config_path = |home: &Path| home.join("config.toml").to_string_lossy().to_string();
+    Ok(EffectiveConfigPath {
+        path: config_path(&codex_home),
+        app_path: app_home.as_deref().map(config_path),
+        diverges,
+    })
+}
+
+fn same_dir(left: &Path, right: &Path) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    canonical(left) == canonical(right)
+}
+
+pub(crate) fn read_config_model(codex_home: Option<PathBuf>) -> Result<Option<String>, String> {
+    let root = require_codex_home(codex_home)?;
+    let (_, document) = config_toml_core::load_global_config_document(&root)?;
+    Ok(config_toml_core::read_top_level_string_ignore_case(
+        &document, "model",
+    ))
+}
+
+pub(crate) fn config_schema_check(codex_home: Option<PathBuf>) -> Result<SchemaStatus, String> {
+    let root = require_codex_home(codex_home)?;
     let (_, document) = config_toml_core::load_global_config_document(&root)?;
-    Ok(config_toml_core::read_top_level_string(&document, "model"))
+    Ok(schema_status_from_document(&document))
+}
+
+/// `config.toml`'s mtime in Unix millis, or `None` when it does not exist.
+pub(crate) fn config_modified_ms(codex_home: Option<PathBuf>) -> Result<Option<u64>, String> {
+    let root = require_codex_home(codex_home)?;
+    let metadata = match std::fs::metadata(root.join("config.toml")) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("Failed to stat config.toml: {err}")),
+    };
+    let modified = metadata
+        .modified()
+        .map_err(|err| format!("Failed to read config.toml mtime: {err}"))?;
+    let millis = modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0);
+    Ok(Some(millis))
+}
+
+/// Rewrites refuse to run on a config newer than the app knows; callers hold
+/// the config lock.
+pub(crate) fn reject_newer_schema(codex_home: &Path) -> Result<(), String> {
+    let (_, document) = config_toml_core::load_global_config_document(codex_home)?;
+    let status = schema_status_from_document(&document);
+    if status.state == SchemaState::Newer {
+        return Err(format!(
+            "config.toml uses settings from a newer schema ({}); update CodexMonitor before rewriting it",
+            status.newer_keys.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// The home config commands without a `codex_home` parameter use: the
+/// `global` scope root override when set, like scoped-file requests, otherwise
+/// the default.
+fn resolve_default_codex_home(scope_overrides: &ScopeRootOverrides) -> Option<PathBuf> {
+    scope_overrides
+        .root(FileScope::Global, None)
+        .or_else(crate::codex::home::resolve_default_codex_home)
+}
+
+fn require_default_codex_home(scope_overrides: &ScopeRootOverrides) -> Result<PathBuf, String> {
+    resolve_default_codex_home(scope_overrides)
+        .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
+}
+
+/// `codex_home` when the caller passed one, otherwise the default home.
+/// Commands resolve it through `resolve_config_codex_home_core`, which already
+/// applies any scope root override.
+fn require_codex_home(codex_home: Option<PathBuf>) -> Result<PathBuf, String> {
+    codex_home
+        .or_else(crate::codex::home::resolve_default_codex_home)
+        .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
+}
+
+/// `(key, default)` for every flag in `FEATURE_CATALOG`, in catalog order.
+pub(crate) fn feature_catalog_defaults() -> impl Iterator<Item = (&'static str, bool)> {
+    FEATURE_CATALOG
+        .iter()
+        .map(|feature| (feature.key, feature.default_enabled))
+}
+
+pub(crate) fn is_catalog_feature(key: &str) -> bool {
+    FEATURE_CATALOG.iter().any(|feature| feature.key == key)
+}
+
+fn features_catalog_from_document(document: &toml_edit::Document) -> Vec<FeatureInfo> {
+    FEATURE_CATALOG
+        .iter()
+        .map(|feature| FeatureInfo {
+            key: feature.key.to_string(),
+            label: feature.label.to_string(),
+            description: feature.description.to_string(),
+            enabled: config_toml_core::read_feature_flag(document, feature.key),
+            default_enabled: feature.default_enabled,
+        })
+        .collect()
+}
+
+fn schema_status_from_document(document: &toml_edit::Document) -> SchemaStatus {
+    let migratable_keys: Vec<String> = config_migrate_core::plan_migration(document)
+        .into_iter()
+        .map(|step| step.from_key)
+        .collect();
+    let unknown_keys: Vec<String> = document
+        .get("features")
+        .and_then(toml_edit::Item::as_table_like)
+        .map(|features| {
+            features
+                .iter()
+                .map(|(key, _)| key)
+                .filter(|key| !FEATURE_CATALOG.iter().any(|feature| feature.key == *key))
+                .map(|key| format!("features.{key}"))
+                .filter(|path| !migratable_keys.contains(path))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut newer_keys: Vec<String> = unknown_keys
+        .iter()
+        .filter(|path| {
+            NEWER_FEATURE_KEYS
+                .iter()
+                .any(|key| path.strip_prefix("features.") == Some(*key))
+        })
+        .cloned()
+        .collect();
+    let declared_version = document
+        .get(SCHEMA_VERSION_KEY)
+        .and_then(toml_edit::Item::as_integer);
+    if declared_version.is_some_and(|version| version > SUPPORTED_SCHEMA_VERSION) {
+        newer_keys.insert(0, SCHEMA_VERSION_KEY.to_string());
+    }
+    let state = if !newer_keys.is_empty() {
+        SchemaState::Newer
+    } else if !migratable_keys.is_empty() {
+        SchemaState::Older
+    } else {
+        SchemaState::Current
+    };
+    SchemaStatus {
+        state,
+        unknown_keys,
+        newer_keys,
+        migratable_keys,
+    }
+}
+
+fn mcp_servers_from_document(document: &toml_edit::Document) -> Vec<McpServerInfo> {
+    config_toml_core::child_table_names(document, "mcp_servers")
+        .into_iter()
+        .filter_map(|name| {
+            let table = config_toml_core::child_table(document, "mcp_servers", &name)?;
+            let command = table
+                .get("command")
+                .and_then(toml_edit::Item::as_str)
+                .map(str::to_string);
+            let args = table
+                .get("args")
+                .and_then(toml_edit::Item::as_array)
+                .map(|args| {
+                    args.iter()
+                        .filter_map(|arg| arg.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let enabled = table
+                .get("enabled")
+                .and_then(toml_edit::Item::as_bool)
+                .unwrap_or(true);
+            Some(McpServerInfo {
+                name,
+                command,
+                args,
+                enabled,
+            })
+        })
+        .collect()
+}
+
+fn set_mcp_server_enabled_in_document(
+    document: &mut toml_edit::Document,
+    name: &str,
+    enabled: bool,
+) -> Result<(), String> {
+    let table = config_toml_core::child_table_mut(document, "mcp_servers", name)
+        .ok_or_else(|| format!("MCP server `{name}` is not configured in config.toml"))?;
+    table.insert("enabled", toml_edit::value(enabled));
+    Ok(())
+}
+
+fn feature_flags_from_document(document: &toml_edit::Document) -> BTreeMap<String, bool> {
+    document
+        .get("features")
+        .and_then(toml_edit::Item::as_table_like)
+        .map(|features| {
+            features
+                .iter()
+                .filter_map(|(key, item)| Some((key.to_string(), item.as_bool()?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn apply_feature_flags_to_document(
+    document: &mut toml_edit::Document,
+    flags: &BTreeMap<String, bool>,
+    clear_missing: bool,
+) -> Result<(), String> {
+    if clear_missing {
+        if let Some(features) = document
+            .get_mut("features")
+            .and_then(toml_edit::Item::as_table_like_mut)
+        {
+            let stale: Vec<String> = features
+                .iter()
+                .filter(|(key, item)| item.is_bool() && !flags.contains_key(*key))
+                .map(|(key, _)| key.to_string())
+                .collect();
+            for key in stale {
+                features.remove(&key);
+            }
+        }
+    }
+    for (key, enabled) in flags {
+        config_toml_core::set_feature_flag(document, key, *enabled)?;
+    }
+    Ok(())
+}
+
+fn feature_slot<'a>(values: &'a mut CodexConfigValues, key: &str) -> Option<&'a mut Option<bool>> {
+    match key {
+        "collaboration_modes" => Some(&mut values.collaboration_modes_enabled),
+        "steer" => Some(&mut values.steer_enabled),
+        "unified_exec" => Some(&mut values.unified_exec_enabled),
+        "apps" => Some(&mut values.apps_enabled),
+        _ => None,
+    }
+}
+
+fn read_config_values_from_document(document: &toml_edit::Document) -> CodexConfigValues {
+    let mut values = CodexConfigValues {
+        personality: read_personality_from_document(document),
+        ..CodexConfigValues::default()
+    };
+    for feature in FEATURE_CATALOG {
+        if let Some(slot) = feature_slot(&mut values, feature.key) {
+            *slot = config_toml_core::read_feature_flag(document, feature.key);
+        }
+    }
+    values
 }
 
-fn resolve_default_codex_home() -> Option<PathBuf> {
-    crate::codex::home::resolve_default_codex_home()
+/// The app-managed settings in `values` keyed by dotted `config.toml` path
+/// (`features.steer`, `personality`), with Codex's default for unset ones.
+pub(crate) fn managed_config_settings(
+    values: &CodexConfigValues,
+) -> Vec<(String, serde_json::Value)> {
+    let mut values = values.clone();
+    let mut settings = Vec::new();
+    for feature in FEATURE_CATALOG {
+        if let Some(slot) = feature_slot(&mut values, feature.key) {
+            settings.push((
+                format!("features.{}", feature.key),
+                serde_json::Value::Bool(slot.unwrap_or(feature.default_enabled)),
+            ));
+        }
+    }
+    let personality = values
+        .personality
+        .as_deref()
+        .and_then(normalize_personality_value)
+        .unwrap_or(DEFAULT_PERSONALITY);
+    settings.push((
+        "personality".to_string(),
+        serde_json::Value::String(personality.to_string()),
+    ));
+    settings
 }
 
-fn read_personality_from_document(document: &toml_edit::Document) -> Option<String> {
-    config_toml_core::read_top_level_string(document, "personality")
+fn apply_config_values_to_document(
+    document: &mut toml_edit::Document,
+    values: &CodexConfigValues,
+) -> Result<Vec<String>, String> {
+    let mut values = values.clone();
+    let mut canonicalized = Vec::new();
+    for feature in FEATURE_CATALOG {
+        if let Some(Some(enabled)) = feature_slot(&mut values, feature.key).copied() {
+            config_toml_core::set_feature_flag(document, feature.key, enabled)?;
+        }
+    }
+    if let Some(personality) = values.personality.as_deref() {
+        let normalized = normalize_personality_value(personality);
+        canonicalized.extend(config_toml_core::set_top_level_string_canonical(
+            document,
+            "personality",
+            normalized,
+        ));
+    }
+    Ok(canonicalized)
+}
+
+pub(crate) fn read_personality_from_document(document: &toml_edit::Document) -> Option<String> {
+    config_toml_core::read_top_level_string_ignore_case(document, "personality")
         .as_deref()
         .and_then(normalize_personality_value)
         .map(|value| value.to_string())
 }
 
-fn normalize_personality_value(value: &str) -> Option<&'static str> {
+fn effective_personality_from_document(document: &toml_edit::Document) -> EffectivePersonality {
+    match read_personality_from_document(document) {
+        Some(value) => EffectivePersonality {
+            value,
+            source: PersonalitySource::Configured,
+        },
+        None => EffectivePersonality {
+            value: DEFAULT_PERSONALITY.to_string(),
+            source: PersonalitySource::Default,
+        },
+    }
+}
+
+/// The one table of Codex defaults, in display order. The values follow the
+/// Codex CLI source (openai/codex): `codex-rs/core/src/config/mod.rs` for the
+/// top-level and `[tools]` settings, `codex-rs/protocol/src/config_types.rs`
+/// for the enum defaults and `codex-rs/core/src/features.rs` for the flags in
+/// `FEATURE_CATALOG`. Recheck them when Codex changes a default.
+fn known_setting_defaults() -> Vec<(String, serde_json::Value)> {
+    let mut defaults = vec![
+        ("model".to_string(), serde_json::Value::Null),
+        (
+            "personality".to_string(),
+            serde_json::Value::from(DEFAULT_PERSONALITY),
+        ),
+        (
+            "model_reasoning_effort".to_string(),
+            serde_json::Value::from(DEFAULT_REASONING_EFFORT),
+        ),
+        (
+            "sandbox_mode".to_string(),
+            serde_json::Value::from(DEFAULT_SANDBOX_MODE),
+        ),
+        (
+            "approval_policy".to_string(),
+            serde_json::Value::from(DEFAULT_APPROVAL_POLICY),
+        ),
+    ];
+    defaults.extend(FEATURE_CATALOG.iter().map(|feature| {
+        (
+            format!("features.{}", feature.key),
+            serde_json::Value::Bool(feature.default_enabled),
+        )
+    }));
+    defaults.push((
+        format!("{TOOLS_TABLE}.web_search"),
+        serde_json::Value::Bool(DEFAULT_WEB_SEARCH),
+    ));
+    defaults.push((
+        format!("{TOOLS_TABLE}.view_image"),
+        serde_json::Value::Bool(DEFAULT_VIEW_IMAGE),
+    ));
+    defaults
+}
+
+/// A file value for a known setting, or `None` when it is not one Codex would
+/// accept and the default applies instead.
+fn known_setting_value(key: &str, value: serde_json::Value) -> Option<serde_json::Value> {
+    if key.starts_with("features.") || key.starts_with(&format!("{TOOLS_TABLE}.")) {
+        return value.is_boolean().then_some(value);
+    }
+    let text = value.as_str()?.trim();
+    let normalized = match key {
+        "personality" => normalize_personality_value(text)?,
+        "model_reasoning_effort" => normalize_reasoning_effort(text)?,
+        _ if text.is_empty() => return None,
+        _ => text,
+    };
+    Some(serde_json::Value::from(normalized))
+}
+
+fn config_with_defaults_from_document(document: &toml_edit::Document) -> Vec<ResolvedSetting> {
+    resolve_with_defaults(config_patch_core::table_leaves(document.as_table()))
+}
+
+/// Known settings resolved against `file` (dotted key, value) pairs, then the
+/// remaining pairs as they are.
+fn resolve_with_defaults(mut file: Vec<(String, serde_json::Value)>) -> Vec<ResolvedSetting> {
+    let mut settings: Vec<ResolvedSetting> = known_setting_defaults()
+        .into_iter()
+        .map(|(key, default)| {
+            // Top-level keys are matched ignoring case, like the other readers.
+            let position = file
+                .iter()
+                .position(|(file_key, _)| file_key == &key)
+                .or_else(|| {
+                    file.iter().position(|(file_key, _)| {
+                        !key.contains('.') && file_key.eq_ignore_ascii_case(&key)
+                    })
+                });
+            let value = position
+                .map(|index| file.remove(index).1)
+                .and_then(|value| known_setting_value(&key, value));
+            match value {
+                Some(value) => ResolvedSetting {
+                    key,
+                    value,
+                    source: SettingSource::File,
+                },
+                None => ResolvedSetting {
+                    key,
+                    value: default,
+                    source: SettingSource::Default,
+                },
+            }
+        })
+        .collect();
+    settings.extend(file.into_iter().map(|(key, value)| ResolvedSetting {
+        key,
+        value,
+        source: SettingSource::File,
+    }));
+    settings
+}
+
+/// The top-level leaves, minus `[profiles]`, with `profile`'s leaves in
+/// place of any they set.
+fn profile_leaves(
+    document: &toml_edit::Document,
+    profile: &str,
+) -> Result<Vec<(String, serde_json::Value)>, String> {
+    let mut root = config_patch_core::table_to_json(document.as_table());
+    let profiles = root
+        .as_object_mut()
+        .and_then(|root| root.remove("profiles"))
+        .unwrap_or_default();
+    let overlay = profiles
+        .get(profile)
+        .filter(|table| table.is_object())
+        .ok_or_else(|| format!("Profile \"{profile}\" is not defined in config.toml"))?;
+    let overlay = config_patch_core::json_leaves(overlay);
+    let mut leaves: Vec<(String, serde_json::Value)> = config_patch_core::json_leaves(&root)
+        .into_iter()
+        .filter(|(key, _)| overlay.iter().all(|(profile_key, _)| profile_key != key))
+        .collect();
+    leaves.extend(overlay);
+    Ok(leaves)
+}
+
+fn config_profile_diff_from_document(
+    document: &toml_edit::Document,
+    profile_a: &str,
+    profile_b: &str,
+) -> Result<Vec<ConfigDelta>, String> {
+    let settings_a = resolve_with_defaults(profile_leaves(document, profile_a)?);
+    let settings_b = resolve_with_defaults(profile_leaves(document, profile_b)?);
+    let mut keys: Vec<&str> = settings_a
+        .iter()
+        .map(|setting| setting.key.as_str())
+        .collect();
+    for setting in &settings_b {
+        if !keys.contains(&setting.key.as_str()) {
+            keys.push(&setting.key);
+        }
+    }
+    let value_for = |settings: &[ResolvedSetting], key: &str| {
+        settings
+            .iter()
+            .find(|setting| setting.key == key)
+            .map(|setting| setting.value.clone())
+            .filter(|value| !value.is_null())
+    };
+    Ok(keys
+        .into_iter()
+        .filter_map(|key| {
+            let value_a = value_for(&settings_a, key);
+            let value_b = value_for(&settings_b, key);
+            (value_a != value_b).then(|| ConfigDelta {
+                key: key.to_string(),
+                value_a,
+                value_b,
+            })
+        })
+        .collect())
+}
+
+fn tools_config_from_document(document: &toml_edit::Document) -> ToolsConfig {
+    ToolsConfig {
+        web_search: tool_setting(document, "web_search", DEFAULT_WEB_SEARCH),
+        view_image: tool_setting(document, "view_image", DEFAULT_VIEW_IMAGE),
+    }
+}
+
+fn tool_setting(document: &toml_edit::Document, key: &str, default: bool) -> ToolSetting {
+    match config_toml_core::read_table_flag(document, TOOLS_TABLE, key) {
+        Some(enabled) => ToolSetting {
+            enabled,
+            source: ToolSource::Configured,
+        },
+        None => ToolSetting {
+            enabled: default,
+            source: ToolSource::Default,
+        },
+    }
+}
+
+fn normalize_tool_key(tool: &str) -> Option<&'static str> {
+    match tool.trim() {
+        "web_search" => Some("web_search"),
+        "view_image" => Some("view_image"),
+        _ => None,
+    }
+}
+
+pub(crate) fn normalize_personality_value(value: &str) -> Option<&'static str> {
     match value.trim().to_ascii_lowercase().as_str() {
         "friendly" => Some("friendly"),
         "pragmatic" => Some("pragmatic"),
@@ -112,10 +1143,78 @@ fn normalize_personality_value(value: &str) -> Option<&'static str> {
     }
 }
 
+pub(crate) fn normalize_sandbox_mode_value(value: &str) -> Option<&'static str> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "read-only" => Some("read-only"),
+        "workspace-write" => Some("workspace-write"),
+        "danger-full-access" => Some("danger-full-access"),
+        _ => None,
+    }
+}
+
+pub(crate) fn normalize_approval_policy_value(value: &str) -> Option<&'static str> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "untrusted" => Some("untrusted"),
+        "on-failure" => Some("on-failure"),
+        "on-request" => Some("on-request"),
+        "never" => Some("never"),
+        _ => None,
+    }
+}
+
+fn normalize_profile_name(profile: Option<&str>) -> Option<&str> {
+    profile.map(str::trim).filter(|profile| !profile.is_empty())
+}
+
+fn read_reasoning_effort_from_document(
+    document: &toml_edit::Document,
+    profile: Option<&str>,
+) -> Option<String> {
+    config_toml_core::read_profile_string(document, profile, "model_reasoning_effort")
+        .as_deref()
+        .and_then(normalize_reasoning_effort)
+        .map(|value| value.to_string())
+}
+
+fn apply_reasoning_effort_to_document(
+    document: &mut toml_edit::Document,
+    profile: Option<&str>,
+    effort: Option<&str>,
+) -> Result<(), String> {
+    let normalized = match effort.map(str::trim).filter(|effort| !effort.is_empty()) {
+        Some(effort) => Some(normalize_reasoning_effort(effort).ok_or_else(|| {
+            format!("Unknown reasoning effort `{effort}`; expected minimal, low, medium, or high")
+        })?),
+        None => None,
+    };
+    config_toml_core::set_profile_string(document, profile, "model_reasoning_effort", normalized)
+}
+
+fn normalize_reasoning_effort(value: &str) -> Option<&'static str> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "minimal" => Some("minimal"),
+        "low" => Some("low"),
+        "medium" => Some("medium"),
+        "high" => Some("high"),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{normalize_personality_value, read_personality_from_document};
+    use super::{
+        apply_config_values_to_document, apply_feature_flags_to_document,
+        apply_reasoning_effort_to_document, config_modified_ms, config_profile_diff_from_document,
+        config_with_defaults_from_document, effective_personality_from_document,
+        feature_flags_from_document, features_catalog_from_document, mcp_servers_from_document,
+        normalize_personality_value, normalize_tool_key, read_config_values_from_document,
+        read_personality_from_document, read_reasoning_effort_from_document,
+        schema_status_from_document, set_mcp_server_enabled_in_document,
+        tools_config_from_document, CodexConfigValues, PersonalitySource, SchemaState,
+        SettingSource, ToolSource,
+    };
     use crate::shared::config_toml_core;
+    use std::collections::BTreeMap;
 
     #[test]
     fn parse_personality_reads_supported_values() {
@@ -137,10 +1236,373 @@ mod tests {
         assert_eq!(read_personality_from_document(&unknown), None);
     }
 
+    #[test]
+    fn effective_personality_falls_back_to_codex_default() {
+        let configured =
+            config_toml_core::parse_document("personality = \"pragmatic\"\n").expect("parse");
+        let unknown =
+            config_toml_core::parse_document("personality = \"grumpy\"\n").expect("parse");
+        let missing = config_toml_core::parse_document("").expect("parse");
+
+        let effective = effective_personality_from_document(&configured);
+        assert_eq!(effective.value, "pragmatic");
+        assert_eq!(effective.source, PersonalitySource::Configured);
+        for document in [unknown, missing] {
+            let effective = effective_personality_from_document(&document);
+            assert_eq!(effective.value, "friendly");
+            assert_eq!(effective.source, PersonalitySource::Default);
+        }
+    }
+
+    #[test]
+    fn tools_config_reports_defaults_and_toggles_only_the_tool_key() {
+        let mut document = config_toml_core::parse_document(
+            "model = \"gpt-5\" # primary\n\n[tools]\nview_image = false\nother = \"x\"\n",
+        )
+        .expect("parse");
+        let tools = tools_config_from_document(&document);
+        assert!(!tools.view_image.enabled);
+        assert_eq!(tools.view_image.source, ToolSource::Configured);
+        assert!(!tools.web_search.enabled);
+        assert_eq!(tools.web_search.source, ToolSource::Default);
+
+        config_toml_core::set_table_flag(&mut document, "tools", "web_search", Some(true))
+            .expect("set");
+        config_toml_core::set_table_flag(&mut document, "tools", "view_image", None)
+            .expect("remove");
+        let tools = tools_config_from_document(&document);
+        assert_eq!(tools.web_search.source, ToolSource::Configured);
+        assert!(tools.view_image.enabled);
+        assert_eq!(tools.view_image.source, ToolSource::Default);
+        let text = document.to_string();
+        assert!(text.contains("model = \"gpt-5\" # primary"));
+        assert!(text.contains("other = \"x\""));
+
+        let mut inline =
+            config_toml_core::parse_document("tools = { view_image = true }\n").expect("parse");
+        config_toml_core::set_table_flag(&mut inline, "tools", "web_search", Some(true))
+            .expect("set inline");
+        assert_eq!(
+            tools_config_from_document(&inline).web_search.source,
+            ToolSource::Configured
+        );
+        assert_eq!(normalize_tool_key(" view_image "), Some("view_image"));
+        assert_eq!(normalize_tool_key("shell"), None);
+    }
+
     #[test]
     fn normalize_personality_is_case_insensitive() {
         assert_eq!(normalize_personality_value("Friendly"), Some("friendly"));
         assert_eq!(normalize_personality_value("PRAGMATIC"), Some("pragmatic"));
         assert_eq!(normalize_personality_value("unknown"), None);
     }
+
+    #[test]
+    fn config_values_round_trip_through_document() {
+        let mut document =
+            config_toml_core::parse_document("model = \"gpt-5\"\n[features]\nsteer = false\n")
+                .expect("parse");
+        let values = CodexConfigValues {
+            collaboration_modes_enabled: Some(true),
+            steer_enabled: None,
+            unified_exec_enabled: Some(false),
+            apps_enabled: None,
+            personality: Some("Pragmatic".to_string()),
+        };
+
+        apply_config_values_to_document(&mut document, &values).expect("apply");
+        let read_back = read_config_values_from_document(&document);
+
+        assert_eq!(read_back.collaboration_modes_enabled, Some(true));
+        assert_eq!(read_back.steer_enabled, Some(false));
+        assert_eq!(read_back.unified_exec_enabled, Some(false));
+        assert_eq!(read_back.apps_enabled, None);
+        assert_eq!(read_back.personality, Some("pragmatic".to_string()));
+        assert_eq!(
+            config_toml_core::read_top_level_string(&document, "model"),
+            Some("gpt-5".to_string())
+        );
+    }
+
+    #[test]
+    fn mixed_case_personality_is_read_and_canonicalized_on_write() {
+        let mut document =
+            config_toml_core::parse_document("Personality = \"Pragmatic\"\n").expect("parse");
+        assert_eq!(
+            read_personality_from_document(&document),
+            Some("pragmatic".to_string())
+        );
+
+        let values = CodexConfigValues {
+            personality: Some("friendly".to_string()),
+            ..CodexConfigValues::default()
+        };
+        let canonicalized = apply_config_values_to_document(&mut document, &values).expect("apply");
+        assert_eq!(canonicalized, vec!["Personality".to_string()]);
+        assert_eq!(document.to_string(), "personality = \"friendly\"\n");
+    }
+
+    #[test]
+    fn features_catalog_reports_configured_and_default_values() {
+        let document =
+            config_toml_core::parse_document("[features]\nsteer = false\nmulti_agent = true\n")
+                .expect("parse");
+        let catalog = features_catalog_from_document(&document);
+        let steer = catalog
+            .iter()
+            .find(|feature| feature.key == "steer")
+            .expect("steer");
+        assert_eq!(steer.enabled, Some(false));
+        assert!(steer.default_enabled);
+        let multi_agent = catalog
+            .iter()
+            .find(|feature| feature.key == "multi_agent")
+            .expect("multi_agent");
+        assert_eq!(multi_agent.enabled, Some(true));
+        let apps = catalog
+            .iter()
+            .find(|feature| feature.key == "apps")
+            .expect("apps");
+        assert_eq!(apps.enabled, None);
+        assert!(!apps.default_enabled);
+    }
+
+    #[test]
+    fn reasoning_effort_is_normalized_and_profile_scoped() {
+        let mut document = config_toml_core::parse_document(
+            "model_reasoning_effort = \"HIGH\"\n[profiles.fast]\nmodel_reasoning_effort = \"turbo\"\n",
+        )
+        .expect("parse");
+
+        assert_eq!(
+            read_reasoning_effort_from_document(&document, None).as_deref(),
+            Some("high")
+        );
+        assert_eq!(
+            read_reasoning_effort_from_document(&document, Some("fast")),
+            None
+        );
+
+        apply_reasoning_effort_to_document(&mut document, Some("fast"), Some("Low"))
+            .expect("write profile effort");
+        assert_eq!(
+            read_reasoning_effort_from_document(&document, Some("fast")).as_deref(),
+            Some("low")
+        );
+
+        let error = apply_reasoning_effort_to_document(&mut document, None, Some("extreme"))
+            .expect_err("unknown effort");
+        assert!(error.contains("extreme"));
+        assert_eq!(
+            read_reasoning_effort_from_document(&document, None).as_deref(),
+            Some("high")
+        );
+    }
+
+    #[test]
+    fn schema_status_flags_renamed_and_unknown_feature_keys() {
+        let current =
+            config_toml_core::parse_document("[features]\nsteer = true\n").expect("parse");
+        let older = config_toml_core::parse_document("[features]\ncollab = true\n").expect("parse");
+        let unknown =
+            config_toml_core::parse_document("[features]\ncollab = true\nhyperdrive = true\n")
+                .expect("parse");
+        let newer = config_toml_core::parse_document(
+            "schema_version = 2\n\n[features]\ncollab = true\nhyperdrive = true\n",
+        )
+        .expect("parse");
+
+        assert_eq!(
+            schema_status_from_document(&current).state,
+            SchemaState::Current
+        );
+        let older = schema_status_from_document(&older);
+        assert_eq!(older.state, SchemaState::Older);
+        assert_eq!(older.migratable_keys, vec!["features.collab".to_string()]);
+        let unknown = schema_status_from_document(&unknown);
+        assert_eq!(unknown.state, SchemaState::Older);
+        assert_eq!(
+            unknown.unknown_keys,
+            vec!["features.hyperdrive".to_string()]
+        );
+        assert!(unknown.newer_keys.is_empty());
+        let newer = schema_status_from_document(&newer);
+        assert_eq!(newer.state, SchemaState::Newer);
+        assert_eq!(newer.newer_keys, vec!["schema_version".to_string()]);
+        assert_eq!(newer.unknown_keys, vec!["features.hyperdrive".to_string()]);
+        assert_eq!(newer.migratable_keys, vec!["features.collab".to_string()]);
+    }
+
+    #[test]
+    fn mcp_servers_are_listed_and_toggled_in_place() {
+        let mut document = config_toml_core::parse_document(
+            "model = \"gpt-5\"\n\n[mcp_servers.docs]\ncommand = \"npx\"\nargs = [\"-y\", \"docs-mcp\"]\n\n[mcp_servers.remote]\nurl = \"https://mcp.example\"\nenabled = false\n",
+        )
+        .expect("parse");
+
+        let servers = mcp_servers_from_document(&document);
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].name, "docs");
+        assert_eq!(servers[0].command.as_deref(), Some("npx"));
+        assert_eq!(
+            servers[0].args,
+            vec!["-y".to_string(), "docs-mcp".to_string()]
+        );
+        assert!(servers[0].enabled);
+        assert_eq!(servers[1].command, None);
+        assert!(!servers[1].enabled);
+
+        set_mcp_server_enabled_in_document(&mut document, "docs", false).expect("disable");
+        assert!(!mcp_servers_from_document(&document)[0].enabled);
+        assert!(document
+            .to_string()
+            .starts_with("model = \"gpt-5\"\n\n[mcp_servers.docs]"));
+        assert!(set_mcp_server_enabled_in_document(&mut document, "missing", true).is_err());
+
+        let empty = config_toml_core::parse_document("model = \"gpt-5\"\n").expect("parse");
+        assert!(mcp_servers_from_document(&empty).is_empty());
+    }
+
+    #[test]
+    fn feature_flags_apply_with_or_without_clearing() {
+        let source = "[features]\nsteer = true\napps = true\nlegacy = \"on\"\n";
+        let preset: BTreeMap<String, bool> = [
+            ("steer".to_string(), false),
+            ("multi_agent".to_string(), true),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut kept = config_toml_core::parse_document(source).expect("parse");
+        apply_feature_flags_to_document(&mut kept, &preset, false).expect("apply");
+        let flags = feature_flags_from_document(&kept);
+        assert_eq!(flags.get("steer"), Some(&false));
+        assert_eq!(flags.get("apps"), Some(&true));
+        assert_eq!(flags.get("multi_agent"), Some(&true));
+
+        let mut cleared = config_toml_core::parse_document(source).expect("parse");
+        apply_feature_flags_to_document(&mut cleared, &preset, true).expect("apply");
+        assert_eq!(feature_flags_from_document(&cleared), preset);
+        assert!(cleared.to_string().contains("legacy = \"on\""));
+    }
+
+    #[test]
+    fn config_modified_ms_is_none_until_config_exists() {
+        let dir = std::env::temp_dir().join(format!("codex-config-mtime-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let _ = std::fs::remove_file(dir.join("config.toml"));
+
+        assert_eq!(config_modified_ms(Some(dir.clone())), Ok(None));
+        std::fs::write(dir.join("config.toml"), "model = \"gpt-5\"\n").expect("write");
+        assert!(matches!(config_modified_ms(Some(dir.clone())), Ok(Some(ms)) if ms > 0));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn config_with_defaults_marks_file_and_default_values() {
+        let document = config_toml_core::parse_document(
+            "Personality = \"Pragmatic\"\nsandbox_mode = \"workspace-write\"\nmodel_reasoning_effort = \"extreme\"\n[features]\nsteer = false\ncustom = true\n[tools]\nview_image = \"yes\"\n",
+        )
+        .expect("parse");
+        let settings = config_with_defaults_from_document(&document);
+        let lookup = |key: &str| {
+            settings
+                .iter()
+                .find(|setting| setting.key == key)
+                .map(|setting| (setting.value.clone(), setting.source))
+                .expect(key)
+        };
+
+        assert_eq!(
+            lookup("personality"),
+            (serde_json::json!("pragmatic"), SettingSource::File)
+        );
+        assert_eq!(
+            lookup("sandbox_mode"),
+            (serde_json::json!("workspace-write"), SettingSource::File)
+        );
+        assert_eq!(
+            lookup("model_reasoning_effort"),
+            (serde_json::json!("medium"), SettingSource::Default)
+        );
+        assert_eq!(
+            lookup("model"),
+            (serde_json::Value::Null, SettingSource::Default)
+        );
+        assert_eq!(
+            lookup("features.steer"),
+            (serde_json::json!(false), SettingSource::File)
+        );
+        assert_eq!(
+            lookup("features.apps"),
+            (serde_json::json!(false), SettingSource::Default)
+        );
+        assert_eq!(
+            lookup("tools.view_image"),
+            (serde_json::json!(true), SettingSource::Default)
+        );
+        // Extra keys pass through after the known ones.
+        assert_eq!(
+            settings.last().map(|setting| setting.key.as_str()),
+            Some("features.custom")
+        );
+        assert_eq!(
+            lookup("features.custom"),
+            (serde_json::json!(true), SettingSource::File)
+        );
+        assert!(settings.iter().all(|setting| setting.key != "Personality"));
+    }
+
+    #[test]
+    fn profile_diff_layers_profiles_over_top_level() {
+        let document = config_toml_core::parse_document(
+            "model = \"gpt-5\"\nsandbox_mode = \"workspace-write\"\n[profiles.fast]\nmodel = \"gpt-5-mini\"\nmodel_reasoning_effort = \"low\"\n[profiles.safe]\nsandbox_mode = \"read-only\"\napproval_policy = \"untrusted\"\nextra = 1\n",
+        )
+        .expect("parse");
+        let deltas =
+            config_profile_diff_from_document(&document, "fast", "safe").expect("both exist");
+        let summary: Vec<_> = deltas
+            .iter()
+            .map(|delta| {
+                (
+                    delta.key.as_str(),
+                    delta.value_a.clone(),
+                    delta.value_b.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "model",
+                    Some(serde_json::json!("gpt-5-mini")),
+                    Some(serde_json::json!("gpt-5"))
+                ),
+                (
+                    "model_reasoning_effort",
+                    Some(serde_json::json!("low")),
+                    Some(serde_json::json!("medium"))
+                ),
+                (
+                    "sandbox_mode",
+                    Some(serde_json::json!("workspace-write")),
+                    Some(serde_json::json!("read-only"))
+                ),
+                (
+                    "approval_policy",
+                    Some(serde_json::json!("on-request")),
+                    Some(serde_json::json!("untrusted"))
+                ),
+                ("extra", None, Some(serde_json::json!(1))),
+            ]
+        );
+        assert!(config_profile_diff_from_document(&document, "fast", "fast")
+            .expect("same profile")
+            .is_empty());
+        let err = config_profile_diff_from_document(&document, "fast", "slow")
+            .expect_err("missing profile");
+        assert!(err.contains("\"slow\""));
+    }
 }
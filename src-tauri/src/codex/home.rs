@@ -1,22 +1,124 @@
 use std::env;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
 use crate::types::WorkspaceEntry;
 
+/// Whether resolved CODEX_HOME paths are canonicalized; see
+/// `set_canonicalize_codex_home`.
+static CANONICALIZE_CODEX_HOME: AtomicBool = AtomicBool::new(false);
+
+/// Makes every resolved CODEX_HOME its canonical, symlink-free form, so a home
+/// behind a symlink compares equal wherever paths are matched (watchers,
+/// workspace-for-path). Paths become stable, but ones already handed out keep
+/// pointing at the old target if the symlink is repointed or the target moves.
+pub(crate) fn set_canonicalize_codex_home(enabled: bool) {
+    CANONICALIZE_CODEX_HOME.store(enabled, Ordering::SeqCst);
+}
+
+/// A missing home cannot be canonicalized and is returned unchanged.
+fn finalize_codex_home(path: PathBuf, canonicalize: bool) -> PathBuf {
+    if canonicalize {
+        path.canonicalize().unwrap_or(path)
+    } else {
+        path
+    }
+}
+
+/// Home chosen by `codex_home_relocate`; see `set_codex_home_override`.
+static CODEX_HOME_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Makes `path` the default CODEX_HOME ahead of the `CODEX_HOME` variable, so
+/// the app and the sessions it spawns follow a relocated home. Sessions already
+/// running keep their old home until they reconnect.
+pub(crate) fn set_codex_home_override(path: Option<&str>) {
+    let path = path.and_then(normalize_codex_home);
+    *CODEX_HOME_OVERRIDE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = path;
+}
+
+fn codex_home_override() -> Option<PathBuf> {
+    CODEX_HOME_OVERRIDE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+fn resolved_codex_home(path: PathBuf) -> PathBuf {
+    finalize_codex_home(path, CANONICALIZE_CODEX_HOME.load(Ordering::SeqCst))
+}
+
+/// A workspace's pinned `codexHome` wins, then its parent's (so worktrees follow
+/// the main workspace), then the global default.
 pub(crate) fn resolve_workspace_codex_home(
-    _entry: &WorkspaceEntry,
-    _parent_entry: Option<&WorkspaceEntry>,
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
 ) -> Option<PathBuf> {
-    resolve_default_codex_home()
+    [Some(entry), parent_entry]
+        .into_iter()
+        .flatten()
+        .find_map(|candidate| {
+            candidate
+                .settings
+                .codex_home
+                .as_deref()
+                .and_then(normalize_codex_home)
+                .map(resolved_codex_home)
+        })
+        .or_else(resolve_default_codex_home)
+}
+
+/// Expands `value` and checks it can hold a `config.toml`: an existing directory,
+/// or a missing directory whose parent exists.
+pub(crate) fn validate_codex_home(value: &str) -> Result<PathBuf, String> {
+    let path = normalize_codex_home(value).ok_or_else(|| "CODEX_HOME path is empty".to_string())?;
+    if !path.is_absolute() {
+        return Err("CODEX_HOME must be an absolute path".to_string());
+    }
+    if path.exists() {
+        if !path.is_dir() {
+            return Err(format!("{} is not a directory", path.display()));
+        }
+        if path.join("config.toml").is_dir() {
+            return Err(format!("{}/config.toml is a directory", path.display()));
+        }
+        return Ok(path);
+    }
+    match path.parent() {
+        Some(parent) if parent.is_dir() => Ok(path),
+        _ => Err(format!(
+            "Parent directory of {} does not exist",
+            path.display()
+        )),
+    }
 }
 
 pub(crate) fn resolve_default_codex_home() -> Option<PathBuf> {
+    if let Some(path) = codex_home_override() {
+        return Some(resolved_codex_home(path));
+    }
     if let Ok(value) = env::var("CODEX_HOME") {
         if let Some(path) = normalize_codex_home(&value) {
-            return Some(path);
+            return Some(resolved_codex_home(path));
         }
     }
-    resolve_home_dir().map(|home| home.join(".codex"))
+    resolve_home_dir().map(|home| resolved_codex_home(home.join(".codex")))
+}
+
+/// CODEX_HOME as the Codex CLI itself resolves it: a non-empty `CODEX_HOME`
+/// taken literally (no `~` or `$VAR` expansion) and canonicalized, which fails
+/// when it does not exist, else `~/.codex`. Ignores app-side overrides.
+pub(crate) fn resolve_codex_cli_home() -> Result<PathBuf, String> {
+    match env::var("CODEX_HOME") {
+        Ok(value) if !value.is_empty() => PathBuf::from(&value)
+            .canonicalize()
+            .map_err(|err| format!("Codex cannot resolve CODEX_HOME `{value}`: {err}")),
+        _ => resolve_home_dir()
+            .map(|home| home.join(".codex"))
+            .ok_or_else(|| "Unable to resolve the home directory".to_string()),
+    }
 }
 
 fn normalize_codex_home(value: &str) -> Option<PathBuf> {
@@ -253,4 +355,82 @@ mod tests {
             None => std::env::remove_var("APPDATA"),
         }
     }
+
+    #[test]
+    fn codex_cli_home_takes_codex_home_literally() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let dir = std::env::temp_dir().join(format!("codex-cli-home-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let prev_codex_home = std::env::var("CODEX_HOME").ok();
+
+        std::env::set_var("CODEX_HOME", &dir);
+        assert_eq!(
+            resolve_codex_cli_home(),
+            Ok(dir.canonicalize().expect("canonical"))
+        );
+        std::env::set_var("CODEX_HOME", "~/.codex-cli-home-missing");
+        assert!(resolve_codex_cli_home().is_err());
+        assert!(resolve_default_codex_home().is_some());
+
+        match prev_codex_home {
+            Some(value) => std::env::set_var("CODEX_HOME", value),
+            None => std::env::remove_var("CODEX_HOME"),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn workspace_codex_home_prefers_pinned_home() {
+        let mut parent = workspace_entry(WorkspaceKind::Main, "/repo");
+        parent.settings.codex_home = Some("/tmp/codex-pinned".to_string());
+        let child = workspace_entry(WorkspaceKind::Worktree, "/repo-wt");
+
+        assert_eq!(
+            resolve_workspace_codex_home(&parent, None),
+            Some(PathBuf::from("/tmp/codex-pinned"))
+        );
+        assert_eq!(
+            resolve_workspace_codex_home(&child, Some(&parent)),
+            Some(PathBuf::from("/tmp/codex-pinned"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_codex_home_is_canonicalized_only_when_enabled() {
+        let dir = std::env::temp_dir().join(format!("codex-home-symlink-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        // The temp dir itself may sit behind a symlink (e.g. /tmp on macOS).
+        let dir = dir.canonicalize().expect("canonical dir");
+        let target = dir.join("target");
+        std::fs::create_dir_all(&target).expect("create target");
+        let link = dir.join("link");
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&target, &link).expect("create symlink");
+
+        assert_eq!(finalize_codex_home(link.clone(), false), link);
+        assert_eq!(finalize_codex_home(link.clone(), true), target);
+        assert_eq!(
+            finalize_codex_home(link.join("missing"), true),
+            link.join("missing")
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn validate_codex_home_requires_usable_directory() {
+        let dir = std::env::temp_dir().join(format!("codex-home-validate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let file = dir.join("not-a-dir");
+        std::fs::write(&file, "x").expect("write file");
+
+        assert_eq!(validate_codex_home(&dir.to_string_lossy()), Ok(dir.clone()));
+        assert!(validate_codex_home(&dir.join("new-home").to_string_lossy()).is_ok());
+        assert!(validate_codex_home(&file.to_string_lossy()).is_err());
+        assert!(validate_codex_home(&dir.join("missing/new-home").to_string_lossy()).is_err());
+        assert!(validate_codex_home("relative/home").is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -1,4 +1,5 @@
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -15,6 +16,16 @@ use crate::event_sink::TauriEventSink;
 use crate::remote_backend;
 use crate::shared::agents_config_core;
 use crate::shared::codex_core::{self, insert_optional_nullable_string};
+use crate::shared::config_drift_core::{config_drift_check_core, DriftEntry};
+use crate::shared::config_duplicates_core::ConfigParseError;
+use crate::shared::config_history_core;
+use crate::shared::config_migrate_core::MigrationStep;
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::config_roundtrip_core::RoundtripReport;
+use crate::shared::config_toml_core::{
+    self, ConfigOp, ConfigValue, IndentInfo, IndentStyle, MergeStrategy,
+};
+use crate::shared::files_core;
 use crate::state::AppState;
 use crate::types::WorkspaceEntry;
 
@@ -31,6 +42,23 @@ fn emit_thread_live_event(app: &AppHandle, workspace_id: &str, method: &str, par
     );
 }
 
+/// The home config commands act on: `codex_home` when given, otherwise the
+/// pinned home of `workspace_id`, otherwise the default.
+async fn config_codex_home(
+    state: &AppState,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+) -> Result<Option<PathBuf>, String> {
+    files_core::resolve_config_codex_home_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        codex_home.as_deref(),
+        workspace_id.as_deref(),
+    )
+    .await
+    .map(Some)
+}
+
 pub(crate) async fn spawn_workspace_session(
     entry: WorkspaceEntry,
     default_codex_bin: Option<String>,
@@ -563,89 +591,1132 @@ pub(crate) async fn set_codex_feature_flag(
         return Ok(());
     }
 
-    config::write_feature_enabled(feature_key.as_str(), enabled)
+    config::write_feature_enabled(
+        &state.scope_root_overrides,
+        feature_key.as_str(),
+        enabled,
+        ConfigWriter::new(
+            "set_codex_feature_flag",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
 }
 
 #[tauri::command]
-pub(crate) async fn get_agents_settings(
+pub(crate) async fn config_undo(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<agents_config_core::AgentsSettingsDto, String> {
+) -> Result<bool, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        let response =
-            remote_backend::call_remote(&*state, app, "get_agents_settings", json!({})).await?;
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_undo",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    agents_config_core::get_agents_settings_core()
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::undo_config_write(
+        codex_home,
+        ConfigWriter::new(
+            "config_undo",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
 }
 
 #[tauri::command]
-pub(crate) async fn set_agents_core_settings(
-    input: agents_config_core::SetAgentsCoreInput,
+pub(crate) async fn config_redo(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<agents_config_core::AgentsSettingsDto, String> {
+) -> Result<bool, String> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
-            "set_agents_core_settings",
-            json!({ "input": input }),
+            "config_redo",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    agents_config_core::set_agents_core_settings_core(input)
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::redo_config_write(
+        codex_home,
+        ConfigWriter::new(
+            "config_redo",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
 }
 
 #[tauri::command]
-pub(crate) async fn create_agent(
-    input: agents_config_core::CreateAgentInput,
+pub(crate) async fn config_history(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<agents_config_core::AgentsSettingsDto, String> {
+) -> Result<Vec<config_history_core::HistoryEntry>, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        let response =
-            remote_backend::call_remote(&*state, app, "create_agent", json!({ "input": input }))
-                .await?;
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_history",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    agents_config_core::create_agent_core(input)
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    Ok(config::read_config_history(codex_home))
 }
 
+/// Returns the formatted `config.toml`; with `dry_run` nothing is written.
 #[tauri::command]
-pub(crate) async fn update_agent(
-    input: agents_config_core::UpdateAgentInput,
+pub(crate) async fn config_format(
+    sort_keys: Option<bool>,
+    dry_run: Option<bool>,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<agents_config_core::AgentsSettingsDto, String> {
+) -> Result<String, String> {
+    let sort_keys = sort_keys.unwrap_or(false);
+    let dry_run = dry_run.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
-        let response =
-            remote_backend::call_remote(&*state, app, "update_agent", json!({ "input": input }))
-                .await?;
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_format",
+            json!({
+                "sortKeys": sort_keys,
+                "dryRun": dry_run,
+                "codexHome": codex_home,
+                "workspaceId": workspace_id
+            }),
+        )
+        .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    agents_config_core::update_agent_core(input)
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::format_config(
+        sort_keys,
+        dry_run,
+        codex_home,
+        ConfigWriter::new(
+            "config_format",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
 }
 
+/// Lists what `config_migrate` would change without writing `config.toml`.
 #[tauri::command]
-pub(crate) async fn delete_agent(
-    input: agents_config_core::DeleteAgentInput,
+pub(crate) async fn config_migrate_preview(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<agents_config_core::AgentsSettingsDto, String> {
+) -> Result<Vec<MigrationStep>, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        let response =
-            remote_backend::call_remote(&*state, app, "delete_agent", json!({ "input": input }))
-                .await?;
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_migrate_preview",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::preview_config_migration(codex_home)
+}
+
+#[tauri::command]
+pub(crate) async fn config_migrate(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<MigrationStep>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_migrate",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::migrate_config(
+        codex_home,
+        ConfigWriter::new(
+            "config_migrate",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Writes only the given top-level keys to `config.toml`, leaving everything
+/// else untouched. A `null` value deletes the key.
+#[tauri::command]
+pub(crate) async fn config_merge(
+    changes: HashMap<String, Option<ConfigValue>>,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "config_merge",
+            json!({
+                "changes": changes,
+                "codexHome": codex_home,
+                "workspaceId": workspace_id
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::merge_config(
+        &changes,
+        codex_home,
+        ConfigWriter::new(
+            "config_merge",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Applies `ops` (set key, set flag, set nested, remove) to `config.toml` in
+/// one write; if any op fails nothing is written and the error names it.
+#[tauri::command]
+pub(crate) async fn config_transaction(
+    ops: Vec<ConfigOp>,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "config_transaction",
+            json!({
+                "ops": ops,
+                "codexHome": codex_home,
+                "workspaceId": workspace_id
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_transaction(
+        &ops,
+        codex_home,
+        ConfigWriter::new(
+            "config_transaction",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Adds `value` to the top-level array `key`, e.g. to trust a project root.
+/// Returns `false` when it was already listed.
+#[tauri::command]
+pub(crate) async fn config_array_add(
+    key: String,
+    value: String,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_array_add",
+            json!({
+                "key": key,
+                "value": value,
+                "codexHome": codex_home,
+                "workspaceId": workspace_id
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_array_add(
+        &key,
+        &value,
+        codex_home,
+        ConfigWriter::new(
+            "config_array_add",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Removes `value` from the top-level array `key`; returns `false` if absent.
+#[tauri::command]
+pub(crate) async fn config_array_remove(
+    key: String,
+    value: String,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_array_remove",
+            json!({
+                "key": key,
+                "value": value,
+                "codexHome": codex_home,
+                "workspaceId": workspace_id
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_array_remove(
+        &key,
+        &value,
+        codex_home,
+        ConfigWriter::new(
+            "config_array_remove",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Returns `config.toml` with secret-looking values replaced by
+/// `"<redacted>"`, safe to share. `extra_keys` names more keys to redact.
+#[tauri::command]
+pub(crate) async fn config_export_redacted(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    extra_keys: Option<Vec<String>>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_export_redacted",
+            json!({
+                "codexHome": codex_home,
+                "workspaceId": workspace_id,
+                "extraKeys": extra_keys
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::export_redacted_config(codex_home, &extra_keys.unwrap_or_default())
+}
+
+/// Returns `config.toml` without comments for a minimal export. The file is
+/// only rewritten when `write` is set.
+#[tauri::command]
+pub(crate) async fn config_strip_comments(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    write: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_strip_comments",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id, "write": write }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::strip_config_comments(
+        codex_home,
+        write,
+        ConfigWriter::new(
+            "config_strip_comments",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Whether `config.toml` is indented with tabs or spaces, and how many lines
+/// use each.
+#[tauri::command]
+pub(crate) async fn config_indent_info(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<IndentInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_indent_info",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_indent_info(codex_home)
+}
+
+/// Rewrites `config.toml` with every indented line in `style` and returns the
+/// new text. Running it again changes nothing.
+#[tauri::command]
+pub(crate) async fn config_reindent(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    style: IndentStyle,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_reindent",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id, "style": style }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::reindent_config(
+        codex_home,
+        style,
+        ConfigWriter::new(
+            "config_reindent",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Why `config.toml` fails to parse, with the duplicated key and its lines when
+/// that is the cause; `None` when it parses.
+#[tauri::command]
+pub(crate) async fn config_load_tolerant(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<ConfigParseError>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_load_tolerant",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_load_tolerant(codex_home)
+}
+
+/// Keeps the last definition of each duplicated key in `config.toml`. Returns
+/// the number of lines removed.
+#[tauri::command]
+pub(crate) async fn config_fix_duplicate_keys(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_fix_duplicate_keys",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    agents_config_core::delete_agent_core(input)
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::fix_config_duplicate_keys(
+        codex_home,
+        ConfigWriter::new(
+            "config_fix_duplicate_keys",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Lists the top-level keys and tables in `config.toml` that writing
+/// `proposed` would drop, so raw edits can warn before saving.
+#[tauri::command]
+pub(crate) async fn config_roundtrip_check(
+    proposed: String,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RoundtripReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_roundtrip_check",
+            json!({ "proposed": proposed, "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_roundtrip_check(codex_home, &proposed)
+}
+
+/// Lists the feature flags and personality whose `config.toml` value changed
+/// behind the app's back, with the app's and the on-disk value.
+#[tauri::command]
+pub(crate) async fn config_drift_check(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<DriftEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "config_drift_check", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    config_drift_check_core(
+        &state.app_settings,
+        &state.scope_root_overrides,
+        &state.config_drift,
+    )
+    .await
+}
+
+/// `config.toml` with Codex's defaults filled in for every known setting, so
+/// the frontend does not need its own copy of them.
+#[tauri::command]
+pub(crate) async fn config_with_defaults(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<config::ResolvedSetting>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_with_defaults",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_with_defaults(codex_home)
+}
+
+/// Merges two `config.toml` texts key by key and returns the result without
+/// writing it. Computed locally in both backend modes.
+#[tauri::command]
+pub(crate) fn config_merge_documents(
+    base: String,
+    incoming: String,
+    strategy: MergeStrategy,
+) -> Result<String, String> {
+    config_toml_core::config_merge_documents_core(&base, &incoming, strategy)
+}
+
+/// How two `[profiles.*]` differ once each is layered over the top-level
+/// settings; identical settings are left out.
+#[tauri::command]
+pub(crate) async fn config_profile_diff(
+    profile_a: String,
+    profile_b: String,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<config::ConfigDelta>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_profile_diff",
+            json!({
+                "profileA": profile_a,
+                "profileB": profile_b,
+                "codexHome": codex_home,
+                "workspaceId": workspace_id
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_profile_diff(&profile_a, &profile_b, codex_home)
+}
+
+/// Adds `[profiles.<name>]` to `config.toml`, copying `base`'s keys when given.
+#[tauri::command]
+pub(crate) async fn config_profile_create(
+    name: String,
+    base: Option<String>,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "config_profile_create",
+            json!({
+                "name": name,
+                "base": base,
+                "codexHome": codex_home,
+                "workspaceId": workspace_id
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_profile_create(
+        &name,
+        base.as_deref(),
+        codex_home,
+        ConfigWriter::new(
+            "config_profile_create",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Removes `[profiles.<name>]`; returns a warning if it was the active profile.
+#[tauri::command]
+pub(crate) async fn config_profile_delete(
+    name: String,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<String>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_profile_delete",
+            json!({ "name": name, "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_profile_delete(
+        &name,
+        codex_home,
+        ConfigWriter::new(
+            "config_profile_delete",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Renames `[profiles.<old>]` to `new`, updating `profile` if it was active.
+#[tauri::command]
+pub(crate) async fn config_profile_rename(
+    old: String,
+    new: String,
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "config_profile_rename",
+            json!({ "old": old, "new": new, "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_profile_rename(
+        &old,
+        &new,
+        codex_home,
+        ConfigWriter::new(
+            "config_profile_rename",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Reads the `[tools]` booleans, reporting Codex's default for unset ones.
+#[tauri::command]
+pub(crate) async fn tools_config_read(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<config::ToolsConfig, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "tools_config_read",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::tools_config_read(codex_home)
+}
+
+/// Sets one `[tools]` boolean; `enabled: null` reverts it to Codex's default.
+#[tauri::command]
+pub(crate) async fn tools_config_set(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    tool: String,
+    enabled: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<config::ToolsConfig, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "tools_config_set",
+            json!({
+                "codexHome": codex_home,
+                "workspaceId": workspace_id,
+                "tool": tool,
+                "enabled": enabled
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::tools_config_set(
+        codex_home,
+        &tool,
+        enabled,
+        ConfigWriter::new(
+            "tools_config_set",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Lists the MCP servers configured under `[mcp_servers]`.
+#[tauri::command]
+pub(crate) async fn mcp_servers_list(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<config::McpServerInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "mcp_servers_list",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::mcp_servers_list(codex_home)
+}
+
+#[tauri::command]
+pub(crate) async fn mcp_server_set_enabled(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    name: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "mcp_server_set_enabled",
+            json!({
+                "codexHome": codex_home,
+                "workspaceId": workspace_id,
+                "name": name,
+                "enabled": enabled
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::mcp_server_set_enabled(
+        codex_home,
+        &name,
+        enabled,
+        ConfigWriter::new(
+            "mcp_server_set_enabled",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Reports whether `config.toml` looks older or newer than the schema this
+/// app understands. Defaults to the resolved CODEX_HOME.
+#[tauri::command]
+pub(crate) async fn config_schema_check(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<config::SchemaStatus, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_schema_check",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_schema_check(codex_home)
+}
+
+/// A cheap change probe: `config.toml`'s mtime in Unix millis, without parsing.
+#[tauri::command]
+pub(crate) async fn config_modified_ms(
+    codex_home: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<u64>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "config_stat",
+            json!({ "codexHome": codex_home, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let codex_home = config_codex_home(&state, codex_home, workspace_id).await?;
+    config::config_modified_ms(codex_home)
+}
+
+/// Returns `config.toml` merged with its `include` files.
+#[tauri::command]
+pub(crate) async fn read_effective_config(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<config_toml_core::EffectiveConfig, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "read_effective_config", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    config::read_effective_config(&state.scope_root_overrides)
+}
+
+/// The `config.toml` the Codex CLI itself reads, which can differ from the one
+/// the app edits.
+#[tauri::command]
+pub(crate) async fn codex_effective_config_path(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<config::EffectiveConfigPath, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "codex_effective_config_path", json!({}))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    config::codex_effective_config_path(&state.scope_root_overrides)
+}
+
+/// Returns the personality Codex will use: the configured one when valid,
+/// otherwise Codex's default.
+#[tauri::command]
+pub(crate) async fn read_effective_personality(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<config::EffectivePersonality, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "read_effective_personality", json!({}))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    config::read_effective_personality(&state.scope_root_overrides)
+}
+
+/// Reads `model_reasoning_effort`, from `[profiles.<profile>]` when a profile
+/// is given. Unrecognized values read as `None`.
+#[tauri::command]
+pub(crate) async fn read_reasoning_effort(
+    profile: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<String>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "read_reasoning_effort",
+            json!({ "profile": profile }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    config::read_reasoning_effort(&state.scope_root_overrides, profile)
+}
+
+#[tauri::command]
+pub(crate) async fn write_reasoning_effort(
+    profile: Option<String>,
+    effort: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "write_reasoning_effort",
+            json!({
+                "profile": profile,
+                "effort": effort
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    config::write_reasoning_effort(
+        &state.scope_root_overrides,
+        profile,
+        effort,
+        ConfigWriter::new(
+            "write_reasoning_effort",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+/// Lists every known Codex feature flag with its label and current value.
+#[tauri::command]
+pub(crate) async fn features_catalog(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<config::FeatureInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "features_catalog", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    config::features_catalog(&state.scope_root_overrides)
+}
+
+#[tauri::command]
+pub(crate) async fn get_agents_settings(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<agents_config_core::AgentsSettingsDto, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "get_agents_settings", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    agents_config_core::get_agents_settings_core()
+}
+
+#[tauri::command]
+pub(crate) async fn set_agents_core_settings(
+    input: agents_config_core::SetAgentsCoreInput,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<agents_config_core::AgentsSettingsDto, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_agents_core_settings",
+            json!({ "input": input }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    agents_config_core::set_agents_core_settings_core(
+        input,
+        ConfigWriter::new(
+            "set_agents_core_settings",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+#[tauri::command]
+pub(crate) async fn create_agent(
+    input: agents_config_core::CreateAgentInput,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<agents_config_core::AgentsSettingsDto, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "create_agent", json!({ "input": input }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    agents_config_core::create_agent_core(
+        input,
+        ConfigWriter::new(
+            "create_agent",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+#[tauri::command]
+pub(crate) async fn update_agent(
+    input: agents_config_core::UpdateAgentInput,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<agents_config_core::AgentsSettingsDto, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "update_agent", json!({ "input": input }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    agents_config_core::update_agent_core(
+        input,
+        ConfigWriter::new(
+            "update_agent",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+}
+
+#[tauri::command]
+pub(crate) async fn delete_agent(
+    input: agents_config_core::DeleteAgentInput,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<agents_config_core::AgentsSettingsDto, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "delete_agent", json!({ "input": input }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    agents_config_core::delete_agent_core(
+        input,
+        ConfigWriter::new(
+            "delete_agent",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
 }
 
 #[tauri::command]
@@ -1,6 +1,10 @@
 use tauri::{AppHandle, Emitter};
 
-use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use crate::backend::events::{
+    AppServerEvent, BatchChanged, ConfigPatch, EventSink, OperationFinished, OperationProgress,
+    PrewarmDone, ScopeChanged, TerminalExit, TerminalOutput, TreeDone, TreeEntries, TreeTruncated,
+    WorkspaceHealth, WorkspacesChanged,
+};
 
 #[derive(Clone)]
 pub(crate) struct TauriEventSink {
@@ -25,4 +29,53 @@ impl EventSink for TauriEventSink {
     fn emit_terminal_exit(&self, event: TerminalExit) {
         let _ = self.app.emit("terminal-exit", event);
     }
+
+    fn emit_operation_progress(&self, event: OperationProgress) {
+        let _ = self.app.emit("operation-progress", event);
+    }
+
+    fn emit_operation_finished(&self, event: OperationFinished) {
+        let name = if event.error.is_some() {
+            "operation-failed"
+        } else {
+            "operation-done"
+        };
+        let _ = self.app.emit(name, event);
+    }
+
+    fn emit_scope_changed(&self, event: ScopeChanged) {
+        let _ = self.app.emit("scope-changed", event);
+    }
+
+    fn emit_batch_changed(&self, event: BatchChanged) {
+        let _ = self.app.emit("batch-changed", event);
+    }
+
+    fn emit_workspaces_changed(&self, event: WorkspacesChanged) {
+        let _ = self.app.emit("workspaces-changed", event);
+    }
+
+    fn emit_config_patch(&self, event: ConfigPatch) {
+        let _ = self.app.emit("config-patch", event);
+    }
+
+    fn emit_prewarm_done(&self, event: PrewarmDone) {
+        let _ = self.app.emit("prewarm-done", event);
+    }
+
+    fn emit_workspace_health(&self, event: WorkspaceHealth) {
+        let _ = self.app.emit("workspace-health", event);
+    }
+
+    fn emit_tree_entries(&self, event: TreeEntries) {
+        let _ = self.app.emit("tree-entry", event);
+    }
+
+    fn emit_tree_truncated(&self, event: TreeTruncated) {
+        let _ = self.app.emit("tree-truncated", event);
+    }
+
+    fn emit_tree_done(&self, event: TreeDone) {
+        let _ = self.app.emit("tree-done", event);
+    }
 }
@@ -0,0 +1,190 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::files::policy::FileKind;
+
+pub(crate) const MANIFEST_NAME: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+pub(crate) const MAX_ENTRY_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveEntry {
+    pub(crate) kind: FileKind,
+    pub(crate) path: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveManifest {
+    version: u32,
+    files: Vec<ArchiveEntry>,
+}
+
+/// Writes `files` plus a manifest into a zip at `dest`. The archive is staged
+/// next to `dest` and renamed into place so a failed export never leaves a
+/// partial zip behind.
+pub(crate) fn write_archive(dest: &Path, files: &[(ArchiveEntry, Vec<u8>)]) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create export directory: {err}"))?;
+        }
+    }
+    let file_name = dest
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| "Export path must name a file".to_string())?;
+    let temp_path = dest.with_file_name(format!(".{file_name}.{}.tmp", uuid::Uuid::new_v4()));
+
+    let result = (|| -> Result<(), String> {
+        let file = File::create(&temp_path)
+            .map_err(|err| format!("Failed to create export archive: {err}"))?;
+        let mut writer = ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+        let manifest = ArchiveManifest {
+            version: MANIFEST_VERSION,
+            files: files.iter().map(|(entry, _)| entry.clone()).collect(),
+        };
+        let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|err| err.to_string())?;
+        writer
+            .start_file(MANIFEST_NAME, options)
+            .map_err(|err| format!("Failed to write export archive: {err}"))?;
+        writer
+            .write_all(&manifest_json)
+            .map_err(|err| format!("Failed to write export archive: {err}"))?;
+
+        for (entry, content) in files {
+            writer
+                .start_file(entry.path.as_str(), options)
+                .map_err(|err| format!("Failed to write export archive: {err}"))?;
+            writer
+                .write_all(content)
+                .map_err(|err| format!("Failed to write export archive: {err}"))?;
+        }
+        writer
+            .finish()
+            .map_err(|err| format!("Failed to write export archive: {err}"))?;
+        std::fs::rename(&temp_path, dest)
+            .map_err(|err| format!("Failed to write export archive: {err}"))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Reads every file listed in the archive manifest. `expected_path` maps a kind
+/// to the only entry name accepted for it, so archive paths are never used to
+/// build filesystem paths.
+pub(crate) fn read_archive(
+    source: &Path,
+    expected_path: impl Fn(FileKind) -> Option<&'static str>,
+) -> Result<Vec<(ArchiveEntry, Vec<u8>)>, String> {
+    let file = File::open(source).map_err(|err| format!("Failed to open import archive: {err}"))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|err| format!("Invalid import archive: {err}"))?;
+    let manifest: ArchiveManifest = {
+        let bytes = read_entry(&mut archive, MANIFEST_NAME)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|err| format!("Invalid import archive manifest: {err}"))?
+    };
+    if manifest.version != MANIFEST_VERSION {
+        return Err(format!(
+            "Unsupported import archive version {}",
+            manifest.version
+        ));
+    }
+
+    let mut files = Vec::with_capacity(manifest.files.len());
+    for entry in manifest.files {
+        if expected_path(entry.kind) != Some(entry.path.as_str()) {
+            return Err(format!(
+                "Import archive entry `{}` is not allowed for this scope",
+                entry.path
+            ));
+        }
+        let content = read_entry(&mut archive, &entry.path)?;
+        files.push((entry, content));
+    }
+    Ok(files)
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| format!("Import archive is missing `{name}`"))?;
+    let mut buffer = Vec::new();
+    entry
+        .take(MAX_ENTRY_BYTES + 1)
+        .read_to_end(&mut buffer)
+        .map_err(|err| format!("Failed to read `{name}` from import archive: {err}"))?;
+    if buffer.len() as u64 > MAX_ENTRY_BYTES {
+        return Err(format!("`{name}` in import archive is too large"));
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_archive, write_archive, ArchiveEntry};
+    use crate::files::policy::FileKind;
+    use uuid::Uuid;
+
+    fn agents_only(kind: FileKind) -> Option<&'static str> {
+        match kind {
+            FileKind::Agents => Some("AGENTS.md"),
+            FileKind::Config => None,
+        }
+    }
+
+    #[test]
+    fn archive_round_trips_files() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-archive-{}", Uuid::new_v4()));
+        let dest = dir.join("export.zip");
+        let entry = ArchiveEntry {
+            kind: FileKind::Agents,
+            path: "AGENTS.md".to_string(),
+        };
+        write_archive(&dest, &[(entry.clone(), b"# Rules\n".to_vec())]).expect("write");
+
+        let files = read_archive(&dest, agents_only).expect("read");
+        assert_eq!(files, vec![(entry, b"# Rules\n".to_vec())]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn empty_archive_contains_only_manifest() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-archive-{}", Uuid::new_v4()));
+        let dest = dir.join("empty.zip");
+        write_archive(&dest, &[]).expect("write");
+
+        let files = read_archive(&dest, agents_only).expect("read");
+        assert!(files.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unexpected_entry_paths_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-archive-{}", Uuid::new_v4()));
+        let dest = dir.join("escape.zip");
+        let entry = ArchiveEntry {
+            kind: FileKind::Agents,
+            path: "../AGENTS.md".to_string(),
+        };
+        write_archive(&dest, &[(entry, b"x".to_vec())]).expect("write");
+
+        let error = read_archive(&dest, agents_only).expect_err("path should be rejected");
+        assert!(error.contains("not allowed"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CompareStatus {
+    Identical,
+    Different,
+    MissingLocal,
+    MissingRemote,
+    MissingBoth,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffSummary {
+    pub(crate) local_lines: u32,
+    pub(crate) remote_lines: u32,
+    /// Lines present only on the remote side.
+    pub(crate) added_lines: u32,
+    /// Lines present only on the local side.
+    pub(crate) removed_lines: u32,
+    /// 1-based line number of the first mismatch.
+    pub(crate) first_difference_line: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompareResult {
+    pub(crate) status: CompareStatus,
+    pub(crate) diff: Option<DiffSummary>,
+}
+
+/// Compares two optional file bodies; `None` means the file does not exist on
+/// that side.
+pub(crate) fn compare_contents(local: Option<&str>, remote: Option<&str>) -> CompareResult {
+    let (local, remote) = match (local, remote) {
+        (Some(local), Some(remote)) => (local, remote),
+        (None, Some(_)) => return missing(CompareStatus::MissingLocal),
+        (Some(_), None) => return missing(CompareStatus::MissingRemote),
+        (None, None) => return missing(CompareStatus::MissingBoth),
+    };
+    if local == remote {
+        return missing(CompareStatus::Identical);
+    }
+
+    let local_lines: Vec<&str> = local.lines().collect();
+    let remote_lines: Vec<&str> = remote.lines().collect();
+    let first_difference = local_lines
+        .iter()
+        .zip(remote_lines.iter())
+        .position(|(left, right)| left != right)
+        .unwrap_or(local_lines.len().min(remote_lines.len()));
+
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for line in &local_lines {
+        *counts.entry(line).or_default() += 1;
+    }
+    for line in &remote_lines {
+        *counts.entry(line).or_default() -= 1;
+    }
+    let removed_lines = counts.values().filter(|count| **count > 0).sum::<i64>();
+    let added_lines = -counts.values().filter(|count| **count < 0).sum::<i64>();
+
+    CompareResult {
+        status: CompareStatus::Different,
+        diff: Some(DiffSummary {
+            local_lines: local_lines.len() as u32,
+            remote_lines: remote_lines.len() as u32,
+            added_lines: added_lines as u32,
+            removed_lines: removed_lines as u32,
+            first_difference_line: first_difference as u32 + 1,
+        }),
+    }
+}
+
+fn missing(status: CompareStatus) -> CompareResult {
+    CompareResult { status, diff: None }
+}
+
+/// Unchanged lines kept around each change, as in a unified diff.
+const DIFF_CONTEXT_LINES: usize = 3;
+/// Largest LCS table built for the changed middle of two files; beyond it the
+/// middle is reported as one removal plus one addition.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffLine {
+    pub(crate) kind: DiffLineKind,
+    pub(crate) text: String,
+}
+
+/// One unified-diff hunk. Starts are 1-based, or 0 when that side is empty.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffHunk {
+    pub(crate) old_start: u32,
+    pub(crate) old_lines: u32,
+    pub(crate) new_start: u32,
+    pub(crate) new_lines: u32,
+    pub(crate) lines: Vec<DiffLine>,
+}
+
+/// Line diff from `old` to `new`; identical inputs produce no hunks.
+pub(crate) fn diff_hunks(old: &str, new: &str) -> Vec<DiffHunk> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old_lines, &new_lines);
+
+    // Line positions on each side before every op.
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for op in &ops {
+        positions.push((old_pos, new_pos));
+        match op {
+            DiffLineKind::Context => {
+                old_pos += 1;
+                new_pos += 1;
+            }
+            DiffLineKind::Removed => old_pos += 1,
+            DiffLineKind::Added => new_pos += 1,
+        }
+    }
+    positions.push((old_pos, new_pos));
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| **op != DiffLineKind::Context)
+        .map(|(index, _)| index)
+        .collect();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for index in changes {
+        let start = index.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (index + 1 + DIFF_CONTEXT_LINES).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let (old_from, new_from) = positions[start];
+            let (old_to, new_to) = positions[end];
+            let lines = (start..end)
+                .map(|index| {
+                    let (old_pos, new_pos) = positions[index];
+                    let text = match ops[index] {
+                        DiffLineKind::Added => new_lines[new_pos],
+                        _ => old_lines[old_pos],
+                    };
+                    DiffLine {
+                        kind: ops[index],
+                        text: text.to_string(),
+                    }
+                })
+                .collect();
+            DiffHunk {
+                old_start: hunk_start(old_from, old_to),
+                old_lines: (old_to - old_from) as u32,
+                new_start: hunk_start(new_from, new_to),
+                new_lines: (new_to - new_from) as u32,
+                lines,
+            }
+        })
+        .collect()
+}
+
+fn hunk_start(from: usize, to: usize) -> u32 {
+    if to > from {
+        from as u32 + 1
+    } else {
+        from as u32
+    }
+}
+
+/// The edit script from `old` to `new`: a longest common subsequence over the
+/// lines between the shared prefix and suffix.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<DiffLineKind> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(left, right)| left == right)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops = vec![DiffLineKind::Context; prefix];
+    let cells = (old_mid.len() + 1).saturating_mul(new_mid.len() + 1);
+    if cells > MAX_DIFF_CELLS {
+        ops.resize(ops.len() + old_mid.len(), DiffLineKind::Removed);
+        ops.resize(ops.len() + new_mid.len(), DiffLineKind::Added);
+    } else {
+        // lengths[i][j]: LCS length of old_mid[i..] and new_mid[j..].
+        let width = new_mid.len() + 1;
+        let mut lengths = vec![0u32; cells];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                lengths[i * width + j] = if old_mid[i] == new_mid[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() && j < new_mid.len() {
+            if old_mid[i] == new_mid[j] {
+                ops.push(DiffLineKind::Context);
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                ops.push(DiffLineKind::Removed);
+                i += 1;
+            } else {
+                ops.push(DiffLineKind::Added);
+                j += 1;
+            }
+        }
+        ops.resize(ops.len() + old_mid.len() - i, DiffLineKind::Removed);
+        ops.resize(ops.len() + new_mid.len() - j, DiffLineKind::Added);
+    }
+    ops.resize(ops.len() + suffix, DiffLineKind::Context);
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare_contents, diff_hunks, CompareStatus, DiffLineKind};
+
+    #[test]
+    fn diff_hunks_keep_context_and_split_distant_changes() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "1\nTWO\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        let hunks = diff_hunks(old, new);
+        assert_eq!(hunks.len(), 2);
+
+        assert_eq!((hunks[0].old_start, hunks[0].old_lines), (1, 5));
+        assert_eq!((hunks[0].new_start, hunks[0].new_lines), (1, 5));
+        let kinds: Vec<_> = hunks[0].lines.iter().map(|line| line.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffLineKind::Context,
+                DiffLineKind::Removed,
+                DiffLineKind::Added,
+                DiffLineKind::Context,
+                DiffLineKind::Context,
+                DiffLineKind::Context,
+            ]
+        );
+        assert_eq!(hunks[1].old_start, 10);
+        assert_eq!(
+            hunks[1].lines.last().map(|line| line.text.as_str()),
+            Some("13")
+        );
+
+        assert!(diff_hunks(old, old).is_empty());
+    }
+
+    #[test]
+    fn diff_hunks_from_empty_are_all_additions() {
+        let hunks = diff_hunks("", "a\nb\n");
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].old_start, hunks[0].old_lines), (0, 0));
+        assert_eq!((hunks[0].new_start, hunks[0].new_lines), (1, 2));
+        assert!(hunks[0]
+            .lines
+            .iter()
+            .all(|line| line.kind == DiffLineKind::Added));
+    }
+
+    #[test]
+    fn identical_contents_have_no_diff() {
+        let result = compare_contents(Some("a\nb\n"), Some("a\nb\n"));
+        assert_eq!(result.status, CompareStatus::Identical);
+        assert!(result.diff.is_none());
+    }
+
+    #[test]
+    fn different_contents_report_summary() {
+        let result = compare_contents(Some("a\nb\nc\n"), Some("a\nx\nc\nd\n"));
+        assert_eq!(result.status, CompareStatus::Different);
+        let diff = result.diff.expect("diff summary");
+        assert_eq!(diff.local_lines, 3);
+        assert_eq!(diff.remote_lines, 4);
+        assert_eq!(diff.added_lines, 2);
+        assert_eq!(diff.removed_lines, 1);
+        assert_eq!(diff.first_difference_line, 2);
+    }
+
+    #[test]
+    fn missing_sides_are_reported() {
+        assert_eq!(
+            compare_contents(None, Some("a")).status,
+            CompareStatus::MissingLocal
+        );
+        assert_eq!(
+            compare_contents(Some("a"), None).status,
+            CompareStatus::MissingRemote
+        );
+        assert_eq!(
+            compare_contents(None, None).status,
+            CompareStatus::MissingBoth
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Per-key write counters. Every bump draws from one process-wide sequence, so
+/// a generation is never reused and the newest of several keys is their `max`.
+/// Keys that were never bumped are at generation 0.
+pub(crate) struct Generations {
+    next: AtomicU64,
+    counters: Mutex<BTreeMap<String, u64>>,
+}
+
+impl Generations {
+    pub(crate) const fn new() -> Self {
+        Self {
+            next: AtomicU64::new(1),
+            counters: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Call after the write to `key` has landed.
+    pub(crate) fn bump(&self, key: &str) -> u64 {
+        let mut counters = self
+            .counters
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let generation = self.next.fetch_add(1, Ordering::SeqCst);
+        counters.insert(key.to_string(), generation);
+        generation
+    }
+
+    /// Call before reading the data a cached value is derived from.
+    pub(crate) fn current<'a>(&self, keys: impl IntoIterator<Item = &'a str>) -> u64 {
+        let counters = self
+            .counters
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        keys.into_iter()
+            .filter_map(|key| counters.get(key).copied())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Cached values tagged with the generation they were computed at.
+pub(crate) struct GenerationCache<V> {
+    entries: HashMap<String, (u64, V)>,
+}
+
+impl<V> Default for GenerationCache<V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<V: Clone> GenerationCache<V> {
+    /// The cached value, unless it predates `generation`.
+    pub(crate) fn get(&self, key: &str, generation: u64) -> Option<V> {
+        self.entries
+            .get(key)
+            .filter(|(cached, _)| *cached == generation)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Drops every entry, for changes the generations do not track, such as a
+    /// scope root override.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Stores `value` computed at `generation`. A slow computation finishing
+    /// after a newer one never replaces the newer value.
+    pub(crate) fn insert(&mut self, key: String, generation: u64, value: V) {
+        match self.entries.get(&key) {
+            Some((cached, _)) if *cached > generation => {}
+            _ => {
+                self.entries.insert(key, (generation, value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GenerationCache, Generations};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    #[test]
+    fn stale_results_never_replace_newer_ones() {
+        let generations = Generations::new();
+        let mut cache = GenerationCache::default();
+        let before = generations.current(["a"]);
+        generations.bump("a");
+        let after = generations.current(["a"]);
+        cache.insert("a".to_string(), after, "new");
+        cache.insert("a".to_string(), before, "old");
+        assert_eq!(cache.get("a", after), Some("new"));
+        assert_eq!(cache.get("a", before), None);
+
+        generations.bump("b");
+        assert!(generations.current(["a", "b"]) > after);
+        assert_eq!(generations.current(["a"]), after);
+    }
+
+    #[test]
+    fn interleaved_writes_and_cached_reads_stay_coherent() {
+        let generations = Arc::new(Generations::new());
+        let cache = Arc::new(Mutex::new(GenerationCache::default()));
+        let source = Arc::new(AtomicU64::new(0));
+
+        let writer = {
+            let generations = generations.clone();
+            let source = source.clone();
+            thread::spawn(move || {
+                for value in 1..=500 {
+                    source.store(value, Ordering::SeqCst);
+                    generations.bump("file");
+                }
+            })
+        };
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let generations = generations.clone();
+                let cache = cache.clone();
+                let source = source.clone();
+                thread::spawn(move || {
+                    for _ in 0..500 {
+                        let generation = generations.current(["file"]);
+                        let cached = cache.lock().unwrap().get("file", generation);
+                        let value = match cached {
+                            Some(value) => value,
+                            None => {
+                                let value = source.load(Ordering::SeqCst);
+                                cache
+                                    .lock()
+                                    .unwrap()
+                                    .insert("file".to_string(), generation, value);
+                                value
+                            }
+                        };
+                        // The single writer bumps once per value, so generation
+                        // `n` means value `n` had been stored.
+                        assert!(value >= generation);
+                    }
+                })
+            })
+            .collect();
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        let generation = generations.current(["file"]);
+        let cached = cache.lock().unwrap().get("file", generation);
+        assert!(matches!(cached, None | Some(500)));
+    }
+}
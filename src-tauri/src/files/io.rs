@@ -1,5 +1,5 @@
-use std::fs::File;
-use std::io::Read;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
@@ -11,6 +11,63 @@ pub(crate) struct TextFileResponse {
     pub truncated: bool,
 }
 
+/// A UTF-8 prefix of a text file that is at most the requested number of bytes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CappedRead {
+    pub(crate) text: String,
+    pub(crate) truncated: bool,
+    pub(crate) total_size: u64,
+}
+
+/// One byte range of a scoped file, base64-encoded so ranges can split UTF-8
+/// sequences.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileRangeResponse {
+    pub(crate) exists: bool,
+    pub(crate) offset: u64,
+    pub(crate) total_bytes: u64,
+    pub(crate) data: String,
+}
+
+/// Whether a scoped file is a symlink and where it points, without reading it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LinkInfo {
+    pub(crate) exists: bool,
+    pub(crate) is_symlink: bool,
+    /// The link target as stored, relative targets unresolved.
+    pub(crate) target: Option<String>,
+    /// The file, or the path the link resolves to, is inside the scope root.
+    /// A link escaping the root is reported here rather than as an error.
+    pub(crate) resolves_within_scope: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+    /// The text has no line breaks.
+    None,
+}
+
+/// A text file split into lines on `\n`, `\r\n` and `\r` alike.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NormalizedLines {
+    pub(crate) exists: bool,
+    pub(crate) lines: Vec<String>,
+    /// The most frequent separator; ties go to `lf`, then `crlf`.
+    pub(crate) line_ending: LineEnding,
+    /// More than one separator style occurs.
+    pub(crate) mixed: bool,
+    /// The text ends with a separator, which adds no empty last line.
+    pub(crate) trailing_newline: bool,
+}
+
 fn missing_response() -> TextFileResponse {
     TextFileResponse {
         exists: false,
@@ -48,21 +105,21 @@ fn resolve_or_create_root(root: &Path, root_context: &str) -> Result<PathBuf, St
     Ok(canonical_root)
 }
 
-pub(crate) fn read_text_file_within(
+fn resolve_existing_file_within(
     root: &Path,
     filename: &str,
     root_may_be_missing: bool,
     root_context: &str,
     file_context: &str,
     allow_external_symlink_target: bool,
-) -> Result<TextFileResponse, String> {
+) -> Result<Option<PathBuf>, String> {
     let Some(canonical_root) = resolve_root(root, root_context, root_may_be_missing)? else {
-        return Ok(missing_response());
+        return Ok(None);
     };
 
     let candidate = canonical_root.join(filename);
     if !candidate.exists() {
-        return Ok(missing_response());
+        return Ok(None);
     }
 
     let candidate_is_symlink = std::fs::symlink_metadata(&candidate)
@@ -77,12 +134,144 @@ pub(crate) fn read_text_file_within(
     {
         return Err(format!("Invalid {file_context} path"));
     }
+    Ok(Some(canonical_path))
+}
+
+fn resolve_write_target_within(
+    root: &Path,
+    filename: &str,
+    create_root: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<PathBuf, String> {
+    let canonical_root = if create_root {
+        resolve_or_create_root(root, root_context)?
+    } else {
+        resolve_root(root, root_context, false)?
+            .ok_or_else(|| format!("Failed to resolve {root_context}"))?
+    };
+
+    let candidate = canonical_root.join(filename);
+    if !candidate.starts_with(&canonical_root) {
+        return Err(format!("Invalid {file_context} path"));
+    }
+
+    if !candidate.exists() {
+        return Ok(candidate);
+    }
+    let candidate_is_symlink = std::fs::symlink_metadata(&candidate)
+        .map_err(|err| format!("Failed to resolve {file_context}: {err}"))?
+        .file_type()
+        .is_symlink();
+    let canonical_path = candidate
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {file_context}: {err}"))?;
+    if !canonical_path.starts_with(&canonical_root)
+        && !(allow_external_symlink_target && candidate_is_symlink)
+    {
+        return Err(format!("Invalid {file_context} path"));
+    }
+    Ok(canonical_path)
+}
+
+pub(crate) fn file_exists_within(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<bool, String> {
+    resolve_existing_file_within(
+        root,
+        filename,
+        root_may_be_missing,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )
+    .map(|path| path.is_some())
+}
+
+/// The file's mtime in Unix millis, or `None` when it does not exist.
+pub(crate) fn file_modified_ms_within(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<Option<u64>, String> {
+    let Some(path) = resolve_existing_file_within(
+        root,
+        filename,
+        root_may_be_missing,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    else {
+        return Ok(None);
+    };
+    let modified = std::fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|err| format!("Failed to read {file_context} mtime: {err}"))?;
+    Ok(Some(
+        modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0),
+    ))
+}
+
+pub(crate) fn read_file_bytes_within(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<Option<Vec<u8>>, String> {
+    let Some(canonical_path) = resolve_existing_file_within(
+        root,
+        filename,
+        root_may_be_missing,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    else {
+        return Ok(None);
+    };
 
     let mut file = File::open(&canonical_path)
         .map_err(|err| format!("Failed to open {file_context}: {err}"))?;
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)
         .map_err(|err| format!("Failed to read {file_context}: {err}"))?;
+    Ok(Some(buffer))
+}
+
+pub(crate) fn read_text_file_within(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<TextFileResponse, String> {
+    let Some(buffer) = read_file_bytes_within(
+        root,
+        filename,
+        root_may_be_missing,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    else {
+        return Ok(missing_response());
+    };
     let content =
         String::from_utf8(buffer).map_err(|_| format!("{file_context} is not valid UTF-8"))?;
 
@@ -93,6 +282,113 @@ pub(crate) fn read_text_file_within(
     })
 }
 
+/// Decodes `bytes`, a prefix of a `total_size`-byte file. When the prefix is
+/// short, a code point split by the cut is dropped rather than rejected.
+fn capped_text(bytes: Vec<u8>, total_size: u64, file_context: &str) -> Result<CappedRead, String> {
+    let truncated = (bytes.len() as u64) < total_size;
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(err) if truncated && err.utf8_error().error_len().is_none() => {
+            let valid_up_to = err.utf8_error().valid_up_to();
+            let mut bytes = err.into_bytes();
+            bytes.truncate(valid_up_to);
+            String::from_utf8(bytes).map_err(|_| format!("{file_context} is not valid UTF-8"))?
+        }
+        Err(_) => return Err(format!("{file_context} is not valid UTF-8")),
+    };
+    Ok(CappedRead {
+        text,
+        truncated,
+        total_size,
+    })
+}
+
+pub(crate) fn split_lines_normalized(text: &str) -> NormalizedLines {
+    let mut lines = Vec::new();
+    let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        let separator_len = match bytes[index] {
+            b'\n' => {
+                lf += 1;
+                1
+            }
+            b'\r' if bytes.get(index + 1) == Some(&b'\n') => {
+                crlf += 1;
+                2
+            }
+            b'\r' => {
+                cr += 1;
+                1
+            }
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+        lines.push(text[start..index].to_string());
+        index += separator_len;
+        start = index;
+    }
+    let trailing_newline = start == text.len() && !lines.is_empty();
+    if !trailing_newline && !text.is_empty() {
+        lines.push(text[start..].to_string());
+    }
+    let line_ending = if lf + crlf + cr == 0 {
+        LineEnding::None
+    } else if lf >= crlf && lf >= cr {
+        LineEnding::Lf
+    } else if crlf >= cr {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Cr
+    };
+    NormalizedLines {
+        exists: true,
+        lines,
+        line_ending,
+        mixed: [lf, crlf, cr].iter().filter(|count| **count > 0).count() > 1,
+        trailing_newline,
+    }
+}
+
+/// Reads at most `max_bytes` of a text file, without loading the rest.
+pub(crate) fn read_text_prefix_within(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+    max_bytes: u64,
+) -> Result<Option<CappedRead>, String> {
+    let Some(canonical_path) = resolve_existing_file_within(
+        root,
+        filename,
+        root_may_be_missing,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    else {
+        return Ok(None);
+    };
+
+    let file = File::open(&canonical_path)
+        .map_err(|err| format!("Failed to open {file_context}: {err}"))?;
+    let total_size = file
+        .metadata()
+        .map_err(|err| format!("Failed to read {file_context}: {err}"))?
+        .len();
+    let mut buffer = Vec::new();
+    file.take(max_bytes)
+        .read_to_end(&mut buffer)
+        .map_err(|err| format!("Failed to read {file_context}: {err}"))?;
+    capped_text(buffer, total_size, file_context).map(Some)
+}
+
 pub(crate) fn write_text_file_within(
     root: &Path,
     filename: &str,
@@ -101,6 +397,187 @@ pub(crate) fn write_text_file_within(
     root_context: &str,
     file_context: &str,
     allow_external_symlink_target: bool,
+) -> Result<(), String> {
+    let target_path = resolve_write_target_within(
+        root,
+        filename,
+        create_root,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?;
+
+    std::fs::write(&target_path, content)
+        .map_err(|err| format!("Failed to write {file_context}: {err}"))
+}
+
+/// `write_text_file_within`, staged in a temp file and renamed over the target
+/// so a crash never leaves it partial. The existing file's permissions are kept.
+pub(crate) fn replace_text_file_within(
+    root: &Path,
+    filename: &str,
+    content: &str,
+    create_root: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<(), String> {
+    let target_path = resolve_write_target_within(
+        root,
+        filename,
+        create_root,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?;
+    replace_resolved_file(&target_path, filename, content.as_bytes(), file_context)
+}
+
+/// Stages `content` next to the resolved `target_path` and renames it over the
+/// target, keeping the existing file's permissions.
+fn replace_resolved_file(
+    target_path: &Path,
+    filename: &str,
+    content: &[u8],
+    file_context: &str,
+) -> Result<(), String> {
+    let temp_path = stage_temp_file(target_path, filename, content, file_context)?;
+    if let Ok(metadata) = std::fs::metadata(target_path) {
+        let _ = std::fs::set_permissions(&temp_path, metadata.permissions());
+    }
+    std::fs::rename(&temp_path, target_path).map_err(|err| {
+        let _ = std::fs::remove_file(&temp_path);
+        format!("Failed to write {file_context}: {err}")
+    })
+}
+
+/// Fails when `filename` under `root` is a symbolic link, for operations that
+/// would replace the link with a regular file.
+pub(crate) fn reject_symlink_within(
+    root: &Path,
+    filename: &str,
+    file_context: &str,
+) -> Result<(), String> {
+    match std::fs::symlink_metadata(root.join(filename)) {
+        Ok(metadata) if metadata.file_type().is_symlink() => Err(format!(
+            "{file_context} is a symbolic link; replace it with a regular file first"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Creates `filename` empty if it is missing, otherwise bumps its modification
+/// time. Returns `true` when the file was created.
+pub(crate) fn touch_file_within(
+    root: &Path,
+    filename: &str,
+    create_root: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<bool, String> {
+    let target_path = resolve_write_target_within(
+        root,
+        filename,
+        create_root,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?;
+
+    if target_path.exists() {
+        OpenOptions::new()
+            .append(true)
+            .open(&target_path)
+            .and_then(|file| file.set_modified(std::time::SystemTime::now()))
+            .map_err(|err| format!("Failed to touch {file_context}: {err}"))?;
+        return Ok(false);
+    }
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&target_path)
+        .map_err(|err| format!("Failed to create {file_context}: {err}"))?;
+    Ok(true)
+}
+
+/// Shrinks `filename`. Emptying it happens in place, so open handles and tail
+/// watchers stay valid. With `keep_last_bytes` the last that many bytes are
+/// kept, minus any partial first line, and staged and renamed over the file
+/// like `replace_text_file_within` so a crash never loses the kept tail.
+/// Returns the number of bytes removed; a missing file removes nothing.
+pub(crate) fn truncate_file_within(
+    root: &Path,
+    filename: &str,
+    keep_last_bytes: Option<u64>,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<u64, String> {
+    let Some(path) = resolve_existing_file_within(
+        root,
+        filename,
+        true,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    else {
+        return Ok(0);
+    };
+    let truncate_err = |err: std::io::Error| format!("Failed to truncate {file_context}: {err}");
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .map_err(truncate_err)?;
+    let len = file.metadata().map_err(truncate_err)?.len();
+    let keep = keep_last_bytes.unwrap_or(0).min(len);
+    let mut tail = Vec::new();
+    if keep > 0 {
+        let start = len - keep;
+        let mut line_start = start == 0;
+        if !line_start {
+            file.seek(SeekFrom::Start(start - 1))
+                .map_err(truncate_err)?;
+            let mut previous = [0u8; 1];
+            file.read_exact(&mut previous).map_err(truncate_err)?;
+            line_start = previous[0] == b'\n';
+        }
+        file.read_to_end(&mut tail).map_err(truncate_err)?;
+        if !line_start {
+            let cut = tail
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .map_or(tail.len(), |index| index + 1);
+            tail.drain(..cut);
+        }
+    }
+    let removed = len - tail.len() as u64;
+    if removed == 0 {
+        return Ok(0);
+    }
+    if tail.is_empty() {
+        file.set_len(0).map_err(truncate_err)?;
+    } else {
+        drop(file);
+        replace_resolved_file(&path, filename, &tail, file_context)?;
+    }
+    Ok(removed)
+}
+
+/// Makes `filename` under `root` a symlink to the existing file `target`,
+/// replacing an earlier symlink but never a regular file. Relative targets are
+/// resolved against the root; targets outside it need
+/// `allow_external_symlink_target`.
+pub(crate) fn create_symlink_within(
+    root: &Path,
+    filename: &str,
+    target: &Path,
+    create_root: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
 ) -> Result<(), String> {
     let canonical_root = if create_root {
         resolve_or_create_root(root, root_context)?
@@ -108,32 +585,651 @@ pub(crate) fn write_text_file_within(
         resolve_root(root, root_context, false)?
             .ok_or_else(|| format!("Failed to resolve {root_context}"))?
     };
+    let link_path = canonical_root.join(filename);
+    if !link_path.starts_with(&canonical_root) {
+        return Err(format!("Invalid {file_context} path"));
+    }
+
+    let canonical_target = canonical_root
+        .join(target)
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve symlink target for {file_context}: {err}"))?;
+    if !canonical_target.is_file() {
+        return Err(format!("Symlink target for {file_context} is not a file"));
+    }
+    if canonical_target == link_path {
+        return Err(format!("{file_context} cannot link to itself"));
+    }
+    if !canonical_target.starts_with(&canonical_root) && !allow_external_symlink_target {
+        return Err(format!(
+            "Symlink target for {file_context} must stay within {root_context}"
+        ));
+    }
+
+    match std::fs::symlink_metadata(&link_path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => std::fs::remove_file(&link_path)
+            .map_err(|err| format!("Failed to replace {file_context} symlink: {err}"))?,
+        Ok(_) => {
+            return Err(format!(
+                "{file_context} already exists as a regular file; remove it first"
+            ))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(format!("Failed to resolve {file_context}: {err}")),
+    }
+    symlink_file(&canonical_target, &link_path).map_err(|err| symlink_error(file_context, err))
+}
+
+#[cfg(unix)]
+fn symlink_file(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink_file(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink_file(_target: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "symlinks are not supported on this platform",
+    ))
+}
+
+/// Windows refuses symlinks without Developer Mode or the privilege to create
+/// them (`ERROR_PRIVILEGE_NOT_HELD`); say so instead of "access denied".
+fn symlink_error(file_context: &str, err: std::io::Error) -> String {
+    const ERROR_PRIVILEGE_NOT_HELD: i32 = 1314;
+    if cfg!(windows)
+        && (err.raw_os_error() == Some(ERROR_PRIVILEGE_NOT_HELD)
+            || err.kind() == std::io::ErrorKind::PermissionDenied)
+    {
+        return format!(
+            "Failed to create {file_context} symlink: this system does not allow creating \
+             symlinks (enable Developer Mode or run with symlink privileges)"
+        );
+    }
+    format!("Failed to create {file_context} symlink: {err}")
+}
+
+/// Whether `file_mode_within` and `set_file_mode_within` can work here; Windows
+/// has no Unix permission bits.
+pub(crate) const PERMISSION_BITS_SUPPORTED: bool = cfg!(unix);
 
+/// Inspects `filename` under `root` with `symlink_metadata` and `read_link`;
+/// the target's content is never opened. A dangling link resolves lexically.
+pub(crate) fn link_info_within(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+) -> Result<LinkInfo, String> {
+    let missing = LinkInfo {
+        exists: false,
+        is_symlink: false,
+        target: None,
+        resolves_within_scope: true,
+    };
+    let Some(canonical_root) = resolve_root(root, root_context, root_may_be_missing)? else {
+        return Ok(missing);
+    };
     let candidate = canonical_root.join(filename);
-    if !candidate.starts_with(&canonical_root) {
-        return Err(format!("Invalid {file_context} path"));
+    let metadata = match std::fs::symlink_metadata(&candidate) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(missing),
+        Err(err) => return Err(format!("Failed to inspect {file_context}: {err}")),
+    };
+    if !metadata.file_type().is_symlink() {
+        return Ok(LinkInfo {
+            exists: true,
+            ..missing
+        });
+    }
+    let target = std::fs::read_link(&candidate)
+        .map_err(|err| format!("Failed to read {file_context} link: {err}"))?;
+    let resolved = candidate.canonicalize().unwrap_or_else(|_| {
+        let parent = candidate.parent().unwrap_or(&canonical_root);
+        normalize_lexically(&parent.join(&target))
+    });
+    Ok(LinkInfo {
+        exists: candidate.exists(),
+        is_symlink: true,
+        target: Some(target.to_string_lossy().to_string()),
+        resolves_within_scope: resolved.starts_with(&canonical_root),
+    })
+}
+
+/// Drops `.` and folds `..` without touching the filesystem.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// The permission bits of `filename` under `root`, following an allowed
+/// symlink to its target; `None` when the file is missing.
+pub(crate) fn file_mode_within(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<Option<u32>, String> {
+    let Some(path) = resolve_existing_file_within(
+        root,
+        filename,
+        root_may_be_missing,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    else {
+        return Ok(None);
+    };
+    file_mode(&path)
+        .map(Some)
+        .map_err(|err| format!("Failed to read permissions of {file_context}: {err}"))
+}
+
+/// Sets the permission bits of an existing `filename` to `mode`; returns
+/// whether they changed.
+pub(crate) fn set_file_mode_within(
+    root: &Path,
+    filename: &str,
+    mode: u32,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<bool, String> {
+    let path = resolve_existing_file_within(
+        root,
+        filename,
+        false,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    .ok_or_else(|| format!("{file_context} does not exist"))?;
+    let current = file_mode(&path)
+        .map_err(|err| format!("Failed to read permissions of {file_context}: {err}"))?;
+    if current == mode {
+        return Ok(false);
+    }
+    set_file_mode(&path, mode)
+        .map_err(|err| format!("Failed to change permissions of {file_context}: {err}"))?;
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn file_mode(path: &Path) -> std::io::Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(std::fs::metadata(path)?.permissions().mode() & 0o777)
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> std::io::Result<u32> {
+    Err(permission_bits_unsupported())
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Err(permission_bits_unsupported())
+}
+
+#[cfg(not(unix))]
+fn permission_bits_unsupported() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "file permission bits are not supported on this platform",
+    )
+}
+
+/// Reports whether `filename` under `root` could be written right now, without
+/// creating the root, the file or any probe: the OS is asked through `may_write`.
+/// Permission problems yield `false`; paths that escape the root are still
+/// errors.
+pub(crate) fn probe_writable_within(
+    root: &Path,
+    filename: &str,
+    create_root: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<bool, String> {
+    if !root.exists() {
+        if !create_root {
+            return Ok(false);
+        }
+        let Some(ancestor) = root.ancestors().find(|ancestor| ancestor.is_dir()) else {
+            return Ok(false);
+        };
+        return Ok(may_write(ancestor));
     }
 
-    let target_path = if candidate.exists() {
-        let candidate_is_symlink = std::fs::symlink_metadata(&candidate)
-            .map_err(|err| format!("Failed to resolve {file_context}: {err}"))?
-            .file_type()
-            .is_symlink();
-        let canonical_path = candidate
-            .canonicalize()
-            .map_err(|err| format!("Failed to resolve {file_context}: {err}"))?;
-        if !canonical_path.starts_with(&canonical_root)
-            && !(allow_external_symlink_target && candidate_is_symlink)
-        {
-            return Err(format!("Invalid {file_context} path"));
+    let existing = resolve_existing_file_within(
+        root,
+        filename,
+        false,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?;
+    match existing {
+        Some(path) => Ok(may_write(&path)),
+        None => {
+            let canonical_root = root
+                .canonicalize()
+                .map_err(|err| format!("Failed to resolve {root_context}: {err}"))?;
+            Ok(may_write(&canonical_root))
         }
-        canonical_path
+    }
+}
+
+/// Whether the current user may write the file at `path`, or create entries in
+/// the directory at `path`, per `access(2)`.
+#[cfg(unix)]
+fn may_write(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mode = if path.is_dir() {
+        libc::W_OK | libc::X_OK
     } else {
-        candidate
+        libc::W_OK
     };
+    unsafe { libc::access(c_path.as_ptr(), mode) == 0 }
+}
 
-    std::fs::write(&target_path, content)
-        .map_err(|err| format!("Failed to write {file_context}: {err}"))
+/// Elsewhere only the read-only attribute is checked, which directories ignore.
+#[cfg(not(unix))]
+fn may_write(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .map(|metadata| !metadata.permissions().readonly())
+        .unwrap_or(false)
+}
+
+/// Removes the probe file on drop unless it was already removed.
+struct ProbeFile<'a> {
+    path: &'a Path,
+    removed: bool,
+}
+
+impl Drop for ProbeFile<'_> {
+    fn drop(&mut self) {
+        if !self.removed {
+            let _ = std::fs::remove_file(self.path);
+        }
+    }
+}
+
+/// Creates, writes, reads back and deletes a probe file directly in `root`.
+/// Each step fails with its own error; the probe is removed even when a later
+/// step fails.
+pub(crate) fn write_probe_within(root: &Path, root_context: &str) -> Result<(), String> {
+    if !root.exists() {
+        return Err(format!("{root_context} {} does not exist", root.display()));
+    }
+    let root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {root_context}: {err}"))?;
+    if !root.is_dir() {
+        return Err(format!(
+            "{root_context} {} is not a directory",
+            root.display()
+        ));
+    }
+    let path = root.join(format!(
+        ".codex-monitor-write-test.{}.probe",
+        uuid::Uuid::new_v4()
+    ));
+    let payload = b"codex-monitor write test\n";
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|err| format!("Failed to create probe file in {}: {err}", root.display()))?;
+    let mut probe = ProbeFile {
+        path: &path,
+        removed: false,
+    };
+    file.write_all(payload)
+        .and_then(|_| file.sync_all())
+        .map_err(|err| format!("Failed to write probe file {}: {err}", path.display()))?;
+    drop(file);
+    let read_back = std::fs::read(&path)
+        .map_err(|err| format!("Failed to read probe file {}: {err}", path.display()))?;
+    if read_back != payload {
+        return Err(format!(
+            "Probe file {} did not read back what was written",
+            path.display()
+        ));
+    }
+    std::fs::remove_file(&path)
+        .map_err(|err| format!("Failed to remove probe file {}: {err}", path.display()))?;
+    probe.removed = true;
+    Ok(())
+}
+
+/// Location of one side of a scoped file copy or swap.
+pub(crate) struct FileLocation<'a> {
+    pub(crate) root: &'a Path,
+    pub(crate) filename: &'a str,
+    pub(crate) root_context: &'a str,
+    pub(crate) file_context: &'a str,
+    pub(crate) allow_external_symlink_target: bool,
+}
+
+/// Streams `source` into `target` through a temp file in the target directory,
+/// then renames it into place so readers never observe a partial copy.
+pub(crate) fn copy_file_within(
+    source: FileLocation<'_>,
+    source_root_may_be_missing: bool,
+    target: FileLocation<'_>,
+    create_target_root: bool,
+) -> Result<u64, String> {
+    let source_path = resolve_existing_file_within(
+        source.root,
+        source.filename,
+        source_root_may_be_missing,
+        source.root_context,
+        source.file_context,
+        source.allow_external_symlink_target,
+    )?
+    .ok_or_else(|| format!("{} does not exist", source.file_context))?;
+    let target_path = resolve_write_target_within(
+        target.root,
+        target.filename,
+        create_target_root,
+        target.root_context,
+        target.file_context,
+        target.allow_external_symlink_target,
+    )?;
+    if source_path == target_path {
+        return Err(format!("Cannot copy {} onto itself", source.file_context));
+    }
+
+    let target_dir = target_path
+        .parent()
+        .ok_or_else(|| format!("Invalid {} path", target.file_context))?;
+    let temp_path = target_dir.join(format!(".{}.{}.tmp", target.filename, uuid::Uuid::new_v4()));
+
+    let result = (|| -> Result<u64, String> {
+        let mut reader = File::open(&source_path)
+            .map_err(|err| format!("Failed to open {}: {err}", source.file_context))?;
+        let mut writer = File::create(&temp_path)
+            .map_err(|err| format!("Failed to write {}: {err}", target.file_context))?;
+        let copied = std::io::copy(&mut reader, &mut writer)
+            .map_err(|err| format!("Failed to copy {}: {err}", source.file_context))?;
+        writer
+            .sync_all()
+            .map_err(|err| format!("Failed to write {}: {err}", target.file_context))?;
+        std::fs::rename(&temp_path, &target_path)
+            .map_err(|err| format!("Failed to write {}: {err}", target.file_context))?;
+        Ok(copied)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Writes `content` to a synced temp file beside `target_path`, ready to be
+/// renamed over it.
+fn stage_temp_file(
+    target_path: &Path,
+    filename: &str,
+    content: &[u8],
+    file_context: &str,
+) -> Result<PathBuf, String> {
+    let target_dir = target_path
+        .parent()
+        .ok_or_else(|| format!("Invalid {file_context} path"))?;
+    let temp_path = target_dir.join(format!(".{filename}.{}.tmp", uuid::Uuid::new_v4()));
+    let result = (|| -> Result<(), String> {
+        let mut writer = File::create(&temp_path)
+            .map_err(|err| format!("Failed to write {file_context}: {err}"))?;
+        writer
+            .write_all(content)
+            .and_then(|_| writer.sync_all())
+            .map_err(|err| format!("Failed to write {file_context}: {err}"))
+    })();
+    match result {
+        Ok(()) => Ok(temp_path),
+        Err(err) => {
+            let _ = std::fs::remove_file(&temp_path);
+            Err(err)
+        }
+    }
+}
+
+/// Replaces `a` with `a_content` and `b` with `b_content`. Both files must
+/// exist. Each side is staged in a temp file and renamed into place, so neither
+/// file is ever missing or partial; if the second rename fails, `a` is put back.
+pub(crate) fn swap_file_contents_within(
+    a: FileLocation<'_>,
+    a_content: &str,
+    b: FileLocation<'_>,
+    b_content: &str,
+) -> Result<(), String> {
+    let resolve = |location: &FileLocation<'_>| -> Result<PathBuf, String> {
+        resolve_existing_file_within(
+            location.root,
+            location.filename,
+            false,
+            location.root_context,
+            location.file_context,
+            location.allow_external_symlink_target,
+        )?
+        .ok_or_else(|| format!("{} does not exist", location.file_context))
+    };
+    let a_path = resolve(&a)?;
+    let b_path = resolve(&b)?;
+    if a_path == b_path {
+        return Err(format!("Cannot swap {} with itself", a.file_context));
+    }
+    let a_original = std::fs::read(&a_path)
+        .map_err(|err| format!("Failed to read {}: {err}", a.file_context))?;
+
+    let a_temp = stage_temp_file(&a_path, a.filename, a_content.as_bytes(), a.file_context)?;
+    let b_temp = match stage_temp_file(&b_path, b.filename, b_content.as_bytes(), b.file_context) {
+        Ok(path) => path,
+        Err(err) => {
+            let _ = std::fs::remove_file(&a_temp);
+            return Err(err);
+        }
+    };
+    if let Err(err) = std::fs::rename(&a_temp, &a_path) {
+        let _ = std::fs::remove_file(&a_temp);
+        let _ = std::fs::remove_file(&b_temp);
+        return Err(format!("Failed to write {}: {err}", a.file_context));
+    }
+    if let Err(err) = std::fs::rename(&b_temp, &b_path) {
+        let _ = std::fs::remove_file(&b_temp);
+        if let Ok(restore) = stage_temp_file(&a_path, a.filename, &a_original, a.file_context) {
+            if std::fs::rename(&restore, &a_path).is_err() {
+                let _ = std::fs::remove_file(&restore);
+            }
+        }
+        return Err(format!("Failed to write {}: {err}", b.file_context));
+    }
+    Ok(())
+}
+
+/// Deletes `filename` under `root`; a symlink is removed, not its target.
+/// Returns `false` when there was nothing to delete.
+pub(crate) fn remove_file_within(
+    root: &Path,
+    filename: &str,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<bool, String> {
+    let existing = resolve_existing_file_within(
+        root,
+        filename,
+        true,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?;
+    if existing.is_none() {
+        return Ok(false);
+    }
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {root_context}: {err}"))?;
+    std::fs::remove_file(canonical_root.join(filename))
+        .map_err(|err| format!("Failed to delete {file_context}: {err}"))?;
+    Ok(true)
+}
+
+fn validate_plain_filename(filename: &str) -> Result<(), String> {
+    if filename.is_empty() || filename == "." || filename == ".." || filename.contains(['/', '\\'])
+    {
+        return Err(format!("Invalid filename `{filename}`"));
+    }
+    Ok(())
+}
+
+/// Renames `old_filename` to `new_filename` inside `root`. Returns `false` when
+/// there is nothing to move; refuses to overwrite an existing `new_filename`.
+pub(crate) fn rename_file_within(
+    root: &Path,
+    old_filename: &str,
+    new_filename: &str,
+    root_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<bool, String> {
+    let Some((source, target)) = plan_rename_within(
+        root,
+        old_filename,
+        new_filename,
+        root_context,
+        allow_external_symlink_target,
+    )?
+    else {
+        return Ok(false);
+    };
+    std::fs::rename(source, target)
+        .map_err(|err| format!("Failed to rename {old_filename} to {new_filename}: {err}"))?;
+    Ok(true)
+}
+
+/// The source and target `rename_file_within` would move between, checked
+/// without touching either; `None` when there is nothing to move.
+pub(crate) fn plan_rename_within(
+    root: &Path,
+    old_filename: &str,
+    new_filename: &str,
+    root_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<Option<(PathBuf, PathBuf)>, String> {
+    validate_plain_filename(old_filename)?;
+    validate_plain_filename(new_filename)?;
+    let existing = resolve_existing_file_within(
+        root,
+        old_filename,
+        false,
+        root_context,
+        old_filename,
+        allow_external_symlink_target,
+    )?;
+    if existing.is_none() {
+        return Ok(None);
+    }
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {root_context}: {err}"))?;
+    let target = canonical_root.join(new_filename);
+    if std::fs::symlink_metadata(&target).is_ok() {
+        return Err(format!(
+            "Both {old_filename} and {new_filename} exist in {root_context}"
+        ));
+    }
+    Ok(Some((canonical_root.join(old_filename), target)))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DirUsage {
+    pub(crate) bytes: u64,
+    pub(crate) files: u64,
+    pub(crate) skipped: u32,
+}
+
+/// Sums regular file sizes under `root` without following symlinks that leave it.
+/// Missing roots report zero usage; unreadable entries are counted in `skipped`.
+pub(crate) fn measure_dir_usage(root: &Path, recursive: bool) -> DirUsage {
+    let mut usage = DirUsage::default();
+    let Ok(canonical_root) = root.canonicalize() else {
+        if root.exists() {
+            usage.skipped += 1;
+        }
+        return usage;
+    };
+
+    let mut pending = vec![canonical_root.clone()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            usage.skipped += 1;
+            continue;
+        };
+        for entry in entries {
+            let Ok(entry) = entry else {
+                usage.skipped += 1;
+                continue;
+            };
+            let path = entry.path();
+            let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+                usage.skipped += 1;
+                continue;
+            };
+            if metadata.file_type().is_symlink() {
+                let Ok(target) = path.canonicalize() else {
+                    usage.skipped += 1;
+                    continue;
+                };
+                if !target.starts_with(&canonical_root) || !target.is_file() {
+                    continue;
+                }
+                match std::fs::metadata(&target) {
+                    Ok(target_metadata) => {
+                        usage.bytes += target_metadata.len();
+                        usage.files += 1;
+                    }
+                    Err(_) => usage.skipped += 1,
+                }
+            } else if metadata.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else if metadata.is_file() {
+                usage.bytes += metadata.len();
+                usage.files += 1;
+            }
+        }
+    }
+    usage
 }
 
 #[cfg(test)]
@@ -146,6 +1242,207 @@ mod tests {
         std::env::temp_dir().join(format!("codex-monitor-file-io-{}", Uuid::new_v4()))
     }
 
+    #[test]
+    fn split_lines_normalized_handles_each_separator_style() {
+        for (text, ending) in [
+            ("a\nb\n", LineEnding::Lf),
+            ("a\r\nb\r\n", LineEnding::Crlf),
+            ("a\rb\r", LineEnding::Cr),
+        ] {
+            let split = split_lines_normalized(text);
+            assert_eq!(split.lines, vec!["a", "b"], "{text:?}");
+            assert_eq!(split.line_ending, ending);
+            assert!(split.trailing_newline);
+            assert!(!split.mixed);
+        }
+
+        let single = split_lines_normalized("only");
+        assert_eq!(single.lines, vec!["only"]);
+        assert_eq!(single.line_ending, LineEnding::None);
+        assert!(!single.trailing_newline);
+        assert!(split_lines_normalized("").lines.is_empty());
+        assert_eq!(split_lines_normalized("\n\n").lines, vec!["", ""]);
+    }
+
+    #[test]
+    fn split_lines_normalized_reports_dominant_ending_of_mixed_files() {
+        let split = split_lines_normalized("a\r\nb\r\nc\nd\re");
+        assert_eq!(split.lines, vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(split.line_ending, LineEnding::Crlf);
+        assert!(split.mixed);
+        assert!(!split.trailing_newline);
+
+        let tie = split_lines_normalized("a\r\nb\n");
+        assert_eq!(tie.line_ending, LineEnding::Lf);
+        assert!(tie.lines.iter().all(|line| !line.contains('\r')));
+    }
+
+    #[test]
+    fn truncate_keeps_whole_trailing_lines_and_empties_in_place() {
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        let path = root.join("monitor.log");
+        std::fs::write(&path, "first\nsecond\nthird\n").expect("write log");
+
+        // The last 9 bytes are "nd\nthird\n"; the partial line is dropped.
+        let removed =
+            truncate_file_within(&root, "monitor.log", Some(9), "root", "log", false).unwrap();
+        assert_eq!(removed, 13);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "third\n");
+        // Exactly on a line boundary keeps that line.
+        let removed =
+            truncate_file_within(&root, "monitor.log", Some(6), "root", "log", false).unwrap();
+        assert_eq!(removed, 0);
+
+        let mut handle = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("open handle");
+        let removed =
+            truncate_file_within(&root, "monitor.log", None, "root", "log", false).unwrap();
+        assert_eq!(removed, 6);
+        handle
+            .write_all(b"after\n")
+            .expect("write through old handle");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "after\n");
+        assert_eq!(
+            truncate_file_within(&root, "missing.log", None, "root", "log", false).unwrap(),
+            0
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn write_probe_cleans_up_and_reports_missing_roots() {
+        let root = temp_dir();
+        let err = write_probe_within(&root, "workspace root").unwrap_err();
+        assert!(err.contains("does not exist"));
+
+        std::fs::create_dir_all(&root).expect("create root");
+        write_probe_within(&root, "workspace root").expect("probe");
+        assert_eq!(std::fs::read_dir(&root).expect("read root").count(), 0);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn probe_writable_handles_missing_and_existing_files() {
+        let root = temp_dir();
+        assert!(
+            probe_writable_within(&root, "AGENTS.md", true, "CODEX_HOME", "AGENTS.md", false)
+                .expect("probe")
+        );
+        assert!(!probe_writable_within(
+            &root,
+            "AGENTS.md",
+            false,
+            "workspace root",
+            "AGENTS.md",
+            false
+        )
+        .expect("probe"));
+        assert!(!root.exists(), "probing must not create the root");
+
+        std::fs::create_dir_all(&root).expect("create root");
+        assert!(probe_writable_within(
+            &root,
+            "AGENTS.md",
+            false,
+            "workspace root",
+            "AGENTS.md",
+            false
+        )
+        .expect("probe"));
+        let entries = std::fs::read_dir(&root).expect("read dir").count();
+        assert_eq!(entries, 0, "probing must not create files");
+
+        std::fs::write(root.join("AGENTS.md"), "hello").expect("write file");
+        assert!(probe_writable_within(
+            &root,
+            "AGENTS.md",
+            false,
+            "workspace root",
+            "AGENTS.md",
+            false
+        )
+        .expect("probe"));
+        let entries = std::fs::read_dir(&root).expect("read dir").count();
+        assert_eq!(entries, 1, "probing must not create files");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn file_exists_tracks_external_deletion() {
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join("AGENTS.md"), "hello").expect("write file");
+        assert!(
+            file_exists_within(&root, "AGENTS.md", true, "CODEX_HOME", "AGENTS.md", false)
+                .expect("exists")
+        );
+        std::fs::remove_file(root.join("AGENTS.md")).expect("remove file");
+        assert!(
+            !file_exists_within(&root, "AGENTS.md", true, "CODEX_HOME", "AGENTS.md", false)
+                .expect("exists")
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn rename_moves_file_once_and_never_overwrites() {
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join("CODEX.md"), "keep me").expect("write file");
+
+        assert!(
+            rename_file_within(&root, "CODEX.md", "AGENTS.md", "workspace root", false)
+                .expect("rename")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("AGENTS.md")).expect("read"),
+            "keep me"
+        );
+        assert!(
+            !rename_file_within(&root, "CODEX.md", "AGENTS.md", "workspace root", false)
+                .expect("second rename is a no-op")
+        );
+
+        std::fs::write(root.join("CODEX.md"), "other").expect("write file");
+        assert!(
+            rename_file_within(&root, "CODEX.md", "AGENTS.md", "workspace root", false).is_err()
+        );
+        assert!(
+            rename_file_within(&root, "../CODEX.md", "AGENTS.md", "workspace root", false).is_err()
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn touch_creates_once_and_keeps_content() {
+        let root = temp_dir();
+        assert!(
+            touch_file_within(&root, "AGENTS.md", true, "CODEX_HOME", "AGENTS.md", false)
+                .expect("touch")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("AGENTS.md")).expect("read"),
+            ""
+        );
+        std::fs::write(root.join("AGENTS.md"), "hello").expect("write file");
+        assert!(
+            !touch_file_within(&root, "AGENTS.md", true, "CODEX_HOME", "AGENTS.md", false)
+                .expect("touch")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("AGENTS.md")).expect("read"),
+            "hello"
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn read_returns_missing_when_root_absent() {
         let root = temp_dir();
@@ -156,6 +1453,37 @@ mod tests {
         assert!(response.content.is_empty());
     }
 
+    #[test]
+    fn capped_read_cuts_at_utf8_boundary() {
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join("AGENTS.md"), "ab€cd").expect("write file");
+
+        let read = |max_bytes| {
+            read_text_prefix_within(
+                &root,
+                "AGENTS.md",
+                false,
+                "CODEX_HOME",
+                "AGENTS.md",
+                false,
+                max_bytes,
+            )
+            .expect("read should succeed")
+            .expect("file exists")
+        };
+        let cut = read(3);
+        assert_eq!(cut.text, "ab");
+        assert!(cut.truncated);
+        assert_eq!(cut.total_size, 7);
+        assert_eq!(read(5).text, "ab€");
+        let whole = read(64);
+        assert_eq!(whole.text, "ab€cd");
+        assert!(!whole.truncated);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn write_creates_root_and_round_trips() {
         let root = temp_dir();
@@ -176,6 +1504,43 @@ mod tests {
         assert_eq!(response.content, "hello");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn link_info_reports_targets_without_rejecting_escapes() {
+        use std::os::unix::fs::symlink;
+
+        let root = temp_dir();
+        let outside = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::create_dir_all(&outside).expect("create outside");
+        let info = |filename: &str| {
+            link_info_within(&root, filename, false, "workspace root", filename).expect("link info")
+        };
+
+        assert!(!info("AGENTS.md").exists);
+        std::fs::write(root.join("AGENTS.md"), "local").expect("write file");
+        let plain = info("AGENTS.md");
+        assert!(plain.exists && !plain.is_symlink && plain.resolves_within_scope);
+
+        symlink("AGENTS.md", root.join("inner.md")).expect("inner link");
+        let inner = info("inner.md");
+        assert!(inner.is_symlink && inner.resolves_within_scope);
+        assert_eq!(inner.target.as_deref(), Some("AGENTS.md"));
+
+        let outside_file = outside.join("AGENTS.md");
+        std::fs::write(&outside_file, "outside").expect("write outside");
+        symlink(&outside_file, root.join("escape.md")).expect("escape link");
+        let escape = info("escape.md");
+        assert!(escape.exists && escape.is_symlink && !escape.resolves_within_scope);
+
+        symlink("../gone.md", root.join("dangling.md")).expect("dangling link");
+        let dangling = info("dangling.md");
+        assert!(!dangling.exists && dangling.is_symlink && !dangling.resolves_within_scope);
+
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&outside);
+    }
+
     #[cfg(unix)]
     #[test]
     fn write_rejects_symlink_escape() {
@@ -314,4 +1679,197 @@ mod tests {
         .expect_err("should reject symlink escape");
         assert!(error.contains("Invalid config.toml path"));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_write_links_within_policy_and_reads_through() {
+        let root = temp_dir();
+        let outside = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::create_dir_all(&outside).expect("create outside");
+        std::fs::write(root.join("canonical.md"), "inside").expect("seed inside");
+        std::fs::write(outside.join("shared.md"), "outside").expect("seed outside");
+
+        let create = |target: &Path, allow_external: bool| {
+            create_symlink_within(
+                &root,
+                "AGENTS.md",
+                target,
+                false,
+                "workspace root",
+                "AGENTS.md",
+                allow_external,
+            )
+        };
+        create(Path::new("canonical.md"), false).expect("link within root");
+        let read = read_text_file_within(&root, "AGENTS.md", false, "root", "AGENTS.md", false)
+            .expect("read through link");
+        assert_eq!(read.content, "inside");
+
+        let error = create(&outside.join("shared.md"), false).expect_err("escape rejected");
+        assert!(error.contains("must stay within workspace root"));
+        create(&outside.join("shared.md"), true).expect("replace link");
+        assert_eq!(
+            std::fs::read_to_string(root.join("AGENTS.md")).expect("read"),
+            "outside"
+        );
+        assert!(create(Path::new("missing.md"), true).is_err());
+
+        std::fs::remove_file(root.join("AGENTS.md")).expect("remove link");
+        std::fs::write(root.join("AGENTS.md"), "regular").expect("seed regular");
+        let error = create(Path::new("canonical.md"), false).expect_err("regular kept");
+        assert!(error.contains("regular file"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_mode_reads_and_restricts_permission_bits() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        let path = root.join("config.toml");
+        std::fs::write(&path, "token = \"x\"").expect("seed");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).expect("chmod");
+
+        let mode = |filename| {
+            file_mode_within(&root, filename, false, "CODEX_HOME", filename, false).expect("mode")
+        };
+        assert_eq!(mode("config.toml"), Some(0o644));
+        assert_eq!(mode("missing.toml"), None);
+
+        let set = || set_file_mode_within(&root, "config.toml", 0o600, "root", "f", false);
+        assert_eq!(set(), Ok(true));
+        assert_eq!(set(), Ok(false));
+        assert_eq!(mode("config.toml"), Some(0o600));
+    }
+
+    // `sha2` is only a dependency off mobile, where dictation uses it.
+    #[cfg(not(any(target_os = "ios", target_os = "android")))]
+    #[test]
+    fn copy_streams_large_file_with_identical_hash() {
+        use sha2::{Digest, Sha256};
+
+        let source_root = temp_dir();
+        let target_root = temp_dir();
+        std::fs::create_dir_all(&source_root).expect("create source root");
+
+        let chunk: Vec<u8> = (0..=255u8).cycle().take(64 * 1024).collect();
+        let mut payload = Vec::with_capacity(8 * 1024 * 1024);
+        while payload.len() < 8 * 1024 * 1024 {
+            payload.extend_from_slice(&chunk);
+        }
+        std::fs::write(source_root.join("AGENTS.md"), &payload).expect("seed source file");
+
+        let copied = copy_file_within(
+            FileLocation {
+                root: &source_root,
+                filename: "AGENTS.md",
+                root_context: "workspace root",
+                file_context: "AGENTS.md",
+                allow_external_symlink_target: false,
+            },
+            false,
+            FileLocation {
+                root: &target_root,
+                filename: "AGENTS.md",
+                root_context: "CODEX_HOME",
+                file_context: "AGENTS.md",
+                allow_external_symlink_target: true,
+            },
+            true,
+        )
+        .expect("copy should succeed");
+        assert_eq!(copied, payload.len() as u64);
+
+        let copied_bytes = std::fs::read(target_root.join("AGENTS.md")).expect("read target");
+        assert_eq!(
+            Sha256::digest(&copied_bytes),
+            Sha256::digest(&payload),
+            "copied content hash should match source"
+        );
+        let leftovers = std::fs::read_dir(&target_root)
+            .expect("list target root")
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        let _ = std::fs::remove_dir_all(&source_root);
+        let _ = std::fs::remove_dir_all(&target_root);
+    }
+
+    #[test]
+    fn copy_fails_when_source_missing() {
+        let source_root = temp_dir();
+        let target_root = temp_dir();
+        std::fs::create_dir_all(&source_root).expect("create source root");
+
+        let error = copy_file_within(
+            FileLocation {
+                root: &source_root,
+                filename: "AGENTS.md",
+                root_context: "workspace root",
+                file_context: "AGENTS.md",
+                allow_external_symlink_target: false,
+            },
+            false,
+            FileLocation {
+                root: &target_root,
+                filename: "AGENTS.md",
+                root_context: "CODEX_HOME",
+                file_context: "AGENTS.md",
+                allow_external_symlink_target: true,
+            },
+            true,
+        )
+        .expect_err("missing source should fail");
+        assert!(error.contains("does not exist"));
+
+        let _ = std::fs::remove_dir_all(&source_root);
+    }
+
+    #[test]
+    fn measure_dir_usage_respects_recursive_flag() {
+        let root = temp_dir();
+        std::fs::create_dir_all(root.join("nested")).expect("create nested dir");
+        std::fs::write(root.join("AGENTS.md"), "12345").expect("write top-level file");
+        std::fs::write(root.join("nested").join("notes.md"), "123").expect("write nested file");
+
+        let shallow = measure_dir_usage(&root, false);
+        assert_eq!(shallow.bytes, 5);
+        assert_eq!(shallow.files, 1);
+
+        let deep = measure_dir_usage(&root, true);
+        assert_eq!(deep.bytes, 8);
+        assert_eq!(deep.files, 2);
+        assert_eq!(deep.skipped, 0);
+
+        let missing = measure_dir_usage(&temp_dir(), true);
+        assert_eq!(missing, DirUsage::default());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn measure_dir_usage_ignores_symlinks_out_of_root() {
+        use std::os::unix::fs::symlink;
+
+        let root = temp_dir();
+        let outside = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::create_dir_all(&outside).expect("create outside");
+        std::fs::write(outside.join("big.bin"), vec![0u8; 1024]).expect("seed outside file");
+        symlink(outside.join("big.bin"), root.join("linked.bin")).expect("create file symlink");
+        symlink(&outside, root.join("linked-dir")).expect("create dir symlink");
+        std::fs::write(root.join("AGENTS.md"), "abc").expect("write local file");
+
+        let usage = measure_dir_usage(&root, true);
+        assert_eq!(usage.bytes, 3);
+        assert_eq!(usage.files, 1);
+
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&outside);
+    }
 }
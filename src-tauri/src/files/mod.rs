@@ -1,17 +1,66 @@
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
 use std::path::PathBuf;
-use tauri::{AppHandle, State};
+use std::process::Stdio;
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
 
-use self::io::TextFileResponse;
-use self::policy::{FileKind, FileScope};
+use self::archive::ArchiveEntry;
+use self::compare::{compare_contents, CompareResult, DiffHunk};
+use self::io::{CappedRead, LinkInfo, NormalizedLines, TextFileResponse};
+use self::ops::WorkspaceInitReport;
+use self::policy::{FileKind, FileScope, PolicyExplanation, ScopedFileRef};
+use self::search::SearchHit;
+use self::template::{defaults_catalog as builtin_defaults_catalog, DefaultInfo, RenderedTemplate};
+use self::validate::{validate_kind_content, ValidationIssue};
+use crate::backend::events::OperationReporter;
+use crate::event_sink::TauriEventSink;
 use crate::remote_backend;
+use crate::remote_backend::offline_mirror::OfflineMirror;
 use crate::shared::codex_core;
-use crate::shared::files_core::{file_read_core, file_write_core};
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::disk_core::{disk_free_core, PathKind};
+use crate::shared::file_snapshot_core::{
+    file_snapshot_core, file_snapshot_list_core, file_snapshot_restore_core, FileSnapshot,
+};
+use crate::shared::files_core::{
+    agents_effective_core, file_copy_core, file_fix_permissions_core, file_link_info_core,
+    file_paths_core, file_permissions_audit_core, file_read_capped_core, file_read_core,
+    file_read_lines_normalized_core, file_read_many_core, file_read_timed_core,
+    file_render_template_core, file_reset_to_default_core, file_search_core, file_swap_core,
+    file_touch_core, file_truncate_core, file_verify_written_core, file_writable_core,
+    file_write_core, file_write_preview_core, file_write_report_core, file_write_symlink_core,
+    file_write_templated_core, policy_explain_core, policy_rename_kind_file_core,
+    prewarm_is_current, remote_cancel_core, resolve_root_core, scope_disk_usage_core,
+    scope_unwatch_core, scope_watch_core, scope_watch_set_coalesce_window_core,
+    scope_write_test_core, verify_read_back, workspace_export_zip_core, workspace_import_zip_core,
+    workspace_initialize_core, workspace_prewarm_begin_core, workspace_prewarm_core,
+    workspace_prewarm_finish_core, workspace_readiness_core, AgentsView, FileReadManyResponse,
+    PathResult, PermIssue, PrewarmProgress, Readiness, RemoteReadTiming, ScopeDiskUsage, TimedRead,
+    WriteReport,
+};
+use crate::shared::orphans_core::{cleanup_orphans_core, OrphanEntry};
+use crate::shared::settings_core::{
+    policy_clear_scope_override_core, policy_set_scope_override_core,
+};
+use crate::shared::tree_scan_core::{
+    tree_scan_cancel_core, tree_scan_run_core, tree_scan_start_core,
+};
+use crate::shared::workspaces_core::command_target;
 use crate::state::AppState;
 
+pub(crate) mod archive;
+pub(crate) mod compare;
+pub(crate) mod generation;
 pub(crate) mod io;
 pub(crate) mod ops;
 pub(crate) mod policy;
+pub(crate) mod remote_stream;
+pub(crate) mod search;
+pub(crate) mod template;
+pub(crate) mod validate;
+pub(crate) mod watch;
 
 async fn file_read_impl(
     scope: FileScope,
@@ -21,67 +70,1861 @@ async fn file_read_impl(
     app: &AppHandle,
 ) -> Result<TextFileResponse, String> {
     if remote_backend::is_remote_mode(state).await {
+        let file = ScopedFileRef {
+            scope,
+            kind,
+            workspace_id,
+        };
+        let response = match remote_backend::call_remote(
+            state,
+            app.clone(),
+            "file_read",
+            json!({ "scope": scope, "kind": kind, "workspaceId": file.workspace_id }),
+        )
+        .await
+        {
+            Ok(response) => response,
+            Err(err) if remote_backend::is_connectivity_error(&err) => {
+                return state.offline_mirror.lock().await.read(&file).ok_or(err);
+            }
+            Err(err) => return Err(err),
+        };
+        let response: TextFileResponse =
+            serde_json::from_value(response).map_err(|err| err.to_string())?;
+        let mut mirror = state.offline_mirror.lock().await;
+        mirror.record_read(&file, &response);
+        return Ok(mirror.read(&file).unwrap_or(response));
+    }
+
+    file_read_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+    )
+    .await
+}
+
+/// The remote half of `file_write_impl`. `call` sends one request to the
+/// daemon; with `verify` the file is then read back through it and must
+/// match `content`. A write queued while offline is not verified.
+async fn remote_file_write<F, Fut>(
+    mirror: &tokio::sync::Mutex<OfflineMirror>,
+    file: ScopedFileRef,
+    content: String,
+    expected_modified_ms: Option<u64>,
+    force: bool,
+    verify: bool,
+    call: F,
+) -> Result<(), String>
+where
+    F: Fn(&'static str, Value) -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    let result = call(
+        "file_write",
+        json!({
+            "scope": file.scope,
+            "kind": file.kind,
+            "workspaceId": file.workspace_id,
+            "content": content,
+            "expectedModifiedMs": expected_modified_ms,
+            "force": force,
+        }),
+    )
+    .await;
+    {
+        let mut mirror = mirror.lock().await;
+        match result {
+            Ok(_) => mirror.record_write(&file, &content),
+            Err(err) if remote_backend::is_connectivity_error(&err) => {
+                return mirror.queue_write(&file, content);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    if !verify {
+        return Ok(());
+    }
+    let response = call(
+        "file_read",
+        json!({ "scope": file.scope, "kind": file.kind, "workspaceId": file.workspace_id }),
+    )
+    .await?;
+    let read_back: TextFileResponse =
+        serde_json::from_value(response).map_err(|err| err.to_string())?;
+    verify_read_back(&content, &read_back)
+}
+
+/// With `verify`, the file is read back after the write and must match
+/// `content`, or the write fails with `VERIFY_FAILED`; see `remote_file_write`
+/// for remote mode.
+async fn file_write_impl(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    content: String,
+    expected_modified_ms: Option<u64>,
+    force: bool,
+    verify: bool,
+    state: &AppState,
+    app: &AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(state).await {
+        let file = ScopedFileRef {
+            scope,
+            kind,
+            workspace_id,
+        };
+        return remote_file_write(
+            &state.offline_mirror,
+            file,
+            content,
+            expected_modified_ms,
+            force,
+            verify,
+            |method, params| remote_backend::call_remote(state, app.clone(), method, params),
+        )
+        .await;
+    }
+
+    let expected = verify.then(|| (content.clone(), workspace_id.clone()));
+    file_write_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+        content,
+        expected_modified_ms,
+        force,
+        ConfigWriter::new(
+            "file_write",
+            &TauriEventSink::new(app.clone()),
+            &state.file_generations,
+        ),
+    )
+    .await?;
+    match expected {
+        Some((content, workspace_id)) => {
+            file_verify_written_core(
+                &state.workspaces,
+                &state.scope_root_overrides,
+                scope,
+                kind,
+                workspace_id,
+                &content,
+            )
+            .await
+        }
+        None => Ok(()),
+    }
+}
+
+async fn file_copy_impl(
+    source: ScopedFileRef,
+    target: ScopedFileRef,
+    state: &AppState,
+    app: &AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(state).await {
+        remote_backend::call_remote(
+            state,
+            app.clone(),
+            "file_copy",
+            json!({ "source": source, "target": target }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    file_copy_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &state.file_generations,
+        source,
+        target,
+    )
+    .await
+}
+
+async fn file_search_impl(
+    scope: FileScope,
+    workspace_id: Option<String>,
+    query: String,
+    regex: bool,
+    max_results: u32,
+    state: &AppState,
+    app: &AppHandle,
+) -> Result<Vec<SearchHit>, String> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "file_search",
+            json!({
+                "scope": scope,
+                "workspaceId": workspace_id,
+                "query": query,
+                "regex": regex,
+                "maxResults": max_results,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_search_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        workspace_id,
+        query,
+        regex,
+        max_results,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn file_read(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TextFileResponse, String> {
+    file_read_impl(scope, kind, workspace_id, &*state, &app).await
+}
+
+/// Reads at most `max_bytes` of a scoped file, so huge files still show a prefix.
+#[tauri::command]
+pub(crate) async fn file_read_capped(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    max_bytes: u64,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<CappedRead, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_read_capped",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+                "maxBytes": max_bytes,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_read_capped_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+        max_bytes,
+    )
+    .await
+}
+
+/// Reads a scoped file split on `\n`, `\r\n` or `\r`, with the dominant line
+/// ending for writing it back.
+#[tauri::command]
+pub(crate) async fn file_read_lines_normalized(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<NormalizedLines, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_read_lines_normalized",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_read_lines_normalized_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+    )
+    .await
+}
+
+/// Like `file_read`, but remote reads arrive in chunks with
+/// `remote-read-progress` events tagged with `request_id`.
+#[tauri::command]
+pub(crate) async fn file_read_streamed(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    request_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TextFileResponse, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_stream::stream_remote_file(
+            &*state,
+            &app,
+            scope,
+            kind,
+            workspace_id,
+            &request_id,
+        )
+        .await;
+    }
+
+    let response = file_read_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+    )
+    .await?;
+    let size = response.content.len() as u64;
+    remote_stream::emit_read_progress(&app, &request_id, size, size);
+    Ok(response)
+}
+
+/// `file_read` with timings, for diagnosing slowness; in remote mode the time is
+/// split into network, backend read and local decoding. Skips the offline
+/// mirror, so a disconnected backend is an error here.
+#[tauri::command]
+pub(crate) async fn file_read_timed(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TimedRead, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let started = Instant::now();
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_read_timed",
+            json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+        )
+        .await?;
+        let round_trip_ms = started.elapsed().as_millis() as u64;
+        let decode_started = Instant::now();
+        let remote_read: TimedRead =
+            serde_json::from_value(response).map_err(|err| err.to_string())?;
+        let local_ms = decode_started.elapsed().as_millis() as u64;
+        return Ok(TimedRead {
+            response: remote_read.response,
+            modified_ms: remote_read.modified_ms,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            remote: Some(RemoteReadTiming {
+                network_ms: round_trip_ms.saturating_sub(remote_read.elapsed_ms),
+                remote_read_ms: remote_read.elapsed_ms,
+                local_ms,
+            }),
+        });
+    }
+
+    file_read_timed_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+    )
+    .await
+}
+
+/// Reads several scoped files in one call. `remote_cancel` with the same
+/// `request_id` stops the batch early and returns the partial results.
+#[tauri::command]
+pub(crate) async fn file_read_many(
+    requests: Vec<ScopedFileRef>,
+    request_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<FileReadManyResponse, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_read_many",
+            json!({ "requests": requests, "requestId": request_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(file_read_many_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &state.remote_reads_in_flight,
+        requests,
+        &request_id,
+    )
+    .await)
+}
+
+/// Cancels a `file_read_many` batch or streamed read; returns whether one with
+/// `request_id` was in flight.
+#[tauri::command]
+pub(crate) async fn remote_cancel(
+    request_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    let cancelled_locally = remote_cancel_core(&state.remote_reads_in_flight, &request_id).await;
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "remote_cancel",
+            json!({ "requestId": request_id }),
+        )
+        .await?;
+        let cancelled_remotely: bool =
+            serde_json::from_value(response).map_err(|err| err.to_string())?;
+        return Ok(cancelled_locally || cancelled_remotely);
+    }
+
+    Ok(cancelled_locally)
+}
+
+/// Returns whether a streamed read with `request_id` was in flight.
+#[tauri::command]
+pub(crate) async fn file_read_stream_cancel(
+    request_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    Ok(state
+        .remote_reads_in_flight
+        .lock()
+        .await
+        .remove(&request_id))
+}
+
+/// Makes the scoped file a symlink to an existing file at `target`. In remote
+/// mode `target` is a path on the remote machine.
+#[tauri::command]
+pub(crate) async fn file_write_symlink(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    target: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "file_write_symlink",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+                "target": remote_backend::normalize_path_for_remote(target),
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    file_write_symlink_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &state.file_generations,
+        scope,
+        kind,
+        workspace_id,
+        target,
+    )
+    .await
+}
+
+/// Creates the scoped file empty if it is missing; returns whether it was created.
+#[tauri::command]
+pub(crate) async fn file_touch(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_touch",
+            json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_touch_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+        ConfigWriter::new(
+            "file_touch",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+    .await
+}
+
+/// The platform's "open in the default text editor" command, with its
+/// leading arguments.
+fn system_editor() -> (&'static str, Vec<String>) {
+    if cfg!(target_os = "macos") {
+        ("open", vec!["-t".to_string()])
+    } else if cfg!(target_os = "windows") {
+        ("notepad", Vec::new())
+    } else {
+        ("xdg-open", Vec::new())
+    }
+}
+
+/// Splits a configured editor command shell-style into program and
+/// arguments, falling back to `system_editor` when none is set.
+fn editor_command(configured: Option<String>) -> Result<(String, Vec<String>), String> {
+    match configured.filter(|editor| !editor.trim().is_empty()) {
+        Some(editor) => {
+            let mut words = shell_words::split(&editor)
+                .map_err(|err| format!("Invalid editor command `{editor}`: {err}"))?;
+            if words.is_empty() {
+                return Err(format!("Invalid editor command `{editor}`"));
+            }
+            let program = words.remove(0);
+            Ok((program, words))
+        }
+        None => {
+            let (program, args) = system_editor();
+            Ok((program.to_string(), args))
+        }
+    }
+}
+
+/// Opens the scoped file in `editor`, the `externalEditor` setting, or the
+/// system's default editor, creating it empty if it is missing. The editor is
+/// spawned detached, so this returns once it has launched rather than when
+/// it exits. It has to run on this machine, so remote mode is refused.
+#[tauri::command]
+pub(crate) async fn open_in_external_editor(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    editor: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return Err(
+            "Opening files in an external editor is not supported in remote mode".to_string(),
+        );
+    }
+
+    let policy = policy::policy_for(scope, kind)?;
+    let root = resolve_root_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        workspace_id.as_deref(),
+    )
+    .await?;
+    if !ops::exists_with_policy(&root, policy)? {
+        ops::touch_with_policy(&state.file_generations, &root, policy)?;
+    }
+    let configured = match editor {
+        Some(editor) => Some(editor),
+        None => state.app_settings.lock().await.external_editor.clone(),
+    };
+    let (program, mut args) = editor_command(configured)?;
+    args.push(root.join(policy.filename).to_string_lossy().to_string());
+    let mut command = command_target(&program, &args)?;
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| format!("Failed to launch editor `{program}`: {err}"))
+}
+
+/// Clears the scoped file in place, or trims it to its last `keep_last_bytes`
+/// bytes of whole lines, so a tail watcher keeps following it. Returns the
+/// number of bytes removed.
+#[tauri::command]
+pub(crate) async fn file_truncate(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    keep_last_bytes: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<u64, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_truncate",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+                "keepLastBytes": keep_last_bytes,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_truncate_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &state.file_generations,
+        scope,
+        kind,
+        workspace_id,
+        keep_last_bytes,
+    )
+    .await
+}
+
+/// Creates the default workspace files that are missing, or all of them with
+/// `overwrite`.
+#[tauri::command]
+pub(crate) async fn workspace_initialize(
+    workspace_id: String,
+    overwrite: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInitReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_initialize",
+            json!({ "workspaceId": workspace_id, "overwrite": overwrite }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspace_initialize_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &state.file_generations,
+        workspace_id,
+        overwrite,
+    )
+    .await
+}
+
+/// A 0-100 setup score for the workspace plus the checklist items it is missing.
+#[tauri::command]
+pub(crate) async fn workspace_readiness(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Readiness, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_readiness",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspace_readiness_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &state.file_generations,
+        &state.readiness_cache,
+        workspace_id,
+    )
+    .await
+}
+
+/// Warms the reads a just-activated workspace needs in the background and
+/// emits `prewarm-done`, listing failed steps, when finished. Calling again,
+/// for any workspace, cancels the prewarm in progress.
+#[tauri::command]
+pub(crate) async fn workspace_prewarm(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let token = workspace_prewarm_begin_core(&state.prewarm).await;
+    let remote = remote_backend::is_remote_mode(&*state).await;
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        if remote {
+            workspace_prewarm_remote(&state, &app, &token, workspace_id).await;
+            return;
+        }
+        workspace_prewarm_core(
+            &state.workspaces,
+            &state.scope_root_overrides,
+            &state.file_generations,
+            &state.readiness_cache,
+            &state.prewarm,
+            &token,
+            workspace_id,
+            &TauriEventSink::new(app.clone()),
+        )
+        .await;
+    });
+    Ok(())
+}
+
+/// The reads remote mode sends through the offline mirror before the
+/// readiness step, so the files stay editable if the connection drops.
+const PREWARM_MIRRORED_READS: [(&str, FileScope, FileKind); 2] = [
+    ("workspace_agents", FileScope::Workspace, FileKind::Agents),
+    ("config_toml", FileScope::Global, FileKind::Config),
+];
+
+/// Remote mode's prewarm. The reads go through `file_read_impl`, so they land
+/// in the offline mirror, and the readiness summary warms the daemon's cache.
+async fn workspace_prewarm_remote(
+    state: &AppState,
+    app: &AppHandle,
+    token: &str,
+    workspace_id: String,
+) {
+    let mut progress = PrewarmProgress::default();
+    for (step, scope, kind) in PREWARM_MIRRORED_READS {
+        if !prewarm_is_current(&state.prewarm, token).await {
+            return;
+        }
+        let read = file_read_impl(scope, kind, Some(workspace_id.clone()), state, app).await;
+        progress.record(step, read);
+    }
+    if !prewarm_is_current(&state.prewarm, token).await {
+        return;
+    }
+    let readiness = remote_backend::call_remote(
+        state,
+        app.clone(),
+        "workspace_readiness",
+        json!({ "workspaceId": workspace_id }),
+    )
+    .await;
+    progress.record("readiness", readiness);
+    let sink = TauriEventSink::new(app.clone());
+    workspace_prewarm_finish_core(&state.prewarm, token, workspace_id, progress, &sink).await;
+}
+
+/// The global and workspace AGENTS files and their merged, marked-up text.
+#[tauri::command]
+pub(crate) async fn agents_effective(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<AgentsView, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "agents_effective",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    agents_effective_core(&state.workspaces, &state.scope_root_overrides, workspace_id).await
+}
+
+/// Scoped files that may hold secrets but are readable beyond their owner.
+#[tauri::command]
+pub(crate) async fn file_permissions_audit(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<PermIssue>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_permissions_audit",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_permissions_audit_core(&state.workspaces, &state.scope_root_overrides, workspace_id).await
+}
+
+/// Makes the scoped file readable and writable by its owner only (`0600`).
+#[tauri::command]
+pub(crate) async fn file_fix_permissions(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_fix_permissions",
+            json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_fix_permissions_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+    )
+    .await
+}
+
+/// Whether a scoped file is a symlink, its target, and whether that stays in
+/// the scope root. The link is inspected, never followed to read content.
+#[tauri::command]
+pub(crate) async fn file_link_info(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<LinkInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_link_info",
+            json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_link_info_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+    )
+    .await
+}
+
+/// Whether the editor can save this file; permission problems return `false`.
+#[tauri::command]
+pub(crate) async fn file_writable(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_writable",
+            json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_writable_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn file_write(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    content: String,
+    expected_modified_ms: Option<u64>,
+    force: Option<bool>,
+    verify: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    file_write_impl(
+        scope,
+        kind,
+        workspace_id,
+        content,
+        expected_modified_ms,
+        force.unwrap_or(false),
+        verify.unwrap_or(false),
+        &*state,
+        &app,
+    )
+    .await
+}
+
+/// Like `file_write`, but reports the resolved path, bytes written, and whether
+/// the file was created or already up to date.
+#[tauri::command]
+pub(crate) async fn file_write_report(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    content: String,
+    expected_modified_ms: Option<u64>,
+    force: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WriteReport, String> {
+    let force = force.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let file = ScopedFileRef {
+            scope,
+            kind,
+            workspace_id,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_write_report",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": file.workspace_id,
+                "content": content,
+                "expectedModifiedMs": expected_modified_ms,
+                "force": force,
+            }),
+        )
+        .await?;
+        let report: WriteReport =
+            serde_json::from_value(response).map_err(|err| err.to_string())?;
+        state
+            .offline_mirror
+            .lock()
+            .await
+            .record_write(&file, &content);
+        return Ok(report);
+    }
+
+    file_write_report_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+        content,
+        expected_modified_ms,
+        force,
+        ConfigWriter::new(
+            "file_write_report",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+    .await
+}
+
+/// The hunks `file_write` would apply, without writing anything.
+#[tauri::command]
+pub(crate) async fn file_write_preview(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    content: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<DiffHunk>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_write_preview",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+                "content": content,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_write_preview_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+        &content,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn file_write_templated(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    template: String,
+    vars: HashMap<String, String>,
+    strict: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let strict = strict.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "file_write_templated",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+                "template": template,
+                "vars": vars,
+                "strict": strict,
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    file_write_templated_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+        template,
+        vars,
+        strict,
+        ConfigWriter::new(
+            "file_write_templated",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+    .await
+}
+
+/// Renders `template` the way `file_write_templated` would and returns the
+/// text without writing it. With `strict`, unknown tokens are listed instead
+/// of failing the render.
+#[tauri::command]
+pub(crate) async fn file_render_template(
+    template: String,
+    vars: HashMap<String, String>,
+    workspace_id: Option<String>,
+    strict: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RenderedTemplate, String> {
+    let strict = strict.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_render_template",
+            json!({
+                "template": template,
+                "vars": vars,
+                "workspaceId": workspace_id,
+                "strict": strict,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(file_render_template_core(&state.workspaces, template, vars, workspace_id, strict).await)
+}
+
+/// The built-in default content per file kind, for "reset to default" and
+/// "create from default". Kinds without one are left out.
+#[tauri::command]
+pub(crate) async fn defaults_catalog(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<DefaultInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "defaults_catalog", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(builtin_defaults_catalog())
+}
+
+/// The `codex://` link to a scoped file, for deep links and logs. Computed
+/// locally in both backend modes.
+#[tauri::command]
+pub(crate) fn file_uri(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+) -> Result<String, String> {
+    policy::file_uri(scope, kind, workspace_id.as_deref())
+}
+
+#[tauri::command]
+pub(crate) fn parse_file_uri(uri: String) -> Result<ScopedFileRef, String> {
+    policy::parse_file_uri(&uri)
+}
+
+#[tauri::command]
+pub(crate) async fn file_copy(
+    source: ScopedFileRef,
+    target: ScopedFileRef,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    file_copy_impl(source, target, &*state, &app).await
+}
+
+fn snapshot_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|err| format!("Failed to resolve app data dir: {err}"))
+}
+
+/// Saves the current content of a scoped file as a snapshot, optionally
+/// labelled. Only the newest snapshots of each file are kept.
+#[tauri::command]
+pub(crate) async fn file_snapshot(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    label: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<FileSnapshot, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_snapshot",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+                "label": label,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let file = ScopedFileRef {
+        scope,
+        kind,
+        workspace_id,
+    };
+    file_snapshot_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &snapshot_data_dir(&app)?,
+        file,
+        label,
+    )
+    .await
+}
+
+/// Lists the snapshots of a scoped file, newest first.
+#[tauri::command]
+pub(crate) async fn file_snapshot_list(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<FileSnapshot>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_snapshot_list",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let file = ScopedFileRef {
+        scope,
+        kind,
+        workspace_id,
+    };
+    file_snapshot_list_core(&snapshot_data_dir(&app)?, file)
+}
+
+/// Writes a snapshot back over the file it was taken from.
+#[tauri::command]
+pub(crate) async fn file_snapshot_restore(
+    snapshot_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WriteReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_snapshot_restore",
+            json!({ "snapshotId": snapshot_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_snapshot_restore_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &snapshot_data_dir(&app)?,
+        &snapshot_id,
+        ConfigWriter::new(
+            "file_snapshot_restore",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+    .await
+}
+
+/// Finds temp files, expired backups and abandoned snapshot stores left behind
+/// by crashes, and deletes them unless `dry_run`.
+#[tauri::command]
+pub(crate) async fn cleanup_orphans(
+    dry_run: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<OrphanEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
-            state,
-            app.clone(),
-            "file_read",
-            json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+            &*state,
+            app,
+            "cleanup_orphans",
+            json!({ "dryRun": dry_run }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    file_read_core(&state.workspaces, scope, kind, workspace_id).await
+    cleanup_orphans_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &snapshot_data_dir(&app)?,
+        dry_run,
+    )
+    .await
 }
 
-async fn file_write_impl(
+/// Starts walking a scope root in the background and returns the scan id.
+/// Entries arrive as `tree-entry` batches, followed by `tree-done`.
+#[tauri::command]
+pub(crate) async fn tree_scan_start(
     scope: FileScope,
-    kind: FileKind,
     workspace_id: Option<String>,
-    content: String,
-    state: &AppState,
-    app: &AppHandle,
+    max_depth: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "tree_scan_start",
+            json!({ "scope": scope, "workspaceId": workspace_id, "maxDepth": max_depth }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let (scan_id, root) = tree_scan_start_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &state.tree_scans,
+        scope,
+        workspace_id,
+    )
+    .await?;
+    tauri::async_runtime::spawn(tree_scan_run_core(
+        state.tree_scans.clone(),
+        root,
+        scan_id.clone(),
+        max_depth,
+        TauriEventSink::new(app),
+    ));
+    Ok(scan_id)
+}
+
+/// Stops a tree scan; returns whether it was still running.
+#[tauri::command]
+pub(crate) async fn tree_scan_cancel(
+    scan_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "tree_scan_cancel",
+            json!({ "scanId": scan_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(tree_scan_cancel_core(&state.tree_scans, &scan_id).await)
+}
+
+/// Swaps two scoped files in place, e.g. to promote a draft to active.
+#[tauri::command]
+pub(crate) async fn file_swap(
+    scope_a: FileScope,
+    kind_a: FileKind,
+    scope_b: FileScope,
+    kind_b: FileKind,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
 ) -> Result<(), String> {
-    if remote_backend::is_remote_mode(state).await {
+    if remote_backend::is_remote_mode(&*state).await {
         remote_backend::call_remote(
-            state,
-            app.clone(),
-            "file_write",
+            &*state,
+            app,
+            "file_swap",
+            json!({
+                "scopeA": scope_a,
+                "kindA": kind_a,
+                "scopeB": scope_b,
+                "kindB": kind_b,
+                "workspaceId": workspace_id,
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    file_swap_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope_a,
+        kind_a,
+        scope_b,
+        kind_b,
+        workspace_id,
+        ConfigWriter::new(
+            "file_swap",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn file_paths(
+    requests: Vec<ScopedFileRef>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<PathResult>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_paths",
+            json!({ "requests": requests }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(file_paths_core(&state.workspaces, &state.scope_root_overrides, requests).await)
+}
+
+#[tauri::command]
+pub(crate) async fn file_reset_to_default(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    backup: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "file_reset_to_default",
             json!({
                 "scope": scope,
                 "kind": kind,
                 "workspaceId": workspace_id,
-                "content": content,
+                "backup": backup,
             }),
         )
         .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_reset_to_default_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &snapshot_data_dir(&app)?,
+        scope,
+        kind,
+        workspace_id,
+        backup,
+        ConfigWriter::new(
+            "file_reset_to_default",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn file_search(
+    scope: FileScope,
+    workspace_id: Option<String>,
+    query: String,
+    regex: bool,
+    max_results: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<SearchHit>, String> {
+    file_search_impl(
+        scope,
+        workspace_id,
+        query,
+        regex,
+        max_results,
+        &*state,
+        &app,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn scope_write_test(
+    scope: FileScope,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "scope_write_test",
+            json!({ "scope": scope, "workspaceId": workspace_id }),
+        )
+        .await?;
         return Ok(());
     }
 
-    file_write_core(&state.workspaces, scope, kind, workspace_id, content).await
+    scope_write_test_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        workspace_id,
+    )
+    .await
 }
 
 #[tauri::command]
-pub(crate) async fn file_read(
+pub(crate) async fn scope_disk_usage(
+    workspace_id: Option<String>,
+    recursive: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ScopeDiskUsage, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "scope_disk_usage",
+            json!({ "workspaceId": workspace_id, "recursive": recursive }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    scope_disk_usage_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        workspace_id,
+        recursive,
+    )
+    .await
+}
+
+/// Free bytes on the volume holding the app data dir, CODEX_HOME or a
+/// workspace root, so the UI can warn before a write runs out of space.
+#[tauri::command]
+pub(crate) async fn disk_free(
+    path_kind: PathKind,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<u64, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "disk_free",
+            json!({ "pathKind": path_kind }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    disk_free_core(&state.workspaces, &snapshot_data_dir(&app)?, path_kind).await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_export_zip(
+    workspace_id: String,
+    dest_path: String,
+    operation_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ArchiveEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_export_zip",
+            json!({
+                "workspaceId": workspace_id,
+                "destPath": remote_backend::normalize_path_for_remote(dest_path),
+                "operationId": operation_id,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let reporter = OperationReporter::new(TauriEventSink::new(app), operation_id);
+    workspace_export_zip_core(&state.workspaces, workspace_id, dest_path, &reporter).await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_import_zip(
+    workspace_id: String,
+    source_path: String,
+    operation_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ArchiveEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_import_zip",
+            json!({
+                "workspaceId": workspace_id,
+                "sourcePath": remote_backend::normalize_path_for_remote(source_path),
+                "operationId": operation_id,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let reporter = OperationReporter::new(TauriEventSink::new(app), operation_id);
+    workspace_import_zip_core(
+        &state.workspaces,
+        &state.file_generations,
+        &state.scope_watchers.coalescer,
+        workspace_id,
+        source_path,
+        &reporter,
+    )
+    .await
+}
+
+/// Reads the file from local disk and from the remote backend and reports how
+/// they differ. Only runs when invoked explicitly; both sides must be reachable.
+#[tauri::command]
+pub(crate) async fn file_compare_local_remote(
     scope: FileScope,
     kind: FileKind,
     workspace_id: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<TextFileResponse, String> {
-    file_read_impl(scope, kind, workspace_id, &*state, &app).await
+) -> Result<CompareResult, String> {
+    let local = file_read_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id.clone(),
+    )
+    .await?;
+    let response = remote_backend::call_remote(
+        &*state,
+        app,
+        "file_read",
+        json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+    )
+    .await?;
+    let remote: TextFileResponse =
+        serde_json::from_value(response).map_err(|err| err.to_string())?;
+    Ok(compare_contents(
+        local.exists.then_some(local.content.as_str()),
+        remote.exists.then_some(remote.content.as_str()),
+    ))
 }
 
 #[tauri::command]
-pub(crate) async fn file_write(
+pub(crate) async fn policy_explain(
     scope: FileScope,
     kind: FileKind,
     workspace_id: Option<String>,
-    content: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<PolicyExplanation, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "policy_explain",
+            json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(policy_explain_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        scope,
+        kind,
+        workspace_id,
+    )
+    .await)
+}
+
+/// Points `scope` at a custom root for `workspace_id`, or the default global
+/// root without one; returns the canonical path that was stored.
+#[tauri::command]
+pub(crate) async fn policy_set_scope_override(
+    scope: FileScope,
+    workspace_id: Option<String>,
+    path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let path = remote_backend::normalize_path_for_remote(path);
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "policy_set_scope_override",
+            json!({ "scope": scope, "workspaceId": workspace_id, "path": path }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    policy_set_scope_override_core(
+        scope,
+        workspace_id.as_deref(),
+        &path,
+        &state.app_settings,
+        &state.settings_path,
+        &state.scope_root_overrides,
+        &state.readiness_cache,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn policy_clear_scope_override(
+    scope: FileScope,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "policy_clear_scope_override",
+            json!({ "scope": scope, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    policy_clear_scope_override_core(
+        scope,
+        workspace_id.as_deref(),
+        &state.app_settings,
+        &state.settings_path,
+        &state.scope_root_overrides,
+        &state.readiness_cache,
+    )
+    .await
+}
+
+/// Watches the scope root recursively; changes arrive as `scope-changed` events
+/// tagged with the returned watch id.
+#[tauri::command]
+pub(crate) async fn scope_watch(
+    scope: FileScope,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "scope_watch",
+            json!({ "scope": scope, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    scope_watch_core(
+        &state.workspaces,
+        &state.scope_root_overrides,
+        &state.scope_watchers,
+        TauriEventSink::new(app),
+        scope,
+        workspace_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn scope_unwatch(
+    watch_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "scope_unwatch",
+            json!({ "watchId": watch_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(scope_unwatch_core(&state.scope_watchers, &watch_id).await)
+}
+
+/// Sets how long `scope-changed` events for one path are held back to merge a
+/// burst of changes into one. Applies to all watches.
+#[tauri::command]
+pub(crate) async fn scope_watch_set_coalesce_window(
+    window_ms: u32,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
-    file_write_impl(scope, kind, workspace_id, content, &*state, &app).await
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "scope_watch_set_coalesce_window",
+            json!({ "windowMs": window_ms }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    scope_watch_set_coalesce_window_core(&state.scope_watchers, u64::from(window_ms))
+}
+
+#[tauri::command]
+pub(crate) async fn policy_rename_kind_file(
+    workspace_id: Option<String>,
+    kind: FileKind,
+    old_name: String,
+    new_name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<String>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "policy_rename_kind_file",
+            json!({
+                "workspaceId": workspace_id,
+                "kind": kind,
+                "oldName": old_name,
+                "newName": new_name,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    policy_rename_kind_file_core(
+        &state.workspaces,
+        &state.file_generations,
+        workspace_id,
+        kind,
+        old_name,
+        new_name,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) fn file_validate_kind(kind: FileKind, content: String) -> Vec<ValidationIssue> {
+    validate_kind_content(kind, &content)
 }
 
 #[tauri::command]
@@ -124,3 +1967,76 @@ pub(crate) fn write_text_file(path: String, content: String) -> Result<(), Strin
     }
     std::fs::write(&target, content).map_err(|err| format!("Failed to write export file: {err}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::remote_file_write;
+    use crate::files::policy::{FileKind, FileScope, ScopedFileRef};
+    use crate::remote_backend::offline_mirror::OfflineMirror;
+    use crate::shared::files_core::VERIFY_FAILED;
+
+    /// A daemon that keeps one file in memory; with `drop_writes` it
+    /// acknowledges writes without storing them.
+    struct FakeRemote {
+        stored: std::sync::Mutex<Option<String>>,
+        drop_writes: bool,
+    }
+
+    impl FakeRemote {
+        async fn call(&self, method: &'static str, params: Value) -> Result<Value, String> {
+            let mut stored = self.stored.lock().expect("stored lock");
+            match method {
+                "file_write" => {
+                    if !self.drop_writes {
+                        *stored = params["content"].as_str().map(str::to_string);
+                    }
+                    Ok(Value::Null)
+                }
+                "file_read" => Ok(json!({
+                    "exists": stored.is_some(),
+                    "content": stored.clone().unwrap_or_default(),
+                    "truncated": false,
+                })),
+                _ => Err(format!("unexpected method {method}")),
+            }
+        }
+    }
+
+    fn write(remote: &FakeRemote, verify: bool) -> Result<(), String> {
+        let mirror = tokio::sync::Mutex::new(OfflineMirror::new(1 << 20));
+        let file = ScopedFileRef {
+            scope: FileScope::Workspace,
+            kind: FileKind::Agents,
+            workspace_id: Some("ws".to_string()),
+        };
+        tokio::runtime::Runtime::new()
+            .expect("runtime")
+            .block_on(remote_file_write(
+                &mirror,
+                file,
+                "new".to_string(),
+                None,
+                false,
+                verify,
+                |method, params| remote.call(method, params),
+            ))
+    }
+
+    #[test]
+    fn verified_remote_writes_fail_when_the_daemon_drops_them() {
+        let dropping = FakeRemote {
+            stored: std::sync::Mutex::new(Some("old".to_string())),
+            drop_writes: true,
+        };
+        assert_eq!(write(&dropping, false), Ok(()));
+        assert_eq!(write(&dropping, true), Err(VERIFY_FAILED.to_string()));
+
+        let faithful = FakeRemote {
+            stored: std::sync::Mutex::new(None),
+            drop_writes: false,
+        };
+        assert_eq!(write(&faithful, true), Ok(()));
+    }
+}
@@ -1,7 +1,20 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::files::io::{read_text_file_within, write_text_file_within, TextFileResponse};
-use crate::files::policy::FilePolicy;
+use serde::{Deserialize, Serialize};
+
+use crate::files::generation::Generations;
+use crate::files::io::{
+    copy_file_within, create_symlink_within, file_exists_within, file_mode_within,
+    file_modified_ms_within, link_info_within, plan_rename_within, probe_writable_within,
+    read_file_bytes_within, read_text_file_within, read_text_prefix_within, reject_symlink_within,
+    remove_file_within, rename_file_within, replace_text_file_within, set_file_mode_within,
+    swap_file_contents_within, touch_file_within, truncate_file_within, write_text_file_within,
+    CappedRead, FileLocation, LinkInfo, TextFileResponse,
+};
+use crate::files::policy::{policy_for, FileKind, FilePolicy, FileScope};
+
+/// Workspace-scoped files a new workspace starts with, and their initial content.
+const WORKSPACE_DEFAULT_FILES: &[(FileKind, &str)] = &[(FileKind::Agents, "")];
 
 pub(crate) fn read_with_policy(
     root: &PathBuf,
@@ -17,15 +30,148 @@ pub(crate) fn read_with_policy(
     )
 }
 
-pub(crate) fn write_with_policy(
+pub(crate) fn read_bytes_with_policy(
     root: &PathBuf,
     policy: FilePolicy,
-    content: &str,
-) -> Result<(), String> {
-    write_text_file_within(
+) -> Result<Option<Vec<u8>>, String> {
+    read_file_bytes_within(
+        root,
+        policy.filename,
+        policy.root_may_be_missing,
+        policy.root_context,
+        policy.filename,
+        policy.allow_external_symlink_target,
+    )
+}
+
+/// Reads at most `max_bytes` of the file; a missing file reads as empty.
+pub(crate) fn read_capped_with_policy(
+    root: &PathBuf,
+    policy: FilePolicy,
+    max_bytes: u64,
+) -> Result<CappedRead, String> {
+    let capped = read_text_prefix_within(
+        root,
+        policy.filename,
+        policy.root_may_be_missing,
+        policy.root_context,
+        policy.filename,
+        policy.allow_external_symlink_target,
+        max_bytes,
+    )?;
+    Ok(capped.unwrap_or(CappedRead {
+        text: String::new(),
+        truncated: false,
+        total_size: 0,
+    }))
+}
+
+pub(crate) fn exists_with_policy(root: &PathBuf, policy: FilePolicy) -> Result<bool, String> {
+    file_exists_within(
+        root,
+        policy.filename,
+        policy.root_may_be_missing,
+        policy.root_context,
+        policy.filename,
+        policy.allow_external_symlink_target,
+    )
+}
+
+pub(crate) fn modified_ms_with_policy(
+    root: &PathBuf,
+    policy: FilePolicy,
+) -> Result<Option<u64>, String> {
+    file_modified_ms_within(
+        root,
+        policy.filename,
+        policy.root_may_be_missing,
+        policy.root_context,
+        policy.filename,
+        policy.allow_external_symlink_target,
+    )
+}
+
+/// The key `filename` under `root` is tracked by. The root is canonicalized
+/// when it exists, so a workspace reached through a symlink or a relative
+/// path shares its generations with the one written through the real path.
+fn generation_key(root: &Path, filename: &str) -> String {
+    let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    root.join(filename).to_string_lossy().to_string()
+}
+
+/// The newest write generation among `files`; take it before reading them.
+pub(crate) fn file_generation(generations: &Generations, files: &[(&Path, &str)]) -> u64 {
+    let keys: Vec<String> = files
+        .iter()
+        .map(|(root, filename)| generation_key(root, filename))
+        .collect();
+    generations.current(keys.iter().map(String::as_str))
+}
+
+/// Bumps `files` for changes made outside this module, such as a CODEX_HOME
+/// relocation copying and deleting files directly.
+pub(crate) fn bump_file_generations(generations: &Generations, files: &[(&Path, &str)]) {
+    for (root, filename) in files {
+        generations.bump(&generation_key(root, filename));
+    }
+}
+
+/// Bumps `files` in `generations` once `result` succeeded. Every write,
+/// rename, copy and delete in this module goes through here, so results
+/// derived from scoped files (such as `workspace_readiness`) know when to
+/// recompute.
+fn bump_generations<T>(
+    generations: &Generations,
+    files: &[(&Path, &str)],
+    result: Result<T, String>,
+) -> Result<T, String> {
+    if result.is_ok() {
+        bump_file_generations(generations, files);
+    }
+    result
+}
+
+/// Moves a file saved under an older filename to `policy.filename`.
+pub(crate) fn rename_to_policy_filename(
+    generations: &Generations,
+    root: &PathBuf,
+    policy: FilePolicy,
+    old_filename: &str,
+) -> Result<bool, String> {
+    bump_generations(
+        generations,
+        &[(root, old_filename), (root, policy.filename)],
+        rename_file_within(
+            root,
+            old_filename,
+            policy.filename,
+            policy.root_context,
+            policy.allow_external_symlink_target,
+        ),
+    )
+}
+
+/// Whether `rename_to_policy_filename` would move a file, failing the same way
+/// it would but without moving anything.
+pub(crate) fn check_rename_to_policy_filename(
+    root: &PathBuf,
+    policy: FilePolicy,
+    old_filename: &str,
+) -> Result<bool, String> {
+    plan_rename_within(
+        root,
+        old_filename,
+        policy.filename,
+        policy.root_context,
+        policy.allow_external_symlink_target,
+    )
+    .map(|plan| plan.is_some())
+}
+
+pub(crate) fn writable_with_policy(root: &PathBuf, policy: FilePolicy) -> Result<bool, String> {
+    probe_writable_within(
         root,
         policy.filename,
-        content,
         policy.create_root,
         policy.root_context,
         policy.filename,
@@ -33,15 +179,302 @@ pub(crate) fn write_with_policy(
     )
 }
 
+/// Permission bits of the policy file; `None` when it is missing.
+pub(crate) fn mode_with_policy(root: &PathBuf, policy: FilePolicy) -> Result<Option<u32>, String> {
+    file_mode_within(
+        root,
+        policy.filename,
+        policy.root_may_be_missing,
+        policy.root_context,
+        policy.filename,
+        policy.allow_external_symlink_target,
+    )
+}
+
+pub(crate) fn link_info_with_policy(
+    root: &PathBuf,
+    policy: FilePolicy,
+) -> Result<LinkInfo, String> {
+    link_info_within(
+        root,
+        policy.filename,
+        policy.root_may_be_missing,
+        policy.root_context,
+        policy.filename,
+    )
+}
+
+pub(crate) fn set_mode_with_policy(
+    root: &PathBuf,
+    policy: FilePolicy,
+    mode: u32,
+) -> Result<bool, String> {
+    set_file_mode_within(
+        root,
+        policy.filename,
+        mode,
+        policy.root_context,
+        policy.filename,
+        policy.allow_external_symlink_target,
+    )
+}
+
+pub(crate) fn write_with_policy(
+    generations: &Generations,
+    root: &PathBuf,
+    policy: FilePolicy,
+    content: &str,
+) -> Result<(), String> {
+    bump_generations(
+        generations,
+        &[(root, policy.filename)],
+        write_text_file_within(
+            root,
+            policy.filename,
+            content,
+            policy.create_root,
+            policy.root_context,
+            policy.filename,
+            policy.allow_external_symlink_target,
+        ),
+    )
+}
+
+/// `write_with_policy` through a temp file and rename; see
+/// `replace_text_file_within`.
+pub(crate) fn replace_with_policy(
+    generations: &Generations,
+    root: &PathBuf,
+    policy: FilePolicy,
+    content: &str,
+) -> Result<(), String> {
+    bump_generations(
+        generations,
+        &[(root, policy.filename)],
+        replace_text_file_within(
+            root,
+            policy.filename,
+            content,
+            policy.create_root,
+            policy.root_context,
+            policy.filename,
+            policy.allow_external_symlink_target,
+        ),
+    )
+}
+
+/// Reads a file `swap_with_policy` or `file_swap` is about to replace: it must
+/// exist, be read in full and not be a symbolic link.
+pub(crate) fn read_swap_side_with_policy(
+    root: &PathBuf,
+    policy: FilePolicy,
+) -> Result<String, String> {
+    reject_symlink_within(root, policy.filename, policy.filename)?;
+    let response = read_with_policy(root, policy)?;
+    if !response.exists {
+        return Err(format!("{} does not exist", policy.filename));
+    }
+    if response.truncated {
+        return Err(format!("{} is too large to swap", policy.filename));
+    }
+    Ok(response.content)
+}
+
+/// Deletes the policy file; returns `false` when it did not exist.
+pub(crate) fn remove_with_policy(
+    generations: &Generations,
+    root: &PathBuf,
+    policy: FilePolicy,
+) -> Result<bool, String> {
+    bump_generations(
+        generations,
+        &[(root, policy.filename)],
+        remove_file_within(
+            root,
+            policy.filename,
+            policy.root_context,
+            policy.filename,
+            policy.allow_external_symlink_target,
+        ),
+    )
+}
+
+pub(crate) fn touch_with_policy(
+    generations: &Generations,
+    root: &PathBuf,
+    policy: FilePolicy,
+) -> Result<bool, String> {
+    bump_generations(
+        generations,
+        &[(root, policy.filename)],
+        touch_file_within(
+            root,
+            policy.filename,
+            policy.create_root,
+            policy.root_context,
+            policy.filename,
+            policy.allow_external_symlink_target,
+        ),
+    )
+}
+
+/// Truncates the policy file in place; see `truncate_file_within`.
+pub(crate) fn truncate_with_policy(
+    generations: &Generations,
+    root: &PathBuf,
+    policy: FilePolicy,
+    keep_last_bytes: Option<u64>,
+) -> Result<u64, String> {
+    bump_generations(
+        generations,
+        &[(root, policy.filename)],
+        truncate_file_within(
+            root,
+            policy.filename,
+            keep_last_bytes,
+            policy.root_context,
+            policy.filename,
+            policy.allow_external_symlink_target,
+        ),
+    )
+}
+
+/// Links the policy file to `target`; escaping the root follows
+/// `allow_external_symlink_target`.
+pub(crate) fn symlink_with_policy(
+    generations: &Generations,
+    root: &PathBuf,
+    policy: FilePolicy,
+    target: &Path,
+) -> Result<(), String> {
+    bump_generations(
+        generations,
+        &[(root, policy.filename)],
+        create_symlink_within(
+            root,
+            policy.filename,
+            target,
+            policy.create_root,
+            policy.root_context,
+            policy.filename,
+            policy.allow_external_symlink_target,
+        ),
+    )
+}
+
+pub(crate) fn copy_with_policy(
+    generations: &Generations,
+    source_root: &PathBuf,
+    source_policy: FilePolicy,
+    target_root: &PathBuf,
+    target_policy: FilePolicy,
+) -> Result<u64, String> {
+    bump_generations(
+        generations,
+        &[(target_root, target_policy.filename)],
+        copy_file_within(
+            FileLocation {
+                root: source_root,
+                filename: source_policy.filename,
+                root_context: source_policy.root_context,
+                file_context: source_policy.filename,
+                allow_external_symlink_target: source_policy.allow_external_symlink_target,
+            },
+            source_policy.root_may_be_missing,
+            FileLocation {
+                root: target_root,
+                filename: target_policy.filename,
+                root_context: target_policy.root_context,
+                file_context: target_policy.filename,
+                allow_external_symlink_target: target_policy.allow_external_symlink_target,
+            },
+            target_policy.create_root,
+        ),
+    )
+}
+
+/// Exchanges the contents of two existing regular files.
+pub(crate) fn swap_with_policy(
+    generations: &Generations,
+    a_root: &PathBuf,
+    a_policy: FilePolicy,
+    b_root: &PathBuf,
+    b_policy: FilePolicy,
+) -> Result<(), String> {
+    let a = read_swap_side_with_policy(a_root, a_policy)?;
+    let b = read_swap_side_with_policy(b_root, b_policy)?;
+    bump_generations(
+        generations,
+        &[(a_root, a_policy.filename), (b_root, b_policy.filename)],
+        swap_file_contents_within(
+            FileLocation {
+                root: a_root,
+                filename: a_policy.filename,
+                root_context: a_policy.root_context,
+                file_context: a_policy.filename,
+                allow_external_symlink_target: a_policy.allow_external_symlink_target,
+            },
+            &b,
+            FileLocation {
+                root: b_root,
+                filename: b_policy.filename,
+                root_context: b_policy.root_context,
+                file_context: b_policy.filename,
+                allow_external_symlink_target: b_policy.allow_external_symlink_target,
+            },
+            &a,
+        ),
+    )
+}
+
+/// Which default files `initialize_workspace_files` wrote and which it left alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct WorkspaceInitReport {
+    pub(crate) created: Vec<FileKind>,
+    pub(crate) skipped: Vec<FileKind>,
+}
+
+/// Writes the default workspace files under `root`, skipping existing ones
+/// unless `overwrite`. Every target is checked before anything is written, so
+/// an unwritable file fails the call without leaving it half done.
+pub(crate) fn initialize_workspace_files(
+    generations: &Generations,
+    root: &PathBuf,
+    overwrite: bool,
+) -> Result<WorkspaceInitReport, String> {
+    let mut report = WorkspaceInitReport::default();
+    let mut pending = Vec::new();
+    for (kind, content) in WORKSPACE_DEFAULT_FILES {
+        let policy = policy_for(FileScope::Workspace, *kind)?;
+        if !overwrite && exists_with_policy(root, policy)? {
+            report.skipped.push(*kind);
+            continue;
+        }
+        if !writable_with_policy(root, policy)? {
+            return Err(format!("{} is not writable", policy.filename));
+        }
+        pending.push((*kind, policy, *content));
+    }
+    for (kind, policy, content) in pending {
+        write_with_policy(generations, root, policy, content)?;
+        report.created.push(kind);
+    }
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
 
     use uuid::Uuid;
 
+    use crate::files::generation::Generations;
     use crate::files::policy::{policy_for, FileKind, FileScope};
 
-    use super::{read_with_policy, write_with_policy};
+    use super::{
+        file_generation, initialize_workspace_files, read_with_policy, remove_with_policy,
+        swap_with_policy, write_with_policy,
+    };
 
     fn temp_dir(prefix: &str) -> std::path::PathBuf {
         let dir = std::env::temp_dir().join(format!("codex-monitor-{prefix}-{}", Uuid::new_v4()));
@@ -53,11 +486,12 @@ mod tests {
 
     #[test]
     fn workspace_agents_round_trip_requires_existing_root() {
+        let generations = Generations::new();
         let root = temp_dir("workspace-agents");
         fs::create_dir_all(&root).expect("create workspace root");
         let policy = policy_for(FileScope::Workspace, FileKind::Agents).expect("policy");
 
-        write_with_policy(&root, policy, "workspace agents").expect("write agents");
+        write_with_policy(&generations, &root, policy, "workspace agents").expect("write agents");
         let response = read_with_policy(&root, policy).expect("read agents");
 
         assert!(response.exists);
@@ -69,22 +503,24 @@ mod tests {
 
     #[test]
     fn workspace_agents_write_fails_when_root_missing() {
+        let generations = Generations::new();
         let root = temp_dir("workspace-missing-root");
         let policy = policy_for(FileScope::Workspace, FileKind::Agents).expect("policy");
 
-        let result = write_with_policy(&root, policy, "should fail");
+        let result = write_with_policy(&generations, &root, policy, "should fail");
         assert!(result.is_err());
     }
 
     #[test]
     fn global_agents_write_creates_root() {
+        let generations = Generations::new();
         let root = temp_dir("global-agents");
         let policy = policy_for(FileScope::Global, FileKind::Agents).expect("policy");
 
         let initial = read_with_policy(&root, policy).expect("initial read");
         assert!(!initial.exists);
 
-        write_with_policy(&root, policy, "global agents").expect("write agents");
+        write_with_policy(&generations, &root, policy, "global agents").expect("write agents");
         let response = read_with_policy(&root, policy).expect("read agents");
 
         assert!(response.exists);
@@ -96,10 +532,12 @@ mod tests {
 
     #[test]
     fn global_config_write_creates_root() {
+        let generations = Generations::new();
         let root = temp_dir("global-config");
         let policy = policy_for(FileScope::Global, FileKind::Config).expect("policy");
 
-        write_with_policy(&root, policy, "[model]\nname = \"test\"\n").expect("write config");
+        write_with_policy(&generations, &root, policy, "[model]\nname = \"test\"\n")
+            .expect("write config");
         let response = read_with_policy(&root, policy).expect("read config");
 
         assert!(response.exists);
@@ -108,4 +546,132 @@ mod tests {
 
         let _ = fs::remove_dir_all(&root);
     }
+
+    #[test]
+    fn initialize_workspace_skips_existing_files_unless_overwriting() {
+        let generations = Generations::new();
+        let root = temp_dir("workspace-initialize");
+        fs::create_dir_all(&root).expect("create workspace root");
+        let policy = policy_for(FileScope::Workspace, FileKind::Agents).expect("policy");
+
+        let first = initialize_workspace_files(&generations, &root, false).expect("initialize");
+        assert_eq!(first.created, vec![FileKind::Agents]);
+        assert!(first.skipped.is_empty());
+
+        write_with_policy(&generations, &root, policy, "custom").expect("write agents");
+        let second =
+            initialize_workspace_files(&generations, &root, false).expect("initialize again");
+        assert!(second.created.is_empty());
+        assert_eq!(second.skipped, vec![FileKind::Agents]);
+        assert_eq!(
+            read_with_policy(&root, policy).expect("read").content,
+            "custom"
+        );
+
+        let forced = initialize_workspace_files(&generations, &root, true).expect("overwrite");
+        assert_eq!(forced.created, vec![FileKind::Agents]);
+        assert_eq!(read_with_policy(&root, policy).expect("read").content, "");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn swap_exchanges_contents_exactly() {
+        let generations = Generations::new();
+        let workspace = temp_dir("swap-workspace");
+        let global = temp_dir("swap-global");
+        fs::create_dir_all(&workspace).expect("create workspace root");
+        let workspace_policy = policy_for(FileScope::Workspace, FileKind::Agents).expect("policy");
+        let global_policy = policy_for(FileScope::Global, FileKind::Agents).expect("policy");
+
+        assert!(swap_with_policy(
+            &generations,
+            &workspace,
+            workspace_policy,
+            &global,
+            global_policy
+        )
+        .is_err());
+
+        write_with_policy(&generations, &workspace, workspace_policy, "draft\n")
+            .expect("write draft");
+        write_with_policy(&generations, &global, global_policy, "active\r\nrules")
+            .expect("write active");
+        swap_with_policy(
+            &generations,
+            &workspace,
+            workspace_policy,
+            &global,
+            global_policy,
+        )
+        .expect("swap");
+
+        let workspace_content = read_with_policy(&workspace, workspace_policy).expect("read");
+        let global_content = read_with_policy(&global, global_policy).expect("read");
+        assert_eq!(workspace_content.content, "active\r\nrules");
+        assert_eq!(global_content.content, "draft\n");
+        let leftovers = fs::read_dir(&workspace)
+            .expect("list workspace")
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        let _ = fs::remove_dir_all(&workspace);
+        let _ = fs::remove_dir_all(&global);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn swap_refuses_symlinked_files() {
+        let generations = Generations::new();
+        let workspace = temp_dir("swap-symlink-workspace");
+        let global = temp_dir("swap-symlink-global");
+        fs::create_dir_all(&workspace).expect("create workspace root");
+        let workspace_policy = policy_for(FileScope::Workspace, FileKind::Agents).expect("policy");
+        let global_policy = policy_for(FileScope::Global, FileKind::Agents).expect("policy");
+        write_with_policy(&generations, &global, global_policy, "active").expect("write active");
+        fs::write(workspace.join("notes.md"), "draft").expect("write target");
+        std::os::unix::fs::symlink("notes.md", workspace.join("AGENTS.md")).expect("symlink");
+
+        let err = swap_with_policy(
+            &generations,
+            &workspace,
+            workspace_policy,
+            &global,
+            global_policy,
+        )
+        .expect_err("symlink");
+        assert!(err.contains("symbolic link"), "{err}");
+        assert_eq!(
+            fs::read_to_string(global.join("AGENTS.md")).expect("read"),
+            "active"
+        );
+
+        let _ = fs::remove_dir_all(&workspace);
+        let _ = fs::remove_dir_all(&global);
+    }
+
+    #[test]
+    fn generations_use_canonical_roots_and_count_deletes() {
+        let generations = Generations::new();
+        let root = temp_dir("generation-keys");
+        fs::create_dir_all(&root).expect("create workspace root");
+        let alias = root
+            .join(".")
+            .join("..")
+            .join(root.file_name().expect("name"));
+        let policy = policy_for(FileScope::Workspace, FileKind::Agents).expect("policy");
+        let files = |root: &std::path::Path| file_generation(&generations, &[(root, "AGENTS.md")]);
+
+        write_with_policy(&generations, &alias, policy, "rules").expect("write agents");
+        let written = files(&root);
+        assert!(written > 0);
+        assert_eq!(files(&alias), written);
+
+        assert!(remove_with_policy(&generations, &root, policy).expect("remove agents"));
+        assert!(files(&alias) > written);
+
+        let _ = fs::remove_dir_all(&root);
+    }
 }
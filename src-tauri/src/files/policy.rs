@@ -1,19 +1,210 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub(crate) enum FileScope {
     Workspace,
     Global,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+impl FileScope {
+    pub(crate) const ALL: [FileScope; 2] = [FileScope::Workspace, FileScope::Global];
+
+    /// The serialized name, which also starts its keys in
+    /// `AppSettings::scope_root_overrides`.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            FileScope::Workspace => "workspace",
+            FileScope::Global => "global",
+        }
+    }
+}
+
+/// Custom scope roots keyed by scope and workspace id, mirrored from the
+/// persisted app settings onto the app or daemon state. `None` is the global
+/// root used without a workspace or for a workspace that has no pinned
+/// CODEX_HOME.
+#[derive(Default)]
+pub(crate) struct ScopeRootOverrides {
+    roots: RwLock<BTreeMap<(FileScope, Option<String>), PathBuf>>,
+}
+
+/// The `AppSettings::scope_root_overrides` key for `scope` in `workspace_id`:
+/// `<scope>:<workspace id>`, or just `global` for the default global root.
+/// A workspace-scope override always names its workspace.
+pub(crate) fn scope_override_key(
+    scope: FileScope,
+    workspace_id: Option<&str>,
+) -> Result<String, String> {
+    match (scope, workspace_id) {
+        (_, Some(workspace_id)) => Ok(format!("{}:{workspace_id}", scope.as_str())),
+        (FileScope::Global, None) => Ok(scope.as_str().to_string()),
+        (FileScope::Workspace, None) => Err("workspaceId is required".to_string()),
+    }
+}
+
+impl ScopeRootOverrides {
+    pub(crate) fn new(overrides: &BTreeMap<String, String>) -> Self {
+        let active = Self::default();
+        active.replace(overrides);
+        active
+    }
+
+    /// Replaces the active overrides with `AppSettings::scope_root_overrides`.
+    /// Unknown scope names, and keys from before overrides were per workspace
+    /// that no longer mean anything, are ignored.
+    pub(crate) fn replace(&self, overrides: &BTreeMap<String, String>) {
+        let parsed = overrides
+            .iter()
+            .filter_map(|(key, path)| {
+                let (name, workspace_id) = match key.split_once(':') {
+                    Some((name, workspace_id)) => (name, Some(workspace_id.to_string())),
+                    None => (key.as_str(), None),
+                };
+                let scope = FileScope::ALL
+                    .into_iter()
+                    .find(|scope| scope.as_str() == name)?;
+                if scope == FileScope::Workspace && workspace_id.is_none() {
+                    return None;
+                }
+                Some(((scope, workspace_id), PathBuf::from(path)))
+            })
+            .collect();
+        if let Ok(mut roots) = self.roots.write() {
+            *roots = parsed;
+        }
+    }
+
+    /// The custom root `scope` resolves to for `workspace_id`, if one is set.
+    /// Kinds still resolve within it: the usual path checks apply against the
+    /// override root.
+    pub(crate) fn root(&self, scope: FileScope, workspace_id: Option<&str>) -> Option<PathBuf> {
+        self.roots
+            .read()
+            .ok()?
+            .get(&(scope, workspace_id.map(str::to_string)))
+            .cloned()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub(crate) enum FileKind {
     Agents,
     Config,
 }
 
+impl FileKind {
+    pub(crate) const ALL: [FileKind; 2] = [FileKind::Agents, FileKind::Config];
+
+    /// The serialized name.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            FileKind::Agents => "agents",
+            FileKind::Config => "config",
+        }
+    }
+}
+
+/// Identifies one scoped file, e.g. one side of a copy.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScopedFileRef {
+    pub(crate) scope: FileScope,
+    pub(crate) kind: FileKind,
+    #[serde(default)]
+    pub(crate) workspace_id: Option<String>,
+}
+
+const FILE_URI_PREFIX: &str = "codex://";
+
+/// The canonical link to a scoped file: `codex://global/<kind>` or
+/// `codex://workspace/<id>/<kind>`, with the id percent-encoded.
+pub(crate) fn file_uri(
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<&str>,
+) -> Result<String, String> {
+    policy_for(scope, kind)?;
+    match scope {
+        FileScope::Global => Ok(format!("{FILE_URI_PREFIX}global/{}", kind.as_str())),
+        FileScope::Workspace => {
+            let workspace_id = workspace_id
+                .filter(|id| !id.is_empty())
+                .ok_or_else(|| "workspace file URIs need a workspace id".to_string())?;
+            Ok(format!(
+                "{FILE_URI_PREFIX}workspace/{}/{}",
+                encode_uri_segment(workspace_id),
+                kind.as_str()
+            ))
+        }
+    }
+}
+
+/// Inverse of `file_uri`. Only combinations `policy_for` accepts parse.
+pub(crate) fn parse_file_uri(uri: &str) -> Result<ScopedFileRef, String> {
+    let invalid = |reason: &str| format!("Invalid file URI `{uri}`: {reason}");
+    let rest = uri
+        .strip_prefix(FILE_URI_PREFIX)
+        .ok_or_else(|| invalid("expected it to start with codex://"))?;
+    let segments: Vec<&str> = rest.split('/').collect();
+    let (scope, workspace_id, kind) = match segments.as_slice() {
+        ["global", kind] => (FileScope::Global, None, *kind),
+        ["workspace", id, kind] if !id.is_empty() => {
+            let id = decode_uri_segment(id).ok_or_else(|| invalid("bad workspace id encoding"))?;
+            (FileScope::Workspace, Some(id), *kind)
+        }
+        _ => {
+            return Err(invalid(
+                "expected codex://global/<kind> or codex://workspace/<id>/<kind>",
+            ))
+        }
+    };
+    let kind = FileKind::ALL
+        .into_iter()
+        .find(|candidate| candidate.as_str() == kind)
+        .ok_or_else(|| invalid(&format!("unknown file kind `{kind}`")))?;
+    policy_for(scope, kind).map_err(|err| invalid(&err))?;
+    Ok(ScopedFileRef {
+        scope,
+        kind,
+        workspace_id,
+    })
+}
+
+fn encode_uri_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn decode_uri_segment(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct FilePolicy {
     pub(crate) filename: &'static str,
@@ -23,6 +214,52 @@ pub(crate) struct FilePolicy {
     pub(crate) allow_external_symlink_target: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ContentFormat {
+    Markdown,
+    Toml,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TomlValueKind {
+    String,
+    Table,
+}
+
+/// Structural expectations for a kind's content, independent of scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KindRules {
+    pub(crate) format: ContentFormat,
+    pub(crate) warn_if_empty: bool,
+    pub(crate) typed_keys: &'static [(&'static str, TomlValueKind)],
+    /// Can hold tokens or API keys, so should only be readable by its owner.
+    pub(crate) may_hold_secrets: bool,
+}
+
+const CONFIG_TYPED_KEYS: &[(&str, TomlValueKind)] = &[
+    ("model", TomlValueKind::String),
+    ("model_reasoning_effort", TomlValueKind::String),
+    ("personality", TomlValueKind::String),
+    ("features", TomlValueKind::Table),
+];
+
+pub(crate) fn rules_for(kind: FileKind) -> KindRules {
+    match kind {
+        FileKind::Agents => KindRules {
+            format: ContentFormat::Markdown,
+            warn_if_empty: true,
+            typed_keys: &[],
+            may_hold_secrets: false,
+        },
+        FileKind::Config => KindRules {
+            format: ContentFormat::Toml,
+            warn_if_empty: false,
+            typed_keys: CONFIG_TYPED_KEYS,
+            may_hold_secrets: true,
+        },
+    }
+}
+
 const AGENTS_FILENAME: &str = "AGENTS.md";
 const CONFIG_FILENAME: &str = "config.toml";
 
@@ -55,9 +292,89 @@ pub(crate) fn policy_for(scope: FileScope, kind: FileKind) -> Result<FilePolicy,
     }
 }
 
+/// The flags of a resolved `FilePolicy`, in a form that can cross the IPC boundary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PolicySummary {
+    pub(crate) filename: String,
+    pub(crate) root_context: String,
+    pub(crate) root_may_be_missing: bool,
+    pub(crate) create_root: bool,
+    pub(crate) allow_external_symlink_target: bool,
+}
+
+impl From<FilePolicy> for PolicySummary {
+    fn from(policy: FilePolicy) -> Self {
+        Self {
+            filename: policy.filename.to_string(),
+            root_context: policy.root_context.to_string(),
+            root_may_be_missing: policy.root_may_be_missing,
+            create_root: policy.create_root,
+            allow_external_symlink_target: policy.allow_external_symlink_target,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SizeLimit {
+    pub(crate) name: String,
+    pub(crate) bytes: u64,
+}
+
+/// How a scope/kind/workspace combination resolves, for support and debugging.
+/// Invalid combinations carry `valid: false` and a `reason` instead of failing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PolicyExplanation {
+    pub(crate) scope: FileScope,
+    pub(crate) kind: FileKind,
+    pub(crate) valid: bool,
+    pub(crate) reason: Option<String>,
+    pub(crate) resolved_path: Option<String>,
+    pub(crate) policy: Option<PolicySummary>,
+    pub(crate) rules: Vec<String>,
+    pub(crate) size_limits: Vec<SizeLimit>,
+}
+
+/// Human-readable list of the validation rules `validate_kind_content` applies.
+pub(crate) fn describe_rules(rules: KindRules) -> Vec<String> {
+    let mut described = vec![match rules.format {
+        ContentFormat::Markdown => "Content is Markdown".to_string(),
+        ContentFormat::Toml => "Content must parse as TOML".to_string(),
+    }];
+    if rules.warn_if_empty {
+        described.push("Warns when content is empty".to_string());
+    }
+    for (key, value_kind) in rules.typed_keys {
+        let expected = match value_kind {
+            TomlValueKind::String => "a string",
+            TomlValueKind::Table => "a table",
+        };
+        described.push(format!("`{key}` must be {expected}"));
+    }
+    if rules.may_hold_secrets {
+        described.push("May hold secrets; should be readable only by its owner".to_string());
+    }
+    described
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{policy_for, FileKind, FileScope};
+    use super::{
+        describe_rules, file_uri, parse_file_uri, policy_for, rules_for, FileKind, FileScope,
+        ScopedFileRef,
+    };
+
+    #[test]
+    fn scope_names_match_serialized_form() {
+        for scope in FileScope::ALL {
+            assert_eq!(
+                serde_json::to_value(scope).expect("serialize"),
+                serde_json::Value::String(scope.as_str().to_string())
+            );
+        }
+    }
 
     #[test]
     fn workspace_agents_policy_is_strict() {
@@ -94,4 +411,59 @@ mod tests {
         let result = policy_for(FileScope::Workspace, FileKind::Config);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn describe_rules_lists_typed_keys() {
+        let rules = describe_rules(rules_for(FileKind::Config));
+        assert_eq!(rules[0], "Content must parse as TOML");
+        assert!(rules.contains(&"`model` must be a string".to_string()));
+        assert!(rules.contains(&"`features` must be a table".to_string()));
+
+        let rules = describe_rules(rules_for(FileKind::Agents));
+        assert_eq!(
+            rules,
+            vec!["Content is Markdown", "Warns when content is empty"]
+        );
+    }
+
+    #[test]
+    fn file_uris_round_trip_and_reject_invalid_combos() {
+        let cases = [
+            (FileScope::Global, FileKind::Agents, None),
+            (FileScope::Global, FileKind::Config, None),
+            (
+                FileScope::Workspace,
+                FileKind::Agents,
+                Some("ws 1/ä".to_string()),
+            ),
+        ];
+        for (scope, kind, workspace_id) in cases {
+            let uri = file_uri(scope, kind, workspace_id.as_deref()).expect("uri");
+            assert_eq!(
+                parse_file_uri(&uri).expect("parse"),
+                ScopedFileRef {
+                    scope,
+                    kind,
+                    workspace_id,
+                }
+            );
+        }
+        assert_eq!(
+            file_uri(FileScope::Workspace, FileKind::Agents, Some("ws-1")).as_deref(),
+            Ok("codex://workspace/ws-1/agents")
+        );
+        assert!(file_uri(FileScope::Workspace, FileKind::Config, Some("ws-1")).is_err());
+        assert!(file_uri(FileScope::Workspace, FileKind::Agents, None).is_err());
+
+        for uri in [
+            "codex://workspace/ws-1/config",
+            "codex://global/notes",
+            "codex://workspace//agents",
+            "codex://workspace/%zz/agents",
+            "https://global/agents",
+        ] {
+            let err = parse_file_uri(uri).expect_err(uri);
+            assert!(err.starts_with("Invalid file URI"), "{err}");
+        }
+    }
 }
@@ -0,0 +1,156 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter};
+
+use super::io::{FileRangeResponse, TextFileResponse};
+use super::policy::{FileKind, FileScope};
+use crate::remote_backend;
+use crate::state::AppState;
+
+pub(crate) const REMOTE_READ_PROGRESS_EVENT: &str = "remote-read-progress";
+const STREAM_CHUNK_BYTES: u32 = 256 * 1024;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RemoteReadProgress<'a> {
+    request_id: &'a str,
+    bytes_loaded: u64,
+    total_bytes: u64,
+}
+
+pub(crate) fn emit_read_progress(
+    app: &AppHandle,
+    request_id: &str,
+    bytes_loaded: u64,
+    total_bytes: u64,
+) {
+    let _ = app.emit(
+        REMOTE_READ_PROGRESS_EVENT,
+        RemoteReadProgress {
+            request_id,
+            bytes_loaded,
+            total_bytes,
+        },
+    );
+}
+
+/// Fetches a remote file in `file_read_range` chunks, emitting progress after
+/// each one. Backends without range support get a single `file_read`.
+/// `file_read_stream_cancel` stops the loop before the next chunk.
+pub(crate) async fn stream_remote_file(
+    state: &AppState,
+    app: &AppHandle,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    request_id: &str,
+) -> Result<TextFileResponse, String> {
+    state
+        .remote_reads_in_flight
+        .lock()
+        .await
+        .insert(request_id.to_string());
+    let result = fetch_ranges(state, app, scope, kind, workspace_id, request_id).await;
+    state.remote_reads_in_flight.lock().await.remove(request_id);
+    result
+}
+
+async fn fetch_ranges(
+    state: &AppState,
+    app: &AppHandle,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    request_id: &str,
+) -> Result<TextFileResponse, String> {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut expected_total = None;
+    loop {
+        if !state
+            .remote_reads_in_flight
+            .lock()
+            .await
+            .contains(request_id)
+        {
+            return Err("Remote file read cancelled".to_string());
+        }
+        let response = match remote_backend::call_remote(
+            state,
+            app.clone(),
+            "file_read_range",
+            json!({
+                "scope": scope,
+                "kind": kind,
+                "workspaceId": workspace_id,
+                "offset": buffer.len() as u64,
+                "length": STREAM_CHUNK_BYTES,
+            }),
+        )
+        .await
+        {
+            Ok(response) => response,
+            Err(error) if buffer.is_empty() && error.contains("unknown method") => {
+                return fetch_whole(state, app, scope, kind, workspace_id, request_id).await;
+            }
+            Err(error) => return Err(error),
+        };
+        let range: FileRangeResponse =
+            serde_json::from_value(response).map_err(|err| err.to_string())?;
+        if !range.exists {
+            emit_read_progress(app, request_id, 0, 0);
+            return Ok(TextFileResponse {
+                exists: false,
+                content: String::new(),
+                truncated: false,
+            });
+        }
+        if range.offset != buffer.len() as u64
+            || expected_total.is_some_and(|total| total != range.total_bytes)
+        {
+            return Err("Remote file changed while it was being read".to_string());
+        }
+        expected_total = Some(range.total_bytes);
+
+        let chunk = STANDARD
+            .decode(range.data.as_bytes())
+            .map_err(|err| format!("Invalid remote file chunk: {err}"))?;
+        buffer.extend_from_slice(&chunk);
+        emit_read_progress(app, request_id, buffer.len() as u64, range.total_bytes);
+        if buffer.len() as u64 >= range.total_bytes {
+            break;
+        }
+        if chunk.is_empty() {
+            return Err("Remote file ended before all bytes were read".to_string());
+        }
+    }
+
+    let content =
+        String::from_utf8(buffer).map_err(|_| "Remote file is not valid UTF-8".to_string())?;
+    Ok(TextFileResponse {
+        exists: true,
+        content,
+        truncated: false,
+    })
+}
+
+async fn fetch_whole(
+    state: &AppState,
+    app: &AppHandle,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    request_id: &str,
+) -> Result<TextFileResponse, String> {
+    let response = remote_backend::call_remote(
+        state,
+        app.clone(),
+        "file_read",
+        json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
+    )
+    .await?;
+    let file: TextFileResponse = serde_json::from_value(response).map_err(|err| err.to_string())?;
+    let size = file.content.len() as u64;
+    emit_read_progress(app, request_id, size, size);
+    Ok(file)
+}
@@ -0,0 +1,109 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::files::policy::FileKind;
+
+pub(crate) const MAX_SEARCH_RESULTS: u32 = 1000;
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SearchHit {
+    pub(crate) kind: FileKind,
+    pub(crate) filename: String,
+    pub(crate) line_number: u32,
+    pub(crate) line: String,
+}
+
+pub(crate) enum SearchMatcher {
+    Substring(String),
+    Pattern(Regex),
+}
+
+impl SearchMatcher {
+    pub(crate) fn new(query: &str, regex: bool) -> Result<Self, String> {
+        if query.is_empty() {
+            return Err("Search query is required".to_string());
+        }
+        if regex {
+            return Regex::new(query)
+                .map(Self::Pattern)
+                .map_err(|err| format!("Invalid search pattern: {err}"));
+        }
+        Ok(Self::Substring(query.to_string()))
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        match self {
+            Self::Substring(query) => line.contains(query.as_str()),
+            Self::Pattern(pattern) => pattern.is_match(line),
+        }
+    }
+}
+
+pub(crate) fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(BINARY_SNIFF_BYTES).any(|byte| *byte == 0)
+}
+
+/// Returns up to `limit` matching lines as 1-based `(line_number, line)` pairs.
+pub(crate) fn find_matching_lines(
+    content: &str,
+    matcher: &SearchMatcher,
+    limit: usize,
+) -> Vec<(u32, String)> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| matcher.is_match(line))
+        .take(limit)
+        .map(|(index, line)| ((index + 1) as u32, line.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_matching_lines, looks_binary, SearchMatcher};
+
+    #[test]
+    fn substring_matches_report_line_numbers() {
+        let matcher = SearchMatcher::new("model", false).expect("matcher");
+        let hits =
+            find_matching_lines("a = 1\nmodel = \"gpt-5\"\r\nb = 2\n# model\n", &matcher, 10);
+        assert_eq!(
+            hits,
+            vec![
+                (2, "model = \"gpt-5\"".to_string()),
+                (4, "# model".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn regex_matches_respect_limit() {
+        let matcher = SearchMatcher::new(r"^\w+ = \d$", true).expect("matcher");
+        let hits = find_matching_lines("a = 1\nb = 2\nc = 3\n", &matcher, 2);
+        assert_eq!(
+            hits,
+            vec![(1, "a = 1".to_string()), (2, "b = 2".to_string())]
+        );
+    }
+
+    #[test]
+    fn invalid_regex_is_reported() {
+        let error = SearchMatcher::new("(unclosed", true)
+            .err()
+            .expect("invalid pattern should fail");
+        assert!(error.starts_with("Invalid search pattern"));
+    }
+
+    #[test]
+    fn empty_query_is_rejected() {
+        assert!(SearchMatcher::new("", false).is_err());
+    }
+
+    #[test]
+    fn nul_bytes_mark_content_as_binary() {
+        assert!(looks_binary(b"abc\0def"));
+        assert!(!looks_binary(b"plain text"));
+    }
+}
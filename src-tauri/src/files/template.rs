@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::files::policy::FileKind;
+
+const DEFAULT_AGENTS_TEMPLATE: &str = "\
+# AGENTS.md
+
+Guidance for Codex agents working in this project.
+
+## Conventions
+
+- Match the style of the surrounding code.
+- Keep changes focused on the task at hand.
+- Run the project's tests before finishing.
+";
+
+/// One built-in default, as listed by `defaults_catalog`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DefaultInfo {
+    pub(crate) kind: FileKind,
+    pub(crate) label: String,
+    pub(crate) content: String,
+    /// Size of `content` in bytes.
+    pub(crate) size: u64,
+}
+
+/// Content a file of `kind` ships with, if it has one.
+pub(crate) fn builtin_default(kind: FileKind) -> Option<&'static str> {
+    match kind {
+        FileKind::Agents => Some(DEFAULT_AGENTS_TEMPLATE),
+        FileKind::Config => None,
+    }
+}
+
+fn builtin_default_label(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::Agents => "Starter AGENTS.md",
+        FileKind::Config => "config.toml",
+    }
+}
+
+/// Every kind that has a built-in default, in `FileKind::ALL` order.
+pub(crate) fn defaults_catalog() -> Vec<DefaultInfo> {
+    FileKind::ALL
+        .into_iter()
+        .filter_map(|kind| {
+            builtin_default(kind).map(|content| DefaultInfo {
+                kind,
+                label: builtin_default_label(kind).to_string(),
+                content: content.to_string(),
+                size: content.len() as u64,
+            })
+        })
+        .collect()
+}
+
+/// The result of `render_template_preview`: the rendered text, plus in strict
+/// mode the tokens that would make `render_template` fail.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RenderedTemplate {
+    pub(crate) content: String,
+    /// Unknown keys, once each in order of first use.
+    pub(crate) unresolved: Vec<String>,
+    pub(crate) unterminated: bool,
+}
+
+enum Unresolved<'a> {
+    Key(&'a str),
+    Unterminated,
+}
+
+/// Replaces `{{key}}` tokens (surrounding whitespace inside the braces is
+/// ignored) with values from `vars`. `\{{` renders a literal `{{`. Unknown or
+/// unterminated tokens are kept verbatim unless `strict` is set.
+pub(crate) fn render_template(
+    template: &str,
+    vars: &HashMap<String, String>,
+    strict: bool,
+) -> Result<String, String> {
+    render_with(template, vars, |unresolved| match unresolved {
+        _ if !strict => Ok(()),
+        Unresolved::Key(key) => Err(format!("Unknown template variable `{key}`")),
+        Unresolved::Unterminated => Err("Unterminated `{{` in template".to_string()),
+    })
+}
+
+/// Renders like the non-strict `render_template`, keeping unknown tokens
+/// verbatim. With `strict`, the tokens strict rendering would reject are
+/// reported instead of failing.
+pub(crate) fn render_template_preview(
+    template: &str,
+    vars: &HashMap<String, String>,
+    strict: bool,
+) -> RenderedTemplate {
+    let mut unresolved = Vec::new();
+    let mut unterminated = false;
+    let content = render_with(template, vars, |token| {
+        match token {
+            Unresolved::Key(key) if strict && !unresolved.iter().any(|seen| seen == key) => {
+                unresolved.push(key.to_string());
+            }
+            Unresolved::Unterminated => unterminated = strict,
+            Unresolved::Key(_) => {}
+        }
+        Ok(())
+    })
+    .unwrap_or_default();
+    RenderedTemplate {
+        content,
+        unresolved,
+        unterminated,
+    }
+}
+
+/// Unresolved tokens are kept verbatim when `on_unresolved` returns `Ok`.
+fn render_with(
+    template: &str,
+    vars: &HashMap<String, String>,
+    mut on_unresolved: impl FnMut(Unresolved<'_>) -> Result<(), String>,
+) -> Result<String, String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(index) = rest.find("{{") {
+        let (before, after_open) = (&rest[..index], &rest[index + 2..]);
+        if let Some(literal) = before.strip_suffix('\\') {
+            rendered.push_str(literal);
+            rendered.push_str("{{");
+            rest = after_open;
+            continue;
+        }
+        rendered.push_str(before);
+
+        let Some(close) = after_open.find("}}") else {
+            on_unresolved(Unresolved::Unterminated)?;
+            rendered.push_str(&rest[index..]);
+            return Ok(rendered);
+        };
+        let key = after_open[..close].trim();
+        match vars.get(key) {
+            Some(value) => rendered.push_str(value),
+            None => {
+                on_unresolved(Unresolved::Key(key))?;
+                rendered.push_str(&rest[index..index + close + 4]);
+            }
+        }
+        rest = &after_open[close + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{defaults_catalog, render_template, render_template_preview};
+    use crate::files::policy::FileKind;
+    use std::collections::HashMap;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("workspace_name".to_string(), "Demo".to_string()),
+            ("date".to_string(), "2026-01-02".to_string()),
+        ])
+    }
+
+    #[test]
+    fn substitutes_known_tokens() {
+        let rendered =
+            render_template("# {{workspace_name}} ({{ date }})\n", &vars(), true).expect("render");
+        assert_eq!(rendered, "# Demo (2026-01-02)\n");
+    }
+
+    #[test]
+    fn unknown_tokens_are_kept_unless_strict() {
+        let rendered = render_template("Hi {{ missing }}!", &vars(), false).expect("render");
+        assert_eq!(rendered, "Hi {{ missing }}!");
+        let error =
+            render_template("Hi {{ missing }}!", &vars(), true).expect_err("strict should fail");
+        assert!(error.contains("missing"));
+    }
+
+    #[test]
+    fn escaped_braces_render_literally() {
+        let rendered = render_template(r"\{{workspace_name}} is {{workspace_name}}", &vars(), true)
+            .expect("render");
+        assert_eq!(rendered, "{{workspace_name}} is Demo");
+    }
+
+    #[test]
+    fn unterminated_token_is_kept_unless_strict() {
+        assert_eq!(
+            render_template("a {{ b", &vars(), false).expect("render"),
+            "a {{ b"
+        );
+        assert!(render_template("a {{ b", &vars(), true).is_err());
+    }
+
+    #[test]
+    fn preview_reports_unresolved_tokens_in_strict_mode() {
+        let preview = render_template_preview(
+            "{{workspace_name}} {{ owner }} {{owner}} {{ team }} {{ tail",
+            &vars(),
+            true,
+        );
+        assert_eq!(
+            preview.content,
+            "Demo {{ owner }} {{owner}} {{ team }} {{ tail"
+        );
+        assert_eq!(preview.unresolved, vec!["owner", "team"]);
+        assert!(preview.unterminated);
+
+        let lenient = render_template_preview("{{ owner }} {{", &vars(), false);
+        assert_eq!(lenient.content, "{{ owner }} {{");
+        assert!(lenient.unresolved.is_empty());
+        assert!(!lenient.unterminated);
+    }
+
+    #[test]
+    fn catalog_lists_only_kinds_with_a_default() {
+        let catalog = defaults_catalog();
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog[0].kind, FileKind::Agents);
+        assert!(catalog[0].content.starts_with("# AGENTS.md"));
+        assert_eq!(catalog[0].size, catalog[0].content.len() as u64);
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use toml_edit::{Document, Item};
+
+use crate::files::policy::{rules_for, ContentFormat, FileKind, KindRules, TomlValueKind};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ValidationSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ValidationIssue {
+    pub(crate) severity: ValidationSeverity,
+    pub(crate) message: String,
+    pub(crate) line: Option<u32>,
+}
+
+/// Applies the kind's rules from `files::policy`; an empty result means valid.
+pub(crate) fn validate_kind_content(kind: FileKind, content: &str) -> Vec<ValidationIssue> {
+    let rules = rules_for(kind);
+    let mut issues = Vec::new();
+    if rules.warn_if_empty && content.trim().is_empty() {
+        issues.push(ValidationIssue {
+            severity: ValidationSeverity::Warning,
+            message: "File is empty".to_string(),
+            line: None,
+        });
+    }
+    match rules.format {
+        ContentFormat::Markdown => {}
+        ContentFormat::Toml => validate_toml(content, rules, &mut issues),
+    }
+    issues
+}
+
+fn validate_toml(content: &str, rules: KindRules, issues: &mut Vec<ValidationIssue>) {
+    if content.trim().is_empty() {
+        return;
+    }
+    let document = match content.parse::<Document>() {
+        Ok(document) => document,
+        Err(err) => {
+            let line = err.span().map(|span| line_number_at(content, span.start));
+            issues.push(ValidationIssue {
+                severity: ValidationSeverity::Error,
+                message: err.message().to_string(),
+                line,
+            });
+            return;
+        }
+    };
+
+    for (key, expected) in rules.typed_keys {
+        let Some(item) = document.get(key) else {
+            continue;
+        };
+        let matches = match expected {
+            TomlValueKind::String => item.as_str().is_some(),
+            TomlValueKind::Table => item.is_table_like(),
+        };
+        if matches {
+            continue;
+        }
+        let expected_label = match expected {
+            TomlValueKind::String => "a string",
+            TomlValueKind::Table => "a table",
+        };
+        issues.push(ValidationIssue {
+            severity: ValidationSeverity::Error,
+            message: format!("`{key}` must be {expected_label}"),
+            line: key_line(content, &document, key),
+        });
+    }
+}
+
+fn key_line(content: &str, document: &Document, key: &str) -> Option<u32> {
+    let item = document.get(key)?;
+    let span = match item {
+        Item::Value(value) => value.span(),
+        Item::Table(table) => table.span(),
+        _ => None,
+    }?;
+    Some(line_number_at(content, span.start))
+}
+
+fn line_number_at(content: &str, offset: usize) -> u32 {
+    let offset = offset.min(content.len());
+    content.as_bytes()[..offset]
+        .iter()
+        .filter(|byte| **byte == b'\n')
+        .count() as u32
+        + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_kind_content, ValidationSeverity};
+    use crate::files::policy::FileKind;
+
+    #[test]
+    fn valid_config_has_no_issues() {
+        let issues = validate_kind_content(
+            FileKind::Config,
+            "model = \"gpt-5\"\n[features]\nsteer = true\n",
+        );
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn config_parse_error_reports_line() {
+        let issues = validate_kind_content(FileKind::Config, "model = \"gpt-5\"\nbroken =\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, ValidationSeverity::Error);
+        assert_eq!(issues[0].line, Some(2));
+    }
+
+    #[test]
+    fn config_key_with_wrong_type_is_reported() {
+        let issues = validate_kind_content(FileKind::Config, "model = 5\nfeatures = \"on\"\n");
+        let messages: Vec<&str> = issues.iter().map(|issue| issue.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["`model` must be a string", "`features` must be a table"]
+        );
+    }
+
+    #[test]
+    fn empty_agents_file_warns() {
+        let issues = validate_kind_content(FileKind::Agents, "  \n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, ValidationSeverity::Warning);
+        assert!(validate_kind_content(FileKind::Agents, "# Rules\n").is_empty());
+    }
+}
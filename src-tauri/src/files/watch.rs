@@ -0,0 +1,743 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::backend::events::{BatchChanged, EventSink, ScopeChangeKind, ScopeChanged};
+
+/// Directories deeper than this below the scope root are not watched.
+const MAX_WATCH_DEPTH: usize = 4;
+/// Stops walking once this many entries are tracked, so huge trees stay cheap.
+const MAX_WATCH_ENTRIES: usize = 5_000;
+/// Only used when the platform watcher cannot be started.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(750);
+/// How long the tree must go without file system events before it is diffed.
+const WATCH_QUIET: Duration = Duration::from_millis(150);
+/// A tree that keeps changing is still diffed after this long.
+const MAX_WATCH_SETTLE: Duration = Duration::from_secs(1);
+/// How long a closed batch waits for the watchers to report its final writes:
+/// event delivery, which FSEvents delays by a few hundred milliseconds, plus
+/// the quiet period.
+const BATCH_SETTLE: Duration = Duration::from_millis(600);
+/// How long a path must stay quiet before its coalesced change is emitted.
+pub(crate) const DEFAULT_COALESCE_WINDOW: Duration = Duration::from_millis(100);
+pub(crate) const MAX_COALESCE_WINDOW: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EntryStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    is_dir: bool,
+}
+
+/// Entries under a scope root keyed by `/`-separated relative path.
+pub(crate) type Snapshot = BTreeMap<String, EntryStamp>;
+
+/// Walks `root` without following symlinks, so nothing outside the scope is
+/// ever visited. Unreadable entries are skipped.
+pub(crate) fn snapshot_dir(root: &Path) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    let mut stack: Vec<(PathBuf, usize)> = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if snapshot.len() >= MAX_WATCH_ENTRIES {
+                return snapshot;
+            }
+            let path = entry.path();
+            let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if metadata.file_type().is_symlink() {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            snapshot.insert(
+                relative,
+                EntryStamp {
+                    modified: metadata.modified().ok(),
+                    len: metadata.len(),
+                    is_dir: metadata.is_dir(),
+                },
+            );
+            if metadata.is_dir() && depth + 1 < MAX_WATCH_DEPTH {
+                stack.push((path, depth + 1));
+            }
+        }
+    }
+    snapshot
+}
+
+pub(crate) fn diff_snapshots(
+    before: &Snapshot,
+    after: &Snapshot,
+) -> Vec<(String, ScopeChangeKind)> {
+    let mut changes = Vec::new();
+    for (path, stamp) in after {
+        match before.get(path) {
+            None => changes.push((path.clone(), ScopeChangeKind::Added)),
+            // Directory mtimes move whenever a child changes; the child is reported instead.
+            Some(previous) if previous != stamp && !(previous.is_dir && stamp.is_dir) => {
+                changes.push((path.clone(), ScopeChangeKind::Modified))
+            }
+            Some(_) => {}
+        }
+    }
+    for path in before.keys() {
+        if !after.contains_key(path) {
+            changes.push((path.clone(), ScopeChangeKind::Removed));
+        }
+    }
+    changes
+}
+
+/// Net effect of two changes to one path in a row; `None` when they cancel out.
+fn merge_changes(previous: ScopeChangeKind, next: ScopeChangeKind) -> Option<ScopeChangeKind> {
+    match (previous, next) {
+        (ScopeChangeKind::Added, ScopeChangeKind::Removed) => None,
+        (ScopeChangeKind::Added, _) => Some(ScopeChangeKind::Added),
+        (ScopeChangeKind::Removed, ScopeChangeKind::Added) => Some(ScopeChangeKind::Modified),
+        (_, next) => Some(next),
+    }
+}
+
+/// Collapses changes to the same path under the same watch into one event,
+/// emitted once the path has been quiet for `window`.
+pub(crate) struct EventCoalescer {
+    window: Duration,
+    pending: BTreeMap<(String, String), (ScopeChangeKind, Instant)>,
+}
+
+impl EventCoalescer {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, event: ScopeChanged, now: Instant) {
+        let key = (event.watch_id, event.path);
+        let merged = match self.pending.get(&key) {
+            Some((previous, _)) => merge_changes(*previous, event.change),
+            None => Some(event.change),
+        };
+        match merged {
+            Some(change) => {
+                self.pending.insert(key, (change, now));
+            }
+            None => {
+                self.pending.remove(&key);
+            }
+        }
+    }
+
+    /// Removes and returns the changes whose paths have been quiet long enough.
+    pub(crate) fn drain_ready(&mut self, now: Instant) -> Vec<ScopeChanged> {
+        let window = self.window;
+        let ready: Vec<(String, String)> = self
+            .pending
+            .iter()
+            .filter(|(_, (_, last_seen))| now.saturating_duration_since(*last_seen) >= window)
+            .map(|(key, _)| key.clone())
+            .collect();
+        ready
+            .into_iter()
+            .filter_map(|key| {
+                let (change, _) = self.pending.remove(&key)?;
+                Some(ScopeChanged {
+                    watch_id: key.0,
+                    path: key.1,
+                    change,
+                })
+            })
+            .collect()
+    }
+
+    /// Removes and returns every pending change from the watches `matches`
+    /// accepts, quiet or not.
+    fn drain_watches(&mut self, mut matches: impl FnMut(&str) -> bool) -> Vec<ScopeChanged> {
+        let keys: Vec<(String, String)> = self
+            .pending
+            .keys()
+            .filter(|(watch_id, _)| matches(watch_id))
+            .cloned()
+            .collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let (change, _) = self.pending.remove(&key)?;
+                Some(ScopeChanged {
+                    watch_id: key.0,
+                    path: key.1,
+                    change,
+                })
+            })
+            .collect()
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.pending
+            .values()
+            .map(|(_, last_seen)| *last_seen + self.window)
+            .min()
+    }
+}
+
+/// Changes held back while an `EventBatch` is open, from the watches whose
+/// root contains `root` or lies inside it.
+struct OpenBatch {
+    batch_id: String,
+    root: PathBuf,
+    depth: u32,
+    changes: Vec<ScopeChanged>,
+}
+
+impl OpenBatch {
+    /// Merges `event` into the change already held for its path, keeping the
+    /// position where that path first changed.
+    fn push(&mut self, event: ScopeChanged) {
+        let existing = self
+            .changes
+            .iter()
+            .position(|held| held.watch_id == event.watch_id && held.path == event.path);
+        let Some(index) = existing else {
+            self.changes.push(event);
+            return;
+        };
+        match merge_changes(self.changes[index].change, event.change) {
+            Some(change) => self.changes[index].change = change,
+            None => {
+                self.changes.remove(index);
+            }
+        }
+    }
+
+    fn covers(&self, watch_root: &Path) -> bool {
+        watch_root.starts_with(&self.root) || self.root.starts_with(watch_root)
+    }
+}
+
+struct CoalescerState {
+    coalescer: EventCoalescer,
+    flushing: bool,
+    /// Canonical root of every running watch, which decides the batch its
+    /// changes are held in.
+    watch_roots: BTreeMap<String, PathBuf>,
+    batches: Vec<OpenBatch>,
+}
+
+impl CoalescerState {
+    fn open_batch(&mut self, batch_id: String, root: PathBuf) {
+        match self
+            .batches
+            .iter_mut()
+            .find(|batch| batch.batch_id == batch_id)
+        {
+            Some(batch) => batch.depth += 1,
+            None => self.batches.push(OpenBatch {
+                batch_id,
+                root,
+                depth: 1,
+                changes: Vec::new(),
+            }),
+        }
+    }
+
+    /// Holds `event` in the oldest open batch covering its watch; returns it
+    /// when no batch does.
+    fn hold(&mut self, event: ScopeChanged) -> Option<ScopeChanged> {
+        let Some(watch_root) = self.watch_roots.get(&event.watch_id) else {
+            return Some(event);
+        };
+        match self
+            .batches
+            .iter_mut()
+            .find(|batch| batch.covers(watch_root))
+        {
+            Some(batch) => {
+                batch.push(event);
+                None
+            }
+            None => Some(event),
+        }
+    }
+
+    /// Closes one level of `batch_id`; returns whether that was the last.
+    fn close_batch(&mut self, batch_id: &str) -> bool {
+        match self
+            .batches
+            .iter_mut()
+            .find(|batch| batch.batch_id == batch_id)
+        {
+            Some(batch) => {
+                batch.depth = batch.depth.saturating_sub(1);
+                batch.depth == 0
+            }
+            None => false,
+        }
+    }
+
+    /// Removes a fully closed batch together with the pending changes of the
+    /// watches it covers. A batch reopened since it closed is left alone.
+    fn take_batch(&mut self, batch_id: &str) -> Option<BatchChanged> {
+        let index = self
+            .batches
+            .iter()
+            .position(|batch| batch.batch_id == batch_id && batch.depth == 0)?;
+        let mut batch = self.batches.remove(index);
+        let watch_roots = &self.watch_roots;
+        let pending = self.coalescer.drain_watches(|watch_id| {
+            watch_roots
+                .get(watch_id)
+                .is_some_and(|watch_root| batch.covers(watch_root))
+        });
+        for event in pending {
+            batch.push(event);
+        }
+        (!batch.changes.is_empty()).then(|| BatchChanged {
+            batch_id: batch.batch_id,
+            changes: batch.changes,
+        })
+    }
+}
+
+/// The coalescer every scope watch emits through, so overlapping watches and
+/// bursts of writes reach the frontend as one change per path.
+#[derive(Clone)]
+pub(crate) struct SharedCoalescer {
+    state: Arc<Mutex<CoalescerState>>,
+}
+
+impl Default for SharedCoalescer {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CoalescerState {
+                coalescer: EventCoalescer::new(DEFAULT_COALESCE_WINDOW),
+                flushing: false,
+                watch_roots: BTreeMap::new(),
+                batches: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl SharedCoalescer {
+    pub(crate) fn set_window(&self, window: Duration) -> Result<(), String> {
+        if window > MAX_COALESCE_WINDOW {
+            return Err(format!(
+                "coalesce window must be at most {}ms",
+                MAX_COALESCE_WINDOW.as_millis()
+            ));
+        }
+        if let Ok(mut state) = self.state.lock() {
+            state.coalescer.window = window;
+        }
+        Ok(())
+    }
+
+    /// Records the canonical root `watch_id` watches, so batches opened on
+    /// that tree hold its changes.
+    pub(crate) fn register_watch(&self, watch_id: &str, root: &Path) {
+        if let Ok(mut state) = self.state.lock() {
+            state
+                .watch_roots
+                .insert(watch_id.to_string(), root.to_path_buf());
+        }
+    }
+
+    pub(crate) fn unregister_watch(&self, watch_id: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            state.watch_roots.remove(watch_id);
+        }
+    }
+
+    /// Queues `event`, starting a flush task on `sink` if none is running.
+    pub(crate) fn submit<E: EventSink>(&self, sink: &E, event: ScopeChanged) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.coalescer.push(event, Instant::now());
+        if state.flushing {
+            return;
+        }
+        state.flushing = true;
+        drop(state);
+        tokio::spawn(self.clone().flush(sink.clone()));
+    }
+
+    async fn flush<E: EventSink>(self, sink: E) {
+        loop {
+            let wait = {
+                let Ok(mut state) = self.state.lock() else {
+                    return;
+                };
+                match state.coalescer.next_deadline() {
+                    Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                    None => {
+                        state.flushing = false;
+                        return;
+                    }
+                }
+            };
+            tokio::time::sleep(wait).await;
+            let ready: Vec<ScopeChanged> = match self.state.lock() {
+                Ok(mut state) => {
+                    let ready = state.coalescer.drain_ready(Instant::now());
+                    ready
+                        .into_iter()
+                        .filter_map(|event| state.hold(event))
+                        .collect()
+                }
+                Err(_) => return,
+            };
+            for event in ready {
+                sink.emit_scope_changed(event);
+            }
+        }
+    }
+
+    /// Holds back the `scope-changed` events of watches on `root` until the
+    /// returned batch is finished, then emits them as one `batch-changed`
+    /// event. Watches on other trees keep emitting as usual. Opening a batch
+    /// with the id of one already open joins it.
+    pub(crate) fn begin_batch<E: EventSink>(
+        &self,
+        sink: &E,
+        batch_id: String,
+        root: &Path,
+    ) -> EventBatch<E> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        if let Ok(mut state) = self.state.lock() {
+            state.open_batch(batch_id.clone(), root);
+        }
+        EventBatch {
+            coalescer: self.clone(),
+            sink: sink.clone(),
+            batch_id,
+            closed: false,
+        }
+    }
+
+    /// Closes one level of `batch_id`. Returns how long the watchers need to
+    /// pick up the operation's final writes when that was the last level.
+    fn end_batch(&self, batch_id: &str) -> Option<Duration> {
+        let mut state = self.state.lock().ok()?;
+        state
+            .close_batch(batch_id)
+            .then(|| BATCH_SETTLE + state.coalescer.window)
+    }
+
+    fn flush_batch<E: EventSink>(&self, sink: &E, batch_id: &str) {
+        let batch = match self.state.lock() {
+            Ok(mut state) => state.take_batch(batch_id),
+            Err(_) => None,
+        };
+        if let Some(batch) = batch {
+            sink.emit_batch_changed(batch);
+        }
+    }
+}
+
+/// An open event batch; see `SharedCoalescer::begin_batch`. `finish` flushes
+/// it once the watchers caught up. Dropping it unfinished, e.g. when the
+/// operation is cancelled, flushes what was held right away.
+pub(crate) struct EventBatch<E: EventSink> {
+    coalescer: SharedCoalescer,
+    sink: E,
+    batch_id: String,
+    closed: bool,
+}
+
+impl<E: EventSink> EventBatch<E> {
+    /// Closes the batch and, unless an enclosing one with the same id is
+    /// still open, waits for the watchers to report the final writes and
+    /// emits the held changes.
+    pub(crate) async fn finish(mut self) {
+        self.closed = true;
+        if let Some(settle) = self.coalescer.end_batch(&self.batch_id) {
+            tokio::time::sleep(settle).await;
+            self.coalescer.flush_batch(&self.sink, &self.batch_id);
+        }
+    }
+}
+
+impl<E: EventSink> Drop for EventBatch<E> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.coalescer.end_batch(&self.batch_id);
+        }
+        self.coalescer.flush_batch(&self.sink, &self.batch_id);
+    }
+}
+
+/// Starts the platform watcher on `root`; every event other than a plain
+/// access wakes `events`. The watcher stops when dropped.
+fn start_fs_watcher(
+    root: &Path,
+    events: mpsc::UnboundedSender<()>,
+) -> notify::Result<notify::RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|event| !matches!(event.kind, EventKind::Access(_))) {
+            let _ = events.send(());
+        }
+    })?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    Ok(watcher)
+}
+
+/// Waits until `events` has been quiet for `WATCH_QUIET`, or `MAX_WATCH_SETTLE`
+/// has passed, so a burst of writes is diffed once. Returns `false` once the
+/// watcher has gone away.
+async fn settle(events: &mut mpsc::UnboundedReceiver<()>) -> bool {
+    let deadline = tokio::time::Instant::now() + MAX_WATCH_SETTLE;
+    loop {
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        match tokio::time::timeout_at((now + WATCH_QUIET).min(deadline), events.recv()).await {
+            Ok(Some(())) => {}
+            Ok(None) => return false,
+            Err(_) => return true,
+        }
+    }
+}
+
+/// Watches `root` until the task is aborted, emitting `scope-changed` events
+/// through `coalescer`. File system events only say that something changed;
+/// the tree is then re-walked with `snapshot_dir`, so symlinks, depth and
+/// entry limits apply as before. Falls back to polling when the platform
+/// watcher is unavailable.
+pub(crate) async fn run_scope_watch<E: EventSink>(
+    sink: E,
+    coalescer: SharedCoalescer,
+    watch_id: String,
+    root: PathBuf,
+) {
+    let (events_tx, mut events) = mpsc::unbounded_channel();
+    let watcher = match start_fs_watcher(&root, events_tx) {
+        Ok(watcher) => Some(watcher),
+        Err(err) => {
+            eprintln!("scope watch {watch_id}: polling {}: {err}", root.display());
+            None
+        }
+    };
+    let initial_root = root.clone();
+    let Ok(mut reported) = tokio::task::spawn_blocking(move || snapshot_dir(&initial_root)).await
+    else {
+        return;
+    };
+    loop {
+        if watcher.is_some() {
+            if events.recv().await.is_none() || !settle(&mut events).await {
+                return;
+            }
+        } else {
+            tokio::time::sleep(WATCH_POLL_INTERVAL).await;
+        }
+        let walk_root = root.clone();
+        let Ok(current) = tokio::task::spawn_blocking(move || snapshot_dir(&walk_root)).await
+        else {
+            return;
+        };
+        for (path, change) in diff_snapshots(&reported, &current) {
+            coalescer.submit(
+                &sink,
+                ScopeChanged {
+                    watch_id: watch_id.clone(),
+                    path,
+                    change,
+                },
+            );
+        }
+        reported = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-watch-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn diff_reports_added_modified_and_removed_files() {
+        let root = temp_dir();
+        std::fs::write(root.join("keep.md"), "a").expect("write");
+        std::fs::write(root.join("gone.md"), "a").expect("write");
+        let before = snapshot_dir(&root);
+
+        std::fs::write(root.join("keep.md"), "changed").expect("write");
+        std::fs::remove_file(root.join("gone.md")).expect("remove");
+        std::fs::create_dir(root.join("docs")).expect("mkdir");
+        std::fs::write(root.join("docs/new.md"), "a").expect("write");
+        let after = snapshot_dir(&root);
+
+        let changes = diff_snapshots(&before, &after);
+        assert!(changes.contains(&("keep.md".to_string(), ScopeChangeKind::Modified)));
+        assert!(changes.contains(&("gone.md".to_string(), ScopeChangeKind::Removed)));
+        assert!(changes.contains(&("docs".to_string(), ScopeChangeKind::Added)));
+        assert!(changes.contains(&("docs/new.md".to_string(), ScopeChangeKind::Added)));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn snapshot_does_not_follow_symlinks_out_of_scope() {
+        let root = temp_dir();
+        let outside = temp_dir();
+        std::fs::write(outside.join("secret.md"), "a").expect("write");
+        std::os::unix::fs::symlink(&outside, root.join("link")).expect("symlink");
+
+        let snapshot = snapshot_dir(&root);
+        assert!(snapshot.is_empty());
+
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&outside);
+    }
+
+    #[test]
+    fn settle_waits_for_a_quiet_period_and_stops_with_the_watcher() {
+        let runtime = tokio::runtime::Runtime::new().expect("runtime");
+        runtime.block_on(async {
+            let (events_tx, mut events) = mpsc::unbounded_channel();
+            events_tx.send(()).expect("send");
+            events_tx.send(()).expect("send");
+
+            let started = Instant::now();
+            assert!(settle(&mut events).await);
+            assert!(started.elapsed() >= WATCH_QUIET);
+            assert!(events.try_recv().is_err(), "the burst is consumed");
+
+            drop(events_tx);
+            assert!(!settle(&mut events).await);
+        });
+    }
+
+    fn change(path: &str, change: ScopeChangeKind) -> ScopeChanged {
+        ScopeChanged {
+            watch_id: "watch-1".to_string(),
+            path: path.to_string(),
+            change,
+        }
+    }
+
+    #[test]
+    fn coalescer_emits_one_change_for_a_burst_of_events() {
+        let window = Duration::from_millis(100);
+        let mut coalescer = EventCoalescer::new(window);
+        let start = Instant::now();
+
+        coalescer.push(change("AGENTS.md", ScopeChangeKind::Added), start);
+        for offset in [10, 20, 40, 60] {
+            coalescer.push(
+                change("AGENTS.md", ScopeChangeKind::Modified),
+                start + Duration::from_millis(offset),
+            );
+            assert!(coalescer
+                .drain_ready(start + Duration::from_millis(offset))
+                .is_empty());
+        }
+        coalescer.push(change("tmp.md", ScopeChangeKind::Added), start);
+        coalescer.push(change("tmp.md", ScopeChangeKind::Removed), start);
+
+        assert!(coalescer
+            .drain_ready(start + Duration::from_millis(150))
+            .is_empty());
+        let ready = coalescer.drain_ready(start + Duration::from_millis(160));
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].path, "AGENTS.md");
+        assert_eq!(ready[0].change, ScopeChangeKind::Added);
+        assert!(coalescer.drain_ready(start + window * 10).is_empty());
+    }
+
+    #[test]
+    fn batch_merges_changes_per_path_in_first_seen_order() {
+        let mut batch = OpenBatch {
+            batch_id: "import".to_string(),
+            root: PathBuf::from("/workspace"),
+            depth: 1,
+            changes: Vec::new(),
+        };
+        batch.push(change("b.md", ScopeChangeKind::Modified));
+        batch.push(change("a.md", ScopeChangeKind::Added));
+        batch.push(change("tmp.md", ScopeChangeKind::Added));
+        batch.push(change("b.md", ScopeChangeKind::Removed));
+        batch.push(change("a.md", ScopeChangeKind::Modified));
+        batch.push(change("tmp.md", ScopeChangeKind::Removed));
+
+        let summary: Vec<_> = batch
+            .changes
+            .iter()
+            .map(|event| (event.path.as_str(), event.change))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("b.md", ScopeChangeKind::Removed),
+                ("a.md", ScopeChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn batches_hold_only_their_watches_until_the_last_close() {
+        let mut state = CoalescerState {
+            coalescer: EventCoalescer::new(Duration::from_millis(100)),
+            flushing: false,
+            watch_roots: BTreeMap::from([
+                ("watch-1".to_string(), PathBuf::from("/workspace/a")),
+                ("watch-2".to_string(), PathBuf::from("/workspace/b")),
+            ]),
+            batches: Vec::new(),
+        };
+        let other = ScopeChanged {
+            watch_id: "watch-2".to_string(),
+            ..change("AGENTS.md", ScopeChangeKind::Modified)
+        };
+
+        state.open_batch("import".to_string(), PathBuf::from("/workspace/a"));
+        state.open_batch("import".to_string(), PathBuf::from("/workspace/a"));
+        assert!(state
+            .hold(change("AGENTS.md", ScopeChangeKind::Added))
+            .is_none());
+        assert_eq!(
+            state.hold(other.clone()).map(|event| event.watch_id),
+            Some("watch-2".to_string())
+        );
+        state
+            .coalescer
+            .push(change("notes.md", ScopeChangeKind::Added), Instant::now());
+        state.coalescer.push(other, Instant::now());
+
+        assert!(!state.close_batch("import"));
+        assert!(state.take_batch("import").is_none(), "still open");
+        assert!(state.close_batch("import"));
+        let batch = state.take_batch("import").expect("batch");
+        let paths: Vec<_> = batch
+            .changes
+            .iter()
+            .map(|event| event.path.as_str())
+            .collect();
+        assert_eq!(batch.batch_id, "import");
+        assert_eq!(paths, vec!["AGENTS.md", "notes.md"]);
+        assert!(state.batches.is_empty());
+        let pending = state.coalescer.drain_watches(|_| true);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].watch_id, "watch-2");
+    }
+}
@@ -116,6 +116,9 @@ pub fn run() {
         .setup(|app| {
             let state = state::AppState::load(&app.handle());
             app.manage(state);
+            tauri::async_runtime::spawn(workspaces::workspace_health_on_startup(
+                app.handle().clone(),
+            ));
             #[cfg(target_os = "macos")]
             {
                 let tray_state = app.state::<tray::TrayState>();
@@ -182,9 +185,77 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             settings::get_app_settings,
             settings::update_app_settings,
+            settings::feature_preset_save,
+            settings::feature_preset_list,
+            settings::feature_preset_apply,
+            settings::feature_preset_apply_all,
+            settings::feature_preset_delete,
             settings::get_codex_config_path,
+            settings::codex_home_relocate,
+            settings::locations,
+            settings::app_setting_get,
+            settings::app_setting_set,
+            settings::app_settings_all,
+            remote_backend::get_remote_rate_limits,
+            remote_backend::set_remote_rate_limits,
+            remote_backend::offline_status,
+            remote_backend::remote_pressure,
+            remote_backend::remote_method_stats,
+            remote_backend::remote_capabilities,
+            remote_backend::remote_reset_all,
             files::file_read,
+            files::file_read_capped,
+            files::file_read_lines_normalized,
+            files::file_read_streamed,
+            files::file_read_stream_cancel,
+            files::file_link_info,
+            files::file_writable,
+            files::file_permissions_audit,
+            files::file_fix_permissions,
+            files::file_touch,
+            files::file_truncate,
+            files::open_in_external_editor,
+            files::file_write_symlink,
+            files::workspace_initialize,
+            files::workspace_readiness,
+            files::workspace_prewarm,
+            files::agents_effective,
             files::file_write,
+            files::file_write_report,
+            files::file_write_preview,
+            files::file_write_templated,
+            files::file_render_template,
+            files::defaults_catalog,
+            files::file_uri,
+            files::parse_file_uri,
+            files::file_copy,
+            files::file_snapshot,
+            files::file_snapshot_list,
+            files::file_snapshot_restore,
+            files::cleanup_orphans,
+            files::tree_scan_start,
+            files::tree_scan_cancel,
+            files::file_swap,
+            files::file_paths,
+            files::file_read_many,
+            files::file_read_timed,
+            files::remote_cancel,
+            files::file_reset_to_default,
+            files::file_search,
+            files::scope_disk_usage,
+            files::disk_free,
+            files::scope_write_test,
+            files::scope_watch,
+            files::scope_unwatch,
+            files::scope_watch_set_coalesce_window,
+            files::file_validate_kind,
+            files::policy_explain,
+            files::policy_set_scope_override,
+            files::policy_clear_scope_override,
+            files::policy_rename_kind_file,
+            files::workspace_export_zip,
+            files::workspace_import_zip,
+            files::file_compare_local_remote,
             files::read_image_as_data_url,
             files::write_text_file,
             codex::get_config_model,
@@ -194,9 +265,25 @@ pub fn run() {
             codex::codex_doctor,
             codex::codex_update,
             workspaces::list_workspaces,
+            workspaces::workspace_state_repair,
+            workspaces::workspace_find_duplicates,
+            workspaces::workspace_check,
+            workspaces::workspace_health,
+            workspaces::workspace_list_broken,
+            workspaces::workspace_relink,
+            workspaces::workspace_prune_broken,
+            workspaces::profile_export,
+            workspaces::profile_import,
+            workspaces::workspace_set_codex_home,
+            workspaces::workspace_effective_model,
+            workspaces::workspace_effective_personality,
+            workspaces::workspace_effective_features,
+            workspaces::effective_sandbox,
+            workspaces::workspace_set_default_override,
             workspaces::is_workspace_path_dir,
             workspaces::add_workspace,
             workspaces::add_workspace_from_git_url,
+            workspaces::workspace_import_existing,
             workspaces::add_clone,
             workspaces::add_worktree,
             workspaces::worktree_setup_status,
@@ -262,6 +349,42 @@ pub fn run() {
             codex::model_list,
             codex::experimental_feature_list,
             codex::set_codex_feature_flag,
+            codex::config_undo,
+            codex::config_redo,
+            codex::config_history,
+            codex::config_format,
+            codex::read_effective_config,
+            codex::codex_effective_config_path,
+            codex::features_catalog,
+            codex::read_effective_personality,
+            codex::read_reasoning_effort,
+            codex::write_reasoning_effort,
+            codex::config_migrate_preview,
+            codex::config_migrate,
+            codex::config_schema_check,
+            codex::config_modified_ms,
+            codex::config_merge,
+            codex::config_merge_documents,
+            codex::config_transaction,
+            codex::config_array_add,
+            codex::config_array_remove,
+            codex::config_export_redacted,
+            codex::config_strip_comments,
+            codex::config_indent_info,
+            codex::config_reindent,
+            codex::config_load_tolerant,
+            codex::config_fix_duplicate_keys,
+            codex::config_roundtrip_check,
+            codex::config_drift_check,
+            codex::config_with_defaults,
+            codex::config_profile_diff,
+            codex::config_profile_create,
+            codex::config_profile_delete,
+            codex::config_profile_rename,
+            codex::tools_config_read,
+            codex::tools_config_set,
+            codex::mcp_servers_list,
+            codex::mcp_server_set_enabled,
             codex::get_agents_settings,
             codex::set_agents_core_settings,
             codex::create_agent,
@@ -311,14 +434,19 @@ pub fn run() {
     app.run(|app_handle, event| {
         #[cfg(desktop)]
         if let RunEvent::ExitRequested { api, .. } = event {
-            if !EXIT_CLEANUP_IN_PROGRESS.load(Ordering::SeqCst)
-                && !keep_daemon_running_after_close(app_handle)
-            {
+            if !EXIT_CLEANUP_IN_PROGRESS.load(Ordering::SeqCst) {
                 api.prevent_exit();
                 EXIT_CLEANUP_IN_PROGRESS.store(true, Ordering::SeqCst);
+                let keep_daemon_running = keep_daemon_running_after_close(app_handle);
                 let app_handle = app_handle.clone();
                 tauri::async_runtime::spawn(async move {
-                    stop_managed_daemons_for_exit(app_handle.clone()).await;
+                    app_handle
+                        .state::<state::AppState>()
+                        .on_shutdown(&app_handle)
+                        .await;
+                    if !keep_daemon_running {
+                        stop_managed_daemons_for_exit(app_handle.clone()).await;
+                    }
                     app_handle.exit(0);
                 });
             }
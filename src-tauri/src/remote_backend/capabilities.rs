@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Protocol version this app and the daemon speak, bumped when a method changes
+/// shape incompatibly. The daemon reports it from `capabilities`.
+pub(crate) const REMOTE_PROTOCOL_VERSION: u32 = 1;
+
+/// What the remote backend reported from `capabilities` on connect. Both
+/// fields are `None` for a daemon that predates the method: it is used without
+/// a version check and every call is attempted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteCapabilities {
+    #[serde(default)]
+    pub(crate) protocol_version: Option<u32>,
+    #[serde(default)]
+    pub(crate) methods: Option<BTreeSet<String>>,
+}
+
+impl RemoteCapabilities {
+    pub(crate) fn check_method(&self, method: &str) -> Result<(), String> {
+        match &self.methods {
+            Some(methods) if !methods.contains(method) => {
+                Err(format!("remote backend does not support `{method}`"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Validates a `capabilities` response. `Err` carries the connect error shown
+/// to the user. A daemon too old to answer, or one that reports no version, is
+/// accepted; only a different reported version is refused.
+pub(crate) fn parse_capabilities(
+    response: Result<Value, String>,
+) -> Result<RemoteCapabilities, String> {
+    let value = match response {
+        Ok(value) => value,
+        Err(err) if err.starts_with("unknown method") => {
+            return Ok(RemoteCapabilities {
+                protocol_version: None,
+                methods: None,
+            });
+        }
+        Err(err) => return Err(format!("remote backend capability check failed: {err}")),
+    };
+    let capabilities: RemoteCapabilities = serde_json::from_value(value)
+        .map_err(|err| format!("invalid remote backend capabilities: {err}"))?;
+    match capabilities.protocol_version {
+        Some(version) if version != REMOTE_PROTOCOL_VERSION => Err(format!(
+            "remote backend speaks protocol version {version}, but this app requires version {REMOTE_PROTOCOL_VERSION}"
+        )),
+        _ => Ok(capabilities),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_capabilities, REMOTE_PROTOCOL_VERSION};
+    use serde_json::json;
+
+    #[test]
+    fn capabilities_gate_protocol_version_and_methods() {
+        let capabilities = parse_capabilities(Ok(json!({
+            "protocolVersion": REMOTE_PROTOCOL_VERSION,
+            "methods": ["file_read", "list_workspaces"],
+        })))
+        .expect("compatible");
+        assert!(capabilities.check_method("file_read").is_ok());
+        assert_eq!(
+            capabilities.check_method("file_write").unwrap_err(),
+            "remote backend does not support `file_write`"
+        );
+
+        let newer = parse_capabilities(Ok(json!({
+            "protocolVersion": REMOTE_PROTOCOL_VERSION + 1,
+            "methods": [],
+        })));
+        assert!(newer.unwrap_err().contains("protocol version"));
+
+        let unversioned = parse_capabilities(Ok(json!({ "methods": ["file_read"] })))
+            .expect("no version is accepted");
+        assert_eq!(unversioned.protocol_version, None);
+        assert!(unversioned.check_method("file_write").is_err());
+
+        let legacy = parse_capabilities(Err("unknown method: capabilities".to_string()))
+            .expect("a daemon without `capabilities` is accepted");
+        assert!(legacy.check_method("file_write").is_ok());
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::protocol::{classify_error, RemoteErrorClass};
+
+/// How the latest call to a method ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MethodOutcome {
+    pub(crate) ok: bool,
+    /// Set for failed calls, e.g. `timeout` or `request`.
+    pub(crate) error_class: Option<RemoteErrorClass>,
+    pub(crate) error: Option<String>,
+    /// The request id of that call, as logged on both sides.
+    pub(crate) request_id: Option<String>,
+}
+
+/// One row of `remote_method_stats`. Methods the backend supports but that
+/// were never called have no last call and a `null` outcome.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteMethodStat {
+    pub(crate) method: String,
+    pub(crate) calls: u64,
+    pub(crate) errors: u64,
+    pub(crate) last_called_at_ms: Option<i64>,
+    pub(crate) last_latency_ms: Option<u64>,
+    pub(crate) last_outcome: Option<MethodOutcome>,
+}
+
+#[derive(Clone)]
+struct MethodRecord {
+    calls: u64,
+    errors: u64,
+    last_called_at_ms: i64,
+    last_latency_ms: u64,
+    last_outcome: MethodOutcome,
+}
+
+/// Per-method call history since launch or the last diagnostics reset.
+#[derive(Default)]
+pub(crate) struct MethodStats {
+    records: Mutex<BTreeMap<String, MethodRecord>>,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+impl MethodStats {
+    pub(crate) fn record<T>(
+        &self,
+        method: &str,
+        request_id: &str,
+        latency: Duration,
+        result: &Result<T, String>,
+    ) {
+        let last_outcome = match result {
+            Ok(_) => MethodOutcome {
+                ok: true,
+                error_class: None,
+                error: None,
+                request_id: Some(request_id.to_string()),
+            },
+            Err(err) => MethodOutcome {
+                ok: false,
+                error_class: Some(classify_error(err)),
+                error: Some(err.clone()),
+                request_id: Some(request_id.to_string()),
+            },
+        };
+        let mut records = self
+            .records
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let record = records
+            .entry(method.to_string())
+            .or_insert_with(|| MethodRecord {
+                calls: 0,
+                errors: 0,
+                last_called_at_ms: 0,
+                last_latency_ms: 0,
+                last_outcome: last_outcome.clone(),
+            });
+        record.calls += 1;
+        if !last_outcome.ok {
+            record.errors += 1;
+        }
+        record.last_called_at_ms = now_ms();
+        record.last_latency_ms = latency.as_millis() as u64;
+        record.last_outcome = last_outcome;
+    }
+
+    /// Every called method plus every one in `supported`, sorted by name.
+    pub(crate) fn snapshot(&self, supported: &BTreeSet<String>) -> Vec<RemoteMethodStat> {
+        let records = self
+            .records
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let methods: BTreeSet<&String> = records.keys().chain(supported.iter()).collect();
+        methods
+            .into_iter()
+            .map(|method| match records.get(method) {
+                Some(record) => RemoteMethodStat {
+                    method: method.clone(),
+                    calls: record.calls,
+                    errors: record.errors,
+                    last_called_at_ms: Some(record.last_called_at_ms),
+                    last_latency_ms: Some(record.last_latency_ms),
+                    last_outcome: Some(record.last_outcome.clone()),
+                },
+                None => RemoteMethodStat {
+                    method: method.clone(),
+                    calls: 0,
+                    errors: 0,
+                    last_called_at_ms: None,
+                    last_latency_ms: None,
+                    last_outcome: None,
+                },
+            })
+            .collect()
+    }
+
+    pub(crate) fn reset(&self) {
+        self.records
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MethodStats;
+    use crate::remote_backend::protocol::RemoteErrorClass;
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+
+    #[test]
+    fn stats_track_last_outcome_and_list_uncalled_methods() {
+        let stats = MethodStats::default();
+        stats.record::<()>("file_read", "req-1", Duration::from_millis(12), &Ok(()));
+        stats.record::<()>(
+            "file_read",
+            "req-2",
+            Duration::from_millis(30),
+            &Err("request timed out".to_string()),
+        );
+        stats.record::<()>(
+            "list_workspaces",
+            "req-3",
+            Duration::from_millis(4),
+            &Ok(()),
+        );
+
+        let supported = BTreeSet::from(["file_read".to_string(), "file_write".to_string()]);
+        let rows = stats.snapshot(&supported);
+        let methods: Vec<&str> = rows.iter().map(|row| row.method.as_str()).collect();
+        assert_eq!(methods, vec!["file_read", "file_write", "list_workspaces"]);
+
+        let read = &rows[0];
+        assert_eq!((read.calls, read.errors), (2, 1));
+        assert_eq!(read.last_latency_ms, Some(30));
+        let outcome = read.last_outcome.as_ref().expect("outcome");
+        assert!(!outcome.ok);
+        assert_eq!(outcome.error_class, Some(RemoteErrorClass::Timeout));
+        assert_eq!(outcome.request_id.as_deref(), Some("req-2"));
+
+        assert_eq!(rows[1].calls, 0);
+        assert!(rows[1].last_outcome.is_none());
+        assert!(rows[2].last_outcome.as_ref().expect("outcome").ok);
+
+        stats.reset();
+        assert!(stats.snapshot(&BTreeSet::new()).is_empty());
+    }
+}
@@ -1,25 +1,54 @@
+pub(crate) mod capabilities;
+pub(crate) mod method_stats;
+pub(crate) mod offline_mirror;
+pub(crate) mod pressure;
 mod protocol;
+pub(crate) mod rate_limit;
 mod tcp_transport;
 mod transport;
 
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::collections::BTreeSet;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::Mutex;
 use tokio::time::timeout;
 
+use crate::backend::events::{EventSink, WorkspaceHealth};
+use crate::event_sink::TauriEventSink;
 use crate::state::AppState;
 use crate::types::BackendMode;
 
-use self::protocol::{build_request_line, DEFAULT_REMOTE_HOST, DISCONNECTED_MESSAGE};
+use self::capabilities::{parse_capabilities, RemoteCapabilities};
+use self::method_stats::RemoteMethodStat;
+use self::offline_mirror::OfflineStatus;
+use self::pressure::{RemotePressure, REMOTE_PRESSURE_EVENT};
+pub(crate) use self::protocol::is_connectivity_error;
+use self::protocol::{
+    build_request_line, classify_error, new_request_id, RemoteCallFailure, RemoteErrorClass,
+    DEFAULT_REMOTE_HOST, DISCONNECTED_MESSAGE, REMOTE_CALL_FAILED_EVENT,
+};
+use self::rate_limit::RateLimitConfig;
 use self::tcp_transport::TcpTransport;
 use self::transport::{PendingMap, RemoteTransport, RemoteTransportConfig, RemoteTransportKind};
 
 const REMOTE_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
 const REMOTE_SEND_TIMEOUT: Duration = Duration::from_secs(15);
+const REQUEST_CANCELLED_MESSAGE: &str = "remote request cancelled by diagnostics reset";
+
+/// What `remote_reset_all` cleared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteResetSummary {
+    pub(crate) cache_entries_cleared: usize,
+    pub(crate) queued_writes_flushed: usize,
+    pub(crate) queued_writes_dropped: usize,
+    pub(crate) requests_cancelled: usize,
+}
 
 pub(crate) fn normalize_path_for_remote(path: String) -> String {
     let trimmed = path.trim();
@@ -68,7 +97,30 @@ struct RemoteBackendInner {
 }
 
 impl RemoteBackend {
-    pub(crate) async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+    pub(crate) async fn has_pending_calls(&self) -> bool {
+        !self.inner.pending.lock().await.is_empty()
+    }
+
+    pub(crate) async fn pending_calls(&self) -> usize {
+        self.inner.pending.lock().await.len()
+    }
+
+    /// Fails every call still waiting for a response; returns how many.
+    pub(crate) async fn cancel_pending_calls(&self) -> usize {
+        let pending: Vec<_> = self.inner.pending.lock().await.drain().collect();
+        let cancelled = pending.len();
+        for (_, sender) in pending {
+            let _ = sender.send(Err(REQUEST_CANCELLED_MESSAGE.to_string()));
+        }
+        cancelled
+    }
+
+    pub(crate) async fn call(
+        &self,
+        method: &str,
+        params: Value,
+        request_id: &str,
+    ) -> Result<Value, String> {
         if !self.inner.connected.load(Ordering::SeqCst) {
             return Err(DISCONNECTED_MESSAGE.to_string());
         }
@@ -77,7 +129,7 @@ impl RemoteBackend {
         let (tx, rx) = tokio::sync::oneshot::channel();
         self.inner.pending.lock().await.insert(id, tx);
 
-        let message = build_request_line(id, method, params)?;
+        let message = build_request_line(id, request_id, method, params)?;
         match timeout(REMOTE_SEND_TIMEOUT, self.inner.out_tx.send(message)).await {
             Ok(Ok(())) => {}
             Ok(Err(_)) => {
@@ -112,22 +164,59 @@ pub(crate) async fn is_remote_mode(state: &AppState) -> bool {
     matches!(settings.backend_mode, BackendMode::Remote)
 }
 
+/// Every call gets a fresh request id; it is sent with the request and, on
+/// failure, logged and emitted with the error class as `remote-call-failed`.
+/// The returned error text is the backend's, unchanged. Load is sampled as
+/// each call starts and ends, for `remote-pressure`.
 pub(crate) async fn call_remote(
     state: &AppState,
     app: AppHandle,
     method: &str,
     params: Value,
 ) -> Result<Value, String> {
+    let request_id = new_request_id();
+    spawn_pressure_report(app.clone());
+    let started = Instant::now();
+    let result = call_remote_with_retry(state, app.clone(), method, params, &request_id).await;
+    state
+        .remote_method_stats
+        .record(method, &request_id, started.elapsed(), &result);
+    spawn_pressure_report(app.clone());
+    if let Err(err) = &result {
+        let failure = RemoteCallFailure::new(method, &request_id, err);
+        eprintln!(
+            "remote backend: `{method}` failed (request id: {request_id}, class: {:?}): {err}",
+            failure.error_class
+        );
+        let _ = app.emit(REMOTE_CALL_FAILED_EVENT, failure);
+    }
+    result
+}
+
+async fn call_remote_with_retry(
+    state: &AppState,
+    app: AppHandle,
+    method: &str,
+    params: Value,
+    request_id: &str,
+) -> Result<Value, String> {
+    state
+        .remote_rate_limiter
+        .acquire(method, can_retry_after_disconnect(method))
+        .await?;
     let client = ensure_remote_backend(state, app.clone()).await?;
-    match client.call(method, params.clone()).await {
+    if let Some(capabilities) = state.remote_capabilities.lock().await.as_ref() {
+        capabilities.check_method(method)?;
+    }
+    match client.call(method, params.clone(), request_id).await {
         Ok(value) => Ok(value),
-        Err(err) if err == DISCONNECTED_MESSAGE => {
+        Err(err) if classify_error(&err) == RemoteErrorClass::Connectivity => {
             *state.remote_backend.lock().await = None;
             if !can_retry_after_disconnect(method) {
                 return Err(err);
             }
             let retry_client = ensure_remote_backend(state, app).await?;
-            match retry_client.call(method, params).await {
+            match retry_client.call(method, params, request_id).await {
                 Ok(value) => Ok(value),
                 Err(retry_err) => {
                     *state.remote_backend.lock().await = None;
@@ -142,17 +231,193 @@ pub(crate) async fn call_remote(
     }
 }
 
+#[tauri::command]
+pub(crate) async fn get_remote_rate_limits(
+    state: State<'_, AppState>,
+) -> Result<RateLimitConfig, String> {
+    Ok(state.remote_rate_limiter.config().await)
+}
+
+#[tauri::command]
+pub(crate) async fn set_remote_rate_limits(
+    config: RateLimitConfig,
+    state: State<'_, AppState>,
+) -> Result<RateLimitConfig, String> {
+    state.remote_rate_limiter.set_config(config).await?;
+    Ok(config)
+}
+
+#[tauri::command]
+pub(crate) async fn offline_status(state: State<'_, AppState>) -> Result<OfflineStatus, String> {
+    Ok(state.offline_mirror.lock().await.status())
+}
+
+/// In-flight calls, rate-limited and offline-queued work as one signal; all
+/// zero in local mode.
+#[tauri::command]
+pub(crate) async fn remote_pressure(state: State<'_, AppState>) -> Result<RemotePressure, String> {
+    Ok(current_pressure(&state).await)
+}
+
+/// Per remote method: call and error counts, and when, how fast and how the
+/// last call ended. Methods the connected backend supports but that were never
+/// called are listed with a `null` outcome.
+#[tauri::command]
+pub(crate) async fn remote_method_stats(
+    state: State<'_, AppState>,
+) -> Result<Vec<RemoteMethodStat>, String> {
+    let supported = state
+        .remote_capabilities
+        .lock()
+        .await
+        .as_ref()
+        .and_then(|capabilities| capabilities.methods.clone())
+        .unwrap_or_else(BTreeSet::new);
+    Ok(state.remote_method_stats.snapshot(&supported))
+}
+
+async fn current_pressure(state: &AppState) -> RemotePressure {
+    let client = state.remote_backend.lock().await.clone();
+    let inflight = match client {
+        Some(client) => client.pending_calls().await,
+        None => 0,
+    };
+    let queued_writes = state.offline_mirror.lock().await.status().pending_writes;
+    RemotePressure::new(
+        inflight,
+        state.remote_rate_limiter.waiting(),
+        queued_writes,
+        state.remote_rate_limiter.throttled(),
+    )
+}
+
+/// Samples off the call path: `remote_backend` stays locked while connecting.
+fn spawn_pressure_report(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        report_pressure(&state, &app).await;
+    });
+}
+
+/// Samples the load and emits `remote-pressure` if `busy` changed.
+pub(crate) async fn report_pressure(state: &AppState, app: &AppHandle) {
+    state
+        .remote_pressure
+        .report(current_pressure(state), |pressure| {
+            let _ = app.emit(REMOTE_PRESSURE_EVENT, pressure);
+        })
+        .await;
+}
+
+/// What the remote backend reported on connect, connecting first in remote
+/// mode. `None` in local mode.
+#[tauri::command]
+pub(crate) async fn remote_capabilities(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<RemoteCapabilities>, String> {
+    if !is_remote_mode(&state).await {
+        return Ok(None);
+    }
+    ensure_remote_backend(&state, app).await?;
+    Ok(state.remote_capabilities.lock().await.clone())
+}
+
+/// Resets remote-mode diagnostics state in one step: cancels in-flight calls
+/// and batched reads, flushes (`flush_queue`) or drops queued offline writes,
+/// clears mirrored reads and refills the rate limiter. With `flush_queue`,
+/// writes that cannot be synced (offline or conflicting) stay queued.
+#[tauri::command]
+pub(crate) async fn remote_reset_all(
+    flush_queue: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RemoteResetSummary, String> {
+    let mut requests_cancelled = {
+        let mut in_flight = state.remote_reads_in_flight.lock().await;
+        let count = in_flight.len();
+        in_flight.clear();
+        count
+    };
+    let client = state.remote_backend.lock().await.clone();
+    if let Some(client) = client.as_ref() {
+        requests_cancelled += client.cancel_pending_calls().await;
+    }
+
+    let mut queued_writes_flushed = 0;
+    if flush_queue {
+        let queued = state.offline_mirror.lock().await.status().pending_writes;
+        if queued > 0 && is_remote_mode(&state).await {
+            match ensure_remote_backend(&state, app.clone()).await {
+                Ok(client) => {
+                    offline_mirror::sync_pending_writes(&state, &client, &app).await;
+                    let remaining = state.offline_mirror.lock().await.status().pending_writes;
+                    queued_writes_flushed = queued.saturating_sub(remaining);
+                }
+                Err(err) => {
+                    eprintln!("remote backend: reset could not flush offline writes: {err}");
+                }
+            }
+        }
+    }
+    let (cache_entries_cleared, queued_writes_dropped) =
+        state.offline_mirror.lock().await.clear(flush_queue);
+    state.remote_rate_limiter.reset().await;
+    state.remote_method_stats.reset();
+    report_pressure(&state, &app).await;
+
+    Ok(RemoteResetSummary {
+        cache_entries_cleared,
+        queued_writes_flushed,
+        queued_writes_dropped,
+        requests_cancelled,
+    })
+}
+
+/// Retry-safe methods are read-only, so they also share the limiter's read bucket.
 fn can_retry_after_disconnect(method: &str) -> bool {
     matches!(
         method,
         "account_rate_limits"
             | "account_read"
+            | "agents_effective"
             | "apps_list"
+            | "codex_effective_config_path"
             | "collaboration_mode_list"
+            | "config_history"
+            | "config_indent_info"
+            | "config_migrate_preview"
+            | "config_export_redacted"
+            | "config_load_tolerant"
+            | "config_roundtrip_check"
+            | "config_drift_check"
+            | "config_profile_diff"
+            | "config_read"
+            | "config_schema_check"
+            | "config_stat"
+            | "config_with_defaults"
             | "connect_workspace"
+            | "defaults_catalog"
+            | "disk_free"
+            | "effective_sandbox"
             | "experimental_feature_list"
+            | "feature_preset_list"
+            | "features_catalog"
             | "set_workspace_runtime_codex_args"
+            | "file_paths"
+            | "file_link_info"
+            | "file_permissions_audit"
             | "file_read"
+            | "file_read_capped"
+            | "file_read_many"
+            | "file_read_timed"
+            | "file_read_lines_normalized"
+            | "file_render_template"
+            | "file_read_range"
+            | "file_search"
+            | "file_snapshot_list"
+            | "file_writable"
+            | "file_write_preview"
             | "get_agents_settings"
             | "get_config_model"
             | "get_git_commit_diff"
@@ -172,17 +437,38 @@ fn can_retry_after_disconnect(method: &str) -> bool {
             | "local_usage_snapshot"
             | "list_workspace_files"
             | "list_workspaces"
+            | "mcp_servers_list"
             | "model_list"
+            | "policy_explain"
+            | "profile_export"
             | "read_agent_config_toml"
+            | "read_effective_config"
+            | "read_effective_personality"
+            | "read_reasoning_effort"
             | "read_workspace_file"
             | "resume_thread"
+            | "scope_disk_usage"
             | "thread_live_subscribe"
             | "thread_live_unsubscribe"
             | "skills_list"
+            | "tools_config_read"
+            | "workspace_check"
+            | "workspace_effective_features"
+            | "workspace_effective_model"
+            | "workspace_effective_personality"
+            | "workspace_find_duplicates"
+            | "workspace_health"
+            | "workspace_list_broken"
+            | "workspace_readiness"
             | "worktree_setup_status"
     )
 }
 
+/// Connects to the remote backend unless already connected.
+pub(crate) async fn connect_remote(state: &AppState, app: AppHandle) -> Result<(), String> {
+    ensure_remote_backend(state, app).await.map(|_| ())
+}
+
 async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<RemoteBackend, String> {
     {
         let guard = state.remote_backend.lock().await;
@@ -201,7 +487,7 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
     let transport: Box<dyn RemoteTransport> = match transport_config.kind() {
         RemoteTransportKind::Tcp => Box::new(TcpTransport),
     };
-    let connection = transport.connect(app, transport_config).await?;
+    let connection = transport.connect(app.clone(), transport_config).await?;
 
     let client = RemoteBackend {
         inner: Arc::new(RemoteBackendInner {
@@ -215,30 +501,68 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
     if matches!(transport_kind, RemoteTransportKind::Tcp) {
         if let Some(token) = auth_token {
             client
-                .call("auth", json!({ "token": token }))
+                .call("auth", json!({ "token": token }), &new_request_id())
                 .await
                 .map(|_| ())?;
         }
     }
 
+    let response = client
+        .call("capabilities", json!({}), &new_request_id())
+        .await;
+    *state.remote_capabilities.lock().await = Some(parse_capabilities(response)?);
+
     {
         let mut guard = state.remote_backend.lock().await;
         *guard = Some(client.clone());
     }
 
+    let health_client = client.clone();
+    let health_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        refresh_workspace_health(&health_client, &health_app).await;
+    });
+
+    if state.offline_mirror.lock().await.status().pending_writes > 0 {
+        let sync_client = client.clone();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<AppState>();
+            offline_mirror::sync_pending_writes(&state, &sync_client, &app).await;
+        });
+    }
+
     Ok(client)
 }
 
-fn resolve_transport_config(
-    settings: &crate::types::AppSettings,
-) -> Result<RemoteTransportConfig, String> {
-    let host = if settings.remote_backend_host.trim().is_empty() {
+/// Re-emits the daemon's `workspace-health` summary after a connect, since
+/// the daemon may have restarted or its workspaces changed while away.
+async fn refresh_workspace_health(client: &RemoteBackend, app: &AppHandle) {
+    let health = client
+        .call("workspace_health", json!({}), &new_request_id())
+        .await
+        .and_then(|value| {
+            serde_json::from_value::<WorkspaceHealth>(value).map_err(|err| err.to_string())
+        });
+    match health {
+        Ok(health) => TauriEventSink::new(app.clone()).emit_workspace_health(health),
+        Err(err) => eprintln!("remote backend: failed to refresh workspace health: {err}"),
+    }
+}
+
+/// The host the remote backend connects to, defaulting when unset.
+pub(crate) fn remote_endpoint(settings: &crate::types::AppSettings) -> String {
+    if settings.remote_backend_host.trim().is_empty() {
         DEFAULT_REMOTE_HOST.to_string()
     } else {
         settings.remote_backend_host.clone()
-    };
+    }
+}
+
+fn resolve_transport_config(
+    settings: &crate::types::AppSettings,
+) -> Result<RemoteTransportConfig, String> {
     Ok(RemoteTransportConfig::Tcp {
-        host,
+        host: remote_endpoint(settings),
         auth_token: settings.remote_backend_token.clone(),
     })
 }
@@ -0,0 +1,596 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::AppHandle;
+
+use super::protocol::{is_transient, new_request_id};
+use super::{report_pressure, RemoteBackend};
+use crate::files::io::TextFileResponse;
+use crate::files::policy::ScopedFileRef;
+use crate::state::AppState;
+
+/// Upper bound on file content mirrored for offline editing.
+pub(crate) const DEFAULT_MIRROR_MAX_BYTES: usize = 8 * 1024 * 1024;
+/// A read larger than this share of the cap is not mirrored, so one big file
+/// cannot evict everything else.
+const MAX_READ_SHARE: usize = 4;
+
+struct MirrorEntry {
+    /// Last content known to be on the remote; `None` when the file was missing.
+    remote: Option<String>,
+    /// Content written while disconnected, not yet synced.
+    pending: Option<String>,
+    /// The remote changed underneath `pending`; it is kept but never synced.
+    conflict: bool,
+    /// Why syncing `pending` failed for good; it is kept but not retried.
+    failure: Option<String>,
+    last_used: u64,
+}
+
+impl MirrorEntry {
+    fn bytes(&self) -> usize {
+        self.remote.as_ref().map_or(0, String::len) + self.pending.as_ref().map_or(0, String::len)
+    }
+}
+
+/// A queued offline write and the remote content it was based on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PendingWrite {
+    pub(crate) file: ScopedFileRef,
+    pub(crate) base: Option<String>,
+    pub(crate) content: String,
+}
+
+/// A queued write that will not be synced until the file is written again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SyncFailure {
+    pub(crate) file: ScopedFileRef,
+    pub(crate) error: String,
+}
+
+/// One queued write as saved to disk, so it survives a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredWrite {
+    file: ScopedFileRef,
+    base: Option<String>,
+    content: String,
+    #[serde(default)]
+    conflict: bool,
+    #[serde(default)]
+    failure: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OfflineStatus {
+    pub(crate) mirrored_files: usize,
+    pub(crate) mirrored_bytes: usize,
+    pub(crate) max_bytes: usize,
+    pub(crate) pending_writes: usize,
+    pub(crate) conflicts: Vec<ScopedFileRef>,
+    pub(crate) failures: Vec<SyncFailure>,
+}
+
+/// Size-capped copy of remote scoped files. Reads are served from it and writes
+/// are queued in it while the remote backend is unreachable. Queued writes are
+/// also saved to `queue_path`, when set, after every change.
+pub(crate) struct OfflineMirror {
+    entries: HashMap<ScopedFileRef, MirrorEntry>,
+    max_bytes: usize,
+    clock: u64,
+    queue_path: Option<PathBuf>,
+}
+
+impl Default for OfflineMirror {
+    fn default() -> Self {
+        Self::new(DEFAULT_MIRROR_MAX_BYTES)
+    }
+}
+
+impl OfflineMirror {
+    pub(crate) fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            max_bytes,
+            clock: 0,
+            queue_path: None,
+        }
+    }
+
+    /// A mirror holding the writes queued in `queue_path`, which it keeps up to
+    /// date. An unreadable queue file is reported and left in place.
+    pub(crate) fn load(queue_path: PathBuf) -> Self {
+        let mut mirror = Self::new(DEFAULT_MIRROR_MAX_BYTES);
+        match read_queue(&queue_path) {
+            Ok(stored) => {
+                for write in stored {
+                    let entry = mirror.entry(&write.file);
+                    entry.remote = write.base;
+                    entry.pending = Some(write.content);
+                    entry.conflict = write.conflict;
+                    entry.failure = write.failure;
+                }
+                mirror.queue_path = Some(queue_path);
+            }
+            Err(err) => eprintln!(
+                "remote backend: ignoring offline queue {}: {err}",
+                queue_path.display()
+            ),
+        }
+        mirror
+    }
+
+    fn save_queue(&self) {
+        let Some(path) = self.queue_path.as_deref() else {
+            return;
+        };
+        let mut stored: Vec<StoredWrite> = self
+            .entries
+            .iter()
+            .filter_map(|(file, entry)| {
+                Some(StoredWrite {
+                    file: file.clone(),
+                    base: entry.remote.clone(),
+                    content: entry.pending.clone()?,
+                    conflict: entry.conflict,
+                    failure: entry.failure.clone(),
+                })
+            })
+            .collect();
+        stored.sort_by(|left, right| left.file.workspace_id.cmp(&right.file.workspace_id));
+        if let Err(err) = write_queue(path, &stored) {
+            eprintln!("remote backend: failed to save offline queue: {err}");
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn entry(&mut self, file: &ScopedFileRef) -> &mut MirrorEntry {
+        let now = self.tick();
+        let entry = self
+            .entries
+            .entry(file.clone())
+            .or_insert_with(|| MirrorEntry {
+                remote: None,
+                pending: None,
+                conflict: false,
+                failure: None,
+                last_used: now,
+            });
+        entry.last_used = now;
+        entry
+    }
+
+    fn total_bytes(&self) -> usize {
+        self.entries.values().map(MirrorEntry::bytes).sum()
+    }
+
+    /// Drops least recently used entries without queued writes until the
+    /// mirror fits. Returns `false` if queued writes alone exceed the cap.
+    fn evict(&mut self) -> bool {
+        while self.total_bytes() > self.max_bytes {
+            let victim = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.pending.is_none())
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(file, _)| file.clone());
+            match victim {
+                Some(file) => {
+                    self.entries.remove(&file);
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Records a successful remote read. Entries with a queued write keep the
+    /// content that write was based on. A read too large to mirror drops the
+    /// file's now stale copy instead.
+    pub(crate) fn record_read(&mut self, file: &ScopedFileRef, response: &TextFileResponse) {
+        if response.content.len() > self.max_bytes / MAX_READ_SHARE {
+            if self
+                .entries
+                .get(file)
+                .is_some_and(|entry| entry.pending.is_none())
+            {
+                self.entries.remove(file);
+            }
+            return;
+        }
+        let entry = self.entry(file);
+        if entry.pending.is_none() {
+            entry.remote = response.exists.then(|| response.content.clone());
+        }
+        self.evict();
+    }
+
+    /// Records a successful remote write, which supersedes any queued write.
+    pub(crate) fn record_write(&mut self, file: &ScopedFileRef, content: &str) {
+        let entry = self.entry(file);
+        let had_pending = entry.pending.take().is_some();
+        entry.remote = Some(content.to_string());
+        entry.conflict = false;
+        entry.failure = None;
+        if content.len() > self.max_bytes / MAX_READ_SHARE {
+            self.entries.remove(file);
+        }
+        self.evict();
+        if had_pending {
+            self.save_queue();
+        }
+    }
+
+    /// The mirrored view of `file`: the queued write if any, else the last
+    /// remote content.
+    pub(crate) fn read(&mut self, file: &ScopedFileRef) -> Option<TextFileResponse> {
+        let now = self.tick();
+        let entry = self.entries.get_mut(file)?;
+        entry.last_used = now;
+        let content = entry.pending.as_ref().or(entry.remote.as_ref());
+        Some(TextFileResponse {
+            exists: content.is_some(),
+            content: content.cloned().unwrap_or_default(),
+            truncated: false,
+        })
+    }
+
+    pub(crate) fn queue_write(
+        &mut self,
+        file: &ScopedFileRef,
+        content: String,
+    ) -> Result<(), String> {
+        let entry = self.entry(file);
+        let previous = entry.pending.replace(content);
+        let previous_failure = entry.failure.take();
+        if !self.evict() {
+            if let Some(entry) = self.entries.get_mut(file) {
+                entry.pending = previous;
+                entry.failure = previous_failure;
+            }
+            return Err("Offline mirror is full; reconnect to save this file".to_string());
+        }
+        self.save_queue();
+        Ok(())
+    }
+
+    /// Queued writes that can be synced, i.e. not flagged as conflicts or
+    /// failures.
+    pub(crate) fn pending_writes(&self) -> Vec<PendingWrite> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| !entry.conflict && entry.failure.is_none())
+            .filter_map(|(file, entry)| {
+                Some(PendingWrite {
+                    file: file.clone(),
+                    base: entry.remote.clone(),
+                    content: entry.pending.clone()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Marks a synced write done unless it was replaced while syncing.
+    pub(crate) fn finish_sync(&mut self, write: &PendingWrite) {
+        let Some(entry) = self.entries.get_mut(&write.file) else {
+            return;
+        };
+        entry.remote = Some(write.content.clone());
+        if entry.pending.as_deref() == Some(write.content.as_str()) {
+            entry.pending = None;
+        }
+        self.save_queue();
+    }
+
+    pub(crate) fn mark_conflict(&mut self, file: &ScopedFileRef) {
+        if let Some(entry) = self.entries.get_mut(file) {
+            entry.conflict = true;
+            self.save_queue();
+        }
+    }
+
+    /// Stops retrying a queued write the remote rejected or answered with
+    /// something unreadable; writing the file again clears it.
+    pub(crate) fn mark_failed(&mut self, file: &ScopedFileRef, error: String) {
+        if let Some(entry) = self.entries.get_mut(file) {
+            entry.failure = Some(error);
+            self.save_queue();
+        }
+    }
+
+    /// Drops mirrored reads, and queued writes unless `keep_pending`.
+    /// Returns how many cached reads and queued writes were dropped.
+    pub(crate) fn clear(&mut self, keep_pending: bool) -> (usize, usize) {
+        let before = self.entries.len();
+        let mut dropped_writes = 0;
+        self.entries.retain(|_, entry| {
+            if entry.pending.is_none() {
+                return false;
+            }
+            // The remote content stays as the base the queued write syncs against.
+            if keep_pending {
+                return true;
+            }
+            dropped_writes += 1;
+            false
+        });
+        let dropped_entries = before - self.entries.len() - dropped_writes;
+        if dropped_writes > 0 {
+            self.save_queue();
+        }
+        (dropped_entries, dropped_writes)
+    }
+
+    pub(crate) fn status(&self) -> OfflineStatus {
+        let mut conflicts: Vec<ScopedFileRef> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.conflict)
+            .map(|(file, _)| file.clone())
+            .collect();
+        conflicts.sort_by(|left, right| left.workspace_id.cmp(&right.workspace_id));
+        let mut failures: Vec<SyncFailure> = self
+            .entries
+            .iter()
+            .filter_map(|(file, entry)| {
+                Some(SyncFailure {
+                    file: file.clone(),
+                    error: entry.failure.clone()?,
+                })
+            })
+            .collect();
+        failures.sort_by(|left, right| left.file.workspace_id.cmp(&right.file.workspace_id));
+        OfflineStatus {
+            mirrored_files: self.entries.len(),
+            mirrored_bytes: self.total_bytes(),
+            max_bytes: self.max_bytes,
+            pending_writes: self
+                .entries
+                .values()
+                .filter(|entry| entry.pending.is_some())
+                .count(),
+            conflicts,
+            failures,
+        }
+    }
+}
+
+fn read_queue(path: &Path) -> Result<Vec<StoredWrite>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&data).map_err(|err| err.to_string())
+}
+
+/// Through a temp file and rename, like the app-kv store; an empty queue
+/// removes the file.
+fn write_queue(path: &Path, stored: &[StoredWrite]) -> Result<(), String> {
+    if stored.is_empty() {
+        return match std::fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.to_string()),
+            _ => Ok(()),
+        };
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(stored).map_err(|err| err.to_string())?;
+    let temp_path = path.with_extension(format!("json.{}.tmp", uuid::Uuid::new_v4()));
+    std::fs::write(&temp_path, data).map_err(|err| err.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|err| {
+        let _ = std::fs::remove_file(&temp_path);
+        err.to_string()
+    })
+}
+
+/// Replays queued offline writes after a reconnect. A write is applied only
+/// when the remote still holds the content it was based on; otherwise it is
+/// flagged as a conflict and left for the user to resolve. Writes the remote
+/// rejects, or whose read-back cannot be parsed, are flagged as failures.
+/// Transient errors stop the sync and keep the rest queued. Load is sampled
+/// for `remote-pressure` as the queue drains.
+pub(crate) async fn sync_pending_writes(state: &AppState, client: &RemoteBackend, app: &AppHandle) {
+    let pending = state.offline_mirror.lock().await.pending_writes();
+    for write in pending {
+        report_pressure(state, app).await;
+        let file = &write.file;
+        let params = json!({
+            "scope": file.scope,
+            "kind": file.kind,
+            "workspaceId": file.workspace_id,
+        });
+        let remote = match client.call("file_read", params, &new_request_id()).await {
+            Ok(value) => match serde_json::from_value::<TextFileResponse>(value) {
+                Ok(remote) => remote,
+                Err(err) => {
+                    let error = format!("Unreadable remote file: {err}");
+                    state.offline_mirror.lock().await.mark_failed(file, error);
+                    continue;
+                }
+            },
+            // Still offline; keep the write queued for the next reconnect.
+            Err(err) if is_transient(&err) => return,
+            Err(err) => {
+                state.offline_mirror.lock().await.mark_failed(file, err);
+                continue;
+            }
+        };
+        if remote.exists.then_some(remote.content) != write.base {
+            state.offline_mirror.lock().await.mark_conflict(file);
+            continue;
+        }
+        let params = json!({
+            "scope": file.scope,
+            "kind": file.kind,
+            "workspaceId": file.workspace_id,
+            "content": write.content,
+        });
+        match client.call("file_write", params, &new_request_id()).await {
+            Ok(_) => state.offline_mirror.lock().await.finish_sync(&write),
+            Err(err) if is_transient(&err) => {
+                eprintln!("remote backend: failed to sync offline write: {err}");
+                return;
+            }
+            Err(err) => state.offline_mirror.lock().await.mark_failed(file, err),
+        }
+    }
+    report_pressure(state, app).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OfflineMirror, DEFAULT_MIRROR_MAX_BYTES};
+    use crate::files::io::TextFileResponse;
+    use crate::files::policy::{FileKind, FileScope, ScopedFileRef};
+
+    fn file(workspace_id: &str) -> ScopedFileRef {
+        ScopedFileRef {
+            scope: FileScope::Workspace,
+            kind: FileKind::Agents,
+            workspace_id: Some(workspace_id.to_string()),
+        }
+    }
+
+    fn remote(content: &str) -> TextFileResponse {
+        TextFileResponse {
+            exists: true,
+            content: content.to_string(),
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn queued_writes_are_served_and_keep_their_base() {
+        let mut mirror = OfflineMirror::new(1024);
+        mirror.record_read(&file("a"), &remote("base"));
+        mirror
+            .queue_write(&file("a"), "edited".to_string())
+            .expect("queue");
+        mirror.record_read(&file("a"), &remote("newer"));
+
+        assert_eq!(mirror.read(&file("a")).expect("mirrored").content, "edited");
+        let pending = mirror.pending_writes();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].base.as_deref(), Some("base"));
+
+        mirror.finish_sync(&pending[0]);
+        assert!(mirror.pending_writes().is_empty());
+        assert_eq!(mirror.status().pending_writes, 0);
+    }
+
+    #[test]
+    fn eviction_spares_queued_writes() {
+        let mut mirror = OfflineMirror::new(20);
+        mirror
+            .queue_write(&file("a"), "123456789012".to_string())
+            .expect("queue");
+        mirror.record_read(&file("b"), &remote("1234"));
+        mirror.record_read(&file("c"), &remote("12345"));
+
+        assert!(mirror.read(&file("a")).is_some());
+        assert!(mirror.read(&file("b")).is_none());
+        assert!(mirror.read(&file("c")).is_some());
+        assert!(mirror
+            .queue_write(&file("d"), "123456789".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn large_reads_are_not_mirrored_and_evict_nothing() {
+        let mut mirror = OfflineMirror::new(20);
+        mirror.record_read(&file("a"), &remote("1234"));
+        mirror.record_read(&file("b"), &remote("old"));
+        mirror.record_read(&file("b"), &remote("123456"));
+
+        assert!(mirror.read(&file("a")).is_some());
+        assert!(mirror.read(&file("b")).is_none(), "stale copy is dropped");
+        assert_eq!(mirror.status().mirrored_files, 1);
+    }
+
+    #[test]
+    fn failed_writes_are_reported_and_not_retried() {
+        let mut mirror = OfflineMirror::new(1024);
+        mirror
+            .queue_write(&file("a"), "edited".to_string())
+            .expect("queue");
+        mirror.mark_failed(&file("a"), "missing `workspaceId`".to_string());
+
+        assert!(mirror.pending_writes().is_empty());
+        let status = mirror.status();
+        assert_eq!(status.failures.len(), 1);
+        assert_eq!(status.failures[0].error, "missing `workspaceId`");
+
+        mirror
+            .queue_write(&file("a"), "edited again".to_string())
+            .expect("queue");
+        assert_eq!(mirror.pending_writes().len(), 1);
+        assert!(mirror.status().failures.is_empty());
+    }
+
+    #[test]
+    fn queued_writes_survive_a_reload() {
+        let dir =
+            std::env::temp_dir().join(format!("codex-offline-queue-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("offline-queue.json");
+        let mut mirror = OfflineMirror::load(path.clone());
+        mirror.record_read(&file("a"), &remote("base"));
+        mirror
+            .queue_write(&file("a"), "edited".to_string())
+            .expect("queue");
+        mirror.record_read(&file("b"), &remote("cached only"));
+
+        let mut reloaded = OfflineMirror::load(path.clone());
+        assert_eq!(reloaded.max_bytes, DEFAULT_MIRROR_MAX_BYTES);
+        let pending = reloaded.pending_writes();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].base.as_deref(), Some("base"));
+        assert_eq!(pending[0].content, "edited");
+        assert!(reloaded.read(&file("b")).is_none());
+
+        reloaded.finish_sync(&pending[0]);
+        assert!(!path.exists(), "an empty queue removes the file");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conflicts_are_reported_and_not_synced() {
+        let mut mirror = OfflineMirror::new(1024);
+        mirror
+            .queue_write(&file("a"), "edited".to_string())
+            .expect("queue");
+        mirror.mark_conflict(&file("a"));
+
+        assert!(mirror.pending_writes().is_empty());
+        let status = mirror.status();
+        assert_eq!(status.conflicts, vec![file("a")]);
+        assert_eq!(status.pending_writes, 1);
+
+        mirror.record_write(&file("a"), "resolved");
+        assert!(mirror.status().conflicts.is_empty());
+    }
+
+    #[test]
+    fn clear_drops_reads_and_optionally_queued_writes() {
+        let mut mirror = OfflineMirror::new(1024);
+        mirror.record_read(&file("a"), &remote("a"));
+        mirror.record_read(&file("b"), &remote("b"));
+        mirror
+            .queue_write(&file("c"), "edited".to_string())
+            .expect("queue");
+
+        assert_eq!(mirror.clear(true), (2, 0));
+        assert_eq!(mirror.status().pending_writes, 1);
+        assert_eq!(mirror.clear(false), (0, 1));
+        assert_eq!(mirror.status().mirrored_files, 0);
+    }
+}
@@ -0,0 +1,101 @@
+use std::future::Future;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+pub(crate) const REMOTE_PRESSURE_EVENT: &str = "remote-pressure";
+/// Calls awaiting a response before the backend counts as busy.
+const INFLIGHT_THRESHOLD: usize = 16;
+/// Calls waiting for a rate-limit token plus offline writes waiting to sync.
+const QUEUED_THRESHOLD: usize = 8;
+
+/// One snapshot of remote call load for `remote_pressure` and the
+/// `remote-pressure` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemotePressure {
+    /// Calls sent and awaiting a response.
+    pub(crate) inflight: usize,
+    /// Calls waiting for a rate-limit token plus queued offline writes.
+    pub(crate) queued: usize,
+    /// A call is currently held back by the rate limiter.
+    pub(crate) rate_limited: bool,
+    /// A threshold is crossed; the UI should hold off autosave and other
+    /// background writes until this clears.
+    pub(crate) busy: bool,
+}
+
+impl RemotePressure {
+    pub(crate) fn new(
+        inflight: usize,
+        waiting_for_token: usize,
+        queued_writes: usize,
+        rate_limited: bool,
+    ) -> Self {
+        let queued = waiting_for_token + queued_writes;
+        Self {
+            inflight,
+            queued,
+            rate_limited,
+            busy: rate_limited || inflight >= INFLIGHT_THRESHOLD || queued >= QUEUED_THRESHOLD,
+        }
+    }
+}
+
+/// Remembers the last reported `busy`, so `remote-pressure` is only emitted
+/// when a threshold is crossed in either direction.
+#[derive(Default)]
+pub(crate) struct PressureTracker {
+    busy: Mutex<bool>,
+}
+
+impl PressureTracker {
+    /// Takes `sample` and hands it to `emit` when `busy` differs from the
+    /// previous report. Reports run one at a time, sampling included, so they
+    /// are emitted in the order they were taken and the last one reflects the
+    /// newest load.
+    pub(crate) async fn report(
+        &self,
+        sample: impl Future<Output = RemotePressure>,
+        emit: impl FnOnce(RemotePressure),
+    ) {
+        let mut busy = self.busy.lock().await;
+        let pressure = sample.await;
+        if *busy != pressure.busy {
+            *busy = pressure.busy;
+            emit(pressure);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PressureTracker, RemotePressure, INFLIGHT_THRESHOLD, QUEUED_THRESHOLD};
+
+    #[test]
+    fn tracker_reports_only_threshold_crossings() {
+        let runtime = tokio::runtime::Runtime::new().expect("runtime");
+        let tracker = PressureTracker::default();
+        let observe = |pressure: RemotePressure| {
+            let mut emitted = None;
+            runtime.block_on(
+                tracker.report(async move { pressure }, |pressure| emitted = Some(pressure)),
+            );
+            emitted
+        };
+        assert_eq!(observe(RemotePressure::new(1, 0, 0, false)), None);
+
+        let busy = RemotePressure::new(INFLIGHT_THRESHOLD, 0, 0, false);
+        assert!(busy.busy);
+        assert_eq!(observe(busy), Some(busy));
+        assert_eq!(
+            observe(RemotePressure::new(0, QUEUED_THRESHOLD - 1, 1, false)),
+            None
+        );
+        assert!(observe(RemotePressure::new(0, 0, 0, true)).is_none());
+
+        let calm = RemotePressure::new(2, 1, 0, false);
+        assert_eq!(calm.queued, 1);
+        assert_eq!(observe(calm), Some(calm));
+    }
+}
@@ -1,7 +1,14 @@
+use serde::Serialize;
 use serde_json::{json, Value};
 
+use super::rate_limit::RATE_LIMITED_PREFIX;
+
 pub(crate) const DEFAULT_REMOTE_HOST: &str = "127.0.0.1:4732";
 pub(crate) const DISCONNECTED_MESSAGE: &str = "remote backend disconnected";
+pub(crate) const CONNECT_FAILED_MESSAGE: &str = "Failed to connect to remote backend";
+/// Emitted with a `RemoteCallFailure` for every failed remote call; the
+/// frontend offers a retry for the transient ones.
+pub(crate) const REMOTE_CALL_FAILED_EVENT: &str = "remote-call-failed";
 
 pub(crate) enum IncomingMessage {
     Response {
@@ -14,15 +21,127 @@ pub(crate) enum IncomingMessage {
     },
 }
 
-pub(crate) fn build_request_line(id: u64, method: &str, params: Value) -> Result<String, String> {
+/// `request_id` is a client-generated correlation id, distinct from the
+/// per-connection JSON-RPC `id`, that appears in logs on both sides.
+pub(crate) fn build_request_line(
+    id: u64,
+    request_id: &str,
+    method: &str,
+    params: Value,
+) -> Result<String, String> {
     let request = json!({
         "id": id,
+        "requestId": request_id,
         "method": method,
         "params": params,
     });
     serde_json::to_string(&request).map_err(|err| err.to_string())
 }
 
+pub(crate) fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RemoteErrorClass {
+    /// The backend is unreachable or the connection dropped.
+    Connectivity,
+    Timeout,
+    /// The backend failed while handling the request (5xx).
+    Server,
+    Cancelled,
+    /// The client-side rate limiter gave up waiting for a token.
+    RateLimited,
+    /// The request itself was rejected (4xx, validation and the like).
+    Request,
+}
+
+impl RemoteErrorClass {
+    /// Whether the same request may succeed if sent again later.
+    pub(crate) fn is_transient(self) -> bool {
+        matches!(
+            self,
+            RemoteErrorClass::Connectivity
+                | RemoteErrorClass::Timeout
+                | RemoteErrorClass::Server
+                | RemoteErrorClass::RateLimited
+        )
+    }
+}
+
+/// The one place remote errors are classified; retry, the offline mirror and
+/// the frontend's retry affordance all go through it.
+pub(crate) fn classify_error(error: &str) -> RemoteErrorClass {
+    if error.starts_with(DISCONNECTED_MESSAGE) || error.starts_with(CONNECT_FAILED_MESSAGE) {
+        return RemoteErrorClass::Connectivity;
+    }
+    if error.starts_with(RATE_LIMITED_PREFIX) {
+        return RemoteErrorClass::RateLimited;
+    }
+    let lower = error.to_ascii_lowercase();
+    if lower.contains("cancelled") || lower.contains("canceled") {
+        return RemoteErrorClass::Cancelled;
+    }
+    if lower.contains("timed out") {
+        return RemoteErrorClass::Timeout;
+    }
+    match http_status(&lower) {
+        Some(500..=599) => RemoteErrorClass::Server,
+        _ => RemoteErrorClass::Request,
+    }
+}
+
+/// The code following a `status` or `http` word, e.g. `status: 503`.
+fn http_status(error: &str) -> Option<u16> {
+    let mut words = error
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty());
+    while let Some(word) = words.next() {
+        if word == "status" || word == "http" {
+            if let Some(code) = words.clone().next().and_then(|next| next.parse().ok()) {
+                return Some(code);
+            }
+        }
+    }
+    None
+}
+
+pub(crate) fn is_transient(error: &str) -> bool {
+    classify_error(error).is_transient()
+}
+
+/// Whether `error` means the backend is unreachable rather than that the
+/// request itself failed.
+pub(crate) fn is_connectivity_error(error: &str) -> bool {
+    classify_error(error) == RemoteErrorClass::Connectivity
+}
+
+/// A failed remote call as reported next to its unchanged error text, so
+/// sentinels such as `DELETED_EXTERNALLY` still compare exactly.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteCallFailure {
+    pub(crate) method: String,
+    pub(crate) request_id: String,
+    pub(crate) error_class: RemoteErrorClass,
+    pub(crate) transient: bool,
+    pub(crate) error: String,
+}
+
+impl RemoteCallFailure {
+    pub(crate) fn new(method: &str, request_id: &str, error: &str) -> Self {
+        let error_class = classify_error(error);
+        Self {
+            method: method.to_string(),
+            request_id: request_id.to_string(),
+            error_class,
+            transient: error_class.is_transient(),
+            error: error.to_string(),
+        }
+    }
+}
+
 pub(crate) fn parse_incoming_line(line: &str) -> Option<IncomingMessage> {
     let message: Value = serde_json::from_str(line).ok()?;
 
@@ -59,3 +178,68 @@ pub(crate) fn parse_incoming_line(line: &str) -> Option<IncomingMessage> {
         params,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_request_line, classify_error, RemoteCallFailure, RemoteErrorClass,
+        DISCONNECTED_MESSAGE,
+    };
+    use serde_json::{json, Value};
+
+    #[test]
+    fn classifies_transient_and_request_errors() {
+        assert_eq!(
+            classify_error(DISCONNECTED_MESSAGE),
+            RemoteErrorClass::Connectivity
+        );
+        assert_eq!(
+            classify_error("remote backend request timed out after 300 seconds"),
+            RemoteErrorClass::Timeout
+        );
+        assert_eq!(
+            classify_error("upstream returned status: 503"),
+            RemoteErrorClass::Server
+        );
+        assert_eq!(
+            classify_error("upstream returned status 404"),
+            RemoteErrorClass::Request
+        );
+        assert_eq!(
+            classify_error("missing `workspaceId`"),
+            RemoteErrorClass::Request
+        );
+        assert_eq!(
+            classify_error("request cancelled"),
+            RemoteErrorClass::Cancelled
+        );
+        assert_eq!(
+            classify_error("RATE_LIMITED: `file_read` waited longer than 5000ms"),
+            RemoteErrorClass::RateLimited
+        );
+        assert!(RemoteErrorClass::RateLimited.is_transient());
+    }
+
+    #[test]
+    fn failure_keeps_error_text_and_carries_request_id() {
+        let failure = RemoteCallFailure::new("file_write", "req-1", DISCONNECTED_MESSAGE);
+        assert_eq!(failure.error, DISCONNECTED_MESSAGE);
+        assert_eq!(failure.request_id, "req-1");
+        assert_eq!(failure.error_class, RemoteErrorClass::Connectivity);
+        assert!(failure.transient);
+
+        let rejected = RemoteCallFailure::new("file_write", "req-2", "DELETED_EXTERNALLY");
+        assert_eq!(rejected.error, "DELETED_EXTERNALLY");
+        assert!(!rejected.transient);
+    }
+
+    #[test]
+    fn request_line_carries_request_id() {
+        let line = build_request_line(7, "req-1", "file_read", json!({ "scope": "global" }))
+            .expect("build line");
+        let value: Value = serde_json::from_str(&line).expect("parse line");
+        assert_eq!(value["id"], 7);
+        assert_eq!(value["requestId"], "req-1");
+        assert_eq!(value["method"], "file_read");
+    }
+}
@@ -0,0 +1,361 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+pub(crate) const RATE_LIMITED_PREFIX: &str = "RATE_LIMITED";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BucketConfig {
+    pub(crate) per_second: f64,
+    pub(crate) burst: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RateLimitConfig {
+    pub(crate) enabled: bool,
+    pub(crate) reads: BucketConfig,
+    pub(crate) writes: BucketConfig,
+    pub(crate) max_queue_wait_ms: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reads: BucketConfig {
+                per_second: 20.0,
+                burst: 40,
+            },
+            writes: BucketConfig {
+                per_second: 10.0,
+                burst: 20,
+            },
+            max_queue_wait_ms: 5_000,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        for (label, bucket) in [("reads", self.reads), ("writes", self.writes)] {
+            if !bucket.per_second.is_finite() || bucket.per_second <= 0.0 {
+                return Err(format!("`{label}.perSecond` must be greater than zero"));
+            }
+            if bucket.burst == 0 {
+                return Err(format!("`{label}.burst` must be at least 1"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    config: BucketConfig,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: BucketConfig, now: Instant) -> Self {
+        Self {
+            config,
+            tokens: f64::from(config.burst),
+            refilled_at: now,
+        }
+    }
+
+    fn reconfigure(&mut self, config: BucketConfig, now: Instant) {
+        self.refill(now);
+        self.config = config;
+        self.tokens = self.tokens.min(f64::from(config.burst));
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.config.per_second).min(f64::from(self.config.burst));
+        self.refilled_at = now;
+    }
+
+    /// Takes the next token and returns how long the caller must wait before
+    /// using it; the bucket goes into debt so later callers queue behind this
+    /// one. Nothing is taken when the wait would exceed `max_wait`, and the
+    /// `Err` carries that wait.
+    fn reserve(&mut self, now: Instant, max_wait: Duration) -> Result<Duration, Duration> {
+        self.refill(now);
+        let wait = if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.config.per_second)
+        };
+        if wait > max_wait {
+            return Err(wait);
+        }
+        self.tokens -= 1.0;
+        Ok(wait)
+    }
+}
+
+/// One token bucket and the callers currently queued on it.
+struct BucketState {
+    bucket: Mutex<TokenBucket>,
+    /// Callers inside `acquire` that have no token yet.
+    waiting: AtomicUsize,
+    /// Callers sleeping until the bucket refills.
+    sleeping: AtomicUsize,
+}
+
+impl BucketState {
+    fn new(config: BucketConfig, now: Instant) -> Self {
+        Self {
+            bucket: Mutex::new(TokenBucket::new(config, now)),
+            waiting: AtomicUsize::new(0),
+            sleeping: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Paces outgoing remote calls with separate token buckets for reads and writes.
+/// Calls over the limit wait for a token instead of failing, up to
+/// `max_queue_wait_ms`.
+pub(crate) struct RemoteRateLimiter {
+    config: Mutex<RateLimitConfig>,
+    reads: BucketState,
+    writes: BucketState,
+}
+
+/// Counts a caller in one of `BucketState`'s counters until it is dropped,
+/// which also covers a caller whose future was dropped mid-wait.
+struct CountGuard<'a> {
+    counter: &'a AtomicUsize,
+}
+
+impl<'a> CountGuard<'a> {
+    fn enter(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self { counter }
+    }
+}
+
+impl Drop for CountGuard<'_> {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Default for RemoteRateLimiter {
+    fn default() -> Self {
+        let config = RateLimitConfig::default();
+        let now = Instant::now();
+        Self {
+            config: Mutex::new(config),
+            reads: BucketState::new(config.reads, now),
+            writes: BucketState::new(config.writes, now),
+        }
+    }
+}
+
+impl RemoteRateLimiter {
+    pub(crate) async fn config(&self) -> RateLimitConfig {
+        *self.config.lock().await
+    }
+
+    pub(crate) async fn set_config(&self, config: RateLimitConfig) -> Result<(), String> {
+        config.validate()?;
+        let now = Instant::now();
+        self.reads
+            .bucket
+            .lock()
+            .await
+            .reconfigure(config.reads, now);
+        self.writes
+            .bucket
+            .lock()
+            .await
+            .reconfigure(config.writes, now);
+        *self.config.lock().await = config;
+        Ok(())
+    }
+
+    /// Refills both buckets, forgetting recent traffic.
+    pub(crate) async fn reset(&self) {
+        let config = self.config().await;
+        let now = Instant::now();
+        *self.reads.bucket.lock().await = TokenBucket::new(config.reads, now);
+        *self.writes.bucket.lock().await = TokenBucket::new(config.writes, now);
+    }
+
+    /// Callers without a token yet, across both buckets.
+    pub(crate) fn waiting(&self) -> usize {
+        self.reads.waiting.load(Ordering::SeqCst) + self.writes.waiting.load(Ordering::SeqCst)
+    }
+
+    /// Whether either bucket has a caller sleeping until it refills.
+    pub(crate) fn throttled(&self) -> bool {
+        self.reads.sleeping.load(Ordering::SeqCst) > 0
+            || self.writes.sleeping.load(Ordering::SeqCst) > 0
+    }
+
+    pub(crate) async fn acquire(&self, method: &str, is_read: bool) -> Result<(), String> {
+        let config = self.config().await;
+        if !config.enabled {
+            return Ok(());
+        }
+        let state = if is_read { &self.reads } else { &self.writes };
+        let max_wait = Duration::from_millis(config.max_queue_wait_ms);
+        let _waiting = CountGuard::enter(&state.waiting);
+        // The lock only covers the reservation; reserved tokens keep callers in
+        // FIFO order while they sleep without it.
+        let reserved = state.bucket.lock().await.reserve(Instant::now(), max_wait);
+        let Ok(wait) = reserved else {
+            return Err(format!(
+                "{RATE_LIMITED_PREFIX}: `{method}` waited longer than {}ms for the remote backend",
+                config.max_queue_wait_ms
+            ));
+        };
+        if !wait.is_zero() {
+            let _sleeping = CountGuard::enter(&state.sleeping);
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BucketConfig, RateLimitConfig, RemoteRateLimiter, TokenBucket};
+    use std::sync::atomic::Ordering;
+    use std::time::{Duration, Instant};
+
+    const CONFIG: BucketConfig = BucketConfig {
+        per_second: 2.0,
+        burst: 2,
+    };
+
+    #[test]
+    fn bucket_allows_burst_then_reports_wait() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(CONFIG, now);
+        assert_eq!(bucket.reserve(now, Duration::ZERO), Ok(Duration::ZERO));
+        assert_eq!(bucket.reserve(now, Duration::ZERO), Ok(Duration::ZERO));
+        let wait = bucket
+            .reserve(now, Duration::ZERO)
+            .expect_err("bucket should be empty");
+        assert_eq!(wait, Duration::from_millis(500));
+        assert_eq!(
+            bucket.reserve(now + Duration::from_millis(500), Duration::ZERO),
+            Ok(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn bucket_refill_is_capped_at_burst() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(CONFIG, now);
+        let later = now + Duration::from_secs(60);
+        assert!(bucket.reserve(later, Duration::ZERO).is_ok());
+        assert!(bucket.reserve(later, Duration::ZERO).is_ok());
+        assert!(bucket.reserve(later, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn reservations_queue_callers_in_order() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(CONFIG, now);
+        let max_wait = Duration::from_secs(5);
+        bucket.reserve(now, max_wait).expect("first");
+        bucket.reserve(now, max_wait).expect("second");
+        assert_eq!(
+            bucket.reserve(now, max_wait),
+            Ok(Duration::from_millis(500))
+        );
+        assert_eq!(bucket.reserve(now, max_wait), Ok(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn waiting_caller_does_not_hold_the_bucket_lock() {
+        let runtime = tokio::runtime::Runtime::new().expect("create tokio runtime");
+        runtime.block_on(async {
+            let limiter = std::sync::Arc::new(RemoteRateLimiter::default());
+            limiter
+                .set_config(RateLimitConfig {
+                    enabled: true,
+                    reads: BucketConfig {
+                        per_second: 5.0,
+                        burst: 1,
+                    },
+                    writes: CONFIG,
+                    max_queue_wait_ms: 1_000,
+                })
+                .await
+                .expect("set config");
+            limiter
+                .acquire("file_read", true)
+                .await
+                .expect("first read");
+            let sleeper = {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.acquire("file_read", true).await })
+            };
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            assert!(limiter.throttled());
+            assert_eq!(limiter.reads.sleeping.load(Ordering::SeqCst), 1);
+            assert_eq!(limiter.writes.waiting.load(Ordering::SeqCst), 0);
+            tokio::time::timeout(Duration::from_millis(50), limiter.reads.bucket.lock())
+                .await
+                .expect("bucket lock is free while the caller sleeps");
+            sleeper.await.expect("join").expect("second read");
+            assert!(!limiter.throttled());
+        });
+    }
+
+    #[test]
+    fn invalid_config_is_rejected() {
+        let mut config = RateLimitConfig::default();
+        config.reads.per_second = 0.0;
+        assert!(config.validate().is_err());
+        let mut config = RateLimitConfig::default();
+        config.writes.burst = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn acquire_fails_when_queue_wait_is_exceeded() {
+        let runtime = tokio::runtime::Runtime::new().expect("create tokio runtime");
+        runtime.block_on(async {
+            let limiter = RemoteRateLimiter::default();
+            limiter
+                .set_config(RateLimitConfig {
+                    enabled: true,
+                    reads: BucketConfig {
+                        per_second: 0.1,
+                        burst: 1,
+                    },
+                    writes: CONFIG,
+                    max_queue_wait_ms: 50,
+                })
+                .await
+                .expect("set config");
+            limiter
+                .acquire("file_read", true)
+                .await
+                .expect("first read");
+            let error = limiter
+                .acquire("file_read", true)
+                .await
+                .expect_err("second read should be rate limited");
+            assert!(error.starts_with("RATE_LIMITED"));
+            limiter
+                .acquire("file_write", false)
+                .await
+                .expect("writes use their own bucket");
+        });
+    }
+}
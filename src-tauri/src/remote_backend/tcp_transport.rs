@@ -1,6 +1,7 @@
 use tauri::AppHandle;
 use tokio::net::TcpStream;
 
+use super::protocol::CONNECT_FAILED_MESSAGE;
 use super::transport::{
     spawn_transport_io, RemoteTransport, RemoteTransportConfig, TransportFuture,
 };
@@ -14,7 +15,7 @@ impl RemoteTransport for TcpTransport {
 
             let stream = TcpStream::connect(host.clone())
                 .await
-                .map_err(|err| format!("Failed to connect to remote backend at {host}: {err}"))?;
+                .map_err(|err| format!("{CONNECT_FAILED_MESSAGE} at {host}: {err}"))?;
             let (reader, writer) = stream.into_split();
             Ok(spawn_transport_io(app, reader, writer))
         })
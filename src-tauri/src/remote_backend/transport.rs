@@ -141,6 +141,11 @@ pub(crate) async fn dispatch_incoming_line(
             "terminal-exit" => {
                 let _ = app.emit("terminal-exit", params);
             }
+            "operation-progress" | "operation-done" | "operation-failed" | "scope-changed"
+            | "batch-changed" | "workspaces-changed" | "config-patch" | "prewarm-done"
+            | "workspace-health" | "tree-entry" | "tree-truncated" | "tree-done" => {
+                let _ = app.emit(method.as_str(), params);
+            }
             _ => {}
         },
     }
@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+/// Reads the app-owned key-value store. Values keep their JSON types.
+pub(crate) fn read_app_kv(path: &Path) -> Result<Map<String, Value>, String> {
+    if !path.exists() {
+        return Ok(Map::new());
+    }
+    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+/// Writes the key-value store through a temp file and rename so a crash
+/// mid-write never leaves a truncated file.
+pub(crate) fn write_app_kv(path: &Path, values: &Map<String, Value>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(values).map_err(|e| e.to_string())?;
+    let temp_path = path.with_extension(format!("json.{}.tmp", uuid::Uuid::new_v4()));
+    std::fs::write(&temp_path, data).map_err(|e| e.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        e.to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_app_kv, write_app_kv};
+    use uuid::Uuid;
+
+    #[test]
+    fn app_kv_round_trips_typed_values() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let path = temp_dir.join("app-kv.json");
+        assert!(read_app_kv(&path).expect("read missing").is_empty());
+
+        let mut values = serde_json::Map::new();
+        values.insert("theme".to_string(), serde_json::json!("dark"));
+        values.insert("lastTab".to_string(), serde_json::json!(3));
+        values.insert("panes".to_string(), serde_json::json!({ "left": true }));
+        write_app_kv(&path, &values).expect("write");
+
+        let read = read_app_kv(&path).expect("read");
+        assert_eq!(read, values);
+        assert_eq!(read.get("lastTab"), Some(&serde_json::json!(3)));
+        let leftovers = std::fs::read_dir(&temp_dir)
+            .expect("read dir")
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .map(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+                    .unwrap_or(false)
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+}
@@ -1,18 +1,69 @@
-use tauri::{State, Window};
+use std::path::PathBuf;
 
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Manager, State, Window};
+
+use self::app_kv::write_app_kv;
+use crate::backend::events::OperationReporter;
+use crate::codex::config::CodexConfigValues;
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::shared::codex_home_core::{codex_home_relocate_core, CodexHomeRelocation};
+use crate::shared::config_patch_core::ConfigWriter;
 use crate::shared::settings_core::{
-    get_app_settings_core, get_codex_config_path_core, update_app_settings_core,
+    apply_codex_config_values, codex_config_values_from_settings, feature_preset_apply_core,
+    feature_preset_delete_core, feature_preset_list_core, feature_preset_save_core,
+    get_app_settings_core, get_codex_config_path_core, persist_app_settings_core,
+    update_app_settings_core,
 };
+use crate::shared::workspace_rpc;
+use crate::shared::workspaces_core::{feature_preset_apply_all_core, PresetApplyAllReport};
 use crate::state::AppState;
-use crate::types::{AppSettings, BackendMode};
+use crate::types::{AppSettings, BackendMode, FeaturePreset};
 use crate::window;
 
+pub(crate) mod app_kv;
+
+async fn read_remote_config_values(
+    state: &AppState,
+    app: &AppHandle,
+) -> Result<CodexConfigValues, String> {
+    let response =
+        remote_backend::call_remote(state, app.clone(), "config_read", json!({})).await?;
+    serde_json::from_value(response).map_err(|err| err.to_string())
+}
+
+async fn write_remote_config_values(
+    state: &AppState,
+    app: &AppHandle,
+    values: CodexConfigValues,
+) -> Result<(), String> {
+    remote_backend::call_remote(
+        state,
+        app.clone(),
+        "config_write",
+        json!({ "values": values }),
+    )
+    .await?;
+    Ok(())
+}
+
 #[tauri::command]
 pub(crate) async fn get_app_settings(
     state: State<'_, AppState>,
     window: Window,
+    app: AppHandle,
 ) -> Result<AppSettings, String> {
-    let settings = get_app_settings_core(&state.app_settings).await;
+    let settings = if remote_backend::is_remote_mode(&*state).await {
+        let mut settings = state.app_settings.lock().await.clone();
+        if let Ok(values) = read_remote_config_values(&*state, &app).await {
+            apply_codex_config_values(&mut settings, values);
+        }
+        settings
+    } else {
+        get_app_settings_core(&state.app_settings, &state.scope_root_overrides).await
+    };
     let _ = window::apply_window_appearance(&window, settings.theme.as_str());
     Ok(settings)
 }
@@ -22,10 +73,33 @@ pub(crate) async fn update_app_settings(
     settings: AppSettings,
     state: State<'_, AppState>,
     window: Window,
+    app: AppHandle,
 ) -> Result<AppSettings, String> {
     let previous = state.app_settings.lock().await.clone();
-    let updated =
-        update_app_settings_core(settings, &state.app_settings, &state.settings_path).await?;
+    let updated = if remote_backend::is_remote_mode(&*state).await {
+        let values = codex_config_values_from_settings(&settings);
+        let _ = write_remote_config_values(&*state, &app, values).await;
+        persist_app_settings_core(
+            settings,
+            &state.app_settings,
+            &state.settings_path,
+            &state.scope_root_overrides,
+        )
+        .await?
+    } else {
+        update_app_settings_core(
+            settings,
+            &state.app_settings,
+            &state.settings_path,
+            &state.scope_root_overrides,
+            ConfigWriter::new(
+                "update_app_settings",
+                &TauriEventSink::new(app),
+                &state.file_generations,
+            ),
+        )
+        .await?
+    };
     if should_reset_remote_backend(&previous, &updated) {
         *state.remote_backend.lock().await = None;
     }
@@ -34,9 +108,279 @@ pub(crate) async fn update_app_settings(
     Ok(updated)
 }
 
+/// Presets live with the backend that owns `config.toml`, so remote mode keeps
+/// them in the daemon's settings.
+#[tauri::command]
+pub(crate) async fn feature_preset_save(
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<FeaturePreset, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "feature_preset_save",
+            json!({ "name": name }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    feature_preset_save_core(
+        &name,
+        &state.app_settings,
+        &state.settings_path,
+        &state.scope_root_overrides,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn feature_preset_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<FeaturePreset>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "feature_preset_list", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(feature_preset_list_core(&state.app_settings).await)
+}
+
+/// Writes the preset's flags in one batch; `clear_missing` also removes flags
+/// the preset does not mention.
+#[tauri::command]
+pub(crate) async fn feature_preset_apply(
+    name: String,
+    clear_missing: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<FeaturePreset, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "feature_preset_apply",
+            json!({ "name": name, "clearMissing": clear_missing }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    feature_preset_apply_core(
+        &name,
+        clear_missing,
+        &state.app_settings,
+        &state.scope_root_overrides,
+        ConfigWriter::new(
+            "feature_preset_apply",
+            &TauriEventSink::new(app),
+            &state.file_generations,
+        ),
+    )
+    .await
+}
+
+/// Applies a preset to the override layer of each listed workspace; with no
+/// workspaces it goes to the global `config.toml` only.
+#[tauri::command]
+pub(crate) async fn feature_preset_apply_all(
+    name: String,
+    workspace_ids: Vec<String>,
+    operation_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<PresetApplyAllReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::FeaturePresetApplyAllRequest {
+            name,
+            workspace_ids,
+            operation_id,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "feature_preset_apply_all",
+            workspace_rpc::to_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let sink = TauriEventSink::new(app);
+    let reporter = OperationReporter::new(sink.clone(), operation_id);
+    feature_preset_apply_all_core(
+        &state.workspaces,
+        &state.storage_path,
+        &state.app_settings,
+        &state.scope_root_overrides,
+        &name,
+        workspace_ids,
+        &reporter,
+        ConfigWriter::new("feature_preset_apply_all", &sink, &state.file_generations),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn feature_preset_delete(
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "feature_preset_delete",
+            json!({ "name": name }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    feature_preset_delete_core(
+        &name,
+        &state.app_settings,
+        &state.settings_path,
+        &state.scope_root_overrides,
+    )
+    .await
+}
+
 #[tauri::command]
-pub(crate) async fn get_codex_config_path() -> Result<String, String> {
-    get_codex_config_path_core()
+pub(crate) async fn get_codex_config_path(state: State<'_, AppState>) -> Result<String, String> {
+    get_codex_config_path_core(&state.scope_root_overrides)
+}
+
+/// Moves `config.toml` (or with `copy_all` the whole home) to `new_path` and
+/// makes it the CODEX_HOME the app resolves. Open sessions keep the old home
+/// until they reconnect.
+#[tauri::command]
+pub(crate) async fn codex_home_relocate(
+    new_path: String,
+    copy_all: bool,
+    overwrite: bool,
+    remove_old: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<CodexHomeRelocation, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_home_relocate",
+            json!({
+                "newPath": remote_backend::normalize_path_for_remote(new_path),
+                "copyAll": copy_all,
+                "overwrite": overwrite,
+                "removeOld": remove_old,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_home_relocate_core(
+        &new_path,
+        copy_all,
+        overwrite,
+        remove_old,
+        &state.app_settings,
+        &state.settings_path,
+        &state.scope_root_overrides,
+        &state.file_generations,
+    )
+    .await
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LocationEntry {
+    pub(crate) path: Option<String>,
+    pub(crate) exists: bool,
+}
+
+/// Where CodexMonitor and Codex keep their files on this machine.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Locations {
+    pub(crate) codex_home: LocationEntry,
+    pub(crate) config_toml: LocationEntry,
+    pub(crate) app_data_dir: LocationEntry,
+    pub(crate) workspaces_file: LocationEntry,
+    pub(crate) log_dir: LocationEntry,
+    /// The remote backend host; `None` in local mode.
+    pub(crate) remote_endpoint: Option<String>,
+}
+
+fn location_entry(path: Option<PathBuf>) -> LocationEntry {
+    LocationEntry {
+        exists: path.as_ref().is_some_and(|path| path.exists()),
+        path: path.map(|path| path.to_string_lossy().to_string()),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn locations(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Locations, String> {
+    let codex_home = crate::codex::home::resolve_default_codex_home();
+    let remote_endpoint = {
+        let settings = state.app_settings.lock().await;
+        matches!(settings.backend_mode, BackendMode::Remote)
+            .then(|| remote_backend::remote_endpoint(&settings))
+    };
+    Ok(Locations {
+        config_toml: location_entry(codex_home.as_ref().map(|home| home.join("config.toml"))),
+        codex_home: location_entry(codex_home),
+        app_data_dir: location_entry(state.storage_path.parent().map(PathBuf::from)),
+        workspaces_file: location_entry(Some(state.storage_path.clone())),
+        log_dir: location_entry(app.path().app_log_dir().ok()),
+        remote_endpoint,
+    })
+}
+
+#[tauri::command]
+pub(crate) async fn app_setting_get(
+    key: String,
+    state: State<'_, AppState>,
+) -> Result<Option<Value>, String> {
+    Ok(state.app_kv.lock().await.get(&key).cloned())
+}
+
+/// Stores `value` under `key`; `null` removes the key.
+#[tauri::command]
+pub(crate) async fn app_setting_set(
+    key: String,
+    value: Value,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return Err("Setting key is required".to_string());
+    }
+    let mut values = state.app_kv.lock().await;
+    let mut next = values.clone();
+    if value.is_null() {
+        next.remove(&key);
+    } else {
+        next.insert(key, value);
+    }
+    write_app_kv(&state.app_kv_path, &next)?;
+    *values = next;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn app_settings_all(
+    state: State<'_, AppState>,
+) -> Result<Map<String, Value>, String> {
+    Ok(state.app_kv.lock().await.clone())
 }
 
 fn should_reset_remote_backend(previous: &AppSettings, updated: &AppSettings) -> bool {
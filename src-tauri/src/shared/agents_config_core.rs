@@ -5,6 +5,8 @@ use std::path::{Component, Path, PathBuf};
 use toml_edit::{value, Document, Item, Table};
 
 use crate::codex::home as codex_home;
+use crate::shared::config_lock_core;
+use crate::shared::config_patch_core::ConfigWriter;
 use crate::shared::config_toml_core;
 
 pub(crate) const DEFAULT_AGENT_MAX_THREADS: u32 = 6;
@@ -107,11 +109,13 @@ pub(crate) fn get_agents_settings_core() -> Result<AgentsSettingsDto, String> {
 
 pub(crate) fn set_agents_core_settings_core(
     input: SetAgentsCoreInput,
+    writer: ConfigWriter<'_>,
 ) -> Result<AgentsSettingsDto, String> {
     validate_max_threads(input.max_threads)?;
     validate_max_depth(input.max_depth)?;
 
     let codex_home = resolve_codex_home()?;
+    let _lock = config_lock_core::lock_config_for_write(&codex_home)?;
     let (_, mut document) = config_toml_core::load_global_config_document(&codex_home)?;
 
     let features = config_toml_core::ensure_table(&mut document, "features")?;
@@ -121,16 +125,20 @@ pub(crate) fn set_agents_core_settings_core(
     agents["max_threads"] = value(input.max_threads as i64);
     agents["max_depth"] = value(input.max_depth as i64);
 
-    config_toml_core::persist_global_config_document(&codex_home, &document)?;
+    config_toml_core::persist_global_config_document(&codex_home, &document, writer)?;
     get_agents_settings_core()
 }
 
-pub(crate) fn create_agent_core(input: CreateAgentInput) -> Result<AgentsSettingsDto, String> {
+pub(crate) fn create_agent_core(
+    input: CreateAgentInput,
+    writer: ConfigWriter<'_>,
+) -> Result<AgentsSettingsDto, String> {
     let name = normalize_agent_name(input.name.as_str())?;
     let description = normalize_optional_string(input.description.as_deref());
     let developer_instructions = normalize_optional_string(input.developer_instructions.as_deref());
 
     let codex_home = resolve_codex_home()?;
+    let _lock = config_lock_core::lock_config_for_write(&codex_home)?;
     let (_, mut document) = config_toml_core::load_global_config_document(&codex_home)?;
 
     {
@@ -167,7 +175,9 @@ pub(crate) fn create_agent_core(input: CreateAgentInput) -> Result<AgentsSetting
         agents[&name] = Item::Table(role);
     }
 
-    if let Err(err) = config_toml_core::persist_global_config_document(&codex_home, &document) {
+    if let Err(err) =
+        config_toml_core::persist_global_config_document(&codex_home, &document, writer)
+    {
         let _ = std::fs::remove_file(&target_path);
         return Err(err);
     }
@@ -175,7 +185,10 @@ pub(crate) fn create_agent_core(input: CreateAgentInput) -> Result<AgentsSetting
     get_agents_settings_core()
 }
 
-pub(crate) fn update_agent_core(input: UpdateAgentInput) -> Result<AgentsSettingsDto, String> {
+pub(crate) fn update_agent_core(
+    input: UpdateAgentInput,
+    writer: ConfigWriter<'_>,
+) -> Result<AgentsSettingsDto, String> {
     let original_name = normalize_agent_lookup_name(input.original_name.as_str())?;
     let name = normalize_agent_name(input.name.as_str())?;
     let description = normalize_optional_string(input.description.as_deref());
@@ -184,6 +197,7 @@ pub(crate) fn update_agent_core(input: UpdateAgentInput) -> Result<AgentsSetting
     let rename_managed_file = input.rename_managed_file.unwrap_or(true);
 
     let codex_home = resolve_codex_home()?;
+    let _lock = config_lock_core::lock_config_for_write(&codex_home)?;
     let (_, mut document) = config_toml_core::load_global_config_document(&codex_home)?;
 
     let mut maybe_renamed_paths: Option<(PathBuf, PathBuf)> = None;
@@ -289,7 +303,9 @@ pub(crate) fn update_agent_core(input: UpdateAgentInput) -> Result<AgentsSetting
         agents[&name] = Item::Table(role);
     }
 
-    if let Err(err) = config_toml_core::persist_global_config_document(&codex_home, &document) {
+    if let Err(err) =
+        config_toml_core::persist_global_config_document(&codex_home, &document, writer)
+    {
         if let Some((path, backup)) = maybe_config_content_backup {
             match backup {
                 Some(bytes) => {
@@ -313,11 +329,15 @@ pub(crate) fn update_agent_core(input: UpdateAgentInput) -> Result<AgentsSetting
     get_agents_settings_core()
 }
 
-pub(crate) fn delete_agent_core(input: DeleteAgentInput) -> Result<AgentsSettingsDto, String> {
+pub(crate) fn delete_agent_core(
+    input: DeleteAgentInput,
+    writer: ConfigWriter<'_>,
+) -> Result<AgentsSettingsDto, String> {
     let name = normalize_agent_lookup_name(input.name.as_str())?;
     let delete_managed_file = input.delete_managed_file.unwrap_or(false);
 
     let codex_home = resolve_codex_home()?;
+    let _lock = config_lock_core::lock_config_for_write(&codex_home)?;
     let (_, mut document) = config_toml_core::load_global_config_document(&codex_home)?;
 
     let removed_config_file = {
@@ -346,7 +366,7 @@ pub(crate) fn delete_agent_core(input: DeleteAgentInput) -> Result<AgentsSetting
     }
 
     if let Err(persist_error) =
-        config_toml_core::persist_global_config_document(&codex_home, &document)
+        config_toml_core::persist_global_config_document(&codex_home, &document, writer)
     {
         if let Some((path, backup)) = deleted_config_backup {
             if let Err(restore_error) = std::fs::write(&path, backup) {
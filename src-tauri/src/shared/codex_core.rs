@@ -16,6 +16,9 @@ use crate::codex::config as codex_config;
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
 use crate::rules;
 use crate::shared::account::{build_account_response, read_auth_account};
+use crate::shared::workspaces_core::{
+    layered_feature_overrides, layered_override, resolve_sandbox_posture, WorkspaceDefaultKey,
+};
 use crate::types::WorkspaceEntry;
 
 const LOGIN_START_TIMEOUT: Duration = Duration::from_secs(30);
@@ -171,16 +174,57 @@ pub(crate) async fn start_thread_core(
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let workspace_path = resolve_workspace_path_core(workspaces, &workspace_id).await?;
-    let params = json!({
-        "cwd": workspace_path,
-        "approvalPolicy": "on-request"
-    });
+    let (entry, parent_entry) = resolve_workspace_and_parent(workspaces, &workspace_id).await?;
+    let (sandbox_mode, approval_policy) = resolve_sandbox_posture(&entry, parent_entry.as_ref())?;
+    let mut params = Map::new();
+    params.insert("cwd".to_string(), json!(entry.path));
+    params.insert("approvalPolicy".to_string(), json!(approval_policy.value));
+    params.insert("sandbox".to_string(), json!(sandbox_mode.value));
+    apply_workspace_overrides(&mut params, &entry, parent_entry.as_ref());
+    insert_feature_overrides(&mut params, &entry, parent_entry.as_ref());
     session
-        .send_request_for_workspace(&workspace_id, "thread/start", params)
+        .send_request_for_workspace(&workspace_id, "thread/start", Value::Object(params))
         .await
 }
 
+/// Sends the workspace's feature overrides as the thread's `features.*`
+/// config, on top of what Codex reads from `config.toml`.
+fn insert_feature_overrides(
+    params: &mut Map<String, Value>,
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+) {
+    let config: Map<String, Value> = layered_feature_overrides(entry, parent_entry)
+        .into_iter()
+        .map(|(key, enabled)| (format!("features.{key}"), json!(enabled)))
+        .collect();
+    if !config.is_empty() {
+        params.insert("config".to_string(), Value::Object(config));
+    }
+}
+
+/// Fills `model` and `personality` from the workspace's overrides where the
+/// caller left them unset. Without an override the field stays unset and
+/// Codex falls back to its own `config.toml`.
+fn apply_workspace_overrides(
+    params: &mut Map<String, Value>,
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+) {
+    let fields = [
+        ("model", WorkspaceDefaultKey::Model),
+        ("personality", WorkspaceDefaultKey::Personality),
+    ];
+    for (field, key) in fields {
+        if params.get(field).filter(|value| !value.is_null()).is_some() {
+            continue;
+        }
+        if let Some(value) = layered_override(entry, parent_entry, key) {
+            params.insert(field.to_string(), json!(value));
+        }
+    }
+}
+
 pub(crate) async fn resume_thread_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
@@ -396,22 +440,22 @@ pub(crate) async fn send_user_message_core(
     collaboration_mode: Option<Value>,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let workspace_path = resolve_workspace_path_core(workspaces, &workspace_id).await?;
+    let (entry, parent_entry) = resolve_workspace_and_parent(workspaces, &workspace_id).await?;
+    let workspace_path = entry.path.clone();
     let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
-    let sandbox_policy = match access_mode.as_str() {
-        "full-access" => json!({ "type": "dangerFullAccess" }),
-        "read-only" => json!({ "type": "readOnly" }),
-        _ => json!({
-            "type": "workspaceWrite",
-            "writableRoots": [workspace_path.clone()],
-            "networkAccess": true
-        }),
-    };
-
-    let approval_policy = if access_mode == "full-access" {
-        "never"
-    } else {
-        "on-request"
+    let (sandbox_policy, approval_policy) = match access_mode.as_str() {
+        "full-access" => (json!({ "type": "dangerFullAccess" }), "never".to_string()),
+        "read-only" => (json!({ "type": "readOnly" }), "on-request".to_string()),
+        _ => {
+            let (sandbox_mode, approval_policy) =
+                resolve_sandbox_posture(&entry, parent_entry.as_ref())?;
+            (
+                sandbox_policy_for_mode(sandbox_mode.value.as_deref(), &workspace_path),
+                approval_policy
+                    .value
+                    .unwrap_or_else(|| codex_config::DEFAULT_APPROVAL_POLICY.to_string()),
+            )
+        }
     };
 
     let input = build_turn_input_items(text, images, app_mentions)?;
@@ -425,6 +469,7 @@ pub(crate) async fn send_user_message_core(
     params.insert("model".to_string(), json!(model));
     params.insert("effort".to_string(), json!(effort));
     insert_optional_nullable_string(&mut params, "serviceTier", service_tier);
+    apply_workspace_overrides(&mut params, &entry, parent_entry.as_ref());
     if let Some(mode) = collaboration_mode {
         if !mode.is_null() {
             params.insert("collaborationMode".to_string(), mode);
@@ -435,6 +480,19 @@ pub(crate) async fn send_user_message_core(
         .await
 }
 
+/// The `turn/start` sandbox policy for a `sandbox_mode` value.
+fn sandbox_policy_for_mode(sandbox_mode: Option<&str>, workspace_path: &str) -> Value {
+    match sandbox_mode {
+        Some("danger-full-access") => json!({ "type": "dangerFullAccess" }),
+        Some("read-only") => json!({ "type": "readOnly" }),
+        _ => json!({
+            "type": "workspaceWrite",
+            "writableRoots": [workspace_path],
+            "networkAccess": true
+        }),
+    }
+}
+
 pub(crate) async fn turn_steer_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
@@ -801,6 +859,7 @@ pub(crate) async fn get_config_model_core(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
     use serde_json::Value;
 
     #[test]
@@ -919,6 +978,91 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn workspace_overrides_fill_only_unset_fields() {
+        let entry = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "ws".to_string(),
+            path: "/tmp/ws".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                model_override: Some("gpt-5-mini".to_string()),
+                personality_override: Some("Pragmatic".to_string()),
+                ..WorkspaceSettings::default()
+            },
+        };
+        let mut params = Map::new();
+        params.insert("model".to_string(), Value::Null);
+        apply_workspace_overrides(&mut params, &entry, None);
+        assert_eq!(params.get("model"), Some(&json!("gpt-5-mini")));
+        assert_eq!(params.get("personality"), Some(&json!("pragmatic")));
+
+        let mut params = Map::new();
+        params.insert("model".to_string(), json!("gpt-5"));
+        apply_workspace_overrides(&mut params, &entry, None);
+        assert_eq!(params.get("model"), Some(&json!("gpt-5")));
+
+        let plain = WorkspaceEntry {
+            settings: WorkspaceSettings::default(),
+            ..entry
+        };
+        let mut params = Map::new();
+        apply_workspace_overrides(&mut params, &plain, None);
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn sandbox_modes_map_to_turn_sandbox_policies() {
+        assert_eq!(
+            sandbox_policy_for_mode(Some("danger-full-access"), "/tmp/ws"),
+            json!({ "type": "dangerFullAccess" })
+        );
+        assert_eq!(
+            sandbox_policy_for_mode(Some("read-only"), "/tmp/ws"),
+            json!({ "type": "readOnly" })
+        );
+        assert_eq!(
+            sandbox_policy_for_mode(Some("workspace-write"), "/tmp/ws"),
+            json!({
+                "type": "workspaceWrite",
+                "writableRoots": ["/tmp/ws"],
+                "networkAccess": true
+            })
+        );
+    }
+
+    #[test]
+    fn feature_overrides_are_sent_as_thread_config() {
+        let entry = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "ws".to_string(),
+            path: "/tmp/ws".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                feature_overrides: [("steer".to_string(), false)].into_iter().collect(),
+                ..WorkspaceSettings::default()
+            },
+        };
+        let mut params = Map::new();
+        insert_feature_overrides(&mut params, &entry, None);
+        assert_eq!(
+            params.get("config"),
+            Some(&json!({ "features.steer": false }))
+        );
+
+        let plain = WorkspaceEntry {
+            settings: WorkspaceSettings::default(),
+            ..entry
+        };
+        let mut params = Map::new();
+        insert_feature_overrides(&mut params, &plain, None);
+        assert!(params.is_empty());
+    }
+
     #[test]
     fn insert_optional_nullable_string_omits_missing_and_preserves_null() {
         let mut params = Map::new();
@@ -0,0 +1,422 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::codex::home::{resolve_default_codex_home, validate_codex_home};
+use crate::files::generation::Generations;
+use crate::files::io::{copy_file_within, write_probe_within, FileLocation};
+use crate::files::ops::bump_file_generations;
+use crate::files::policy::ScopeRootOverrides;
+use crate::shared::settings_core::persist_app_settings_core;
+use crate::types::AppSettings;
+
+const CONFIG_FILENAME: &str = "config.toml";
+
+/// What `codex_home_relocate_core` moved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexHomeRelocation {
+    pub(crate) from: String,
+    pub(crate) to: String,
+    /// Files copied, relative to the home, in copy order.
+    pub(crate) copied: Vec<String>,
+    pub(crate) bytes: u64,
+    /// Symlinks and other entries that are not regular files; left behind.
+    pub(crate) skipped: Vec<String>,
+    /// The copied files were deleted from the old home.
+    pub(crate) removed_old: bool,
+    /// Why removing the old copies stopped; the relocation itself succeeded.
+    pub(crate) remove_error: Option<String>,
+}
+
+/// Copies `config.toml` (or with `copy_all` every regular file) from the
+/// current CODEX_HOME to `new_path`, then records `new_path` as the app's
+/// CODEX_HOME. Each file lands through a temp file and rename, and resolution
+/// only switches once every copy succeeded; on failure the files created so far
+/// are removed again and the destination files `overwrite` replaced are put
+/// back. Existing files in the destination are a conflict unless `overwrite`.
+/// `remove_old` deletes only what was copied, plus directories it leaves empty.
+pub(crate) async fn codex_home_relocate_core(
+    new_path: &str,
+    copy_all: bool,
+    overwrite: bool,
+    remove_old: bool,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    scope_overrides: &ScopeRootOverrides,
+    generations: &Generations,
+) -> Result<CodexHomeRelocation, String> {
+    let old_home =
+        resolve_default_codex_home().ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())?;
+    let new_home = validate_codex_home(new_path)?;
+    let relocated = {
+        let (old_home, new_home) = (old_home.clone(), new_home.clone());
+        tokio::task::spawn_blocking(move || {
+            relocate_files(&old_home, &new_home, copy_all, overwrite)
+        })
+        .await
+        .map_err(|err| format!("Relocation task failed: {err}"))??
+    };
+    bump_file_generations(generations, &home_files(&new_home, &relocated.copied));
+
+    let to = new_home.to_string_lossy().to_string();
+    let mut settings = app_settings.lock().await.clone();
+    settings.codex_home_override = Some(to.clone());
+    if let Err(err) =
+        persist_app_settings_core(settings, app_settings, settings_path, scope_overrides).await
+    {
+        relocated.roll_back(&new_home);
+        bump_file_generations(generations, &home_files(&new_home, &relocated.copied));
+        return Err(err);
+    }
+    let Relocated {
+        copied,
+        bytes,
+        skipped,
+        backup,
+    } = relocated;
+    backup.discard();
+
+    let remove_error = if remove_old {
+        let home = old_home.clone();
+        let files = copied.clone();
+        let result = tokio::task::spawn_blocking(move || remove_copied_checked(&home, &files))
+            .await
+            .unwrap_or_else(|err| Err(format!("Removal task failed: {err}")));
+        bump_file_generations(generations, &home_files(&old_home, &copied));
+        result.err()
+    } else {
+        None
+    };
+    Ok(CodexHomeRelocation {
+        from: old_home.to_string_lossy().to_string(),
+        to,
+        removed_old: remove_old && remove_error.is_none(),
+        copied,
+        bytes,
+        skipped,
+        remove_error,
+    })
+}
+
+/// What `relocate_files` copied, relative to the home, with the bytes copied
+/// and the entries skipped.
+struct Relocated {
+    copied: Vec<String>,
+    bytes: u64,
+    skipped: Vec<String>,
+    backup: Backup,
+}
+
+impl Relocated {
+    /// Removes the copies and puts back what they replaced.
+    fn roll_back(&self, new_home: &Path) {
+        remove_copied(new_home, &self.copied);
+        self.backup.restore(new_home);
+    }
+}
+
+/// Destination files an overwriting relocation replaced, moved aside inside
+/// the destination until the relocation is committed or rolled back.
+struct Backup {
+    dir: PathBuf,
+    files: Vec<String>,
+}
+
+impl Backup {
+    fn new(home: &Path) -> Self {
+        Self {
+            dir: home.join(format!(".codex-monitor-relocate-{}", uuid::Uuid::new_v4())),
+            files: Vec::new(),
+        }
+    }
+
+    /// Moves `relative` aside if it exists in `home`.
+    fn stash(&mut self, home: &Path, relative: &str) -> Result<(), String> {
+        let path = home.join(relative);
+        if !path.exists() {
+            return Ok(());
+        }
+        let backup = self.dir.join(relative);
+        if let Some(parent) = backup.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to back up {relative}: {err}"))?;
+        }
+        std::fs::rename(&path, &backup)
+            .map_err(|err| format!("Failed to back up {relative}: {err}"))?;
+        self.files.push(relative.to_string());
+        Ok(())
+    }
+
+    /// Best effort: moves every stashed file back into `home`.
+    fn restore(&self, home: &Path) {
+        for relative in &self.files {
+            let path = home.join(relative);
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            if let Err(err) = std::fs::rename(self.dir.join(relative), &path) {
+                eprintln!("codex home: failed to restore {}: {err}", path.display());
+            }
+        }
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+
+    fn discard(self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Copies the files into `new_home`, moving aside the ones `overwrite`
+/// replaces. On error `new_home` is left as it was.
+fn relocate_files(
+    old_home: &Path,
+    new_home: &Path,
+    copy_all: bool,
+    overwrite: bool,
+) -> Result<Relocated, String> {
+    let old_canonical = old_home
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve current CODEX_HOME: {err}"))?;
+    std::fs::create_dir_all(new_home)
+        .map_err(|err| format!("Failed to create {}: {err}", new_home.display()))?;
+    let new_canonical = new_home
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {}: {err}", new_home.display()))?;
+    if new_canonical == old_canonical {
+        return Err(format!("{} is already the CODEX_HOME", new_home.display()));
+    }
+    if copy_all && new_canonical.starts_with(&old_canonical) {
+        return Err("Cannot copy CODEX_HOME into a folder inside itself".to_string());
+    }
+    write_probe_within(&new_canonical, "destination")?;
+
+    let (files, skipped) = if copy_all {
+        list_home_files(&old_canonical)
+    } else if old_canonical.join(CONFIG_FILENAME).is_file() {
+        (vec![CONFIG_FILENAME.to_string()], Vec::new())
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    if !overwrite {
+        let conflicts: Vec<&str> = files
+            .iter()
+            .filter(|relative| new_canonical.join(relative).exists())
+            .map(String::as_str)
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(format!(
+                "{} already contains {}; pass overwrite to replace",
+                new_home.display(),
+                conflicts.join(", ")
+            ));
+        }
+    }
+
+    let mut relocated = Relocated {
+        copied: Vec::new(),
+        bytes: 0,
+        skipped,
+        backup: Backup::new(&new_canonical),
+    };
+    for relative in files {
+        if overwrite {
+            if let Err(err) = relocated.backup.stash(&new_canonical, &relative) {
+                relocated.roll_back(&new_canonical);
+                return Err(err);
+            }
+        }
+        let (dir, filename) = split_relative(&relative);
+        let source_root = old_canonical.join(dir);
+        let target_root = new_canonical.join(dir);
+        let result = copy_file_within(
+            FileLocation {
+                root: &source_root,
+                filename,
+                root_context: "current CODEX_HOME",
+                file_context: &relative,
+                allow_external_symlink_target: false,
+            },
+            false,
+            FileLocation {
+                root: &target_root,
+                filename,
+                root_context: "destination",
+                file_context: &relative,
+                allow_external_symlink_target: false,
+            },
+            true,
+        );
+        match result {
+            Ok(size) => {
+                relocated.bytes += size;
+                relocated.copied.push(relative);
+            }
+            Err(err) => {
+                relocated.roll_back(&new_canonical);
+                return Err(err);
+            }
+        }
+    }
+    Ok(relocated)
+}
+
+/// Regular files under `root` relative to it, and the entries skipped.
+fn list_home_files(root: &Path) -> (Vec<String>, Vec<String>) {
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative_dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(root.join(&relative_dir)) else {
+            skipped.push(relative_string(&relative_dir));
+            continue;
+        };
+        for entry in entries.flatten() {
+            let relative = relative_dir.join(entry.file_name());
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => pending.push(relative),
+                Ok(file_type) if file_type.is_file() => files.push(relative_string(&relative)),
+                _ => skipped.push(relative_string(&relative)),
+            }
+        }
+    }
+    files.sort();
+    skipped.sort();
+    (files, skipped)
+}
+
+/// `copied` as the `(root, filename)` pairs file generations are keyed by.
+fn home_files<'a>(home: &'a Path, copied: &'a [String]) -> Vec<(&'a Path, &'a str)> {
+    copied
+        .iter()
+        .map(|relative| (home, relative.as_str()))
+        .collect()
+}
+
+fn relative_string(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn split_relative(relative: &str) -> (&str, &str) {
+    relative.rsplit_once('/').unwrap_or(("", relative))
+}
+
+/// Best effort, for rolling back a failed relocation.
+fn remove_copied(home: &Path, copied: &[String]) {
+    let _ = remove_copied_checked(home, copied);
+}
+
+fn remove_copied_checked(home: &Path, copied: &[String]) -> Result<(), String> {
+    for relative in copied {
+        let path = home.join(relative);
+        std::fs::remove_file(&path)
+            .map_err(|err| format!("Failed to remove {}: {err}", path.display()))?;
+    }
+    let mut dirs: Vec<&str> = copied
+        .iter()
+        .filter_map(|relative| relative.rsplit_once('/').map(|(dir, _)| dir))
+        .collect();
+    // Deepest first, so parents are empty by the time they are tried.
+    dirs.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    dirs.dedup();
+    for dir in dirs {
+        let mut current = Some(dir);
+        while let Some(dir) = current {
+            if std::fs::remove_dir(home.join(dir)).is_err() {
+                break;
+            }
+            current = dir.rsplit_once('/').map(|(parent, _)| parent);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{relocate_files, remove_copied_checked};
+    use std::path::Path;
+    use uuid::Uuid;
+
+    #[test]
+    fn relocation_copies_checks_conflicts_and_removes_old_copies() {
+        let base = std::env::temp_dir().join(format!("codex-monitor-relocate-{}", Uuid::new_v4()));
+        let old_home = base.join("old");
+        let new_home = base.join("new");
+        std::fs::create_dir_all(old_home.join("sessions/2026")).expect("create old home");
+        std::fs::write(old_home.join("config.toml"), "model = \"a\"\n").expect("write config");
+        std::fs::write(old_home.join("sessions/2026/one.jsonl"), "{}\n").expect("write session");
+
+        let relocated = relocate_files(&old_home, &new_home, false, false).expect("config only");
+        assert_eq!(relocated.copied, vec!["config.toml"]);
+        assert_eq!(relocated.bytes, 12);
+        assert!(relocated.skipped.is_empty());
+        assert!(relocated.backup.files.is_empty());
+
+        let conflict = relocate_files(&old_home, &new_home, true, false).unwrap_err();
+        assert!(conflict.contains("config.toml"));
+        assert!(!new_home.join("sessions").exists());
+
+        let relocated = relocate_files(&old_home, &new_home, true, true).expect("whole home");
+        let copied = relocated.copied.clone();
+        assert_eq!(copied, vec!["config.toml", "sessions/2026/one.jsonl"]);
+        assert_eq!(relocated.backup.files, vec!["config.toml"]);
+        relocated.backup.discard();
+        assert_eq!(dir_names(&new_home), vec!["config.toml", "sessions"]);
+        assert_eq!(
+            std::fs::read_to_string(new_home.join("sessions/2026/one.jsonl")).expect("read"),
+            "{}\n"
+        );
+
+        remove_copied_checked(&old_home, &copied).expect("remove old copies");
+        assert!(!old_home.join("config.toml").exists());
+        assert!(!old_home.join("sessions").exists());
+        assert!(old_home.is_dir());
+
+        assert!(relocate_files(&new_home, &new_home, false, true)
+            .unwrap_err()
+            .contains("already the CODEX_HOME"));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    fn dir_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .expect("list dir")
+            .map(|entry| {
+                entry
+                    .expect("entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn rolling_back_an_overwrite_restores_the_replaced_files() {
+        let base = std::env::temp_dir().join(format!("codex-monitor-rollback-{}", Uuid::new_v4()));
+        let old_home = base.join("old");
+        let new_home = base.join("new");
+        std::fs::create_dir_all(&old_home).expect("create old home");
+        std::fs::create_dir_all(&new_home).expect("create new home");
+        std::fs::write(old_home.join("config.toml"), "model = \"a\"\n").expect("write config");
+        std::fs::write(old_home.join("AGENTS.md"), "rules\n").expect("write agents");
+        std::fs::write(new_home.join("config.toml"), "model = \"b\"\n").expect("write config");
+
+        let relocated = relocate_files(&old_home, &new_home, true, true).expect("relocate");
+        assert_eq!(
+            std::fs::read_to_string(new_home.join("config.toml")).expect("read"),
+            "model = \"a\"\n"
+        );
+        relocated.roll_back(&new_home);
+
+        assert_eq!(dir_names(&new_home), vec!["config.toml"]);
+        assert_eq!(
+            std::fs::read_to_string(new_home.join("config.toml")).expect("read"),
+            "model = \"b\"\n"
+        );
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::codex::config::{self as codex_config, CodexConfigValues};
+use crate::codex::home::resolve_default_codex_home;
+use crate::files::policy::{policy_for, FileKind, FileScope, ScopeRootOverrides};
+use crate::shared::settings_core::codex_config_values_from_settings;
+use crate::types::AppSettings;
+
+/// An app-managed `config.toml` setting whose value on disk no longer matches
+/// the app's. Both values are effective ones, so an unset key reads as Codex's
+/// default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DriftEntry {
+    pub(crate) key: String,
+    pub(crate) app_value: Value,
+    pub(crate) disk_value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ConfigFingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// What the last `config_drift_check` compared and found.
+pub(crate) struct DriftCheck {
+    fingerprint: Option<ConfigFingerprint>,
+    app_values: CodexConfigValues,
+    entries: Vec<DriftEntry>,
+}
+
+pub(crate) type ConfigDriftCache = Mutex<Option<DriftCheck>>;
+
+/// `None` when the file is missing.
+fn config_fingerprint(path: &Path) -> Option<ConfigFingerprint> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(ConfigFingerprint {
+        len: metadata.len(),
+        modified: metadata.modified().ok(),
+    })
+}
+
+/// Compares the feature flags and personality held in app settings against a
+/// fresh read of `config.toml`. The file is only re-read and diffed when its
+/// size or mtime changed, or the app's values did, since the last check.
+pub(crate) async fn config_drift_check_core(
+    app_settings: &Mutex<AppSettings>,
+    scope_overrides: &ScopeRootOverrides,
+    cache: &ConfigDriftCache,
+) -> Result<Vec<DriftEntry>, String> {
+    let app_values = codex_config_values_from_settings(&*app_settings.lock().await);
+    let Some(codex_home) = resolve_default_codex_home() else {
+        return Ok(Vec::new());
+    };
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let fingerprint = config_fingerprint(&codex_home.join(policy.filename));
+
+    let mut cache = cache.lock().await;
+    if let Some(last) = cache.as_ref() {
+        if last.fingerprint == fingerprint && last.app_values == app_values {
+            return Ok(last.entries.clone());
+        }
+    }
+    let disk_values = codex_config::read_config_values(scope_overrides)?;
+    let entries = drift_entries(&app_values, &disk_values);
+    *cache = Some(DriftCheck {
+        fingerprint,
+        app_values,
+        entries: entries.clone(),
+    });
+    Ok(entries)
+}
+
+fn drift_entries(app: &CodexConfigValues, disk: &CodexConfigValues) -> Vec<DriftEntry> {
+    let disk = codex_config::managed_config_settings(disk);
+    codex_config::managed_config_settings(app)
+        .into_iter()
+        .zip(disk)
+        .filter(|((_, app_value), (_, disk_value))| app_value != disk_value)
+        .map(|((key, app_value), (_, disk_value))| DriftEntry {
+            key,
+            app_value,
+            disk_value,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::drift_entries;
+    use crate::codex::config::CodexConfigValues;
+    use serde_json::json;
+
+    #[test]
+    fn drift_compares_effective_values() {
+        let app = CodexConfigValues {
+            collaboration_modes_enabled: Some(true),
+            steer_enabled: Some(true),
+            unified_exec_enabled: Some(false),
+            apps_enabled: Some(false),
+            personality: Some("friendly".to_string()),
+        };
+        // Unset keys on disk read as Codex's defaults.
+        let disk = CodexConfigValues {
+            steer_enabled: Some(false),
+            personality: Some("Pragmatic".to_string()),
+            ..CodexConfigValues::default()
+        };
+
+        let entries = drift_entries(&app, &disk);
+        let keys: Vec<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec!["features.steer", "features.unified_exec", "personality"]
+        );
+        assert_eq!(entries[0].app_value, json!(true));
+        assert_eq!(entries[0].disk_value, json!(false));
+        assert_eq!(entries[2].disk_value, json!("pragmatic"));
+        assert!(drift_entries(&app, &app).is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use toml_edit::Document;
+
+use crate::files::ops::read_with_policy;
+use crate::files::policy::{policy_for, FileKind, FileScope};
+use crate::shared::config_lock_core;
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::config_toml_core;
+
+/// Fix passes before giving up on a file that keeps reporting duplicates.
+const MAX_FIX_PASSES: usize = 64;
+
+/// A key defined more than once in `config.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DuplicateKey {
+    pub(crate) key: String,
+    /// Dotted table holding the key; `None` for the document root.
+    pub(crate) table: Option<String>,
+    /// 1-based line of each definition in file order; empty when none could be
+    /// located.
+    pub(crate) lines: Vec<usize>,
+    /// Every definition but the last is a one-line `key = value`, so keeping
+    /// the last is a matter of deleting lines.
+    pub(crate) fixable: bool,
+}
+
+/// Why `config.toml` failed to parse; `duplicate` is set when a repeated key
+/// is the cause.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigParseError {
+    pub(crate) message: String,
+    pub(crate) duplicate: Option<DuplicateKey>,
+}
+
+/// `parse_document`, but a duplicate-key failure says which key and where.
+pub(crate) fn parse_document_tolerant(contents: &str) -> Result<Document, ConfigParseError> {
+    config_toml_core::parse_document(contents).map_err(|message| ConfigParseError {
+        duplicate: duplicate_key_in(&message)
+            .map(|(key, table)| locate_duplicate(contents, key, table)),
+        message,
+    })
+}
+
+/// Deletes every definition of a duplicated key but the last, until the text
+/// parses. Returns the fixed text and the number of lines removed.
+pub(crate) fn fix_duplicate_keys(contents: &str) -> Result<(String, usize), ConfigParseError> {
+    let mut text = contents.to_string();
+    let mut removed = 0;
+    for _ in 0..MAX_FIX_PASSES {
+        let error = match parse_document_tolerant(&text) {
+            Ok(_) => return Ok((text, removed)),
+            Err(error) => error,
+        };
+        let Some(duplicate) = error
+            .duplicate
+            .as_ref()
+            .filter(|duplicate| duplicate.fixable)
+        else {
+            return Err(error);
+        };
+        let earlier: HashSet<usize> = duplicate.lines[..duplicate.lines.len() - 1]
+            .iter()
+            .copied()
+            .collect();
+        text = text
+            .split_inclusive('\n')
+            .enumerate()
+            .filter(|(index, _)| !earlier.contains(&(index + 1)))
+            .map(|(_, line)| line)
+            .collect();
+        removed += earlier.len();
+    }
+    Err(ConfigParseError {
+        message: "config.toml still has duplicate keys after repeated fixes".to_string(),
+        duplicate: None,
+    })
+}
+
+/// Reports why `config.toml` under `codex_home` does not parse; `None` when it
+/// parses or is missing.
+pub(crate) fn config_load_tolerant_core(
+    codex_home: &Path,
+) -> Result<Option<ConfigParseError>, String> {
+    let contents = read_config_text(codex_home)?;
+    Ok(parse_document_tolerant(&contents).err())
+}
+
+/// Applies `fix_duplicate_keys` to `config.toml`, recording the rewrite for
+/// `config_undo`. Returns the number of lines removed.
+pub(crate) fn config_fix_duplicate_keys_core(
+    codex_home: &Path,
+    writer: ConfigWriter<'_>,
+) -> Result<usize, String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let contents = read_config_text(codex_home)?;
+    let (fixed, removed) = fix_duplicate_keys(&contents).map_err(|error| error.message)?;
+    if removed > 0 {
+        config_toml_core::write_global_config_text(codex_home, &fixed, writer)?;
+    }
+    Ok(removed)
+}
+
+fn read_config_text(codex_home: &Path) -> Result<String, String> {
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    Ok(read_with_policy(&codex_home.to_path_buf(), policy)?.content)
+}
+
+/// The key and table named by a toml_edit duplicate-key error, e.g.
+/// "duplicate key `web` in table `features`".
+fn duplicate_key_in(message: &str) -> Option<(String, Option<String>)> {
+    let (_, rest) = message.split_once("duplicate key `")?;
+    let (key, rest) = rest.split_once('`')?;
+    let table = rest
+        .trim_start()
+        .strip_prefix("in table `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(table, _)| table.to_string());
+    Some((key.to_string(), table))
+}
+
+fn locate_duplicate(contents: &str, key: String, table: Option<String>) -> DuplicateKey {
+    let header_for_key = match &table {
+        Some(table) => format!("{table}.{key}"),
+        None => key.clone(),
+    };
+    let mut current: Option<String> = None;
+    let mut lines = Vec::new();
+    let mut headers = false;
+    for (index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            let path = header_path(trimmed);
+            if path == header_for_key {
+                lines.push(index + 1);
+                headers = true;
+            } else if trimmed.starts_with("[[") && Some(&path) == table.as_ref() {
+                // Each array-of-tables entry has its own keys.
+                lines.clear();
+            }
+            current = Some(path);
+            continue;
+        }
+        if current == table && line_key(trimmed).as_deref() == Some(key.as_str()) {
+            lines.push(index + 1);
+        }
+    }
+    let source: Vec<&str> = contents.lines().collect();
+    let fixable = !headers
+        && lines.len() >= 2
+        && lines[..lines.len() - 1]
+            .iter()
+            .all(|line| config_toml_core::parse_document(source[line - 1]).is_ok());
+    DuplicateKey {
+        key,
+        table,
+        lines,
+        fixable,
+    }
+}
+
+/// `[a."b c"]` and `[[a.b]]` as dotted paths, unquoted.
+fn header_path(header: &str) -> String {
+    let inner = header.trim_start_matches('[');
+    let inner = inner.split(']').next().unwrap_or_default();
+    inner.split('.').map(unquote).collect::<Vec<_>>().join(".")
+}
+
+fn line_key(line: &str) -> Option<String> {
+    if line.starts_with('#') {
+        return None;
+    }
+    let (key, _) = line.split_once('=')?;
+    Some(unquote(key).to_string())
+}
+
+fn unquote(segment: &str) -> &str {
+    let segment = segment.trim();
+    segment
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .or_else(|| {
+            segment
+                .strip_prefix('\'')
+                .and_then(|rest| rest.strip_suffix('\''))
+        })
+        .unwrap_or(segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fix_duplicate_keys, parse_document_tolerant};
+
+    #[test]
+    fn duplicate_keys_are_located_and_fixed_keeping_the_last() {
+        let contents = "model = \"a\"\n[features]\nweb = true\nsteer = false\nweb = false\n";
+        let error = parse_document_tolerant(contents).expect_err("duplicate key");
+        let duplicate = error.duplicate.expect("structured duplicate");
+        assert_eq!(duplicate.key, "web");
+        assert_eq!(duplicate.table.as_deref(), Some("features"));
+        assert_eq!(duplicate.lines, vec![3, 5]);
+        assert!(duplicate.fixable);
+
+        let (fixed, removed) = fix_duplicate_keys(contents).expect("fixable");
+        assert_eq!(removed, 1);
+        assert_eq!(
+            fixed,
+            "model = \"a\"\n[features]\nsteer = false\nweb = false\n"
+        );
+
+        let root = "model = \"a\"\nmodel = \"b\"\nmodel = \"c\"\n";
+        let (fixed, removed) = fix_duplicate_keys(root).expect("fixable");
+        assert_eq!((fixed.as_str(), removed), ("model = \"c\"\n", 2));
+    }
+
+    #[test]
+    fn multiline_and_table_duplicates_are_reported_but_not_fixed() {
+        let multiline = "args = [\n  \"a\",\n]\nargs = []\n";
+        let duplicate = parse_document_tolerant(multiline)
+            .expect_err("duplicate key")
+            .duplicate
+            .expect("structured duplicate");
+        assert_eq!(duplicate.lines, vec![1, 4]);
+        assert!(!duplicate.fixable);
+        assert!(fix_duplicate_keys(multiline).is_err());
+
+        let tables = "[features]\nweb = true\n[features]\nsteer = true\n";
+        let duplicate = parse_document_tolerant(tables)
+            .expect_err("duplicate table")
+            .duplicate
+            .expect("structured duplicate");
+        assert_eq!(duplicate.key, "features");
+        assert_eq!(duplicate.lines, vec![1, 3]);
+        assert!(!duplicate.fixable);
+
+        let invalid = parse_document_tolerant("model = \n").expect_err("invalid");
+        assert!(invalid.duplicate.is_none());
+    }
+}
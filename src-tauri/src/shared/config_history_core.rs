@@ -0,0 +1,306 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::files::ops::read_with_policy;
+use crate::files::policy::{policy_for, FileKind, FileScope};
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::{config_lock_core, config_toml_core};
+
+const MAX_HISTORY_ENTRIES: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HistoryEntry {
+    pub(crate) recorded_at_ms: i64,
+    pub(crate) summary: String,
+    pub(crate) undone: bool,
+}
+
+#[derive(Clone, Debug)]
+struct ConfigChange {
+    recorded_at_ms: i64,
+    summary: String,
+    before: Option<String>,
+    after: String,
+}
+
+#[derive(Default)]
+struct ConfigHistory {
+    undo: VecDeque<ConfigChange>,
+    redo: Vec<ConfigChange>,
+}
+
+/// In-memory undo/redo stacks keyed by `CODEX_HOME`.
+static CONFIG_HISTORY: Mutex<Option<HashMap<PathBuf, ConfigHistory>>> = Mutex::new(None);
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn with_history<T>(codex_home: &Path, f: impl FnOnce(&mut ConfigHistory) -> T) -> T {
+    let mut guard = CONFIG_HISTORY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let histories = guard.get_or_insert_with(HashMap::new);
+    f(histories.entry(codex_home.to_path_buf()).or_default())
+}
+
+/// Records a `config.toml` write so it can be undone. A new write clears the redo stack.
+pub(crate) fn record_config_write(codex_home: &Path, before: Option<String>, after: &str) {
+    if before.as_deref() == Some(after) {
+        return;
+    }
+    let change = ConfigChange {
+        recorded_at_ms: now_ms(),
+        summary: summarize_change(before.as_deref(), after),
+        before,
+        after: after.to_string(),
+    };
+    with_history(codex_home, |history| {
+        history.redo.clear();
+        history.undo.push_back(change);
+        while history.undo.len() > MAX_HISTORY_ENTRIES {
+            history.undo.pop_front();
+        }
+    });
+}
+
+/// Fails unless `config.toml` still holds `expected` (`None` for a missing
+/// file), so an edit made outside the app since the recorded write survives.
+fn ensure_unchanged(codex_home: &Path, expected: Option<&str>, action: &str) -> Result<(), String> {
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let current = read_with_policy(&codex_home.to_path_buf(), policy)?;
+    if current.exists.then_some(current.content.as_str()) == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "config.toml was changed outside the app since this write; refusing to {action} it"
+        ))
+    }
+}
+
+/// Restores the state before the latest recorded write. Returns `false` when
+/// there is nothing to undo; fails, keeping the entry, when `config.toml` no
+/// longer holds what that write left.
+pub(crate) fn config_undo_core(
+    codex_home: &Path,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let Some(change) = with_history(codex_home, |history| history.undo.pop_back()) else {
+        return Ok(false);
+    };
+    let restored = config_lock_core::lock_config_for_write(codex_home).and_then(|_lock| {
+        ensure_unchanged(codex_home, Some(&change.after), "undo")?;
+        match change.before.as_deref() {
+            Some(before) => {
+                config_toml_core::write_global_config_text_untracked(codex_home, before, writer)
+            }
+            None => config_toml_core::remove_global_config_untracked(codex_home, writer),
+        }
+    });
+    if let Err(err) = restored {
+        with_history(codex_home, |history| history.undo.push_back(change));
+        return Err(err);
+    }
+    with_history(codex_home, |history| history.redo.push(change));
+    Ok(true)
+}
+
+/// Re-applies the most recently undone write. Returns `false` when there is
+/// nothing to redo; fails, keeping the entry, when `config.toml` changed since
+/// the undo.
+pub(crate) fn config_redo_core(
+    codex_home: &Path,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let Some(change) = with_history(codex_home, |history| history.redo.pop()) else {
+        return Ok(false);
+    };
+    let reapplied = config_lock_core::lock_config_for_write(codex_home).and_then(|_lock| {
+        ensure_unchanged(codex_home, change.before.as_deref(), "redo")?;
+        config_toml_core::write_global_config_text_untracked(codex_home, &change.after, writer)
+    });
+    if let Err(err) = reapplied {
+        with_history(codex_home, |history| history.redo.push(change));
+        return Err(err);
+    }
+    with_history(codex_home, |history| history.undo.push_back(change));
+    Ok(true)
+}
+
+/// Lists recorded changes oldest first; undone entries are still redoable.
+pub(crate) fn config_history_core(codex_home: &Path) -> Vec<HistoryEntry> {
+    with_history(codex_home, |history| {
+        let applied = history.undo.iter().map(|change| HistoryEntry {
+            recorded_at_ms: change.recorded_at_ms,
+            summary: change.summary.clone(),
+            undone: false,
+        });
+        let undone = history.redo.iter().rev().map(|change| HistoryEntry {
+            recorded_at_ms: change.recorded_at_ms,
+            summary: change.summary.clone(),
+            undone: true,
+        });
+        applied.chain(undone).collect()
+    })
+}
+
+fn summarize_change(before: Option<&str>, after: &str) -> String {
+    let Some(before) = before else {
+        return "Created config.toml".to_string();
+    };
+    let (Ok(before_doc), Ok(after_doc)) = (
+        config_toml_core::parse_document(before),
+        config_toml_core::parse_document(after),
+    ) else {
+        return "Edited config.toml".to_string();
+    };
+
+    let mut keys: Vec<&str> = before_doc
+        .iter()
+        .map(|(key, _)| key)
+        .chain(after_doc.iter().map(|(key, _)| key))
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    let changed: Vec<&str> = keys
+        .into_iter()
+        .filter(|key| {
+            let before_item = before_doc.get(key).map(|item| item.to_string());
+            let after_item = after_doc.get(key).map(|item| item.to_string());
+            before_item != after_item
+        })
+        .collect();
+    if changed.is_empty() {
+        "Reformatted config.toml".to_string()
+    } else {
+        format!("Changed {}", changed.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        config_history_core, config_redo_core, config_undo_core, record_config_write,
+        summarize_change,
+    };
+    use crate::shared::config_patch_core::ConfigWriter;
+    use crate::shared::config_toml_core;
+    use uuid::Uuid;
+
+    #[test]
+    fn summary_lists_changed_top_level_keys() {
+        let summary = summarize_change(
+            Some("model = \"a\"\n[features]\nsteer = true\n"),
+            "model = \"b\"\n[features]\nsteer = true\n",
+        );
+        assert_eq!(summary, "Changed model");
+        assert_eq!(
+            summarize_change(None, "model = \"a\"\n"),
+            "Created config.toml"
+        );
+    }
+
+    #[test]
+    fn undo_and_redo_restore_recorded_states() {
+        let codex_home =
+            std::env::temp_dir().join(format!("codex-monitor-config-history-{}", Uuid::new_v4()));
+
+        config_toml_core::write_global_config_text(
+            &codex_home,
+            "model = \"a\"\n",
+            ConfigWriter::silent("test"),
+        )
+        .expect("first write");
+        config_toml_core::write_global_config_text(
+            &codex_home,
+            "model = \"b\"\n",
+            ConfigWriter::silent("test"),
+        )
+        .expect("second write");
+        assert_eq!(config_history_core(&codex_home).len(), 2);
+
+        assert!(config_undo_core(&codex_home, ConfigWriter::silent("test")).expect("undo"));
+        let (_, document) =
+            config_toml_core::load_global_config_document(&codex_home).expect("load");
+        assert_eq!(
+            config_toml_core::read_top_level_string(&document, "model"),
+            Some("a".to_string())
+        );
+
+        assert!(config_redo_core(&codex_home, ConfigWriter::silent("test")).expect("redo"));
+        let (_, document) =
+            config_toml_core::load_global_config_document(&codex_home).expect("load");
+        assert_eq!(
+            config_toml_core::read_top_level_string(&document, "model"),
+            Some("b".to_string())
+        );
+
+        assert!(config_undo_core(&codex_home, ConfigWriter::silent("test")).expect("undo"));
+        assert!(
+            config_undo_core(&codex_home, ConfigWriter::silent("test")).expect("undo the create")
+        );
+        assert!(!codex_home.join("config.toml").exists());
+        assert!(!config_undo_core(&codex_home, ConfigWriter::silent("test"))
+            .expect("undo past beginning"));
+        assert!(
+            config_redo_core(&codex_home, ConfigWriter::silent("test")).expect("redo the create")
+        );
+        assert!(codex_home.join("config.toml").exists());
+        assert!(config_undo_core(&codex_home, ConfigWriter::silent("test"))
+            .expect("undo the create again"));
+
+        record_config_write(&codex_home, Some("x".to_string()), "x");
+        let history = config_history_core(&codex_home);
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|entry| entry.undone));
+
+        let _ = std::fs::remove_dir_all(&codex_home);
+    }
+
+    #[test]
+    fn undo_and_redo_refuse_to_overwrite_external_edits() {
+        let codex_home = std::env::temp_dir().join(format!(
+            "codex-monitor-config-history-external-{}",
+            Uuid::new_v4()
+        ));
+        let config_path = codex_home.join("config.toml");
+        for contents in ["model = \"a\"\n", "model = \"b\"\n"] {
+            config_toml_core::write_global_config_text(
+                &codex_home,
+                contents,
+                ConfigWriter::silent("test"),
+            )
+            .expect("write");
+        }
+
+        std::fs::write(&config_path, "model = \"external\"\n").expect("external edit");
+        let error = config_undo_core(&codex_home, ConfigWriter::silent("test"))
+            .expect_err("undo over an external edit");
+        assert!(error.contains("changed outside the app"), "{error}");
+        assert_eq!(
+            std::fs::read_to_string(&config_path).expect("read"),
+            "model = \"external\"\n"
+        );
+        let history = config_history_core(&codex_home);
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|entry| !entry.undone));
+
+        std::fs::write(&config_path, "model = \"b\"\n").expect("put back");
+        assert!(config_undo_core(&codex_home, ConfigWriter::silent("test")).expect("undo"));
+        std::fs::write(&config_path, "model = \"external\"\n").expect("external edit");
+        assert!(config_redo_core(&codex_home, ConfigWriter::silent("test")).is_err());
+        assert!(config_history_core(&codex_home)
+            .last()
+            .is_some_and(|entry| entry.undone));
+
+        let _ = std::fs::remove_dir_all(&codex_home);
+    }
+}
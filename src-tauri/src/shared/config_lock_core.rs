@@ -0,0 +1,197 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+/// Advisory lock for `config.toml`, next to it in CODEX_HOME. A writer holds
+/// an exclusive `flock` on this file from reading `config.toml` until its
+/// change is written back; other tools (the Codex CLI included) can cooperate
+/// by taking the same lock. The file is never removed, so its mere presence
+/// does not mean it is held.
+pub(crate) const CONFIG_LOCK_FILENAME: &str = "config.toml.lock";
+
+/// Prefix of the error returned when another process holds the lock.
+pub(crate) const CONFIG_LOCKED: &str = "CONFIG_LOCKED";
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(25);
+
+thread_local! {
+    /// Lock files this thread holds, with how many guards share each. Config
+    /// writes run synchronously, so a write nested in a locked
+    /// read-modify-write is always on the thread that took the lock.
+    static HELD: RefCell<HashMap<PathBuf, usize>> = RefCell::new(HashMap::new());
+}
+
+/// Held while `config.toml` is read, changed and written; dropping the last
+/// guard for a CODEX_HOME releases the lock, and the OS releases it if the
+/// process dies first.
+pub(crate) struct ConfigWriteLock {
+    path: PathBuf,
+    /// `None` for a guard nested in one this thread already holds.
+    file: Option<File>,
+}
+
+impl Drop for ConfigWriteLock {
+    fn drop(&mut self) {
+        HELD.with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(depth) = held.get_mut(&self.path) {
+                *depth -= 1;
+                if *depth == 0 {
+                    held.remove(&self.path);
+                }
+            }
+        });
+        if let Some(file) = &self.file {
+            unlock_file(file);
+        }
+    }
+}
+
+/// Waits up to two seconds for the `config.toml` lock under `codex_home`.
+/// Taking it again on the same thread while it is held shares the held lock,
+/// so a read-modify-write can lock around the writes it is built from.
+pub(crate) fn lock_config_for_write(codex_home: &Path) -> Result<ConfigWriteLock, String> {
+    lock_with_timeout(&codex_home.join(CONFIG_LOCK_FILENAME), LOCK_TIMEOUT)
+}
+
+fn lock_with_timeout(path: &Path, timeout: Duration) -> Result<ConfigWriteLock, String> {
+    let path = canonical_lock_path(path)?;
+    let nested = HELD.with(|held| {
+        held.borrow_mut()
+            .get_mut(&path)
+            .map(|depth| *depth += 1)
+            .is_some()
+    });
+    if nested {
+        return Ok(ConfigWriteLock { path, file: None });
+    }
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+    wait_for_lock(&file, &path, timeout)?;
+    HELD.with(|held| held.borrow_mut().insert(path.clone(), 1));
+    Ok(ConfigWriteLock {
+        path,
+        file: Some(file),
+    })
+}
+
+/// The lock path with its directory created and resolved, so every spelling
+/// of a CODEX_HOME maps to the same held-lock entry.
+fn canonical_lock_path(path: &Path) -> Result<PathBuf, String> {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(format!("Invalid lock path {}", path.display()));
+    };
+    std::fs::create_dir_all(parent)
+        .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    let parent = parent
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {}: {err}", parent.display()))?;
+    Ok(parent.join(name))
+}
+
+/// Retries until `timeout`. Config writes are synchronous but called from
+/// async commands, so on a multi-threaded runtime the wait runs in
+/// `block_in_place` and the worker's other tasks move to another thread
+/// instead of stalling behind it.
+fn wait_for_lock(file: &File, path: &Path, timeout: Duration) -> Result<(), String> {
+    let locked = |result: std::io::Result<bool>| {
+        result.map_err(|err| format!("Failed to lock {}: {err}", path.display()))
+    };
+    if locked(try_lock_file(file))? {
+        return Ok(());
+    }
+    let wait = || {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            std::thread::sleep(LOCK_RETRY_INTERVAL);
+            if locked(try_lock_file(file))? {
+                return Ok(());
+            }
+        }
+        Err(format!(
+            "{CONFIG_LOCKED}: another process is writing config.toml ({} is held)",
+            path.display()
+        ))
+    };
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(wait)
+        }
+        _ => wait(),
+    }
+}
+
+#[cfg(unix)]
+fn try_lock_file(file: &File) -> std::io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+        Ok(false)
+    } else {
+        Err(err)
+    }
+}
+
+#[cfg(unix)]
+fn unlock_file(file: &File) {
+    use std::os::unix::io::AsRawFd;
+
+    unsafe {
+        libc::flock(file.as_raw_fd(), libc::LOCK_UN);
+    }
+}
+
+/// No cross-process lock off unix; writes go ahead as before.
+#[cfg(not(unix))]
+fn try_lock_file(_file: &File) -> std::io::Result<bool> {
+    Ok(true)
+}
+
+#[cfg(not(unix))]
+fn unlock_file(_file: &File) {}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{lock_with_timeout, CONFIG_LOCKED, CONFIG_LOCK_FILENAME};
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    #[test]
+    fn other_writers_time_out_until_the_outer_lock_is_dropped() {
+        let home = std::env::temp_dir().join(format!("codex-monitor-lock-{}", Uuid::new_v4()));
+        let path = home.join(CONFIG_LOCK_FILENAME);
+        let lock_elsewhere = |timeout| {
+            let path = path.clone();
+            std::thread::spawn(move || lock_with_timeout(&path, timeout).map(drop))
+                .join()
+                .expect("lock thread")
+        };
+
+        let held = lock_with_timeout(&path, Duration::ZERO).expect("first lock");
+        let nested = lock_with_timeout(&path, Duration::ZERO).expect("nested lock");
+        drop(nested);
+        let err = lock_elsewhere(Duration::from_millis(60))
+            .err()
+            .expect("second writer fails");
+        assert!(err.starts_with(CONFIG_LOCKED));
+
+        drop(held);
+        lock_elsewhere(Duration::ZERO).expect("lock after release");
+        assert!(path.exists());
+        let _ = std::fs::remove_dir_all(&home);
+    }
+}
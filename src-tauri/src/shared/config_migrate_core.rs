@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use toml_edit::{Document, Item};
+
+use crate::shared::config_lock_core;
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::config_toml_core;
+
+/// Feature keys that Codex renamed; the old key is migrated to the new one.
+const RENAMED_FEATURES: &[(&str, &str)] = &[("collab", "multi_agent")];
+
+/// One change `config_migrate` makes. `to_key`/`new` are `None` when the old
+/// key is dropped because the new key is already set.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MigrationStep {
+    pub(crate) from_key: String,
+    pub(crate) to_key: Option<String>,
+    pub(crate) old: String,
+    pub(crate) new: Option<String>,
+}
+
+/// Computes the migration steps for `config.toml` without writing anything.
+pub(crate) fn config_migrate_preview_core(codex_home: &Path) -> Result<Vec<MigrationStep>, String> {
+    let (_, document) = config_toml_core::load_global_config_document(codex_home)?;
+    Ok(plan_migration(&document))
+}
+
+/// Applies the steps `config_migrate_preview_core` reports and returns them.
+pub(crate) fn config_migrate_core(
+    codex_home: &Path,
+    writer: ConfigWriter<'_>,
+) -> Result<Vec<MigrationStep>, String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let (_, mut document) = config_toml_core::load_global_config_document(codex_home)?;
+    let steps = plan_migration(&document);
+    if steps.is_empty() {
+        return Ok(steps);
+    }
+    apply_migration(&mut document, &steps)?;
+    config_toml_core::persist_global_config_document(codex_home, &document, writer)?;
+    Ok(steps)
+}
+
+pub(crate) fn plan_migration(document: &Document) -> Vec<MigrationStep> {
+    let Some(features) = document.get("features").and_then(Item::as_table_like) else {
+        return Vec::new();
+    };
+    RENAMED_FEATURES
+        .iter()
+        .filter_map(|(old_key, new_key)| {
+            let value = features.get(old_key)?.as_value()?;
+            let rendered = value.to_string();
+            let rendered = rendered.trim();
+            let keep_new = features.get(new_key).is_some();
+            Some(MigrationStep {
+                from_key: format!("features.{old_key}"),
+                to_key: (!keep_new).then(|| format!("features.{new_key}")),
+                old: format!("{old_key} = {rendered}"),
+                new: (!keep_new).then(|| format!("{new_key} = {rendered}")),
+            })
+        })
+        .collect()
+}
+
+fn apply_migration(document: &mut Document, steps: &[MigrationStep]) -> Result<(), String> {
+    let features = document
+        .get_mut("features")
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| "`features` must be a table in config.toml".to_string())?;
+    for step in steps {
+        let old_key = feature_key(&step.from_key)?;
+        let mut item = features
+            .remove(old_key)
+            .ok_or_else(|| format!("`{}` is no longer set in config.toml", step.from_key))?;
+        if let Some(to_key) = step.to_key.as_deref() {
+            if let Some(value) = item.as_value_mut() {
+                value.decor_mut().clear();
+            }
+            features.insert(feature_key(to_key)?, item);
+        }
+    }
+    Ok(())
+}
+
+fn feature_key(path: &str) -> Result<&str, String> {
+    path.strip_prefix("features.")
+        .ok_or_else(|| format!("Unsupported migration key `{path}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_migration, plan_migration};
+    use crate::shared::config_toml_core::parse_document;
+
+    #[test]
+    fn plan_renames_collab_to_multi_agent() {
+        let document = parse_document("[features]\ncollab = true\n").expect("parse");
+        let steps = plan_migration(&document);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].from_key, "features.collab");
+        assert_eq!(steps[0].to_key.as_deref(), Some("features.multi_agent"));
+        assert_eq!(steps[0].old, "collab = true");
+        assert_eq!(steps[0].new.as_deref(), Some("multi_agent = true"));
+    }
+
+    #[test]
+    fn plan_drops_collab_when_multi_agent_is_set() {
+        let document =
+            parse_document("[features]\ncollab = true\nmulti_agent = false\n").expect("parse");
+        let steps = plan_migration(&document);
+        assert_eq!(steps.len(), 1);
+        assert!(steps[0].to_key.is_none());
+        assert!(steps[0].new.is_none());
+    }
+
+    #[test]
+    fn apply_performs_exactly_the_planned_steps() {
+        let mut document =
+            parse_document("model = \"gpt-5\"\n[features]\ncollab = false\nsteer = true\n")
+                .expect("parse");
+        let steps = plan_migration(&document);
+        apply_migration(&mut document, &steps).expect("apply");
+        assert!(document["features"].get("collab").is_none());
+        assert_eq!(document["features"]["multi_agent"].as_bool(), Some(false));
+        assert_eq!(document["features"]["steer"].as_bool(), Some(true));
+        assert!(plan_migration(&document).is_empty());
+    }
+
+    #[test]
+    fn plan_is_empty_without_features() {
+        let document = parse_document("model = \"gpt-5\"\n").expect("parse");
+        assert!(plan_migration(&document).is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+use std::path::Path;
+
+use serde_json::{Map, Value as JsonValue};
+use toml_edit::{Item, Table, Value};
+
+use crate::backend::events::{ConfigKeyChange, ConfigPatch, EventSink};
+use crate::files::generation::Generations;
+use crate::shared::config_toml_core;
+
+/// Where `config-patch` events go. Every `EventSink` is one.
+pub(crate) trait ConfigPatchSink: Send + Sync {
+    fn emit_patch(&self, patch: ConfigPatch);
+}
+
+impl<E: EventSink> ConfigPatchSink for E {
+    fn emit_patch(&self, patch: ConfigPatch) {
+        self.emit_config_patch(patch);
+    }
+}
+
+/// The command behind a `config.toml` write, the sink its `config-patch` goes
+/// to and the app's file generations it bumps; passed down to every function
+/// that writes the file.
+#[derive(Clone, Copy)]
+pub(crate) struct ConfigWriter<'a> {
+    origin: &'static str,
+    sink: Option<&'a dyn ConfigPatchSink>,
+    generations: &'a Generations,
+}
+
+impl<'a> ConfigWriter<'a> {
+    pub(crate) fn new(
+        origin: &'static str,
+        sink: &'a dyn ConfigPatchSink,
+        generations: &'a Generations,
+    ) -> Self {
+        Self {
+            origin,
+            sink: Some(sink),
+            generations,
+        }
+    }
+
+    /// A writer whose patches go nowhere and whose generations nothing reads.
+    #[cfg(test)]
+    pub(crate) fn silent(origin: &'static str) -> Self {
+        static GENERATIONS: Generations = Generations::new();
+        Self {
+            origin,
+            sink: None,
+            generations: &GENERATIONS,
+        }
+    }
+
+    pub(crate) fn generations(&self) -> &'a Generations {
+        self.generations
+    }
+
+    /// Emits the semantic difference between two `config.toml` texts, with
+    /// secret values redacted. Writes that only touch formatting or comments,
+    /// and texts that fail to parse, emit nothing.
+    pub(crate) fn publish(&self, codex_home: &Path, before: Option<&str>, after: &str) {
+        let Some(sink) = self.sink else {
+            return;
+        };
+        let Some(changes) = config_diff(before.unwrap_or_default(), after) else {
+            return;
+        };
+        if changes.is_empty() {
+            return;
+        }
+        sink.emit_patch(ConfigPatch {
+            origin: self.origin.to_string(),
+            codex_home: codex_home.to_string_lossy().to_string(),
+            changes,
+        });
+    }
+}
+
+/// Key-by-key changes from `before` to `after`, recursing into tables present on
+/// both sides. Values under a key `config_export_redacted` would hide are
+/// replaced by the same placeholder. Returns `None` when either side is not
+/// valid TOML.
+pub(crate) fn config_diff(before: &str, after: &str) -> Option<Vec<ConfigKeyChange>> {
+    let before = config_toml_core::parse_document(before).ok()?;
+    let after = config_toml_core::parse_document(after).ok()?;
+    let before = table_to_json(before.as_table());
+    let after = table_to_json(after.as_table());
+    let mut changes = Vec::new();
+    diff_json(&mut Vec::new(), Some(&before), Some(&after), &mut changes);
+    Some(changes)
+}
+
+fn diff_json(
+    path: &mut Vec<String>,
+    old: Option<&JsonValue>,
+    new: Option<&JsonValue>,
+    changes: &mut Vec<ConfigKeyChange>,
+) {
+    if let (Some(JsonValue::Object(old_map)), Some(JsonValue::Object(new_map))) = (old, new) {
+        let mut keys: Vec<&String> = old_map.keys().collect();
+        keys.extend(new_map.keys().filter(|key| !old_map.contains_key(*key)));
+        for key in keys {
+            path.push(key.clone());
+            diff_json(path, old_map.get(key), new_map.get(key), changes);
+            path.pop();
+        }
+        return;
+    }
+    if old == new {
+        return;
+    }
+    let secret = path
+        .iter()
+        .any(|segment| config_toml_core::is_secret_key(segment, &[]));
+    changes.push(ConfigKeyChange {
+        path: key_path(path),
+        old: old.map(|value| redact_json(value, secret)),
+        new: new.map(|value| redact_json(value, secret)),
+    });
+}
+
+fn redact_json(value: &JsonValue, redact: bool) -> JsonValue {
+    match value {
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let redact = redact || config_toml_core::is_secret_key(key, &[]);
+                    (key.clone(), redact_json(value, redact))
+                })
+                .collect(),
+        ),
+        JsonValue::Array(items) if items.iter().any(JsonValue::is_object) => {
+            JsonValue::Array(items.iter().map(|item| redact_json(item, redact)).collect())
+        }
+        _ if redact => JsonValue::String(config_toml_core::REDACTED.to_string()),
+        _ => value.clone(),
+    }
+}
+
+fn key_path(segments: &[String]) -> String {
+    segments
+        .iter()
+        .map(|segment| {
+            let bare = !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+            if bare {
+                segment.clone()
+            } else {
+                format!("{segment:?}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Every non-table value in `table` keyed by its dotted path, in key order.
+/// Arrays are single values.
+pub(crate) fn table_leaves(table: &Table) -> Vec<(String, JsonValue)> {
+    json_leaves(&table_to_json(table))
+}
+
+/// `table_leaves` for a table already converted with `table_to_json`.
+pub(crate) fn json_leaves(value: &JsonValue) -> Vec<(String, JsonValue)> {
+    let mut leaves = Vec::new();
+    collect_leaves(&mut Vec::new(), value, &mut leaves);
+    leaves
+}
+
+fn collect_leaves(
+    path: &mut Vec<String>,
+    value: &JsonValue,
+    leaves: &mut Vec<(String, JsonValue)>,
+) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, value) in map {
+                path.push(key.clone());
+                collect_leaves(path, value, leaves);
+                path.pop();
+            }
+        }
+        _ => leaves.push((key_path(path), value.clone())),
+    }
+}
+
+pub(crate) fn table_to_json(table: &Table) -> JsonValue {
+    let mut map = Map::new();
+    for (key, item) in table.iter() {
+        if let Some(value) = item_to_json(item) {
+            map.insert(key.to_string(), value);
+        }
+    }
+    JsonValue::Object(map)
+}
+
+pub(crate) fn item_to_json(item: &Item) -> Option<JsonValue> {
+    match item {
+        Item::None => None,
+        Item::Value(value) => Some(value_to_json(value)),
+        Item::Table(table) => Some(table_to_json(table)),
+        Item::ArrayOfTables(tables) => {
+            Some(JsonValue::Array(tables.iter().map(table_to_json).collect()))
+        }
+    }
+}
+
+fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::String(text) => JsonValue::String(text.value().clone()),
+        Value::Integer(number) => JsonValue::from(*number.value()),
+        Value::Float(number) => serde_json::Number::from_f64(*number.value())
+            .map(JsonValue::Number)
+            .unwrap_or_else(|| JsonValue::String(number.value().to_string())),
+        Value::Boolean(flag) => JsonValue::Bool(*flag.value()),
+        Value::Datetime(datetime) => JsonValue::String(datetime.value().to_string()),
+        Value::Array(array) => JsonValue::Array(array.iter().map(value_to_json).collect()),
+        Value::InlineTable(table) => JsonValue::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::config_diff;
+
+    #[test]
+    fn diff_reports_leaf_changes_and_ignores_formatting() {
+        let before = "model = \"a\"\n# comment\n[features]\nweb = true\nold = 1\n";
+        let after =
+            "model   =   \"a\"\n[features]\nweb = false\n\n[tools]\nshell = { on = true }\n";
+        let changes = config_diff(before, after).expect("valid toml");
+        let mut summary: Vec<_> = changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.old.clone(), change.new.clone()))
+            .collect();
+        summary.sort_by(|left, right| left.0.cmp(right.0));
+        assert_eq!(
+            summary,
+            vec![
+                ("features.old", Some(json!(1)), None),
+                ("features.web", Some(json!(true)), Some(json!(false))),
+                ("tools", None, Some(json!({ "shell": { "on": true } }))),
+            ]
+        );
+
+        let reformatted = config_diff("a = 1\n", "a=1 # note\n").expect("valid toml");
+        assert!(reformatted.is_empty());
+    }
+
+    #[test]
+    fn diff_redacts_secret_values() {
+        let before = "[mcp_servers.docs]\napi_key = \"old\"\n";
+        let after = "[mcp_servers.docs]\napi_key = \"new\"\n[auth]\ntoken = \"t\"\nmode = \"x\"\n\n[[hooks]]\nsecret = \"s\"\nname = \"h\"\n";
+        let changes = config_diff(before, after).expect("valid toml");
+        let mut summary: Vec<_> = changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.old.clone(), change.new.clone()))
+            .collect();
+        summary.sort_by(|left, right| left.0.cmp(right.0));
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "auth",
+                    None,
+                    Some(json!({ "mode": "<redacted>", "token": "<redacted>" }))
+                ),
+                (
+                    "hooks",
+                    None,
+                    Some(json!([{ "name": "h", "secret": "<redacted>" }]))
+                ),
+                (
+                    "mcp_servers.docs.api_key",
+                    Some(json!("<redacted>")),
+                    Some(json!("<redacted>"))
+                ),
+            ]
+        );
+    }
+}
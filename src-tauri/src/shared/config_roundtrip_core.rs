@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use toml_edit::{Document, TableLike};
+
+use crate::shared::config_toml_core;
+
+/// What writing a proposed `config.toml` would drop from the current one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RoundtripReport {
+    /// Top-level keys and dotted table paths (`profiles.fast`) in the current
+    /// file that the proposed text no longer has, in current document order.
+    pub(crate) lost_keys: Vec<String>,
+}
+
+/// Compares `proposed` against `config.toml` under `codex_home`. A missing
+/// file loses nothing; a proposed text that does not parse is an error.
+pub(crate) fn config_roundtrip_check_core(
+    codex_home: &Path,
+    proposed: &str,
+) -> Result<RoundtripReport, String> {
+    let (_, current) = config_toml_core::load_global_config_document(codex_home)?;
+    let proposed = config_toml_core::parse_document(proposed)
+        .map_err(|err| format!("Proposed config does not parse: {err}"))?;
+    Ok(roundtrip_report(&current, &proposed))
+}
+
+fn roundtrip_report(current: &Document, proposed: &Document) -> RoundtripReport {
+    let mut lost_keys = Vec::new();
+    collect_lost(
+        current.as_table(),
+        Some(proposed.as_table()),
+        "",
+        &mut lost_keys,
+    );
+    RoundtripReport { lost_keys }
+}
+
+/// Keys are only compared at the top level; below it only tables count, so
+/// editing values inside `[features]` is not reported but dropping
+/// `[profiles.fast]` is.
+fn collect_lost(
+    current: &dyn TableLike,
+    proposed: Option<&dyn TableLike>,
+    prefix: &str,
+    lost: &mut Vec<String>,
+) {
+    for (key, item) in current.iter() {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        let counterpart = proposed.and_then(|table| table.get(key));
+        let Some(table) = item.as_table_like() else {
+            if prefix.is_empty() && counterpart.is_none() {
+                lost.push(path);
+            }
+            continue;
+        };
+        match counterpart {
+            Some(counterpart) => {
+                collect_lost(table, counterpart.as_table_like(), &path, lost);
+            }
+            None => lost.push(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::roundtrip_report;
+    use crate::shared::config_toml_core::parse_document;
+
+    #[test]
+    fn roundtrip_reports_dropped_top_level_keys_and_tables() {
+        let current = parse_document(
+            "model = \"gpt-5\"\nexperimental_flag = 1\n[features]\nsteer = true\n[profiles.fast]\nmodel = \"mini\"\n[profiles.deep]\nmodel = \"big\"\n",
+        )
+        .expect("parse current");
+        let proposed =
+            parse_document("model = \"gpt-5.1\"\n[features]\n[profiles.deep]\nmodel = \"big\"\n")
+                .expect("parse proposed");
+
+        let report = roundtrip_report(&current, &proposed);
+        assert_eq!(report.lost_keys, vec!["experimental_flag", "profiles.fast"]);
+
+        let dotted =
+            parse_document("features.steer = false\nexperimental_flag = 2\nprofiles = {}\n")
+                .expect("parse dotted");
+        assert_eq!(
+            roundtrip_report(&current, &dotted).lost_keys,
+            vec!["model", "profiles.fast", "profiles.deep"]
+        );
+        assert!(roundtrip_report(&current, &current).lost_keys.is_empty());
+    }
+}
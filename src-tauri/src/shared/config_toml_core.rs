@@ -1,9 +1,16 @@
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use toml_edit::{value, Document, Item, Table};
+use serde::{Deserialize, Serialize};
+use toml_edit::{value, Decor, Document, InlineTable, Item, Table, TableLike, Value};
 
-use crate::files::ops::{read_with_policy, write_with_policy};
+use crate::codex::config as codex_config;
+use crate::files::ops::{
+    read_with_policy, remove_with_policy, replace_with_policy, touch_with_policy,
+};
 use crate::files::policy::{policy_for, FileKind, FileScope};
+use crate::shared::config_patch_core::{self, ConfigWriter};
+use crate::shared::{config_history_core, config_lock_core};
 
 pub(crate) fn load_global_config_document(codex_home: &Path) -> Result<(bool, Document), String> {
     let policy = policy_for(FileScope::Global, FileKind::Config)?;
@@ -17,17 +24,685 @@ pub(crate) fn load_global_config_document(codex_home: &Path) -> Result<(bool, Do
     Ok((response.exists, document))
 }
 
+/// Writes `document` to `config.toml`, refusing when the file on disk declares
+/// a newer schema than the app knows.
 pub(crate) fn persist_global_config_document(
     codex_home: &Path,
     document: &Document,
+    writer: ConfigWriter<'_>,
 ) -> Result<(), String> {
-    let policy = policy_for(FileScope::Global, FileKind::Config)?;
-    let root = codex_home.to_path_buf();
+    let _lock = lock_for_rewrite(codex_home)?;
     let mut rendered = document.to_string();
     if !rendered.ends_with('\n') {
         rendered.push('\n');
     }
-    write_with_policy(&root, policy, rendered.as_str())
+    write_global_config_text(codex_home, rendered.as_str(), writer)
+}
+
+/// Writes `config.toml` and records the previous contents for `config_undo`.
+pub(crate) fn write_global_config_text(
+    codex_home: &Path,
+    contents: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let before = write_and_publish(codex_home, contents, writer)?;
+    config_history_core::record_config_write(codex_home, before, contents);
+    Ok(())
+}
+
+/// Writes `config.toml` without touching the undo history.
+pub(crate) fn write_global_config_text_untracked(
+    codex_home: &Path,
+    contents: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    write_and_publish(codex_home, contents, writer).map(|_| ())
+}
+
+/// Deletes `config.toml` without touching the undo history; undoing the write
+/// that created the file puts it back to not existing.
+pub(crate) fn remove_global_config_untracked(
+    codex_home: &Path,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let root = codex_home.to_path_buf();
+    let previous = read_with_policy(&root, policy)?;
+    if remove_with_policy(writer.generations(), &root, policy)? {
+        writer.publish(codex_home, Some(&previous.content), "");
+    }
+    Ok(())
+}
+
+/// Bumps `config.toml`'s mtime, or creates it empty under the config lock,
+/// publishing and recording the creation like any other write. Returns `true`
+/// when the file was created.
+pub(crate) fn touch_global_config(
+    codex_home: &Path,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    {
+        let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+        let policy = policy_for(FileScope::Global, FileKind::Config)?;
+        let root = codex_home.to_path_buf();
+        if !touch_with_policy(writer.generations(), &root, policy)? {
+            return Ok(false);
+        }
+        writer.publish(codex_home, None, "");
+    }
+    config_history_core::record_config_write(codex_home, None, "");
+    Ok(true)
+}
+
+/// Takes the config lock, then checks the schema, so the file checked is the
+/// one rewritten.
+fn lock_for_rewrite(codex_home: &Path) -> Result<config_lock_core::ConfigWriteLock, String> {
+    let lock = config_lock_core::lock_config_for_write(codex_home)?;
+    codex_config::reject_newer_schema(codex_home)?;
+    Ok(lock)
+}
+
+/// Writes `config.toml` under its cross-process lock, through a temp file and
+/// rename, emits the resulting `config-patch` and returns the previous contents.
+fn write_and_publish(
+    codex_home: &Path,
+    contents: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<Option<String>, String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let root = codex_home.to_path_buf();
+    let previous = read_with_policy(&root, policy)?;
+    replace_with_policy(writer.generations(), &root, policy, contents)?;
+    let before = previous.exists.then_some(previous.content);
+    writer.publish(codex_home, before.as_deref(), contents);
+    Ok(before)
+}
+
+/// Re-renders `config.toml` with normalized spacing, optionally sorting keys and
+/// tables alphabetically. Comments stay attached to the key or table they precede.
+/// With `dry_run` the formatted text is returned without writing.
+pub(crate) fn format_global_config(
+    codex_home: &Path,
+    sort_keys: bool,
+    dry_run: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<String, String> {
+    let _lock = if dry_run {
+        None
+    } else {
+        Some(lock_for_rewrite(codex_home)?)
+    };
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let response = read_with_policy(&codex_home.to_path_buf(), policy)?;
+    let formatted = format_config_text(response.content.as_str(), sort_keys)?;
+    if !dry_run && response.exists && formatted != response.content {
+        write_global_config_text(codex_home, formatted.as_str(), writer)?;
+    }
+    Ok(formatted)
+}
+
+pub(crate) fn format_config_text(contents: &str, sort_keys: bool) -> Result<String, String> {
+    let mut document = parse_document(contents)?;
+    if sort_keys {
+        let mut position = 1;
+        sort_table(document.as_table_mut(), &mut position);
+    }
+    normalize_table(document.as_table_mut());
+    let trailing = comment_lines(document.trailing().as_str());
+    document.set_trailing(trailing);
+
+    let rendered = document.to_string();
+    let trimmed = rendered.trim_matches('\n');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!("{trimmed}\n"))
+}
+
+fn sort_table(table: &mut Table, position: &mut usize) {
+    table.sort_values();
+    let mut keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+    keys.sort();
+    for key in keys {
+        match table.get_mut(&key) {
+            Some(Item::Table(child)) => {
+                child.set_position(*position);
+                *position += 1;
+                sort_table(child, position);
+            }
+            Some(Item::ArrayOfTables(array)) => {
+                for child in array.iter_mut() {
+                    child.set_position(*position);
+                    *position += 1;
+                    sort_table(child, position);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn normalize_table(table: &mut Table) {
+    let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+    for key in keys {
+        if let Some(decor) = table.key_decor_mut(&key) {
+            let prefix = comment_lines(decor.prefix().and_then(|raw| raw.as_str()));
+            decor.set_prefix(prefix);
+            decor.set_suffix(" ");
+        }
+        match table.get_mut(&key) {
+            Some(Item::Value(value)) => {
+                let decor = value.decor_mut();
+                let suffix = trailing_comment(decor.suffix().and_then(|raw| raw.as_str()));
+                decor.set_prefix(" ");
+                decor.set_suffix(suffix);
+            }
+            Some(Item::Table(child)) => {
+                normalize_header(child.decor_mut());
+                normalize_table(child);
+            }
+            Some(Item::ArrayOfTables(array)) => {
+                for child in array.iter_mut() {
+                    normalize_header(child.decor_mut());
+                    normalize_table(child);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn normalize_header(decor: &mut Decor) {
+    let prefix = comment_lines(decor.prefix().and_then(|raw| raw.as_str()));
+    let suffix = trailing_comment(decor.suffix().and_then(|raw| raw.as_str()));
+    decor.set_prefix(format!("\n{prefix}"));
+    decor.set_suffix(suffix);
+}
+
+/// Keeps only the comment lines of a decor prefix, one per line with no indentation.
+fn comment_lines(raw: Option<&str>) -> String {
+    raw.unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+fn trailing_comment(raw: Option<&str>) -> String {
+    match raw.map(str::trim) {
+        Some(comment) if comment.starts_with('#') => format!(" {comment}"),
+        _ => String::new(),
+    }
+}
+
+/// Spaces per indent when converting to tabs and no line is indented with
+/// spaces alone.
+const DEFAULT_INDENT_WIDTH: usize = 4;
+
+/// The indentation `config_reindent` writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum IndentStyle {
+    Tabs,
+    Spaces { width: usize },
+}
+
+impl IndentStyle {
+    fn unit(self) -> Result<String, String> {
+        match self {
+            Self::Tabs => Ok("\t".to_string()),
+            Self::Spaces { width } if (1..=8).contains(&width) => Ok(" ".repeat(width)),
+            Self::Spaces { width } => Err(format!("indent width {width} is not between 1 and 8")),
+        }
+    }
+}
+
+/// How the indented lines of `config.toml` are indented. Blank lines and
+/// text inside multi-line strings are not counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IndentInfo {
+    /// The style most indented lines use; `None` when no line is indented.
+    pub(crate) style: Option<IndentStyle>,
+    pub(crate) tab_lines: usize,
+    pub(crate) space_lines: usize,
+    /// Lines indented with both tabs and spaces.
+    pub(crate) mixed_lines: usize,
+    /// Set when lines disagree, whether within a line or between lines.
+    pub(crate) mixed: bool,
+}
+
+pub(crate) fn config_indent_info_core(codex_home: &Path) -> Result<IndentInfo, String> {
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let response = read_with_policy(&codex_home.to_path_buf(), policy)?;
+    Ok(indent_info_text(response.content.as_str())?.0)
+}
+
+/// Rewrites every indented line of `config.toml` in `style`, counting a tab
+/// or a run of the file's own space width as one indent, so a 2-space file
+/// becomes a 4-space one and back. Values and comments are untouched, and a
+/// reindented file reindents to itself.
+pub(crate) fn config_reindent_core(
+    codex_home: &Path,
+    style: IndentStyle,
+    writer: ConfigWriter<'_>,
+) -> Result<String, String> {
+    let _lock = lock_for_rewrite(codex_home)?;
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let response = read_with_policy(&codex_home.to_path_buf(), policy)?;
+    let reindented = reindent_text(response.content.as_str(), style)?;
+    if response.exists && reindented != response.content {
+        write_global_config_text(codex_home, reindented.as_str(), writer)?;
+    }
+    Ok(reindented)
+}
+
+/// The counts, and the space width indents are measured in.
+fn indent_info_text(contents: &str) -> Result<(IndentInfo, usize), String> {
+    let mut document = parse_document(contents)?;
+    let mut info = IndentInfo::default();
+    let mut space_width = 0;
+    walk_document_indents(&mut document, &mut |indent: &str| {
+        match (indent.contains('\t'), indent.contains(' ')) {
+            (true, true) => info.mixed_lines += 1,
+            (true, false) => info.tab_lines += 1,
+            (false, true) => {
+                info.space_lines += 1;
+                space_width = gcd(space_width, indent.len());
+            }
+            (false, false) => {}
+        }
+        indent.to_string()
+    });
+    let space_width = if space_width == 0 {
+        DEFAULT_INDENT_WIDTH
+    } else {
+        space_width
+    };
+    info.mixed = info.mixed_lines > 0 || (info.tab_lines > 0 && info.space_lines > 0);
+    info.style = if info.tab_lines + info.space_lines + info.mixed_lines == 0 {
+        None
+    } else if info.tab_lines > info.space_lines {
+        Some(IndentStyle::Tabs)
+    } else {
+        Some(IndentStyle::Spaces { width: space_width })
+    };
+    Ok((info, space_width))
+}
+
+fn reindent_text(contents: &str, style: IndentStyle) -> Result<String, String> {
+    let unit = style.unit()?;
+    let (_, source_width) = indent_info_text(contents)?;
+    let reindented = reindent_with_width(contents, &unit, source_width)?;
+    // When every indent of the output spans several steps (say, only tabs two
+    // deep), the output is detected with a wider width than `style`. Redoing
+    // the rewrite at that width gives the text a second run leaves unchanged.
+    if let IndentStyle::Spaces { width } = style {
+        let (_, output_width) = indent_info_text(&reindented)?;
+        if output_width != width {
+            return reindent_with_width(&reindented, &unit, output_width);
+        }
+    }
+    Ok(reindented)
+}
+
+/// Counts a tab or a run of `space_width` spaces as one `unit`.
+fn reindent_with_width(contents: &str, unit: &str, space_width: usize) -> Result<String, String> {
+    let mut document = parse_document(contents)?;
+    walk_document_indents(&mut document, &mut |indent: &str| {
+        let tabs = indent.matches('\t').count();
+        let spaces = indent.len() - tabs;
+        unit.repeat(tabs + spaces.div_ceil(space_width))
+    });
+    Ok(document.to_string())
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Hands the indentation of every line that starts in decor whitespace to
+/// `reindent` and puts back what it returns.
+fn walk_document_indents(document: &mut Document, reindent: &mut dyn FnMut(&str) -> String) {
+    walk_table_indents(document.as_table_mut(), true, reindent);
+    let trailing = reindent_raw(document.trailing().as_str(), true, false, reindent);
+    document.set_trailing(trailing);
+}
+
+/// `keys_at_line_start` is false inside dotted keys (`a.b = 1`), where only
+/// the first segment begins the line.
+fn walk_table_indents(
+    table: &mut Table,
+    keys_at_line_start: bool,
+    reindent: &mut dyn FnMut(&str) -> String,
+) {
+    let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+    for key in keys {
+        // A header's key decor sits inside its brackets.
+        let line_start = keys_at_line_start
+            && match table.get(&key) {
+                Some(Item::Table(child)) => child.is_dotted(),
+                Some(Item::ArrayOfTables(_)) => false,
+                _ => true,
+            };
+        if let Some(decor) = table.key_decor_mut(&key) {
+            reindent_decor(decor, line_start, reindent);
+        }
+        match table.get_mut(&key) {
+            Some(Item::Value(value)) => walk_value_indents(value, reindent),
+            Some(Item::Table(child)) => {
+                let dotted = child.is_dotted();
+                if !dotted && !child.is_implicit() {
+                    reindent_decor(child.decor_mut(), true, reindent);
+                }
+                walk_table_indents(child, !dotted, reindent);
+            }
+            Some(Item::ArrayOfTables(array)) => {
+                for child in array.iter_mut() {
+                    reindent_decor(child.decor_mut(), true, reindent);
+                    walk_table_indents(child, true, reindent);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn walk_value_indents(value: &mut Value, reindent: &mut dyn FnMut(&str) -> String) {
+    reindent_decor(value.decor_mut(), false, reindent);
+    match value {
+        Value::Array(array) => {
+            for item in array.iter_mut() {
+                walk_value_indents(item, reindent);
+            }
+            let trailing = reindent_raw(array.trailing().as_str(), false, true, reindent);
+            array.set_trailing(trailing);
+        }
+        Value::InlineTable(table) => {
+            let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+            for key in keys {
+                if let Some(decor) = table.key_decor_mut(&key) {
+                    reindent_decor(decor, false, reindent);
+                }
+                if let Some(value) = table.get_mut(&key) {
+                    walk_value_indents(value, reindent);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Unset decor is left alone so toml_edit keeps rendering its default spacing.
+fn reindent_decor(decor: &mut Decor, line_start: bool, reindent: &mut dyn FnMut(&str) -> String) {
+    if let Some(prefix) = decor.prefix().and_then(|raw| raw.as_str()) {
+        let prefix = reindent_raw(Some(prefix), line_start, true, reindent);
+        decor.set_prefix(prefix);
+    }
+    if let Some(suffix) = decor.suffix().and_then(|raw| raw.as_str()) {
+        let suffix = reindent_raw(Some(suffix), false, true, reindent);
+        decor.set_suffix(suffix);
+    }
+}
+
+/// Rewrites the leading whitespace of each line in `raw`. Text before the
+/// first line break only counts as a line when `line_start` says `raw` begins
+/// one. Blank lines are kept, as is trailing whitespace that precedes nothing.
+fn reindent_raw(
+    raw: Option<&str>,
+    line_start: bool,
+    precedes_content: bool,
+    reindent: &mut dyn FnMut(&str) -> String,
+) -> String {
+    let raw = raw.unwrap_or_default();
+    let mut rewritten = String::with_capacity(raw.len());
+    for (index, piece) in raw.split_inclusive('\n').enumerate() {
+        let rest = piece.trim_start_matches([' ', '\t']);
+        // Only the last piece can be all whitespace without a line break.
+        let blank = matches!(rest, "\n" | "\r\n") || (rest.is_empty() && !precedes_content);
+        if (index > 0 || line_start) && !blank {
+            rewritten.push_str(&reindent(&piece[..piece.len() - rest.len()]));
+            rewritten.push_str(rest);
+        } else {
+            rewritten.push_str(piece);
+        }
+    }
+    rewritten
+}
+
+/// `config.toml` re-rendered without comments; keys, values, and table layout
+/// are unchanged. With `write` the stripped text also replaces the file.
+pub(crate) fn config_strip_comments_core(
+    codex_home: &Path,
+    write: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<String, String> {
+    let _lock = if write {
+        Some(lock_for_rewrite(codex_home)?)
+    } else {
+        None
+    };
+    let policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let response = read_with_policy(&codex_home.to_path_buf(), policy)?;
+    let stripped = strip_comments_text(response.content.as_str())?;
+    if write && response.exists && stripped != response.content {
+        write_global_config_text(codex_home, stripped.as_str(), writer)?;
+    }
+    Ok(stripped)
+}
+
+fn strip_comments_text(contents: &str) -> Result<String, String> {
+    let mut document = parse_document(contents)?;
+    strip_table_comments(document.as_table_mut());
+    let trailing = without_comments(document.trailing().as_str());
+    document.set_trailing(trailing);
+    Ok(document.to_string())
+}
+
+fn strip_table_comments(table: &mut Table) {
+    strip_decor_comments(table.decor_mut());
+    let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+    for key in keys {
+        if let Some(decor) = table.key_decor_mut(&key) {
+            strip_decor_comments(decor);
+        }
+        match table.get_mut(&key) {
+            Some(Item::Value(value)) => strip_value_comments(value),
+            Some(Item::Table(child)) => strip_table_comments(child),
+            Some(Item::ArrayOfTables(array)) => {
+                for child in array.iter_mut() {
+                    strip_table_comments(child);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn strip_inline_table_comments(table: &mut InlineTable) {
+    let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+    for key in keys {
+        if let Some(decor) = table.key_decor_mut(&key) {
+            strip_decor_comments(decor);
+        }
+        if let Some(value) = table.get_mut(&key) {
+            strip_value_comments(value);
+        }
+    }
+}
+
+fn strip_value_comments(value: &mut Value) {
+    strip_decor_comments(value.decor_mut());
+    match value {
+        Value::Array(array) => {
+            for item in array.iter_mut() {
+                strip_value_comments(item);
+            }
+            let trailing = without_comments(array.trailing().as_str());
+            array.set_trailing(trailing);
+        }
+        Value::InlineTable(table) => strip_inline_table_comments(table),
+        _ => {}
+    }
+}
+
+/// Unset decor is left alone so toml_edit keeps rendering its default spacing.
+fn strip_decor_comments(decor: &mut Decor) {
+    if let Some(prefix) = decor.prefix().and_then(|raw| raw.as_str()) {
+        let prefix = without_comments(Some(prefix));
+        decor.set_prefix(prefix);
+    }
+    if let Some(suffix) = decor.suffix().and_then(|raw| raw.as_str()) {
+        let suffix = without_comments(Some(suffix));
+        decor.set_suffix(suffix);
+    }
+}
+
+/// Drops every comment line from decor whitespace, keeping blank lines. Decor
+/// never holds anything but whitespace and comments, so a `#` always starts one.
+fn without_comments(raw: Option<&str>) -> String {
+    raw.unwrap_or_default()
+        .split_inclusive('\n')
+        .filter(|line| !line.contains('#'))
+        .collect()
+}
+
+const INCLUDE_KEY: &str = "include";
+
+/// `config.toml` with its `include = [...]` files merged in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EffectiveConfig {
+    pub(crate) merged: String,
+    /// Files that contributed to `merged`, lowest precedence first.
+    pub(crate) sources: Vec<String>,
+    /// Include targets that were listed but do not exist.
+    pub(crate) missing_includes: Vec<String>,
+}
+
+/// Resolves `include` directives recursively, relative to the including file.
+/// Later includes override earlier ones and the including file overrides all of
+/// its includes; tables are merged key by key. Include cycles are an error.
+pub(crate) fn read_effective_global_config(codex_home: &Path) -> Result<EffectiveConfig, String> {
+    let (exists, document) = load_global_config_document(codex_home)?;
+    let mut effective = EffectiveConfig {
+        merged: String::new(),
+        sources: Vec::new(),
+        missing_includes: Vec::new(),
+    };
+    if !exists {
+        return Ok(effective);
+    }
+    let main_path = codex_home.join("config.toml");
+    let main_path = main_path.canonicalize().unwrap_or(main_path);
+    let mut stack = vec![main_path.clone()];
+    let table = merge_includes(document, &main_path, &mut stack, &mut effective)?;
+
+    let mut merged = Document::new();
+    *merged.as_table_mut() = table;
+    let mut position = 1;
+    renumber_tables(merged.as_table_mut(), &mut position);
+    effective.merged = merged.to_string();
+    Ok(effective)
+}
+
+fn merge_includes(
+    mut document: Document,
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    effective: &mut EffectiveConfig,
+) -> Result<Table, String> {
+    let includes = take_includes(&mut document, path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut merged = Table::new();
+    for include in includes {
+        let include_path = base_dir.join(&include);
+        let Ok(include_path) = include_path.canonicalize() else {
+            effective
+                .missing_includes
+                .push(include_path.display().to_string());
+            continue;
+        };
+        if stack.contains(&include_path) {
+            let chain: Vec<String> = stack
+                .iter()
+                .chain(std::iter::once(&include_path))
+                .map(|entry| entry.display().to_string())
+                .collect();
+            return Err(format!("Include cycle detected: {}", chain.join(" -> ")));
+        }
+        let contents = std::fs::read_to_string(&include_path)
+            .map_err(|err| format!("Failed to read {}: {err}", include_path.display()))?;
+        let included = contents
+            .parse::<Document>()
+            .map_err(|err| format!("Failed to parse {}: {err}", include_path.display()))?;
+        stack.push(include_path.clone());
+        let table = merge_includes(included, &include_path, stack, effective)?;
+        stack.pop();
+        merge_tables(&mut merged, &table);
+    }
+    merge_tables(&mut merged, document.as_table());
+    effective.sources.push(path.display().to_string());
+    Ok(merged)
+}
+
+fn take_includes(document: &mut Document, path: &Path) -> Result<Vec<String>, String> {
+    let Some(item) = document.remove(INCLUDE_KEY) else {
+        return Ok(Vec::new());
+    };
+    let invalid = || {
+        format!(
+            "`{INCLUDE_KEY}` must be an array of strings in {}",
+            path.display()
+        )
+    };
+    item.as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|entry| entry.as_str().map(str::to_string).ok_or_else(invalid))
+        .collect()
+}
+
+fn merge_tables(base: &mut Table, overlay: &Table) {
+    for (key, item) in overlay.iter() {
+        match (base.get_mut(key), item) {
+            (Some(Item::Table(base_child)), Item::Table(overlay_child)) => {
+                merge_tables(base_child, overlay_child);
+            }
+            _ => {
+                base.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+/// Tables merged from several files carry positions from their own documents;
+/// renumber them so the merged document renders in merge order.
+fn renumber_tables(table: &mut Table, position: &mut usize) {
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Table(child) => {
+                child.set_position(*position);
+                *position += 1;
+                renumber_tables(child, position);
+            }
+            Item::ArrayOfTables(array) => {
+                for child in array.iter_mut() {
+                    child.set_position(*position);
+                    *position += 1;
+                    renumber_tables(child, position);
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 pub(crate) fn parse_document(contents: &str) -> Result<Document, String> {
@@ -43,29 +718,74 @@ pub(crate) fn ensure_table<'a>(
     document: &'a mut Document,
     key: &str,
 ) -> Result<&'a mut Table, String> {
-    if document.get(key).is_none() {
-        document[key] = Item::Table(Table::new());
-    }
-    document[key]
-        .as_table_mut()
+    ensure_child_item(document.as_item_mut(), key, false)
+        .and_then(Item::as_table_mut)
         .ok_or_else(|| format!("`{key}` must be a table in config.toml"))
 }
 
+/// `name` in the table `parent`, created when missing: inline inside an inline
+/// table, otherwise a standard table, `implicit` (header-less until it holds
+/// values) if asked. `None` when `parent` is not a table.
+fn ensure_child_item<'a>(parent: &'a mut Item, name: &str, implicit: bool) -> Option<&'a mut Item> {
+    let inline = parent.is_inline_table();
+    let table = parent.as_table_like_mut()?;
+    if table.get(name).is_none() {
+        let child = if inline {
+            Item::Value(Value::InlineTable(InlineTable::new()))
+        } else {
+            let mut child = Table::new();
+            child.set_implicit(implicit);
+            Item::Table(child)
+        };
+        table.insert(name, child);
+    }
+    table.get_mut(name)
+}
+
 pub(crate) fn read_feature_flag(document: &Document, key: &str) -> Option<bool> {
+    read_table_flag(document, "features", key)
+}
+
+pub(crate) fn set_feature_flag(
+    document: &mut Document,
+    key: &str,
+    enabled: bool,
+) -> Result<(), String> {
+    set_table_flag(document, "features", key, Some(enabled))
+}
+
+/// `[table] key` as a boolean, for `[features]` and tables such as `[tools]`
+/// that hold flags the same way. Inline tables are read too.
+pub(crate) fn read_table_flag(document: &Document, table: &str, key: &str) -> Option<bool> {
     document
-        .get("features")
+        .get(table)
         .and_then(Item::as_table_like)
         .and_then(|table| table.get(key))
         .and_then(Item::as_bool)
 }
 
-pub(crate) fn set_feature_flag(
+/// Sets `[table] key`, or removes it when `enabled` is `None`. Only that key
+/// is touched; the table is created when setting and left in place when the
+/// last key is removed.
+pub(crate) fn set_table_flag(
     document: &mut Document,
+    table: &str,
     key: &str,
-    enabled: bool,
+    enabled: Option<bool>,
 ) -> Result<(), String> {
-    let features = ensure_table(document, "features")?;
-    features[key] = value(enabled);
+    let Some(enabled) = enabled else {
+        if let Some(existing) = document.get_mut(table).and_then(Item::as_table_like_mut) {
+            existing.remove(key);
+        }
+        return Ok(());
+    };
+    if document.get(table).is_none() {
+        document[table] = Item::Table(Table::new());
+    }
+    let existing = document[table]
+        .as_table_like_mut()
+        .ok_or_else(|| format!("`{table}` must be a table in config.toml"))?;
+    existing.insert(key, value(enabled));
     Ok(())
 }
 
@@ -91,3 +811,1260 @@ pub(crate) fn set_top_level_string(document: &mut Document, key: &str, value_raw
     }
     document[key] = value(trimmed);
 }
+
+/// Like `read_top_level_string`, but also finds `key` spelled in another case
+/// (`Model`, `PERSONALITY`). The exact spelling wins when both are present.
+pub(crate) fn read_top_level_string_ignore_case(document: &Document, key: &str) -> Option<String> {
+    read_top_level_string(document, key).or_else(|| {
+        misspelled_top_level_keys(document, key)
+            .iter()
+            .find_map(|found| read_top_level_string(document, found))
+    })
+}
+
+/// Sets `key` under its canonical spelling, dropping any copies spelled in
+/// another case. Returns the misspelled keys that were replaced.
+pub(crate) fn set_top_level_string_canonical(
+    document: &mut Document,
+    key: &str,
+    value_raw: Option<&str>,
+) -> Vec<String> {
+    let misspelled = misspelled_top_level_keys(document, key);
+    for found in &misspelled {
+        let _ = document.remove(found);
+    }
+    set_top_level_string(document, key, value_raw);
+    misspelled
+}
+
+fn misspelled_top_level_keys(document: &Document, key: &str) -> Vec<String> {
+    document
+        .iter()
+        .map(|(found, _)| found)
+        .filter(|found| *found != key && found.eq_ignore_ascii_case(key))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The table holding `key` for `profile`: the document root, or
+/// `[profiles.<profile>]` when a profile is given.
+fn profile_table<'a>(document: &'a Document, profile: Option<&str>) -> Option<&'a dyn TableLike> {
+    match profile {
+        None => Some(document.as_table()),
+        Some(profile) => child_table(document, "profiles", profile),
+    }
+}
+
+fn ensure_profile_table<'a>(
+    document: &'a mut Document,
+    profile: Option<&str>,
+) -> Result<&'a mut Table, String> {
+    match profile {
+        None => Ok(document.as_table_mut()),
+        Some(profile) => ensure_child_table(document, "profiles", profile),
+    }
+}
+
+/// The `[parent.name]` table, whether written as a table or inline.
+pub(crate) fn child_table<'a>(
+    document: &'a Document,
+    parent: &str,
+    name: &str,
+) -> Option<&'a dyn TableLike> {
+    document
+        .get(parent)
+        .and_then(Item::as_table_like)
+        .and_then(|children| children.get(name))
+        .and_then(Item::as_table_like)
+}
+
+pub(crate) fn child_table_mut<'a>(
+    document: &'a mut Document,
+    parent: &str,
+    name: &str,
+) -> Option<&'a mut dyn TableLike> {
+    document
+        .get_mut(parent)
+        .and_then(Item::as_table_like_mut)
+        .and_then(|children| children.get_mut(name))
+        .and_then(Item::as_table_like_mut)
+}
+
+/// The names of the tables under `[parent]`, in document order.
+pub(crate) fn child_table_names(document: &Document, parent: &str) -> Vec<String> {
+    document
+        .get(parent)
+        .and_then(Item::as_table_like)
+        .map(|children| {
+            children
+                .iter()
+                .filter(|(_, item)| item.is_table_like())
+                .map(|(name, _)| name.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Creates `[parent.name]` if needed; `[parent]` itself stays implicit.
+fn ensure_child_table<'a>(
+    document: &'a mut Document,
+    parent: &str,
+    name: &str,
+) -> Result<&'a mut Table, String> {
+    let children = ensure_table(document, parent)?;
+    if children.is_empty() {
+        children.set_implicit(true);
+    }
+    ensure_child_item(&mut document[parent], name, false)
+        .and_then(Item::as_table_mut)
+        .ok_or_else(|| format!("`{parent}.{name}` must be a table in config.toml"))
+}
+
+pub(crate) fn read_profile_string(
+    document: &Document,
+    profile: Option<&str>,
+    key: &str,
+) -> Option<String> {
+    let value = profile_table(document, profile)?.get(key)?.as_str()?;
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+/// Sets or, with `None`, removes `key` at the top level or under
+/// `[profiles.<profile>]`.
+pub(crate) fn set_profile_string(
+    document: &mut Document,
+    profile: Option<&str>,
+    key: &str,
+    value_raw: Option<&str>,
+) -> Result<(), String> {
+    let value_raw = value_raw.map(str::trim).filter(|value| !value.is_empty());
+    let Some(value_raw) = value_raw else {
+        match profile {
+            None => {
+                let _ = document.remove(key);
+            }
+            Some(profile) => {
+                if let Some(table) = child_table_mut(document, "profiles", profile) {
+                    let _ = table.remove(key);
+                }
+            }
+        }
+        return Ok(());
+    };
+    let table = ensure_profile_table(document, profile)?;
+    table[key] = value(value_raw);
+    Ok(())
+}
+
+/// A top-level value `config_merge` can write. A `null` change deletes the key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ConfigValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    fn to_toml(&self) -> toml_edit::Value {
+        match self {
+            Self::Bool(value) => (*value).into(),
+            Self::Integer(value) => (*value).into(),
+            Self::Float(value) => (*value).into(),
+            Self::String(value) => value.as_str().into(),
+            Self::Array(items) => {
+                toml_edit::Value::Array(items.iter().map(Self::to_toml).collect())
+            }
+        }
+    }
+}
+
+/// Applies only `changes` to `config.toml`; every other key, table, and
+/// comment is left as it is.
+pub(crate) fn config_merge_core(
+    codex_home: &Path,
+    changes: &HashMap<String, Option<ConfigValue>>,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let (_, mut document) = load_global_config_document(codex_home)?;
+    apply_config_changes(&mut document, changes)?;
+    persist_global_config_document(codex_home, &document, writer)
+}
+
+fn apply_config_changes(
+    document: &mut Document,
+    changes: &HashMap<String, Option<ConfigValue>>,
+) -> Result<(), String> {
+    let mut keys: Vec<&String> = changes.keys().collect();
+    keys.sort();
+    // Validate everything first so a rejected change writes nothing.
+    for key in &keys {
+        if key.trim().is_empty() {
+            return Err("config key is empty".to_string());
+        }
+        if document.get(key).is_some_and(|item| !item.is_value()) {
+            return Err(format!(
+                "`{key}` is a table in config.toml; only top-level values can be merged"
+            ));
+        }
+    }
+    for key in keys {
+        let Some(change) = &changes[key] else {
+            let _ = document.remove(key);
+            continue;
+        };
+        let mut next = change.to_toml();
+        if let Some(existing) = document.get(key).and_then(Item::as_value) {
+            *next.decor_mut() = existing.decor().clone();
+        }
+        document[key.as_str()] = Item::Value(next);
+    }
+    Ok(())
+}
+
+/// Which side `config_merge_documents` keeps when both set a key to
+/// different values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MergeStrategy {
+    PreferBase,
+    PreferIncoming,
+    /// Fail naming the first conflicting key.
+    Error,
+}
+
+/// Merges `incoming` into `base` key by key, recursing into tables both
+/// define. Keys only one side sets are kept with their comments; conflicts
+/// are settled by `strategy`, and the winning key keeps its own comments.
+/// Tables new to `base` render after its own. Nothing is written.
+pub(crate) fn config_merge_documents_core(
+    base: &str,
+    incoming: &str,
+    strategy: MergeStrategy,
+) -> Result<String, String> {
+    let mut merged = parse_document(base).map_err(|err| format!("base: {err}"))?;
+    let incoming = parse_document(incoming).map_err(|err| format!("incoming: {err}"))?;
+    let mut position = max_table_position(merged.as_table()) + 1;
+    merge_tables_with_strategy(
+        merged.as_table_mut(),
+        incoming.as_table(),
+        strategy,
+        &mut Vec::new(),
+        &mut position,
+    )?;
+    Ok(merged.to_string())
+}
+
+fn merge_tables_with_strategy(
+    base: &mut Table,
+    incoming: &Table,
+    strategy: MergeStrategy,
+    path: &mut Vec<String>,
+    position: &mut usize,
+) -> Result<(), String> {
+    for (key, item) in incoming.iter() {
+        path.push(key.to_string());
+        let take_incoming = match (base.get_mut(key), item) {
+            (None, _) => true,
+            (Some(Item::Table(base_child)), Item::Table(incoming_child)) => {
+                merge_tables_with_strategy(base_child, incoming_child, strategy, path, position)?;
+                false
+            }
+            (Some(existing), _) => {
+                let same = config_patch_core::item_to_json(existing)
+                    == config_patch_core::item_to_json(item);
+                match strategy {
+                    _ if same => false,
+                    MergeStrategy::PreferBase => false,
+                    MergeStrategy::PreferIncoming => true,
+                    MergeStrategy::Error => {
+                        return Err(format!(
+                            "`{}` differs between the base and incoming config",
+                            path.join(".")
+                        ))
+                    }
+                }
+            }
+        };
+        if take_incoming {
+            let mut item = item.clone();
+            renumber_item(&mut item, position);
+            base.insert(key, item);
+            if let (Some(decor), Some(incoming_decor)) =
+                (base.key_decor_mut(key), incoming.key_decor(key))
+            {
+                *decor = incoming_decor.clone();
+            }
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+fn renumber_item(item: &mut Item, position: &mut usize) {
+    match item {
+        Item::Table(table) => {
+            table.set_position(*position);
+            *position += 1;
+            renumber_tables(table, position);
+        }
+        Item::ArrayOfTables(array) => {
+            for table in array.iter_mut() {
+                table.set_position(*position);
+                *position += 1;
+                renumber_tables(table, position);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn max_table_position(table: &Table) -> usize {
+    table
+        .iter()
+        .map(|(_, item)| match item {
+            Item::Table(child) => child.position().unwrap_or(0).max(max_table_position(child)),
+            Item::ArrayOfTables(array) => array
+                .iter()
+                .map(|child| child.position().unwrap_or(0).max(max_table_position(child)))
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// One step of `config_transaction`. A `path` lists the table segments and
+/// ends with the key, e.g. `["profiles", "fast", "model"]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub(crate) enum ConfigOp {
+    /// Sets a top-level value.
+    SetKey { key: String, value: ConfigValue },
+    /// Sets `[features] <key>`.
+    SetFlag { key: String, enabled: bool },
+    /// Sets a value below nested tables, creating missing tables.
+    SetNested {
+        path: Vec<String>,
+        value: ConfigValue,
+    },
+    /// Removes the key or table at `path`; a missing one is not an error.
+    Remove { path: Vec<String> },
+}
+
+impl ConfigOp {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::SetKey { .. } => "set_key",
+            Self::SetFlag { .. } => "set_flag",
+            Self::SetNested { .. } => "set_nested",
+            Self::Remove { .. } => "remove",
+        }
+    }
+}
+
+/// Applies `ops` in order to one loaded document and persists it only when
+/// every op succeeded, so a failure leaves `config.toml` as it was. The error
+/// names the failing op by its index.
+pub(crate) fn config_transaction_core(
+    codex_home: &Path,
+    ops: &[ConfigOp],
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let (_, mut document) = load_global_config_document(codex_home)?;
+    apply_config_ops(&mut document, ops)?;
+    persist_global_config_document(codex_home, &document, writer)
+}
+
+fn apply_config_ops(document: &mut Document, ops: &[ConfigOp]) -> Result<(), String> {
+    for (index, op) in ops.iter().enumerate() {
+        apply_config_op(document, op)
+            .map_err(|err| format!("ops[{index}] ({}) failed: {err}", op.name()))?;
+    }
+    Ok(())
+}
+
+fn apply_config_op(document: &mut Document, op: &ConfigOp) -> Result<(), String> {
+    match op {
+        ConfigOp::SetKey { key, value } => apply_config_changes(
+            document,
+            &HashMap::from([(key.clone(), Some(value.clone()))]),
+        ),
+        ConfigOp::SetFlag { key, enabled } => {
+            let key = key.trim();
+            if key.is_empty() {
+                return Err("feature key is empty".to_string());
+            }
+            set_feature_flag(document, key, *enabled)
+        }
+        ConfigOp::SetNested { path, value } => {
+            let (tables, key) = split_config_path(path)?;
+            let table = nested_table_mut(document, tables)?;
+            if table.get(key).is_some_and(|item| !item.is_value()) {
+                return Err(format!(
+                    "`{}` is a table in config.toml; only values can be set",
+                    path.join(".")
+                ));
+            }
+            let mut next = value.to_toml();
+            if let Some(existing) = table.get(key).and_then(Item::as_value) {
+                *next.decor_mut() = existing.decor().clone();
+            }
+            table.insert(key, Item::Value(next));
+            Ok(())
+        }
+        ConfigOp::Remove { path } => {
+            let (tables, key) = split_config_path(path)?;
+            let mut item = document.as_item_mut();
+            for segment in tables {
+                match item
+                    .as_table_like_mut()
+                    .and_then(|table| table.get_mut(segment))
+                {
+                    Some(next) => item = next,
+                    None => return Ok(()),
+                }
+            }
+            if let Some(table) = item.as_table_like_mut() {
+                table.remove(key);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn split_config_path(path: &[String]) -> Result<(&[String], &str), String> {
+    if path.iter().any(|segment| segment.trim().is_empty()) {
+        return Err("config path has an empty segment".to_string());
+    }
+    let (key, tables) = path
+        .split_last()
+        .ok_or_else(|| "config path is empty".to_string())?;
+    Ok((tables, key))
+}
+
+/// The table at `tables`, creating missing ones: inline inside inline tables,
+/// implicit (header-less until they hold values) elsewhere.
+fn nested_table_mut<'a>(
+    document: &'a mut Document,
+    tables: &[String],
+) -> Result<&'a mut dyn TableLike, String> {
+    let not_a_table =
+        |path: &[String]| format!("`{}` is not a table in config.toml", path.join("."));
+    let mut item = document.as_item_mut();
+    for (depth, segment) in tables.iter().enumerate() {
+        item =
+            ensure_child_item(item, segment, true).ok_or_else(|| not_a_table(&tables[..depth]))?;
+    }
+    item.as_table_like_mut().ok_or_else(|| not_a_table(tables))
+}
+
+pub(crate) const REDACTED: &str = "<redacted>";
+/// Key segments (split on `_`/`-`) that mark a value as secret.
+const SECRET_KEY_SEGMENTS: &[&str] = &["token", "secret"];
+
+/// `config.toml` with secret-looking values replaced by `"<redacted>"`, for
+/// sharing. Structure and comments are kept, so the result still parses.
+/// `extra_keys` names more keys to redact, matched case-insensitively.
+pub(crate) fn config_export_redacted_core(
+    codex_home: &Path,
+    extra_keys: &[String],
+) -> Result<String, String> {
+    let (_, mut document) = load_global_config_document(codex_home)?;
+    redact_table(document.as_table_mut(), false, extra_keys);
+    Ok(document.to_string())
+}
+
+pub(crate) fn is_secret_key(key: &str, extra_keys: &[String]) -> bool {
+    if extra_keys
+        .iter()
+        .any(|extra| extra.trim().eq_ignore_ascii_case(key))
+    {
+        return true;
+    }
+    let lower = key.to_ascii_lowercase();
+    let segments: Vec<&str> = lower.split(['_', '-']).collect();
+    lower == "auth"
+        || segments.windows(2).any(|pair| pair == ["api", "key"])
+        || segments
+            .iter()
+            .any(|segment| SECRET_KEY_SEGMENTS.contains(segment))
+}
+
+/// Everything below a secret key (e.g. an `[auth]` table) is redacted too.
+fn redact_table(table: &mut dyn TableLike, inherited: bool, extra_keys: &[String]) {
+    for (key, item) in table.iter_mut() {
+        let redact = inherited || is_secret_key(key.get(), extra_keys);
+        redact_item(item, redact, extra_keys);
+    }
+}
+
+fn redact_item(item: &mut Item, redact: bool, extra_keys: &[String]) {
+    match item {
+        Item::Table(table) => redact_table(table, redact, extra_keys),
+        Item::ArrayOfTables(tables) => {
+            for table in tables.iter_mut() {
+                redact_table(table, redact, extra_keys);
+            }
+        }
+        Item::Value(toml_edit::Value::InlineTable(table)) => {
+            redact_table(table, redact, extra_keys)
+        }
+        Item::Value(value) if redact => {
+            let decor = value.decor().clone();
+            *value = REDACTED.into();
+            *value.decor_mut() = decor;
+        }
+        _ => {}
+    }
+}
+
+/// Adds `value` to the top-level string array `key`, creating it if needed.
+/// Returns `false` when the value was already present.
+pub(crate) fn config_array_add_core(
+    codex_home: &Path,
+    key: &str,
+    value: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let (_, mut document) = load_global_config_document(codex_home)?;
+    let changed = add_to_array(&mut document, key, value)?;
+    if changed {
+        persist_global_config_document(codex_home, &document, writer)?;
+    }
+    Ok(changed)
+}
+
+/// Removes `value` from the top-level array `key`. Returns `false` when it was
+/// not there.
+pub(crate) fn config_array_remove_core(
+    codex_home: &Path,
+    key: &str,
+    value: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let (_, mut document) = load_global_config_document(codex_home)?;
+    let changed = remove_from_array(&mut document, key, value)?;
+    if changed {
+        persist_global_config_document(codex_home, &document, writer)?;
+    }
+    Ok(changed)
+}
+
+fn validate_array_change<'a>(key: &str, value: &'a str) -> Result<&'a str, String> {
+    if key.trim().is_empty() {
+        return Err("config key is empty".to_string());
+    }
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("array value is empty".to_string());
+    }
+    Ok(value)
+}
+
+fn add_to_array(document: &mut Document, key: &str, value: &str) -> Result<bool, String> {
+    let value = validate_array_change(key, value)?;
+    if document.get(key).is_none() {
+        document[key] = toml_edit::value(toml_edit::Array::from_iter([value]));
+        return Ok(true);
+    }
+    let array = document[key]
+        .as_array_mut()
+        .ok_or_else(|| format!("`{key}` must be an array in config.toml"))?;
+    if array.iter().any(|item| item.as_str() == Some(value)) {
+        return Ok(false);
+    }
+    // Match the spacing of the existing items so multi-line arrays stay multi-line.
+    let decor = array.iter().last().map(|item| item.decor().clone());
+    array.push(value);
+    if let (Some(decor), Some(pushed)) = (decor, array.iter_mut().last()) {
+        *pushed.decor_mut() = decor;
+    }
+    Ok(true)
+}
+
+fn remove_from_array(document: &mut Document, key: &str, value: &str) -> Result<bool, String> {
+    let value = validate_array_change(key, value)?;
+    let Some(item) = document.get_mut(key) else {
+        return Ok(false);
+    };
+    let array = item
+        .as_array_mut()
+        .ok_or_else(|| format!("`{key}` must be an array in config.toml"))?;
+    let mut changed = false;
+    while let Some(index) = array.iter().position(|item| item.as_str() == Some(value)) {
+        let removed = array.remove(index);
+        if index == 0 {
+            if let (Some(prefix), Some(first)) =
+                (removed.decor().prefix().cloned(), array.get_mut(0))
+            {
+                first.decor_mut().set_prefix(prefix);
+            }
+        }
+        changed = true;
+    }
+    Ok(changed)
+}
+
+/// Adds an empty `[profiles.<name>]`, or one holding a copy of `base`'s keys.
+pub(crate) fn config_profile_create_core(
+    codex_home: &Path,
+    name: &str,
+    base: Option<&str>,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let (_, mut document) = load_global_config_document(codex_home)?;
+    create_profile(&mut document, name, base)?;
+    persist_global_config_document(codex_home, &document, writer)
+}
+
+/// Removes `[profiles.<name>]`. Returns a warning when it is the active
+/// `profile`, which is left pointing at it.
+pub(crate) fn config_profile_delete_core(
+    codex_home: &Path,
+    name: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<Option<String>, String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let (_, mut document) = load_global_config_document(codex_home)?;
+    let warning = delete_profile(&mut document, name)?;
+    persist_global_config_document(codex_home, &document, writer)?;
+    Ok(warning)
+}
+
+/// Renames `[profiles.<old>]` in place, keeping its comments, and follows the
+/// rename in the active `profile`.
+pub(crate) fn config_profile_rename_core(
+    codex_home: &Path,
+    old: &str,
+    new: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let _lock = config_lock_core::lock_config_for_write(codex_home)?;
+    let (_, mut document) = load_global_config_document(codex_home)?;
+    rename_profile(&mut document, old, new)?;
+    persist_global_config_document(codex_home, &document, writer)
+}
+
+fn profile_not_defined(name: &str) -> String {
+    format!("Profile \"{name}\" is not defined in config.toml")
+}
+
+fn validate_new_profile<'a>(document: &Document, name: &'a str) -> Result<&'a str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("profile name is empty".to_string());
+    }
+    let taken = document
+        .get("profiles")
+        .and_then(Item::as_table_like)
+        .is_some_and(|profiles| profiles.contains_key(name));
+    if taken {
+        return Err(format!("Profile \"{name}\" already exists"));
+    }
+    Ok(name)
+}
+
+fn create_profile(document: &mut Document, name: &str, base: Option<&str>) -> Result<(), String> {
+    let name = validate_new_profile(document, name)?;
+    let mut profile = Table::new();
+    if let Some(base) = base.map(str::trim).filter(|base| !base.is_empty()) {
+        let base_table =
+            child_table(document, "profiles", base).ok_or_else(|| profile_not_defined(base))?;
+        for (key, item) in base_table.iter() {
+            profile.insert(key, item.clone());
+        }
+    }
+    let mut position = max_table_position(document.as_table()) + 1;
+    profile.set_position(position);
+    position += 1;
+    renumber_tables(&mut profile, &mut position);
+    *ensure_child_table(document, "profiles", name)? = profile;
+    Ok(())
+}
+
+fn delete_profile(document: &mut Document, name: &str) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if child_table(document, "profiles", name).is_none() {
+        return Err(profile_not_defined(name));
+    }
+    if let Some(profiles) = document
+        .get_mut("profiles")
+        .and_then(Item::as_table_like_mut)
+    {
+        profiles.remove(name);
+    }
+    let active = read_top_level_string(document, "profile");
+    Ok((active.as_deref() == Some(name)).then(|| {
+        format!("Deleted the active profile \"{name}\"; `profile` in config.toml still names it")
+    }))
+}
+
+fn rename_profile(document: &mut Document, old: &str, new: &str) -> Result<(), String> {
+    let old = old.trim();
+    if child_table(document, "profiles", old).is_none() {
+        return Err(profile_not_defined(old));
+    }
+    if new.trim() == old {
+        return Ok(());
+    }
+    let new = validate_new_profile(document, new)?;
+    let profiles = document
+        .get_mut("profiles")
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| profile_not_defined(old))?;
+    let profile = profiles
+        .remove(old)
+        .ok_or_else(|| profile_not_defined(old))?;
+    profiles.insert(new, profile);
+    if read_top_level_string(document, "profile").as_deref() == Some(old) {
+        if let Some(active) = document.get_mut("profile").and_then(Item::as_value_mut) {
+            let decor = active.decor().clone();
+            *active = Value::from(new);
+            *active.decor_mut() = decor;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{
+        add_to_array, apply_config_changes, config_array_add_core, config_export_redacted_core,
+        config_merge_core, config_merge_documents_core, config_reindent_core,
+        config_transaction_core, create_profile, delete_profile, format_config_text,
+        indent_info_text, parse_document, read_effective_global_config, read_profile_string,
+        read_top_level_string, read_top_level_string_ignore_case, reindent_text, remove_from_array,
+        rename_profile, set_profile_string, set_top_level_string_canonical, strip_comments_text,
+        ConfigOp, ConfigValue, IndentStyle, MergeStrategy,
+    };
+    use crate::shared::config_patch_core::{table_to_json, ConfigWriter};
+    use uuid::Uuid;
+
+    fn temp_codex_home() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-config-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    const MESSY: &str = "\n\nmodel   =  \"gpt-5\"   # default model\n\n\n  # personality comment\npersonality=\"friendly\"\n[features]\n  steer=true\n\n\n# agents section\n[agents]\nmax_threads = 4\n";
+
+    #[test]
+    fn format_normalizes_spacing_and_keeps_comments() {
+        let formatted = format_config_text(MESSY, false).expect("format");
+        assert_eq!(
+            formatted,
+            "model = \"gpt-5\" # default model\n# personality comment\npersonality = \"friendly\"\n\n[features]\nsteer = true\n\n# agents section\n[agents]\nmax_threads = 4\n"
+        );
+    }
+
+    #[test]
+    fn format_sorts_keys_and_tables() {
+        let formatted = format_config_text(MESSY, true).expect("format");
+        assert_eq!(
+            formatted,
+            "model = \"gpt-5\" # default model\n# personality comment\npersonality = \"friendly\"\n\n# agents section\n[agents]\nmax_threads = 4\n\n[features]\nsteer = true\n"
+        );
+    }
+
+    #[test]
+    fn format_is_idempotent() {
+        for sort_keys in [false, true] {
+            let once = format_config_text(MESSY, sort_keys).expect("format");
+            let twice = format_config_text(&once, sort_keys).expect("format again");
+            assert_eq!(once, twice);
+        }
+        assert_eq!(format_config_text("  \n", true).expect("format"), "");
+    }
+
+    #[test]
+    fn effective_config_merges_includes_with_main_file_winning() {
+        let home = temp_codex_home();
+        std::fs::write(
+            home.join("base.toml"),
+            "model = \"base\"\npersonality = \"calm\"\n[features]\nsteer = false\napps = true\n",
+        )
+        .expect("write include");
+        std::fs::write(
+            home.join("config.toml"),
+            "include = [\"base.toml\", \"missing.toml\"]\nmodel = \"main\"\n[features]\nsteer = true\n",
+        )
+        .expect("write config");
+
+        let effective = read_effective_global_config(&home).expect("effective config");
+        let merged: toml_edit::Document = effective.merged.parse().expect("merged parses");
+        assert_eq!(merged["model"].as_str(), Some("main"));
+        assert_eq!(merged["personality"].as_str(), Some("calm"));
+        assert_eq!(merged["features"]["steer"].as_bool(), Some(true));
+        assert_eq!(merged["features"]["apps"].as_bool(), Some(true));
+        assert!(merged.get("include").is_none());
+        assert_eq!(effective.sources.len(), 2);
+        assert!(effective.sources[0].ends_with("base.toml"));
+        assert!(effective.sources[1].ends_with("config.toml"));
+        assert_eq!(effective.missing_includes.len(), 1);
+        assert!(effective.missing_includes[0].ends_with("missing.toml"));
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn effective_config_rejects_include_cycles() {
+        let home = temp_codex_home();
+        std::fs::write(home.join("a.toml"), "include = [\"config.toml\"]\n").expect("write a");
+        std::fs::write(home.join("config.toml"), "include = [\"a.toml\"]\n").expect("write config");
+
+        let error = read_effective_global_config(&home).expect_err("cycle should fail");
+        assert!(error.contains("Include cycle detected"));
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn profile_strings_are_scoped_to_profile_tables() {
+        let mut document = parse_document("model_reasoning_effort = \"low\"\n").expect("parse");
+        set_profile_string(
+            &mut document,
+            Some("deep"),
+            "model_reasoning_effort",
+            Some("high"),
+        )
+        .expect("set profile");
+
+        assert_eq!(
+            read_profile_string(&document, None, "model_reasoning_effort").as_deref(),
+            Some("low")
+        );
+        assert_eq!(
+            read_profile_string(&document, Some("deep"), "model_reasoning_effort").as_deref(),
+            Some("high")
+        );
+        assert!(read_profile_string(&document, Some("other"), "model_reasoning_effort").is_none());
+        assert!(document.to_string().contains("[profiles.deep]"));
+
+        set_profile_string(&mut document, Some("deep"), "model_reasoning_effort", None)
+            .expect("clear profile");
+        assert!(read_profile_string(&document, Some("deep"), "model_reasoning_effort").is_none());
+        assert_eq!(
+            read_profile_string(&document, None, "model_reasoning_effort").as_deref(),
+            Some("low")
+        );
+    }
+
+    #[test]
+    fn mixed_case_top_level_keys_are_read_and_canonicalized() {
+        let mut document =
+            parse_document("Model = \"gpt-5\"\nPERSONALITY = \"friendly\"\n").expect("parse");
+        assert_eq!(read_top_level_string(&document, "model"), None);
+        assert_eq!(
+            read_top_level_string_ignore_case(&document, "model"),
+            Some("gpt-5".to_string())
+        );
+
+        let replaced = set_top_level_string_canonical(&mut document, "model", Some("gpt-5"));
+        assert_eq!(replaced, vec!["Model".to_string()]);
+        assert_eq!(
+            read_top_level_string(&document, "model"),
+            Some("gpt-5".to_string())
+        );
+        assert!(document.get("Model").is_none());
+        assert_eq!(
+            read_top_level_string(&document, "PERSONALITY"),
+            Some("friendly".to_string())
+        );
+        assert!(set_top_level_string_canonical(&mut document, "model", Some("o3")).is_empty());
+
+        let both = parse_document("model = \"a\"\nMODEL = \"b\"\n").expect("parse");
+        assert_eq!(
+            read_top_level_string_ignore_case(&both, "model"),
+            Some("a".to_string())
+        );
+    }
+
+    #[test]
+    fn merge_changes_only_the_given_keys() {
+        let mut document = parse_document(MESSY).expect("parse");
+        let changes = HashMap::from([
+            (
+                "model".to_string(),
+                Some(ConfigValue::String("gpt-5.1".to_string())),
+            ),
+            ("personality".to_string(), None),
+            (
+                "approval_policy".to_string(),
+                Some(ConfigValue::String("never".to_string())),
+            ),
+        ]);
+        apply_config_changes(&mut document, &changes).expect("merge");
+
+        let rendered = document.to_string();
+        assert!(rendered.contains("model   =  \"gpt-5.1\"   # default model"));
+        assert!(rendered.contains("approval_policy = \"never\""));
+        assert!(!rendered.contains("personality=\"friendly\""));
+        assert!(rendered.contains("[features]\n  steer=true"));
+        assert!(rendered.contains("# agents section\n[agents]\nmax_threads = 4"));
+
+        let table_change = HashMap::from([("agents".to_string(), None)]);
+        assert!(apply_config_changes(&mut document, &table_change).is_err());
+        assert!(document.get("agents").is_some());
+    }
+
+    #[test]
+    fn array_add_and_remove_are_idempotent() {
+        let mut document =
+            parse_document("projects = [\n  \"/a\",\n  \"/b\",\n]\nmodel = \"gpt-5\"\n")
+                .expect("parse");
+
+        assert!(add_to_array(&mut document, "projects", "/c").expect("add"));
+        assert!(!add_to_array(&mut document, "projects", "/c").expect("add again"));
+        assert_eq!(
+            document.to_string(),
+            "projects = [\n  \"/a\",\n  \"/b\",\n  \"/c\",\n]\nmodel = \"gpt-5\"\n"
+        );
+
+        assert!(remove_from_array(&mut document, "projects", "/a").expect("remove"));
+        assert!(!remove_from_array(&mut document, "projects", "/a").expect("remove again"));
+        assert!(!remove_from_array(&mut document, "missing", "/a").expect("remove missing"));
+        assert_eq!(
+            document.to_string(),
+            "projects = [\n  \"/b\",\n  \"/c\",\n]\nmodel = \"gpt-5\"\n"
+        );
+
+        assert!(add_to_array(&mut document, "model", "/a").is_err());
+    }
+
+    #[test]
+    fn redacted_export_hides_secrets_and_stays_parseable() {
+        let home = temp_codex_home();
+        std::fs::write(
+            home.join("config.toml"),
+            "model = \"gpt-5\"\nmodel_max_output_tokens = 4096\nopenai_api_key = \"sk-1\" # mine\n\n[auth]\nmode = \"chatgpt\"\n\n[mcp_servers.docs]\nurl = \"https://docs\"\nbearer_token = \"t-1\"\nheaders = { X-Team = \"team-1\" }\n",
+        )
+        .expect("write config");
+
+        let redacted = config_export_redacted_core(&home, &["x-team".to_string()]).expect("export");
+        let document = parse_document(&redacted).expect("redacted parses");
+        assert_eq!(document["model"].as_str(), Some("gpt-5"));
+        assert_eq!(document["model_max_output_tokens"].as_integer(), Some(4096));
+        assert_eq!(document["openai_api_key"].as_str(), Some("<redacted>"));
+        assert_eq!(document["auth"]["mode"].as_str(), Some("<redacted>"));
+        let docs = &document["mcp_servers"]["docs"];
+        assert_eq!(docs["url"].as_str(), Some("https://docs"));
+        assert_eq!(docs["bearer_token"].as_str(), Some("<redacted>"));
+        assert_eq!(docs["headers"]["X-Team"].as_str(), Some("<redacted>"));
+        assert!(redacted.contains("\"<redacted>\" # mine"));
+        assert!(!redacted.contains("sk-1") && !redacted.contains("team-1"));
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn config_transaction_applies_all_ops_or_none() {
+        let home = temp_codex_home();
+        let original = "model = \"gpt-5\" # primary\n[features]\nsteer = true\n";
+        std::fs::write(home.join("config.toml"), original).expect("write config");
+
+        let failing = vec![
+            ConfigOp::SetKey {
+                key: "model".to_string(),
+                value: ConfigValue::String("gpt-5.1".to_string()),
+            },
+            ConfigOp::SetNested {
+                path: vec!["model".to_string(), "name".to_string()],
+                value: ConfigValue::Bool(true),
+            },
+        ];
+        let error = config_transaction_core(&home, &failing, ConfigWriter::silent("test"))
+            .expect_err("second op fails");
+        assert!(error.starts_with("ops[1] (set_nested) failed"), "{error}");
+        assert_eq!(
+            std::fs::read_to_string(home.join("config.toml")).expect("read"),
+            original
+        );
+
+        let ops = vec![
+            ConfigOp::SetKey {
+                key: "model".to_string(),
+                value: ConfigValue::String("gpt-5.1".to_string()),
+            },
+            ConfigOp::SetFlag {
+                key: "apps".to_string(),
+                enabled: false,
+            },
+            ConfigOp::SetNested {
+                path: vec![
+                    "profiles".to_string(),
+                    "fast".to_string(),
+                    "model".to_string(),
+                ],
+                value: ConfigValue::String("mini".to_string()),
+            },
+            ConfigOp::Remove {
+                path: vec!["features".to_string(), "steer".to_string()],
+            },
+            ConfigOp::Remove {
+                path: vec!["missing".to_string(), "key".to_string()],
+            },
+        ];
+        config_transaction_core(&home, &ops, ConfigWriter::silent("test")).expect("transaction");
+        let written = std::fs::read_to_string(home.join("config.toml")).expect("read");
+        let document = parse_document(&written).expect("parse");
+        assert_eq!(document["model"].as_str(), Some("gpt-5.1"));
+        assert!(written.contains("# primary"));
+        assert_eq!(document["features"]["apps"].as_bool(), Some(false));
+        assert!(document["features"].get("steer").is_none());
+        assert_eq!(document["profiles"]["fast"]["model"].as_str(), Some("mini"));
+        assert!(written.contains("[profiles.fast]") && !written.contains("[profiles]\n"));
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn writes_refuse_a_config_from_a_newer_schema() {
+        let home = temp_codex_home();
+        let original = "schema_version = 2\nmodel = \"gpt-5\"\n[features]\n\tsteer = true\n";
+        std::fs::write(home.join("config.toml"), original).expect("write config");
+
+        let changes = HashMap::from([(
+            "model".to_string(),
+            Some(ConfigValue::String("gpt-5.1".to_string())),
+        )]);
+        let merged = config_merge_core(&home, &changes, ConfigWriter::silent("test"));
+        let added = config_array_add_core(&home, "notify", "say", ConfigWriter::silent("test"));
+        let reindented = config_reindent_core(
+            &home,
+            IndentStyle::Spaces { width: 2 },
+            ConfigWriter::silent("test"),
+        );
+        for error in [merged.err(), added.err(), reindented.err()] {
+            assert!(error.expect("refused").contains("newer schema"));
+        }
+        assert_eq!(
+            std::fs::read_to_string(home.join("config.toml")).expect("read"),
+            original
+        );
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn strip_comments_keeps_values_and_tables() {
+        let source = "# header\nmodel = \"gpt-5\" # primary\n\n# notes\n[features]\nsteer = true\nlist = [\n  \"a\", # first\n  \"b\",\n  # end\n]\n\n[profiles.fast] # quick\nmodel = \"gpt-5-mini\"\n# trailing\n";
+
+        let stripped = strip_comments_text(source).expect("strip");
+        assert!(!stripped.contains('#'));
+        let original = parse_document(source).expect("parse source");
+        let document = parse_document(&stripped).expect("parse stripped");
+        assert_eq!(document["model"].as_str(), Some("gpt-5"));
+        assert_eq!(document["features"]["steer"].as_bool(), Some(true));
+        assert_eq!(
+            document["features"]["list"]
+                .as_array()
+                .map(|list| list.len()),
+            Some(2)
+        );
+        assert_eq!(
+            document["profiles"]["fast"]["model"].as_str(),
+            original["profiles"]["fast"]["model"].as_str()
+        );
+        assert!(stripped.contains("\n[features]\n"));
+    }
+
+    #[test]
+    fn merge_documents_keeps_disjoint_keys_and_their_comments() {
+        let base = "# base model\nmodel = \"gpt-5\"\n\n[features]\nsteer = true\n";
+        let incoming = "# reasoning\nmodel_reasoning_effort = \"high\"\n\n[features]\napps = false\n\n# fast profile\n[profiles.fast]\nmodel = \"mini\"\n";
+
+        let merged = config_merge_documents_core(base, incoming, MergeStrategy::Error)
+            .expect("no conflicts");
+        let document = parse_document(&merged).expect("parse merged");
+        assert_eq!(document["model"].as_str(), Some("gpt-5"));
+        assert_eq!(document["model_reasoning_effort"].as_str(), Some("high"));
+        assert_eq!(document["features"]["steer"].as_bool(), Some(true));
+        assert_eq!(document["features"]["apps"].as_bool(), Some(false));
+        assert_eq!(document["profiles"]["fast"]["model"].as_str(), Some("mini"));
+        assert!(merged.contains("# base model") && merged.contains("# reasoning"));
+        assert!(merged.contains("# fast profile"));
+        assert!(merged.find("[features]") < merged.find("[profiles.fast]"));
+    }
+
+    #[test]
+    fn merge_documents_settles_conflicts_by_strategy() {
+        let base = "# ours\nmodel = \"gpt-5\"\n[features]\nsteer = true\nlist = [1, 2]\n";
+        let incoming = "# theirs\nmodel = \"mini\"\n[features]\nsteer = false\nlist = [1,2]\n";
+
+        let err = config_merge_documents_core(base, incoming, MergeStrategy::Error)
+            .expect_err("conflict");
+        assert!(err.contains("`model`"), "{err}");
+        let nested = "[features]\nsteer = false\n";
+        let err = config_merge_documents_core(base, nested, MergeStrategy::Error)
+            .expect_err("nested conflict");
+        assert!(err.contains("`features.steer`"), "{err}");
+
+        let ours = config_merge_documents_core(base, incoming, MergeStrategy::PreferBase)
+            .expect("prefer base");
+        assert_eq!(ours, base);
+
+        let theirs = config_merge_documents_core(base, incoming, MergeStrategy::PreferIncoming)
+            .expect("prefer incoming");
+        let document = parse_document(&theirs).expect("parse merged");
+        assert_eq!(document["model"].as_str(), Some("mini"));
+        assert_eq!(document["features"]["steer"].as_bool(), Some(false));
+        assert!(theirs.contains("# theirs") && !theirs.contains("# ours"));
+        // Equal values never conflict, so the base formatting stays.
+        assert!(theirs.contains("list = [1, 2]"));
+
+        let err = config_merge_documents_core("model = ", "", MergeStrategy::Error)
+            .expect_err("bad base");
+        assert!(err.starts_with("base:"), "{err}");
+    }
+
+    #[test]
+    fn create_profile_copies_base_keys() {
+        let mut document = parse_document(
+            "model = \"gpt-5\"\n\n# fast profile\n[profiles.fast]\nmodel = \"gpt-5-mini\" # quick\n\n[profiles.fast.features]\nsteer = true\n",
+        )
+        .expect("parse");
+
+        create_profile(&mut document, " copy ", Some("fast")).expect("create from base");
+        create_profile(&mut document, "empty", None).expect("create empty");
+        assert_eq!(
+            document["profiles"]["copy"]["model"].as_str(),
+            Some("gpt-5-mini")
+        );
+        assert_eq!(
+            document["profiles"]["copy"]["features"]["steer"].as_bool(),
+            Some(true)
+        );
+        let rendered = document.to_string();
+        assert_eq!(rendered.matches("# fast profile").count(), 1);
+        assert!(rendered.contains("[profiles.empty]"));
+        assert!(rendered.find("[profiles.fast.features]") < rendered.find("[profiles.copy]"));
+
+        let err = create_profile(&mut document, "fast", None).expect_err("taken");
+        assert!(err.contains("already exists"), "{err}");
+        let err = create_profile(&mut document, "other", Some("missing")).expect_err("no base");
+        assert!(err.contains("\"missing\" is not defined"), "{err}");
+        assert!(create_profile(&mut document, "  ", None).is_err());
+    }
+
+    #[test]
+    fn rename_profile_keeps_comments_and_follows_active_profile() {
+        let mut document = parse_document(
+            "profile = \"fast\" # active\n\n# fast profile\n[profiles.fast]\nmodel = \"mini\"\n\n# safe profile\n[profiles.safe]\nsandbox_mode = \"read-only\"\n",
+        )
+        .expect("parse");
+
+        rename_profile(&mut document, "fast", "quick").expect("rename");
+        let rendered = document.to_string();
+        assert!(
+            rendered.contains("# fast profile\n[profiles.quick]"),
+            "{rendered}"
+        );
+        assert!(
+            rendered.contains("# safe profile\n[profiles.safe]"),
+            "{rendered}"
+        );
+        assert!(!rendered.contains("[profiles.fast]"));
+        assert_eq!(
+            read_top_level_string(&document, "profile").as_deref(),
+            Some("quick")
+        );
+        assert!(rendered.contains("# active"));
+        let err = rename_profile(&mut document, "quick", "safe").expect_err("taken");
+        assert!(err.contains("already exists"), "{err}");
+        assert!(rename_profile(&mut document, "fast", "other").is_err());
+
+        let warning = delete_profile(&mut document, "quick").expect("delete active");
+        assert!(warning.is_some_and(|warning| warning.contains("active profile")));
+        assert_eq!(delete_profile(&mut document, "safe"), Ok(None));
+        assert!(delete_profile(&mut document, "safe").is_err());
+    }
+
+    #[test]
+    fn reindent_normalizes_mixed_indentation_idempotently() {
+        let source = "[features]\n  steer = true # keep\n\tapps = false\n\n# tools\n[tools]\n    web_search = true\nlist = [\n\t\"a\", # first\n    \"b\",\n  # end\n]\nnote = \"\"\"\n\tliteral tab\n\"\"\"\n";
+        let (info, width) = indent_info_text(source).expect("detect");
+        assert_eq!(info.style, Some(IndentStyle::Spaces { width: 2 }));
+        assert_eq!(
+            (info.tab_lines, info.space_lines, info.mixed_lines),
+            (2, 4, 0)
+        );
+        assert!(info.mixed);
+        assert_eq!(width, 2);
+
+        let tabs = reindent_text(source, IndentStyle::Tabs).expect("tabs");
+        assert_eq!(
+            tabs,
+            "[features]\n\tsteer = true # keep\n\tapps = false\n\n# tools\n[tools]\n\t\tweb_search = true\nlist = [\n\t\"a\", # first\n\t\t\"b\",\n\t# end\n]\nnote = \"\"\"\n\tliteral tab\n\"\"\"\n"
+        );
+        let spaces = reindent_text(source, IndentStyle::Spaces { width: 2 }).expect("spaces");
+        assert!(spaces.contains("\n  apps = false\n"), "{spaces}");
+        assert!(spaces.contains("\n    web_search = true\n"), "{spaces}");
+        let (info, _) = indent_info_text(&spaces).expect("detect spaces");
+        assert_eq!(info.style, Some(IndentStyle::Spaces { width: 2 }));
+        assert!(!info.mixed);
+
+        for (style, once) in [
+            (IndentStyle::Tabs, &tabs),
+            (IndentStyle::Spaces { width: 2 }, &spaces),
+        ] {
+            assert_eq!(&reindent_text(once, style).expect("again"), once);
+            let before = parse_document(source).expect("parse source");
+            let after = parse_document(once).expect("parse reindented");
+            assert_eq!(
+                table_to_json(after.as_table()),
+                table_to_json(before.as_table())
+            );
+        }
+        assert!(reindent_text(source, IndentStyle::Spaces { width: 0 }).is_err());
+
+        let two = "[tools]\n  web_search = true\nlist = [\n  \"a\",\n    \"b\",\n]\n";
+        let four = reindent_text(two, IndentStyle::Spaces { width: 4 }).expect("2 to 4");
+        assert_eq!(
+            four,
+            "[tools]\n    web_search = true\nlist = [\n    \"a\",\n        \"b\",\n]\n"
+        );
+        assert_eq!(
+            reindent_text(&four, IndentStyle::Spaces { width: 4 }).expect("4 again"),
+            four
+        );
+        let narrowed = reindent_text(&four, IndentStyle::Spaces { width: 2 }).expect("4 to 2");
+        assert_eq!(narrowed, two);
+        assert_eq!(
+            reindent_text(&narrowed, IndentStyle::Spaces { width: 2 }).expect("2 again"),
+            two
+        );
+        let eight = "[tools]\n        web_search = true\nlist = [\n        \"a\",\n                \"b\",\n]\n";
+        let halved = reindent_text(eight, IndentStyle::Spaces { width: 4 }).expect("8 to 4");
+        assert_eq!(halved, four);
+        assert_eq!(
+            reindent_text(&halved, IndentStyle::Spaces { width: 4 }).expect("4 again"),
+            four
+        );
+        let deep_tabs = "[tools]\n\t\tweb_search = true\n";
+        let spaced = reindent_text(deep_tabs, IndentStyle::Spaces { width: 4 }).expect("deep tabs");
+        assert_eq!(spaced, "[tools]\n    web_search = true\n");
+        assert_eq!(
+            reindent_text(&spaced, IndentStyle::Spaces { width: 4 }).expect("spaced again"),
+            spaced
+        );
+        assert_eq!(
+            indent_info_text("model = \"gpt-5\"\n")
+                .expect("flat")
+                .0
+                .style,
+            None
+        );
+    }
+}
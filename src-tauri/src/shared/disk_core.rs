@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::codex::home::resolve_default_codex_home;
+use crate::types::WorkspaceEntry;
+
+/// A location whose volume `disk_free` reports on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum PathKind {
+    AppData,
+    CodexHome,
+    WorkspaceRoot {
+        #[serde(rename = "workspaceId")]
+        workspace_id: String,
+    },
+}
+
+async fn resolve_path_kind(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    data_dir: &Path,
+    path_kind: &PathKind,
+) -> Result<(PathBuf, &'static str), String> {
+    match path_kind {
+        PathKind::AppData => Ok((data_dir.to_path_buf(), "app data dir")),
+        PathKind::CodexHome => resolve_default_codex_home()
+            .map(|home| (home, "CODEX_HOME"))
+            .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string()),
+        PathKind::WorkspaceRoot { workspace_id } => workspaces
+            .lock()
+            .await
+            .get(workspace_id)
+            .map(|entry| (PathBuf::from(&entry.path), "workspace root"))
+            .ok_or_else(|| "workspace not found".to_string()),
+    }
+}
+
+/// Bytes available to this process on the volume holding `path_kind`.
+pub(crate) async fn disk_free_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    data_dir: &Path,
+    path_kind: PathKind,
+) -> Result<u64, String> {
+    let (path, context) = resolve_path_kind(workspaces, data_dir, &path_kind).await?;
+    let path = path
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {context} {}: {err}", path.display()))?;
+    volume_free_bytes(&path)
+        .map_err(|err| format!("Failed to read free space for {context}: {err}"))
+}
+
+#[cfg(unix)]
+// The statvfs field widths differ between platforms.
+#[allow(clippy::unnecessary_cast)]
+fn volume_free_bytes(path: &Path) -> std::io::Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(windows)]
+fn volume_free_bytes(path: &Path) -> std::io::Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory: *const u16,
+            free_to_caller: *mut u64,
+            total: *mut u64,
+            total_free: *mut u64,
+        ) -> i32;
+    }
+
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut free_to_caller = 0u64;
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut free_to_caller,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(free_to_caller)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn volume_free_bytes(_path: &Path) -> std::io::Result<u64> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "free space is not available on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{volume_free_bytes, PathKind};
+    use serde_json::json;
+
+    #[test]
+    fn path_kinds_use_tagged_json() {
+        let kind: PathKind =
+            serde_json::from_value(json!({ "kind": "workspace_root", "workspaceId": "ws" }))
+                .expect("parse workspace root");
+        assert_eq!(
+            kind,
+            PathKind::WorkspaceRoot {
+                workspace_id: "ws".to_string()
+            }
+        );
+        let kind: PathKind =
+            serde_json::from_value(json!({ "kind": "app_data" })).expect("parse app data");
+        assert_eq!(kind, PathKind::AppData);
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn temp_dir_volume_reports_free_space() {
+        let free = volume_free_bytes(&std::env::temp_dir()).expect("free space");
+        assert!(free > 0);
+        assert!(volume_free_bytes(&std::env::temp_dir().join("codex-monitor-missing")).is_err());
+    }
+}
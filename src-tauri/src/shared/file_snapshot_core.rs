@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::files::ops::read_with_policy;
+use crate::files::policy::{policy_for, FileKind, FileScope, ScopeRootOverrides, ScopedFileRef};
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::files_core::{file_write_report_core, resolve_root_core, WriteReport};
+use crate::types::WorkspaceEntry;
+
+/// Snapshots kept per file; taking another prunes the oldest.
+const MAX_SNAPSHOTS_PER_FILE: usize = 20;
+const SNAPSHOTS_DIR: &str = "file-snapshots";
+
+/// A manual save point of one scoped file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileSnapshot {
+    pub(crate) id: String,
+    #[serde(flatten)]
+    pub(crate) file: ScopedFileRef,
+    pub(crate) label: Option<String>,
+    pub(crate) created_at_ms: i64,
+    /// Size of the file content in bytes.
+    pub(crate) size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct StoredSnapshot {
+    #[serde(flatten)]
+    snapshot: FileSnapshot,
+    content: String,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Workspace files are keyed by workspace id, so a moved workspace keeps its
+/// snapshots.
+fn normalize_file(file: ScopedFileRef) -> ScopedFileRef {
+    match file.scope {
+        FileScope::Global => ScopedFileRef {
+            workspace_id: None,
+            ..file
+        },
+        FileScope::Workspace => file,
+    }
+}
+
+/// `workspace_id`, or `global`, made safe for a file name.
+pub(crate) fn store_owner(workspace_id: Option<&str>) -> String {
+    workspace_id
+        .unwrap_or("global")
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn store_path(data_dir: &Path, file: &ScopedFileRef) -> PathBuf {
+    let owner = store_owner(file.workspace_id.as_deref());
+    data_dir.join(SNAPSHOTS_DIR).join(format!(
+        "{}-{owner}-{}.json",
+        file.scope.as_str(),
+        file.kind.as_str()
+    ))
+}
+
+/// Snapshot stores of workspaces that are no longer registered.
+pub(crate) fn orphaned_snapshot_stores(data_dir: &Path, workspace_ids: &[String]) -> Vec<PathBuf> {
+    let owners: HashSet<String> = workspace_ids
+        .iter()
+        .map(|id| store_owner(Some(id)))
+        .collect();
+    let Ok(entries) = std::fs::read_dir(data_dir.join(SNAPSHOTS_DIR)) else {
+        return Vec::new();
+    };
+    let mut orphaned: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                return false;
+            };
+            let owner = name.strip_prefix("workspace-").and_then(|rest| {
+                FileKind::ALL
+                    .into_iter()
+                    .find_map(|kind| rest.strip_suffix(&format!("-{}.json", kind.as_str())))
+            });
+            owner.is_some_and(|owner| !owners.contains(owner))
+        })
+        .collect();
+    orphaned.sort();
+    orphaned
+}
+
+fn read_store(path: &Path) -> Result<Vec<StoredSnapshot>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => serde_json::from_str(&data)
+            .map_err(|err| format!("Failed to parse {}: {err}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(format!("Failed to read {}: {err}", path.display())),
+    }
+}
+
+fn write_store(path: &Path, snapshots: &[StoredSnapshot]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(snapshots).map_err(|err| err.to_string())?;
+    std::fs::write(path, data).map_err(|err| err.to_string())
+}
+
+/// Appends `stored`, dropping the oldest snapshots beyond the cap.
+fn push_capped(snapshots: &mut Vec<StoredSnapshot>, stored: StoredSnapshot) {
+    snapshots.push(stored);
+    snapshots.sort_by_key(|stored| stored.snapshot.created_at_ms);
+    let excess = snapshots.len().saturating_sub(MAX_SNAPSHOTS_PER_FILE);
+    snapshots.drain(..excess);
+}
+
+/// Copies the current content of a scoped file into its snapshot store under
+/// the app data dir.
+pub(crate) async fn file_snapshot_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    data_dir: &Path,
+    file: ScopedFileRef,
+    label: Option<String>,
+) -> Result<FileSnapshot, String> {
+    let file = normalize_file(file);
+    let policy = policy_for(file.scope, file.kind)?;
+    let root = resolve_root_core(
+        workspaces,
+        scope_overrides,
+        file.scope,
+        file.workspace_id.as_deref(),
+    )
+    .await?;
+    let current = read_with_policy(&root, policy)?;
+    if !current.exists {
+        return Err(format!(
+            "{} does not exist; nothing to snapshot",
+            policy.filename
+        ));
+    }
+    let snapshot = FileSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        file,
+        label: label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty()),
+        created_at_ms: now_ms(),
+        size: current.content.len() as u64,
+    };
+    let path = store_path(data_dir, &snapshot.file);
+    let mut snapshots = read_store(&path)?;
+    push_capped(
+        &mut snapshots,
+        StoredSnapshot {
+            snapshot: snapshot.clone(),
+            content: current.content,
+        },
+    );
+    write_store(&path, &snapshots)?;
+    Ok(snapshot)
+}
+
+/// Snapshots of one scoped file, newest first.
+pub(crate) fn file_snapshot_list_core(
+    data_dir: &Path,
+    file: ScopedFileRef,
+) -> Result<Vec<FileSnapshot>, String> {
+    let file = normalize_file(file);
+    let snapshots = read_store(&store_path(data_dir, &file))?;
+    Ok(snapshots
+        .into_iter()
+        .rev()
+        .map(|stored| stored.snapshot)
+        .collect())
+}
+
+/// Writes a snapshot back over the file it was taken from, through the
+/// regular scoped-file write so watchers and config history see it. The
+/// snapshot itself is kept.
+pub(crate) async fn file_snapshot_restore_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    data_dir: &Path,
+    snapshot_id: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<WriteReport, String> {
+    let stored = find_snapshot(data_dir, snapshot_id)?
+        .ok_or_else(|| format!("snapshot `{snapshot_id}` not found"))?;
+    let file = stored.snapshot.file;
+    file_write_report_core(
+        workspaces,
+        scope_overrides,
+        file.scope,
+        file.kind,
+        file.workspace_id,
+        stored.content,
+        None,
+        true,
+        writer,
+    )
+    .await
+}
+
+fn find_snapshot(data_dir: &Path, snapshot_id: &str) -> Result<Option<StoredSnapshot>, String> {
+    let Ok(entries) = std::fs::read_dir(data_dir.join(SNAPSHOTS_DIR)) else {
+        return Ok(None);
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(stored) = read_store(&path)?
+            .into_iter()
+            .find(|stored| stored.snapshot.id == snapshot_id)
+        {
+            return Ok(Some(stored));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        file_snapshot_list_core, find_snapshot, orphaned_snapshot_stores, push_capped, read_store,
+        store_path, write_store, FileSnapshot, StoredSnapshot, MAX_SNAPSHOTS_PER_FILE,
+    };
+    use crate::files::policy::{FileKind, FileScope, ScopedFileRef};
+    use uuid::Uuid;
+
+    fn stored(file: &ScopedFileRef, created_at_ms: i64) -> StoredSnapshot {
+        StoredSnapshot {
+            snapshot: FileSnapshot {
+                id: format!("snap-{created_at_ms}"),
+                file: file.clone(),
+                label: None,
+                created_at_ms,
+                size: 1,
+            },
+            content: created_at_ms.to_string(),
+        }
+    }
+
+    #[test]
+    fn snapshots_are_capped_listed_newest_first_and_found_by_id() {
+        let data_dir =
+            std::env::temp_dir().join(format!("codex-monitor-snapshots-{}", Uuid::new_v4()));
+        let file = ScopedFileRef {
+            scope: FileScope::Workspace,
+            kind: FileKind::Agents,
+            workspace_id: Some("ws/1".to_string()),
+        };
+        let path = store_path(&data_dir, &file);
+        assert_eq!(
+            path.file_name().and_then(|name| name.to_str()),
+            Some("workspace-ws_1-agents.json")
+        );
+
+        let mut snapshots = Vec::new();
+        for created_at_ms in 0..(MAX_SNAPSHOTS_PER_FILE as i64 + 3) {
+            push_capped(&mut snapshots, stored(&file, created_at_ms));
+        }
+        assert_eq!(snapshots.len(), MAX_SNAPSHOTS_PER_FILE);
+        assert_eq!(snapshots[0].snapshot.created_at_ms, 3);
+        write_store(&path, &snapshots).expect("write store");
+        assert_eq!(
+            read_store(&path).expect("read store").len(),
+            snapshots.len()
+        );
+
+        let listed = file_snapshot_list_core(&data_dir, file).expect("list");
+        assert_eq!(listed[0].id, format!("snap-{}", MAX_SNAPSHOTS_PER_FILE + 2));
+        let found = find_snapshot(&data_dir, "snap-5")
+            .expect("find")
+            .expect("snapshot");
+        assert_eq!(found.content, "5");
+        assert!(find_snapshot(&data_dir, "snap-0").expect("find").is_none());
+        assert!(orphaned_snapshot_stores(&data_dir, &["ws/1".to_string()]).is_empty());
+        assert_eq!(orphaned_snapshot_stores(&data_dir, &[]), vec![path.clone()]);
+
+        let global = ScopedFileRef {
+            scope: FileScope::Global,
+            kind: FileKind::Config,
+            workspace_id: Some("ignored".to_string()),
+        };
+        assert!(file_snapshot_list_core(&data_dir, global)
+            .expect("list global")
+            .is_empty());
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
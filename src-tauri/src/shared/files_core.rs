@@ -1,19 +1,242 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
+use crate::backend::events::{EventSink, OperationReporter, PrewarmDone, PrewarmFailure};
+use crate::codex::config::read_personality_from_document;
 use crate::codex::home as codex_home;
-use crate::files::io::TextFileResponse;
-use crate::files::ops::{read_with_policy, write_with_policy};
-use crate::files::policy::{policy_for, FileKind, FileScope};
+use crate::files::archive::{read_archive, write_archive, ArchiveEntry, MAX_ENTRY_BYTES};
+use crate::files::compare::{diff_hunks, DiffHunk};
+use crate::files::generation::{GenerationCache, Generations};
+use crate::files::io::{
+    measure_dir_usage, split_lines_normalized, write_probe_within, CappedRead, FileRangeResponse,
+    LinkInfo, NormalizedLines, TextFileResponse, PERMISSION_BITS_SUPPORTED,
+};
+use crate::files::ops::{
+    check_rename_to_policy_filename, copy_with_policy, exists_with_policy, file_generation,
+    initialize_workspace_files, link_info_with_policy, mode_with_policy, modified_ms_with_policy,
+    read_bytes_with_policy, read_capped_with_policy, read_swap_side_with_policy, read_with_policy,
+    rename_to_policy_filename, replace_with_policy, set_mode_with_policy, swap_with_policy,
+    symlink_with_policy, touch_with_policy, truncate_with_policy, writable_with_policy,
+    write_with_policy, WorkspaceInitReport,
+};
+use crate::files::policy::{
+    describe_rules, policy_for, rules_for, FileKind, FilePolicy, FileScope, PolicyExplanation,
+    ScopeRootOverrides, ScopedFileRef, SizeLimit,
+};
+use crate::files::search::{
+    find_matching_lines, looks_binary, SearchHit, SearchMatcher, MAX_SEARCH_RESULTS,
+};
+use crate::files::template::{
+    builtin_default, render_template, render_template_preview, RenderedTemplate,
+};
+use crate::files::watch::{run_scope_watch, SharedCoalescer};
+use crate::shared::config_lock_core;
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::config_toml_core;
+use crate::shared::file_snapshot_core::store_owner;
 use crate::types::WorkspaceEntry;
 
-fn resolve_default_codex_home() -> Result<PathBuf, String> {
-    codex_home::resolve_default_codex_home()
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScopeUsage {
+    pub(crate) scope: FileScope,
+    pub(crate) root: String,
+    pub(crate) bytes: u64,
+    pub(crate) file_count: u64,
+    pub(crate) skipped_entries: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScopeDiskUsage {
+    pub(crate) scopes: Vec<ScopeUsage>,
+    pub(crate) total_bytes: u64,
+}
+
+/// Owner read/write only, for kinds that may hold secrets.
+const PRIVATE_FILE_MODE: u32 = 0o600;
+
+/// Under the app data dir; holds the copies `file_reset_to_default` backs up.
+pub(crate) const BACKUPS_DIR: &str = "file-backups";
+
+/// A scoped file that may hold secrets but is not private to its owner, or
+/// could not be checked.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PermIssue {
+    pub(crate) file: ScopedFileRef,
+    pub(crate) path: String,
+    /// Octal permission bits such as `"644"`; `None` where there are none.
+    pub(crate) mode: Option<String>,
+    pub(crate) problem: String,
+    pub(crate) suggested_fix: Option<String>,
+}
+
+/// Running `scope_watch` tasks keyed by watch id, and the coalescer they all
+/// emit through.
+#[derive(Default)]
+pub(crate) struct ScopeWatchers {
+    pub(crate) tasks: Mutex<HashMap<String, tokio::task::AbortHandle>>,
+    pub(crate) coalescer: SharedCoalescer,
+}
+
+/// The resolved path for one `ScopedFileRef`, or why it could not be resolved.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PathResult {
+    pub(crate) file: ScopedFileRef,
+    pub(crate) path: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+/// One file of a `file_read_many` batch, or why it could not be read.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileReadResult {
+    pub(crate) file: ScopedFileRef,
+    pub(crate) response: Option<TextFileResponse>,
+    pub(crate) error: Option<String>,
+}
+
+/// `cancelled` is set when the batch stopped early; `results` then holds only
+/// the files read before that.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileReadManyResponse {
+    pub(crate) results: Vec<FileReadResult>,
+    pub(crate) cancelled: bool,
+}
+
+/// Where the time of a remote `file_read_timed` went, in milliseconds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteReadTiming {
+    /// The round trip minus the backend's own read.
+    pub(crate) network_ms: u64,
+    /// The read on the backend, as it reported it.
+    pub(crate) remote_read_ms: u64,
+    /// Decoding the response here.
+    pub(crate) local_ms: u64,
+}
+
+/// A scoped-file read with how long it took; `remote` is set in remote mode.
+/// `modified_ms` is what to pass as `expected_modified_ms` when saving.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TimedRead {
+    #[serde(flatten)]
+    pub(crate) response: TextFileResponse,
+    pub(crate) modified_ms: Option<u64>,
+    pub(crate) elapsed_ms: u64,
+    pub(crate) remote: Option<RemoteReadTiming>,
+}
+
+/// How set up a workspace is, as `score` out of 100 and the checklist items
+/// still `missing`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Readiness {
+    pub(crate) score: u8,
+    pub(crate) missing: Vec<String>,
+}
+
+/// One AGENTS file that feeds a workspace's effective instructions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentsSource {
+    pub(crate) scope: FileScope,
+    pub(crate) path: String,
+    pub(crate) exists: bool,
+    pub(crate) content: String,
+}
+
+/// The AGENTS sources in precedence order and their concatenation, each
+/// section headed by a marker naming its source.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentsView {
+    pub(crate) sources: Vec<AgentsSource>,
+    pub(crate) merged: String,
+}
+
+/// `workspace_readiness` results keyed by the workspace root and CODEX_HOME
+/// they were computed from, tagged with the write generation of those files.
+/// Relinking a workspace or repinning its home therefore never hits an entry
+/// computed for the old roots.
+pub(crate) type ReadinessCache = Mutex<GenerationCache<Readiness>>;
+
+const READINESS_CHECKS: [&str; 4] = ["workspace_agents", "config_toml", "model", "personality"];
+
+/// The global root without a workspace: the `global` scope override when set,
+/// otherwise the default CODEX_HOME.
+fn resolve_default_codex_home(scope_overrides: &ScopeRootOverrides) -> Result<PathBuf, String> {
+    scope_overrides
+        .root(FileScope::Global, None)
+        .or_else(codex_home::resolve_default_codex_home)
         .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
 }
 
+/// A workspace id that is no longer known falls back to the default home: a
+/// global-scope request does not need the workspace itself. A pinned home must
+/// still exist, so a removed directory is reported rather than recreated.
+async fn resolve_workspace_codex_home_for_id(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    workspace_id: &str,
+) -> Result<PathBuf, String> {
+    let workspaces = workspaces.lock().await;
+    let Some(entry) = workspaces.get(workspace_id) else {
+        return resolve_default_codex_home(scope_overrides);
+    };
+    let parent = entry
+        .parent_id
+        .as_ref()
+        .and_then(|parent_id| workspaces.get(parent_id));
+    let pinned = [Some(entry), parent]
+        .into_iter()
+        .flatten()
+        .any(|candidate| candidate.settings.codex_home.is_some());
+    if !pinned {
+        return resolve_default_codex_home(scope_overrides);
+    }
+    let home = codex_home::resolve_workspace_codex_home(entry, parent)
+        .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())?;
+    if !home.is_dir() {
+        return Err(format!(
+            "Pinned CODEX_HOME {} does not exist",
+            home.display()
+        ));
+    }
+    Ok(home)
+}
+
+/// The home a config command acts on: an explicit `codex_home`, which must be
+/// an existing directory, otherwise the global-scope root for `workspace_id`.
+pub(crate) async fn resolve_config_codex_home_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    codex_home: Option<&str>,
+    workspace_id: Option<&str>,
+) -> Result<PathBuf, String> {
+    match codex_home {
+        Some(value) => {
+            let home = codex_home::validate_codex_home(value)?;
+            if !home.is_dir() {
+                return Err(format!("CODEX_HOME {} does not exist", home.display()));
+            }
+            Ok(home)
+        }
+        None => {
+            resolve_root_core(workspaces, scope_overrides, FileScope::Global, workspace_id).await
+        }
+    }
+}
+
 async fn resolve_workspace_root(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
@@ -27,11 +250,24 @@ async fn resolve_workspace_root(
 
 pub(crate) async fn resolve_root_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
     scope: FileScope,
     workspace_id: Option<&str>,
 ) -> Result<PathBuf, String> {
+    // An override for this workspace beats everything, including its pinned
+    // CODEX_HOME; the plain `global` override only replaces the default home.
+    if let Some(root) = workspace_id.and_then(|id| scope_overrides.root(scope, Some(id))) {
+        return Ok(root);
+    }
     match scope {
-        FileScope::Global => resolve_default_codex_home(),
+        // A workspace id on a global-scope request selects that workspace's
+        // pinned CODEX_HOME.
+        FileScope::Global => match workspace_id {
+            Some(workspace_id) => {
+                resolve_workspace_codex_home_for_id(workspaces, scope_overrides, workspace_id).await
+            }
+            None => resolve_default_codex_home(scope_overrides),
+        },
         FileScope::Workspace => {
             let workspace_id = workspace_id.ok_or_else(|| "workspaceId is required".to_string())?;
             resolve_workspace_root(workspaces, workspace_id).await
@@ -39,25 +275,1620 @@ pub(crate) async fn resolve_root_core(
     }
 }
 
+/// Resolves `scope`/`kind`/`workspace_id` the way reads and writes would, without
+/// touching the file itself.
+pub(crate) async fn policy_explain_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+) -> PolicyExplanation {
+    let mut explanation = PolicyExplanation {
+        scope,
+        kind,
+        valid: false,
+        reason: None,
+        resolved_path: None,
+        policy: None,
+        rules: Vec::new(),
+        size_limits: Vec::new(),
+    };
+    let policy = match policy_for(scope, kind) {
+        Ok(policy) => policy,
+        Err(reason) => {
+            explanation.reason = Some(reason);
+            return explanation;
+        }
+    };
+    explanation.policy = Some(policy.into());
+    explanation.rules = describe_rules(rules_for(kind));
+    if scope == FileScope::Workspace {
+        explanation.size_limits.push(SizeLimit {
+            name: "zip import entry".to_string(),
+            bytes: MAX_ENTRY_BYTES,
+        });
+    }
+    match resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await {
+        Ok(root) => {
+            explanation.valid = true;
+            explanation.resolved_path =
+                Some(root.join(policy.filename).to_string_lossy().to_string());
+        }
+        Err(reason) => explanation.reason = Some(reason),
+    }
+    explanation
+}
+
 pub(crate) async fn file_read_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
     scope: FileScope,
     kind: FileKind,
     workspace_id: Option<String>,
 ) -> Result<TextFileResponse, String> {
     let policy = policy_for(scope, kind)?;
-    let root = resolve_root_core(workspaces, scope, workspace_id.as_deref()).await?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
     read_with_policy(&root, policy)
 }
 
+/// `file_read_core`, timed, for diagnosing slow reads.
+pub(crate) async fn file_read_timed_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+) -> Result<TimedRead, String> {
+    let started = Instant::now();
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    let response = read_with_policy(&root, policy)?;
+    let modified_ms = modified_ms_with_policy(&root, policy)?;
+    Ok(TimedRead {
+        response,
+        modified_ms,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        remote: None,
+    })
+}
+
+pub(crate) async fn file_read_capped_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    max_bytes: u64,
+) -> Result<CappedRead, String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    read_capped_with_policy(&root, policy, max_bytes)
+}
+
+/// Reads a scoped file as lines, whatever its line endings, and reports the
+/// dominant ending so a later write can keep it.
+pub(crate) async fn file_read_lines_normalized_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+) -> Result<NormalizedLines, String> {
+    let response = file_read_core(workspaces, scope_overrides, scope, kind, workspace_id).await?;
+    if !response.exists {
+        return Ok(NormalizedLines {
+            exists: false,
+            ..split_lines_normalized("")
+        });
+    }
+    Ok(split_lines_normalized(&response.content))
+}
+
+/// Largest range a single `file_read_range` call returns.
+pub(crate) const MAX_RANGE_BYTES: u32 = 1024 * 1024;
+
+pub(crate) async fn file_read_range_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    offset: u64,
+    length: u32,
+) -> Result<FileRangeResponse, String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    let Some(bytes) = read_bytes_with_policy(&root, policy)? else {
+        return Ok(FileRangeResponse {
+            exists: false,
+            offset: 0,
+            total_bytes: 0,
+            data: String::new(),
+        });
+    };
+    let total_bytes = bytes.len() as u64;
+    let start = offset.min(total_bytes) as usize;
+    let end = start
+        .saturating_add(length.min(MAX_RANGE_BYTES) as usize)
+        .min(bytes.len());
+    Ok(FileRangeResponse {
+        exists: true,
+        offset: start as u64,
+        total_bytes,
+        data: STANDARD.encode(&bytes[start..end]),
+    })
+}
+
+/// What a scoped-file write did. `skipped` means the file already held the
+/// content, so nothing was written.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WriteReport {
+    pub(crate) path: String,
+    pub(crate) bytes_written: u64,
+    pub(crate) created: bool,
+    pub(crate) skipped: bool,
+}
+
+/// Returned by `file_write_core` when the caller read an existing file
+/// (`expected_modified_ms` is set) that has since been deleted.
+pub(crate) const DELETED_EXTERNALLY: &str = "DELETED_EXTERNALLY";
+/// Returned by `file_write_core` when the file's mtime no longer matches
+/// `expected_modified_ms`, i.e. something else saved it after the caller read it.
+pub(crate) const MODIFIED_EXTERNALLY: &str = "MODIFIED_EXTERNALLY";
+/// Returned by `file_write` with `verify` when the file read back after the
+/// write does not hold what was written.
+pub(crate) const VERIFY_FAILED: &str = "VERIFY_FAILED";
+
+/// Compares the file with what the caller last read; skipped with `force` or
+/// without an `expected_modified_ms`.
+fn check_unchanged_since_read(
+    root: &PathBuf,
+    policy: FilePolicy,
+    expected_modified_ms: Option<u64>,
+    force: bool,
+) -> Result<(), String> {
+    let Some(expected) = expected_modified_ms.filter(|_| !force) else {
+        return Ok(());
+    };
+    match modified_ms_with_policy(root, policy)? {
+        None => Err(DELETED_EXTERNALLY.to_string()),
+        Some(actual) if actual != expected => Err(MODIFIED_EXTERNALLY.to_string()),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Compares SHA-256 digests of `expected` and a read taken after writing it.
+pub(crate) fn verify_read_back(expected: &str, read_back: &TextFileResponse) -> Result<(), String> {
+    let digest = |text: &str| ring::digest::digest(&ring::digest::SHA256, text.as_bytes());
+    if !read_back.exists
+        || read_back.truncated
+        || digest(expected).as_ref() != digest(&read_back.content).as_ref()
+    {
+        return Err(VERIFY_FAILED.to_string());
+    }
+    Ok(())
+}
+
+/// Reads the scoped file back after a write of `expected`; see
+/// `verify_read_back`.
+pub(crate) async fn file_verify_written_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    expected: &str,
+) -> Result<(), String> {
+    let read_back = file_read_core(workspaces, scope_overrides, scope, kind, workspace_id).await?;
+    verify_read_back(expected, &read_back)
+}
+
+/// Diffs `content` against the file on disk without writing; a missing file
+/// diffs as empty, so every line shows as added.
+pub(crate) async fn file_write_preview_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    content: &str,
+) -> Result<Vec<DiffHunk>, String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    let current = read_with_policy(&root, policy)?;
+    Ok(diff_hunks(&current.content, content))
+}
+
+/// Writes a scoped file. `force` recreates a file that was deleted externally,
+/// or overwrites one saved elsewhere, instead of failing with
+/// `DELETED_EXTERNALLY` or `MODIFIED_EXTERNALLY`.
 pub(crate) async fn file_write_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
     scope: FileScope,
     kind: FileKind,
     workspace_id: Option<String>,
     content: String,
+    expected_modified_ms: Option<u64>,
+    force: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    check_unchanged_since_read(&root, policy, expected_modified_ms, force)?;
+    write_resolved(&root, scope, kind, policy, &content, writer)
+}
+
+/// `file_write_core`, reporting what was written. A file that already holds
+/// `content` is left untouched; one that cannot be read first, e.g. because
+/// it is too large, is written anyway.
+pub(crate) async fn file_write_report_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    content: String,
+    expected_modified_ms: Option<u64>,
+    force: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<WriteReport, String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    check_unchanged_since_read(&root, policy, expected_modified_ms, force)?;
+    let existing = read_with_policy(&root, policy).ok();
+    let existed = !matches!(&existing, Some(existing) if !existing.exists);
+    let path = root.join(policy.filename).to_string_lossy().to_string();
+    if existing.is_some_and(|existing| {
+        existing.exists && !existing.truncated && existing.content == content
+    }) {
+        return Ok(WriteReport {
+            path,
+            bytes_written: 0,
+            created: false,
+            skipped: true,
+        });
+    }
+    write_resolved(&root, scope, kind, policy, &content, writer)?;
+    Ok(WriteReport {
+        path,
+        bytes_written: content.len() as u64,
+        created: !existed,
+        skipped: false,
+    })
+}
+
+fn write_resolved(
+    root: &PathBuf,
+    scope: FileScope,
+    kind: FileKind,
+    policy: FilePolicy,
+    content: &str,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    if matches!((scope, kind), (FileScope::Global, FileKind::Config)) {
+        return config_toml_core::write_global_config_text(root, content, writer);
+    }
+    write_with_policy(writer.generations(), root, policy, content)
+}
+
+/// Moves a workspace-scoped `kind` file from `old_name` to the name the policy
+/// now expects. Without `workspace_id` every workspace is migrated. Returns the
+/// ids of the workspaces whose file was moved. When any workspace cannot be
+/// migrated, nothing is moved and the error names each one.
+pub(crate) async fn policy_rename_kind_file_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    generations: &Generations,
+    workspace_id: Option<String>,
+    kind: FileKind,
+    old_name: String,
+    new_name: String,
+) -> Result<Vec<String>, String> {
+    let policy = policy_for(FileScope::Workspace, kind)?;
+    if new_name != policy.filename {
+        return Err(format!(
+            "`{new_name}` is not the expected filename (`{}`)",
+            policy.filename
+        ));
+    }
+    let targets: Vec<(String, PathBuf)> = {
+        let workspaces = workspaces.lock().await;
+        match workspace_id {
+            Some(workspace_id) => {
+                let entry = workspaces
+                    .get(&workspace_id)
+                    .ok_or_else(|| "workspace not found".to_string())?;
+                vec![(workspace_id, PathBuf::from(&entry.path))]
+            }
+            None => {
+                let mut targets: Vec<(String, PathBuf)> = workspaces
+                    .values()
+                    .map(|entry| (entry.id.clone(), PathBuf::from(&entry.path)))
+                    // Skip workspaces whose folder is gone instead of failing the batch.
+                    .filter(|(_, root)| root.is_dir())
+                    .collect();
+                targets.sort();
+                targets
+            }
+        }
+    };
+
+    // Check every workspace first so one conflict does not leave the batch
+    // half migrated.
+    let mut pending = Vec::new();
+    let mut conflicts = Vec::new();
+    for (id, root) in targets {
+        match check_rename_to_policy_filename(&root, policy, &old_name) {
+            Ok(true) => pending.push((id, root)),
+            Ok(false) => {}
+            Err(err) => conflicts.push(format!("{id}: {err}")),
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(format!("Nothing was renamed; {}", conflicts.join("; ")));
+    }
+
+    let mut migrated = Vec::new();
+    for (id, root) in pending {
+        if rename_to_policy_filename(generations, &root, policy, &old_name)? {
+            migrated.push(id);
+        }
+    }
+    Ok(migrated)
+}
+
+pub(crate) async fn file_touch_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    if matches!((scope, kind), (FileScope::Global, FileKind::Config)) {
+        return config_toml_core::touch_global_config(&root, writer);
+    }
+    touch_with_policy(writer.generations(), &root, policy)
+}
+
+/// Empties the scoped file, or keeps its last `keep_last_bytes` bytes of whole
+/// lines; returns the number of bytes removed. `config.toml` is refused, since
+/// a cut would leave it unparseable.
+pub(crate) async fn file_truncate_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    generations: &Generations,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    keep_last_bytes: Option<u64>,
+) -> Result<u64, String> {
+    if matches!((scope, kind), (FileScope::Global, FileKind::Config)) {
+        return Err("config.toml cannot be truncated".to_string());
+    }
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    truncate_with_policy(generations, &root, policy, keep_last_bytes)
+}
+
+/// Makes the scoped file a symlink to `target`; see `symlink_with_policy`.
+pub(crate) async fn file_write_symlink_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    generations: &Generations,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    target: String,
+) -> Result<(), String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    symlink_with_policy(generations, &root, policy, Path::new(&target))
+}
+
+/// Checks every existing file whose kind may hold secrets, in CODEX_HOME and,
+/// with `workspace_id`, that workspace. Files group or others can access are
+/// reported; without permission bits (Windows) each file is reported as
+/// unchecked instead of failing.
+pub(crate) async fn file_permissions_audit_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    workspace_id: Option<String>,
+) -> Result<Vec<PermIssue>, String> {
+    let mut issues = Vec::new();
+    for scope in FileScope::ALL {
+        if scope == FileScope::Workspace && workspace_id.is_none() {
+            continue;
+        }
+        for kind in FileKind::ALL {
+            if !rules_for(kind).may_hold_secrets {
+                continue;
+            }
+            let Ok(policy) = policy_for(scope, kind) else {
+                continue;
+            };
+            let root =
+                resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref())
+                    .await?;
+            let file = ScopedFileRef {
+                scope,
+                kind,
+                workspace_id: workspace_id
+                    .clone()
+                    .filter(|_| scope == FileScope::Workspace),
+            };
+            let path = root.join(policy.filename).to_string_lossy().to_string();
+            if !PERMISSION_BITS_SUPPORTED {
+                if exists_with_policy(&root, policy)? {
+                    issues.push(PermIssue {
+                        file,
+                        path,
+                        mode: None,
+                        problem: "File permissions cannot be checked on this platform".to_string(),
+                        suggested_fix: None,
+                    });
+                }
+                continue;
+            }
+            let Some(mode) = mode_with_policy(&root, policy)? else {
+                continue;
+            };
+            if mode & 0o077 == 0 {
+                continue;
+            }
+            let audience = match (mode & 0o070 != 0, mode & 0o007 != 0) {
+                (true, true) => "group and others",
+                (true, false) => "its group",
+                _ => "other users",
+            };
+            issues.push(PermIssue {
+                file,
+                problem: format!(
+                    "{} may hold secrets but is accessible to {audience}",
+                    policy.filename
+                ),
+                suggested_fix: Some(format!("chmod 600 {path}")),
+                mode: Some(format!("{mode:o}")),
+                path,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+/// Restricts the scoped file to owner read/write (`0600`); returns whether its
+/// permissions changed.
+pub(crate) async fn file_fix_permissions_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+) -> Result<bool, String> {
+    let policy = policy_for(scope, kind)?;
+    if !PERMISSION_BITS_SUPPORTED {
+        return Err(format!(
+            "File permissions cannot be changed on this platform; restrict access to {} from its file properties",
+            policy.filename
+        ));
+    }
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    set_mode_with_policy(&root, policy, PRIVATE_FILE_MODE)
+}
+
+pub(crate) async fn workspace_initialize_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    generations: &Generations,
+    workspace_id: String,
+    overwrite: bool,
+) -> Result<WorkspaceInitReport, String> {
+    let root = resolve_root_core(
+        workspaces,
+        scope_overrides,
+        FileScope::Workspace,
+        Some(&workspace_id),
+    )
+    .await?;
+    initialize_workspace_files(generations, &root, overwrite)
+}
+
+pub(crate) async fn file_writable_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+) -> Result<bool, String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    writable_with_policy(&root, policy)
+}
+
+pub(crate) async fn file_link_info_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+) -> Result<LinkInfo, String> {
+    let policy = policy_for(scope, kind)?;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    link_info_with_policy(&root, policy)
+}
+
+/// Reads the global then the workspace AGENTS file, the order Codex layers
+/// them in, so later (more specific) sections take precedence. Missing files
+/// contribute empty sections.
+pub(crate) async fn agents_effective_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    workspace_id: String,
+) -> Result<AgentsView, String> {
+    let mut sources = Vec::new();
+    for scope in [FileScope::Global, FileScope::Workspace] {
+        let policy = policy_for(scope, FileKind::Agents)?;
+        let root =
+            resolve_root_core(workspaces, scope_overrides, scope, Some(&workspace_id)).await?;
+        let response = read_with_policy(&root, policy)?;
+        sources.push(AgentsSource {
+            scope,
+            path: root.join(policy.filename).to_string_lossy().to_string(),
+            exists: response.exists,
+            content: response.content,
+        });
+    }
+    let merged = sources
+        .iter()
+        .map(|source| {
+            format!(
+                "<!-- {} AGENTS: {} -->\n{}",
+                source.scope.as_str(),
+                source.path,
+                source.content.trim_end()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Ok(AgentsView { sources, merged })
+}
+
+/// Writes and removes a probe file in the scope root, exercising root
+/// resolution and the filesystem end to end. Unlike `file_writable_core` this
+/// never creates a missing root.
+pub(crate) async fn scope_write_test_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    workspace_id: Option<String>,
+) -> Result<(), String> {
+    let root_context = policy_for(scope, FileKind::Agents)?.root_context;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    write_probe_within(&root, root_context)
+}
+
+/// Scores the workspace AGENTS.md, a parseable config.toml in its CODEX_HOME,
+/// and a configured model and personality. Cached until either file is next
+/// written or deleted, which every config change also goes through.
+pub(crate) async fn workspace_readiness_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    generations: &Generations,
+    cache: &ReadinessCache,
+    workspace_id: String,
+) -> Result<Readiness, String> {
+    let workspace_root = resolve_root_core(
+        workspaces,
+        scope_overrides,
+        FileScope::Workspace,
+        Some(&workspace_id),
+    )
+    .await?;
+    let codex_home =
+        resolve_workspace_codex_home_for_id(workspaces, scope_overrides, &workspace_id).await?;
+    let agents_policy = policy_for(FileScope::Workspace, FileKind::Agents)?;
+    let config_policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let generation = file_generation(
+        generations,
+        &[
+            (workspace_root.as_path(), agents_policy.filename),
+            (codex_home.as_path(), config_policy.filename),
+        ],
+    );
+    let cache_key = format!("{}\n{}", workspace_root.display(), codex_home.display());
+    if let Some(readiness) = cache.lock().await.get(&cache_key, generation) {
+        return Ok(readiness);
+    }
+
+    let agents_exists = exists_with_policy(&workspace_root, agents_policy)?;
+    let config = read_with_policy(&codex_home, config_policy)?;
+    let document = config
+        .exists
+        .then(|| config_toml_core::parse_document(&config.content).ok())
+        .flatten();
+
+    let mut missing = Vec::new();
+    if !agents_exists {
+        missing.push("workspace_agents");
+    }
+    match &document {
+        Some(document) => {
+            if config_toml_core::read_top_level_string_ignore_case(document, "model").is_none() {
+                missing.push("model");
+            }
+            if read_personality_from_document(document).is_none() {
+                missing.push("personality");
+            }
+        }
+        None => missing.extend(["config_toml", "model", "personality"]),
+    }
+    let passed = READINESS_CHECKS.len() - missing.len();
+    let readiness = Readiness {
+        score: (passed * 100 / READINESS_CHECKS.len()) as u8,
+        missing: missing.into_iter().map(str::to_string).collect(),
+    };
+    cache
+        .lock()
+        .await
+        .insert(cache_key, generation, readiness.clone());
+    Ok(readiness)
+}
+
+/// The token of the running `workspace_prewarm`. Starting another replaces it,
+/// which stops the first before its next step.
+pub(crate) type PrewarmSlot = Mutex<Option<String>>;
+
+/// Claims `slot` for a new prewarm, cancelling the one in progress; pass the
+/// token to `workspace_prewarm_core`.
+pub(crate) async fn workspace_prewarm_begin_core(slot: &PrewarmSlot) -> String {
+    let token = uuid::Uuid::new_v4().to_string();
+    *slot.lock().await = Some(token.clone());
+    token
+}
+
+/// Whether `token` still holds `slot`; a prewarm checks before each step.
+pub(crate) async fn prewarm_is_current(slot: &PrewarmSlot, token: &str) -> bool {
+    slot.lock().await.as_deref() == Some(token)
+}
+
+/// The steps a prewarm has finished so far.
+#[derive(Debug, Default)]
+pub(crate) struct PrewarmProgress {
+    warmed: Vec<String>,
+    failed: Vec<PrewarmFailure>,
+}
+
+impl PrewarmProgress {
+    pub(crate) fn record<T>(&mut self, step: &str, result: Result<T, String>) {
+        match result {
+            Ok(_) => self.warmed.push(step.to_string()),
+            Err(error) => self.failed.push(PrewarmFailure {
+                step: step.to_string(),
+                error,
+            }),
+        }
+    }
+}
+
+/// Warms the readiness summary of a just-activated workspace into the
+/// readiness cache. Local file reads are not cached, so there is nothing else
+/// to warm here; remote mode also mirrors a few reads, see the app's
+/// `workspace_prewarm_remote`. Emits
+/// `prewarm-done` unless a newer prewarm took the slot; returns whether it ran
+/// to the end.
+pub(crate) async fn workspace_prewarm_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    generations: &Generations,
+    readiness_cache: &ReadinessCache,
+    slot: &PrewarmSlot,
+    token: &str,
+    workspace_id: String,
+    sink: &E,
+) -> bool {
+    if !prewarm_is_current(slot, token).await {
+        return false;
+    }
+    let mut progress = PrewarmProgress::default();
+    let readiness = workspace_readiness_core(
+        workspaces,
+        scope_overrides,
+        generations,
+        readiness_cache,
+        workspace_id.clone(),
+    )
+    .await;
+    progress.record("readiness", readiness);
+    workspace_prewarm_finish_core(slot, token, workspace_id, progress, sink).await
+}
+
+/// Releases `slot` and emits `prewarm-done` with `progress`, unless a newer
+/// prewarm took the slot; returns whether it did.
+pub(crate) async fn workspace_prewarm_finish_core<E: EventSink>(
+    slot: &PrewarmSlot,
+    token: &str,
+    workspace_id: String,
+    progress: PrewarmProgress,
+    sink: &E,
+) -> bool {
+    {
+        let mut slot = slot.lock().await;
+        if slot.as_deref() != Some(token) {
+            return false;
+        }
+        *slot = None;
+    }
+    sink.emit_prewarm_done(PrewarmDone {
+        workspace_id,
+        warmed: progress.warmed,
+        failed: progress.failed,
+    });
+    true
+}
+
+/// Renders `template` and writes it through `file_write_core`. Built-in
+/// variables (`date`, and `workspace_id`/`workspace_name` for a workspace) can
+/// be overridden by `vars`.
+pub(crate) async fn file_write_templated_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    template: String,
+    vars: HashMap<String, String>,
+    strict: bool,
+    writer: ConfigWriter<'_>,
 ) -> Result<(), String> {
+    let all_vars = template_vars(workspaces, workspace_id.as_deref(), vars).await;
+    let content = render_template(&template, &all_vars, strict)?;
+    file_write_core(
+        workspaces,
+        scope_overrides,
+        scope,
+        kind,
+        workspace_id,
+        content,
+        None,
+        false,
+        writer,
+    )
+    .await
+}
+
+/// Renders `template` with the same variables `file_write_templated_core`
+/// would use, without writing anything.
+pub(crate) async fn file_render_template_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    template: String,
+    vars: HashMap<String, String>,
+    workspace_id: Option<String>,
+    strict: bool,
+) -> RenderedTemplate {
+    let all_vars = template_vars(workspaces, workspace_id.as_deref(), vars).await;
+    render_template_preview(&template, &all_vars, strict)
+}
+
+/// Built-in variables overridden by `vars`.
+async fn template_vars(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: Option<&str>,
+    vars: HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut all_vars = HashMap::from([(
+        "date".to_string(),
+        chrono::Local::now().format("%Y-%m-%d").to_string(),
+    )]);
+    if let Some(workspace_id) = workspace_id {
+        let workspaces = workspaces.lock().await;
+        if let Some(entry) = workspaces.get(workspace_id) {
+            all_vars.insert("workspace_id".to_string(), entry.id.clone());
+            all_vars.insert("workspace_name".to_string(), entry.name.clone());
+        }
+    }
+    all_vars.extend(vars);
+    all_vars
+}
+
+/// Overwrites the file with its built-in default. With `backup`, an existing
+/// file is first copied as is to a timestamped backup under the app data dir's
+/// `BACKUPS_DIR`, where `cleanup_orphans` removes it once expired. Returns
+/// whether a backup was made.
+pub(crate) async fn file_reset_to_default_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    data_dir: &Path,
+    scope: FileScope,
+    kind: FileKind,
+    workspace_id: Option<String>,
+    backup: bool,
+    writer: ConfigWriter<'_>,
+) -> Result<bool, String> {
+    let Some(content) = builtin_default(kind) else {
+        return Err(format!("{kind:?} files have no built-in default"));
+    };
     let policy = policy_for(scope, kind)?;
-    let root = resolve_root_core(workspaces, scope, workspace_id.as_deref()).await?;
-    write_with_policy(&root, policy, &content)
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    let path = root.join(policy.filename);
+    let backed_up = backup && path.is_file();
+    if backed_up {
+        let dir = backup_dir(data_dir, scope, workspace_id.as_deref());
+        std::fs::create_dir_all(&dir)
+            .map_err(|err| format!("Failed to create backup directory: {err}"))?;
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let backup_path = dir.join(format!("{}.{stamp}.bak", policy.filename));
+        std::fs::copy(&path, &backup_path)
+            .map_err(|err| format!("Failed to back up {}: {err}", policy.filename))?;
+    }
+    file_write_core(
+        workspaces,
+        scope_overrides,
+        scope,
+        kind,
+        workspace_id,
+        content.to_string(),
+        None,
+        false,
+        writer,
+    )
+    .await?;
+    Ok(backed_up)
+}
+
+/// Starts watching the scope root for changes and returns the watch id that
+/// tags its `scope-changed` events.
+pub(crate) async fn scope_watch_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    watchers: &ScopeWatchers,
+    sink: E,
+    scope: FileScope,
+    workspace_id: Option<String>,
+) -> Result<String, String> {
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    let root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve scope root: {err}"))?;
+    if !root.is_dir() {
+        return Err("Scope root is not a directory".to_string());
+    }
+    let watch_id = uuid::Uuid::new_v4().to_string();
+    watchers.coalescer.register_watch(&watch_id, &root);
+    let task = tokio::spawn(run_scope_watch(
+        sink,
+        watchers.coalescer.clone(),
+        watch_id.clone(),
+        root,
+    ));
+    watchers
+        .tasks
+        .lock()
+        .await
+        .insert(watch_id.clone(), task.abort_handle());
+    Ok(watch_id)
+}
+
+/// Sets how long a path must stay quiet before its change is emitted, for
+/// every scope watch.
+pub(crate) fn scope_watch_set_coalesce_window_core(
+    watchers: &ScopeWatchers,
+    window_ms: u64,
+) -> Result<(), String> {
+    watchers
+        .coalescer
+        .set_window(std::time::Duration::from_millis(window_ms))
+}
+
+/// Stops a watch; returns `false` if the id is unknown.
+pub(crate) async fn scope_unwatch_core(watchers: &ScopeWatchers, watch_id: &str) -> bool {
+    match watchers.tasks.lock().await.remove(watch_id) {
+        Some(task) => {
+            task.abort();
+            watchers.coalescer.unregister_watch(watch_id);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Resolves the on-disk path of every request; a failure only affects its own
+/// result.
+pub(crate) async fn file_paths_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    requests: Vec<ScopedFileRef>,
+) -> Vec<PathResult> {
+    let mut results = Vec::with_capacity(requests.len());
+    for file in requests {
+        let resolved = match policy_for(file.scope, file.kind) {
+            Ok(policy) => resolve_root_core(
+                workspaces,
+                scope_overrides,
+                file.scope,
+                file.workspace_id.as_deref(),
+            )
+            .await
+            .map(|root| root.join(policy.filename).to_string_lossy().to_string()),
+            Err(err) => Err(err),
+        };
+        let (path, error) = match resolved {
+            Ok(path) => (Some(path), None),
+            Err(err) => (None, Some(err)),
+        };
+        results.push(PathResult { file, path, error });
+    }
+    results
+}
+
+/// Reads `requests` in order while `request_id` stays in `in_flight`;
+/// removing it (see `remote_cancel`) stops the batch before the next file.
+pub(crate) async fn file_read_many_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    in_flight: &Mutex<HashSet<String>>,
+    requests: Vec<ScopedFileRef>,
+    request_id: &str,
+) -> FileReadManyResponse {
+    in_flight.lock().await.insert(request_id.to_string());
+    let mut results = Vec::with_capacity(requests.len());
+    let mut cancelled = false;
+    for file in requests {
+        if !in_flight.lock().await.contains(request_id) {
+            cancelled = true;
+            break;
+        }
+        let read = file_read_core(
+            workspaces,
+            scope_overrides,
+            file.scope,
+            file.kind,
+            file.workspace_id.clone(),
+        )
+        .await;
+        let (response, error) = match read {
+            Ok(response) => (Some(response), None),
+            Err(err) => (None, Some(err)),
+        };
+        results.push(FileReadResult {
+            file,
+            response,
+            error,
+        });
+    }
+    in_flight.lock().await.remove(request_id);
+    FileReadManyResponse { results, cancelled }
+}
+
+/// Cancels a `file_read_many` batch; returns whether it was still running.
+pub(crate) async fn remote_cancel_core(
+    in_flight: &Mutex<HashSet<String>>,
+    request_id: &str,
+) -> bool {
+    in_flight.lock().await.remove(request_id)
+}
+
+pub(crate) async fn file_copy_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    generations: &Generations,
+    source: ScopedFileRef,
+    target: ScopedFileRef,
+) -> Result<(), String> {
+    let source_policy = policy_for(source.scope, source.kind)?;
+    let target_policy = policy_for(target.scope, target.kind)?;
+    let source_root = resolve_root_core(
+        workspaces,
+        scope_overrides,
+        source.scope,
+        source.workspace_id.as_deref(),
+    )
+    .await?;
+    let target_root = resolve_root_core(
+        workspaces,
+        scope_overrides,
+        target.scope,
+        target.workspace_id.as_deref(),
+    )
+    .await?;
+    copy_with_policy(
+        generations,
+        &source_root,
+        source_policy,
+        &target_root,
+        target_policy,
+    )
+    .map(|_| ())
+}
+
+/// Backups of one scope's files, one directory per workspace (or `global`).
+fn backup_dir(data_dir: &Path, scope: FileScope, workspace_id: Option<&str>) -> PathBuf {
+    data_dir
+        .join(BACKUPS_DIR)
+        .join(format!("{}-{}", scope.as_str(), store_owner(workspace_id)))
+}
+
+/// Exchanges two existing files of one workspace (or CODEX_HOME). Fails
+/// without touching either when one is missing, truncated or a symbolic link.
+/// Each side is replaced through a temp file and rename; `config.toml` goes
+/// through `write_global_config_text`, so the swap is validated, recorded for
+/// undo and published like any other config write.
+pub(crate) async fn file_swap_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope_a: FileScope,
+    kind_a: FileKind,
+    scope_b: FileScope,
+    kind_b: FileKind,
+    workspace_id: Option<String>,
+    writer: ConfigWriter<'_>,
+) -> Result<(), String> {
+    let policy_a = policy_for(scope_a, kind_a)?;
+    let policy_b = policy_for(scope_b, kind_b)?;
+    let root_a = resolve_root_core(
+        workspaces,
+        scope_overrides,
+        scope_a,
+        workspace_id.as_deref(),
+    )
+    .await?;
+    let root_b = resolve_root_core(
+        workspaces,
+        scope_overrides,
+        scope_b,
+        workspace_id.as_deref(),
+    )
+    .await?;
+    let is_config = |scope, kind| matches!((scope, kind), (FileScope::Global, FileKind::Config));
+    let (config_root, other_root, other_policy) =
+        match (is_config(scope_a, kind_a), is_config(scope_b, kind_b)) {
+            (true, false) => (root_a, root_b, policy_b),
+            (false, true) => (root_b, root_a, policy_a),
+            _ => {
+                return swap_with_policy(writer.generations(), &root_a, policy_a, &root_b, policy_b)
+            }
+        };
+    let _lock = config_lock_core::lock_config_for_write(&config_root)?;
+    let config_policy = policy_for(FileScope::Global, FileKind::Config)?;
+    let config = read_swap_side_with_policy(&config_root, config_policy)?;
+    let other = read_swap_side_with_policy(&other_root, other_policy)?;
+    config_toml_core::parse_document(&other).map_err(|err| {
+        format!(
+            "Cannot swap {} into config.toml: {err}",
+            other_policy.filename
+        )
+    })?;
+    replace_with_policy(writer.generations(), &other_root, other_policy, &config)?;
+    if let Err(err) = config_toml_core::write_global_config_text(&config_root, &other, writer) {
+        let _ = replace_with_policy(writer.generations(), &other_root, other_policy, &other);
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub(crate) async fn file_search_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scope: FileScope,
+    workspace_id: Option<String>,
+    query: String,
+    regex: bool,
+    max_results: u32,
+) -> Result<Vec<SearchHit>, String> {
+    let matcher = SearchMatcher::new(&query, regex)?;
+    let limit = max_results.min(MAX_SEARCH_RESULTS) as usize;
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+
+    let mut hits = Vec::new();
+    for kind in FileKind::ALL {
+        if hits.len() >= limit {
+            break;
+        }
+        let Ok(policy) = policy_for(scope, kind) else {
+            continue;
+        };
+        let Some(bytes) = read_bytes_with_policy(&root, policy)? else {
+            continue;
+        };
+        if looks_binary(&bytes) {
+            continue;
+        }
+        let Ok(content) = String::from_utf8(bytes) else {
+            continue;
+        };
+        for (line_number, line) in find_matching_lines(&content, &matcher, limit - hits.len()) {
+            hits.push(SearchHit {
+                kind,
+                filename: policy.filename.to_string(),
+                line_number,
+                line,
+            });
+        }
+    }
+    Ok(hits)
+}
+
+pub(crate) async fn scope_disk_usage_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    workspace_id: Option<String>,
+    recursive: bool,
+) -> Result<ScopeDiskUsage, String> {
+    let mut scopes = Vec::new();
+    for scope in [FileScope::Workspace, FileScope::Global] {
+        if scope == FileScope::Workspace && workspace_id.is_none() {
+            continue;
+        }
+        let root =
+            resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+        let measured_root = root.clone();
+        let usage =
+            tokio::task::spawn_blocking(move || measure_dir_usage(&measured_root, recursive))
+                .await
+                .map_err(|err| err.to_string())?;
+        scopes.push(ScopeUsage {
+            scope,
+            root: root.to_string_lossy().to_string(),
+            bytes: usage.bytes,
+            file_count: usage.files,
+            skipped_entries: usage.skipped,
+        });
+    }
+    let total_bytes = scopes.iter().map(|usage| usage.bytes).sum();
+    Ok(ScopeDiskUsage {
+        scopes,
+        total_bytes,
+    })
+}
+
+fn workspace_archive_path(kind: FileKind) -> Option<&'static str> {
+    policy_for(FileScope::Workspace, kind)
+        .ok()
+        .map(|policy| policy.filename)
+}
+
+/// Exports every workspace-scoped file that exists into a zip at `dest_path`,
+/// reporting one progress step per file kind.
+pub(crate) async fn workspace_export_zip_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    dest_path: String,
+    reporter: &OperationReporter<E>,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let result: Result<Vec<ArchiveEntry>, String> = async {
+        let dest = PathBuf::from(dest_path.trim());
+        if dest.as_os_str().is_empty() {
+            return Err("Export path is required".to_string());
+        }
+        let root = resolve_workspace_root(workspaces, &workspace_id).await?;
+        let policies: Vec<_> = FileKind::ALL
+            .into_iter()
+            .filter_map(|kind| {
+                policy_for(FileScope::Workspace, kind)
+                    .ok()
+                    .map(|policy| (kind, policy))
+            })
+            .collect();
+        let total = policies.len() as u32;
+        let mut files = Vec::new();
+        for (index, (kind, policy)) in policies.into_iter().enumerate() {
+            reporter.progress(index as u32, total, Some(policy.filename));
+            let response = read_with_policy(&root, policy)?;
+            if !response.exists {
+                continue;
+            }
+            let entry = ArchiveEntry {
+                kind,
+                path: policy.filename.to_string(),
+            };
+            files.push((entry, response.content.into_bytes()));
+        }
+        write_archive(&dest, &files)?;
+        reporter.progress(total, total, None);
+        Ok(files.into_iter().map(|(entry, _)| entry).collect())
+    }
+    .await;
+    reporter.finish(result)
+}
+
+/// Restores workspace-scoped files from a zip written by `workspace_export_zip_core`,
+/// overwriting files that already exist. Watches on the workspace see the
+/// resulting changes as one `batch-changed` event, tagged with the operation
+/// id if any.
+pub(crate) async fn workspace_import_zip_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    generations: &Generations,
+    coalescer: &SharedCoalescer,
+    workspace_id: String,
+    source_path: String,
+    reporter: &OperationReporter<E>,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let batch_id = reporter
+        .operation_id()
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let result: Result<Vec<ArchiveEntry>, String> = async {
+        let source = PathBuf::from(source_path.trim());
+        if source.as_os_str().is_empty() {
+            return Err("Import path is required".to_string());
+        }
+        let root = resolve_workspace_root(workspaces, &workspace_id).await?;
+        let batch = coalescer.begin_batch(reporter.sink(), batch_id, &root);
+        let imported = import_archive_files(generations, &root, &source, reporter);
+        batch.finish().await;
+        imported
+    }
+    .await;
+    reporter.finish(result)
+}
+
+fn import_archive_files<E: EventSink>(
+    generations: &Generations,
+    root: &PathBuf,
+    source: &Path,
+    reporter: &OperationReporter<E>,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let files = read_archive(source, workspace_archive_path)?;
+    let total = files.len() as u32;
+    let mut imported = Vec::with_capacity(files.len());
+    for (entry, content) in files {
+        reporter.progress(imported.len() as u32, total, Some(&entry.path));
+        let policy = policy_for(FileScope::Workspace, entry.kind)?;
+        let content = String::from_utf8(content)
+            .map_err(|_| format!("`{}` in import archive is not valid UTF-8", entry.path))?;
+        write_with_policy(generations, root, policy, &content)?;
+        imported.push(entry);
+    }
+    reporter.progress(total, total, None);
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        file_reset_to_default_core, file_write_core, policy_rename_kind_file_core,
+        resolve_root_core, verify_read_back, workspace_readiness_core, BACKUPS_DIR,
+        DELETED_EXTERNALLY, MODIFIED_EXTERNALLY, VERIFY_FAILED,
+    };
+    use crate::files::generation::Generations;
+    use crate::files::io::TextFileResponse;
+    use crate::files::policy::{scope_override_key, FileKind, FileScope, ScopeRootOverrides};
+    use crate::shared::config_patch_core::ConfigWriter;
+    use crate::shared::orphans_core::{cleanup_orphans_core, OrphanKind};
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::collections::{BTreeMap, HashMap};
+    use tokio::sync::Mutex;
+    use uuid::Uuid;
+
+    fn temp_root(label: &str) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{label}-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        root
+    }
+
+    fn entry(id: &str, root: &std::path::Path) -> (String, WorkspaceEntry) {
+        let entry = WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: root.to_string_lossy().to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+        (id.to_string(), entry)
+    }
+
+    #[test]
+    fn write_detects_external_deletion_and_modification() {
+        let root = temp_root("write");
+        let workspaces = Mutex::new(HashMap::from([entry("w1", &root)]));
+        let scope_overrides = ScopeRootOverrides::default();
+        let write = |content: &str, expected: Option<u64>, force: bool| {
+            tokio::runtime::Runtime::new()
+                .expect("runtime")
+                .block_on(file_write_core(
+                    &workspaces,
+                    &scope_overrides,
+                    FileScope::Workspace,
+                    FileKind::Agents,
+                    Some("w1".to_string()),
+                    content.to_string(),
+                    expected,
+                    force,
+                    ConfigWriter::silent("test"),
+                ))
+        };
+        let path = root.join("AGENTS.md");
+        let mtime = |path: &std::path::Path| {
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .expect("mtime")
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("epoch")
+                .as_millis() as u64
+        };
+
+        write("one", None, false).expect("first write");
+        let read_at = mtime(&path);
+        write("two", Some(read_at), false).expect("unchanged since read");
+
+        assert_eq!(
+            write("three", Some(read_at.saturating_sub(1_000)), false),
+            Err(MODIFIED_EXTERNALLY.to_string())
+        );
+
+        std::fs::remove_file(&path).expect("delete externally");
+        assert_eq!(
+            write("three", Some(read_at), false),
+            Err(DELETED_EXTERNALLY.to_string())
+        );
+        assert!(!path.exists(), "a rejected save must not recreate the file");
+        write("three", Some(read_at), true).expect("forced write");
+        assert_eq!(std::fs::read_to_string(&path).expect("read"), "three");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn verify_read_back_needs_the_whole_written_content() {
+        let read = |exists: bool, content: &str, truncated: bool| TextFileResponse {
+            exists,
+            content: content.to_string(),
+            truncated,
+        };
+        assert_eq!(verify_read_back("new", &read(true, "new", false)), Ok(()));
+        for stale in [
+            read(true, "old", false),
+            read(false, "", false),
+            read(true, "new", true),
+        ] {
+            assert_eq!(
+                verify_read_back("new", &stale),
+                Err(VERIFY_FAILED.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn reset_backups_go_to_the_data_dir_and_expire_through_cleanup() {
+        let root = temp_root("reset");
+        let data_dir = temp_root("reset-data");
+        std::fs::write(root.join("AGENTS.md"), "custom rules").expect("write agents");
+        let workspaces = Mutex::new(HashMap::from([entry("w1", &root)]));
+        let scope_overrides = ScopeRootOverrides::default();
+        let runtime = tokio::runtime::Runtime::new().expect("runtime");
+
+        let backed_up = runtime
+            .block_on(file_reset_to_default_core(
+                &workspaces,
+                &scope_overrides,
+                &data_dir,
+                FileScope::Workspace,
+                FileKind::Agents,
+                Some("w1".to_string()),
+                true,
+                ConfigWriter::silent("test"),
+            ))
+            .expect("reset");
+        assert!(backed_up);
+        let siblings: Vec<_> = std::fs::read_dir(&root)
+            .expect("read root")
+            .flatten()
+            .map(|entry| entry.file_name())
+            .collect();
+        assert_eq!(siblings, vec!["AGENTS.md"]);
+        let backup_dir = data_dir.join(BACKUPS_DIR).join("workspace-w1");
+        let backup = std::fs::read_dir(&backup_dir)
+            .expect("backup dir")
+            .flatten()
+            .map(|entry| entry.path())
+            .next()
+            .expect("backup");
+        assert_eq!(
+            std::fs::read_to_string(&backup).expect("read backup"),
+            "custom rules"
+        );
+
+        let cleanup_backups = |dry_run| {
+            runtime
+                .block_on(cleanup_orphans_core(
+                    &workspaces,
+                    &scope_overrides,
+                    &data_dir,
+                    dry_run,
+                ))
+                .expect("cleanup")
+                .into_iter()
+                .filter(|orphan| orphan.kind == OrphanKind::Backup)
+                .collect::<Vec<_>>()
+        };
+        assert!(cleanup_backups(true).is_empty(), "a fresh backup is kept");
+        let expired =
+            std::time::SystemTime::now() - std::time::Duration::from_secs(31 * 24 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(&backup)
+            .and_then(|file| file.set_modified(expired))
+            .expect("age backup");
+        let removed = cleanup_backups(false);
+        assert_eq!(removed.len(), 1);
+        assert!(removed[0].removed);
+        assert!(!backup.exists());
+
+        for dir in [&root, &data_dir] {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    #[test]
+    fn readiness_is_not_reused_after_a_workspace_is_relinked() {
+        let bare = temp_root("readiness-bare");
+        let ready = temp_root("readiness-ready");
+        let home = temp_root("readiness-home");
+        std::fs::write(ready.join("AGENTS.md"), "rules").expect("write agents");
+        let (id, mut workspace) = entry("w1", &bare);
+        workspace.settings.codex_home = Some(home.to_string_lossy().to_string());
+        let workspaces = Mutex::new(HashMap::from([(id, workspace)]));
+        let scope_overrides = ScopeRootOverrides::default();
+        let generations = Generations::new();
+        let cache = Default::default();
+        let runtime = tokio::runtime::Runtime::new().expect("runtime");
+        let readiness = || {
+            runtime
+                .block_on(workspace_readiness_core(
+                    &workspaces,
+                    &scope_overrides,
+                    &generations,
+                    &cache,
+                    "w1".to_string(),
+                ))
+                .expect("readiness")
+        };
+
+        assert!(readiness()
+            .missing
+            .contains(&"workspace_agents".to_string()));
+        runtime.block_on(async {
+            let mut workspaces = workspaces.lock().await;
+            let workspace = workspaces.get_mut("w1").expect("workspace");
+            workspace.path = ready.to_string_lossy().to_string();
+        });
+        assert!(!readiness()
+            .missing
+            .contains(&"workspace_agents".to_string()));
+
+        for dir in [&bare, &ready, &home] {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    #[test]
+    fn scope_overrides_apply_per_workspace_after_the_pinned_home() {
+        let folder = temp_root("override-folder");
+        let pinned_home = temp_root("override-pinned");
+        let default_home = temp_root("override-default");
+        let custom = temp_root("override-custom");
+        let pinned_id = format!("pinned-{}", Uuid::new_v4());
+        let other_id = format!("other-{}", Uuid::new_v4());
+        let (_, mut pinned) = entry(&pinned_id, &folder);
+        pinned.settings.codex_home = Some(pinned_home.to_string_lossy().to_string());
+        let workspaces = Mutex::new(HashMap::from([
+            (pinned_id.clone(), pinned),
+            entry(&other_id, &folder),
+        ]));
+        let key = |scope, workspace_id: Option<&str>| {
+            scope_override_key(scope, workspace_id).expect("key")
+        };
+        let custom_path = custom.to_string_lossy().to_string();
+        let scope_overrides = ScopeRootOverrides::new(&BTreeMap::from([
+            (
+                key(FileScope::Global, None),
+                default_home.to_string_lossy().to_string(),
+            ),
+            (
+                key(FileScope::Workspace, Some(&other_id)),
+                custom_path.clone(),
+            ),
+            (key(FileScope::Global, Some(&other_id)), custom_path.clone()),
+            // Unkeyed workspace overrides predate per-workspace keys.
+            ("workspace".to_string(), custom_path),
+        ]));
+        assert!(scope_override_key(FileScope::Workspace, None).is_err());
+        let runtime = tokio::runtime::Runtime::new().expect("runtime");
+        let resolve = |scope, workspace_id: &str| {
+            runtime
+                .block_on(resolve_root_core(
+                    &workspaces,
+                    &scope_overrides,
+                    scope,
+                    Some(workspace_id),
+                ))
+                .expect("resolve")
+        };
+
+        assert_eq!(resolve(FileScope::Workspace, &pinned_id), folder);
+        assert_eq!(resolve(FileScope::Global, &pinned_id), pinned_home);
+        assert_eq!(resolve(FileScope::Workspace, &other_id), custom);
+        assert_eq!(resolve(FileScope::Global, &other_id), custom);
+        assert_eq!(resolve(FileScope::Global, "unknown"), default_home);
+        let unset = ScopeRootOverrides::default();
+        let resolved = runtime.block_on(resolve_root_core(
+            &workspaces,
+            &unset,
+            FileScope::Workspace,
+            Some(&other_id),
+        ));
+        assert_eq!(
+            resolved.expect("resolve"),
+            folder,
+            "overrides are per state"
+        );
+
+        for dir in [&folder, &pinned_home, &default_home, &custom] {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    #[test]
+    fn rename_kind_file_moves_nothing_when_any_workspace_conflicts() {
+        let clean = temp_root("rename-clean");
+        let conflicting = temp_root("rename-conflict");
+        std::fs::write(clean.join("AGENT.md"), "clean").expect("write old name");
+        std::fs::write(conflicting.join("AGENT.md"), "old").expect("write old name");
+        std::fs::write(conflicting.join("AGENTS.md"), "new").expect("write new name");
+        let workspaces = Mutex::new(HashMap::from([
+            entry("clean", &clean),
+            entry("conflicting", &conflicting),
+        ]));
+        let rename = || {
+            tokio::runtime::Runtime::new()
+                .expect("runtime")
+                .block_on(policy_rename_kind_file_core(
+                    &workspaces,
+                    &Generations::new(),
+                    None,
+                    FileKind::Agents,
+                    "AGENT.md".to_string(),
+                    "AGENTS.md".to_string(),
+                ))
+        };
+
+        let err = rename().expect_err("conflict");
+        assert!(err.contains("conflicting"), "{err}");
+        assert!(
+            clean.join("AGENT.md").exists(),
+            "no workspace may be migrated"
+        );
+
+        std::fs::remove_file(conflicting.join("AGENT.md")).expect("resolve conflict");
+        assert_eq!(rename(), Ok(vec!["clean".to_string()]));
+        assert_eq!(
+            std::fs::read_to_string(clean.join("AGENTS.md")).expect("read"),
+            "clean"
+        );
+
+        let _ = std::fs::remove_dir_all(&clean);
+        let _ = std::fs::remove_dir_all(&conflicting);
+    }
 }
@@ -2,16 +2,28 @@ pub(crate) mod account;
 pub(crate) mod agents_config_core;
 pub(crate) mod codex_aux_core;
 pub(crate) mod codex_core;
+pub(crate) mod codex_home_core;
 pub(crate) mod codex_update_core;
+pub(crate) mod config_drift_core;
+pub(crate) mod config_duplicates_core;
+pub(crate) mod config_history_core;
+pub(crate) mod config_lock_core;
+pub(crate) mod config_migrate_core;
+pub(crate) mod config_patch_core;
+pub(crate) mod config_roundtrip_core;
 pub(crate) mod config_toml_core;
+pub(crate) mod disk_core;
+pub(crate) mod file_snapshot_core;
 pub(crate) mod files_core;
 pub(crate) mod git_core;
 pub(crate) mod git_rpc;
 pub(crate) mod git_ui_core;
 pub(crate) mod local_usage_core;
+pub(crate) mod orphans_core;
 pub(crate) mod process_core;
 pub(crate) mod prompts_core;
 pub(crate) mod settings_core;
+pub(crate) mod tree_scan_core;
 pub(crate) mod workspace_rpc;
 pub(crate) mod workspaces_core;
 pub(crate) mod worktree_core;
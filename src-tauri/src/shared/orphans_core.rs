@@ -0,0 +1,259 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::files::policy::{policy_for, FileKind, FileScope, ScopeRootOverrides};
+use crate::shared::file_snapshot_core::orphaned_snapshot_stores;
+use crate::shared::files_core::{resolve_root_core, BACKUPS_DIR};
+use crate::types::WorkspaceEntry;
+
+/// Files modified more recently than this are never reported, so a write or
+/// backup in progress is left alone.
+const MIN_ORPHAN_AGE: Duration = Duration::from_secs(60 * 60);
+/// Backups are kept this long before they count as orphans.
+const BACKUP_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OrphanKind {
+    /// A temp file an interrupted atomic write left behind.
+    Temp,
+    /// A `.bak` copy past its retention.
+    Backup,
+    /// The snapshot store of a workspace that was removed.
+    Snapshot,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OrphanEntry {
+    pub(crate) path: String,
+    pub(crate) kind: OrphanKind,
+    pub(crate) size: u64,
+    pub(crate) modified_ms: Option<i64>,
+    /// Always `false` on a dry run.
+    pub(crate) removed: bool,
+    pub(crate) error: Option<String>,
+}
+
+fn scoped_filenames() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = FileScope::ALL
+        .into_iter()
+        .flat_map(|scope| {
+            FileKind::ALL
+                .into_iter()
+                .filter_map(move |kind| policy_for(scope, kind).ok())
+        })
+        .map(|policy| policy.filename)
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// `.<filename>.<uuid>.tmp`, from staged scoped-file writes.
+fn is_scoped_temp(name: &str, filenames: &[&str]) -> bool {
+    filenames.iter().any(|filename| {
+        name.strip_prefix('.')
+            .and_then(|rest| rest.strip_prefix(filename))
+            .and_then(|rest| rest.strip_prefix('.'))
+            .and_then(|rest| rest.strip_suffix(".tmp"))
+            .is_some_and(|id| !id.is_empty())
+    })
+}
+
+/// `<filename>.<YYYYmmdd-HHMMSS>.bak`, from `file_reset_to_default`, in a
+/// directory under `BACKUPS_DIR`.
+fn is_scoped_backup(name: &str, filenames: &[&str]) -> bool {
+    filenames.iter().any(|filename| {
+        name.strip_prefix(filename)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .and_then(|rest| rest.strip_suffix(".bak"))
+            .is_some_and(|stamp| {
+                stamp.len() == 15
+                    && stamp.char_indices().all(|(index, ch)| match index {
+                        8 => ch == '-',
+                        _ => ch.is_ascii_digit(),
+                    })
+            })
+    })
+}
+
+/// `app-kv.json.<uuid>.tmp`, and the `.corrupt-<ms>.bak` copies storage
+/// repair makes, in the app data dir.
+fn data_dir_orphan_kind(name: &str) -> Option<OrphanKind> {
+    if name.starts_with("app-kv.json.") && name.ends_with(".tmp") {
+        return Some(OrphanKind::Temp);
+    }
+    if name.contains(".corrupt-") && name.ends_with(".bak") {
+        return Some(OrphanKind::Backup);
+    }
+    None
+}
+
+/// The age past which an orphan of `kind` may go.
+fn min_age(kind: OrphanKind) -> Duration {
+    match kind {
+        OrphanKind::Temp | OrphanKind::Snapshot => MIN_ORPHAN_AGE,
+        OrphanKind::Backup => BACKUP_RETENTION,
+    }
+}
+
+fn candidate(path: PathBuf, kind: OrphanKind, now: SystemTime) -> Option<OrphanEntry> {
+    let metadata = std::fs::symlink_metadata(&path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    let modified = metadata.modified().ok()?;
+    if now.duration_since(modified).unwrap_or_default() < min_age(kind) {
+        return None;
+    }
+    Some(OrphanEntry {
+        path: path.to_string_lossy().to_string(),
+        kind,
+        size: metadata.len(),
+        modified_ms: modified
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|duration| duration.as_millis() as i64),
+        removed: false,
+        error: None,
+    })
+}
+
+fn scan_dir(
+    dir: &Path,
+    classify: impl Fn(&str) -> Option<OrphanKind>,
+) -> Vec<(PathBuf, OrphanKind)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let kind = classify(name.to_str()?)?;
+            Some((entry.path(), kind))
+        })
+        .collect()
+}
+
+/// Lists stray temp files in every scope root and the app data dir, expired
+/// backups, and snapshot stores of removed workspaces. Anything modified
+/// within the last hour is skipped. Unless `dry_run`, each one is deleted.
+pub(crate) async fn cleanup_orphans_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    data_dir: &Path,
+    dry_run: bool,
+) -> Result<Vec<OrphanEntry>, String> {
+    let workspace_ids: Vec<String> = workspaces.lock().await.keys().cloned().collect();
+    let mut roots = BTreeSet::new();
+    if let Ok(root) = resolve_root_core(workspaces, scope_overrides, FileScope::Global, None).await
+    {
+        roots.insert(root);
+    }
+    for workspace_id in &workspace_ids {
+        for scope in FileScope::ALL {
+            if let Ok(root) =
+                resolve_root_core(workspaces, scope_overrides, scope, Some(workspace_id)).await
+            {
+                roots.insert(root);
+            }
+        }
+    }
+
+    let filenames = scoped_filenames();
+    let mut found = Vec::new();
+    for root in &roots {
+        found.extend(scan_dir(root, |name| {
+            is_scoped_temp(name, &filenames).then_some(OrphanKind::Temp)
+        }));
+    }
+    if let Ok(entries) = std::fs::read_dir(data_dir.join(BACKUPS_DIR)) {
+        for entry in entries.flatten() {
+            found.extend(scan_dir(&entry.path(), |name| {
+                is_scoped_backup(name, &filenames).then_some(OrphanKind::Backup)
+            }));
+        }
+    }
+    found.extend(scan_dir(data_dir, data_dir_orphan_kind));
+    found.extend(
+        orphaned_snapshot_stores(data_dir, &workspace_ids)
+            .into_iter()
+            .map(|path| (path, OrphanKind::Snapshot)),
+    );
+
+    let now = SystemTime::now();
+    let mut orphans: Vec<OrphanEntry> = found
+        .into_iter()
+        .filter_map(|(path, kind)| candidate(path, kind, now))
+        .collect();
+    orphans.sort_by(|a, b| a.path.cmp(&b.path));
+    if !dry_run {
+        for orphan in &mut orphans {
+            match std::fs::remove_file(&orphan.path) {
+                Ok(()) => orphan.removed = true,
+                Err(err) => orphan.error = Some(err.to_string()),
+            }
+        }
+    }
+    Ok(orphans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        candidate, data_dir_orphan_kind, is_scoped_backup, is_scoped_temp, scoped_filenames,
+        OrphanKind, MIN_ORPHAN_AGE,
+    };
+    use std::time::SystemTime;
+    use uuid::Uuid;
+
+    #[test]
+    fn recognizes_only_app_written_leftovers() {
+        let filenames = scoped_filenames();
+        assert_eq!(filenames, vec!["AGENTS.md", "config.toml"]);
+
+        assert!(is_scoped_temp(".AGENTS.md.0b9c.tmp", &filenames));
+        assert!(!is_scoped_temp(".AGENTS.md..tmp", &filenames));
+        assert!(!is_scoped_temp("notes.tmp", &filenames));
+        assert!(is_scoped_backup(
+            "config.toml.20260101-120000.bak",
+            &filenames
+        ));
+        assert!(!is_scoped_backup("config.toml.old.bak", &filenames));
+        assert!(!is_scoped_backup(
+            "README.md.20260101-120000.bak",
+            &filenames
+        ));
+        assert_eq!(
+            data_dir_orphan_kind("app-kv.json.1234.tmp"),
+            Some(OrphanKind::Temp)
+        );
+        assert_eq!(
+            data_dir_orphan_kind("workspaces.json.corrupt-1700000000000.bak"),
+            Some(OrphanKind::Backup)
+        );
+        assert_eq!(data_dir_orphan_kind("settings.json"), None);
+    }
+
+    #[test]
+    fn fresh_files_are_never_candidates() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-orphans-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join(".AGENTS.md.abc.tmp");
+        std::fs::write(&path, "partial").expect("write temp");
+
+        let now = SystemTime::now();
+        assert!(candidate(path.clone(), OrphanKind::Temp, now).is_none());
+        let later = now + MIN_ORPHAN_AGE * 2;
+        let orphan = candidate(path.clone(), OrphanKind::Temp, later).expect("old enough");
+        assert_eq!(orphan.size, 7);
+        assert!(candidate(path, OrphanKind::Backup, later).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,8 +3,12 @@ use std::path::PathBuf;
 use tokio::sync::Mutex;
 
 use crate::codex::config as codex_config;
+use crate::codex::home::{set_canonicalize_codex_home, set_codex_home_override};
+use crate::files::policy::{scope_override_key, FileScope, ScopeRootOverrides};
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::files_core::ReadinessCache;
 use crate::storage::write_settings;
-use crate::types::AppSettings;
+use crate::types::{AppSettings, FeaturePreset};
 
 fn normalize_personality(value: &str) -> Option<&'static str> {
     match value.trim() {
@@ -14,27 +18,49 @@ fn normalize_personality(value: &str) -> Option<&'static str> {
     }
 }
 
-pub(crate) async fn get_app_settings_core(app_settings: &Mutex<AppSettings>) -> AppSettings {
-    let mut settings = app_settings.lock().await.clone();
-    if let Ok(Some(collaboration_modes_enabled)) = codex_config::read_collaboration_modes_enabled()
-    {
+pub(crate) fn codex_config_values_from_settings(
+    settings: &AppSettings,
+) -> codex_config::CodexConfigValues {
+    codex_config::CodexConfigValues {
+        collaboration_modes_enabled: Some(settings.collaboration_modes_enabled),
+        steer_enabled: Some(settings.steer_enabled),
+        unified_exec_enabled: Some(settings.unified_exec_enabled),
+        apps_enabled: Some(settings.experimental_apps_enabled),
+        personality: Some(settings.personality.clone()),
+    }
+}
+
+pub(crate) fn apply_codex_config_values(
+    settings: &mut AppSettings,
+    values: codex_config::CodexConfigValues,
+) {
+    if let Some(collaboration_modes_enabled) = values.collaboration_modes_enabled {
         settings.collaboration_modes_enabled = collaboration_modes_enabled;
     }
-    if let Ok(Some(steer_enabled)) = codex_config::read_steer_enabled() {
+    if let Some(steer_enabled) = values.steer_enabled {
         settings.steer_enabled = steer_enabled;
     }
-    if let Ok(Some(unified_exec_enabled)) = codex_config::read_unified_exec_enabled() {
+    if let Some(unified_exec_enabled) = values.unified_exec_enabled {
         settings.unified_exec_enabled = unified_exec_enabled;
     }
-    if let Ok(Some(apps_enabled)) = codex_config::read_apps_enabled() {
+    if let Some(apps_enabled) = values.apps_enabled {
         settings.experimental_apps_enabled = apps_enabled;
     }
-    if let Ok(personality) = codex_config::read_personality() {
-        settings.personality = personality
-            .as_deref()
-            .and_then(normalize_personality)
-            .unwrap_or("friendly")
-            .to_string();
+    settings.personality = values
+        .personality
+        .as_deref()
+        .and_then(normalize_personality)
+        .unwrap_or("friendly")
+        .to_string();
+}
+
+pub(crate) async fn get_app_settings_core(
+    app_settings: &Mutex<AppSettings>,
+    scope_overrides: &ScopeRootOverrides,
+) -> AppSettings {
+    let mut settings = app_settings.lock().await.clone();
+    if let Ok(values) = codex_config::read_config_values(scope_overrides) {
+        apply_codex_config_values(&mut settings, values);
     }
     settings
 }
@@ -43,20 +69,176 @@ pub(crate) async fn update_app_settings_core(
     settings: AppSettings,
     app_settings: &Mutex<AppSettings>,
     settings_path: &PathBuf,
+    scope_overrides: &ScopeRootOverrides,
+    writer: ConfigWriter<'_>,
+) -> Result<AppSettings, String> {
+    let _ = codex_config::write_config_values(
+        scope_overrides,
+        &codex_config_values_from_settings(&settings),
+        writer,
+    );
+    persist_app_settings_core(settings, app_settings, settings_path, scope_overrides).await
+}
+
+/// Persists app settings without touching Codex `config.toml`; used when the
+/// Codex-owned values live on a remote backend.
+pub(crate) async fn persist_app_settings_core(
+    settings: AppSettings,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    scope_overrides: &ScopeRootOverrides,
 ) -> Result<AppSettings, String> {
-    let _ = codex_config::write_collaboration_modes_enabled(settings.collaboration_modes_enabled);
-    let _ = codex_config::write_steer_enabled(settings.steer_enabled);
-    let _ = codex_config::write_unified_exec_enabled(settings.unified_exec_enabled);
-    let _ = codex_config::write_apps_enabled(settings.experimental_apps_enabled);
-    let _ = codex_config::write_personality(settings.personality.as_str());
     write_settings(settings_path, &settings)?;
+    set_canonicalize_codex_home(settings.canonicalize_codex_home);
+    set_codex_home_override(settings.codex_home_override.as_deref());
+    scope_overrides.replace(&settings.scope_root_overrides);
     let mut current = app_settings.lock().await;
     *current = settings.clone();
     Ok(settings)
 }
 
-pub(crate) fn get_codex_config_path_core() -> Result<String, String> {
-    codex_config::config_toml_path()
+fn normalize_preset_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Preset name is required".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Captures the current `[features]` flags under `name`, replacing any preset
+/// with that name.
+pub(crate) async fn feature_preset_save_core(
+    name: &str,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<FeaturePreset, String> {
+    let name = normalize_preset_name(name)?;
+    let preset = FeaturePreset {
+        name,
+        flags: codex_config::read_feature_flags(scope_overrides)?,
+    };
+    let mut settings = app_settings.lock().await.clone();
+    match settings
+        .feature_presets
+        .iter_mut()
+        .find(|existing| existing.name == preset.name)
+    {
+        Some(existing) => *existing = preset.clone(),
+        None => settings.feature_presets.push(preset.clone()),
+    }
+    persist_app_settings_core(settings, app_settings, settings_path, scope_overrides).await?;
+    Ok(preset)
+}
+
+pub(crate) async fn feature_preset_list_core(
+    app_settings: &Mutex<AppSettings>,
+) -> Vec<FeaturePreset> {
+    app_settings.lock().await.feature_presets.clone()
+}
+
+pub(crate) async fn find_feature_preset_core(
+    name: &str,
+    app_settings: &Mutex<AppSettings>,
+) -> Result<FeaturePreset, String> {
+    let name = normalize_preset_name(name)?;
+    app_settings
+        .lock()
+        .await
+        .feature_presets
+        .iter()
+        .find(|preset| preset.name == name)
+        .cloned()
+        .ok_or_else(|| format!("Feature preset `{name}` not found"))
+}
+
+pub(crate) async fn feature_preset_apply_core(
+    name: &str,
+    clear_missing: bool,
+    app_settings: &Mutex<AppSettings>,
+    scope_overrides: &ScopeRootOverrides,
+    writer: ConfigWriter<'_>,
+) -> Result<FeaturePreset, String> {
+    let preset = find_feature_preset_core(name, app_settings).await?;
+    codex_config::write_feature_flags(scope_overrides, &preset.flags, clear_missing, writer)?;
+    Ok(preset)
+}
+
+/// Returns `false` when no preset had that name.
+pub(crate) async fn feature_preset_delete_core(
+    name: &str,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<bool, String> {
+    let name = normalize_preset_name(name)?;
+    let mut settings = app_settings.lock().await.clone();
+    let before = settings.feature_presets.len();
+    settings
+        .feature_presets
+        .retain(|preset| preset.name != name);
+    if settings.feature_presets.len() == before {
+        return Ok(false);
+    }
+    persist_app_settings_core(settings, app_settings, settings_path, scope_overrides).await?;
+    Ok(true)
+}
+
+/// Points `scope` at `path`, which must be an existing directory. Returns the
+/// canonical root that was stored.
+/// Points `scope` at `path` for `workspace_id`, or the default global root
+/// without one. Cached readiness is dropped, since it may describe the old root.
+pub(crate) async fn policy_set_scope_override_core(
+    scope: FileScope,
+    workspace_id: Option<&str>,
+    path: &str,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    scope_overrides: &ScopeRootOverrides,
+    readiness_cache: &ReadinessCache,
+) -> Result<String, String> {
+    let key = scope_override_key(scope, workspace_id)?;
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err("Override path is required".to_string());
+    }
+    let root = PathBuf::from(trimmed)
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve override path: {err}"))?;
+    if !root.is_dir() {
+        return Err("Override path is not a directory".to_string());
+    }
+    let root = root.to_string_lossy().to_string();
+    let mut settings = app_settings.lock().await.clone();
+    settings.scope_root_overrides.insert(key, root.clone());
+    persist_app_settings_core(settings, app_settings, settings_path, scope_overrides).await?;
+    readiness_cache.lock().await.clear();
+    Ok(root)
+}
+
+/// Returns `false` when `scope` had no override for `workspace_id`.
+pub(crate) async fn policy_clear_scope_override_core(
+    scope: FileScope,
+    workspace_id: Option<&str>,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    scope_overrides: &ScopeRootOverrides,
+    readiness_cache: &ReadinessCache,
+) -> Result<bool, String> {
+    let key = scope_override_key(scope, workspace_id)?;
+    let mut settings = app_settings.lock().await.clone();
+    if settings.scope_root_overrides.remove(&key).is_none() {
+        return Ok(false);
+    }
+    persist_app_settings_core(settings, app_settings, settings_path, scope_overrides).await?;
+    readiness_cache.lock().await.clear();
+    Ok(true)
+}
+
+pub(crate) fn get_codex_config_path_core(
+    scope_overrides: &ScopeRootOverrides,
+) -> Result<String, String> {
+    codex_config::config_toml_path(scope_overrides)
         .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
         .and_then(|path| {
             path.to_str()
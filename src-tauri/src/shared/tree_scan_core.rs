@@ -0,0 +1,241 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ignore::WalkBuilder;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::backend::events::{
+    EventSink, TreeDone, TreeEntries, TreeNode, TreeNodeKind, TreeTruncated,
+};
+use crate::files::policy::{FileScope, ScopeRootOverrides};
+use crate::shared::files_core::resolve_root_core;
+use crate::types::WorkspaceEntry;
+use crate::utils::normalize_git_path;
+use crate::workspaces::files::should_skip_dir;
+
+/// A scan stops and emits `tree-truncated` after this many entries.
+pub(crate) const TREE_SCAN_MAX_ENTRIES: usize = 20_000;
+const TREE_SCAN_BATCH_SIZE: usize = 200;
+
+/// Ids of running tree scans; `tree_scan_cancel` removes one to stop it.
+pub(crate) type TreeScans = Arc<Mutex<HashSet<String>>>;
+
+/// Resolves the scope root and registers a new scan; the walk itself is
+/// `tree_scan_run_core`, spawned by the caller.
+pub(crate) async fn tree_scan_start_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    scope_overrides: &ScopeRootOverrides,
+    scans: &TreeScans,
+    scope: FileScope,
+    workspace_id: Option<String>,
+) -> Result<(String, PathBuf), String> {
+    let root =
+        resolve_root_core(workspaces, scope_overrides, scope, workspace_id.as_deref()).await?;
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    let scan_id = Uuid::new_v4().to_string();
+    scans.lock().await.insert(scan_id.clone());
+    Ok((scan_id, root))
+}
+
+/// Stops a running scan; returns whether it was still running.
+pub(crate) async fn tree_scan_cancel_core(scans: &TreeScans, scan_id: &str) -> bool {
+    scans.lock().await.remove(scan_id)
+}
+
+fn tree_node(root: &Path, entry: &ignore::DirEntry) -> Option<TreeNode> {
+    let file_type = entry.file_type()?;
+    let kind = if file_type.is_symlink() {
+        TreeNodeKind::Symlink
+    } else if file_type.is_dir() {
+        TreeNodeKind::Dir
+    } else {
+        TreeNodeKind::File
+    };
+    let rel_path = entry.path().strip_prefix(root).ok()?;
+    let path = normalize_git_path(&rel_path.to_string_lossy());
+    if path.is_empty() {
+        return None;
+    }
+    Some(TreeNode {
+        path,
+        kind,
+        depth: entry.depth(),
+    })
+}
+
+struct WalkOutcome {
+    total: usize,
+    truncated: bool,
+    cancelled: bool,
+}
+
+/// Walks `root` the way the file list does (ignore rules applied, hidden
+/// entries kept, symlinks listed but never followed), handing entries to
+/// `emit` in batches. Checks for cancellation between batches.
+fn walk_tree(
+    scans: &TreeScans,
+    root: &Path,
+    scan_id: &str,
+    max_depth: Option<usize>,
+    limit: usize,
+    mut emit: impl FnMut(Vec<TreeNode>),
+) -> WalkOutcome {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .max_depth(max_depth)
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let name = entry.file_name().to_string_lossy();
+                return !should_skip_dir(&name);
+            }
+            true
+        })
+        .build();
+
+    let is_running = || scans.blocking_lock().contains(scan_id);
+    let mut batch = Vec::with_capacity(TREE_SCAN_BATCH_SIZE);
+    let mut outcome = WalkOutcome {
+        total: 0,
+        truncated: false,
+        cancelled: false,
+    };
+    for entry in walker.flatten() {
+        let Some(node) = tree_node(root, &entry) else {
+            continue;
+        };
+        if outcome.total == limit {
+            outcome.truncated = true;
+            break;
+        }
+        batch.push(node);
+        outcome.total += 1;
+        if batch.len() == TREE_SCAN_BATCH_SIZE {
+            if !is_running() {
+                outcome.cancelled = true;
+                return outcome;
+            }
+            emit(std::mem::take(&mut batch));
+        }
+    }
+    if !is_running() {
+        outcome.cancelled = true;
+    } else if !batch.is_empty() {
+        emit(batch);
+    }
+    outcome
+}
+
+/// Runs a scan registered by `tree_scan_start_core`, emitting `tree-entry`
+/// batches, `tree-truncated` if the cap is hit, and always `tree-done` last;
+/// a walk that panics ends with `tree-done` carrying `error`.
+pub(crate) async fn tree_scan_run_core<E: EventSink>(
+    scans: TreeScans,
+    root: PathBuf,
+    scan_id: String,
+    max_depth: Option<usize>,
+    sink: E,
+) {
+    let walk_scans = scans.clone();
+    let walk_id = scan_id.clone();
+    let walk_sink = sink.clone();
+    let outcome = tokio::task::spawn_blocking(move || {
+        walk_tree(
+            &walk_scans,
+            &root,
+            &walk_id,
+            max_depth,
+            TREE_SCAN_MAX_ENTRIES,
+            |entries| {
+                walk_sink.emit_tree_entries(TreeEntries {
+                    scan_id: walk_id.clone(),
+                    entries,
+                })
+            },
+        )
+    })
+    .await;
+    scans.lock().await.remove(&scan_id);
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            sink.emit_tree_done(TreeDone {
+                scan_id,
+                total: 0,
+                cancelled: false,
+                error: Some(format!("Tree scan failed: {err}")),
+            });
+            return;
+        }
+    };
+    if outcome.truncated {
+        sink.emit_tree_truncated(TreeTruncated {
+            scan_id: scan_id.clone(),
+            limit: TREE_SCAN_MAX_ENTRIES,
+        });
+    }
+    sink.emit_tree_done(TreeDone {
+        scan_id,
+        total: outcome.total,
+        cancelled: outcome.cancelled,
+        error: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{walk_tree, TreeScans};
+    use crate::backend::events::{TreeNode, TreeNodeKind};
+    use uuid::Uuid;
+
+    #[test]
+    fn walk_batches_skips_build_dirs_and_truncates() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-tree-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src/nested")).expect("create dirs");
+        std::fs::create_dir_all(root.join("node_modules/pkg")).expect("create dirs");
+        std::fs::write(root.join("src/main.rs"), "").expect("write file");
+        std::fs::write(root.join("src/nested/deep.rs"), "").expect("write file");
+        std::fs::write(root.join("node_modules/pkg/index.js"), "").expect("write file");
+
+        let scans: TreeScans = Default::default();
+        scans.blocking_lock().insert("scan".to_string());
+        let mut nodes: Vec<TreeNode> = Vec::new();
+        let outcome = walk_tree(&scans, &root, "scan", Some(2), 100, |batch| {
+            nodes.extend(batch)
+        });
+        assert!(!outcome.cancelled && !outcome.truncated);
+        let mut paths: Vec<(String, TreeNodeKind)> = nodes
+            .into_iter()
+            .map(|node| (node.path, node.kind))
+            .collect();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            paths,
+            vec![
+                ("src".to_string(), TreeNodeKind::Dir),
+                ("src/main.rs".to_string(), TreeNodeKind::File),
+                ("src/nested".to_string(), TreeNodeKind::Dir),
+            ]
+        );
+        assert_eq!(outcome.total, 3);
+
+        let outcome = walk_tree(&scans, &root, "scan", None, 2, |_| {});
+        assert!(outcome.truncated);
+        assert_eq!(outcome.total, 2);
+
+        scans.blocking_lock().clear();
+        let mut emitted = 0;
+        let outcome = walk_tree(&scans, &root, "scan", None, 100, |_| emitted += 1);
+        assert!(outcome.cancelled);
+        assert_eq!(emitted, 0);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
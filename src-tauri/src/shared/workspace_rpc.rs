@@ -2,6 +2,7 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::shared::workspaces_core::WorkspaceDefaultKey;
 use crate::types::WorkspaceSettings;
 
 #[allow(dead_code)]
@@ -28,14 +29,56 @@ pub(crate) struct SetWorkspaceRuntimeCodexArgsRequest {
     pub(crate) codex_args: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SetWorkspaceCodexHomeRequest {
+    pub(crate) id: String,
+    pub(crate) path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SetWorkspaceDefaultOverrideRequest {
+    pub(crate) workspace_id: String,
+    pub(crate) key: WorkspaceDefaultKey,
+    pub(crate) value: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FeaturePresetApplyAllRequest {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) workspace_ids: Vec<String>,
+    #[serde(default)]
+    pub(crate) operation_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceRelinkRequest {
+    pub(crate) id: String,
+    pub(crate) new_root: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct IsWorkspacePathDirRequest {
     pub(crate) path: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct AddWorkspaceRequest {
     pub(crate) path: String,
+    #[serde(default)]
+    pub(crate) reject_duplicate: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceImportExistingRequest {
+    pub(crate) root_path: String,
+    #[serde(default)]
+    pub(crate) name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1,22 +1,45 @@
 mod connect;
 mod crud_persistence;
+mod defaults;
 mod git_orchestration;
+mod health;
 mod helpers;
 mod io;
+mod profile;
 mod runtime_codex_args;
 mod worktree;
 
 pub(crate) use connect::connect_workspace_core;
 pub(crate) use crud_persistence::{
     add_clone_core, add_workspace_core, add_workspace_from_git_url_core, remove_workspace_core,
-    update_workspace_settings_core,
+    update_workspace_settings_core, workspace_import_existing_core, workspace_list_broken_core,
+    workspace_prune_broken_core, workspace_relink_core, workspace_set_codex_home_core,
+    workspace_state_repair_core, AddedWorkspace, ImportedWorkspace,
+};
+pub(crate) use defaults::{
+    effective_sandbox_core, feature_preset_apply_all_core, layered_feature_overrides,
+    layered_override, resolve_sandbox_posture, workspace_effective_features_core,
+    workspace_effective_model_core, workspace_effective_personality_core,
+    workspace_set_default_override_core, EffectiveFeatureFlag, EffectiveWorkspaceDefault,
+    PresetApplyAllReport, SandboxResolution, WorkspaceDefaultKey,
 };
 pub(crate) use git_orchestration::{apply_worktree_changes_core, run_git_command_unit};
-pub(crate) use helpers::{is_workspace_path_dir_core, list_workspaces_core};
+pub(crate) use health::{
+    workspace_check_core, workspace_health_core, workspace_health_startup_core,
+    WorkspaceHealthCache,
+};
+pub(crate) use helpers::{
+    is_workspace_path_dir_core, list_workspaces_core, workspace_find_duplicates_core,
+    WorkspaceOverlap,
+};
 pub(crate) use io::{
-    get_open_app_icon_core, list_workspace_files_core, open_workspace_in_core,
+    command_target, get_open_app_icon_core, list_workspace_files_core, open_workspace_in_core,
     read_workspace_file_core,
 };
+pub(crate) use profile::{
+    profile_export_core, profile_import_core, ProfileImportReport, ProfileImportRequest,
+    ProfileImportStrategy,
+};
 pub(crate) use runtime_codex_args::{
     set_workspace_runtime_codex_args_core, WorkspaceRuntimeCodexArgsResult,
 };
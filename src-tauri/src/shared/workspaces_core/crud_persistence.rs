@@ -3,28 +3,60 @@ use std::future::Future;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{EventSink, WorkspacesChanged};
 use crate::codex::args::resolve_workspace_codex_args;
-use crate::codex::home::resolve_workspace_codex_home;
+use crate::codex::home::{resolve_workspace_codex_home, validate_codex_home};
 use crate::shared::process_core::kill_child_process_tree;
 use crate::shared::{git_core, worktree_core};
-use crate::storage::write_workspaces;
+use crate::storage::{repair_workspaces, write_workspaces, WorkspaceStateRepairReport};
 use crate::types::{AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings};
 
 use super::connect::{kill_session_by_id, take_live_shared_session, workspace_session_spawn_lock};
-use super::helpers::{normalize_setup_script, normalize_workspace_path_input};
+use super::helpers::{
+    check_new_workspace_root, list_workspaces_core, normalize_setup_script,
+    normalize_workspace_path_input,
+};
+
+/// Managed files `workspace_import_existing_core` looks for, relative to the root.
+const IMPORT_MANAGED_FILES: [&str; 2] = ["AGENTS.md", ".codex/config.toml"];
+
+/// A newly added workspace and what the caller should be told about it, such
+/// as overlapping another workspace's root.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AddedWorkspace {
+    #[serde(flatten)]
+    pub(crate) workspace: WorkspaceInfo,
+    #[serde(default)]
+    pub(crate) warnings: Vec<String>,
+}
+
+/// A workspace adopted from an existing project directory.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportedWorkspace {
+    #[serde(flatten)]
+    pub(crate) workspace: AddedWorkspace,
+    /// Managed files that were already present, relative to the root.
+    pub(crate) found_files: Vec<String>,
+}
 
+/// `name` defaults to the folder name when unset or blank.
 pub(crate) async fn add_workspace_core<F, Fut>(
     path: String,
+    name: Option<String>,
+    reject_duplicate: bool,
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
     spawn_session: F,
-) -> Result<WorkspaceInfo, String>
+) -> Result<AddedWorkspace, String>
 where
     F: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> Fut,
     Fut: Future<Output = Result<Arc<WorkspaceSession>, String>>,
@@ -34,12 +66,21 @@ where
         return Err("Workspace path must be a folder.".to_string());
     }
     let path = normalized_path.to_string_lossy().to_string();
+    let warnings = {
+        let existing: Vec<WorkspaceEntry> = workspaces.lock().await.values().cloned().collect();
+        check_new_workspace_root(&existing, &path, reject_duplicate)?
+    };
 
-    let name = PathBuf::from(&path)
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("Workspace")
-        .to_string();
+    let name = name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| {
+            PathBuf::from(&path)
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Workspace")
+                .to_string()
+        });
     let entry = WorkspaceEntry {
         id: Uuid::new_v4().to_string(),
         name: name.clone(),
@@ -91,15 +132,56 @@ where
         .await;
     sessions.lock().await.insert(entry.id.clone(), session);
 
-    Ok(WorkspaceInfo {
-        id: entry.id,
-        name: entry.name,
-        path: entry.path,
-        connected: true,
-        kind: entry.kind,
-        parent_id: entry.parent_id,
-        worktree: entry.worktree,
-        settings: entry.settings,
+    Ok(AddedWorkspace {
+        workspace: WorkspaceInfo {
+            id: entry.id,
+            name: entry.name,
+            path: entry.path,
+            connected: true,
+            kind: entry.kind,
+            parent_id: entry.parent_id,
+            worktree: entry.worktree,
+            settings: entry.settings,
+        },
+        warnings,
+    })
+}
+
+/// Registers an existing project directory as a workspace. Nothing in it is
+/// created or overwritten; an already registered root is rejected.
+pub(crate) async fn workspace_import_existing_core<F, Fut>(
+    root_path: String,
+    name: Option<String>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    storage_path: &PathBuf,
+    spawn_session: F,
+) -> Result<ImportedWorkspace, String>
+where
+    F: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> Fut,
+    Fut: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+{
+    let root = normalize_workspace_path_input(&root_path);
+    let found_files = IMPORT_MANAGED_FILES
+        .iter()
+        .filter(|relative| root.join(relative).is_file())
+        .map(|relative| relative.to_string())
+        .collect();
+    let workspace = add_workspace_core(
+        root_path,
+        name,
+        true,
+        workspaces,
+        sessions,
+        app_settings,
+        storage_path,
+        spawn_session,
+    )
+    .await?;
+    Ok(ImportedWorkspace {
+        workspace,
+        found_files,
     })
 }
 
@@ -610,6 +692,169 @@ where
     })
 }
 
+/// Pins (or with `None`, unpins) the workspace's `CODEX_HOME`, creating the
+/// directory if needed. A running session keeps its current home until the
+/// workspace reconnects.
+pub(crate) async fn workspace_set_codex_home_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+    id: String,
+    path: Option<String>,
+) -> Result<WorkspaceInfo, String> {
+    let codex_home = match path.as_deref().map(str::trim) {
+        Some(value) if !value.is_empty() => {
+            let home = validate_codex_home(value)?;
+            std::fs::create_dir_all(&home)
+                .map_err(|err| format!("Failed to create {}: {err}", home.display()))?;
+            Some(home.to_string_lossy().to_string())
+        }
+        _ => None,
+    };
+    let entry_snapshot = {
+        let mut workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get_mut(&id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        entry.settings.codex_home = codex_home;
+        let entry_snapshot = entry.clone();
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        write_workspaces(storage_path, &list)?;
+        entry_snapshot
+    };
+    let connected = sessions.lock().await.contains_key(&id);
+    Ok(WorkspaceInfo {
+        id: entry_snapshot.id,
+        name: entry_snapshot.name,
+        path: entry_snapshot.path,
+        connected,
+        kind: entry_snapshot.kind,
+        parent_id: entry_snapshot.parent_id,
+        worktree: entry_snapshot.worktree,
+        settings: entry_snapshot.settings,
+    })
+}
+
+/// Workspaces whose root no longer exists or is not a directory.
+pub(crate) async fn workspace_list_broken_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+) -> Vec<WorkspaceInfo> {
+    list_workspaces_core(workspaces, sessions)
+        .await
+        .into_iter()
+        .filter(|workspace| !Path::new(&workspace.path).is_dir())
+        .collect()
+}
+
+/// Points a workspace at `new_root`, which must be an existing folder.
+pub(crate) async fn workspace_relink_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+    sink: E,
+    id: String,
+    new_root: String,
+) -> Result<WorkspaceInfo, String> {
+    let new_root = normalize_workspace_path_input(&new_root);
+    if !new_root.is_dir() {
+        return Err("Workspace path must be a folder.".to_string());
+    }
+    let new_root = new_root.to_string_lossy().to_string();
+    let entry_snapshot = {
+        let mut workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get_mut(&id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        let previous_root = std::mem::replace(&mut entry.path, new_root);
+        let entry_snapshot = entry.clone();
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        if let Err(error) = write_workspaces(storage_path, &list) {
+            if let Some(entry) = workspaces.get_mut(&id) {
+                entry.path = previous_root;
+            }
+            return Err(error);
+        }
+        entry_snapshot
+    };
+    let session = sessions.lock().await.get(&id).cloned();
+    if let Some(session) = &session {
+        session
+            .register_workspace_with_path(&entry_snapshot.id, Some(&entry_snapshot.path))
+            .await;
+    }
+    sink.emit_workspaces_changed(WorkspacesChanged {
+        workspace_ids: vec![entry_snapshot.id.clone()],
+    });
+    Ok(WorkspaceInfo {
+        id: entry_snapshot.id,
+        name: entry_snapshot.name,
+        path: entry_snapshot.path,
+        connected: session.is_some(),
+        kind: entry_snapshot.kind,
+        parent_id: entry_snapshot.parent_id,
+        worktree: entry_snapshot.worktree,
+        settings: entry_snapshot.settings,
+    })
+}
+
+/// Removes every workspace whose root is missing and returns their ids.
+pub(crate) async fn workspace_prune_broken_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+    sink: E,
+) -> Result<Vec<String>, String> {
+    let removed_ids = {
+        let mut workspaces = workspaces.lock().await;
+        let mut removed: Vec<WorkspaceEntry> = Vec::new();
+        workspaces.retain(|_, entry| {
+            let keep = Path::new(&entry.path).is_dir();
+            if !keep {
+                removed.push(entry.clone());
+            }
+            keep
+        });
+        if removed.is_empty() {
+            return Ok(Vec::new());
+        }
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        if let Err(error) = write_workspaces(storage_path, &list) {
+            for entry in removed {
+                workspaces.insert(entry.id.clone(), entry);
+            }
+            return Err(error);
+        }
+        let mut ids: Vec<String> = removed.into_iter().map(|entry| entry.id).collect();
+        ids.sort();
+        ids
+    };
+    for id in &removed_ids {
+        kill_session_by_id(sessions, id).await;
+    }
+    sink.emit_workspaces_changed(WorkspacesChanged {
+        workspace_ids: removed_ids.clone(),
+    });
+    Ok(removed_ids)
+}
+
+/// Repairs the persisted workspace list and, when it had to be rewritten,
+/// replaces the in-memory list with the recovered entries.
+pub(crate) async fn workspace_state_repair_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    storage_path: &PathBuf,
+) -> Result<WorkspaceStateRepairReport, String> {
+    let mut workspaces = workspaces.lock().await;
+    let (entries, report) = repair_workspaces(storage_path)?;
+    if report.repaired {
+        *workspaces = entries
+            .into_iter()
+            .map(|entry| (entry.id.clone(), entry))
+            .collect();
+    }
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{default_repo_name_from_url, validate_target_folder_name};
@@ -0,0 +1,714 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use toml_edit::Document;
+
+use crate::backend::events::{EventSink, OperationReporter};
+use crate::codex::config::{
+    feature_catalog_defaults, is_catalog_feature, normalize_approval_policy_value,
+    normalize_personality_value, normalize_sandbox_mode_value, read_personality_from_document,
+    DEFAULT_APPROVAL_POLICY, DEFAULT_PERSONALITY, DEFAULT_SANDBOX_MODE,
+};
+use crate::codex::home::resolve_workspace_codex_home;
+use crate::files::policy::ScopeRootOverrides;
+use crate::shared::config_patch_core::ConfigWriter;
+use crate::shared::config_toml_core;
+use crate::shared::settings_core::{feature_preset_apply_core, find_feature_preset_core};
+use crate::storage::write_workspaces;
+use crate::types::{AppSettings, FeaturePreset, WorkspaceEntry};
+
+use super::helpers::resolve_entry_and_parent;
+
+/// A default that a workspace can override on top of the global `config.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum WorkspaceDefaultKey {
+    Model,
+    Personality,
+    SandboxMode,
+    ApprovalPolicy,
+}
+
+/// Which layer an effective workspace default came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum WorkspaceDefaultSource {
+    Workspace,
+    Global,
+    Default,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EffectiveWorkspaceDefault {
+    /// `None` only for a model set nowhere, which leaves the choice to Codex.
+    pub(crate) value: Option<String>,
+    pub(crate) source: WorkspaceDefaultSource,
+}
+
+/// The model new threads in the workspace start with.
+pub(crate) async fn workspace_effective_model_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<EffectiveWorkspaceDefault, String> {
+    workspace_effective_default_core(workspaces, workspace_id, WorkspaceDefaultKey::Model).await
+}
+
+/// The personality new threads in the workspace start with.
+pub(crate) async fn workspace_effective_personality_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<EffectiveWorkspaceDefault, String> {
+    workspace_effective_default_core(workspaces, workspace_id, WorkspaceDefaultKey::Personality)
+        .await
+}
+
+/// Layers the workspace override (a worktree inherits its parent's) over the
+/// `config.toml` of the workspace's resolved CODEX_HOME.
+async fn workspace_effective_default_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    key: WorkspaceDefaultKey,
+) -> Result<EffectiveWorkspaceDefault, String> {
+    let (entry, parent_entry) = resolve_entry_and_parent(workspaces, workspace_id).await?;
+    if let Some(value) = layered_override(&entry, parent_entry.as_ref(), key) {
+        return Ok(EffectiveWorkspaceDefault {
+            value: Some(value),
+            source: WorkspaceDefaultSource::Workspace,
+        });
+    }
+    let document = workspace_global_document(&entry, parent_entry.as_ref())?;
+    Ok(global_default(&document, key))
+}
+
+/// The workspace's own override for `key`; a worktree inherits its parent's.
+pub(crate) fn layered_override(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+    key: WorkspaceDefaultKey,
+) -> Option<String> {
+    [Some(entry), parent_entry]
+        .into_iter()
+        .flatten()
+        .find_map(|candidate| workspace_override(candidate, key))
+}
+
+/// A catalog feature flag as new threads in the workspace see it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EffectiveFeatureFlag {
+    pub(crate) key: String,
+    pub(crate) enabled: bool,
+    pub(crate) source: WorkspaceDefaultSource,
+}
+
+/// The workspace's feature overrides over its parent's. New threads send them
+/// as `features.*` config overrides.
+pub(crate) fn layered_feature_overrides(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+) -> BTreeMap<String, bool> {
+    let mut flags = parent_entry
+        .map(|parent| parent.settings.feature_overrides.clone())
+        .unwrap_or_default();
+    flags.extend(
+        entry
+            .settings
+            .feature_overrides
+            .iter()
+            .map(|(key, enabled)| (key.clone(), *enabled)),
+    );
+    flags
+}
+
+/// Every catalog flag with the workspace's override layered over the
+/// `config.toml` of its CODEX_HOME and Codex's default.
+pub(crate) async fn workspace_effective_features_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<Vec<EffectiveFeatureFlag>, String> {
+    let (entry, parent_entry) = resolve_entry_and_parent(workspaces, workspace_id).await?;
+    let overrides = layered_feature_overrides(&entry, parent_entry.as_ref());
+    let document = workspace_global_document(&entry, parent_entry.as_ref())?;
+    Ok(effective_features(&overrides, &document))
+}
+
+fn effective_features(
+    overrides: &BTreeMap<String, bool>,
+    document: &Document,
+) -> Vec<EffectiveFeatureFlag> {
+    feature_catalog_defaults()
+        .map(|(key, default_enabled)| {
+            let global = config_toml_core::read_feature_flag(document, key);
+            let (enabled, source) = match (overrides.get(key), global) {
+                (Some(enabled), _) => (*enabled, WorkspaceDefaultSource::Workspace),
+                (None, Some(enabled)) => (enabled, WorkspaceDefaultSource::Global),
+                (None, None) => (default_enabled, WorkspaceDefaultSource::Default),
+            };
+            EffectiveFeatureFlag {
+                key: key.to_string(),
+                enabled,
+                source,
+            }
+        })
+        .collect()
+}
+
+fn workspace_global_document(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+) -> Result<Document, String> {
+    match resolve_workspace_codex_home(entry, parent_entry) {
+        Some(codex_home) => Ok(config_toml_core::load_global_config_document(&codex_home)?.1),
+        None => Ok(Document::new()),
+    }
+}
+
+/// The sandbox posture new threads in the workspace start with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SandboxResolution {
+    pub(crate) sandbox_mode: EffectiveWorkspaceDefault,
+    pub(crate) approval_policy: EffectiveWorkspaceDefault,
+    /// Combinations that let Codex act without the user seeing it first.
+    pub(crate) warnings: Vec<String>,
+}
+
+/// The sandbox mode turns are sent with when neither the workspace nor
+/// `config.toml` sets one.
+const APP_SANDBOX_MODE: &str = "workspace-write";
+
+/// The `sandbox_mode` and `approval_policy` new threads and `current`-access
+/// turns are started with: the workspace override, else `config.toml`, else
+/// the app's own default.
+pub(crate) fn resolve_sandbox_posture(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+) -> Result<(EffectiveWorkspaceDefault, EffectiveWorkspaceDefault), String> {
+    let mode_override = layered_override(entry, parent_entry, WorkspaceDefaultKey::SandboxMode);
+    let policy_override =
+        layered_override(entry, parent_entry, WorkspaceDefaultKey::ApprovalPolicy);
+    let document = match (&mode_override, &policy_override) {
+        (Some(_), Some(_)) => Document::new(),
+        _ => workspace_global_document(entry, parent_entry)?,
+    };
+    let resolve = |value: Option<String>, key| match value {
+        Some(value) => EffectiveWorkspaceDefault {
+            value: Some(value),
+            source: WorkspaceDefaultSource::Workspace,
+        },
+        None => global_default(&document, key),
+    };
+    let mut sandbox_mode = resolve(mode_override, WorkspaceDefaultKey::SandboxMode);
+    if sandbox_mode.source == WorkspaceDefaultSource::Default {
+        sandbox_mode.value = Some(APP_SANDBOX_MODE.to_string());
+    }
+    let approval_policy = resolve(policy_override, WorkspaceDefaultKey::ApprovalPolicy);
+    Ok((sandbox_mode, approval_policy))
+}
+
+/// Reports `resolve_sandbox_posture` for the workspace, which is what
+/// `start_thread_core` and `send_user_message_core` send, and flags risky
+/// combinations.
+pub(crate) async fn effective_sandbox_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<SandboxResolution, String> {
+    let (entry, parent_entry) = resolve_entry_and_parent(workspaces, workspace_id).await?;
+    let (sandbox_mode, approval_policy) = resolve_sandbox_posture(&entry, parent_entry.as_ref())?;
+    let warnings = sandbox_warnings(&sandbox_mode, &approval_policy);
+    Ok(SandboxResolution {
+        sandbox_mode,
+        approval_policy,
+        warnings,
+    })
+}
+
+fn sandbox_warnings(
+    sandbox_mode: &EffectiveWorkspaceDefault,
+    approval_policy: &EffectiveWorkspaceDefault,
+) -> Vec<String> {
+    let mode = sandbox_mode
+        .value
+        .as_deref()
+        .unwrap_or(DEFAULT_SANDBOX_MODE);
+    let policy = approval_policy
+        .value
+        .as_deref()
+        .unwrap_or(DEFAULT_APPROVAL_POLICY);
+    let warning = match (mode, policy) {
+        ("danger-full-access", "never") => {
+            "danger-full-access with approval_policy `never` runs every command unsandboxed without asking"
+        }
+        ("danger-full-access", "on-failure") => {
+            "danger-full-access with approval_policy `on-failure` runs commands unsandboxed before asking"
+        }
+        ("workspace-write", "never") => {
+            "workspace-write with approval_policy `never` edits the workspace without asking"
+        }
+        _ => return Vec::new(),
+    };
+    vec![warning.to_string()]
+}
+
+/// Sets (or with `None`, clears) the workspace's override. A cleared override
+/// falls back to the global `config.toml`; the result is the new effective
+/// value.
+pub(crate) async fn workspace_set_default_override_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    storage_path: &PathBuf,
+    workspace_id: &str,
+    key: WorkspaceDefaultKey,
+    value: Option<String>,
+) -> Result<EffectiveWorkspaceDefault, String> {
+    let value = match value.as_deref().map(str::trim) {
+        Some(value) if !value.is_empty() => Some(normalize_override(key, value)?),
+        _ => None,
+    };
+    {
+        let mut workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get_mut(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        match key {
+            WorkspaceDefaultKey::Model => entry.settings.model_override = value,
+            WorkspaceDefaultKey::Personality => entry.settings.personality_override = value,
+            WorkspaceDefaultKey::SandboxMode => entry.settings.sandbox_mode_override = value,
+            WorkspaceDefaultKey::ApprovalPolicy => entry.settings.approval_policy_override = value,
+        }
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        write_workspaces(storage_path, &list)?;
+    }
+    workspace_effective_default_core(workspaces, workspace_id, key).await
+}
+
+/// How `feature_preset_apply_all` went for one workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PresetApplyOutcome {
+    pub(crate) workspace_id: String,
+    pub(crate) ok: bool,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PresetApplyAllReport {
+    pub(crate) preset: FeaturePreset,
+    /// Set when no workspaces were listed and the preset went to the global
+    /// `config.toml` instead.
+    pub(crate) global: bool,
+    pub(crate) workspaces: Vec<PresetApplyOutcome>,
+}
+
+/// Merges a feature preset's flags into the override layer of each listed
+/// workspace; every flag must be in the feature catalog. An empty
+/// `workspace_ids` means global only: the preset is applied to `config.toml`
+/// like `feature_preset_apply` and no workspace is touched. Progress is
+/// reported per workspace.
+pub(crate) async fn feature_preset_apply_all_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    storage_path: &PathBuf,
+    app_settings: &Mutex<AppSettings>,
+    scope_overrides: &ScopeRootOverrides,
+    name: &str,
+    workspace_ids: Vec<String>,
+    reporter: &OperationReporter<E>,
+    writer: ConfigWriter<'_>,
+) -> Result<PresetApplyAllReport, String> {
+    let result = async {
+        if workspace_ids.is_empty() {
+            let preset =
+                feature_preset_apply_core(name, false, app_settings, scope_overrides, writer)
+                    .await?;
+            return Ok(PresetApplyAllReport {
+                preset,
+                global: true,
+                workspaces: Vec::new(),
+            });
+        }
+        let preset = find_feature_preset_core(name, app_settings).await?;
+        check_catalog_flags(&preset)?;
+        let mut workspaces = workspaces.lock().await;
+        let previous = workspaces.clone();
+        let total = workspace_ids.len() as u32;
+        let mut outcomes = Vec::with_capacity(workspace_ids.len());
+        for (index, workspace_id) in workspace_ids.into_iter().enumerate() {
+            reporter.progress(index as u32, total, Some(&workspace_id));
+            outcomes.push(apply_preset_flags(&mut workspaces, workspace_id, &preset));
+        }
+        if outcomes.iter().any(|outcome| outcome.ok) {
+            let list: Vec<_> = workspaces.values().cloned().collect();
+            if let Err(error) = write_workspaces(storage_path, &list) {
+                *workspaces = previous;
+                return Err(error);
+            }
+        }
+        reporter.progress(total, total, None);
+        Ok(PresetApplyAllReport {
+            preset,
+            global: false,
+            workspaces: outcomes,
+        })
+    }
+    .await;
+    reporter.finish(result)
+}
+
+fn check_catalog_flags(preset: &FeaturePreset) -> Result<(), String> {
+    let unknown: Vec<&str> = preset
+        .flags
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !is_catalog_feature(key))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "Preset `{}` sets unknown feature flags: {}",
+        preset.name,
+        unknown.join(", ")
+    ))
+}
+
+fn apply_preset_flags(
+    workspaces: &mut HashMap<String, WorkspaceEntry>,
+    workspace_id: String,
+    preset: &FeaturePreset,
+) -> PresetApplyOutcome {
+    match workspaces.get_mut(&workspace_id) {
+        Some(entry) => {
+            entry.settings.feature_overrides.extend(
+                preset
+                    .flags
+                    .iter()
+                    .map(|(key, value)| (key.clone(), *value)),
+            );
+            PresetApplyOutcome {
+                workspace_id,
+                ok: true,
+                error: None,
+            }
+        }
+        None => PresetApplyOutcome {
+            workspace_id,
+            ok: false,
+            error: Some("workspace not found".to_string()),
+        },
+    }
+}
+
+fn normalize_override(key: WorkspaceDefaultKey, value: &str) -> Result<String, String> {
+    match key {
+        WorkspaceDefaultKey::Model => Ok(value.to_string()),
+        WorkspaceDefaultKey::Personality => normalize_personality_value(value)
+            .map(str::to_string)
+            .ok_or_else(|| format!("Unsupported personality `{value}`")),
+        WorkspaceDefaultKey::SandboxMode => normalize_sandbox_mode_value(value)
+            .map(str::to_string)
+            .ok_or_else(|| format!("Unsupported sandbox mode `{value}`")),
+        WorkspaceDefaultKey::ApprovalPolicy => normalize_approval_policy_value(value)
+            .map(str::to_string)
+            .ok_or_else(|| format!("Unsupported approval policy `{value}`")),
+    }
+}
+
+fn workspace_override(entry: &WorkspaceEntry, key: WorkspaceDefaultKey) -> Option<String> {
+    match key {
+        WorkspaceDefaultKey::Model => entry
+            .settings
+            .model_override
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string),
+        WorkspaceDefaultKey::Personality => entry
+            .settings
+            .personality_override
+            .as_deref()
+            .and_then(normalize_personality_value)
+            .map(str::to_string),
+        WorkspaceDefaultKey::SandboxMode => entry
+            .settings
+            .sandbox_mode_override
+            .as_deref()
+            .and_then(normalize_sandbox_mode_value)
+            .map(str::to_string),
+        WorkspaceDefaultKey::ApprovalPolicy => entry
+            .settings
+            .approval_policy_override
+            .as_deref()
+            .and_then(normalize_approval_policy_value)
+            .map(str::to_string),
+    }
+}
+
+fn global_default(document: &Document, key: WorkspaceDefaultKey) -> EffectiveWorkspaceDefault {
+    let global = match key {
+        WorkspaceDefaultKey::Model => {
+            config_toml_core::read_top_level_string_ignore_case(document, "model")
+        }
+        WorkspaceDefaultKey::Personality => read_personality_from_document(document),
+        WorkspaceDefaultKey::SandboxMode => {
+            config_toml_core::read_top_level_string_ignore_case(document, "sandbox_mode")
+                .as_deref()
+                .and_then(normalize_sandbox_mode_value)
+                .map(str::to_string)
+        }
+        WorkspaceDefaultKey::ApprovalPolicy => {
+            config_toml_core::read_top_level_string_ignore_case(document, "approval_policy")
+                .as_deref()
+                .and_then(normalize_approval_policy_value)
+                .map(str::to_string)
+        }
+    };
+    match (global, key) {
+        (Some(value), _) => EffectiveWorkspaceDefault {
+            value: Some(value),
+            source: WorkspaceDefaultSource::Global,
+        },
+        (None, WorkspaceDefaultKey::Model) => EffectiveWorkspaceDefault {
+            value: None,
+            source: WorkspaceDefaultSource::Default,
+        },
+        (None, WorkspaceDefaultKey::Personality) => EffectiveWorkspaceDefault {
+            value: Some(DEFAULT_PERSONALITY.to_string()),
+            source: WorkspaceDefaultSource::Default,
+        },
+        (None, WorkspaceDefaultKey::SandboxMode) => EffectiveWorkspaceDefault {
+            value: Some(DEFAULT_SANDBOX_MODE.to_string()),
+            source: WorkspaceDefaultSource::Default,
+        },
+        (None, WorkspaceDefaultKey::ApprovalPolicy) => EffectiveWorkspaceDefault {
+            value: Some(DEFAULT_APPROVAL_POLICY.to_string()),
+            source: WorkspaceDefaultSource::Default,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::{
+        apply_preset_flags, check_catalog_flags, effective_features, global_default,
+        layered_feature_overrides, normalize_override, resolve_sandbox_posture, sandbox_warnings,
+        workspace_override, EffectiveWorkspaceDefault, WorkspaceDefaultKey, WorkspaceDefaultSource,
+    };
+    use crate::shared::config_toml_core::parse_document;
+    use crate::types::{FeaturePreset, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+    #[test]
+    fn workspace_overrides_layer_over_global_and_default() {
+        let entry = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "ws".to_string(),
+            path: "/tmp/ws".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                model_override: Some(" gpt-5-mini ".to_string()),
+                personality_override: Some("Grumpy".to_string()),
+                ..WorkspaceSettings::default()
+            },
+        };
+        assert_eq!(
+            workspace_override(&entry, WorkspaceDefaultKey::Model).as_deref(),
+            Some("gpt-5-mini")
+        );
+        assert_eq!(
+            workspace_override(&entry, WorkspaceDefaultKey::Personality),
+            None
+        );
+        assert_eq!(
+            normalize_override(WorkspaceDefaultKey::Personality, "Pragmatic").as_deref(),
+            Ok("pragmatic")
+        );
+        assert!(normalize_override(WorkspaceDefaultKey::Personality, "grumpy").is_err());
+
+        let global = parse_document("model = \"gpt-5\"\npersonality = \"pragmatic\"\n")
+            .expect("parse global");
+        let model = global_default(&global, WorkspaceDefaultKey::Model);
+        assert_eq!(model.value.as_deref(), Some("gpt-5"));
+        assert_eq!(model.source, WorkspaceDefaultSource::Global);
+
+        let empty = parse_document("").expect("parse empty");
+        let model = global_default(&empty, WorkspaceDefaultKey::Model);
+        assert_eq!(
+            (model.value, model.source),
+            (None, WorkspaceDefaultSource::Default)
+        );
+        let personality = global_default(&empty, WorkspaceDefaultKey::Personality);
+        assert_eq!(personality.value.as_deref(), Some("friendly"));
+        assert_eq!(personality.source, WorkspaceDefaultSource::Default);
+    }
+
+    #[test]
+    fn sandbox_defaults_resolve_and_flag_risky_combinations() {
+        let entry = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "ws".to_string(),
+            path: "/tmp/ws".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                sandbox_mode_override: Some("Danger-Full-Access".to_string()),
+                approval_policy_override: Some("sometimes".to_string()),
+                ..WorkspaceSettings::default()
+            },
+        };
+        assert_eq!(
+            workspace_override(&entry, WorkspaceDefaultKey::SandboxMode).as_deref(),
+            Some("danger-full-access")
+        );
+        assert_eq!(
+            workspace_override(&entry, WorkspaceDefaultKey::ApprovalPolicy),
+            None
+        );
+        assert!(normalize_override(WorkspaceDefaultKey::ApprovalPolicy, "sometimes").is_err());
+
+        let global = parse_document("approval_policy = \"never\"\n").expect("parse global");
+        let policy = global_default(&global, WorkspaceDefaultKey::ApprovalPolicy);
+        assert_eq!(policy.value.as_deref(), Some("never"));
+        assert_eq!(policy.source, WorkspaceDefaultSource::Global);
+        let mode = global_default(&global, WorkspaceDefaultKey::SandboxMode);
+        assert_eq!(mode.value.as_deref(), Some("read-only"));
+        assert_eq!(mode.source, WorkspaceDefaultSource::Default);
+
+        let setting = |value: &str| EffectiveWorkspaceDefault {
+            value: Some(value.to_string()),
+            source: WorkspaceDefaultSource::Workspace,
+        };
+        assert!(sandbox_warnings(&mode, &policy).is_empty());
+        assert_eq!(
+            sandbox_warnings(&setting("danger-full-access"), &policy).len(),
+            1
+        );
+        assert!(sandbox_warnings(&setting("danger-full-access"), &setting("untrusted")).is_empty());
+
+        let overridden = WorkspaceEntry {
+            settings: WorkspaceSettings {
+                sandbox_mode_override: Some("read-only".to_string()),
+                approval_policy_override: Some("never".to_string()),
+                ..WorkspaceSettings::default()
+            },
+            ..entry
+        };
+        let (mode, policy) = resolve_sandbox_posture(&overridden, None).expect("resolve posture");
+        assert_eq!(mode, setting("read-only"));
+        assert_eq!(policy, setting("never"));
+    }
+
+    #[test]
+    fn preset_flags_merge_into_workspace_overrides() {
+        let entry = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "ws".to_string(),
+            path: "/tmp/ws".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                feature_overrides: BTreeMap::from([
+                    ("steer".to_string(), true),
+                    ("apps".to_string(), true),
+                ]),
+                ..WorkspaceSettings::default()
+            },
+        };
+        let mut workspaces = HashMap::from([("ws".to_string(), entry)]);
+        let preset = FeaturePreset {
+            name: "team".to_string(),
+            flags: BTreeMap::from([
+                ("steer".to_string(), false),
+                ("unified_exec".to_string(), true),
+            ]),
+        };
+
+        let applied = apply_preset_flags(&mut workspaces, "ws".to_string(), &preset);
+        assert!(applied.ok);
+        let missing = apply_preset_flags(&mut workspaces, "gone".to_string(), &preset);
+        assert!(!missing.ok);
+        assert_eq!(missing.error.as_deref(), Some("workspace not found"));
+
+        assert_eq!(
+            workspaces["ws"].settings.feature_overrides,
+            BTreeMap::from([
+                ("apps".to_string(), true),
+                ("steer".to_string(), false),
+                ("unified_exec".to_string(), true),
+            ])
+        );
+    }
+
+    #[test]
+    fn feature_overrides_layer_over_parent_global_and_default() {
+        let parent = WorkspaceEntry {
+            id: "parent".to_string(),
+            name: "parent".to_string(),
+            path: "/tmp/parent".to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                feature_overrides: BTreeMap::from([
+                    ("steer".to_string(), false),
+                    ("apps".to_string(), true),
+                ]),
+                ..WorkspaceSettings::default()
+            },
+        };
+        let child = WorkspaceEntry {
+            id: "child".to_string(),
+            parent_id: Some("parent".to_string()),
+            kind: WorkspaceKind::Worktree,
+            settings: WorkspaceSettings {
+                feature_overrides: BTreeMap::from([("apps".to_string(), false)]),
+                ..WorkspaceSettings::default()
+            },
+            ..parent.clone()
+        };
+        let overrides = layered_feature_overrides(&child, Some(&parent));
+        assert_eq!(
+            overrides,
+            BTreeMap::from([("apps".to_string(), false), ("steer".to_string(), false)])
+        );
+
+        let document = parse_document("[features]\nunified_exec = true\n").expect("parse");
+        let flags = effective_features(&overrides, &document);
+        let flag = |key: &str| {
+            flags
+                .iter()
+                .find(|flag| flag.key == key)
+                .map(|flag| (flag.enabled, flag.source))
+        };
+        assert_eq!(
+            flag("steer"),
+            Some((false, WorkspaceDefaultSource::Workspace))
+        );
+        assert_eq!(
+            flag("unified_exec"),
+            Some((true, WorkspaceDefaultSource::Global))
+        );
+        assert_eq!(
+            flag("collaboration_modes").map(|(_, source)| source),
+            Some(WorkspaceDefaultSource::Default)
+        );
+    }
+
+    #[test]
+    fn presets_with_unknown_flags_are_rejected() {
+        let preset = FeaturePreset {
+            name: "team".to_string(),
+            flags: BTreeMap::from([("steer".to_string(), true), ("made_up".to_string(), true)]),
+        };
+        let err = check_catalog_flags(&preset).expect_err("unknown flag");
+        assert!(err.contains("made_up"));
+        assert!(!err.contains("steer"));
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::Mutex;
+
+use crate::backend::events::{EventSink, WorkspaceCheck, WorkspaceHealth, WorkspaceRootStatus};
+use crate::types::WorkspaceEntry;
+
+/// The last `workspace-health` summary, kept until the set of workspace roots
+/// changes.
+pub(crate) type WorkspaceHealthCache = Mutex<Option<CachedHealth>>;
+
+pub(crate) struct CachedHealth {
+    /// The `(workspace id, root)` pairs checked; an add, remove or relink makes
+    /// them differ and the summary stale.
+    roots: Vec<(String, String)>,
+    health: WorkspaceHealth,
+}
+
+fn checked_roots(entries: &[WorkspaceEntry]) -> Vec<(String, String)> {
+    let mut roots: Vec<_> = entries
+        .iter()
+        .map(|entry| (entry.id.clone(), entry.path.clone()))
+        .collect();
+    roots.sort();
+    roots
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn check_root(path: &Path) -> (WorkspaceRootStatus, Option<String>) {
+    match std::fs::metadata(path) {
+        Ok(metadata) if !metadata.is_dir() => (WorkspaceRootStatus::NotDirectory, None),
+        Ok(_) => match std::fs::read_dir(path) {
+            Ok(_) => (WorkspaceRootStatus::Ok, None),
+            Err(err) => (WorkspaceRootStatus::Unreadable, Some(err.to_string())),
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            (WorkspaceRootStatus::Missing, None)
+        }
+        Err(err) => (WorkspaceRootStatus::Unreadable, Some(err.to_string())),
+    }
+}
+
+fn check_entry(entry: &WorkspaceEntry) -> WorkspaceCheck {
+    let (status, error) = check_root(Path::new(&entry.path));
+    WorkspaceCheck {
+        workspace_id: entry.id.clone(),
+        path: entry.path.clone(),
+        status,
+        error,
+    }
+}
+
+/// Whether the workspace root exists, is a folder, and can be listed.
+pub(crate) async fn workspace_check_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<WorkspaceCheck, String> {
+    let entry = workspaces
+        .lock()
+        .await
+        .get(workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+    Ok(check_entry(&entry))
+}
+
+fn health_summary(entries: &[WorkspaceEntry]) -> WorkspaceHealth {
+    let mut workspaces: Vec<WorkspaceCheck> = entries.iter().map(check_entry).collect();
+    workspaces.sort_by(|a, b| a.workspace_id.cmp(&b.workspace_id));
+    let broken = workspaces
+        .iter()
+        .filter(|check| check.status != WorkspaceRootStatus::Ok)
+        .map(|check| check.workspace_id.clone())
+        .collect();
+    WorkspaceHealth {
+        checked_at_ms: now_ms(),
+        workspaces,
+        broken,
+    }
+}
+
+/// Checks every workspace root, caches the summary and emits it as
+/// `workspace-health`. Run once in the background at startup.
+pub(crate) async fn workspace_health_startup_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    cache: &WorkspaceHealthCache,
+    sink: &E,
+) {
+    let entries: Vec<WorkspaceEntry> = workspaces.lock().await.values().cloned().collect();
+    let health = health_summary(&entries);
+    *cache.lock().await = Some(CachedHealth {
+        roots: checked_roots(&entries),
+        health: health.clone(),
+    });
+    sink.emit_workspace_health(health);
+}
+
+/// The cached summary; checks now (and caches) if startup has not finished it
+/// yet or workspaces were added, removed or relinked since.
+pub(crate) async fn workspace_health_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    cache: &WorkspaceHealthCache,
+) -> WorkspaceHealth {
+    let entries: Vec<WorkspaceEntry> = workspaces.lock().await.values().cloned().collect();
+    let roots = checked_roots(&entries);
+    let mut cache = cache.lock().await;
+    if let Some(cached) = cache.as_ref().filter(|cached| cached.roots == roots) {
+        return cached.health.clone();
+    }
+    let health = health_summary(&entries);
+    *cache = Some(CachedHealth {
+        roots,
+        health: health.clone(),
+    });
+    health
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{health_summary, workspace_health_core, WorkspaceRootStatus};
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use tokio::sync::Mutex;
+    use uuid::Uuid;
+
+    fn entry(id: &str, path: &std::path::Path) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn health_summary_flags_missing_and_file_roots() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-health-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        let file = root.join("not-a-dir");
+        std::fs::write(&file, "").expect("write file");
+
+        let health = health_summary(&[
+            entry("c-missing", &root.join("gone")),
+            entry("a-ok", &root),
+            entry("b-file", &file),
+        ]);
+        let statuses: Vec<_> = health
+            .workspaces
+            .iter()
+            .map(|check| (check.workspace_id.as_str(), check.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("a-ok", WorkspaceRootStatus::Ok),
+                ("b-file", WorkspaceRootStatus::NotDirectory),
+                ("c-missing", WorkspaceRootStatus::Missing),
+            ]
+        );
+        assert_eq!(health.broken, vec!["b-file", "c-missing"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn cached_health_is_rechecked_when_workspaces_change() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-health-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        let workspaces = Mutex::new(HashMap::from([("a".to_string(), entry("a", &root))]));
+        let cache = Mutex::new(None);
+
+        tokio::runtime::Runtime::new()
+            .expect("runtime")
+            .block_on(async {
+                let first = workspace_health_core(&workspaces, &cache).await;
+                assert!(first.broken.is_empty());
+
+                let gone = root.join("gone");
+                workspaces
+                    .lock()
+                    .await
+                    .insert("b".to_string(), entry("b", &gone));
+                let added = workspace_health_core(&workspaces, &cache).await;
+                assert_eq!(added.broken, vec!["b"]);
+
+                workspaces
+                    .lock()
+                    .await
+                    .insert("b".to_string(), entry("b", &root));
+                let relinked = workspace_health_core(&workspaces, &cache).await;
+                assert!(relinked.broken.is_empty());
+            });
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
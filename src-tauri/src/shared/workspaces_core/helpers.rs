@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use serde::Serialize;
 use tokio::sync::Mutex;
 
 use crate::backend::app_server::WorkspaceSession;
@@ -81,6 +82,108 @@ pub(crate) fn normalize_workspace_path_input(path: &str) -> PathBuf {
     PathBuf::from(trimmed)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum WorkspaceOverlapKind {
+    /// Both roots resolve to the same directory.
+    Duplicate,
+    /// The other root lives inside this one.
+    Nested,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceOverlap {
+    pub(crate) kind: WorkspaceOverlapKind,
+    pub(crate) workspace_id: String,
+    pub(crate) path: String,
+    pub(crate) other_workspace_id: String,
+    pub(crate) other_path: String,
+}
+
+fn canonical_workspace_root(path: &str) -> PathBuf {
+    let normalized = normalize_workspace_path_input(path);
+    normalized.canonicalize().unwrap_or(normalized)
+}
+
+/// Pairs of workspaces whose canonical roots are the same directory, or where
+/// one root sits under the other. Worktrees and clones nested under their own
+/// parent are expected and not reported.
+pub(crate) fn find_workspace_overlaps(entries: &[WorkspaceEntry]) -> Vec<WorkspaceOverlap> {
+    let mut roots: Vec<(&WorkspaceEntry, PathBuf)> = entries
+        .iter()
+        .map(|entry| (entry, canonical_workspace_root(&entry.path)))
+        .collect();
+    roots.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+
+    let mut overlaps = Vec::new();
+    for (index, (entry, root)) in roots.iter().enumerate() {
+        for (other, other_root) in &roots[index + 1..] {
+            let (kind, outer, inner) = if root == other_root {
+                (WorkspaceOverlapKind::Duplicate, entry, other)
+            } else if other_root.starts_with(root) {
+                (WorkspaceOverlapKind::Nested, entry, other)
+            } else if root.starts_with(other_root) {
+                (WorkspaceOverlapKind::Nested, other, entry)
+            } else {
+                continue;
+            };
+            if kind == WorkspaceOverlapKind::Nested
+                && inner.parent_id.as_deref() == Some(outer.id.as_str())
+            {
+                continue;
+            }
+            overlaps.push(WorkspaceOverlap {
+                kind,
+                workspace_id: outer.id.clone(),
+                path: outer.path.clone(),
+                other_workspace_id: inner.id.clone(),
+                other_path: inner.path.clone(),
+            });
+        }
+    }
+    overlaps
+}
+
+/// Warnings for each existing workspace root `path` overlaps. An exact
+/// duplicate is rejected instead when `reject_duplicate` is set.
+pub(super) fn check_new_workspace_root(
+    existing: &[WorkspaceEntry],
+    path: &str,
+    reject_duplicate: bool,
+) -> Result<Vec<String>, String> {
+    let root = canonical_workspace_root(path);
+    let mut warnings = Vec::new();
+    for entry in existing {
+        let other_root = canonical_workspace_root(&entry.path);
+        if other_root == root {
+            if reject_duplicate {
+                return Err(format!(
+                    "{path} is already added as workspace \"{}\".",
+                    entry.name
+                ));
+            }
+            warnings.push(format!(
+                "{path} is already added as workspace \"{}\".",
+                entry.name
+            ));
+        } else if root.starts_with(&other_root) || other_root.starts_with(&root) {
+            warnings.push(format!(
+                "{path} overlaps workspace \"{}\" at {}.",
+                entry.name, entry.path
+            ));
+        }
+    }
+    Ok(warnings)
+}
+
+pub(crate) async fn workspace_find_duplicates_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+) -> Vec<WorkspaceOverlap> {
+    let entries: Vec<WorkspaceEntry> = workspaces.lock().await.values().cloned().collect();
+    find_workspace_overlaps(&entries)
+}
+
 pub(crate) async fn list_workspaces_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
@@ -147,8 +250,10 @@ pub(super) fn sort_workspaces(workspaces: &mut [WorkspaceInfo]) {
 #[cfg(test)]
 mod tests {
     use super::{
-        copy_agents_md_from_parent_to_worktree, normalize_workspace_path_input, AGENTS_MD_FILE_NAME,
+        check_new_workspace_root, copy_agents_md_from_parent_to_worktree, find_workspace_overlaps,
+        normalize_workspace_path_input, WorkspaceOverlapKind, AGENTS_MD_FILE_NAME,
     };
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
     use std::path::PathBuf;
     use std::sync::Mutex;
     use uuid::Uuid;
@@ -201,6 +306,71 @@ mod tests {
         let _ = std::fs::remove_dir_all(worktree);
     }
 
+    fn entry(id: &str, path: &PathBuf, parent_id: Option<&str>) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            kind: WorkspaceKind::Main,
+            parent_id: parent_id.map(str::to_string),
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn finds_duplicate_and_nested_workspace_roots() {
+        let root = make_temp_dir();
+        let nested = root.join("packages").join("app");
+        std::fs::create_dir_all(&nested).expect("create nested dir");
+        let entries = vec![
+            entry("a", &root, None),
+            entry("b", &root.join("packages").join(".."), None),
+            entry("c", &nested, None),
+            entry("d", &nested, Some("a")),
+        ];
+
+        let overlaps = find_workspace_overlaps(&entries);
+        let summary: Vec<_> = overlaps
+            .iter()
+            .map(|overlap| {
+                (
+                    overlap.kind,
+                    overlap.workspace_id.as_str(),
+                    overlap.other_workspace_id.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (WorkspaceOverlapKind::Duplicate, "a", "b"),
+                (WorkspaceOverlapKind::Nested, "a", "c"),
+                (WorkspaceOverlapKind::Nested, "b", "c"),
+                (WorkspaceOverlapKind::Nested, "b", "d"),
+                (WorkspaceOverlapKind::Duplicate, "c", "d"),
+            ]
+        );
+
+        let existing = vec![entry("a", &root, None)];
+        let candidate = root.to_string_lossy().to_string();
+        assert!(check_new_workspace_root(&existing, &candidate, true).is_err());
+        let warnings = check_new_workspace_root(&existing, &candidate, false).expect("warn");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("already added"), "{warnings:?}");
+        let nested = nested.to_string_lossy().to_string();
+        let warnings = check_new_workspace_root(&existing, &nested, true).expect("warn");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("overlaps"), "{warnings:?}");
+        let elsewhere = std::env::temp_dir().to_string_lossy().to_string();
+        assert_eq!(
+            check_new_workspace_root(&[], &elsewhere, true),
+            Ok(Vec::new())
+        );
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
     #[test]
     fn normalize_workspace_path_input_expands_home_prefix() {
         let _guard = ENV_LOCK.lock().expect("lock env");
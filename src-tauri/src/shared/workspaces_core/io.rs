@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::env;
 use std::path::{Path, PathBuf};
 
+use tokio::process::Command;
 use tokio::sync::Mutex;
 
 use crate::shared::process_core::tokio_command;
@@ -152,6 +153,43 @@ fn build_launch_args(
     launch_args
 }
 
+/// The process for a `program` launch target. On Windows, `.cmd` and `.bat`
+/// shims run through `cmd /C`.
+pub(crate) fn command_target(program: &str, launch_args: &[String]) -> Result<Command, String> {
+    #[cfg(target_os = "windows")]
+    let cmd = {
+        let resolved = resolve_windows_executable(program, None);
+        let resolved_path = resolved.as_deref().unwrap_or_else(|| Path::new(program));
+        let ext = resolved_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        if matches!(ext.as_deref(), Some("cmd") | Some("bat")) {
+            let mut cmd = tokio_command("cmd");
+            let command_line = build_cmd_c_command(resolved_path, launch_args)?;
+            cmd.arg("/D");
+            cmd.arg("/S");
+            cmd.arg("/C");
+            cmd.raw_arg(command_line);
+            cmd
+        } else {
+            let mut cmd = tokio_command(resolved_path);
+            cmd.args(launch_args);
+            cmd
+        }
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let cmd = {
+        let mut cmd = tokio_command(program);
+        cmd.args(launch_args);
+        cmd
+    };
+
+    Ok(cmd)
+}
+
 pub(crate) async fn open_workspace_in_core(
     path: String,
     app: Option<String>,
@@ -194,37 +232,7 @@ pub(crate) async fn open_workspace_in_core(
             command_launch_strategy(trimmed),
         );
 
-        #[cfg(target_os = "windows")]
-        let mut cmd = {
-            let resolved = resolve_windows_executable(trimmed, None);
-            let resolved_path = resolved.as_deref().unwrap_or_else(|| Path::new(trimmed));
-            let ext = resolved_path
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext.to_ascii_lowercase());
-
-            if matches!(ext.as_deref(), Some("cmd") | Some("bat")) {
-                let mut cmd = tokio_command("cmd");
-                let command_line = build_cmd_c_command(resolved_path, &launch_args)?;
-                cmd.arg("/D");
-                cmd.arg("/S");
-                cmd.arg("/C");
-                cmd.raw_arg(command_line);
-                cmd
-            } else {
-                let mut cmd = tokio_command(resolved_path);
-                cmd.args(&launch_args);
-                cmd
-            }
-        };
-
-        #[cfg(not(target_os = "windows"))]
-        let mut cmd = {
-            let mut cmd = tokio_command(trimmed);
-            cmd.args(&launch_args);
-            cmd
-        };
-
+        let mut cmd = command_target(trimmed, &launch_args)?;
         cmd.output()
             .await
             .map_err(|error| format!("Failed to open app ({target_label}): {error}"))?
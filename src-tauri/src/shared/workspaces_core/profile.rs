@@ -0,0 +1,482 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{EventSink, WorkspacesChanged};
+use crate::codex::home::resolve_home_dir;
+use crate::files::policy::ScopeRootOverrides;
+use crate::shared::settings_core::persist_app_settings_core;
+use crate::storage::write_workspaces;
+use crate::types::{AppSettings, FeaturePreset, WorkspaceEntry};
+
+use super::connect::kill_session_by_id;
+use super::helpers::normalize_workspace_path_input;
+
+/// Bundle layout version written by `profile_export_core`. Imports accept this
+/// version and older ones.
+pub(crate) const PROFILE_VERSION: u32 = 1;
+
+/// Workspaces, app settings and feature presets for moving to another machine.
+/// Separate from `config_export_redacted`, which covers Codex's `config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileBundle {
+    pub(crate) version: u32,
+    pub(crate) exported_at_ms: i64,
+    pub(crate) workspaces: Vec<ProfileWorkspace>,
+    /// Remote backend tokens are stripped; presets travel in `feature_presets`.
+    pub(crate) settings: AppSettings,
+    pub(crate) feature_presets: Vec<FeaturePreset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileWorkspace {
+    /// `path` is written as `~/...` when it lies under the home directory.
+    #[serde(flatten)]
+    pub(crate) entry: WorkspaceEntry,
+    /// The root is outside the home directory and was kept absolute, so it
+    /// probably does not exist on another machine.
+    pub(crate) machine_specific: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ProfileImportMode {
+    /// Adds workspaces that are not registered yet and presets whose name is
+    /// new; local settings are kept.
+    #[default]
+    Merge,
+    /// Replaces the workspace list, settings and presets with the bundle's.
+    /// Remote backend connection settings and a relocated CODEX_HOME stay local.
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum MissingRootPolicy {
+    #[default]
+    Skip,
+    /// Imports the workspace anyway; `workspace_list_broken` reports it until
+    /// it is relinked.
+    MarkBroken,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileImportStrategy {
+    #[serde(default)]
+    pub(crate) mode: ProfileImportMode,
+    #[serde(default)]
+    pub(crate) missing_roots: MissingRootPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileImportRequest {
+    pub(crate) bundle: String,
+    #[serde(default)]
+    pub(crate) strategy: ProfileImportStrategy,
+}
+
+/// Workspace ids by outcome, each sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileImportReport {
+    pub(crate) imported: Vec<String>,
+    /// Imported although the root does not exist on this machine.
+    pub(crate) broken: Vec<String>,
+    /// Missing roots under `MissingRootPolicy::Skip`, and worktrees whose
+    /// parent was not imported.
+    pub(crate) skipped: Vec<String>,
+    /// Already registered under the same id or root.
+    pub(crate) unchanged: Vec<String>,
+    /// Local workspaces dropped by `ProfileImportMode::Replace`.
+    pub(crate) removed: Vec<String>,
+}
+
+pub(crate) async fn profile_export_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+) -> Result<String, String> {
+    let entries: Vec<WorkspaceEntry> = workspaces.lock().await.values().cloned().collect();
+    let settings = app_settings.lock().await.clone();
+    let home = resolve_home_dir();
+    let bundle = export_bundle(entries, &settings, home.as_deref(), now_ms());
+    serde_json::to_string_pretty(&bundle).map_err(|err| err.to_string())
+}
+
+/// Applies a `profile_export_core` bundle. Imported workspaces start
+/// disconnected; sessions of workspaces removed by a replace are stopped.
+pub(crate) async fn profile_import_core<E: EventSink>(
+    request: ProfileImportRequest,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    storage_path: &PathBuf,
+    settings_path: &PathBuf,
+    scope_overrides: &ScopeRootOverrides,
+    sink: E,
+) -> Result<ProfileImportReport, String> {
+    let bundle = parse_profile_bundle(&request.bundle)?;
+    let strategy = request.strategy;
+    let report = {
+        let mut workspaces = workspaces.lock().await;
+        let (next, report) = plan_import(bundle.workspaces, &workspaces, strategy, |root| {
+            root.is_dir()
+        });
+        let list: Vec<_> = next.values().cloned().collect();
+        write_workspaces(storage_path, &list)?;
+        *workspaces = next;
+        report
+    };
+    let current = app_settings.lock().await.clone();
+    let settings = import_settings(
+        &current,
+        bundle.settings,
+        bundle.feature_presets,
+        strategy.mode,
+    );
+    persist_app_settings_core(settings, app_settings, settings_path, scope_overrides).await?;
+
+    for id in &report.removed {
+        kill_session_by_id(sessions, id).await;
+    }
+    let mut changed: Vec<String> = report
+        .imported
+        .iter()
+        .chain(&report.removed)
+        .cloned()
+        .collect();
+    if !changed.is_empty() {
+        changed.sort();
+        sink.emit_workspaces_changed(WorkspacesChanged {
+            workspace_ids: changed,
+        });
+    }
+    Ok(report)
+}
+
+fn parse_profile_bundle(text: &str) -> Result<ProfileBundle, String> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|err| format!("Invalid profile bundle: {err}"))?;
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| "Not a CodexMonitor profile bundle: missing `version`".to_string())?;
+    if version == 0 || version > u64::from(PROFILE_VERSION) {
+        return Err(format!(
+            "Profile bundle version {version} is not supported; this app reads versions 1 to {PROFILE_VERSION}"
+        ));
+    }
+    serde_json::from_value(value).map_err(|err| format!("Invalid profile bundle: {err}"))
+}
+
+fn export_bundle(
+    mut entries: Vec<WorkspaceEntry>,
+    settings: &AppSettings,
+    home: Option<&Path>,
+    exported_at_ms: i64,
+) -> ProfileBundle {
+    entries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    let workspaces = entries
+        .into_iter()
+        .map(|mut entry| {
+            let portable = home.and_then(|home| portable_path(&entry.path, home));
+            let machine_specific = portable.is_none();
+            if let Some(portable) = portable {
+                entry.path = portable;
+            }
+            if let Some(codex_home) = entry.settings.codex_home.as_mut() {
+                if let Some(portable) = home.and_then(|home| portable_path(codex_home, home)) {
+                    *codex_home = portable;
+                }
+            }
+            ProfileWorkspace {
+                entry,
+                machine_specific,
+            }
+        })
+        .collect();
+    let mut settings = settings.clone();
+    settings.remote_backend_token = None;
+    for target in &mut settings.remote_backends {
+        target.token = None;
+    }
+    let feature_presets = std::mem::take(&mut settings.feature_presets);
+    ProfileBundle {
+        version: PROFILE_VERSION,
+        exported_at_ms,
+        workspaces,
+        settings,
+        feature_presets,
+    }
+}
+
+/// `path` as `~/...` when it lies under `home`.
+fn portable_path(path: &str, home: &Path) -> Option<String> {
+    let rest = Path::new(path).strip_prefix(home).ok()?;
+    let rest = rest.to_string_lossy().replace('\\', "/");
+    if rest.is_empty() {
+        Some("~".to_string())
+    } else {
+        Some(format!("~/{rest}"))
+    }
+}
+
+fn plan_import(
+    mut bundled: Vec<ProfileWorkspace>,
+    existing: &HashMap<String, WorkspaceEntry>,
+    strategy: ProfileImportStrategy,
+    root_exists: impl Fn(&Path) -> bool,
+) -> (HashMap<String, WorkspaceEntry>, ProfileImportReport) {
+    let mut report = ProfileImportReport::default();
+    let mut next = match strategy.mode {
+        ProfileImportMode::Merge => existing.clone(),
+        ProfileImportMode::Replace => HashMap::new(),
+    };
+    let mut roots: HashSet<PathBuf> = next
+        .values()
+        .map(|entry| PathBuf::from(&entry.path))
+        .collect();
+    // Parents first, so a worktree can check that its parent made it in.
+    bundled.sort_by_key(|workspace| workspace.entry.parent_id.is_some());
+    for ProfileWorkspace { mut entry, .. } in bundled {
+        let root = normalize_workspace_path_input(&entry.path);
+        entry.path = root.to_string_lossy().to_string();
+        if let Some(codex_home) = entry.settings.codex_home.as_mut() {
+            *codex_home = normalize_workspace_path_input(codex_home)
+                .to_string_lossy()
+                .to_string();
+        }
+        if next.contains_key(&entry.id) || roots.contains(&root) {
+            report.unchanged.push(entry.id);
+            continue;
+        }
+        if let Some(parent_id) = &entry.parent_id {
+            if !next.contains_key(parent_id) {
+                report.skipped.push(entry.id);
+                continue;
+            }
+        }
+        if !root_exists(&root) {
+            match strategy.missing_roots {
+                MissingRootPolicy::Skip => {
+                    report.skipped.push(entry.id);
+                    continue;
+                }
+                MissingRootPolicy::MarkBroken => report.broken.push(entry.id.clone()),
+            }
+        }
+        roots.insert(root);
+        report.imported.push(entry.id.clone());
+        next.insert(entry.id.clone(), entry);
+    }
+    if strategy.mode == ProfileImportMode::Replace {
+        report.removed = existing
+            .keys()
+            .filter(|id| !next.contains_key(*id))
+            .cloned()
+            .collect();
+    }
+    for ids in [
+        &mut report.imported,
+        &mut report.broken,
+        &mut report.skipped,
+        &mut report.unchanged,
+        &mut report.removed,
+    ] {
+        ids.sort();
+    }
+    (next, report)
+}
+
+fn import_settings(
+    current: &AppSettings,
+    bundled: AppSettings,
+    presets: Vec<FeaturePreset>,
+    mode: ProfileImportMode,
+) -> AppSettings {
+    match mode {
+        ProfileImportMode::Merge => {
+            let mut settings = current.clone();
+            for preset in presets {
+                if !settings
+                    .feature_presets
+                    .iter()
+                    .any(|existing| existing.name == preset.name)
+                {
+                    settings.feature_presets.push(preset);
+                }
+            }
+            settings
+        }
+        ProfileImportMode::Replace => {
+            let mut settings = bundled;
+            settings.backend_mode = current.backend_mode.clone();
+            settings.remote_backend_provider = current.remote_backend_provider.clone();
+            settings.remote_backend_host = current.remote_backend_host.clone();
+            settings.remote_backend_token = current.remote_backend_token.clone();
+            settings.remote_backends = current.remote_backends.clone();
+            settings.active_remote_backend_id = current.active_remote_backend_id.clone();
+            settings.codex_home_override = current.codex_home_override.clone();
+            settings.feature_presets = presets;
+            settings
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        export_bundle, import_settings, parse_profile_bundle, plan_import, MissingRootPolicy,
+        ProfileImportMode, ProfileImportStrategy, PROFILE_VERSION,
+    };
+    use crate::types::{AppSettings, FeaturePreset, WorkspaceEntry, WorkspaceKind};
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::Path;
+
+    fn entry(id: &str, path: &str, parent_id: Option<&str>) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string(),
+            kind: if parent_id.is_some() {
+                WorkspaceKind::Worktree
+            } else {
+                WorkspaceKind::Main
+            },
+            parent_id: parent_id.map(str::to_string),
+            worktree: None,
+            settings: Default::default(),
+        }
+    }
+
+    #[test]
+    fn export_relativizes_home_roots_and_strips_tokens() {
+        let settings = AppSettings {
+            remote_backend_token: Some("secret".to_string()),
+            feature_presets: vec![FeaturePreset {
+                name: "fast".to_string(),
+                flags: BTreeMap::new(),
+            }],
+            ..AppSettings::default()
+        };
+        let bundle = export_bundle(
+            vec![
+                entry("a", "/home/me/dev/app", None),
+                entry("b", "/srv/shared", None),
+            ],
+            &settings,
+            Some(Path::new("/home/me")),
+            1,
+        );
+        assert_eq!(bundle.version, PROFILE_VERSION);
+        assert_eq!(bundle.workspaces[0].entry.path, "~/dev/app");
+        assert!(!bundle.workspaces[0].machine_specific);
+        assert_eq!(bundle.workspaces[1].entry.path, "/srv/shared");
+        assert!(bundle.workspaces[1].machine_specific);
+        assert_eq!(bundle.settings.remote_backend_token, None);
+        assert!(bundle.settings.feature_presets.is_empty());
+        assert_eq!(bundle.feature_presets.len(), 1);
+
+        let text = serde_json::to_string(&bundle).expect("serialize");
+        let parsed = parse_profile_bundle(&text).expect("round trip");
+        assert_eq!(parsed.workspaces.len(), 2);
+        let newer = text.replacen(
+            &format!("\"version\":{PROFILE_VERSION}"),
+            &format!("\"version\":{}", PROFILE_VERSION + 1),
+            1,
+        );
+        assert!(parse_profile_bundle(&newer)
+            .unwrap_err()
+            .contains("not supported"));
+        assert!(parse_profile_bundle("{}")
+            .unwrap_err()
+            .contains("missing `version`"));
+    }
+
+    #[test]
+    fn import_merges_or_replaces_and_handles_missing_roots() {
+        let bundle = export_bundle(
+            vec![
+                entry("kept", "/work/kept", None),
+                entry("new", "/work/new", None),
+                entry("gone", "/work/gone", None),
+                entry("gone-wt", "/work/gone-wt", Some("gone")),
+            ],
+            &AppSettings::default(),
+            None,
+            1,
+        );
+        let existing: HashMap<String, WorkspaceEntry> = [
+            ("local".to_string(), entry("local", "/work/local", None)),
+            (
+                "other-id".to_string(),
+                entry("other-id", "/work/kept", None),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let exists = |root: &Path| root != Path::new("/work/gone");
+
+        let (next, report) = plan_import(
+            bundle.workspaces.clone(),
+            &existing,
+            ProfileImportStrategy::default(),
+            exists,
+        );
+        assert_eq!(report.imported, vec!["new"]);
+        assert_eq!(report.unchanged, vec!["kept"]);
+        assert_eq!(report.skipped, vec!["gone", "gone-wt"]);
+        assert!(report.removed.is_empty());
+        assert_eq!(next.len(), 3);
+
+        let (next, report) = plan_import(
+            bundle.workspaces,
+            &existing,
+            ProfileImportStrategy {
+                mode: ProfileImportMode::Replace,
+                missing_roots: MissingRootPolicy::MarkBroken,
+            },
+            exists,
+        );
+        assert_eq!(report.imported, vec!["gone", "gone-wt", "kept", "new"]);
+        assert_eq!(report.broken, vec!["gone"]);
+        assert_eq!(report.removed, vec!["local", "other-id"]);
+        assert_eq!(next.len(), 4);
+
+        let current = AppSettings {
+            remote_backend_token: Some("local-token".to_string()),
+            ..AppSettings::default()
+        };
+        let presets = vec![FeaturePreset {
+            name: "fast".to_string(),
+            flags: BTreeMap::new(),
+        }];
+        let replaced = import_settings(
+            &current,
+            AppSettings::default(),
+            presets.clone(),
+            ProfileImportMode::Replace,
+        );
+        assert_eq!(
+            replaced.remote_backend_token.as_deref(),
+            Some("local-token")
+        );
+        assert_eq!(replaced.feature_presets, presets);
+    }
+}
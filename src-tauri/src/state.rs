@@ -1,15 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
 use tauri::{AppHandle, Manager};
 use tokio::process::Child;
 use tokio::sync::Mutex;
 
 use crate::dictation::DictationState;
+use crate::settings::app_kv::{read_app_kv, write_app_kv};
 use crate::shared::codex_core::CodexLoginCancelState;
-use crate::storage::{read_settings, read_workspaces};
+use crate::storage::{read_settings, read_workspaces, write_settings, write_workspaces};
 use crate::types::{AppSettings, TcpDaemonState, TcpDaemonStatus, WorkspaceEntry};
 
+/// Upper bound on how long quitting waits for saving state, syncing queued
+/// offline writes and in-flight remote calls, together.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+const SHUTDOWN_REMOTE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 pub(crate) struct TcpDaemonRuntime {
     pub(crate) child: Option<Child>,
     pub(crate) status: TcpDaemonStatus,
@@ -35,12 +45,39 @@ pub(crate) struct AppState {
     pub(crate) sessions: Mutex<HashMap<String, Arc<crate::codex::WorkspaceSession>>>,
     pub(crate) terminal_sessions: Mutex<HashMap<String, Arc<crate::terminal::TerminalSession>>>,
     pub(crate) remote_backend: Mutex<Option<crate::remote_backend::RemoteBackend>>,
+    pub(crate) remote_rate_limiter: crate::remote_backend::rate_limit::RemoteRateLimiter,
+    /// Last `busy` reported through `remote-pressure`.
+    pub(crate) remote_pressure: crate::remote_backend::pressure::PressureTracker,
+    pub(crate) remote_method_stats: crate::remote_backend::method_stats::MethodStats,
+    /// Reported by the remote backend on the latest connect.
+    pub(crate) remote_capabilities:
+        Mutex<Option<crate::remote_backend::capabilities::RemoteCapabilities>>,
+    /// Request ids of streamed remote reads and local `file_read_many` batches;
+    /// removing one cancels that read.
+    pub(crate) remote_reads_in_flight: Mutex<HashSet<String>>,
+    /// Remote scoped files kept for reads and queued writes while disconnected.
+    pub(crate) offline_mirror: Mutex<crate::remote_backend::offline_mirror::OfflineMirror>,
+    pub(crate) scope_watchers: crate::shared::files_core::ScopeWatchers,
+    /// Write generations of scoped files, which `readiness_cache` entries are
+    /// checked against.
+    pub(crate) file_generations: crate::files::generation::Generations,
+    /// Parsed from `AppSettings::scope_root_overrides` on every settings write.
+    pub(crate) scope_root_overrides: crate::files::policy::ScopeRootOverrides,
+    pub(crate) readiness_cache: crate::shared::files_core::ReadinessCache,
+    pub(crate) prewarm: crate::shared::files_core::PrewarmSlot,
+    pub(crate) workspace_health: crate::shared::workspaces_core::WorkspaceHealthCache,
+    pub(crate) tree_scans: crate::shared::tree_scan_core::TreeScans,
     pub(crate) storage_path: PathBuf,
     pub(crate) settings_path: PathBuf,
     pub(crate) app_settings: Mutex<AppSettings>,
+    pub(crate) config_drift: crate::shared::config_drift_core::ConfigDriftCache,
+    /// UI preferences owned by CodexMonitor; never written to Codex's `config.toml`.
+    pub(crate) app_kv_path: PathBuf,
+    pub(crate) app_kv: Mutex<serde_json::Map<String, serde_json::Value>>,
     pub(crate) dictation: Mutex<DictationState>,
     pub(crate) codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
     pub(crate) tcp_daemon: Mutex<TcpDaemonRuntime>,
+    shutdown_started: AtomicBool,
 }
 
 impl AppState {
@@ -51,19 +88,159 @@ impl AppState {
             .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| ".".into()));
         let storage_path = data_dir.join("workspaces.json");
         let settings_path = data_dir.join("settings.json");
+        let app_kv_path = data_dir.join("app-kv.json");
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        crate::codex::home::set_canonicalize_codex_home(app_settings.canonicalize_codex_home);
+        crate::codex::home::set_codex_home_override(app_settings.codex_home_override.as_deref());
+        let scope_root_overrides =
+            crate::files::policy::ScopeRootOverrides::new(&app_settings.scope_root_overrides);
+        let app_kv = read_app_kv(&app_kv_path).unwrap_or_default();
         Self {
             workspaces: Mutex::new(workspaces),
             sessions: Mutex::new(HashMap::new()),
             terminal_sessions: Mutex::new(HashMap::new()),
             remote_backend: Mutex::new(None),
+            remote_rate_limiter: crate::remote_backend::rate_limit::RemoteRateLimiter::default(),
+            remote_pressure: Default::default(),
+            remote_method_stats: Default::default(),
+            remote_capabilities: Mutex::new(None),
+            remote_reads_in_flight: Mutex::new(HashSet::new()),
+            offline_mirror: Mutex::new(crate::remote_backend::offline_mirror::OfflineMirror::load(
+                data_dir.join("offline-queue.json"),
+            )),
+            scope_watchers: Default::default(),
+            file_generations: crate::files::generation::Generations::new(),
+            scope_root_overrides,
+            readiness_cache: Default::default(),
+            prewarm: Default::default(),
+            workspace_health: Mutex::new(None),
+            tree_scans: Default::default(),
             storage_path,
             settings_path,
             app_settings: Mutex::new(app_settings),
+            config_drift: Mutex::new(None),
+            app_kv_path,
+            app_kv: Mutex::new(app_kv),
             dictation: Mutex::new(DictationState::default()),
             codex_login_cancels: Mutex::new(HashMap::new()),
             tcp_daemon: Mutex::new(TcpDaemonRuntime::default()),
+            shutdown_started: AtomicBool::new(false),
+        }
+    }
+
+    /// Tears down app-owned resources before exit: stops scope watchers, saves
+    /// workspace, settings and app-kv state that changed, syncs queued offline
+    /// writes and gives in-flight remote calls time to finish, all within
+    /// `SHUTDOWN_TIMEOUT`, then kills terminal sessions. Runs at most once;
+    /// later calls return immediately.
+    pub(crate) async fn on_shutdown(&self, app: &AppHandle) {
+        if self.shutdown_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        for (_, task) in self.scope_watchers.tasks.lock().await.drain() {
+            task.abort();
+        }
+
+        let finished = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+            self.save_changed_state().await;
+            self.drain_remote(app).await;
+        })
+        .await;
+        if finished.is_err() {
+            eprintln!(
+                "shutdown: still saving or syncing after {}s; closing anyway",
+                SHUTDOWN_TIMEOUT.as_secs()
+            );
+        }
+
+        let terminals: Vec<_> = self
+            .terminal_sessions
+            .lock()
+            .await
+            .drain()
+            .map(|(_, session)| session)
+            .collect();
+        if !terminals.is_empty() {
+            let _ = tokio::task::spawn_blocking(move || {
+                for session in terminals {
+                    let _ = session.child.blocking_lock().kill();
+                }
+            })
+            .await;
         }
     }
+
+    /// Writes each store whose in-memory state differs from the file. A file
+    /// that no longer reads cleanly (corrupt or unreadable) is left alone
+    /// rather than replaced with whatever defaults were loaded in its place.
+    async fn save_changed_state(&self) {
+        let workspaces = self.workspaces.lock().await.clone();
+        let settings = self.app_settings.lock().await.clone();
+        let app_kv = self.app_kv.lock().await.clone();
+        let storage_path = self.storage_path.clone();
+        let settings_path = self.settings_path.clone();
+        let app_kv_path = self.app_kv_path.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            save_if_changed(
+                "workspaces",
+                read_workspaces(&storage_path),
+                &workspaces,
+                |workspaces| {
+                    let list: Vec<_> = workspaces.values().cloned().collect();
+                    write_workspaces(&storage_path, &list)
+                },
+            );
+            save_if_changed(
+                "settings",
+                read_settings(&settings_path),
+                &settings,
+                |settings| write_settings(&settings_path, settings),
+            );
+            save_if_changed(
+                "app settings store",
+                read_app_kv(&app_kv_path),
+                &app_kv,
+                |app_kv| write_app_kv(&app_kv_path, app_kv),
+            );
+        })
+        .await;
+    }
+
+    /// Syncs queued offline writes over a live connection, then waits for the
+    /// remaining in-flight calls.
+    async fn drain_remote(&self, app: &AppHandle) {
+        let remote = self.remote_backend.lock().await.take();
+        let Some(remote) = remote else {
+            return;
+        };
+        if self.offline_mirror.lock().await.status().pending_writes > 0 {
+            crate::remote_backend::offline_mirror::sync_pending_writes(self, &remote, app).await;
+        }
+        while remote.has_pending_calls().await {
+            tokio::time::sleep(SHUTDOWN_REMOTE_POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn save_if_changed<T: Serialize>(
+    label: &str,
+    on_disk: Result<T, String>,
+    current: &T,
+    write: impl FnOnce(&T) -> Result<(), String>,
+) {
+    let on_disk = match on_disk {
+        Ok(on_disk) => on_disk,
+        Err(error) => {
+            eprintln!("shutdown: not saving {label}; the file does not load cleanly: {error}");
+            return;
+        }
+    };
+    if serde_json::to_value(&on_disk).ok() == serde_json::to_value(current).ok() {
+        return;
+    }
+    if let Err(error) = write(current) {
+        eprintln!("shutdown: failed to save {label}: {error}");
+    }
 }
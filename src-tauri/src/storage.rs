@@ -1,9 +1,20 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 use crate::types::{AppSettings, WorkspaceEntry};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceStateRepairReport {
+    /// `false` when the file was already valid (or missing) and left untouched.
+    pub(crate) repaired: bool,
+    pub(crate) recovered: usize,
+    pub(crate) dropped: usize,
+    pub(crate) backup_path: Option<String>,
+}
+
 pub(crate) fn read_workspaces(path: &PathBuf) -> Result<HashMap<String, WorkspaceEntry>, String> {
     if !path.exists() {
         return Ok(HashMap::new());
@@ -24,6 +35,117 @@ pub(crate) fn write_workspaces(path: &PathBuf, entries: &[WorkspaceEntry]) -> Re
     std::fs::write(path, data).map_err(|e| e.to_string())
 }
 
+/// Salvages valid entries from a corrupt workspaces file. The original is
+/// copied to a `.corrupt-<ms>.bak` sibling before a clean file is written.
+pub(crate) fn repair_workspaces(
+    path: &PathBuf,
+) -> Result<(Vec<WorkspaceEntry>, WorkspaceStateRepairReport), String> {
+    if !path.exists() {
+        let report = WorkspaceStateRepairReport {
+            repaired: false,
+            recovered: 0,
+            dropped: 0,
+            backup_path: None,
+        };
+        return Ok((Vec::new(), report));
+    }
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let data = String::from_utf8_lossy(&bytes);
+    if let Ok(list) = serde_json::from_str::<Vec<WorkspaceEntry>>(&data) {
+        let ids: HashSet<&str> = list.iter().map(|entry| entry.id.as_str()).collect();
+        if ids.len() == list.len() {
+            let report = WorkspaceStateRepairReport {
+                repaired: false,
+                recovered: list.len(),
+                dropped: 0,
+                backup_path: None,
+            };
+            return Ok((list, report));
+        }
+    }
+
+    let (candidates, mut dropped) = match serde_json::from_str::<Value>(&data) {
+        Ok(Value::Array(items)) => (items, 0),
+        Ok(_) => (Vec::new(), 1),
+        Err(_) => salvage_array_objects(&data),
+    };
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for candidate in candidates {
+        match serde_json::from_value::<WorkspaceEntry>(candidate) {
+            Ok(entry) if seen.insert(entry.id.clone()) => entries.push(entry),
+            _ => dropped += 1,
+        }
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0);
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "workspaces.json".to_string());
+    let backup_path = path.with_file_name(format!("{file_name}.corrupt-{timestamp}.bak"));
+    std::fs::copy(path, &backup_path).map_err(|e| format!("Failed to back up {file_name}: {e}"))?;
+    write_workspaces(path, &entries)?;
+
+    let report = WorkspaceStateRepairReport {
+        repaired: true,
+        recovered: entries.len(),
+        dropped,
+        backup_path: Some(backup_path.to_string_lossy().to_string()),
+    };
+    Ok((entries, report))
+}
+
+/// Collects the complete objects of a truncated or malformed top-level JSON
+/// array. Returns the parsed objects and how many could not be parsed.
+fn salvage_array_objects(data: &str) -> (Vec<Value>, usize) {
+    let mut objects = Vec::new();
+    let mut dropped = 0;
+    let mut depth = 0usize;
+    let mut start = None;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, ch) in data.char_indices() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '[' | '{' => {
+                if ch == '{' && depth == 1 {
+                    start = Some(index);
+                }
+                depth += 1;
+            }
+            ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                if ch == '}' && depth == 1 {
+                    if let Some(begin) = start.take() {
+                        match serde_json::from_str::<Value>(&data[begin..=index]) {
+                            Ok(value) => objects.push(value),
+                            Err(_) => dropped += 1,
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if start.is_some() {
+        dropped += 1;
+    }
+    (objects, dropped)
+}
+
 pub(crate) fn read_settings(path: &PathBuf) -> Result<AppSettings, String> {
     if !path.exists() {
         return Ok(AppSettings::default());
@@ -94,7 +216,7 @@ fn migrate_follow_up_message_behavior(value: &mut Value) {
 
 #[cfg(test)]
 mod tests {
-    use super::{read_settings, read_workspaces, write_workspaces};
+    use super::{read_settings, read_workspaces, repair_workspaces, write_workspaces};
     use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
     use uuid::Uuid;
 
@@ -228,4 +350,58 @@ mod tests {
         let settings = read_settings(&path).expect("read settings");
         assert_eq!(settings.follow_up_message_behavior, "queue");
     }
+
+    const VALID_ENTRY: &str =
+        r#"{"id":"w1","name":"One","path":"/tmp/one","settings":{"sidebarCollapsed":false}}"#;
+
+    #[test]
+    fn repair_salvages_entries_from_truncated_file() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).expect("create temp dir");
+        let path = temp_dir.join("workspaces.json");
+        let corrupt =
+            format!(r#"[{VALID_ENTRY}, {{"id":"w2","name":"No path"}}, {{"id":"w3","name":"Trunc"#);
+        std::fs::write(&path, &corrupt).expect("write corrupt file");
+
+        let (entries, report) = repair_workspaces(&path).expect("repair");
+        assert!(report.repaired);
+        assert_eq!(report.recovered, 1);
+        assert_eq!(report.dropped, 2);
+        assert_eq!(entries[0].id, "w1");
+
+        let backup = report.backup_path.expect("backup path");
+        assert_eq!(
+            std::fs::read_to_string(backup).expect("read backup"),
+            corrupt
+        );
+        let reread = read_workspaces(&path).expect("clean file parses");
+        assert!(reread.contains_key("w1"));
+    }
+
+    #[test]
+    fn repair_leaves_valid_file_untouched() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).expect("create temp dir");
+        let path = temp_dir.join("workspaces.json");
+        std::fs::write(&path, format!("[{VALID_ENTRY}]")).expect("write file");
+
+        let (entries, report) = repair_workspaces(&path).expect("repair");
+        assert!(!report.repaired);
+        assert_eq!(report.recovered, 1);
+        assert_eq!(entries.len(), 1);
+        assert!(report.backup_path.is_none());
+    }
+
+    #[test]
+    fn repair_drops_duplicate_ids() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).expect("create temp dir");
+        let path = temp_dir.join("workspaces.json");
+        std::fs::write(&path, format!("[{VALID_ENTRY},{VALID_ENTRY}]")).expect("write file");
+
+        let (entries, report) = repair_workspaces(&path).expect("repair");
+        assert!(report.repaired);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(report.dropped, 1);
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -329,6 +331,24 @@ pub(crate) struct WorkspaceSettings {
     pub(crate) launch_scripts: Option<Vec<LaunchScriptEntry>>,
     #[serde(default, rename = "worktreeSetupScript")]
     pub(crate) worktree_setup_script: Option<String>,
+    /// Pinned `CODEX_HOME` for this workspace; `None` uses the global default.
+    #[serde(default, rename = "codexHome")]
+    pub(crate) codex_home: Option<String>,
+    /// Default model for new threads here; `None` uses the global `config.toml`.
+    #[serde(default, rename = "modelOverride")]
+    pub(crate) model_override: Option<String>,
+    /// Default personality here; `None` uses the global `config.toml`.
+    #[serde(default, rename = "personalityOverride")]
+    pub(crate) personality_override: Option<String>,
+    /// `sandbox_mode` here; `None` uses the global `config.toml`.
+    #[serde(default, rename = "sandboxModeOverride")]
+    pub(crate) sandbox_mode_override: Option<String>,
+    /// `approval_policy` here; `None` uses the global `config.toml`.
+    #[serde(default, rename = "approvalPolicyOverride")]
+    pub(crate) approval_policy_override: Option<String>,
+    /// `[features]` flags set for this workspace on top of the global ones.
+    #[serde(default, rename = "featureOverrides")]
+    pub(crate) feature_overrides: BTreeMap<String, bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -347,6 +367,15 @@ pub(crate) struct WorktreeSetupStatus {
     pub(crate) script: Option<String>,
 }
 
+/// A named snapshot of `[features]` flags, kept in app settings rather than
+/// `config.toml`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct FeaturePreset {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) flags: BTreeMap<String, bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct OpenAppTarget {
     pub(crate) id: String,
@@ -393,6 +422,19 @@ pub(crate) struct AppSettings {
     pub(crate) active_remote_backend_id: Option<String>,
     #[serde(default, rename = "keepDaemonRunningAfterAppClose")]
     pub(crate) keep_daemon_running_after_app_close: bool,
+    #[serde(default, rename = "canonicalizeCodexHome")]
+    pub(crate) canonicalize_codex_home: bool,
+    /// Set by `codex_home_relocate`; preferred over `CODEX_HOME` and `~/.codex`.
+    #[serde(default, rename = "codexHomeOverride")]
+    pub(crate) codex_home_override: Option<String>,
+    #[serde(default, rename = "featurePresets")]
+    pub(crate) feature_presets: Vec<FeaturePreset>,
+    #[serde(default, rename = "scopeRootOverrides")]
+    pub(crate) scope_root_overrides: BTreeMap<String, String>,
+    /// Command `open_in_external_editor` runs, e.g. `code --wait`; the system
+    /// default editor when unset.
+    #[serde(default, rename = "externalEditor")]
+    pub(crate) external_editor: Option<String>,
     #[serde(default = "default_access_mode", rename = "defaultAccessMode")]
     pub(crate) default_access_mode: String,
     #[serde(
@@ -1119,6 +1161,11 @@ impl Default for AppSettings {
             remote_backends: default_remote_backends(),
             active_remote_backend_id: None,
             keep_daemon_running_after_app_close: false,
+            canonicalize_codex_home: false,
+            codex_home_override: None,
+            feature_presets: Vec::new(),
+            scope_root_overrides: BTreeMap::new(),
+            external_editor: None,
             default_access_mode: "current".to_string(),
             review_delivery_mode: default_review_delivery_mode(),
             composer_model_shortcut: default_composer_model_shortcut(),
@@ -1217,6 +1264,10 @@ mod tests {
         assert!(settings.remote_backends.is_empty());
         assert!(settings.active_remote_backend_id.is_none());
         assert!(!settings.keep_daemon_running_after_app_close);
+        assert!(!settings.canonicalize_codex_home);
+        assert!(settings.feature_presets.is_empty());
+        assert!(settings.scope_root_overrides.is_empty());
+        assert!(settings.external_editor.is_none());
         assert_eq!(settings.default_access_mode, "current");
         assert_eq!(settings.review_delivery_mode, "inline");
         let expected_primary = if cfg!(target_os = "macos") {
@@ -17,11 +17,19 @@ use super::worktree::{
 };
 
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{WorkspaceCheck, WorkspaceHealth};
 use crate::codex::spawn_workspace_session;
+use crate::event_sink::TauriEventSink;
 use crate::git_utils::resolve_git_root;
 use crate::remote_backend;
+use crate::shared::workspaces_core::{
+    AddedWorkspace, EffectiveFeatureFlag, EffectiveWorkspaceDefault, ImportedWorkspace,
+    ProfileImportReport, ProfileImportRequest, ProfileImportStrategy, SandboxResolution,
+    WorkspaceDefaultKey, WorkspaceOverlap,
+};
 use crate::shared::{workspace_rpc, workspaces_core};
 use crate::state::AppState;
+use crate::storage::WorkspaceStateRepairReport;
 use crate::types::{WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus};
 
 fn spawn_with_app(
@@ -89,6 +97,386 @@ pub(crate) async fn list_workspaces(
     Ok(workspaces_core::list_workspaces_core(&state.workspaces, &state.sessions).await)
 }
 
+#[tauri::command]
+pub(crate) async fn workspace_state_repair(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceStateRepairReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_state_repair",
+            workspace_remote_empty_params(),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_state_repair_core(&state.workspaces, &state.storage_path).await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_list_broken(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_list_broken",
+            workspace_remote_empty_params(),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(workspaces_core::workspace_list_broken_core(&state.workspaces, &state.sessions).await)
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_check(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceCheck, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_check",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_check_core(&state.workspaces, &workspace_id).await
+}
+
+/// The root check from startup, also sent as `workspace-health`.
+#[tauri::command]
+pub(crate) async fn workspace_health(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceHealth, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_health",
+            workspace_remote_empty_params(),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(workspaces_core::workspace_health_core(&state.workspaces, &state.workspace_health).await)
+}
+
+/// Checks every workspace root once the app is up, without holding up the
+/// window, and emits the summary as `workspace-health`. In remote mode this
+/// connects, and each connect re-emits the daemon's summary.
+pub(crate) async fn workspace_health_on_startup(app: AppHandle) {
+    let state = app.state::<AppState>();
+    if remote_backend::is_remote_mode(&*state).await {
+        if let Err(err) = remote_backend::connect_remote(&state, app.clone()).await {
+            eprintln!("workspace health: failed to connect to the remote backend: {err}");
+        }
+        return;
+    }
+    workspaces_core::workspace_health_startup_core(
+        &state.workspaces,
+        &state.workspace_health,
+        &TauriEventSink::new(app.clone()),
+    )
+    .await;
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_relink(
+    id: String,
+    new_root: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let new_root = remote_backend::normalize_path_for_remote(new_root);
+        let request = workspace_rpc::WorkspaceRelinkRequest { id, new_root };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_relink",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_relink_core(
+        &state.workspaces,
+        &state.sessions,
+        &state.storage_path,
+        TauriEventSink::new(app),
+        id,
+        new_root,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_prune_broken(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<String>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_prune_broken",
+            workspace_remote_empty_params(),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_prune_broken_core(
+        &state.workspaces,
+        &state.sessions,
+        &state.storage_path,
+        TauriEventSink::new(app),
+    )
+    .await
+}
+
+/// The workspace list, app settings and feature presets as a versioned JSON
+/// bundle for `profile_import`.
+#[tauri::command]
+pub(crate) async fn profile_export(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "profile_export",
+            workspace_remote_empty_params(),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::profile_export_core(&state.workspaces, &state.app_settings).await
+}
+
+#[tauri::command]
+pub(crate) async fn profile_import(
+    bundle: String,
+    strategy: Option<ProfileImportStrategy>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ProfileImportReport, String> {
+    let request = ProfileImportRequest {
+        bundle,
+        strategy: strategy.unwrap_or_default(),
+    };
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "profile_import",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::profile_import_core(
+        request,
+        &state.workspaces,
+        &state.sessions,
+        &state.app_settings,
+        &state.storage_path,
+        &state.settings_path,
+        &state.scope_root_overrides,
+        TauriEventSink::new(app),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_find_duplicates(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceOverlap>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_find_duplicates",
+            workspace_remote_empty_params(),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(workspaces_core::workspace_find_duplicates_core(&state.workspaces).await)
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_set_codex_home(
+    id: String,
+    path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::SetWorkspaceCodexHomeRequest {
+            id,
+            path: path.map(remote_backend::normalize_path_for_remote),
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_set_codex_home",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_set_codex_home_core(
+        &state.workspaces,
+        &state.sessions,
+        &state.storage_path,
+        id,
+        path,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_effective_model(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<EffectiveWorkspaceDefault, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_effective_model",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_effective_model_core(&state.workspaces, &workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_effective_personality(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<EffectiveWorkspaceDefault, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_effective_personality",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_effective_personality_core(&state.workspaces, &workspace_id).await
+}
+
+/// The feature flags new threads in the workspace start with.
+#[tauri::command]
+pub(crate) async fn workspace_effective_features(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<EffectiveFeatureFlag>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_effective_features",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_effective_features_core(&state.workspaces, &workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn effective_sandbox(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<SandboxResolution, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceIdRequest { workspace_id };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "effective_sandbox",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::effective_sandbox_core(&state.workspaces, &workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_set_default_override(
+    workspace_id: String,
+    key: WorkspaceDefaultKey,
+    value: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<EffectiveWorkspaceDefault, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::SetWorkspaceDefaultOverrideRequest {
+            workspace_id,
+            key,
+            value,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_set_default_override",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_set_default_override_core(
+        &state.workspaces,
+        &state.storage_path,
+        &workspace_id,
+        key,
+        value,
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn set_workspace_runtime_codex_args(
     workspace_id: String,
@@ -145,12 +533,17 @@ pub(crate) async fn is_workspace_path_dir(
 #[tauri::command]
 pub(crate) async fn add_workspace(
     path: String,
+    reject_duplicate: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> Result<AddedWorkspace, String> {
+    let reject_duplicate = reject_duplicate.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         let path = remote_backend::normalize_path_for_remote(path);
-        let request = workspace_rpc::AddWorkspaceRequest { path };
+        let request = workspace_rpc::AddWorkspaceRequest {
+            path,
+            reject_duplicate,
+        };
         let response = remote_backend::call_remote(
             &*state,
             app,
@@ -163,6 +556,44 @@ pub(crate) async fn add_workspace(
 
     workspaces_core::add_workspace_core(
         path,
+        None,
+        reject_duplicate,
+        &state.workspaces,
+        &state.sessions,
+        &state.app_settings,
+        &state.storage_path,
+        |entry, default_bin, codex_args, codex_home| {
+            spawn_with_app(&app, entry, default_bin, codex_args, codex_home)
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_import_existing(
+    root_path: String,
+    name: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ImportedWorkspace, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let request = workspace_rpc::WorkspaceImportExistingRequest {
+            root_path: remote_backend::normalize_path_for_remote(root_path),
+            name,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_import_existing",
+            workspace_remote_params(&request)?,
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::workspace_import_existing_core(
+        root_path,
+        name,
         &state.workspaces,
         &state.sessions,
         &state.app_settings,
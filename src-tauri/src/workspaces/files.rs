@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::utils::normalize_git_path;
 
-fn should_skip_dir(name: &str) -> bool {
+pub(crate) fn should_skip_dir(name: &str) -> bool {
     matches!(
         name,
         ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
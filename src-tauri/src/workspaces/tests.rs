@@ -56,6 +56,12 @@ fn workspace_with_id_and_kind(
             launch_script: None,
             launch_scripts: None,
             worktree_setup_script: None,
+            codex_home: None,
+            model_override: None,
+            personality_override: None,
+            sandbox_mode_override: None,
+            approval_policy_override: None,
+            feature_overrides: Default::default(),
         },
     }
 }
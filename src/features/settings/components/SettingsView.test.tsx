@@ -77,6 +77,11 @@ const baseSettings: AppSettings = {
   ],
   activeRemoteBackendId: "remote-default",
   keepDaemonRunningAfterAppClose: false,
+  canonicalizeCodexHome: false,
+  codexHomeOverride: null,
+  featurePresets: [],
+  scopeRootOverrides: {},
+  externalEditor: null,
   defaultAccessMode: "current",
   reviewDeliveryMode: "inline",
   composerModelShortcut: null,
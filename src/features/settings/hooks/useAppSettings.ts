@@ -143,6 +143,11 @@ function buildDefaultSettings(): AppSettings {
     remoteBackends: [defaultRemote],
     activeRemoteBackendId: defaultRemote.id,
     keepDaemonRunningAfterAppClose: false,
+    canonicalizeCodexHome: false,
+    codexHomeOverride: null,
+    featurePresets: [],
+    scopeRootOverrides: {},
+    externalEditor: null,
     defaultAccessMode: "current",
     reviewDeliveryMode: "inline",
     composerModelShortcut: isMac ? "cmd+shift+m" : "ctrl+shift+m",
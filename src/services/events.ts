@@ -1,4 +1,5 @@
 import { listen } from "@tauri-apps/api/event";
+import type { RemoteErrorClass } from "./tauri";
 import type {
   AppServerEvent,
   DictationEvent,
@@ -19,6 +20,100 @@ export type TerminalExitEvent = {
   terminalId: string;
 };
 
+export type OperationProgressEvent = {
+  operationId: string;
+  completed: number;
+  total: number;
+  currentItem: string | null;
+};
+
+export type OperationFinishedEvent = {
+  operationId: string;
+  error?: string;
+};
+
+export type ScopeChangedEvent = {
+  watchId: string;
+  path: string;
+  change: "added" | "removed" | "modified";
+};
+
+export type BatchChangedEvent = {
+  batchId: string;
+  changes: ScopeChangedEvent[];
+};
+
+export type WorkspacesChangedEvent = {
+  workspaceIds: string[];
+};
+
+export type ConfigKeyChange = {
+  path: string;
+  old: unknown | null;
+  new: unknown | null;
+};
+
+export type ConfigPatchEvent = {
+  origin: string;
+  codexHome: string;
+  changes: ConfigKeyChange[];
+};
+
+export type RemoteReadProgressEvent = {
+  requestId: string;
+  bytesLoaded: number;
+  totalBytes: number;
+};
+
+export type RemotePressureEvent = {
+  inflight: number;
+  queued: number;
+  rateLimited: boolean;
+  busy: boolean;
+};
+
+export type RemoteCallFailedEvent = {
+  method: string;
+  requestId: string;
+  errorClass: RemoteErrorClass;
+  transient: boolean;
+  error: string;
+};
+
+export type WorkspaceHealthEvent = {
+  checkedAtMs: number;
+  workspaces: {
+    workspaceId: string;
+    path: string;
+    status: "ok" | "missing" | "not_directory" | "unreadable";
+    error: string | null;
+  }[];
+  broken: string[];
+};
+
+export type TreeNode = {
+  path: string;
+  kind: "file" | "dir" | "symlink";
+  depth: number;
+};
+
+export type TreeEntryEvent = {
+  scanId: string;
+  entries: TreeNode[];
+};
+
+export type TreeTruncatedEvent = {
+  scanId: string;
+  limit: number;
+};
+
+export type TreeDoneEvent = {
+  scanId: string;
+  total: number;
+  cancelled: boolean;
+  error?: string;
+};
+
 type SubscriptionOptions = {
   onError?: (error: unknown) => void;
 };
@@ -91,6 +186,20 @@ const dictationDownloadHub = createEventHub<DictationModelStatus>("dictation-dow
 const dictationEventHub = createEventHub<DictationEvent>("dictation-event");
 const terminalOutputHub = createEventHub<TerminalOutputEvent>("terminal-output");
 const terminalExitHub = createEventHub<TerminalExitEvent>("terminal-exit");
+const operationProgressHub = createEventHub<OperationProgressEvent>("operation-progress");
+const operationDoneHub = createEventHub<OperationFinishedEvent>("operation-done");
+const operationFailedHub = createEventHub<OperationFinishedEvent>("operation-failed");
+const scopeChangedHub = createEventHub<ScopeChangedEvent>("scope-changed");
+const batchChangedHub = createEventHub<BatchChangedEvent>("batch-changed");
+const workspacesChangedHub = createEventHub<WorkspacesChangedEvent>("workspaces-changed");
+const configPatchHub = createEventHub<ConfigPatchEvent>("config-patch");
+const remoteReadProgressHub = createEventHub<RemoteReadProgressEvent>("remote-read-progress");
+const remotePressureHub = createEventHub<RemotePressureEvent>("remote-pressure");
+const remoteCallFailedHub = createEventHub<RemoteCallFailedEvent>("remote-call-failed");
+const workspaceHealthHub = createEventHub<WorkspaceHealthEvent>("workspace-health");
+const treeEntryHub = createEventHub<TreeEntryEvent>("tree-entry");
+const treeTruncatedHub = createEventHub<TreeTruncatedEvent>("tree-truncated");
+const treeDoneHub = createEventHub<TreeDoneEvent>("tree-done");
 const updaterCheckHub = createEventHub<void>("updater-check");
 const trayOpenThreadHub = createEventHub<TrayOpenThreadPayload>("tray-open-thread");
 const menuNewAgentHub = createEventHub<void>("menu-new-agent");
@@ -153,6 +262,104 @@ export function subscribeTerminalExit(
   return terminalExitHub.subscribe(onEvent, options);
 }
 
+export function subscribeOperationProgress(
+  onEvent: (event: OperationProgressEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return operationProgressHub.subscribe(onEvent, options);
+}
+
+export function subscribeOperationDone(
+  onEvent: (event: OperationFinishedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return operationDoneHub.subscribe(onEvent, options);
+}
+
+export function subscribeOperationFailed(
+  onEvent: (event: OperationFinishedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return operationFailedHub.subscribe(onEvent, options);
+}
+
+export function subscribeScopeChanged(
+  onEvent: (event: ScopeChangedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return scopeChangedHub.subscribe(onEvent, options);
+}
+
+export function subscribeBatchChanged(
+  onEvent: (event: BatchChangedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return batchChangedHub.subscribe(onEvent, options);
+}
+
+export function subscribeWorkspacesChanged(
+  onEvent: (event: WorkspacesChangedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return workspacesChangedHub.subscribe(onEvent, options);
+}
+
+export function subscribeConfigPatch(
+  onEvent: (event: ConfigPatchEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return configPatchHub.subscribe(onEvent, options);
+}
+
+export function subscribeRemoteReadProgress(
+  onEvent: (event: RemoteReadProgressEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return remoteReadProgressHub.subscribe(onEvent, options);
+}
+
+export function subscribeRemotePressure(
+  onEvent: (event: RemotePressureEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return remotePressureHub.subscribe(onEvent, options);
+}
+
+export function subscribeRemoteCallFailed(
+  onEvent: (event: RemoteCallFailedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return remoteCallFailedHub.subscribe(onEvent, options);
+}
+
+export function subscribeWorkspaceHealth(
+  onEvent: (event: WorkspaceHealthEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return workspaceHealthHub.subscribe(onEvent, options);
+}
+
+export function subscribeTreeEntry(
+  onEvent: (event: TreeEntryEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return treeEntryHub.subscribe(onEvent, options);
+}
+
+export function subscribeTreeTruncated(
+  onEvent: (event: TreeTruncatedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return treeTruncatedHub.subscribe(onEvent, options);
+}
+
+export function subscribeTreeDone(
+  onEvent: (event: TreeDoneEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return treeDoneHub.subscribe(onEvent, options);
+}
+
 export function subscribeUpdaterCheck(
   onEvent: () => void,
   options?: SubscriptionOptions,
@@ -8,6 +8,8 @@ import {
   compactThread,
   createGitHubRepo,
   fetchGit,
+  fileCopy,
+  fileSearch,
   forkThread,
   getAppsList,
   getAgentsSettings,
@@ -528,6 +530,36 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("copies a scoped file between scopes", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+
+    await fileCopy(
+      { scope: "workspace", kind: "agents", workspaceId: "ws-agent" },
+      { scope: "global", kind: "agents" },
+    );
+
+    expect(invokeMock).toHaveBeenCalledWith("file_copy", {
+      source: { scope: "workspace", kind: "agents", workspaceId: "ws-agent" },
+      target: { scope: "global", kind: "agents" },
+    });
+  });
+
+  it("searches scoped files with default options", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await fileSearch("workspace", "model", { workspaceId: "ws-1" });
+
+    expect(invokeMock).toHaveBeenCalledWith("file_search", {
+      scope: "workspace",
+      workspaceId: "ws-1",
+      query: "model",
+      regex: false,
+      maxResults: 200,
+    });
+  });
+
   it("reads agents settings", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({
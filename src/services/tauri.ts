@@ -3,6 +3,7 @@ import { open, save } from "@tauri-apps/plugin-dialog";
 import type { Options as NotificationOptions } from "@tauri-apps/plugin-notification";
 import type {
   AppSettings,
+  FeaturePreset,
   CodexUpdateResult,
   CodexDoctorResult,
   DictationModelStatus,
@@ -104,10 +105,302 @@ export async function listWorkspaces(): Promise<WorkspaceInfo[]> {
   }
 }
 
+export type WorkspaceStateRepairReport = {
+  repaired: boolean;
+  recovered: number;
+  dropped: number;
+  backupPath: string | null;
+};
+
+export async function repairWorkspaceState(): Promise<WorkspaceStateRepairReport> {
+  return invoke<WorkspaceStateRepairReport>("workspace_state_repair");
+}
+
+export async function listBrokenWorkspaces(): Promise<WorkspaceInfo[]> {
+  return invoke<WorkspaceInfo[]>("workspace_list_broken");
+}
+
+export async function relinkWorkspace(id: string, newRoot: string): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("workspace_relink", { id, newRoot });
+}
+
+export async function pruneBrokenWorkspaces(): Promise<string[]> {
+  return invoke<string[]>("workspace_prune_broken");
+}
+
+export type WorkspaceCheck = {
+  workspaceId: string;
+  path: string;
+  status: "ok" | "missing" | "not_directory" | "unreadable";
+  error: string | null;
+};
+
+export type WorkspaceHealth = {
+  checkedAtMs: number;
+  workspaces: WorkspaceCheck[];
+  broken: string[];
+};
+
+export async function checkWorkspace(workspaceId: string): Promise<WorkspaceCheck> {
+  return invoke<WorkspaceCheck>("workspace_check", { workspaceId });
+}
+
+export async function getWorkspaceHealth(): Promise<WorkspaceHealth> {
+  return invoke<WorkspaceHealth>("workspace_health");
+}
+
+export type ProfileImportStrategy = {
+  mode?: "merge" | "replace";
+  missingRoots?: "skip" | "markBroken";
+};
+
+export type ProfileImportReport = {
+  imported: string[];
+  broken: string[];
+  skipped: string[];
+  unchanged: string[];
+  removed: string[];
+};
+
+export async function exportProfile(): Promise<string> {
+  return invoke<string>("profile_export");
+}
+
+export async function importProfile(
+  bundle: string,
+  strategy: ProfileImportStrategy = {},
+): Promise<ProfileImportReport> {
+  return invoke<ProfileImportReport>("profile_import", { bundle, strategy });
+}
+
+export type WorkspaceOverlap = {
+  kind: "duplicate" | "nested";
+  workspaceId: string;
+  path: string;
+  otherWorkspaceId: string;
+  otherPath: string;
+};
+
+export async function findDuplicateWorkspaces(): Promise<WorkspaceOverlap[]> {
+  return invoke<WorkspaceOverlap[]>("workspace_find_duplicates");
+}
+
+export async function setWorkspaceCodexHome(
+  id: string,
+  path: string | null,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("workspace_set_codex_home", { id, path });
+}
+
+export type EffectiveWorkspaceDefault = {
+  value: string | null;
+  source: "workspace" | "global" | "default";
+};
+
+export async function getWorkspaceEffectiveModel(
+  workspaceId: string,
+): Promise<EffectiveWorkspaceDefault> {
+  return invoke<EffectiveWorkspaceDefault>("workspace_effective_model", {
+    workspaceId,
+  });
+}
+
+export async function getWorkspaceEffectivePersonality(
+  workspaceId: string,
+): Promise<EffectiveWorkspaceDefault> {
+  return invoke<EffectiveWorkspaceDefault>("workspace_effective_personality", {
+    workspaceId,
+  });
+}
+
+export type EffectiveFeatureFlag = {
+  key: string;
+  enabled: boolean;
+  source: EffectiveWorkspaceDefault["source"];
+};
+
+export async function getWorkspaceEffectiveFeatures(
+  workspaceId: string,
+): Promise<EffectiveFeatureFlag[]> {
+  return invoke<EffectiveFeatureFlag[]>("workspace_effective_features", {
+    workspaceId,
+  });
+}
+
+export type SandboxResolution = {
+  sandboxMode: EffectiveWorkspaceDefault;
+  approvalPolicy: EffectiveWorkspaceDefault;
+  warnings: string[];
+};
+
+export async function getEffectiveSandbox(
+  workspaceId: string,
+): Promise<SandboxResolution> {
+  return invoke<SandboxResolution>("effective_sandbox", { workspaceId });
+}
+
+export async function setWorkspaceDefaultOverride(
+  workspaceId: string,
+  key: "model" | "personality" | "sandbox_mode" | "approval_policy",
+  value: string | null,
+): Promise<EffectiveWorkspaceDefault> {
+  return invoke<EffectiveWorkspaceDefault>("workspace_set_default_override", {
+    workspaceId,
+    key,
+    value,
+  });
+}
+
 export async function getCodexConfigPath(): Promise<string> {
   return invoke<string>("get_codex_config_path");
 }
 
+export type CodexHomeRelocation = {
+  from: string;
+  to: string;
+  copied: string[];
+  bytes: number;
+  skipped: string[];
+  removedOld: boolean;
+  removeError: string | null;
+};
+
+export async function relocateCodexHome(
+  newPath: string,
+  options: { copyAll?: boolean; overwrite?: boolean; removeOld?: boolean } = {},
+): Promise<CodexHomeRelocation> {
+  return invoke<CodexHomeRelocation>("codex_home_relocate", {
+    newPath,
+    copyAll: options.copyAll ?? false,
+    overwrite: options.overwrite ?? false,
+    removeOld: options.removeOld ?? false,
+  });
+}
+
+export type LocationEntry = {
+  path: string | null;
+  exists: boolean;
+};
+
+export type Locations = {
+  codexHome: LocationEntry;
+  configToml: LocationEntry;
+  appDataDir: LocationEntry;
+  workspacesFile: LocationEntry;
+  logDir: LocationEntry;
+  remoteEndpoint: string | null;
+};
+
+export async function getLocations(): Promise<Locations> {
+  return invoke<Locations>("locations");
+}
+
+export async function getAppSetting<T = unknown>(key: string): Promise<T | null> {
+  return invoke<T | null>("app_setting_get", { key });
+}
+
+export async function setAppSetting(key: string, value: unknown): Promise<void> {
+  return invoke("app_setting_set", { key, value: value ?? null });
+}
+
+export async function getAllAppSettings(): Promise<Record<string, unknown>> {
+  return invoke<Record<string, unknown>>("app_settings_all");
+}
+
+export type RemoteRateLimitBucket = {
+  perSecond: number;
+  burst: number;
+};
+
+export type RemoteRateLimitConfig = {
+  enabled: boolean;
+  reads: RemoteRateLimitBucket;
+  writes: RemoteRateLimitBucket;
+  maxQueueWaitMs: number;
+};
+
+export async function getRemoteRateLimits(): Promise<RemoteRateLimitConfig> {
+  return invoke<RemoteRateLimitConfig>("get_remote_rate_limits");
+}
+
+export async function setRemoteRateLimits(
+  config: RemoteRateLimitConfig,
+): Promise<RemoteRateLimitConfig> {
+  return invoke<RemoteRateLimitConfig>("set_remote_rate_limits", { config });
+}
+
+export type OfflineStatus = {
+  mirroredFiles: number;
+  mirroredBytes: number;
+  maxBytes: number;
+  pendingWrites: number;
+  conflicts: ScopedFileRef[];
+  failures: { file: ScopedFileRef; error: string }[];
+};
+
+export async function getOfflineStatus(): Promise<OfflineStatus> {
+  return invoke<OfflineStatus>("offline_status");
+}
+
+export type RemoteCapabilities = {
+  protocolVersion: number | null;
+  methods: string[] | null;
+};
+
+export async function getRemoteCapabilities(): Promise<RemoteCapabilities | null> {
+  return invoke<RemoteCapabilities | null>("remote_capabilities");
+}
+
+export type RemoteResetSummary = {
+  cacheEntriesCleared: number;
+  queuedWritesFlushed: number;
+  queuedWritesDropped: number;
+  requestsCancelled: number;
+};
+
+export async function resetRemoteDiagnostics(
+  flushQueue: boolean,
+): Promise<RemoteResetSummary> {
+  return invoke<RemoteResetSummary>("remote_reset_all", { flushQueue });
+}
+
+export type RemotePressure = {
+  inflight: number;
+  queued: number;
+  rateLimited: boolean;
+  busy: boolean;
+};
+
+export async function getRemotePressure(): Promise<RemotePressure> {
+  return invoke<RemotePressure>("remote_pressure");
+}
+
+export type RemoteErrorClass =
+  | "connectivity"
+  | "timeout"
+  | "server"
+  | "cancelled"
+  | "rate_limited"
+  | "request";
+
+export type RemoteMethodStat = {
+  method: string;
+  calls: number;
+  errors: number;
+  lastCalledAtMs: number | null;
+  lastLatencyMs: number | null;
+  lastOutcome: {
+    ok: boolean;
+    errorClass: RemoteErrorClass | null;
+    error: string | null;
+    requestId: string | null;
+  } | null;
+};
+
+export async function getRemoteMethodStats(): Promise<RemoteMethodStat[]> {
+  return invoke<RemoteMethodStat[]>("remote_method_stats");
+}
+
 export type TextFileResponse = {
   exists: boolean;
   content: string;
@@ -135,52 +428,732 @@ export type AgentsSettings = {
   agents: AgentSummary[];
 };
 
-export type SetAgentsCoreInput = {
-  multiAgentEnabled: boolean;
-  maxThreads: number;
-  maxDepth: number;
+export type SetAgentsCoreInput = {
+  multiAgentEnabled: boolean;
+  maxThreads: number;
+  maxDepth: number;
+};
+
+export type CreateAgentInput = {
+  name: string;
+  description?: string | null;
+  developerInstructions?: string | null;
+  template?: "blank" | string | null;
+  model?: string | null;
+  reasoningEffort?: string | null;
+};
+
+export type UpdateAgentInput = {
+  originalName: string;
+  name: string;
+  description?: string | null;
+  developerInstructions?: string | null;
+  renameManagedFile?: boolean;
+};
+
+export type DeleteAgentInput = {
+  name: string;
+  deleteManagedFile?: boolean;
+};
+
+export type FileScope = "workspace" | "global";
+export type FileKind = "agents" | "config";
+
+export type ScopedFileRef = {
+  scope: FileScope;
+  kind: FileKind;
+  workspaceId?: string;
+};
+
+async function fileRead(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<TextFileResponse> {
+  return invoke<TextFileResponse>("file_read", { scope, kind, workspaceId });
+}
+
+export type TimedRead = TextFileResponse & {
+  modifiedMs: number | null;
+  elapsedMs: number;
+  remote: {
+    networkMs: number;
+    remoteReadMs: number;
+    localMs: number;
+  } | null;
+};
+
+export async function readFileTimed(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<TimedRead> {
+  return invoke<TimedRead>("file_read_timed", { scope, kind, workspaceId });
+}
+
+export async function readFileStreamed(
+  scope: FileScope,
+  kind: FileKind,
+  requestId: string,
+  workspaceId?: string,
+): Promise<TextFileResponse> {
+  return invoke<TextFileResponse>("file_read_streamed", {
+    scope,
+    kind,
+    workspaceId,
+    requestId,
+  });
+}
+
+export type CappedRead = {
+  text: string;
+  truncated: boolean;
+  totalSize: number;
+};
+
+export async function readFileCapped(
+  scope: FileScope,
+  kind: FileKind,
+  maxBytes: number,
+  workspaceId?: string,
+): Promise<CappedRead> {
+  return invoke<CappedRead>("file_read_capped", {
+    scope,
+    kind,
+    workspaceId,
+    maxBytes,
+  });
+}
+
+export type NormalizedLines = {
+  exists: boolean;
+  lines: string[];
+  lineEnding: "lf" | "crlf" | "cr" | "none";
+  mixed: boolean;
+  trailingNewline: boolean;
+};
+
+export async function readFileLinesNormalized(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<NormalizedLines> {
+  return invoke<NormalizedLines>("file_read_lines_normalized", {
+    scope,
+    kind,
+    workspaceId,
+  });
+}
+
+export async function cancelFileReadStream(requestId: string): Promise<boolean> {
+  return invoke<boolean>("file_read_stream_cancel", { requestId });
+}
+
+export async function fileTouch(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<boolean> {
+  return invoke<boolean>("file_touch", { scope, kind, workspaceId });
+}
+
+export async function openInExternalEditor(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+  editor?: string | null,
+): Promise<void> {
+  return invoke("open_in_external_editor", {
+    scope,
+    kind,
+    workspaceId,
+    editor: editor ?? null,
+  });
+}
+
+export async function truncateFile(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+  keepLastBytes?: number,
+): Promise<number> {
+  return invoke<number>("file_truncate", {
+    scope,
+    kind,
+    workspaceId,
+    keepLastBytes: keepLastBytes ?? null,
+  });
+}
+
+export async function fileWriteSymlink(
+  scope: FileScope,
+  kind: FileKind,
+  target: string,
+  workspaceId?: string,
+): Promise<void> {
+  await invoke("file_write_symlink", { scope, kind, workspaceId, target });
+}
+
+export type WorkspaceInitReport = {
+  created: FileKind[];
+  skipped: FileKind[];
+};
+
+export async function initializeWorkspace(
+  workspaceId: string,
+  overwrite = false,
+): Promise<WorkspaceInitReport> {
+  return invoke<WorkspaceInitReport>("workspace_initialize", { workspaceId, overwrite });
+}
+
+export type Readiness = {
+  score: number;
+  missing: string[];
+};
+
+export async function getWorkspaceReadiness(workspaceId: string): Promise<Readiness> {
+  return invoke<Readiness>("workspace_readiness", { workspaceId });
+}
+
+export type PrewarmFailure = {
+  step: string;
+  error: string;
+};
+
+export type PrewarmDone = {
+  workspaceId: string;
+  warmed: string[];
+  failed: PrewarmFailure[];
+};
+
+export async function prewarmWorkspace(workspaceId: string): Promise<void> {
+  return invoke("workspace_prewarm", { workspaceId });
+}
+
+export type AgentsSource = {
+  scope: FileScope;
+  path: string;
+  exists: boolean;
+  content: string;
+};
+
+export type AgentsView = {
+  sources: AgentsSource[];
+  merged: string;
+};
+
+export async function getEffectiveAgents(workspaceId: string): Promise<AgentsView> {
+  return invoke<AgentsView>("agents_effective", { workspaceId });
+}
+
+export type PermIssue = {
+  file: ScopedFileRef;
+  path: string;
+  mode: string | null;
+  problem: string;
+  suggestedFix: string | null;
+};
+
+export async function auditFilePermissions(workspaceId?: string): Promise<PermIssue[]> {
+  return invoke<PermIssue[]>("file_permissions_audit", { workspaceId });
+}
+
+export async function fixFilePermissions(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<boolean> {
+  return invoke<boolean>("file_fix_permissions", { scope, kind, workspaceId });
+}
+
+export async function fileWritable(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<boolean> {
+  return invoke<boolean>("file_writable", { scope, kind, workspaceId });
+}
+
+export type LinkInfo = {
+  exists: boolean;
+  isSymlink: boolean;
+  target: string | null;
+  resolvesWithinScope: boolean;
+};
+
+export async function fileLinkInfo(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<LinkInfo> {
+  return invoke<LinkInfo>("file_link_info", { scope, kind, workspaceId });
+}
+
+export type FileWriteOptions = {
+  expectedModifiedMs?: number;
+  force?: boolean;
+  verify?: boolean;
+};
+
+async function fileWrite(
+  scope: FileScope,
+  kind: FileKind,
+  content: string,
+  workspaceId?: string,
+  options: FileWriteOptions = {},
+): Promise<void> {
+  return invoke("file_write", {
+    scope,
+    kind,
+    workspaceId,
+    content,
+    expectedModifiedMs: options.expectedModifiedMs,
+    force: options.force,
+    verify: options.verify,
+  });
+}
+
+export type WriteReport = {
+  path: string;
+  bytesWritten: number;
+  created: boolean;
+  skipped: boolean;
+};
+
+export async function fileWriteReport(
+  scope: FileScope,
+  kind: FileKind,
+  content: string,
+  workspaceId?: string,
+  options: Omit<FileWriteOptions, "verify"> = {},
+): Promise<WriteReport> {
+  return invoke<WriteReport>("file_write_report", {
+    scope,
+    kind,
+    workspaceId,
+    content,
+    expectedModifiedMs: options.expectedModifiedMs,
+    force: options.force,
+  });
+}
+
+export type DiffLine = {
+  kind: "context" | "added" | "removed";
+  text: string;
+};
+
+export type DiffHunk = {
+  oldStart: number;
+  oldLines: number;
+  newStart: number;
+  newLines: number;
+  lines: DiffLine[];
+};
+
+export async function fileWritePreview(
+  scope: FileScope,
+  kind: FileKind,
+  content: string,
+  workspaceId?: string,
+): Promise<DiffHunk[]> {
+  return invoke<DiffHunk[]>("file_write_preview", {
+    scope,
+    kind,
+    workspaceId,
+    content,
+  });
+}
+
+export async function fileWriteTemplated(
+  scope: FileScope,
+  kind: FileKind,
+  template: string,
+  options: {
+    workspaceId?: string;
+    vars?: Record<string, string>;
+    strict?: boolean;
+  } = {},
+): Promise<void> {
+  return invoke("file_write_templated", {
+    scope,
+    kind,
+    workspaceId: options.workspaceId,
+    template,
+    vars: options.vars ?? {},
+    strict: options.strict ?? false,
+  });
+}
+
+export type RenderedTemplate = {
+  content: string;
+  unresolved: string[];
+  unterminated: boolean;
+};
+
+export async function renderFileTemplate(
+  template: string,
+  options: {
+    workspaceId?: string;
+    vars?: Record<string, string>;
+    strict?: boolean;
+  } = {},
+): Promise<RenderedTemplate> {
+  return invoke<RenderedTemplate>("file_render_template", {
+    template,
+    vars: options.vars ?? {},
+    workspaceId: options.workspaceId,
+    strict: options.strict ?? false,
+  });
+}
+
+export type DefaultInfo = {
+  kind: FileKind;
+  label: string;
+  content: string;
+  size: number;
+};
+
+export async function getDefaultsCatalog(): Promise<DefaultInfo[]> {
+  return invoke<DefaultInfo[]>("defaults_catalog");
+}
+
+export async function fileUri(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<string> {
+  return invoke<string>("file_uri", { scope, kind, workspaceId });
+}
+
+export async function parseFileUri(uri: string): Promise<ScopedFileRef> {
+  return invoke<ScopedFileRef>("parse_file_uri", { uri });
+}
+
+export async function resetFileToDefault(
+  scope: FileScope,
+  kind: FileKind,
+  backup: boolean,
+  workspaceId?: string,
+): Promise<boolean> {
+  return invoke<boolean>("file_reset_to_default", {
+    scope,
+    kind,
+    workspaceId,
+    backup,
+  });
+}
+
+export type PathResult = {
+  file: ScopedFileRef;
+  path: string | null;
+  error: string | null;
+};
+
+export async function resolveFilePaths(requests: ScopedFileRef[]): Promise<PathResult[]> {
+  return invoke<PathResult[]>("file_paths", { requests });
+}
+
+export type FileReadResult = {
+  file: ScopedFileRef;
+  response: TextFileResponse | null;
+  error: string | null;
+};
+
+export type FileReadManyResponse = {
+  results: FileReadResult[];
+  cancelled: boolean;
+};
+
+export async function fileReadMany(
+  requests: ScopedFileRef[],
+  requestId: string,
+): Promise<FileReadManyResponse> {
+  return invoke<FileReadManyResponse>("file_read_many", { requests, requestId });
+}
+
+export async function remoteCancel(requestId: string): Promise<boolean> {
+  return invoke<boolean>("remote_cancel", { requestId });
+}
+
+export async function fileCopy(
+  source: ScopedFileRef,
+  target: ScopedFileRef,
+): Promise<void> {
+  return invoke("file_copy", { source, target });
+}
+
+export type FileSnapshot = ScopedFileRef & {
+  id: string;
+  label: string | null;
+  createdAtMs: number;
+  size: number;
+};
+
+export async function fileSnapshot(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+  label?: string | null,
+): Promise<FileSnapshot> {
+  return invoke<FileSnapshot>("file_snapshot", {
+    scope,
+    kind,
+    workspaceId,
+    label: label ?? null,
+  });
+}
+
+export async function listFileSnapshots(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<FileSnapshot[]> {
+  return invoke<FileSnapshot[]>("file_snapshot_list", { scope, kind, workspaceId });
+}
+
+export async function restoreFileSnapshot(snapshotId: string): Promise<WriteReport> {
+  return invoke<WriteReport>("file_snapshot_restore", { snapshotId });
+}
+
+export type OrphanEntry = {
+  path: string;
+  kind: "temp" | "backup" | "snapshot";
+  size: number;
+  modifiedMs: number | null;
+  removed: boolean;
+  error: string | null;
+};
+
+export async function cleanupOrphans(dryRun: boolean): Promise<OrphanEntry[]> {
+  return invoke<OrphanEntry[]>("cleanup_orphans", { dryRun });
+}
+
+export async function startTreeScan(
+  scope: FileScope,
+  workspaceId: string | null,
+  maxDepth: number | null = null,
+): Promise<string> {
+  return invoke<string>("tree_scan_start", { scope, workspaceId, maxDepth });
+}
+
+export async function cancelTreeScan(scanId: string): Promise<boolean> {
+  return invoke<boolean>("tree_scan_cancel", { scanId });
+}
+
+export async function fileSwap(
+  scopeA: FileScope,
+  kindA: FileKind,
+  scopeB: FileScope,
+  kindB: FileKind,
+  workspaceId?: string | null,
+): Promise<void> {
+  return invoke("file_swap", {
+    scopeA,
+    kindA,
+    scopeB,
+    kindB,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type FileSearchHit = {
+  kind: FileKind;
+  filename: string;
+  lineNumber: number;
+  line: string;
+};
+
+export async function fileSearch(
+  scope: FileScope,
+  query: string,
+  options: { workspaceId?: string; regex?: boolean; maxResults?: number } = {},
+): Promise<FileSearchHit[]> {
+  return invoke<FileSearchHit[]>("file_search", {
+    scope,
+    workspaceId: options.workspaceId,
+    query,
+    regex: options.regex ?? false,
+    maxResults: options.maxResults ?? 200,
+  });
+}
+
+export type ScopeUsage = {
+  scope: FileScope;
+  root: string;
+  bytes: number;
+  fileCount: number;
+  skippedEntries: number;
+};
+
+export type ScopeDiskUsage = {
+  scopes: ScopeUsage[];
+  totalBytes: number;
+};
+
+export async function getScopeDiskUsage(
+  workspaceId: string | null,
+  recursive = false,
+): Promise<ScopeDiskUsage> {
+  return invoke<ScopeDiskUsage>("scope_disk_usage", { workspaceId, recursive });
+}
+
+export type PathKind =
+  | { kind: "app_data" }
+  | { kind: "codex_home" }
+  | { kind: "workspace_root"; workspaceId: string };
+
+export async function getDiskFree(pathKind: PathKind): Promise<number> {
+  return invoke<number>("disk_free", { pathKind });
+}
+
+export async function testScopeWrite(
+  scope: FileScope,
+  workspaceId?: string,
+): Promise<void> {
+  await invoke("scope_write_test", { scope, workspaceId });
+}
+
+export type WorkspaceArchiveEntry = {
+  kind: FileKind;
+  path: string;
 };
 
-export type CreateAgentInput = {
-  name: string;
-  description?: string | null;
-  developerInstructions?: string | null;
-  template?: "blank" | string | null;
-  model?: string | null;
-  reasoningEffort?: string | null;
-};
+export async function exportWorkspaceZip(
+  workspaceId: string,
+  destPath: string,
+  operationId?: string,
+): Promise<WorkspaceArchiveEntry[]> {
+  return invoke<WorkspaceArchiveEntry[]>("workspace_export_zip", {
+    workspaceId,
+    destPath,
+    operationId,
+  });
+}
 
-export type UpdateAgentInput = {
-  originalName: string;
-  name: string;
-  description?: string | null;
-  developerInstructions?: string | null;
-  renameManagedFile?: boolean;
-};
+export async function importWorkspaceZip(
+  workspaceId: string,
+  sourcePath: string,
+  operationId?: string,
+): Promise<WorkspaceArchiveEntry[]> {
+  return invoke<WorkspaceArchiveEntry[]>("workspace_import_zip", {
+    workspaceId,
+    sourcePath,
+    operationId,
+  });
+}
 
-export type DeleteAgentInput = {
-  name: string;
-  deleteManagedFile?: boolean;
+export type PolicyExplanation = {
+  scope: FileScope;
+  kind: FileKind;
+  valid: boolean;
+  reason: string | null;
+  resolvedPath: string | null;
+  policy: {
+    filename: string;
+    rootContext: string;
+    rootMayBeMissing: boolean;
+    createRoot: boolean;
+    allowExternalSymlinkTarget: boolean;
+  } | null;
+  rules: string[];
+  sizeLimits: { name: string; bytes: number }[];
 };
 
-type FileScope = "workspace" | "global";
-type FileKind = "agents" | "config";
+export async function explainFilePolicy(
+  scope: FileScope,
+  kind: FileKind,
+  workspaceId?: string,
+): Promise<PolicyExplanation> {
+  return invoke<PolicyExplanation>("policy_explain", { scope, kind, workspaceId });
+}
+
+export async function setScopeOverride(
+  scope: FileScope,
+  path: string,
+  workspaceId?: string | null,
+): Promise<string> {
+  return invoke<string>("policy_set_scope_override", {
+    scope,
+    workspaceId: workspaceId ?? null,
+    path,
+  });
+}
 
-async function fileRead(
+export async function clearScopeOverride(
   scope: FileScope,
+  workspaceId?: string | null,
+): Promise<boolean> {
+  return invoke<boolean>("policy_clear_scope_override", {
+    scope,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function watchScope(scope: FileScope, workspaceId?: string): Promise<string> {
+  return invoke<string>("scope_watch", { scope, workspaceId });
+}
+
+export async function unwatchScope(watchId: string): Promise<boolean> {
+  return invoke<boolean>("scope_unwatch", { watchId });
+}
+
+export async function setScopeWatchCoalesceWindow(windowMs: number): Promise<void> {
+  return invoke("scope_watch_set_coalesce_window", { windowMs });
+}
+
+export async function renameKindFile(
   kind: FileKind,
+  oldName: string,
+  newName: string,
   workspaceId?: string,
-): Promise<TextFileResponse> {
-  return invoke<TextFileResponse>("file_read", { scope, kind, workspaceId });
+): Promise<string[]> {
+  return invoke<string[]>("policy_rename_kind_file", {
+    workspaceId,
+    kind,
+    oldName,
+    newName,
+  });
 }
 
-async function fileWrite(
+export type FileCompareResult = {
+  status:
+    | "identical"
+    | "different"
+    | "missing_local"
+    | "missing_remote"
+    | "missing_both";
+  diff: {
+    localLines: number;
+    remoteLines: number;
+    addedLines: number;
+    removedLines: number;
+    firstDifferenceLine: number;
+  } | null;
+};
+
+export async function compareLocalRemoteFile(
   scope: FileScope,
   kind: FileKind,
-  content: string,
   workspaceId?: string,
-): Promise<void> {
-  return invoke("file_write", { scope, kind, workspaceId, content });
+): Promise<FileCompareResult> {
+  return invoke<FileCompareResult>("file_compare_local_remote", {
+    scope,
+    kind,
+    workspaceId,
+  });
+}
+
+export type FileValidationIssue = {
+  severity: "error" | "warning";
+  message: string;
+  line: number | null;
+};
+
+export async function validateFileKindContent(
+  kind: FileKind,
+  content: string,
+): Promise<FileValidationIssue[]> {
+  return invoke<FileValidationIssue[]>("file_validate_kind", { kind, content });
 }
 
 export async function readImageAsDataUrl(path: string): Promise<string> {
@@ -191,16 +1164,22 @@ export async function readGlobalAgentsMd(): Promise<GlobalAgentsResponse> {
   return fileRead("global", "agents");
 }
 
-export async function writeGlobalAgentsMd(content: string): Promise<void> {
-  return fileWrite("global", "agents", content);
+export async function writeGlobalAgentsMd(
+  content: string,
+  options?: FileWriteOptions,
+): Promise<void> {
+  return fileWrite("global", "agents", content, undefined, options);
 }
 
 export async function readGlobalCodexConfigToml(): Promise<GlobalCodexConfigResponse> {
   return fileRead("global", "config");
 }
 
-export async function writeGlobalCodexConfigToml(content: string): Promise<void> {
-  return fileWrite("global", "config", content);
+export async function writeGlobalCodexConfigToml(
+  content: string,
+  options?: FileWriteOptions,
+): Promise<void> {
+  return fileWrite("global", "config", content, undefined, options);
 }
 
 export async function getAgentsSettings(): Promise<AgentsSettings> {
@@ -248,8 +1227,32 @@ export async function getConfigModel(workspaceId: string): Promise<string | null
   return trimmed.length > 0 ? trimmed : null;
 }
 
-export async function addWorkspace(path: string): Promise<WorkspaceInfo> {
-  return invoke<WorkspaceInfo>("add_workspace", { path });
+export type AddedWorkspace = WorkspaceInfo & {
+  warnings: string[];
+};
+
+export async function addWorkspace(
+  path: string,
+  rejectDuplicate = false,
+): Promise<AddedWorkspace> {
+  return invoke<AddedWorkspace>(
+    "add_workspace",
+    rejectDuplicate ? { path, rejectDuplicate } : { path },
+  );
+}
+
+export type ImportedWorkspace = AddedWorkspace & {
+  foundFiles: string[];
+};
+
+export async function importExistingWorkspace(
+  rootPath: string,
+  name?: string,
+): Promise<ImportedWorkspace> {
+  return invoke<ImportedWorkspace>("workspace_import_existing", {
+    rootPath,
+    name: name ?? null,
+  });
 }
 
 export async function addWorkspaceFromGitUrl(
@@ -738,6 +1741,489 @@ export async function setCodexFeatureFlag(
   return invoke("set_codex_feature_flag", { featureKey, enabled });
 }
 
+export type ConfigHistoryEntry = {
+  recordedAtMs: number;
+  summary: string;
+  undone: boolean;
+};
+
+export async function configUndo(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<boolean> {
+  return invoke<boolean>("config_undo", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function configRedo(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<boolean> {
+  return invoke<boolean>("config_redo", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function getConfigHistory(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ConfigHistoryEntry[]> {
+  return invoke<ConfigHistoryEntry[]>("config_history", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function formatConfigToml(
+  options: {
+    sortKeys?: boolean;
+    dryRun?: boolean;
+    codexHome?: string | null;
+    workspaceId?: string | null;
+  } = {},
+): Promise<string> {
+  return invoke<string>("config_format", {
+    sortKeys: options.sortKeys ?? false,
+    dryRun: options.dryRun ?? false,
+    codexHome: options.codexHome ?? null,
+    workspaceId: options.workspaceId ?? null,
+  });
+}
+
+export type ConfigMigrationStep = {
+  fromKey: string;
+  toKey: string | null;
+  old: string;
+  new: string | null;
+};
+
+export async function previewConfigMigration(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ConfigMigrationStep[]> {
+  return invoke<ConfigMigrationStep[]>("config_migrate_preview", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function migrateConfig(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ConfigMigrationStep[]> {
+  return invoke<ConfigMigrationStep[]>("config_migrate", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type ConfigValue = boolean | number | string | ConfigValue[];
+
+export async function mergeConfig(
+  changes: Record<string, ConfigValue | null>,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<void> {
+  return invoke("config_merge", {
+    changes,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type MergeStrategy = "prefer_base" | "prefer_incoming" | "error";
+
+export async function mergeConfigDocuments(
+  base: string,
+  incoming: string,
+  strategy: MergeStrategy,
+): Promise<string> {
+  return invoke<string>("config_merge_documents", { base, incoming, strategy });
+}
+
+export type ConfigOp =
+  | { op: "set_key"; key: string; value: ConfigValue }
+  | { op: "set_flag"; key: string; enabled: boolean }
+  | { op: "set_nested"; path: string[]; value: ConfigValue }
+  | { op: "remove"; path: string[] };
+
+export async function configTransaction(
+  ops: ConfigOp[],
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<void> {
+  return invoke("config_transaction", {
+    ops,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function configArrayAdd(
+  key: string,
+  value: string,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<boolean> {
+  return invoke<boolean>("config_array_add", {
+    key,
+    value,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function configArrayRemove(
+  key: string,
+  value: string,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<boolean> {
+  return invoke<boolean>("config_array_remove", {
+    key,
+    value,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function exportRedactedConfig(
+  extraKeys: string[] = [],
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<string> {
+  return invoke<string>("config_export_redacted", {
+    extraKeys,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function stripConfigComments(
+  write = false,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<string> {
+  return invoke<string>("config_strip_comments", {
+    write,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type IndentStyle = { kind: "tabs" } | { kind: "spaces"; width: number };
+
+export type IndentInfo = {
+  style: IndentStyle | null;
+  tabLines: number;
+  spaceLines: number;
+  mixedLines: number;
+  mixed: boolean;
+};
+
+export async function getConfigIndentInfo(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<IndentInfo> {
+  return invoke<IndentInfo>("config_indent_info", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function reindentConfig(
+  style: IndentStyle,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<string> {
+  return invoke<string>("config_reindent", {
+    style,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type ConfigDuplicateKey = {
+  key: string;
+  table: string | null;
+  lines: number[];
+  fixable: boolean;
+};
+
+export type ConfigParseError = {
+  message: string;
+  duplicate: ConfigDuplicateKey | null;
+};
+
+export async function loadConfigTolerant(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ConfigParseError | null> {
+  return invoke<ConfigParseError | null>("config_load_tolerant", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function fixConfigDuplicateKeys(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<number> {
+  return invoke<number>("config_fix_duplicate_keys", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type ConfigRoundtripReport = {
+  lostKeys: string[];
+};
+
+export async function checkConfigRoundtrip(
+  proposed: string,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ConfigRoundtripReport> {
+  return invoke<ConfigRoundtripReport>("config_roundtrip_check", {
+    proposed,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type ConfigDriftEntry = {
+  key: string;
+  appValue: unknown;
+  diskValue: unknown;
+};
+
+export async function checkConfigDrift(): Promise<ConfigDriftEntry[]> {
+  return invoke<ConfigDriftEntry[]>("config_drift_check");
+}
+
+export type ToolSetting = {
+  enabled: boolean;
+  source: "configured" | "default";
+};
+
+export type ToolsConfig = {
+  webSearch: ToolSetting;
+  viewImage: ToolSetting;
+};
+
+export type ResolvedSetting = {
+  key: string;
+  value: unknown;
+  source: "file" | "default";
+};
+
+export async function getConfigWithDefaults(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ResolvedSetting[]> {
+  return invoke<ResolvedSetting[]>("config_with_defaults", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type ConfigDelta = {
+  key: string;
+  valueA: unknown | null;
+  valueB: unknown | null;
+};
+
+export async function getConfigProfileDiff(
+  profileA: string,
+  profileB: string,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ConfigDelta[]> {
+  return invoke<ConfigDelta[]>("config_profile_diff", {
+    profileA,
+    profileB,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function createConfigProfile(
+  name: string,
+  base?: string | null,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<void> {
+  return invoke("config_profile_create", {
+    name,
+    base: base ?? null,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function deleteConfigProfile(
+  name: string,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<string | null> {
+  return invoke<string | null>("config_profile_delete", {
+    name,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function renameConfigProfile(
+  old: string,
+  next: string,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<void> {
+  return invoke("config_profile_rename", {
+    old,
+    new: next,
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function readToolsConfig(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ToolsConfig> {
+  return invoke<ToolsConfig>("tools_config_read", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function setToolEnabled(
+  tool: "web_search" | "view_image",
+  enabled: boolean | null,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ToolsConfig> {
+  return invoke<ToolsConfig>("tools_config_set", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+    tool,
+    enabled,
+  });
+}
+
+export type McpServerInfo = {
+  name: string;
+  command: string | null;
+  args: string[];
+  enabled: boolean;
+};
+
+export async function listMcpServers(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<McpServerInfo[]> {
+  return invoke<McpServerInfo[]>("mcp_servers_list", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function setMcpServerEnabled(
+  name: string,
+  enabled: boolean,
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<void> {
+  return invoke("mcp_server_set_enabled", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+    name,
+    enabled,
+  });
+}
+
+export type ConfigSchemaStatus = {
+  state: "current" | "older" | "newer";
+  unknownKeys: string[];
+  newerKeys: string[];
+  migratableKeys: string[];
+};
+
+export async function checkConfigSchema(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<ConfigSchemaStatus> {
+  return invoke<ConfigSchemaStatus>("config_schema_check", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function getConfigModifiedMs(
+  codexHome?: string | null,
+  workspaceId?: string | null,
+): Promise<number | null> {
+  return invoke<number | null>("config_modified_ms", {
+    codexHome: codexHome ?? null,
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export type EffectiveConfig = {
+  merged: string;
+  sources: string[];
+  missingIncludes: string[];
+};
+
+export async function readEffectiveConfig(): Promise<EffectiveConfig> {
+  return invoke<EffectiveConfig>("read_effective_config");
+}
+
+export type EffectiveConfigPath = {
+  path: string;
+  appPath: string | null;
+  diverges: boolean;
+};
+
+export async function getCodexEffectiveConfigPath(): Promise<EffectiveConfigPath> {
+  return invoke<EffectiveConfigPath>("codex_effective_config_path");
+}
+
+export type EffectivePersonality = {
+  value: string;
+  source: "configured" | "default";
+};
+
+export async function readEffectivePersonality(): Promise<EffectivePersonality> {
+  return invoke<EffectivePersonality>("read_effective_personality");
+}
+
+export async function readReasoningEffort(profile?: string | null): Promise<string | null> {
+  return invoke<string | null>("read_reasoning_effort", { profile: profile ?? null });
+}
+
+export async function writeReasoningEffort(
+  effort: string | null,
+  profile?: string | null,
+): Promise<void> {
+  return invoke("write_reasoning_effort", { effort, profile: profile ?? null });
+}
+
+export type FeatureInfo = {
+  key: string;
+  label: string;
+  description: string;
+  enabled: boolean | null;
+  defaultEnabled: boolean;
+};
+
+export async function getFeaturesCatalog(): Promise<FeatureInfo[]> {
+  return invoke<FeatureInfo[]>("features_catalog");
+}
+
 export async function generateRunMetadata(workspaceId: string, prompt: string) {
   return invoke<{ title: string; worktreeName: string }>("generate_run_metadata", {
     workspaceId,
@@ -862,6 +2348,47 @@ export async function updateAppSettings(settings: AppSettings): Promise<AppSetti
   return invoke<AppSettings>("update_app_settings", { settings });
 }
 
+export async function saveFeaturePreset(name: string): Promise<FeaturePreset> {
+  return invoke<FeaturePreset>("feature_preset_save", { name });
+}
+
+export async function listFeaturePresets(): Promise<FeaturePreset[]> {
+  return invoke<FeaturePreset[]>("feature_preset_list");
+}
+
+export async function applyFeaturePreset(
+  name: string,
+  clearMissing: boolean,
+): Promise<FeaturePreset> {
+  return invoke<FeaturePreset>("feature_preset_apply", { name, clearMissing });
+}
+
+export type PresetApplyAllReport = {
+  preset: FeaturePreset;
+  global: boolean;
+  workspaces: {
+    workspaceId: string;
+    ok: boolean;
+    error: string | null;
+  }[];
+};
+
+export async function applyFeaturePresetToWorkspaces(
+  name: string,
+  workspaceIds: string[],
+  operationId?: string,
+): Promise<PresetApplyAllReport> {
+  return invoke<PresetApplyAllReport>("feature_preset_apply_all", {
+    name,
+    workspaceIds,
+    operationId,
+  });
+}
+
+export async function deleteFeaturePreset(name: string): Promise<boolean> {
+  return invoke<boolean>("feature_preset_delete", { name });
+}
+
 export async function tailscaleStatus(): Promise<TailscaleStatus> {
   return invoke<TailscaleStatus>("tailscale_status");
 }
@@ -925,8 +2452,12 @@ export async function readAgentMd(workspaceId: string): Promise<AgentMdResponse>
   return fileRead("workspace", "agents", workspaceId);
 }
 
-export async function writeAgentMd(workspaceId: string, content: string): Promise<void> {
-  return fileWrite("workspace", "agents", content, workspaceId);
+export async function writeAgentMd(
+  workspaceId: string,
+  content: string,
+  options?: FileWriteOptions,
+): Promise<void> {
+  return fileWrite("workspace", "agents", content, workspaceId, options);
 }
 
 export async function listGitBranches(workspaceId: string) {
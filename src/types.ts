@@ -7,6 +7,12 @@ export type WorkspaceSettings = {
   launchScript?: string | null;
   launchScripts?: LaunchScriptEntry[] | null;
   worktreeSetupScript?: string | null;
+  codexHome?: string | null;
+  modelOverride?: string | null;
+  personalityOverride?: string | null;
+  sandboxModeOverride?: string | null;
+  approvalPolicyOverride?: string | null;
+  featureOverrides?: Record<string, boolean>;
 };
 
 export type LaunchScriptIconId =
@@ -232,6 +238,11 @@ export type OpenAppTarget = {
   args: string[];
 };
 
+export type FeaturePreset = {
+  name: string;
+  flags: Record<string, boolean>;
+};
+
 export type AppSettings = {
   codexBin: string | null;
   codexArgs: string | null;
@@ -242,6 +253,11 @@ export type AppSettings = {
   remoteBackends: RemoteBackendTarget[];
   activeRemoteBackendId: string | null;
   keepDaemonRunningAfterAppClose: boolean;
+  canonicalizeCodexHome: boolean;
+  codexHomeOverride: string | null;
+  featurePresets: FeaturePreset[];
+  scopeRootOverrides: Record<string, string>;
+  externalEditor: string | null;
   defaultAccessMode: AccessMode;
   reviewDeliveryMode: "inline" | "detached";
   composerModelShortcut: string | null;